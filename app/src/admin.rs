@@ -0,0 +1,3233 @@
+use crate::api;
+use crate::i18n::use_i18n;
+use crate::t;
+use crate::time::{DEFAULT_TIME_ZONE, LocalTime, UserTimeZone, format_in_user_tz};
+use api::Forum;
+use api::account_merge::MergeReport;
+use api::analytics::{AnalyticsSettings, DailyVisits};
+use api::announcements::{Announcement, Severity};
+use api::attachments::AttachmentRef;
+use api::bulk_users::{BulkReport, MatchedUser};
+use api::challenge::{ChallengeKind, ChallengeProvider};
+use api::counters::CounterStatus;
+use api::flags::{FlagSettings, FlaggedItem};
+use api::forum_archive::ArchiveFormat;
+use api::groups::{GroupPermission, UserGroup};
+use api::legal::LegalPageKind;
+use api::mod_log::{ExportFormat, ExportStatus, ModAction};
+use api::mod_reasons::{ModReason, ModReasonStat};
+use api::moderation::{HeldItem, QueueAge, QueueSummary};
+use api::reactions::ReactionOption;
+use api::scheduling::{ScheduledPost, ScheduledTarget};
+use api::site_settings::{GuestCapability, HomeRoute, SiteSettings};
+use api::spam::SpamSettings;
+use api::user::Role;
+use api::user_import::ImportReport;
+use api::webhooks::{Webhook, WebhookEvent};
+use api::word_filter::{FilterAction, WordFilterRule};
+
+use leptos::either::{Either, EitherOf3};
+use leptos::{logging, prelude::*, task::spawn_local};
+use leptos_meta::Title;
+use wasm_bindgen::JsCast;
+
+/// Renders the admin area, accessible to [`Role::Moderator`] and above
+///
+/// Sections only meant for [`Role::Admin`] are hidden for moderators.
+/// The server functions check permissions themselves,
+/// this is only so that others don't see a useless page
+#[component]
+pub fn Admin() -> impl IntoView {
+    let i18n = use_i18n();
+    let user_res = Resource::new(move || (), |()| api::user::get_current_user());
+
+    let admin_view = move || {
+        Suspend::new(async move {
+            let user = match user_res.await {
+                Ok(Some(user)) if user.role >= Role::Moderator => Ok(user),
+                Ok(_) => Err("admin.staff_only"),
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    Err("admin.load_failed")
+                }
+            };
+            let user = match user {
+                Ok(user) => user,
+                Err(key) => return Either::Left(view! { <p>{t!(key)}</p> }),
+            };
+            let is_admin = user.role >= Role::Admin;
+
+            let view = view! {
+              <AdminSection title="admin.section.moderation_queue">
+                <ModerationQueue moderator_name=user.name />
+              </AdminSection>
+              <AdminSection title="admin.section.flagged_posts">
+                <FlaggedPosts />
+              </AdminSection>
+              <AdminSection title="admin.section.scheduled">
+                <ScheduledPosts />
+              </AdminSection>
+              <Show when=move || is_admin>
+                <AdminSection title="admin.section.users">
+                  <BulkUserTools />
+                </AdminSection>
+                <AdminSection title="admin.section.import_users">
+                  <UserImportTool />
+                </AdminSection>
+                <AdminSection title="admin.section.merge_accounts">
+                  <AccountMergeTool />
+                </AdminSection>
+                <AdminSection title="admin.section.groups">
+                  <GroupManager />
+                </AdminSection>
+                <AdminSection title="admin.section.site">
+                  <SiteSettingsEditor />
+                </AdminSection>
+                <AdminSection title="admin.section.forums">
+                  <ForumSettings />
+                </AdminSection>
+                <AdminSection title="admin.section.announcements">
+                  <AnnouncementEditor />
+                </AdminSection>
+                <AdminSection title="admin.section.flagging">
+                  <FlagSettingsEditor />
+                </AdminSection>
+                <AdminSection title="admin.section.word_filter">
+                  <WordFilterEditor />
+                </AdminSection>
+                <AdminSection title="admin.section.spam">
+                  <SpamSettingsEditor />
+                </AdminSection>
+                <AdminSection title="admin.section.mod_reasons">
+                  <ModReasonEditor />
+                </AdminSection>
+                <AdminSection title="admin.section.reactions">
+                  <ReactionSetEditor />
+                </AdminSection>
+                <AdminSection title="admin.section.webhooks">
+                  <WebhookEditor />
+                </AdminSection>
+                <AdminSection title="admin.section.counters">
+                  <CounterOverview />
+                </AdminSection>
+                <AdminSection title="admin.section.analytics">
+                  <AnalyticsOverview />
+                </AdminSection>
+                <AdminSection title="admin.section.mod_log">
+                  <ModLogExports />
+                </AdminSection>
+                <AdminSection title="admin.section.legal_pages">
+                  {LegalPageKind::ALL
+                    .into_iter()
+                    .map(|kind| LegalPageEditor(LegalPageEditorProps { kind }))
+                    .collect_view()}
+                </AdminSection>
+              </Show>
+            };
+            Either::Right(view)
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("admin.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("admin.title")}</h1>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{admin_view}</Suspense>
+    }
+}
+
+/// Renders a titled section of the admin area
+#[component]
+fn AdminSection(
+    /// Translation key of the heading
+    title: &'static str,
+    children: Children,
+) -> impl IntoView {
+    view! {
+      <section class="flex flex-col gap-4 p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <h2 class="text-2xl font-bold text-purple-950">{t!(title)}</h2>
+        {children()}
+      </section>
+    }
+}
+
+/// Renders a form for publishing a new version of a legal page,
+/// prefilled with the current version
+#[component]
+fn LegalPageEditor(kind: LegalPageKind) -> impl IntoView {
+    let i18n = use_i18n();
+    let update = ServerAction::<api::legal::UpdateLegalPage>::new();
+    let page_res = Resource::new(
+        move || update.version().get(),
+        move |_| api::legal::get_legal_page(kind, None),
+    );
+
+    let status_view = move || {
+        let Some(result) = update.value().get() else {
+            return Either::Left(().into_view());
+        };
+        let view = match result {
+            Ok(version) => Either::Left(view! {
+              <p class="font-bold">{t!("admin.legal.published", version = version)}</p>
+            }),
+            Err(err) => {
+                Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+            }
+        };
+        Either::Right(view)
+    };
+
+    let editor_view = move || {
+        Suspend::new(async move {
+            // not written yet is fine, start with an empty page
+            let current = page_res.await.ok().map(|rendered| rendered.page);
+            let version = current.as_ref().map_or(0, |page| page.version);
+            let content = current.map(|page| page.content).unwrap_or_default();
+            view! {
+              <p class="text-sm">{t!("admin.legal.current_version", version = version)}</p>
+              <textarea
+                name="content"
+                rows="8"
+                required
+                class="py-2 px-4 w-full text-sm bg-purple-100 rounded-lg border border-purple-400"
+              >
+                {content}
+              </textarea>
+            }
+        })
+    };
+
+    view! {
+      <details class="p-2 bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!(kind.title_key())}</summary>
+        {status_view}
+        <ActionForm action=update attr:class="flex flex-col gap-2">
+          <input class="hidden" name="kind" value=format!("{kind:?}") />
+          <Suspense>{editor_view}</Suspense>
+          <label>
+            <input type="checkbox" name="requires_acceptance" value="true" />
+            " "
+            {t!("admin.legal.requires_acceptance")}
+          </label>
+          <input
+            type="submit"
+            value=t!("admin.legal.publish")
+            class="py-2 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+      </details>
+    }
+}
+
+/// Renders a form for finding users and applying a [`BulkAction`][api::bulk_users::BulkAction] to them,
+/// with a dry run by default
+#[component]
+fn BulkUserTools() -> impl IntoView {
+    let i18n = use_i18n();
+    let bulk_action = ServerAction::<api::bulk_users::BulkUserAction>::new();
+    let groups_res = Resource::new(move || (), |()| api::groups::get_user_groups());
+
+    let report_view = move || {
+        let result = bulk_action.value().get()?;
+        let view = match result {
+            Ok(report) => Either::Left(view! { <BulkReportView report /> }),
+            Err(err) => {
+                Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+            }
+        };
+        Some(view)
+    };
+
+    let input_class = "p-1 bg-purple-50 rounded-lg border border-purple-400";
+    view! {
+      <ActionForm action=bulk_action attr:class="flex flex-col gap-2">
+        <div class="flex flex-wrap gap-4">
+          <label>{t!("admin.bulk.email")} " " <input name="email" class=input_class /></label>
+          <label>{t!("admin.bulk.ip")} " " <input name="ip" class=input_class /></label>
+        </div>
+        <div class="flex flex-wrap gap-4">
+          <label>
+            {t!("admin.bulk.registered_from")} " "
+            <input type="date" name="registered_from" class=input_class />
+          </label>
+          <label>
+            {t!("admin.bulk.registered_to")} " "
+            <input type="date" name="registered_to" class=input_class />
+          </label>
+        </div>
+        <div class="flex flex-wrap gap-4">
+          <label>
+            {t!("admin.bulk.action")} " "
+            <select name="action" class=input_class>
+              <option value="Verify">{t!("admin.bulk.verify")}</option>
+              <option value="Ban">{t!("admin.bulk.ban")}</option>
+              <option value="DeleteSpam">{t!("admin.bulk.delete_spam")}</option>
+              <option value="MoveToGroup">{t!("admin.bulk.move_to_group")}</option>
+            </select>
+          </label>
+          <label>
+            {t!("admin.bulk.group")} " "
+            <select name="group_id" class=input_class>
+              <Suspense>
+                {move || Suspend::new(async move {
+                  groups_res
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|group| view! { <option value=group.id>{group.name}</option> })
+                    .collect_view()
+                })}
+              </Suspense>
+            </select>
+          </label>
+          <label>
+            {t!("admin.bulk.ban_reason")} " "
+            <input name="reason" placeholder=t!("admin.bulk.ban_reason_placeholder") class=input_class />
+          </label>
+          <label>
+            {t!("admin.reason_category")} " " {mod_reason_select(false, input_class)}
+          </label>
+        </div>
+        <label>
+          <input type="checkbox" name="apply" value="true" />
+          " "
+          {t!("admin.bulk.apply")}
+        </label>
+        <input
+          type="submit"
+          value=t!("admin.bulk.run")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+      {report_view}
+    }
+}
+
+/// Renders the users matched by a [`BulkAction`][api::bulk_users::BulkAction] and what happened to them
+#[component]
+fn BulkReportView(report: BulkReport) -> impl IntoView {
+    let i18n = use_i18n();
+    let summary_key = if report.applied {
+        "admin.bulk.applied"
+    } else {
+        "admin.bulk.dry_run"
+    };
+    let params = [
+        ("users", report.users.len().to_string()),
+        ("posts", report.post_count.to_string()),
+        ("threads", report.thread_count.to_string()),
+    ];
+    let summary = move || i18n.t_with(summary_key, &params);
+    let rows = report
+        .users
+        .into_iter()
+        .map(|MatchedUser { user, details }| {
+            let (email, ip) = details
+                .map(|details| (details.email, details.registration_ip))
+                .unwrap_or_default();
+            view! {
+              <tr>
+                <td>{user.id}</td>
+                <td>{user.name}</td>
+                <td>{email}</td>
+                <td>{ip}</td>
+                <td>{user.created_at.strftime("%F").to_string()}</td>
+                <td>{t!(if user.verified { "common.yes" } else { "common.no" })}</td>
+              </tr>
+            }
+        })
+        .collect_view();
+
+    view! {
+      <p class="font-bold">{summary}</p>
+      <table class="text-sm text-left">
+        <thead>
+          <tr>
+            <th>{t!("admin.bulk.id")}</th>
+            <th>{t!("members.name")}</th>
+            <th>{t!("admin.bulk.email_header")}</th>
+            <th>{t!("admin.bulk.ip_header")}</th>
+            <th>{t!("admin.bulk.registered")}</th>
+            <th>{t!("admin.bulk.verified")}</th>
+          </tr>
+        </thead>
+        <tbody>{rows}</tbody>
+      </table>
+    }
+}
+
+/// Renders a form for importing users from a CSV, with a dry run by default,
+/// see [`api::user_import`]
+#[component]
+fn UserImportTool() -> impl IntoView {
+    let i18n = use_i18n();
+    let import = ServerAction::<api::user_import::ImportUsers>::new();
+
+    let report_view = move || {
+        let result = import.value().get()?;
+        let view = match result {
+            Ok(report) => Either::Left(view! { <ImportReportView report /> }),
+            Err(err) => {
+                Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+            }
+        };
+        Some(view)
+    };
+
+    view! {
+      <p class="text-sm">
+        {t!("admin.import.help.before")} " " <code>"username,email,group"</code>
+        {t!("admin.import.help.after", days = api::user_import::INVITATION_DAYS)}
+      </p>
+      <ActionForm action=import attr:class="flex flex-col gap-2">
+        <textarea
+          name="csv"
+          rows="8"
+          required
+          placeholder="username,email,group\nalice,alice@example.com,Regulars"
+          class="py-2 px-4 w-full font-mono text-sm bg-purple-100 rounded-lg border border-purple-400"
+        ></textarea>
+        <label>
+          <input type="checkbox" name="apply" value="true" />
+          " "
+          {t!("admin.import.apply")}
+        </label>
+        <input
+          type="submit"
+          value=t!("admin.import.submit")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+      {report_view}
+    }
+}
+
+/// Renders the outcome of every line of an [`ImportReport`]
+#[component]
+fn ImportReportView(report: ImportReport) -> impl IntoView {
+    let i18n = use_i18n();
+    let valid = report
+        .results
+        .iter()
+        .filter(|result| result.result.is_ok())
+        .count();
+    let summary_key = if report.applied {
+        "admin.import.applied"
+    } else {
+        "admin.import.dry_run"
+    };
+    let params = [
+        ("valid", valid.to_string()),
+        ("total", report.results.len().to_string()),
+    ];
+    let summary = move || i18n.t_with(summary_key, &params);
+    let rows = report
+        .results
+        .into_iter()
+        .map(|result| {
+            let outcome = match result.result {
+                Ok(()) => Either::Left(view! { <td>{t!("admin.import.ok")}</td> }),
+                Err(err) => {
+                    Either::Right(view! { <td class="text-red-700">{i18n.error(&err)}</td> })
+                }
+            };
+            view! {
+              <tr>
+                <td>{result.line}</td>
+                <td>{result.username}</td>
+                {outcome}
+              </tr>
+            }
+        })
+        .collect_view();
+
+    view! {
+      <p class="font-bold">{summary}</p>
+      <table class="text-sm text-left">
+        <thead>
+          <tr>
+            <th>{t!("admin.import.line")}</th>
+            <th>{t!("auth.username")}</th>
+            <th>{t!("admin.import.result")}</th>
+          </tr>
+        </thead>
+        <tbody>{rows}</tbody>
+      </table>
+    }
+}
+
+/// Renders a form for merging a duplicate account into the primary one,
+/// with a dry run by default
+#[component]
+fn AccountMergeTool() -> impl IntoView {
+    let i18n = use_i18n();
+    let merge = ServerAction::<api::account_merge::MergeAccounts>::new();
+
+    let report_view = move || {
+        let result = merge.value().get()?;
+        let view = match result {
+            Ok(report) => Either::Left(view! { <MergeReportView report /> }),
+            Err(err) => {
+                Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+            }
+        };
+        Some(view)
+    };
+
+    let input_class = "p-1 bg-purple-50 rounded-lg border border-purple-400";
+    view! {
+      <ActionForm action=merge attr:class="flex flex-col gap-2">
+        <div class="flex flex-wrap gap-4">
+          <label>
+            {t!("admin.merge.duplicate")} " "
+            <input
+              name="duplicate_name"
+              required
+              placeholder=t!("admin.merge.duplicate_placeholder")
+              class=input_class
+            />
+          </label>
+          <label>
+            {t!("admin.merge.primary")} " "
+            <input
+              name="primary_name"
+              required
+              placeholder=t!("admin.merge.primary_placeholder")
+              class=input_class
+            />
+          </label>
+        </div>
+        <label>
+          <input type="checkbox" name="apply" value="true" />
+          " "
+          {t!("admin.merge.apply")}
+        </label>
+        <input
+          type="submit"
+          value=t!("admin.merge.submit")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+      {report_view}
+    }
+}
+
+/// Renders what [`merge_accounts`][api::account_merge::merge_accounts] reassigned
+/// or would reassign
+#[component]
+fn MergeReportView(report: MergeReport) -> impl IntoView {
+    let i18n = use_i18n();
+    let summary_key = if report.applied {
+        "admin.merge.applied"
+    } else {
+        "admin.merge.dry_run"
+    };
+    let params = [
+        ("posts", report.post_count.to_string()),
+        ("threads", report.thread_count.to_string()),
+        ("thanks", report.thanks_count.to_string()),
+        ("duplicate", report.duplicate.name.clone()),
+        ("duplicate_id", report.duplicate.id.to_string()),
+        ("primary", report.primary.name),
+        ("primary_id", report.primary.id.to_string()),
+    ];
+    let summary = move || i18n.t_with(summary_key, &params);
+    let disabled = report
+        .applied
+        .then(|| t!("admin.merge.disabled", name = report.duplicate.name));
+
+    view! {
+      <p class="font-bold">{summary}</p>
+      <p>{disabled}</p>
+    }
+}
+
+/// Renders all [`UserGroups`][UserGroup] with forms for editing them,
+/// creating new ones and managing the groups of a user
+#[component]
+fn GroupManager() -> impl IntoView {
+    let save = ServerAction::<api::groups::SaveUserGroup>::new();
+    let delete = ServerAction::<api::groups::DeleteUserGroup>::new();
+    let set_groups = ServerAction::<api::groups::SetUserGroups>::new();
+    let groups_res = Resource::new(
+        move || (save.version().get(), delete.version().get()),
+        |_| api::groups::get_user_groups(),
+    );
+
+    let save_result = move || save_status(save.value().get().map(|result| result.map(|_| ())));
+    let membership_status = move || save_status(set_groups.value().get());
+
+    let groups_view = move || {
+        Suspend::new(async move {
+            let groups = match groups_res.await {
+                Ok(groups) => groups,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("admin.groups.load_failed")}</p> });
+                }
+            };
+
+            let group_forms = groups
+                .iter()
+                .cloned()
+                .map(|group| {
+                    let group_id = group.id;
+                    view! {
+                      <details class="p-2 bg-purple-100 rounded-lg">
+                        <summary class="font-bold hover:cursor-pointer">
+                          <span style=format!("color: {}", group.color)>{group.name.clone()}</span>
+                          " (#"{group_id}")"
+                        </summary>
+                        <GroupForm save group=Some(group) />
+                        <button
+                          on:click=move |_| {
+                            delete.dispatch(api::groups::DeleteUserGroup { group_id });
+                          }
+                          class="mt-2 text-red-700 underline hover:no-underline hover:cursor-pointer"
+                        >
+                          {t!("admin.groups.delete")}
+                        </button>
+                      </details>
+                    }
+                })
+                .collect_view();
+
+            let group_checkboxes = groups
+                .iter()
+                .enumerate()
+                .map(|(i, group)| {
+                    view! {
+                      <label>
+                        <input type="checkbox" name=format!("group_ids[{i}]") value=group.id />
+                        " "
+                        {group.name.clone()}
+                      </label>
+                    }
+                })
+                .collect_view();
+            let primary_options = groups
+                .into_iter()
+                .map(|group| view! { <option value=group.id>{group.name}</option> })
+                .collect_view();
+
+            Either::Right(view! {
+              {group_forms}
+              <h3 class="mt-2 font-bold">{t!("admin.groups.of_user")}</h3>
+              <p class="text-sm">{t!("admin.groups.of_user_hint")}</p>
+              {membership_status}
+              <ActionForm action=set_groups attr:class="flex flex-col gap-2">
+                <label>
+                  {t!("admin.user_id")} " "
+                  <input
+                    type="number"
+                    min="1"
+                    name="user_id"
+                    required
+                    class="p-1 w-24 bg-purple-50 rounded-lg border border-purple-400"
+                  />
+                </label>
+                <div class="flex flex-wrap gap-4">{group_checkboxes}</div>
+                <label>
+                  {t!("admin.groups.primary")} " "
+                  <select
+                    name="primary_group_id"
+                    class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+                  >
+                    <option value="">{t!("admin.none")}</option>
+                    {primary_options}
+                  </select>
+                </label>
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+            })
+        })
+    };
+
+    view! {
+      {save_result}
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{groups_view}</Suspense>
+      <h3 class="mt-2 font-bold">{t!("admin.groups.new")}</h3>
+      <GroupForm save group=None />
+    }
+}
+
+/// Renders a form for saving a [`UserGroup`], a new one if `group` is [`None`]
+#[component]
+fn GroupForm(
+    save: ServerAction<api::groups::SaveUserGroup>,
+    group: Option<UserGroup>,
+) -> impl IntoView {
+    let group_id = group.as_ref().map(|group| group.id);
+    let (name, color, badge, permissions) = group.map_or_else(
+        || (String::new(), "#7e22ce".to_string(), None, vec![]),
+        |group| (group.name, group.color, group.badge, group.permissions),
+    );
+
+    let permission_checkboxes = GroupPermission::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, permission)| {
+            view! {
+              <label>
+                <input
+                  type="checkbox"
+                  name=format!("permissions[{i}]")
+                  value=format!("{permission:?}")
+                  checked=permissions.contains(&permission)
+                />
+                " "
+                {t!(permission.description_key())}
+              </label>
+            }
+        })
+        .collect_view();
+
+    let input_class = "p-1 bg-purple-50 rounded-lg border border-purple-400";
+    view! {
+      <ActionForm action=save attr:class="flex flex-col gap-2">
+        {group_id.map(|group_id| view! { <input class="hidden" name="group_id" value=group_id /> })}
+        <div class="flex flex-wrap gap-4 items-center">
+          <label>
+            {t!("members.name")} " " <input name="name" required value=name class=input_class />
+          </label>
+          <label>
+            {t!("admin.groups.color")} " " <input type="color" name="color" value=color />
+          </label>
+          <label>
+            {t!("admin.groups.badge")} " "
+            <input name="badge" placeholder="VIP" value=badge class=input_class />
+          </label>
+        </div>
+        {permission_checkboxes}
+        <input
+          type="submit"
+          value=t!("admin.groups.save")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+    }
+}
+
+/// Renders all held [`Posts`][api::Post] with buttons to approve, reject or assign them
+///
+/// `moderator_name` is the logged-in moderator, who can claim posts for themselves
+#[component]
+fn ModerationQueue(moderator_name: String) -> impl IntoView {
+    let i18n = use_i18n();
+    let approve = ServerAction::<api::moderation::ApprovePost>::new();
+    let reject = ServerAction::<api::moderation::RejectPost>::new();
+    let assign = ServerAction::<api::moderation::AssignHeldPost>::new();
+    let versions = move || {
+        (
+            approve.version().get(),
+            reject.version().get(),
+            assign.version().get(),
+        )
+    };
+    let held_res = Resource::new(versions, |_| api::moderation::get_held_posts());
+    let summary_res = Resource::new(versions, |_| api::moderation::get_queue_summary());
+    let moderator_name = StoredValue::new(moderator_name);
+
+    let summary_view = move || {
+        Suspend::new(async move {
+            let summary = summary_res.await.ok()?;
+            Some(view! { <QueueSummaryView summary /> })
+        })
+    };
+
+    let assign_error = move || {
+        let Some(Err(e)) = assign.value().get() else {
+            return None;
+        };
+        Some(view! { <p class="font-bold text-red-700">{i18n.error(&e)}</p> })
+    };
+
+    let queue_view = move || {
+        Suspend::new(async move {
+            let held = match held_res.await {
+                Ok(held) => held,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("admin.queue.load_failed")}</p> });
+                }
+            };
+            if held.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("admin.queue.empty")}</p> });
+            }
+
+            let now = jiff::Timestamp::now();
+            let view = held
+                .into_iter()
+                .map(|item| {
+                    HeldPost(HeldPostProps {
+                        item,
+                        now,
+                        moderator_name: moderator_name.get_value(),
+                        approve,
+                        reject,
+                        assign,
+                    })
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ol class="flex flex-col gap-2">{view}</ol> })
+        })
+    };
+
+    view! {
+      <Suspense>{summary_view}</Suspense>
+      {assign_error}
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{queue_view}</Suspense>
+    }
+}
+
+/// Renders how many held posts there are, how many of them are unclaimed and overdue
+#[component]
+fn QueueSummaryView(summary: QueueSummary) -> impl IntoView {
+    let stat = |label_key: &'static str, count: u32, alert: bool| {
+        view! {
+          <div
+            class="py-1 px-3 rounded-lg"
+            class=(["bg-red-200", "text-red-900", "font-bold"], move || alert && count > 0)
+            class=(["bg-purple-100"], move || !alert || count == 0)
+          >
+            {count}
+            " "
+            {t!(label_key)}
+          </div>
+        }
+    };
+
+    view! {
+      <div class="flex flex-wrap gap-2 text-sm">
+        {stat("admin.queue.held", summary.held, false)}
+        {stat("admin.queue.unclaimed", summary.unclaimed, true)}
+        {stat("admin.queue.overdue", summary.overdue, true)}
+      </div>
+    }
+}
+
+/// A list item showing a held [`Post`][api::Post] with how long it's been waiting, its assignee
+/// and buttons to approve, reject or assign it
+#[component]
+fn HeldPost(
+    item: HeldItem,
+    now: jiff::Timestamp,
+    moderator_name: String,
+    approve: ServerAction<api::moderation::ApprovePost>,
+    reject: ServerAction<api::moderation::RejectPost>,
+    assign: ServerAction<api::moderation::AssignHeldPost>,
+) -> impl IntoView {
+    let HeldItem {
+        post,
+        thread,
+        assignee,
+    } = item;
+    let post_id = post.id;
+    let is_origin = thread.origin_post_id == post.id;
+    let age = QueueAge::of(post.created_at, now);
+    let age_class = match age {
+        QueueAge::Fresh => "text-purple-900",
+        QueueAge::Ageing => "font-bold text-amber-700",
+        QueueAge::Overdue => "font-bold text-red-700",
+    };
+    let claimed_by_me = assignee
+        .as_ref()
+        .is_some_and(|assignee| assignee.name == moderator_name);
+    let assignee_view = match assignee {
+        Some(assignee) => Either::Left(view! {
+          <span>
+            {t!("admin.queue.assigned_to")} " " <span class="font-bold">{assignee.name}</span>
+          </span>
+        }),
+        None => {
+            Either::Right(view! { <span class="italic">{t!("admin.queue.unclaimed_post")}</span> })
+        }
+    };
+
+    view! {
+      <li class="p-2 bg-purple-100 rounded-lg">
+        <p class="text-sm">
+          {t!("admin.post_in", id = post.id)}
+          " "
+          <span class="font-bold">{thread.subject}</span>
+          {is_origin.then(|| view! { " " {t!("admin.queue.new_thread")} })}
+        </p>
+        <p class="flex flex-wrap gap-2 text-sm">
+          <span class=age_class>
+            {t!("admin.queue.held_since")} " "
+            <LocalTime timestamp=post.created_at relative=true />
+          </span>
+          {assignee_view}
+          {post
+            .spam_score
+            .map(|score| {
+              view! {
+                <span class="font-bold text-red-700">
+                  {t!("admin.queue.spam_score", score = score)}
+                </span>
+              }
+            })}
+        </p>
+        <p class="my-2 whitespace-pre-wrap break-words">{post.content}</p>
+        <div class="flex flex-wrap gap-2">
+          <button
+            on:click=move |_| {
+              approve.dispatch(api::moderation::ApprovePost { post_id });
+            }
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          >
+            {t!("admin.queue.approve")}
+          </button>
+          <button
+            on:click=move |_| {
+              reject.dispatch(api::moderation::RejectPost { post_id });
+            }
+            class="py-1 px-4 font-bold text-red-50 bg-red-800 rounded-lg hover:bg-red-900 hover:cursor-pointer"
+          >
+            {t!("admin.queue.reject")}
+          </button>
+          <button
+            on:click=move |_| {
+              let moderator_name = if claimed_by_me {
+                  String::new()
+              } else {
+                  moderator_name.clone()
+              };
+              assign.dispatch(api::moderation::AssignHeldPost { post_id, moderator_name });
+            }
+            class="py-1 px-4 font-bold text-purple-900 bg-purple-200 rounded-lg hover:bg-purple-300 hover:cursor-pointer"
+          >
+            {t!(if claimed_by_me { "admin.queue.unclaim" } else { "admin.queue.claim" })}
+          </button>
+          <ActionForm action=assign attr:class="flex gap-1 items-center">
+            <input type="hidden" name="post_id" value=post_id />
+            <input
+              name="moderator_name"
+              placeholder=t!("role.moderator")
+              class="p-1 text-sm bg-purple-50 rounded-lg border border-purple-400"
+            />
+            <input
+              type="submit"
+              value=t!("admin.queue.assign")
+              class="text-sm underline hover:no-underline hover:cursor-pointer"
+            />
+          </ActionForm>
+        </div>
+      </li>
+    }
+}
+
+/// Renders all flagged posts with buttons to dismiss their flags or hold them for review
+#[component]
+fn FlaggedPosts() -> impl IntoView {
+    let dismiss = ServerAction::<api::flags::DismissFlags>::new();
+    let hold = ServerAction::<api::flags::HoldFlaggedPost>::new();
+    let flagged_res = Resource::new(
+        move || (dismiss.version().get(), hold.version().get()),
+        |_| api::flags::get_flagged_posts(),
+    );
+
+    let flagged_view = move || {
+        Suspend::new(async move {
+            let flagged = match flagged_res.await {
+                Ok(flagged) => flagged,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("admin.flags.load_failed")}</p> });
+                }
+            };
+            if flagged.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("admin.flags.empty")}</p> });
+            }
+
+            let view = flagged
+                .into_iter()
+                .map(|item| {
+                    FlaggedPost(FlaggedPostProps {
+                        item,
+                        dismiss,
+                        hold,
+                    })
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ol class="flex flex-col gap-2">{view}</ol> })
+        })
+    };
+
+    view! {
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{flagged_view}</Suspense>
+    }
+}
+
+/// A list item showing a flagged [`Post`][api::Post] with who flagged it and why
+#[component]
+fn FlaggedPost(
+    item: FlaggedItem,
+    dismiss: ServerAction<api::flags::DismissFlags>,
+    hold: ServerAction<api::flags::HoldFlaggedPost>,
+) -> impl IntoView {
+    let FlaggedItem {
+        post,
+        thread,
+        total_weight,
+        flags,
+    } = item;
+    let post_id = post.id;
+    let flags = flags
+        .into_iter()
+        .map(|flag| {
+            view! {
+              <li>
+                <span class="font-bold">{flag.reporter_name}</span>
+                " "
+                {t!("admin.flags.weight", weight = flag.weight)}
+                {(!flag.reason.is_empty()).then(|| format!(": {}", flag.reason))}
+              </li>
+            }
+        })
+        .collect_view();
+
+    view! {
+      <li class="p-2 bg-purple-100 rounded-lg">
+        <p class="text-sm">
+          <a href=format!("/post/{post_id}") class="underline hover:no-underline">
+            {t!("thread.post_number", id = post_id)}
+          </a>
+          " "
+          {t!("admin.flags.in")}
+          " "
+          <span class="font-bold">{thread.subject}</span>
+          " - "
+          {t!("admin.flags.total_weight", weight = total_weight)}
+          {post.collapsed.then(|| view! { " " {t!("admin.flags.collapsed")} })}
+        </p>
+        <p class="my-2 whitespace-pre-wrap break-words">{post.content}</p>
+        <ul class="mb-2 text-sm list-disc list-inside">{flags}</ul>
+        <div class="flex flex-wrap gap-2">
+          <button
+            on:click=move |_| {
+              dismiss.dispatch(api::flags::DismissFlags { post_id });
+            }
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          >
+            {t!("admin.flags.dismiss")}
+          </button>
+          <button
+            on:click=move |_| {
+              hold.dispatch(api::flags::HoldFlaggedPost { post_id });
+            }
+            class="py-1 px-4 font-bold text-red-50 bg-red-800 rounded-lg hover:bg-red-900 hover:cursor-pointer"
+          >
+            {t!("admin.flags.hold")}
+          </button>
+        </div>
+      </li>
+    }
+}
+
+/// Renders a form for the [`SiteSettings`][api::site_settings::SiteSettings],
+/// prefilled with the current ones
+#[component]
+fn SiteSettingsEditor() -> impl IntoView {
+    let update = ServerAction::<api::site_settings::UpdateHomeRoute>::new();
+    let update_window = ServerAction::<api::site_settings::UpdateEditWindow>::new();
+    let update_guests = ServerAction::<api::site_settings::UpdateGuestAccess>::new();
+    let update_embeds = ServerAction::<api::site_settings::UpdateEmbedOrigins>::new();
+    let update_limits = ServerAction::<api::site_settings::UpdateLengthLimits>::new();
+    let update_challenge = ServerAction::<api::site_settings::UpdateChallenge>::new();
+    let update_license = ServerAction::<api::site_settings::UpdateContentLicense>::new();
+    let settings_res = Resource::new(|| (), |()| api::site_settings::get_site_settings());
+
+    let form_view = move || {
+        Suspend::new(async move {
+            let settings = match settings_res.await {
+                Ok(settings) => settings,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    SiteSettings::default()
+                }
+            };
+            let home_route = settings.home_route;
+            let limits = settings.length_limits;
+            let challenge_kind = settings.challenge.kind();
+            let (site_key, difficulty) = match settings.challenge {
+                ChallengeProvider::HCaptcha { site_key }
+                | ChallengeProvider::Turnstile { site_key } => {
+                    (site_key, ChallengeProvider::DEFAULT_DIFFICULTY)
+                }
+                ChallengeProvider::ProofOfWork { difficulty } => (String::new(), difficulty),
+                ChallengeProvider::Off => (String::new(), ChallengeProvider::DEFAULT_DIFFICULTY),
+            };
+            let license = settings.content_license.unwrap_or_default();
+            view! {
+              <ActionForm action=update attr:class="flex flex-wrap gap-2 items-center">
+                <label>
+                  {t!("admin.site.home_page")} " "
+                  <select
+                    name="home_route"
+                    class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+                  >
+                    {HomeRoute::ALL
+                      .into_iter()
+                      .map(|route| {
+                        view! {
+                          <option value=format!("{route:?}") selected=route == home_route>
+                            {t!(route.label_key())}
+                          </option>
+                        }
+                      })
+                      .collect_view()}
+                  </select>
+                </label>
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+              {move || save_status(update.value().get())}
+              <ActionForm action=update_window attr:class="flex flex-wrap gap-2 items-center">
+                <label>
+                  {t!("admin.site.edit_window")} " "
+                  <input
+                    type="number"
+                    name="edit_window_minutes"
+                    min="0"
+                    required
+                    value=settings.edit_window_minutes.unwrap_or(0)
+                    class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+                  />
+                  " "
+                  {t!("admin.site.edit_window_unit")}
+                </label>
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+              {move || save_status(update_window.value().get())}
+              <ActionForm action=update_guests attr:class="flex flex-wrap gap-2 items-center">
+                {t!("admin.site.guests_can_see")} " "
+                {GuestCapability::ALL
+                  .into_iter()
+                  .map(|capability| {
+                    view! {
+                      <label class="flex gap-1 items-center">
+                        <input
+                          type="checkbox"
+                          name=capability.name()
+                          value="true"
+                          checked=settings.guest_access.allows(capability)
+                        />
+                        {t!(capability.label_key())}
+                      </label>
+                    }
+                  })
+                  .collect_view()}
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+              {move || save_status(update_guests.value().get())}
+              <ActionForm action=update_embeds attr:class="flex flex-wrap gap-2 items-end">
+                <label class="flex flex-col">
+                  {t!("admin.site.embed_origins")}
+                  <textarea
+                    name="embed_origins"
+                    rows="3"
+                    placeholder="https://blog.example"
+                    class="p-1 w-80 bg-purple-50 rounded-lg border border-purple-400"
+                  >
+                    {settings.embed_origins.join("\n")}
+                  </textarea>
+                </label>
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+              {move || save_status(update_embeds.value().get())}
+              <ActionForm action=update_limits attr:class="flex flex-wrap gap-2 items-center">
+                {t!("admin.site.at_most")} " "
+                {[
+                  ("max_subject_len", limits.max_subject_len, "admin.site.max_subject_len"),
+                  ("max_content_len", limits.max_content_len, "admin.site.max_content_len"),
+                ]
+                  .into_iter()
+                  .map(|(name, value, label_key)| {
+                    view! {
+                      <label>
+                        <input
+                          type="number"
+                          name=name
+                          min="1"
+                          required
+                          value=value
+                          class="p-1 w-24 bg-purple-50 rounded-lg border border-purple-400"
+                        />
+                        " "
+                        {t!(label_key)}
+                      </label>
+                    }
+                  })
+                  .collect_view()}
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+              {move || save_status(update_limits.value().get())}
+              <ActionForm action=update_challenge attr:class="flex flex-wrap gap-2 items-center">
+                <label>
+                  {t!("admin.site.challenge")} " "
+                  <select name="kind" class="p-1 bg-purple-50 rounded-lg border border-purple-400">
+                    {ChallengeKind::ALL
+                      .into_iter()
+                      .map(|kind| {
+                        view! {
+                          <option value=format!("{kind:?}") selected=kind == challenge_kind>
+                            {t!(kind.label_key())}
+                          </option>
+                        }
+                      })
+                      .collect_view()}
+                  </select>
+                </label>
+                <label>
+                  {t!("admin.site.site_key")} " "
+                  <input
+                    name="site_key"
+                    value=site_key
+                    placeholder=t!("admin.site.site_key_placeholder")
+                    class="p-1 w-48 bg-purple-50 rounded-lg border border-purple-400"
+                  />
+                </label>
+                <label>
+                  {t!("admin.site.difficulty")} " "
+                  <input
+                    type="number"
+                    name="difficulty"
+                    min="1"
+                    max=ChallengeProvider::MAX_DIFFICULTY
+                    value=difficulty
+                    class="p-1 w-16 bg-purple-50 rounded-lg border border-purple-400"
+                  />
+                </label>
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+              {move || save_status(update_challenge.value().get())}
+              <ActionForm action=update_license attr:class="flex flex-wrap gap-2 items-center">
+                <label>
+                  {t!("admin.site.license")} " "
+                  <input
+                    name="name"
+                    value=license.name
+                    placeholder="CC BY-SA 4.0"
+                    class="p-1 w-40 bg-purple-50 rounded-lg border border-purple-400"
+                  />
+                </label>
+                <label>
+                  {t!("admin.site.license_link")} " "
+                  <input
+                    type="url"
+                    name="url"
+                    value=license.url
+                    placeholder="https://creativecommons.org/licenses/by-sa/4.0/"
+                    class="p-1 w-64 bg-purple-50 rounded-lg border border-purple-400"
+                  />
+                </label>
+                <label>
+                  {t!("admin.site.attribution")} " "
+                  <input
+                    name="attribution"
+                    value=license.attribution
+                    placeholder=t!("admin.site.attribution_placeholder")
+                    class="p-1 w-48 bg-purple-50 rounded-lg border border-purple-400"
+                  />
+                </label>
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+              {move || save_status(update_license.value().get())}
+            }
+        })
+    };
+
+    view! {
+      <p class="text-sm">{t!("admin.site.edit_window_help")}</p>
+      <p class="text-sm">{t!("admin.site.guests_help")}</p>
+      <p class="text-sm">
+        {t!("admin.site.embed_help")} " " <code>"/embed/thread/<id>"</code> "."
+      </p>
+      <p class="text-sm">
+        {t!("admin.site.challenge_help.before")} " " <code>"CHALLENGE_SECRET_KEY"</code> " "
+        {t!("admin.site.challenge_help.after")}
+      </p>
+      <p class="text-sm">{t!("admin.site.license_help")}</p>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{form_view}</Suspense>
+    }
+}
+
+/// Renders a form for the [`FlagSettings`], prefilled with the current ones
+#[component]
+fn FlagSettingsEditor() -> impl IntoView {
+    let update = ServerAction::<api::flags::UpdateFlagSettings>::new();
+    let settings_res = Resource::new(|| (), |()| api::flags::get_flag_settings());
+
+    let form_view = move || {
+        Suspend::new(async move {
+            let settings = match settings_res.await {
+                Ok(settings) => settings,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    FlagSettings::default()
+                }
+            };
+            let field = |name: &'static str, label_key: &'static str, value: u32| {
+                view! {
+                  <label class="flex gap-2 justify-between items-center max-w-xs">
+                    {t!(label_key)}
+                    <input
+                      type="number"
+                      name=name
+                      min="0"
+                      required
+                      value=value
+                      class="p-1 w-20 bg-purple-100 rounded-lg border border-purple-400"
+                    />
+                  </label>
+                }
+            };
+            view! {
+              <ActionForm action=update attr:class="flex flex-col gap-2">
+                {field("threshold", "admin.flagging.threshold", settings.threshold)}
+                {field("new_weight", "admin.flagging.new_weight", settings.new_weight)}
+                {field("member_weight", "admin.flagging.member_weight", settings.member_weight)}
+                {field("trusted_weight", "admin.flagging.trusted_weight", settings.trusted_weight)}
+                {field("staff_weight", "admin.flagging.staff_weight", settings.staff_weight)}
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-2 px-4 max-w-xs font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+            }
+        })
+    };
+
+    view! {
+      <p class="text-sm">
+        {t!(
+          "admin.flagging.help",
+          days = api::flags::NEW_ACCOUNT_DAYS,
+          posts = api::flags::TRUSTED_POST_COUNT,
+        )}
+      </p>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{form_view}</Suspense>
+      {move || save_status(update.value().get())}
+    }
+}
+
+/// Renders a form for the [`SpamSettings`], prefilled with the current ones
+#[component]
+fn SpamSettingsEditor() -> impl IntoView {
+    let update = ServerAction::<api::spam::UpdateSpamSettings>::new();
+    let settings_res = Resource::new(|| (), |()| api::spam::get_spam_settings());
+
+    let form_view = move || {
+        Suspend::new(async move {
+            let settings = match settings_res.await {
+                Ok(settings) => settings,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    SpamSettings::default()
+                }
+            };
+            let field = |name: &'static str, label_key: &'static str, value: u32| {
+                view! {
+                  <label class="flex gap-2 justify-between items-center max-w-xs">
+                    {t!(label_key)}
+                    <input
+                      type="number"
+                      name=name
+                      min="0"
+                      required
+                      value=value
+                      class="p-1 w-20 bg-purple-100 rounded-lg border border-purple-400"
+                    />
+                  </label>
+                }
+            };
+            view! {
+              <ActionForm action=update attr:class="flex flex-col gap-2">
+                {field("threshold", "admin.spam.threshold", settings.threshold)}
+                {field("link_weight", "admin.spam.link_weight", settings.link_weight)}
+                {field(
+                  "duplicate_weight",
+                  "admin.spam.duplicate_weight",
+                  settings.duplicate_weight,
+                )}
+                {field("velocity_weight", "admin.spam.velocity_weight", settings.velocity_weight)}
+                {field("free_posts", "admin.spam.free_posts", settings.free_posts)}
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-2 px-4 max-w-xs font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+            }
+        })
+    };
+
+    view! {
+      <p class="text-sm">
+        {t!(
+          "admin.spam.help",
+          hours = api::spam::DUPLICATE_HOURS,
+          minutes = api::spam::VELOCITY_MINUTES,
+        )}
+      </p>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{form_view}</Suspense>
+      {move || save_status(update.value().get())}
+    }
+}
+
+/// Hidden form field with the IANA name of the [`UserTimeZone`],
+/// which dates and times entered in the form are in
+fn time_zone_input() -> impl IntoView {
+    let time_zone = use_context::<UserTimeZone>();
+    let name = move || time_zone.map_or_else(|| DEFAULT_TIME_ZONE.to_string(), UserTimeZone::name);
+    view! { <input type="hidden" name="time_zone" prop:value=name /> }
+}
+
+/// Renders forms for scheduling threads and replies and all [`ScheduledPosts`][ScheduledPost]
+/// with their edit and cancel actions, see [`api::scheduling`]
+#[component]
+fn ScheduledPosts() -> impl IntoView {
+    let i18n = use_i18n();
+    let schedule_thread = ServerAction::<api::scheduling::ScheduleThread>::new();
+    let schedule_reply = ServerAction::<api::scheduling::ScheduleReply>::new();
+    let edit = ServerAction::<api::scheduling::EditScheduledPost>::new();
+    let cancel = ServerAction::<api::scheduling::CancelScheduledPost>::new();
+    let scheduled_res = Resource::new(
+        move || {
+            (
+                schedule_thread.version().get(),
+                schedule_reply.version().get(),
+                edit.version().get(),
+                cancel.version().get(),
+            )
+        },
+        |_| api::scheduling::get_scheduled_posts(),
+    );
+
+    let error = move || {
+        let errors = [
+            schedule_thread.value().get().and_then(Result::err),
+            schedule_reply.value().get().and_then(Result::err),
+            edit.value().get().and_then(Result::err),
+        ];
+        let e = errors.into_iter().flatten().next()?;
+        Some(view! { <p class="font-bold text-red-700">{i18n.error(&e)}</p> })
+    };
+
+    let scheduled_view = move || {
+        Suspend::new(async move {
+            let scheduled = match scheduled_res.await {
+                Ok(scheduled) => scheduled,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("admin.scheduled.load_failed")}</p> });
+                }
+            };
+            if scheduled.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("admin.scheduled.empty")}</p> });
+            }
+
+            let view = scheduled
+                .into_iter()
+                .map(|scheduled| {
+                    ScheduledPostRow(ScheduledPostRowProps {
+                        scheduled,
+                        edit,
+                        cancel,
+                    })
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ol class="flex flex-col gap-2">{view}</ol> })
+        })
+    };
+
+    let input_class = "p-1 bg-purple-50 rounded-lg border border-purple-400";
+    let submit_class = "py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer";
+
+    view! {
+      {error}
+      <details class="p-2 bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("admin.scheduled.thread")}</summary>
+        <ActionForm action=schedule_thread attr:class="flex flex-col gap-2">
+          {time_zone_input()}
+          <label>
+            {t!("admin.forum_number")}
+            <input type="number" min="1" name="forum_id" required class=input_class />
+          </label>
+          <input name="subject" placeholder=t!("admin.scheduled.subject") required class=input_class />
+          <textarea
+            name="content"
+            rows="4"
+            placeholder=t!("admin.scheduled.content")
+            required
+            class=input_class
+          ></textarea>
+          <label>
+            {t!("admin.scheduled.publish_at")} " "
+            <input type="datetime-local" name="publish_at" required class=input_class />
+          </label>
+          <input type="submit" value=t!("admin.scheduled.submit") class=submit_class />
+        </ActionForm>
+      </details>
+      <details class="p-2 bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("admin.scheduled.reply")}</summary>
+        <ActionForm action=schedule_reply attr:class="flex flex-col gap-2">
+          {time_zone_input()}
+          <label>
+            {t!("admin.thread_number")}
+            <input type="number" min="1" name="thread_id" required class=input_class />
+          </label>
+          <textarea
+            name="content"
+            rows="4"
+            placeholder=t!("admin.scheduled.content")
+            required
+            class=input_class
+          ></textarea>
+          <label>
+            {t!("admin.scheduled.publish_at")} " "
+            <input type="datetime-local" name="publish_at" required class=input_class />
+          </label>
+          <input type="submit" value=t!("admin.scheduled.submit") class=submit_class />
+        </ActionForm>
+      </details>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{scheduled_view}</Suspense>
+    }
+}
+
+/// A list item showing a [`ScheduledPost`] with forms to edit it and to share a preview of it,
+/// and a cancel button
+#[component]
+fn ScheduledPostRow(
+    scheduled: ScheduledPost,
+    edit: ServerAction<api::scheduling::EditScheduledPost>,
+    cancel: ServerAction<api::scheduling::CancelScheduledPost>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let scheduled_id = scheduled.id;
+    let time_zone = use_context::<UserTimeZone>()
+        .map_or_else(|| DEFAULT_TIME_ZONE.to_string(), UserTimeZone::name);
+    let publish_at = format_in_user_tz(scheduled.publish_at, &time_zone, "%Y-%m-%dT%H:%M");
+    let (target_key, target_id, subject) = match scheduled.target {
+        ScheduledTarget::Thread { forum_id, subject } => {
+            ("admin.scheduled.target_thread", forum_id, Some(subject))
+        }
+        ScheduledTarget::Reply { thread_id } => ("admin.scheduled.target_reply", thread_id, None),
+    };
+    let share = ServerAction::<api::previews::CreatePreviewLink>::new();
+    let revoke = ServerAction::<api::previews::RevokePreviewLinks>::new();
+    let share_status = move || {
+        let view = match share.value().get()? {
+            Ok(url) => Either::Left(view! {
+              <p class="text-sm">
+                {t!("admin.scheduled.preview_link")} " "
+                <a href=url.clone() class="font-mono underline break-all">
+                  {url}
+                </a>
+              </p>
+            }),
+            Err(e) => Either::Right(
+                view! { <p class="text-sm font-bold text-red-700">{i18n.error(&e)}</p> },
+            ),
+        };
+        Some(view)
+    };
+    let subject_input = subject.clone().map(|subject| {
+        view! {
+          <input
+            name="subject"
+            value=subject
+            required
+            class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+          />
+        }
+    });
+
+    view! {
+      <li class="p-2 bg-purple-100 rounded-lg">
+        <p class="text-sm">
+          {t!(target_key, id = target_id)} " - " <LocalTime timestamp=scheduled.publish_at />
+          {subject.map(|subject| view! { <span class="font-bold">" " {subject}</span> })}
+        </p>
+        {scheduled
+          .error
+          .map(|error| {
+            view! {
+              <p class="text-sm font-bold text-red-700">
+                {t!("admin.scheduled.failed", error = error)}
+              </p>
+            }
+          })}
+        <p class="my-2 whitespace-pre-wrap break-words">{scheduled.content.clone()}</p>
+        <details>
+          <summary class="text-sm underline hover:cursor-pointer">{t!("admin.edit")}</summary>
+          <ActionForm action=edit attr:class="flex flex-col gap-2">
+            <input type="hidden" name="scheduled_id" value=scheduled_id />
+            {time_zone_input()}
+            {subject_input}
+            <textarea
+              name="content"
+              rows="4"
+              required
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            >
+              {scheduled.content}
+            </textarea>
+            <input
+              type="datetime-local"
+              name="publish_at"
+              value=publish_at
+              required
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            <input
+              type="submit"
+              value=t!("common.save")
+              class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+            />
+          </ActionForm>
+        </details>
+        <details>
+          <summary class="text-sm underline hover:cursor-pointer">{t!("admin.scheduled.share")}</summary>
+          <ActionForm action=share attr:class="flex flex-wrap gap-2 items-center">
+            <input type="hidden" name="scheduled_id" value=scheduled_id />
+            <label class="text-sm">
+              {t!("admin.scheduled.valid_for")} " "
+              <input
+                type="number"
+                name="valid_hours"
+                min="1"
+                max=api::previews::MAX_PREVIEW_HOURS
+                value=api::previews::DEFAULT_PREVIEW_HOURS
+                class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+              />
+              " "
+              {t!("admin.scheduled.hours")}
+            </label>
+            <input
+              type="submit"
+              value=t!("admin.scheduled.create_link")
+              class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+            />
+            <button
+              type="button"
+              on:click=move |_| {
+                revoke.dispatch(api::previews::RevokePreviewLinks { scheduled_id });
+              }
+              class="text-sm text-red-700 underline hover:no-underline hover:cursor-pointer"
+            >
+              {move || {
+                let key = if revoke.version().get() > 0 {
+                  "admin.scheduled.links_revoked"
+                } else {
+                  "admin.scheduled.revoke_links"
+                };
+                i18n.t(key)
+              }}
+            </button>
+          </ActionForm>
+          {share_status}
+        </details>
+        <button
+          on:click=move |_| {
+            cancel.dispatch(api::scheduling::CancelScheduledPost { scheduled_id });
+          }
+          class="mt-2 text-red-700 underline hover:no-underline hover:cursor-pointer"
+        >
+          {t!("common.cancel")}
+        </button>
+      </li>
+    }
+}
+
+/// Renders all [`Announcements`][Announcement] with their edit and delete actions
+/// and a form for creating new ones
+#[component]
+fn AnnouncementEditor() -> impl IntoView {
+    let save = ServerAction::<api::announcements::SaveAnnouncement>::new();
+    let delete = ServerAction::<api::announcements::DeleteAnnouncement>::new();
+    let announcements_res = Resource::new(
+        move || (save.version().get(), delete.version().get()),
+        |_| api::announcements::get_all_announcements(),
+    );
+
+    let announcements_view = move || {
+        Suspend::new(async move {
+            let announcements = match announcements_res.await {
+                Ok(announcements) => announcements,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("admin.announcements.load_failed")}</p> });
+                }
+            };
+            if announcements.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("admin.announcements.empty")}</p> });
+            }
+
+            let view = announcements
+                .into_iter()
+                .map(|announcement| {
+                    AnnouncementRow(AnnouncementRowProps {
+                        announcement,
+                        save,
+                        delete,
+                    })
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ol class="flex flex-col gap-2">{view}</ol> })
+        })
+    };
+
+    view! {
+      <p class="text-sm">{t!("admin.announcements.help")}</p>
+      {move || save_status(save.value().get())}
+      <details class="p-2 bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("admin.announcements.new")}</summary>
+        <AnnouncementForm save announcement=None />
+      </details>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{announcements_view}</Suspense>
+    }
+}
+
+/// A list item showing an [`Announcement`] with a form to edit it and a delete button
+#[component]
+fn AnnouncementRow(
+    announcement: Announcement,
+    save: ServerAction<api::announcements::SaveAnnouncement>,
+    delete: ServerAction<api::announcements::DeleteAnnouncement>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let announcement_id = announcement.id;
+    let forum_id = announcement.forum_id;
+    let shown_in = move || {
+        forum_id.map_or_else(
+            || i18n.t("admin.announcements.everywhere"),
+            |id| i18n.t_with("admin.forum", &[("id", id.to_string())]),
+        )
+    };
+    let severity = match announcement.severity {
+        Severity::Info => "admin.announcements.info",
+        Severity::Warning => "admin.announcements.warning",
+        Severity::Critical => "admin.announcements.critical",
+    };
+    let ends_at = announcement.ends_at.map(|ends_at| {
+        view! {
+          " "
+          {t!("admin.announcements.until")}
+          " "
+          <LocalTime timestamp=ends_at />
+        }
+    });
+    let is_active = announcement.is_active(jiff::Timestamp::now());
+
+    view! {
+      <li class="p-2 bg-purple-100 rounded-lg">
+        <p class="text-sm">
+          <span class="font-bold">{t!(severity)}</span> " - " {shown_in} " - "
+          {t!("admin.announcements.from")} " " <LocalTime timestamp=announcement.starts_at />
+          {ends_at} {(!is_active).then(|| view! { " " {t!("admin.announcements.not_shown")} })}
+        </p>
+        <p class="my-2 whitespace-pre-wrap break-words">{announcement.content.clone()}</p>
+        <details>
+          <summary class="text-sm underline hover:cursor-pointer">{t!("admin.edit")}</summary>
+          <AnnouncementForm save announcement=Some(announcement) />
+        </details>
+        <button
+          on:click=move |_| {
+            delete.dispatch(api::announcements::DeleteAnnouncement { announcement_id });
+          }
+          class="mt-2 text-red-700 underline hover:no-underline hover:cursor-pointer"
+        >
+          {t!("admin.delete")}
+        </button>
+      </li>
+    }
+}
+
+/// Form for creating an [`Announcement`], or editing it if given
+#[component]
+fn AnnouncementForm(
+    save: ServerAction<api::announcements::SaveAnnouncement>,
+    announcement: Option<Announcement>,
+) -> impl IntoView {
+    let time_zone = use_context::<UserTimeZone>()
+        .map_or_else(|| DEFAULT_TIME_ZONE.to_string(), UserTimeZone::name);
+    let format = |timestamp| format_in_user_tz(timestamp, &time_zone, "%Y-%m-%dT%H:%M");
+    let announcement_id = announcement.as_ref().map(|announcement| announcement.id);
+    let forum_id = announcement
+        .as_ref()
+        .and_then(|announcement| announcement.forum_id);
+    let severity = announcement
+        .as_ref()
+        .map(|announcement| announcement.severity)
+        .unwrap_or_default();
+    let starts_at = announcement
+        .as_ref()
+        .map(|announcement| format(announcement.starts_at));
+    let ends_at = announcement
+        .as_ref()
+        .and_then(|announcement| announcement.ends_at)
+        .map(format);
+    let content = announcement
+        .map(|announcement| announcement.content)
+        .unwrap_or_default();
+
+    let input_class = "p-1 bg-purple-50 rounded-lg border border-purple-400";
+    let severity_option = move |value: Severity, label_key: &'static str| {
+        view! {
+          <option value=format!("{value:?}") selected=value == severity>
+            {t!(label_key)}
+          </option>
+        }
+    };
+
+    view! {
+      <ActionForm action=save attr:class="flex flex-col gap-2">
+        {announcement_id.map(|id| view! { <input type="hidden" name="announcement_id" value=id /> })}
+        {time_zone_input()}
+        <label>
+          {t!("admin.forum_number")} " "
+          <input
+            type="number"
+            min="1"
+            name="forum_id"
+            value=forum_id
+            placeholder=t!("admin.announcements.everywhere_placeholder")
+            class=input_class
+          />
+        </label>
+        <textarea name="content" rows="4" placeholder="Markdown" required class=input_class>
+          {content}
+        </textarea>
+        <select name="severity" class=input_class>
+          {severity_option(Severity::Info, "admin.announcements.info")}
+          {severity_option(Severity::Warning, "admin.announcements.warning")}
+          {severity_option(Severity::Critical, "admin.announcements.critical")}
+        </select>
+        <label>
+          {t!("admin.announcements.starts_at")} " "
+          <input type="datetime-local" name="starts_at" value=starts_at class=input_class /> " "
+          {t!("admin.announcements.starts_at_hint")}
+        </label>
+        <label>
+          {t!("admin.announcements.ends_at")} " "
+          <input type="datetime-local" name="ends_at" value=ends_at class=input_class /> " "
+          {t!("admin.announcements.ends_at_hint")}
+        </label>
+        <input
+          type="submit"
+          value=t!("common.save")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+    }
+}
+
+/// Renders all [`WordFilterRules`][WordFilterRule] and a form for adding new ones
+#[component]
+fn WordFilterEditor() -> impl IntoView {
+    let i18n = use_i18n();
+    let add = ServerAction::<api::word_filter::AddWordFilterRule>::new();
+    let delete = ServerAction::<api::word_filter::DeleteWordFilterRule>::new();
+    let rules_res = Resource::new(
+        move || (add.version().get(), delete.version().get()),
+        |_| api::word_filter::get_word_filter_rules(),
+    );
+
+    let rules_view = move || {
+        Suspend::new(async move {
+            let rules = match rules_res.await {
+                Ok(rules) => rules,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("admin.word_filter.load_failed")}</p> });
+                }
+            };
+            let view = rules
+                .into_iter()
+                .map(|rule| WordFilterRuleRow(WordFilterRuleRowProps { rule, delete }))
+                .collect_view();
+            Either::Right(view)
+        })
+    };
+
+    let error_view = move || {
+        let Some(Err(e)) = add.value().get() else {
+            return Either::Left(().into_view());
+        };
+        Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&e)}</p> })
+    };
+
+    view! {
+      <p class="text-sm">
+        {t!("admin.word_filter.help.before")} " " <code>"word*"</code> ", " <code>"*word"</code>
+        " " {t!("admin.word_filter.help.or")} " " <code>"*word*"</code> " "
+        {t!("admin.word_filter.help.after")}
+      </p>
+      <table class="w-full table-fixed">
+        <thead>
+          <tr>
+            <th scope="col">{t!("admin.word_filter.pattern")}</th>
+            <th scope="col">{t!("admin.bulk.action")}</th>
+            <th scope="col" class="w-20"></th>
+          </tr>
+        </thead>
+        <tbody>
+          <Suspense>{rules_view}</Suspense>
+        </tbody>
+      </table>
+      {error_view}
+      <ActionForm action=add attr:class="flex flex-wrap gap-2 items-center">
+        <input
+          name="pattern"
+          required
+          placeholder="*spam*"
+          class="p-2 text-sm bg-purple-100 rounded-lg border border-purple-400"
+        />
+        <select name="action" class="p-2 text-sm bg-purple-100 rounded-lg border border-purple-400">
+          <option value="Mask">{t!("admin.word_filter.mask")}</option>
+          <option value="Hold">{t!("admin.flags.hold")}</option>
+          <option value="Reject">{t!("admin.queue.reject")}</option>
+        </select>
+        <input
+          type="submit"
+          value=t!("admin.word_filter.add")
+          class="py-2 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+    }
+}
+
+/// A table row representing a [`WordFilterRule`] with a delete button
+#[component]
+fn WordFilterRuleRow(
+    rule: WordFilterRule,
+    delete: ServerAction<api::word_filter::DeleteWordFilterRule>,
+) -> impl IntoView {
+    let rule_id = rule.id;
+    let action = match rule.action {
+        FilterAction::Mask => "admin.word_filter.mask",
+        FilterAction::Hold => "admin.flags.hold",
+        FilterAction::Reject => "admin.queue.reject",
+    };
+
+    view! {
+      <tr class="text-center">
+        <td class="font-mono">{rule.pattern}</td>
+        <td>{t!(action)}</td>
+        <td>
+          <button
+            on:click=move |_| {
+              delete.dispatch(api::word_filter::DeleteWordFilterRule { rule_id });
+            }
+            class="text-red-700 underline hover:no-underline hover:cursor-pointer"
+          >
+            {t!("admin.delete")}
+          </button>
+        </td>
+      </tr>
+    }
+}
+
+/// Select named `reason_id` of the [`ModReasons`][ModReason], archived ones only with
+/// `include_archived`, whose first option is no reason at all
+fn mod_reason_select(include_archived: bool, class: &'static str) -> impl IntoView {
+    let reasons_res = Resource::new(
+        move || (),
+        move |()| api::mod_reasons::get_mod_reasons(String::new(), include_archived),
+    );
+    let options = move || {
+        Suspend::new(async move {
+            reasons_res
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|reason| view! { <option value=reason.id>{reason.name}</option> })
+                .collect_view()
+        })
+    };
+    view! {
+      <select name="reason_id" class=class>
+        <option value="">{t!("admin.mod_reasons.any")}</option>
+        <Suspense>{options}</Suspense>
+      </select>
+    }
+}
+
+/// Renders the [`ModReasons`][ModReason] with how often they were given,
+/// and a form for adding new ones
+#[component]
+fn ModReasonEditor() -> impl IntoView {
+    let i18n = use_i18n();
+    let create = ServerAction::<api::mod_reasons::CreateModReason>::new();
+    let set_archived = ServerAction::<api::mod_reasons::SetModReasonArchived>::new();
+    let reasons_res = Resource::new(
+        move || (create.version().get(), set_archived.version().get()),
+        |_| api::mod_reasons::get_mod_reasons(String::new(), true),
+    );
+    let stats_res = Resource::new(move || (), |()| api::mod_reasons::get_mod_reason_stats());
+
+    let reasons_view = move || {
+        Suspend::new(async move {
+            let (reasons, stats) = match (reasons_res.await, stats_res.await) {
+                (Ok(reasons), Ok(stats)) => (reasons, stats),
+                (Err(err), _) | (_, Err(err)) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("admin.mod_reasons.load_failed")}</p> });
+                }
+            };
+            let view = reasons
+                .into_iter()
+                .map(|reason| {
+                    let stats = stats
+                        .iter()
+                        .filter(|stat| stat.reason_id == reason.id)
+                        .cloned()
+                        .collect();
+                    ModReasonRow(ModReasonRowProps {
+                        reason,
+                        stats,
+                        set_archived,
+                    })
+                })
+                .collect_view();
+            Either::Right(view)
+        })
+    };
+
+    let error_view = move || {
+        let Some(Err(e)) = create.value().get() else {
+            return Either::Left(().into_view());
+        };
+        Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&e)}</p> })
+    };
+
+    view! {
+      <p class="text-sm">{t!("admin.mod_reasons.help")}</p>
+      <table class="w-full table-fixed">
+        <thead>
+          <tr>
+            <th scope="col">{t!("admin.mod_reasons.reason")}</th>
+            <th scope="col">{t!("admin.mod_reasons.given_for")}</th>
+            <th scope="col" class="w-24"></th>
+          </tr>
+        </thead>
+        <tbody>
+          <Suspense>{reasons_view}</Suspense>
+        </tbody>
+      </table>
+      {error_view}
+      <ActionForm action=create attr:class="flex flex-wrap gap-2 items-center">
+        <input
+          name="name"
+          required
+          placeholder=t!("admin.mod_reasons.name_placeholder")
+          class="p-2 text-sm bg-purple-100 rounded-lg border border-purple-400"
+        />
+        <input
+          name="description"
+          placeholder=t!("admin.mod_reasons.description_placeholder")
+          class="p-2 text-sm bg-purple-100 rounded-lg border border-purple-400 grow"
+        />
+        <input
+          type="submit"
+          value=t!("admin.mod_reasons.add")
+          class="py-2 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+    }
+}
+
+/// A table row representing a [`ModReason`] with its [`ModReasonStats`][ModReasonStat]
+/// and a button for (un)archiving it
+#[component]
+fn ModReasonRow(
+    reason: ModReason,
+    stats: Vec<ModReasonStat>,
+    set_archived: ServerAction<api::mod_reasons::SetModReasonArchived>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let reason_id = reason.id;
+    let archived = reason.archived;
+    let stats = move || {
+        if stats.is_empty() {
+            return i18n.t("admin.never");
+        }
+        stats
+            .iter()
+            .map(|stat| format!("{} {}x", i18n.t(stat.action.label_key()), stat.count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    view! {
+      <tr class="text-center" class:text-gray-500=archived>
+        <td title=reason.description>{reason.name}</td>
+        <td class="text-sm">{stats}</td>
+        <td>
+          <button
+            on:click=move |_| {
+              set_archived
+                .dispatch(api::mod_reasons::SetModReasonArchived {
+                  reason_id,
+                  archived: !archived,
+                });
+            }
+            class="underline hover:no-underline hover:cursor-pointer"
+          >
+            {t!(if archived { "admin.mod_reasons.unarchive" } else { "admin.mod_reasons.archive" })}
+          </button>
+        </td>
+      </tr>
+    }
+}
+
+/// Renders all [`Webhooks`][Webhook] and a form for adding new ones
+#[component]
+fn WebhookEditor() -> impl IntoView {
+    let i18n = use_i18n();
+    let add = ServerAction::<api::webhooks::AddWebhook>::new();
+    let delete = ServerAction::<api::webhooks::DeleteWebhook>::new();
+    let webhooks_res = Resource::new(
+        move || (add.version().get(), delete.version().get()),
+        |_| api::webhooks::get_webhooks(),
+    );
+
+    let webhooks_view = move || {
+        Suspend::new(async move {
+            let webhooks = match webhooks_res.await {
+                Ok(webhooks) => webhooks,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("admin.webhooks.load_failed")}</p> });
+                }
+            };
+            let view = webhooks
+                .into_iter()
+                .map(|webhook| WebhookRow(WebhookRowProps { webhook, delete }))
+                .collect_view();
+            Either::Right(view)
+        })
+    };
+
+    let error_view = move || {
+        let Some(Err(e)) = add.value().get() else {
+            return Either::Left(().into_view());
+        };
+        Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&e)}</p> })
+    };
+
+    view! {
+      <p class="text-sm">
+        {t!("admin.webhooks.help.before")} " " <code>"X-Dafoerum-Signature: sha256=<hmac>"</code>
+        {t!("admin.webhooks.help.after")}
+      </p>
+      <table class="w-full table-fixed">
+        <thead>
+          <tr>
+            <th scope="col">{t!("admin.webhooks.url")}</th>
+            <th scope="col">{t!("admin.webhooks.events")}</th>
+            <th scope="col">{t!("admin.webhooks.forum")}</th>
+            <th scope="col">{t!("admin.webhooks.secret")}</th>
+            <th scope="col">{t!("admin.webhooks.last_delivery")}</th>
+            <th scope="col" class="w-20"></th>
+          </tr>
+        </thead>
+        <tbody>
+          <Suspense>{webhooks_view}</Suspense>
+        </tbody>
+      </table>
+      {error_view}
+      <ActionForm action=add attr:class="flex flex-wrap gap-2 items-center">
+        <input
+          name="url"
+          type="url"
+          required
+          placeholder="https://discord.com/api/webhooks/..."
+          class="p-2 text-sm bg-purple-100 rounded-lg border border-purple-400 grow"
+        />
+        <input
+          name="secret"
+          placeholder=t!("admin.webhooks.secret_placeholder")
+          class="p-2 text-sm bg-purple-100 rounded-lg border border-purple-400"
+        />
+        <label>
+          {t!("admin.forum_number")} " "
+          <input
+            type="number"
+            min="1"
+            name="forum_id"
+            placeholder=t!("admin.webhooks.all_forums_placeholder")
+            class="p-2 w-28 text-sm bg-purple-100 rounded-lg border border-purple-400"
+          />
+        </label>
+        <label>
+          <input type="checkbox" name="new_threads" value="true" checked />
+          " "
+          {t!("admin.webhooks.new_threads")}
+        </label>
+        <label>
+          <input type="checkbox" name="new_posts" value="true" />
+          " "
+          {t!("admin.webhooks.new_posts")}
+        </label>
+        <input
+          type="submit"
+          value=t!("admin.webhooks.add")
+          class="py-2 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+    }
+}
+
+/// A table row representing a [`Webhook`] with its last delivery and a delete button
+#[component]
+fn WebhookRow(
+    webhook: Webhook,
+    delete: ServerAction<api::webhooks::DeleteWebhook>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let webhook_id = webhook.id;
+    let event_keys = webhook
+        .events
+        .iter()
+        .map(|event| match event {
+            WebhookEvent::NewThread => "admin.webhooks.new_threads",
+            WebhookEvent::NewPost => "admin.webhooks.new_posts",
+        })
+        .collect::<Vec<_>>();
+    let events = move || {
+        event_keys
+            .iter()
+            .map(|key| i18n.t(key))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let last_delivery = match (webhook.last_error, webhook.last_delivered_at) {
+        (Some(error), _) => EitherOf3::A(view! { <span class="text-red-700">{error}</span> }),
+        (None, Some(timestamp)) => EitherOf3::B(view! { <LocalTime timestamp /> }),
+        (None, None) => EitherOf3::C(t!("admin.never")),
+    };
+
+    view! {
+      <tr class="text-center">
+        <td class="font-mono break-all">{webhook.url}</td>
+        <td>{events}</td>
+        <td>
+          {match webhook.forum_id {
+            Some(forum_id) => {
+              Either::Left(
+                view! {
+                  <a href=format!("/forum/{forum_id}") class="underline hover:no-underline">
+                    "#"
+                    {forum_id}
+                  </a>
+                },
+              )
+            }
+            None => Either::Right(t!("admin.webhooks.all_forums")),
+          }}
+        </td>
+        <td class="font-mono break-all select-all">{webhook.secret}</td>
+        <td>{last_delivery}</td>
+        <td>
+          <button
+            on:click=move |_| {
+              delete.dispatch(api::webhooks::DeleteWebhook { webhook_id });
+            }
+            class="text-red-700 underline hover:no-underline hover:cursor-pointer"
+          >
+            {t!("admin.delete")}
+          </button>
+        </td>
+      </tr>
+    }
+}
+
+/// Renders the [`CounterStatus`] of every counter with a resync button for those behind
+#[component]
+fn CounterOverview() -> impl IntoView {
+    let i18n = use_i18n();
+    let resync = ServerAction::<api::counters::ResyncCounter>::new();
+    let counters_res = Resource::new(
+        move || resync.version().get(),
+        |_| api::counters::get_counters(),
+    );
+
+    let counters_view = move || {
+        Suspend::new(async move {
+            let counters = match counters_res.await {
+                Ok(counters) => counters,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("admin.counters.load_failed")}</p> });
+                }
+            };
+            let view = counters
+                .into_iter()
+                .map(|status| CounterRow(CounterRowProps { status, resync }))
+                .collect_view();
+            Either::Right(view)
+        })
+    };
+
+    let error_view = move || {
+        let Some(Err(e)) = resync.value().get() else {
+            return Either::Left(().into_view());
+        };
+        Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&e)}</p> })
+    };
+
+    view! {
+      <p class="text-sm">{t!("admin.counters.help")}</p>
+      <table class="w-full table-fixed">
+        <thead>
+          <tr>
+            <th scope="col">{t!("admin.counters.counter")}</th>
+            <th scope="col">{t!("admin.counters.sequence")}</th>
+            <th scope="col">{t!("admin.counters.highest_id")}</th>
+            <th scope="col">{t!("admin.counters.status")}</th>
+            <th scope="col" class="w-20"></th>
+          </tr>
+        </thead>
+        <tbody>
+          <Suspense>{counters_view}</Suspense>
+        </tbody>
+      </table>
+      {error_view}
+    }
+}
+
+/// A table row representing a [`CounterStatus`], with a resync button if it's behind
+#[component]
+fn CounterRow(
+    status: CounterStatus,
+    resync: ServerAction<api::counters::ResyncCounter>,
+) -> impl IntoView {
+    let behind = status.is_behind();
+    let max_id = status
+        .max_id
+        .map_or_else(|| "-".to_string(), |max_id| max_id.to_string());
+    let resync_button = behind.then(|| {
+        let (category, expected_sequence) = (status.category.clone(), status.sequence);
+        view! {
+          <button
+            on:click=move |_| {
+              resync
+                .dispatch(api::counters::ResyncCounter {
+                  category: category.clone(),
+                  expected_sequence,
+                });
+            }
+            class="text-red-700 underline hover:no-underline hover:cursor-pointer"
+          >
+            {t!("admin.counters.resync")}
+          </button>
+        }
+    });
+
+    view! {
+      <tr class="text-center">
+        <td class="font-mono">{status.category}</td>
+        <td>{status.sequence}</td>
+        <td>{max_id}</td>
+        <td class=if behind { "font-bold text-red-700" } else { "" }>
+          {t!(if behind { "admin.counters.behind" } else { "admin.counters.ok" })}
+        </td>
+        <td>{resync_button}</td>
+      </tr>
+    }
+}
+
+/// Renders the switch for [`api::analytics`] and the [`DailyVisits`] counted so far
+#[component]
+fn AnalyticsOverview() -> impl IntoView {
+    let update = ServerAction::<api::analytics::UpdateAnalyticsSettings>::new();
+    let settings_res = Resource::new(
+        move || update.version().get(),
+        |_| api::analytics::get_analytics_settings(),
+    );
+    let visits_res = Resource::new(|| (), |()| api::analytics::get_daily_visits());
+
+    let form_view = move || {
+        Suspend::new(async move {
+            let settings = match settings_res.await {
+                Ok(settings) => settings,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    AnalyticsSettings::default()
+                }
+            };
+            view! {
+              <ActionForm action=update attr:class="flex flex-wrap gap-4 items-center">
+                <label>
+                  <input type="checkbox" name="enabled" value="true" checked=settings.enabled />
+                  " "
+                  {t!("admin.analytics.enabled")}
+                </label>
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+            }
+        })
+    };
+
+    let visits_view = move || {
+        Suspend::new(async move {
+            let days = match visits_res.await {
+                Ok(days) => days,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! {
+                      <tr>
+                        <td colspan="3">{t!("admin.analytics.load_failed")}</td>
+                      </tr>
+                    });
+                }
+            };
+            if days.is_empty() {
+                return EitherOf3::B(view! {
+                  <tr>
+                    <td colspan="3" class="text-center">{t!("admin.analytics.empty")}</td>
+                  </tr>
+                });
+            }
+            let view = days
+                .into_iter()
+                .map(|DailyVisits { day, views, visitors }| {
+                    view! {
+                      <tr class="text-center">
+                        <td class="font-mono">{day}</td>
+                        <td>{views}</td>
+                        <td>{visitors}</td>
+                      </tr>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view)
+        })
+    };
+
+    view! {
+      <p class="text-sm">{t!("admin.analytics.help")}</p>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{form_view}</Suspense>
+      {move || save_status(update.value().get())}
+      <table class="w-full table-fixed">
+        <thead>
+          <tr>
+            <th scope="col">{t!("admin.analytics.day")}</th>
+            <th scope="col">{t!("admin.analytics.views")}</th>
+            <th scope="col">{t!("admin.analytics.visitors")}</th>
+          </tr>
+        </thead>
+        <tbody>
+          <Suspense>{visits_view}</Suspense>
+        </tbody>
+      </table>
+    }
+}
+
+/// Renders a form for exporting the [`api::mod_log`] and all exports made so far
+#[component]
+fn ModLogExports() -> impl IntoView {
+    let i18n = use_i18n();
+    let export = ServerAction::<api::mod_log::ExportModLog>::new();
+    // exports are written in the background, so their status has to be reloaded by hand
+    let (refreshed, set_refreshed) = signal(0_u32);
+    let exports_res = Resource::new(
+        move || (export.version().get(), refreshed.get()),
+        |_| api::mod_log::get_mod_log_exports(),
+    );
+
+    let export_status = move || {
+        let view = match export.value().get()? {
+            Ok(id) => Either::Left(view! {
+              <p class="font-bold">{t!("admin.mod_log.started", id = id)}</p>
+            }),
+            Err(err) => {
+                Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+            }
+        };
+        Some(view)
+    };
+
+    let exports_view = move || {
+        Suspend::new(async move {
+            let exports = match exports_res.await {
+                Ok(exports) => exports,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("admin.mod_log.load_failed")}</p> });
+                }
+            };
+            if exports.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("admin.mod_log.empty")}</p> });
+            }
+
+            let view = exports
+                .into_iter()
+                .map(|export| {
+                    let url = export.url();
+                    let status = match export.status {
+                        ExportStatus::Running => {
+                            EitherOf3::A(view! { <span>{t!("admin.mod_log.preparing")}</span> })
+                        }
+                        ExportStatus::Done { entry_count } => EitherOf3::B(view! {
+                          <a href=url class="underline hover:no-underline">
+                            {t!("admin.mod_log.download", count = entry_count)}
+                          </a>
+                        }),
+                        ExportStatus::Failed(err) => EitherOf3::C(view! {
+                          <span class="text-red-700">{t!("admin.mod_log.failed", error = err)}</span>
+                        }),
+                    };
+                    view! {
+                      <li>
+                        "#" {export.id} " " {export.format.extension()} ", "
+                        {t!("admin.mod_log.requested")} " "
+                        <LocalTime timestamp=export.requested_at /> " - " {status}
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ul class="text-sm">{view}</ul> })
+        })
+    };
+
+    let input_class = "p-1 bg-purple-50 rounded-lg border border-purple-400";
+
+    view! {
+      <p class="text-sm">{t!("admin.mod_log.help")}</p>
+      <ActionForm action=export attr:class="flex flex-col gap-2 max-w-md">
+        {time_zone_input()}
+        <label class="flex flex-col">
+          {t!("admin.mod_log.from")} <input type="datetime-local" name="since" class=input_class />
+        </label>
+        <label class="flex flex-col">
+          {t!("admin.mod_log.until")} <input type="datetime-local" name="until" class=input_class />
+        </label>
+        <label class="flex flex-col">
+          {t!("admin.mod_log.moderators")}
+          <input name="moderator_names" class=input_class />
+        </label>
+        <select name="action" class=input_class>
+          <option value="">{t!("admin.mod_log.all_actions")}</option>
+          {ModAction::ALL
+            .into_iter()
+            .map(|action| {
+              view! { <option value=format!("{action:?}")>{t!(action.label_key())}</option> }
+            })
+            .collect_view()}
+        </select>
+        {mod_reason_select(true, input_class)}
+        <select name="format" class=input_class>
+          {ExportFormat::ALL
+            .into_iter()
+            .map(|format| {
+              view! { <option value=format!("{format:?}")>{format.extension().to_uppercase()}</option> }
+            })
+            .collect_view()}
+        </select>
+        <input
+          type="submit"
+          value=t!("admin.mod_log.export")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+      {export_status}
+      <div class="flex gap-2 items-center">
+        <h3 class="font-bold">{t!("admin.mod_log.exports")}</h3>
+        <button
+          type="button"
+          on:click=move |_| set_refreshed.update(|count| *count += 1)
+          class="py-1 px-2 text-xs font-medium text-purple-700 rounded-lg border border-purple-300 hover:bg-purple-100 hover:cursor-pointer"
+        >
+          {t!("admin.mod_log.refresh")}
+        </button>
+      </div>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{exports_view}</Suspense>
+    }
+}
+
+/// Renders all [`ReactionOptions`][ReactionOption] and a form for adding new ones
+#[component]
+fn ReactionSetEditor() -> impl IntoView {
+    let i18n = use_i18n();
+    let add = ServerAction::<api::reactions::AddReactionOption>::new();
+    let update = ServerAction::<api::reactions::UpdateReactionOption>::new();
+    let delete = ServerAction::<api::reactions::DeleteReactionOption>::new();
+    let set_negative = ServerAction::<api::reactions::SetNegativeReactionsEnabled>::new();
+    let options_res = Resource::new(
+        move || {
+            (
+                add.version().get(),
+                update.version().get(),
+                delete.version().get(),
+                set_negative.version().get(),
+            )
+        },
+        |_| api::reactions::get_all_reaction_options(),
+    );
+
+    let image = RwSignal::<Option<AttachmentRef>>::new(None);
+    let (upload_error, set_upload_error) = signal::<Option<String>>(None);
+    Effect::new(move || {
+        if let Some(Ok(_)) = add.value().get() {
+            image.set(None);
+        }
+    });
+    let on_image_change = move |ev: web_sys::Event| {
+        let Some(file) = ev
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok())
+            .and_then(|input| input.files())
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+        set_upload_error(None);
+        spawn_local(async move {
+            match crate::forum::attachment::upload_file::<AttachmentRef>("/attachments", file).await
+            {
+                Ok(attachment) => image.set(Some(attachment)),
+                Err(err) => {
+                    logging::log!("{err}");
+                    set_upload_error(Some(err));
+                }
+            }
+        });
+    };
+
+    let options_view = move || {
+        Suspend::new(async move {
+            let options = match options_res.await {
+                Ok(options) => options,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("admin.reactions.load_failed")}</p> });
+                }
+            };
+            let view = options
+                .into_iter()
+                .map(|option| {
+                    ReactionOptionRow(ReactionOptionRowProps {
+                        option,
+                        update,
+                        delete,
+                    })
+                })
+                .collect_view();
+            Either::Right(view)
+        })
+    };
+
+    let error_view = move || {
+        let error = match (add.value().get(), update.value().get()) {
+            (Some(Err(e)), _) | (_, Some(Err(e))) => i18n.error(&e),
+            _ => upload_error.get()?,
+        };
+        Some(view! { <p class="font-bold text-red-700">{error}</p> })
+    };
+
+    view! {
+      <p class="text-sm">{t!("admin.reactions.help")}</p>
+      <div class="flex gap-2">
+        <button
+          on:click=move |_| {
+            set_negative.dispatch(api::reactions::SetNegativeReactionsEnabled { enabled: false });
+          }
+          class="py-1 px-3 text-sm font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        >
+          {t!("admin.reactions.disable_negative")}
+        </button>
+        <button
+          on:click=move |_| {
+            set_negative.dispatch(api::reactions::SetNegativeReactionsEnabled { enabled: true });
+          }
+          class="py-1 px-3 text-sm font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        >
+          {t!("admin.reactions.enable_negative")}
+        </button>
+      </div>
+      <table class="w-full table-fixed">
+        <thead>
+          <tr>
+            <th scope="col">{t!("admin.reactions.reaction")}</th>
+            <th scope="col" class="w-3/5">{t!("admin.reactions.settings")}</th>
+            <th scope="col" class="w-20"></th>
+          </tr>
+        </thead>
+        <tbody>
+          <Suspense>{options_view}</Suspense>
+        </tbody>
+      </table>
+      {error_view}
+      <ActionForm action=add attr:class="flex flex-wrap gap-2 items-center">
+        <input
+          name="emoji"
+          required
+          placeholder=move || {
+            if image.get().is_some() {
+              i18n.t("admin.reactions.image_name")
+            } else {
+              "👍".to_string()
+            }
+          }
+          class="p-2 text-sm bg-purple-100 rounded-lg border border-purple-400"
+        />
+        <label class="text-sm">
+          <input type="checkbox" name="negative" value="true" />
+          " "
+          {t!("admin.reactions.negative")}
+        </label>
+        <label class="text-sm">
+          {t!("admin.reactions.custom_image")} " "
+          <input type="file" accept="image/*" on:change=on_image_change />
+        </label>
+        {move || {
+          image
+            .get()
+            .map(|image| {
+              view! {
+                <input type="hidden" name="image_attachment_id" value=image.id />
+                <img src=image.url() alt=image.file_name class="w-6 h-6" />
+              }
+            })
+        }}
+        <input
+          type="submit"
+          value=t!("admin.reactions.add")
+          class="py-2 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+    }
+}
+
+/// A table row with the settings of a [`ReactionOption`] and a delete button
+#[component]
+fn ReactionOptionRow(
+    option: ReactionOption,
+    update: ServerAction<api::reactions::UpdateReactionOption>,
+    delete: ServerAction<api::reactions::DeleteReactionOption>,
+) -> impl IntoView {
+    let option_id = option.id;
+    let label = match option.image {
+        Some(image) => Either::Left(view! {
+          <img src=image.url() alt=option.emoji.clone() title=option.emoji class="inline w-6 h-6" />
+        }),
+        None => Either::Right(option.emoji),
+    };
+
+    view! {
+      <tr class="text-center">
+        <td class="text-xl">{label}</td>
+        <td>
+          <ActionForm action=update attr:class="flex flex-wrap gap-2 justify-center items-center">
+            <input type="hidden" name="option_id" value=option_id />
+            <label class="text-sm">
+              {t!("admin.reactions.order")} " "
+              <input
+                type="number"
+                name="order"
+                min="0"
+                value=option.order
+                class="p-1 w-16 bg-purple-100 rounded-lg border border-purple-400"
+              />
+            </label>
+            <label class="text-sm">
+              <input type="checkbox" name="negative" value="true" checked=option.negative />
+              " "
+              {t!("admin.reactions.negative")}
+            </label>
+            <label class="text-sm">
+              <input type="checkbox" name="enabled" value="true" checked=option.enabled />
+              " "
+              {t!("admin.reactions.enabled")}
+            </label>
+            <input
+              type="submit"
+              value=t!("common.save")
+              class="text-sm underline hover:no-underline hover:cursor-pointer"
+            />
+          </ActionForm>
+        </td>
+        <td>
+          <button
+            on:click=move |_| {
+              delete.dispatch(api::reactions::DeleteReactionOption { option_id });
+            }
+            class="text-red-700 underline hover:no-underline hover:cursor-pointer"
+          >
+            {t!("admin.delete")}
+          </button>
+        </td>
+      </tr>
+    }
+}
+
+/// Renders the settings of every [`Forum`] grouped by category
+#[component]
+fn ForumSettings() -> impl IntoView {
+    let i18n = use_i18n();
+    let delete = ServerAction::<api::forum_archive::DeleteForum>::new();
+    let categories_res = Resource::new(move || delete.version().get(), |_| api::get_categories());
+    let archives_res = Resource::new(
+        move || delete.version().get(),
+        |_| api::forum_archive::get_forum_archives(),
+    );
+
+    let forums_view = move || {
+        Suspend::new(async move {
+            let categories = match categories_res.await {
+                Ok(categories) => categories,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("admin.forums.load_failed")}</p> });
+                }
+            };
+            let view = categories
+                .into_iter()
+                .map(|category| {
+                    let forums = category
+                        .forums
+                        .into_iter()
+                        .map(|forum| ForumSettingsForm(ForumSettingsFormProps { forum, delete }))
+                        .collect_view();
+                    view! {
+                      <CategorySettingsForm name=category.name description=category.description />
+                      {forums}
+                    }
+                })
+                .collect_view();
+            Either::Right(view)
+        })
+    };
+
+    let delete_status = move || {
+        let view = match delete.value().get()? {
+            Ok(Some(archive_id)) => EitherOf3::A(view! {
+              <p class="font-bold">{t!("admin.forums.deleted_archived", id = archive_id)}</p>
+            }),
+            Ok(None) => {
+                EitherOf3::B(view! { <p class="font-bold">{t!("admin.forums.deleted")}</p> })
+            }
+            Err(err) => {
+                EitherOf3::C(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+            }
+        };
+        Some(view)
+    };
+
+    let archives_view = move || {
+        Suspend::new(async move {
+            let archives = match archives_res.await {
+                Ok(archives) => archives,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(
+                        view! { <p>{t!("admin.forums.archives_load_failed")}</p> },
+                    );
+                }
+            };
+            if archives.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("admin.forums.no_archives")}</p> });
+            }
+
+            let view = archives
+                .into_iter()
+                .map(|archive| {
+                    let downloads = ArchiveFormat::ALL
+                        .into_iter()
+                        .map(|format| {
+                            view! {
+                              " "
+                              <a href=archive.url(format) class="underline hover:no-underline">
+                                {format.extension()}
+                              </a>
+                            }
+                        })
+                        .collect_view();
+                    view! {
+                      <li>
+                        <span class="font-bold">{archive.forum_name.clone()}</span>
+                        " (#" {archive.forum_id} ") - "
+                        {t!(
+                          "admin.forums.archive_counts",
+                          threads = archive.thread_count,
+                          posts = archive.post_count,
+                        )} " " <LocalTime timestamp=archive.archived_at /> " -" {downloads}
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ul class="text-sm">{view}</ul> })
+        })
+    };
+
+    view! {
+      {delete_status}
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{forums_view}</Suspense>
+      <h3 class="font-bold">{t!("admin.forums.archives")}</h3>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{archives_view}</Suspense>
+    }
+}
+
+/// Renders the settings form of the [`Category`][api::Category] named `name`
+#[component]
+fn CategorySettingsForm(name: String, description: Option<String>) -> impl IntoView {
+    let update_description = ServerAction::<api::UpdateCategoryDescription>::new();
+    let description_status = move || save_status(update_description.value().get());
+
+    view! {
+      <h3 class="mt-2 font-bold">{t!("admin.forums.category", name = name)}</h3>
+      {description_status}
+      <ActionForm action=update_description attr:class="flex flex-col gap-2">
+        <input class="hidden" name="category" value=name />
+        <label class="flex flex-col">
+          {t!("admin.forums.description")}
+          <textarea
+            name="description"
+            maxlength=api::MAX_CATEGORY_DESCRIPTION_LEN
+            rows="2"
+            class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+          >
+            {description}
+          </textarea>
+        </label>
+        <input
+          type="submit"
+          value=t!("common.save")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+    }
+}
+
+/// Renders the settings form of a single [`Forum`]
+#[component]
+fn ForumSettingsForm(
+    forum: Forum,
+    delete: ServerAction<api::forum_archive::DeleteForum>,
+) -> impl IntoView {
+    let update_solved = ServerAction::<api::UpdateForumSolvedWorkflow>::new();
+    let update_requirements = ServerAction::<api::UpdateForumPostingRequirements>::new();
+    let update_caps = ServerAction::<api::UpdateForumActivityCaps>::new();
+    let update_parent = ServerAction::<api::UpdateForumParent>::new();
+    let update_metadata = ServerAction::<api::UpdateForumMetadata>::new();
+    let update_permissions = ServerAction::<api::UpdateForumPermissions>::new();
+    let update_rules = ServerAction::<api::forum_rules::UpdateForumRules>::new();
+
+    let solved_status = move || save_status(update_solved.value().get());
+    let requirements_status = move || save_status(update_requirements.value().get());
+    let caps_status = move || save_status(update_caps.value().get());
+    let parent_status = move || save_status(update_parent.value().get());
+    let metadata_status = move || save_status(update_metadata.value().get());
+    let permissions_status = move || save_status(update_permissions.value().get());
+    let rules_status = move || save_status(update_rules.value().get());
+
+    let read_role = forum.read_role;
+    let read_role_option =
+        move |value: &'static str, label_key: &'static str, role: Option<Role>| {
+            view! {
+              <option value=value selected=read_role == role>
+                {t!(label_key)}
+              </option>
+            }
+        };
+
+    view! {
+      <details class="p-2 bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{forum.name}" (#"{forum.id}")"</summary>
+        {metadata_status}
+        <ActionForm action=update_metadata attr:class="flex flex-col gap-2">
+          <input class="hidden" name="forum_id" value=forum.id />
+          <label>
+            {t!("admin.forums.icon")} " "
+            <input
+              name="icon"
+              maxlength=api::MAX_FORUM_ICON_LEN
+              value=forum.icon
+              class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            />
+          </label>
+          <label class="flex flex-col">
+            {t!("admin.forums.description")}
+            <textarea
+              name="description"
+              maxlength=api::MAX_FORUM_DESCRIPTION_LEN
+              rows="2"
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            >
+              {forum.description}
+            </textarea>
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <h3 class="mt-4 font-bold">{t!("admin.forums.solved_workflow")}</h3>
+        {solved_status}
+        <ActionForm action=update_solved attr:class="flex flex-col gap-2">
+          <input class="hidden" name="forum_id" value=forum.id />
+          <label>
+            <input
+              type="checkbox"
+              name="solved_workflow"
+              value="true"
+              checked=forum.solved_workflow
+            />
+            " "
+            {t!("admin.forums.solved_workflow_enabled")}
+          </label>
+          <label>
+            {t!("admin.forums.lock_after")} " "
+            <input
+              type="number"
+              min="0"
+              name="lock_solved_after_hours"
+              value=forum.lock_solved_after_hours
+              class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            " "
+            {t!("admin.forums.lock_after_unit")}
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <h3 class="mt-4 font-bold">{t!("admin.forums.parent")}</h3>
+        {parent_status}
+        <ActionForm action=update_parent attr:class="flex flex-col gap-2">
+          <input class="hidden" name="forum_id" value=forum.id />
+          <label>
+            {t!("admin.forums.subforum_of")}
+            <input
+              type="number"
+              min="1"
+              name="parent_forum_id"
+              value=forum.parent_forum_id
+              class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            " "
+            {t!("admin.forums.top_level_hint")}
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <h3 class="mt-4 font-bold">{t!("admin.forums.permissions")}</h3>
+        {permissions_status}
+        <ActionForm action=update_permissions attr:class="flex flex-col gap-2">
+          <input class="hidden" name="forum_id" value=forum.id />
+          <label>
+            {t!("admin.forums.visible_to")} " "
+            <select
+              name="read_role"
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            >
+              {read_role_option("", "admin.forums.read_role.everyone", None)}
+              {read_role_option("Member", "admin.forums.read_role.member", Some(Role::Member))}
+              {read_role_option(
+                "Moderator",
+                "admin.forums.read_role.moderator",
+                Some(Role::Moderator),
+              )}
+              {read_role_option("Admin", "admin.forums.read_role.admin", Some(Role::Admin))}
+            </select>
+          </label>
+          <label>
+            <input type="checkbox" name="read_only" value="true" checked=forum.read_only />
+            " "
+            {t!("admin.forums.read_only")}
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <h3 class="mt-4 font-bold">{t!("admin.forums.rules")}</h3>
+        {rules_status}
+        <ActionForm action=update_rules attr:class="flex flex-col gap-2">
+          <input class="hidden" name="forum_id" value=forum.id />
+          <label class="flex flex-col">
+            {t!("admin.forums.rules_label")}
+            <textarea
+              name="rules"
+              maxlength=api::forum_rules::MAX_RULES_LEN
+              rows="5"
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            >
+              {forum.rules}
+            </textarea>
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <h3 class="mt-4 font-bold">{t!("admin.forums.requirements")}</h3>
+        {requirements_status}
+        <ActionForm action=update_requirements attr:class="flex flex-col gap-2">
+          <input class="hidden" name="forum_id" value=forum.id />
+          <label>
+            {t!("admin.forums.min_age")} " "
+            <input
+              type="number"
+              min="0"
+              name="min_account_age_hours"
+              value=forum.min_account_age_hours
+              class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            " "
+            {t!("admin.forums.min_age_unit")}
+          </label>
+          <label>
+            {t!("admin.forums.min_posts")} " "
+            <input
+              type="number"
+              min="0"
+              name="min_post_count"
+              value=forum.min_post_count
+              class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            " "
+            {t!("admin.forums.min_posts_unit")}
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <h3 class="mt-4 font-bold">{t!("admin.forums.caps")}</h3>
+        {caps_status}
+        <ActionForm action=update_caps attr:class="flex flex-col gap-2">
+          <input class="hidden" name="forum_id" value=forum.id />
+          <label>
+            {t!("admin.forums.max_threads")} " "
+            <input
+              type="number"
+              min="1"
+              name="max_threads_per_day"
+              value=forum.max_threads_per_day
+              class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            " "
+            {t!("admin.forums.max_threads_unit")}
+          </label>
+          <label>
+            {t!("admin.forums.max_posts")} " "
+            <input
+              type="number"
+              min="1"
+              name="max_posts_per_hour"
+              value=forum.max_posts_per_hour
+              class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            " "
+            {t!("admin.forums.max_posts_unit")}
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <h3 class="mt-4 font-bold">{t!("admin.delete")}</h3>
+        <ActionForm action=delete attr:class="flex flex-col gap-2">
+          <input class="hidden" name="forum_id" value=forum.id />
+          <label>
+            <input type="checkbox" required />
+            " "
+            {t!("admin.forums.delete_confirm")}
+          </label>
+          <input
+            type="submit"
+            value=t!("admin.forums.delete")
+            class="py-1 px-4 font-bold text-red-50 bg-red-800 rounded-lg hover:bg-red-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+      </details>
+    }
+}
+
+/// Renders the result of a settings form submission, if there was one
+fn save_status(result: Option<Result<(), api::ApiError>>) -> Option<impl IntoView> {
+    let i18n = use_i18n();
+    let view = match result? {
+        Ok(()) => Either::Left(view! { <p class="font-bold">{t!("admin.saved")}</p> }),
+        Err(err) => {
+            Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+        }
+    };
+    Some(view)
+}
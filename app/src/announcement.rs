@@ -0,0 +1,111 @@
+//! Banners of the active [`Announcements`][api::announcements::Announcement]
+//!
+//! Dismissed announcements are remembered in localStorage, so they also stay hidden for guests
+
+use crate::api;
+use crate::t;
+use api::announcements::{RenderedAnnouncement, Severity};
+
+use leptos::{logging, prelude::*};
+
+/// Key of the dismissed announcement ids in localStorage
+const DISMISSED_KEY: &str = "dafoerum_dismissed_announcements";
+
+/// Reads the ids of the dismissed announcements
+fn load_dismissed() -> Vec<u32> {
+    let Some(storage) = window().local_storage().ok().flatten() else {
+        return vec![];
+    };
+    let Some(json) = storage.get_item(DISMISSED_KEY).ok().flatten() else {
+        return vec![];
+    };
+    serde_json::from_str(&json).unwrap_or_default()
+}
+
+/// Saves the ids of the dismissed announcements
+fn store_dismissed(dismissed: &[u32]) {
+    let Some(storage) = window().local_storage().ok().flatten() else {
+        return;
+    };
+    // storage full or disabled, they'll just show up again after a reload then
+    if let Ok(json) = serde_json::to_string(dismissed) {
+        let _ = storage.set_item(DISMISSED_KEY, &json);
+    }
+}
+
+/// Renders the active announcements of the given [`Forum`][api::Forum],
+/// or of the home page if [`None`]
+#[component]
+pub fn AnnouncementBanners(forum_id: Option<u32>) -> impl IntoView {
+    let announcements_res = Resource::new(
+        move || (),
+        move |()| api::announcements::get_active_announcements(forum_id),
+    );
+    let dismissed = RwSignal::new(vec![]);
+    // localStorage is only available on the client
+    Effect::new(move || dismissed.set(load_dismissed()));
+
+    let banners_view = move || {
+        Suspend::new(async move {
+            let announcements = match announcements_res.await {
+                Ok(announcements) => announcements,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    vec![]
+                }
+            };
+            announcements
+                .into_iter()
+                .map(|announcement| {
+                    AnnouncementBanner(AnnouncementBannerProps {
+                        announcement,
+                        dismissed,
+                    })
+                })
+                .collect_view()
+        })
+    };
+
+    view! { <Suspense>{banners_view}</Suspense> }
+}
+
+/// A single announcement, hidden once its id is in `dismissed`
+#[component]
+fn AnnouncementBanner(
+    announcement: RenderedAnnouncement,
+    dismissed: RwSignal<Vec<u32>>,
+) -> impl IntoView {
+    let id = announcement.announcement.id;
+    let severity = announcement.announcement.severity;
+    let colors = match severity {
+        Severity::Info => "text-purple-900 bg-purple-100 border-purple-400",
+        Severity::Warning => "text-amber-900 bg-amber-100 border-amber-400",
+        Severity::Critical => "text-red-900 bg-red-100 border-red-400",
+    };
+    let dismiss = move |_| {
+        dismissed.update(|dismissed| dismissed.push(id));
+        dismissed.with_untracked(|dismissed| store_dismissed(dismissed));
+    };
+    let dismiss_button = (severity != Severity::Critical).then(|| {
+        view! {
+          <button
+            on:click=dismiss
+            aria-label=t!("announcement.dismiss")
+            class="ml-4 font-bold hover:cursor-pointer"
+          >
+            "\u{2715}"
+          </button>
+        }
+    });
+
+    view! {
+      <div
+        role="status"
+        class=format!("flex justify-between items-start p-4 mb-2 w-19/20 rounded-lg border-2 {colors}")
+        class:hidden=move || dismissed.with(|dismissed| dismissed.contains(&id))
+      >
+        <article class="markdown" inner_html=announcement.html></article>
+        {dismiss_button}
+      </div>
+    }
+}
@@ -2,11 +2,82 @@
 //!
 //! Helper functions are in the [`helper`] submodule
 
+pub mod account_merge;
+#[cfg(feature = "ssr")]
+pub mod activitypub;
+pub mod analytics;
+pub mod announcements;
+pub mod api_keys;
+pub mod attachments;
+pub mod avatars;
+pub mod breadcrumbs;
+pub mod bulk_users;
+#[cfg(feature = "ssr")]
+pub mod cache;
+pub mod challenge;
+pub mod counters;
+pub mod csrf;
+pub mod dashboard;
+pub mod embed;
+#[cfg(feature = "ssr")]
+pub mod events;
+pub mod feeds;
+pub mod flags;
+pub mod forum_archive;
+pub mod forum_rules;
+pub mod groups;
 #[cfg(feature = "ssr")]
 pub mod helper;
+pub mod inbound_email;
+pub mod legal;
+pub mod link_previews;
+pub mod login_alerts;
+#[cfg(feature = "ssr")]
+pub mod mailer;
+pub mod marketplace;
+pub mod megathread;
+pub mod messages;
+pub mod mod_log;
+pub mod mod_reasons;
+pub mod mod_shortcuts;
+pub mod moderation;
+pub mod my_content;
+pub mod og_image;
+pub mod onboarding;
+pub mod participants;
+pub mod presence;
+pub mod previews;
+pub mod profiles;
+pub mod reactions;
+pub mod read_markers;
+pub mod rest;
+pub mod scheduling;
+pub mod seo;
+pub mod site_settings;
+pub mod spam;
+pub mod subscriptions;
+pub mod thread_meta;
+pub mod thread_references;
+pub mod thread_tools;
+pub mod typing;
+pub mod unanswered;
+pub mod user;
+pub mod user_import;
+pub mod validation;
+#[cfg(feature = "ssr")]
+pub mod visibility;
+pub mod watch_digests;
+pub mod web_push;
+pub mod webhooks;
+pub mod wiki;
+pub mod word_filter;
 
 #[cfg(feature = "ssr")]
 use mongodb::{Collection, Database, bson};
+#[cfg(feature = "ssr")]
+use site_settings::GuestCapability;
+#[cfg(feature = "ssr")]
+use validation::Field;
 
 use leptos::{
     prelude::*,
@@ -16,6 +87,8 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 /// Error type used for backend-frontend interaction
+///
+/// Clients can translate it using its [`Message`][messages::Message], see [`ApiError::message`]
 #[derive(Debug, Clone, Error, Deserialize, Serialize, Default)]
 pub enum ApiError {
     /// A wrapper around [`ServerFnErrorErr`], basically whenever something
@@ -23,12 +96,16 @@ pub enum ApiError {
     #[error("server fn error: {0}")]
     ServerFn(ServerFnErrorErr),
 
-    /// Used when a generic mongodb error happens
-    #[error("opaque mongodb error: {0}")]
-    Db(String),
-    /// Used for de/serializations errors during database communication
-    #[error("(de)serialization error in db: {0}")]
-    DbDeSer(String),
+    /// Used when talking to the database fails, see [`DbErrorCode`]
+    ///
+    /// `detail` is the driver's message, meant for logs rather than users,
+    /// see [`ApiError::user_message`]
+    #[error("database error ({code}){}: {detail}", context_suffix(context.as_ref()))]
+    Db {
+        code: DbErrorCode,
+        context: Option<DbContext>,
+        detail: String,
+    },
     /// Used when, for some reason (shouldn't ever happen...), the
     /// database is not in leptos context (see [`provide_context`])
     #[error("database not in leptos context")]
@@ -44,12 +121,506 @@ pub enum ApiError {
     /// Used when the subject of a thread is empty
     #[error("subject cannot be empty")]
     EmptySubject,
+    /// Used when written text breaks a [`Rule`][validation::Rule] of its field,
+    /// see [`validation::validate`]
+    #[error("{field} {rule}")]
+    Validation {
+        field: validation::Field,
+        rule: validation::Rule,
+    },
+
+    /// Used when an action requires a logged-in [`User`][user::User]
+    #[error("you have to be logged in to do this")]
+    NotLoggedIn,
+    /// Used when a logged-in browser's request lacks its [`csrf`] token,
+    /// e.g. when sent by another site or from a page older than the token
+    #[error("this form has expired, please reload the page and try again")]
+    CsrfInvalid,
+    /// Used when a guest's answer doesn't pass the
+    /// [`ChallengeProvider`][challenge::ChallengeProvider]
+    #[error("please prove you're human and try again")]
+    ChallengeFailed,
+    /// Used when a guest tries to see something the [`GuestAccess`][site_settings::GuestAccess]
+    /// doesn't allow them to
+    #[error("log in to see this")]
+    LoginRequired,
+    /// Used when the logged-in [`User`][user::User] lacks the required [`Role`][user::Role]
+    #[error("you don't have the permission to do this")]
+    Forbidden,
+    /// Used when the username and password don't match any [`User`][user::User]
+    #[error("invalid username or password")]
+    InvalidCredentials,
+    /// Used when registering with a name that's already in use
+    #[error("username {0} is already taken")]
+    UsernameTaken(String),
+    /// Used when a given username doesn't belong to any [`User`][user::User]
+    #[error("there is no user called {0}")]
+    UnknownUsername(String),
+    /// Used when the username is empty
+    #[error("username cannot be empty")]
+    EmptyUsername,
+    /// Used when an email address doesn't look like one, see [`user_import::looks_like_email`]
+    #[error("invalid email address {0}")]
+    InvalidEmail(String),
+    /// Used when importing a [`User`][user::User] with an email that's already in use
+    #[error("email address {0} is already in use")]
+    EmailTaken(String),
+    /// Used when the password is shorter than [`user::MIN_PASSWORD_LEN`]
+    #[error(
+        "password has to be at least {} characters long",
+        user::MIN_PASSWORD_LEN
+    )]
+    PasswordTooShort,
+    /// Used when a banned [`User`][user::User] tries to post
+    ///
+    /// `until` is [`None`] for permanent bans
+    #[error("you are banned{}: {reason}", until_suffix(*until))]
+    Banned {
+        until: Option<jiff::Timestamp>,
+        reason: String,
+    },
+    /// Used when a muted [`User`][user::User] tries to post
+    ///
+    /// `until` is [`None`] for permanent mutes
+    #[error("you are muted{}: {reason}", until_suffix(*until))]
+    Muted {
+        until: Option<jiff::Timestamp>,
+        reason: String,
+    },
+
+    /// Used when the logged-in [`User`][user::User] doesn't meet the posting
+    /// requirements of a [`Forum`] yet
+    ///
+    /// Contains the forum's requirements, [`None`] for ones not set
+    #[error(
+        "you can't post in this forum yet: {}",
+        requirements_text(*min_account_age_hours, *min_post_count)
+    )]
+    PostingRequirementsNotMet {
+        min_account_age_hours: Option<u32>,
+        min_post_count: Option<u32>,
+    },
+    /// Used when the logged-in [`User`][user::User] already created
+    /// [`Forum::max_threads_per_day`] threads in a forum within the last day
+    ///
+    /// `retry_at` is when they can create the next one
+    #[error(
+        "you can create at most {max_threads_per_day} threads a day in this forum, \
+         try again after {retry_at}"
+    )]
+    ThreadCapReached {
+        max_threads_per_day: u32,
+        retry_at: jiff::Timestamp,
+    },
+    /// Used when the logged-in [`User`][user::User] already wrote
+    /// [`Forum::max_posts_per_hour`] posts in a forum within the last hour
+    ///
+    /// `retry_at` is when they can write the next one
+    #[error(
+        "you can write at most {max_posts_per_hour} posts an hour in this forum, \
+         try again after {retry_at}"
+    )]
+    PostCapReached {
+        max_posts_per_hour: u32,
+        retry_at: jiff::Timestamp,
+    },
+
+    /// Used when a bulk action would be run without restricting the affected users
+    #[error("at least one filter is required")]
+    EmptyFilter,
+    /// Used when a date can't be parsed, expects `YYYY-MM-DD`
+    #[error("invalid date {0}, expected YYYY-MM-DD")]
+    InvalidDate(String),
+    /// Used when the publication time of a [`scheduling::ScheduledPost`]
+    /// can't be parsed or isn't in the future
+    #[error("invalid publication time {0}, expected a date and time in the future")]
+    InvalidPublishTime(String),
+    /// Used when the start or end of an [`Announcement`][announcements::Announcement]
+    /// can't be parsed or it would end before it starts
+    #[error("invalid announcement dates, expected a start before the end")]
+    InvalidAnnouncementDates,
+    /// Used when a date of a [moderation log export][mod_log::export_mod_log]
+    /// can't be parsed or the range ends before it starts
+    #[error("invalid export dates, expected a start before the end")]
+    InvalidExportDates,
+    /// Used when an inbound email isn't addressed to a known reply address,
+    /// see [`inbound_email`]
+    #[error("unknown reply address")]
+    InvalidReplyAddress,
+    /// Used when an inbound email wasn't verifiably sent from the email
+    /// of the reply address' owner
+    #[error("sender doesn't match the reply address")]
+    SenderMismatch,
+    /// Used when a time zone isn't in the IANA time zone database
+    #[error("unknown time zone {0}, expected e.g. Europe/Berlin")]
+    UnknownTimeZone(String),
+
+    /// Used when an uploaded file is bigger than [`attachments::MAX_ATTACHMENT_SIZE`]
+    #[error(
+        "files can be at most {} MiB big",
+        attachments::MAX_ATTACHMENT_SIZE / 1024 / 1024
+    )]
+    AttachmentTooLarge,
+    /// Used when an uploaded file isn't one of the types allowed by [`attachments::sniff_mime`]
+    #[error("only PNG, JPEG, GIF, WebP and PDF files can be attached")]
+    AttachmentTypeNotAllowed,
+    /// Used when a post has more than [`attachments::MAX_ATTACHMENTS_PER_POST`] attachments
+    #[error(
+        "a post can have at most {} attachments",
+        attachments::MAX_ATTACHMENTS_PER_POST
+    )]
+    TooManyAttachments,
+    /// Used when an upload request is malformed
+    #[error("invalid upload: {0}")]
+    InvalidUpload(String),
+    /// Used when an uploaded image can't be decoded or converted
+    #[error("image couldn't be processed: {0}")]
+    ImageProcessing(String),
+    /// Used when a file can't be written to or read from disk
+    #[error("file storage error: {0}")]
+    Storage(String),
+
+    /// Used when content contains a word blocked by the
+    /// [`word filter`][word_filter::FilterAction::Reject]
+    #[error("your text contains the blocked word \"{0}\"")]
+    ContentBlocked(String),
+    /// Used when posting in a locked [`Thread`]
+    #[error("this thread is locked")]
+    ThreadLocked,
+    /// Used when flagging a [`Post`] with a reason longer than [`flags::MAX_FLAG_REASON_LEN`]
+    #[error("reason can be at most {max} characters long", max = flags::MAX_FLAG_REASON_LEN)]
+    FlagReasonTooLong,
+    /// Used when setting [`flags::FlagSettings::threshold`] to `0`, which would collapse every post
+    #[error("the threshold has to be at least 1")]
+    InvalidFlagThreshold,
+    /// Used when posting in a [`Forum`] without having acknowledged its [`Forum::rules`]
+    #[error("please read and acknowledge the rules of this forum before posting")]
+    RulesNotAcknowledged,
+    /// Used when [`Forum::rules`] are longer than [`forum_rules::MAX_RULES_LEN`]
+    #[error("forum rules can be at most {max} characters long", max = forum_rules::MAX_RULES_LEN)]
+    RulesTooLong,
+    /// Used when a request to the [JSON API][rest] has no or an unknown [`api_keys::ApiKey`]
+    #[error("missing or invalid API key")]
+    InvalidApiKey,
+    /// Used when naming an [`api_keys::ApiKey`] with nothing or more than
+    /// [`api_keys::MAX_API_KEY_NAME_LEN`] characters
+    #[error(
+        "API key names have to be between 1 and {max} characters long",
+        max = api_keys::MAX_API_KEY_NAME_LEN
+    )]
+    InvalidApiKeyName,
+    /// Used when naming a [`ModReason`][mod_reasons::ModReason] with nothing or more than
+    /// [`mod_reasons::MAX_MOD_REASON_NAME_LEN`] characters
+    #[error(
+        "reason names have to be between 1 and {max} characters long",
+        max = mod_reasons::MAX_MOD_REASON_NAME_LEN
+    )]
+    InvalidModReasonName,
+    /// Used when adding a [`ModReason`][mod_reasons::ModReason] whose name is already in use
+    #[error("there already is a reason called {0}")]
+    ModReasonExists(String),
+    /// Used when creating more than [`api_keys::MAX_API_KEYS`] keys
+    #[error("you can have at most {max} API keys", max = api_keys::MAX_API_KEYS)]
+    TooManyApiKeys,
+    /// Used when searching for nothing
+    #[error("search query cannot be empty")]
+    EmptySearchQuery,
+    /// Used when a [`Webhook`][webhooks::Webhook] url isn't an `http(s)://` url
+    #[error("invalid webhook url {0}, expected http(s)://...")]
+    InvalidWebhookUrl(String),
+    /// Used when a [`Webhook`][webhooks::Webhook] wouldn't be notified of anything
+    #[error("select at least one event for the webhook")]
+    NoWebhookEvents,
+    /// Used when resyncing a [`Counter`] that doesn't belong to a known collection
+    #[error("counter {0} doesn't belong to a known collection")]
+    UnknownCounter(String),
+    /// Used when resyncing a [`Counter`] that isn't behind the ids in use
+    #[error("counter {0} is already ahead of all ids in use")]
+    CounterNotBehind(String),
+    /// Used when a [`Counter`] changed since it was looked at
+    #[error("counter {0} changed in the meantime, reload and try again")]
+    CounterChanged(String),
+    /// Used when opening a [`PreviewLink`][previews::PreviewLink] that doesn't exist (anymore)
+    #[error("this preview link is invalid or has expired")]
+    InvalidPreviewLink,
+    /// Used when a [`PreviewLink`][previews::PreviewLink] would be valid for no time
+    /// or longer than [`previews::MAX_PREVIEW_HOURS`]
+    #[error("preview links can be valid for 1 to {max} hours", max = previews::MAX_PREVIEW_HOURS)]
+    InvalidPreviewDuration,
+    /// Used when opening the link of a [`LoginAlert`][login_alerts::LoginAlert]
+    /// that doesn't exist (anymore)
+    #[error("this link is invalid, has expired or was already used")]
+    InvalidLoginAlertLink,
+    /// Used when opening the link of an [`Invitation`][user_import::Invitation]
+    /// that doesn't exist (anymore)
+    #[error("this invitation link is invalid, has expired or was already used")]
+    InvalidInvitationLink,
+    /// Used when [importing users][user_import::import_users] from a CSV without any lines
+    #[error("there are no users to import")]
+    NothingToImport,
+    /// Used when importing more than [`user_import::MAX_IMPORT_ROWS`] users at once
+    #[error("at most {max} users can be imported at once", max = user_import::MAX_IMPORT_ROWS)]
+    TooManyImportRows,
+    /// Used when opening the unsubscribe link of a
+    /// [`WatchDigest`][watch_digests::WatchDigest] that doesn't exist (anymore)
+    #[error("this unsubscribe link is invalid")]
+    InvalidUnsubscribeLink,
+    /// Used when an origin allowed to embed threads isn't of the form `https://host[:port]`,
+    /// see [`embed::normalize_origin`]
+    #[error("invalid origin {0}, expected e.g. https://blog.example")]
+    InvalidOrigin(String),
+    /// Used when a CAPTCHA [`ChallengeProvider`][challenge::ChallengeProvider] is picked
+    /// without its site key
+    #[error("this CAPTCHA needs a site key")]
+    MissingSiteKey,
+    /// Used when the url of a [`ContentLicense`][site_settings::ContentLicense]
+    /// isn't an `http(s)://` url
+    #[error("invalid license url {0}, expected http(s)://...")]
+    InvalidLicenseUrl(String),
+    /// Used when subscribing to push notifications with an endpoint that isn't of
+    /// a known push service, see [`web_push::is_push_service`]
+    #[error("this browser's push service isn't supported")]
+    UnsupportedPushService,
+    /// Used when deleting a [`Forum`] that still has subforums
+    #[error("this forum has subforums, move or delete them first")]
+    ForumHasSubforums,
+    /// Used when a non-moderator posts in a [`Forum::read_only`] forum
+    #[error("only moderators can post in this forum")]
+    ForumReadOnly,
+    /// Used when trying to solve a [`Thread`] in a [`Forum`] without the solved workflow
+    #[error("threads in this forum can't be marked as solved")]
+    SolvingDisabled,
+
+    /// Used when a [`WikiPage`][wiki::WikiPage] with the given slug doesn't exist
+    #[error("wiki page {0} doesn't exist")]
+    WikiPageNotFound(String),
+    /// Used when a wiki slug contains anything but lowercase letters, digits and `-`
+    #[error("invalid wiki slug {0}: only lowercase letters, digits and - are allowed")]
+    InvalidSlug(String),
+
+    /// Used when a [`UserGroup`][groups::UserGroup] would be saved without a name
+    #[error("group name cannot be empty")]
+    EmptyGroupName,
+    /// Used when a color isn't of the form `#rrggbb`
+    #[error("invalid color {0}, expected #rrggbb")]
+    InvalidColor(String),
+    /// Used when moving users to a [`UserGroup`][groups::UserGroup] without choosing one
+    #[error("select a group to move the users to")]
+    NoGroupSelected,
+    /// Used when a given group name doesn't belong to any [`UserGroup`][groups::UserGroup]
+    #[error("there is no group called {0}")]
+    UnknownGroup(String),
+    /// Used when the primary group of a [`User`][user::User] isn't one of their groups
+    #[error("the primary group has to be one of the user's groups")]
+    PrimaryGroupNotJoined,
+
+    /// Used when a [`Forum`] would be moved below itself, one of its subforums
+    /// or a forum in another [`Category`]
+    #[error(
+        "a forum can only be moved below another forum of the same category that isn't one of its subforums"
+    )]
+    InvalidParentForum,
+    /// Used when merging a [`Thread`] into itself
+    #[error("a thread cannot be merged into itself")]
+    MergeIntoSelf,
+    /// Used when merging a [`User`][user::User] account into itself
+    #[error("an account cannot be merged into itself")]
+    MergeAccountIntoSelf,
+    /// Used when merging a [`User`][user::User] account that already got merged or into one,
+    /// which would chain merges
+    #[error("{0} has already been merged into another account")]
+    AccountAlreadyMerged(String),
+    /// Used when the [`Posts`][Post] to split off aren't from a single [`Thread`]
+    /// or include its origin post
+    #[error("select one or more posts of a single thread, not including its first post")]
+    InvalidSplit,
+    /// Used when moving a [`Post`] without a target [`Thread`], into its own thread
+    /// or when it's the origin post of its thread
+    #[error("pick another thread to move the post to, first posts only move with their thread")]
+    InvalidMove,
+    /// Used when warning the author of a [`Post`] by a guest or a deleted account
+    #[error("the post has no author to warn")]
+    NoAuthorToWarn,
+    /// Used when a forum description is longer than [`MAX_FORUM_DESCRIPTION_LEN`]
+    #[error("forum description can be at most {MAX_FORUM_DESCRIPTION_LEN} characters long")]
+    ForumDescriptionTooLong,
+    /// Used when a forum icon is longer than [`MAX_FORUM_ICON_LEN`]
+    #[error("forum icon can be at most {MAX_FORUM_ICON_LEN} characters long")]
+    ForumIconTooLong,
+    /// Used when a category description is longer than [`MAX_CATEGORY_DESCRIPTION_LEN`]
+    #[error("category description can be at most {MAX_CATEGORY_DESCRIPTION_LEN} characters long")]
+    CategoryDescriptionTooLong,
+    /// Used when there is no [`Category`] with the given name
+    #[error("there is no category named \"{0}\"")]
+    UnknownCategory(String),
+    /// Used when a tag is empty, too long or contains something other than letters,
+    /// digits and dashes, see [`thread_tools::normalize_tag`]
+    #[error(
+        "\"{0}\" isn't a valid tag, tags are letters, digits and dashes and at most {max} characters long",
+        max = thread_tools::MAX_TAG_LEN
+    )]
+    InvalidTag(String),
+    /// Used when a [`Thread`] would have more than [`thread_tools::MAX_TAGS`] tags
+    #[error("a thread can have at most {max} tags", max = thread_tools::MAX_TAGS)]
+    TooManyTags,
+    /// Used when a [`Thread::prefix`] is longer than [`thread_tools::MAX_PREFIX_LEN`]
+    #[error("prefix can be at most {max} characters long", max = thread_tools::MAX_PREFIX_LEN)]
+    PrefixTooLong,
+    /// Used when the price of a [`marketplace::Listing`] can't be parsed,
+    /// see [`marketplace::parse_price`]
+    #[error("invalid price {0}, expected an amount like 12.50")]
+    InvalidPrice(String),
+    /// Used when the location of a [`marketplace::Listing`] is empty
+    /// or longer than [`marketplace::MAX_LOCATION_LEN`]
+    #[error(
+        "location can't be empty and can be at most {max} characters long",
+        max = marketplace::MAX_LOCATION_LEN
+    )]
+    InvalidListingLocation,
+    /// Used when a batch operation on [`Threads`][Thread] got no threads
+    #[error("select one or more threads")]
+    NoThreadsSelected,
+
+    /// Used when thanking a [`Post`] a second time
+    #[error("you already thanked this post")]
+    AlreadyThanked,
+    /// Used when reacting with a [`ReactionOption`][reactions::ReactionOption]
+    /// that doesn't exist or is disabled
+    #[error("reaction {0} isn't available")]
+    UnknownReaction(u32),
+    /// Used when editing an own [`Post`] after the
+    /// [edit window][site_settings::SiteSettings::edit_window_minutes] is over
+    #[error("posts can only be edited within {0} minutes of posting")]
+    EditWindowExpired(u32),
+    /// Used when a moderator makes a [staff edit][Post::staff_edit] without a reason
+    #[error("staff edits need a reason")]
+    EditReasonRequired,
+    /// Used when the reason of a staff edit is longer than [`MAX_EDIT_REASON_LEN`]
+    #[error("edit reason can be at most {MAX_EDIT_REASON_LEN} characters long")]
+    EditReasonTooLong,
+
+    /// Used when content was saved, but is only visible after a moderator approves it
+    #[error("your post will be visible after it has been approved by a moderator")]
+    HeldForReview,
 
     /// Dummy error for ergonomics of `.unwrap_or_default()`
     #[default]
     #[error("this shouldn't happen!")]
     Dummy,
 }
+/// Machine-readable kind of an [`ApiError::Db`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DbErrorCode {
+    /// The database couldn't be reached or didn't answer in time
+    Unavailable,
+    /// A unique index was violated, e.g. by two inserts racing each other
+    DuplicateKey,
+    /// A document didn't match its Rust type or the other way round
+    Serialization,
+    /// Anything else, e.g. an invalid query
+    Other,
+}
+impl DbErrorCode {
+    /// The code as sent to clients, e.g. `duplicate_key`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Unavailable => "unavailable",
+            Self::DuplicateKey => "duplicate_key",
+            Self::Serialization => "serialization",
+            Self::Other => "other",
+        }
+    }
+}
+impl std::fmt::Display for DbErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What was being done when an [`ApiError::Db`] happened
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DbOperation {
+    Read,
+    Insert,
+    Update,
+    Delete,
+}
+
+/// Which document an [`ApiError::Db`] happened with, see [`ApiError::in_context`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DbContext {
+    pub operation: DbOperation,
+    /// E.g. `thread`, like in [`ApiError::NotFound`]
+    pub entity: String,
+    pub id: Option<u32>,
+}
+
+impl ApiError {
+    /// Adds what was being done to an [`ApiError::Db`] without a [`DbContext`] yet,
+    /// all other errors are returned as they are
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::{ApiError, DbErrorCode, DbOperation};
+    ///
+    /// let err = ApiError::Db { code: DbErrorCode::Other, context: None, detail: "oops".into() };
+    /// let err = err.in_context(DbOperation::Read, "thread", Some(3));
+    /// assert_eq!(err.to_string(), "database error (other) while reading thread 3: oops");
+    /// ```
+    #[must_use]
+    pub fn in_context(self, operation: DbOperation, entity: &str, id: Option<u32>) -> Self {
+        match self {
+            Self::Db {
+                code,
+                context: None,
+                detail,
+            } => Self::Db {
+                code,
+                context: Some(DbContext {
+                    operation,
+                    entity: entity.to_string(),
+                    id,
+                }),
+                detail,
+            },
+            other => other,
+        }
+    }
+}
+
+/// Formats the [`DbContext`] of an [`ApiError::Db`] for its message
+fn context_suffix(context: Option<&DbContext>) -> String {
+    let Some(context) = context else {
+        return String::new();
+    };
+    let operation = match context.operation {
+        DbOperation::Read => "reading",
+        DbOperation::Insert => "inserting",
+        DbOperation::Update => "updating",
+        DbOperation::Delete => "deleting",
+    };
+    let id = context.id.map(|id| format!(" {id}")).unwrap_or_default();
+    format!(" while {operation} {}{id}", context.entity)
+}
+/// Formats the end of a sanction for [`ApiError`] messages
+fn until_suffix(until: Option<jiff::Timestamp>) -> String {
+    until.map_or_else(String::new, |until| format!(" until {until}"))
+}
+/// Formats the posting requirements of a [`Forum`] for [`ApiError`] messages
+fn requirements_text(min_account_age_hours: Option<u32>, min_post_count: Option<u32>) -> String {
+    let mut requirements = vec![];
+    if let Some(hours) = min_account_age_hours {
+        requirements.push(format!("your account has to be at least {hours} hours old"));
+    }
+    if let Some(count) = min_post_count {
+        requirements.push(format!("you need at least {count} posts"));
+    }
+    requirements.join(" and ")
+}
 impl FromServerFnError for ApiError {
     type Encoder = server_fn::codec::JsonEncoding;
 
@@ -60,11 +631,26 @@ impl FromServerFnError for ApiError {
 #[cfg(feature = "ssr")]
 impl From<mongodb::error::Error> for ApiError {
     fn from(value: mongodb::error::Error) -> Self {
-        use mongodb::error::ErrorKind as E;
-        let msg = value.to_string();
-        match *value.kind {
-            E::BsonSerialization(_) | E::BsonDeserialization(_) => ApiError::DbDeSer(msg),
-            _ => ApiError::Db(msg),
+        use mongodb::error::{ErrorKind as E, WriteFailure};
+
+        // see https://www.mongodb.com/docs/manual/reference/error-codes/
+        const DUPLICATE_KEY: i32 = 11000;
+
+        let code = match value.kind.as_ref() {
+            E::BsonSerialization(_) | E::BsonDeserialization(_) => DbErrorCode::Serialization,
+            E::Io(_)
+            | E::DnsResolve { .. }
+            | E::ServerSelection { .. }
+            | E::ConnectionPoolCleared { .. } => DbErrorCode::Unavailable,
+            E::Write(WriteFailure::WriteError(err)) if err.code == DUPLICATE_KEY => {
+                DbErrorCode::DuplicateKey
+            }
+            _ => DbErrorCode::Other,
+        };
+        ApiError::Db {
+            code,
+            context: None,
+            detail: value.to_string(),
         }
     }
 }
@@ -85,6 +671,13 @@ trait CollectionName: std::marker::Send + std::marker::Sync + std::marker::Sized
 trait GetCollection: CollectionName {
     /// Returns the type's [`Collection`] in the [`Database`]
     fn collection(db: &Database) -> Collection<Self>;
+
+    /// Returns the type's [`Collection`] in the [`Database`] for heavy read-only queries
+    /// like stats, which are routed according to [`helper::heavy_read_preference`]
+    ///
+    /// Reads may be slightly stale when served by a replica,
+    /// so never use this for anything that's written back
+    fn replica_collection(db: &Database) -> Collection<Self>;
 }
 #[cfg(feature = "ssr")]
 impl<T> GetCollection for T
@@ -94,6 +687,13 @@ where
     fn collection(db: &Database) -> Collection<Self> {
         db.collection::<Self>(Self::collection_name())
     }
+
+    fn replica_collection(db: &Database) -> Collection<Self> {
+        let options = mongodb::options::CollectionOptions::builder()
+            .selection_criteria(helper::heavy_read_preference())
+            .build();
+        db.collection_with_options::<Self>(Self::collection_name(), options)
+    }
 }
 
 /// Used for creating new things that require an incrementing id
@@ -104,8 +704,7 @@ where
 pub struct Counter {
     /// Name of the thing to sequence, e.g. "post", "thread", "user" etc.
     ///
-    /// This *must* exist in the db collection, otherwise dependent functions
-    /// like [`helper::get_and_increment_id_of`] will panic
+    /// Will be created by [`helper::get_and_increment_id_of`] if it doesn't exist yet
     category: String,
 
     /// Current highest id
@@ -122,6 +721,9 @@ impl CollectionName for Counter {
 pub struct Category {
     pub name: String,
     pub order: u32,
+    /// Shown under the heading of its section on the forum index
+    #[serde(default)]
+    pub description: Option<String>,
     pub forums: Vec<Forum>,
 }
 impl CollectionName for Category {
@@ -130,15 +732,82 @@ impl CollectionName for Category {
     }
 }
 
-/// Represents a top-level forum: contains multiple [`Threads`][Thread]
-/// TBD: can contain [`Subforum`][Subforum]
+/// Maximum amount of characters of [`Forum::description`]
+pub const MAX_FORUM_DESCRIPTION_LEN: usize = 300;
+/// Maximum amount of characters of [`Category::description`]
+pub const MAX_CATEGORY_DESCRIPTION_LEN: usize = 300;
+/// Maximum amount of characters of [`Forum::icon`]
+pub const MAX_FORUM_ICON_LEN: usize = 4;
+/// Maximum amount of characters of [`Post::edit_reason`]
+pub const MAX_EDIT_REASON_LEN: usize = 200;
+
+/// Represents a forum: contains multiple [`Threads`][Thread] and possibly subforums
 ///
-/// [`Threads`][Thread] are saved in a separate db collection and refer to their parent forum
+/// [`Threads`][Thread] are saved in a separate db collection and refer to their parent forum.
+/// Subforums are saved in the same [`Category`] as their parent and refer to it
+/// with [`Forum::parent_forum_id`]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Forum {
     pub id: u32,
     pub name: String,
     pub latest_thread_id: u32,
+    /// Short text shown below the name
+    #[serde(default)]
+    pub description: Option<String>,
+    /// An emoji or a few characters shown in front of the name
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// [`None`] for top-level forums
+    #[serde(default)]
+    pub parent_forum_id: Option<u32>,
+    /// Whether thread authors can mark their [`Threads`][Thread] as solved,
+    /// meant for support forums
+    #[serde(default)]
+    pub solved_workflow: bool,
+    /// If set, solved [`Threads`][Thread] are locked this many hours after being solved
+    #[serde(default)]
+    pub lock_solved_after_hours: Option<u32>,
+    /// If set, accounts have to be at least this many hours old to post here
+    #[serde(default)]
+    pub min_account_age_hours: Option<u32>,
+    /// If set, users need at least this many visible posts anywhere to post here
+    #[serde(default)]
+    pub min_post_count: Option<u32>,
+    /// If set, users can create at most this many threads here within a day,
+    /// e.g. for marketplace forums, see [`helper::ensure_within_activity_caps`]
+    #[serde(default)]
+    pub max_threads_per_day: Option<u32>,
+    /// If set, users can write at most this many posts (including the ones starting threads)
+    /// here within an hour
+    #[serde(default)]
+    pub max_posts_per_hour: Option<u32>,
+    /// If set, only users with at least this role can see the forum, its subforums
+    /// and everything in them, e.g. [`Role::Member`][user::Role::Member] for members-only forums
+    #[serde(default)]
+    pub read_role: Option<user::Role>,
+    /// Only moderators can create threads and post in read-only forums, e.g. for announcements
+    #[serde(default)]
+    pub read_only: bool,
+    /// Markdown rules users have to acknowledge before posting here, see [`forum_rules`]
+    #[serde(default)]
+    pub rules: Option<String>,
+}
+impl Forum {
+    /// Checks if `user` (anonymous if [`None`]) may see this forum
+    /// according to its [`Forum::read_role`], ignoring the forums above it
+    #[must_use]
+    pub fn is_visible_to(&self, user: Option<&user::User>) -> bool {
+        self.read_role
+            .is_none_or(|role| user.is_some_and(|user| user.role >= role))
+    }
+
+    /// Checks if only moderators and admins can see this forum, meant for internal discussions
+    /// that are kept out of public listings, see [`visibility`]
+    #[must_use]
+    pub fn is_staff_only(&self) -> bool {
+        self.read_role
+            .is_some_and(|role| role >= user::Role::Moderator)
+    }
 }
 
 /// Represents a thread: it's part of a [`Forum`] and contains multiple [`Posts`][Post]
@@ -151,6 +820,92 @@ pub struct Thread {
     pub forum_id: u32,
     pub subject: String,
     pub latest_post_id: u32,
+    /// [`None`] for threads created without being logged in
+    #[serde(default)]
+    pub author_id: Option<u32>,
+    /// Hidden until approved by a moderator, see [`moderation`]
+    #[serde(default)]
+    pub pending_review: bool,
+    /// Locked threads don't accept new [`Posts`][Post]
+    #[serde(default)]
+    pub locked: bool,
+    /// When the thread was marked as solved, see [`Forum::solved_workflow`]
+    #[serde(with = "jiff_timestamp_as_bson_datetime::optional", default)]
+    pub solved_at: Option<jiff::Timestamp>,
+    /// Whether the origin post is a wiki post, editable by the [`Thread::wiki_editor_ids`]
+    /// besides its author, e.g. the index of a megathread
+    #[serde(default)]
+    pub wiki_post: bool,
+    /// [`Users`][user::User] allowed to edit the origin post if it's a [`Thread::wiki_post`]
+    #[serde(default)]
+    pub wiki_editor_ids: Vec<u32>,
+    /// [`Users`][user::User] allowed to edit the origin post and the subject besides its author,
+    /// e.g. the organizers of an event, see [`set_thread_co_authors`]
+    #[serde(default)]
+    pub co_author_ids: Vec<u32>,
+    /// Soft-deleted threads are only visible to moderators,
+    /// see [`thread_tools::delete_thread`]
+    #[serde(with = "jiff_timestamp_as_bson_datetime::optional", default)]
+    pub deleted_at: Option<jiff::Timestamp>,
+    /// Lowercase tags set by moderators, see [`thread_tools::bulk_edit_thread_tags`]
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Shown in brackets in front of the subject, e.g. `Guide`
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// Set for marketplace threads, see [`marketplace`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listing: Option<marketplace::Listing>,
+}
+impl Thread {
+    /// Checks if the thread is locked, either explicitly or because it has been solved
+    /// for longer than [`Forum::lock_solved_after_hours`] of its `forum`
+    #[must_use]
+    pub fn is_locked(&self, forum: &Forum, now: jiff::Timestamp) -> bool {
+        if self.locked {
+            return true;
+        }
+        let (Some(solved_at), Some(hours)) = (self.solved_at, forum.lock_solved_after_hours) else {
+            return false;
+        };
+        solved_at + jiff::SignedDuration::from_hours(i64::from(hours)) <= now
+    }
+
+    /// Checks if `user` may edit the [`Post`] with `post_id` in this thread
+    /// as one of the [`Thread::wiki_editor_ids`] of its wiki post
+    #[must_use]
+    pub fn is_wiki_editor(&self, post_id: u32, user: &user::User) -> bool {
+        self.wiki_post && self.origin_post_id == post_id && self.wiki_editor_ids.contains(&user.id)
+    }
+
+    /// Checks if `user` is one of the [`Thread::co_author_ids`]
+    #[must_use]
+    pub fn is_co_author(&self, user: &user::User) -> bool {
+        self.co_author_ids.contains(&user.id)
+    }
+
+    /// Checks if `user` may edit the [`Post`] with `post_id` in this thread besides its author
+    /// and moderators, i.e. it's the origin post and they're a co-author or wiki editor
+    #[must_use]
+    pub fn can_edit_as_collaborator(&self, post_id: u32, user: &user::User) -> bool {
+        self.is_wiki_editor(post_id, user)
+            || (self.origin_post_id == post_id && self.is_co_author(user))
+    }
+
+    /// The subject with the [`Thread::prefix`] in brackets in front of it,
+    /// and a `[Solved]` one if the thread has been solved
+    #[must_use]
+    pub fn display_subject(&self) -> String {
+        let subject = match &self.prefix {
+            Some(prefix) => format!("[{prefix}] {}", self.subject),
+            None => self.subject.clone(),
+        };
+        if self.solved_at.is_some() {
+            format!("[Solved] {subject}")
+        } else {
+            subject
+        }
+    }
 }
 impl CollectionName for Thread {
     fn collection_name() -> &'static str {
@@ -160,29 +915,75 @@ impl CollectionName for Thread {
 
 /// Represents a post: it's part of a thread and contains a message
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "independent flags stored as is in the db"
+)]
 pub struct Post {
     pub id: u32,
     pub content: String,
+    /// Finds posts with the same content, see [`spam::content_hash`]
+    #[serde(default)]
+    pub content_hash: String,
 
     /// Will be de/serialized as [`bson::DateTime`] for communication with the db
     #[serde(with = "jiff_timestamp_as_bson_datetime")]
     pub created_at: jiff::Timestamp,
     pub thread_id: u32,
-}
-impl Post {
-    /// 2025-03-07T02:12:38+01:00
-    #[allow(
-        clippy::missing_panics_doc,
-        reason = "unwrapping after a static str in"
-    )]
-    #[must_use]
-    pub fn date_in_berlin(&self) -> String {
-        self.created_at
-            .in_tz("Europe/Berlin")
-            .unwrap()
-            .strftime("%FT%T%:z")
-            .to_string()
-    }
+    /// [`None`] for posts created without being logged in
+    #[serde(default)]
+    pub author_id: Option<u32>,
+    /// Hidden until approved by a moderator, see [`moderation`]
+    #[serde(default)]
+    pub pending_review: bool,
+    /// What the [`spam`] heuristics scored it, only set for posts they held
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spam_score: Option<u32>,
+    /// Shown folded after being flagged too often, until a moderator reviews it,
+    /// see [`flags`]
+    #[serde(default)]
+    pub collapsed: bool,
+    /// How often the post has been thanked, see [`thank_post`]
+    #[serde(default)]
+    pub thanks: u32,
+    /// Incremented on every [`edit`][edit_post], `0` if never edited
+    #[serde(default)]
+    pub revision: u32,
+    #[serde(default, with = "jiff_timestamp_as_bson_datetime::optional")]
+    pub edited_at: Option<jiff::Timestamp>,
+    /// Who made the last edit, [`None`] if never edited
+    #[serde(default)]
+    pub edited_by: Option<u32>,
+    /// Whether the last edit was made by a moderator as staff,
+    /// i.e. to someone else's post or after the edit window
+    #[serde(default)]
+    pub staff_edit: bool,
+    /// Why a moderator made the last edit, only set for [staff edits][Self::staff_edit]
+    #[serde(default)]
+    pub edit_reason: Option<String>,
+    /// Listed in the index of megathreads, see [`megathread`]
+    #[serde(default)]
+    pub highlighted: bool,
+    /// Set while its [`Thread`] is [soft-deleted][Thread::deleted_at],
+    /// hides it from e.g. the latest posts
+    #[serde(default)]
+    pub thread_deleted: bool,
+    /// Soft-deleted posts are hidden from everyone but kept for the [moderation log][mod_log],
+    /// see [`mod_shortcuts::PostShortcut::Delete`]
+    #[serde(with = "jiff_timestamp_as_bson_datetime::optional", default)]
+    pub deleted_at: Option<jiff::Timestamp>,
+    /// Id of the moderator who deleted it
+    #[serde(default)]
+    pub deleted_by: Option<u32>,
+    /// The [`Post`] this one is replying to
+    #[serde(default)]
+    pub quote: Option<Quote>,
+    #[serde(default)]
+    pub attachments: Vec<attachments::AttachmentRef>,
+    /// Cards of the links in it, added in the background after creating it,
+    /// see [`link_previews`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link_previews: Vec<link_previews::LinkPreview>,
 }
 impl CollectionName for Post {
     fn collection_name() -> &'static str {
@@ -190,11 +991,83 @@ impl CollectionName for Post {
     }
 }
 
-/// Queries all [`Categories`][Category] contining top-level [`Forums`][Forum] from the db
+/// A [`Post`] with the [`Member`][groups::Member] who wrote it, for listing posts
+/// without fetching every author on its own
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PostWithAuthor {
+    pub post: Post,
+    /// [`None`] for posts created without being logged in and of deleted accounts
+    pub author: Option<groups::Member>,
+    /// [`Post::content`] rendered from Markdown, with highlighted code blocks
+    pub html: String,
+}
+
+/// A quoted [`Post`], snapshotted at the time of quoting
+///
+/// Keeps the quoted text so that later edits of the source
+/// don't silently change the meaning of the quoting post
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Quote {
+    pub post_id: u32,
+    /// [`Post::revision`] of the source at the time of quoting
+    pub revision: u32,
+    pub content: String,
+    /// Set once the source post gets edited after it was quoted
+    #[serde(default)]
+    pub source_edited: bool,
+}
+
+/// A previous version of an edited [`Post`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PostRevision {
+    pub post_id: u32,
+    pub revision: u32,
+    pub content: String,
+    /// Who wrote this version, [`None`] for the original by the [`Post::author_id`]
+    #[serde(default)]
+    pub edited_by: Option<u32>,
+    /// Whether this version was a [staff edit][Post::staff_edit]
+    #[serde(default)]
+    pub staff_edit: bool,
+    /// See [`Post::edit_reason`]
+    #[serde(default)]
+    pub edit_reason: Option<String>,
+    /// When this version was replaced by an edit
+    #[serde(with = "jiff_timestamp_as_bson_datetime")]
+    pub replaced_at: jiff::Timestamp,
+}
+impl CollectionName for PostRevision {
+    fn collection_name() -> &'static str {
+        "post_revisions"
+    }
+}
+
+/// Records that a [`User`][user::User] thanked the author of a [`Post`]
+///
+/// Exists so that every user can thank a post only once
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Thanks {
+    pub post_id: u32,
+    pub from_user_id: u32,
+    pub to_user_id: u32,
+    #[serde(with = "jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+impl CollectionName for Thanks {
+    fn collection_name() -> &'static str {
+        "thanks"
+    }
+}
+
+/// Queries all [`Categories`][Category] with all their [`Forums`][Forum] from the db
+///
+/// Subforums are included, filter by [`Forum::parent_forum_id`] for the top-level ones.
+/// Forums the logged-in [`User`][user::User] can't see are left out, see [`Forum::read_role`]
 #[server]
 pub async fn get_categories() -> Result<Vec<Category>, ApiError> {
     let db = helper::get_db()?;
     // tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    let user = helper::get_reader(GuestCapability::Forums, &db).await?;
     let category_col = Category::collection(&db);
     let mut categories = vec![];
     let mut categories_cursor = category_col
@@ -205,6 +1078,19 @@ pub async fn get_categories() -> Result<Vec<Category>, ApiError> {
     while categories_cursor.advance().await? {
         categories.push(categories_cursor.deserialize_current()?);
     }
+
+    // hidden forums never leave the server, nor do categories with only hidden forums
+    let forums: Vec<Forum> = categories
+        .iter()
+        .flat_map(|category| category.forums.clone())
+        .collect();
+    categories.retain_mut(|category| {
+        let had_forums = !category.forums.is_empty();
+        category
+            .forums
+            .retain(|forum| helper::can_see_forum(&forums, forum.id, user.as_ref()));
+        !had_forums || !category.forums.is_empty()
+    });
     Ok(categories)
 }
 
@@ -214,29 +1100,98 @@ pub async fn get_categories() -> Result<Vec<Category>, ApiError> {
 pub async fn get_forum(forum_id: u32) -> Result<(Forum, String), ApiError> {
     let db = helper::get_db()?;
     // tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    let user = helper::get_reader(GuestCapability::Forums, &db).await?;
+    helper::ensure_can_see_forum(forum_id, user.as_ref(), &db).await?;
     helper::get_forum(forum_id, db).await
 }
 
+/// Returns the direct subforums of the given [`Forum`]
+#[server]
+pub async fn get_subforums(forum_id: u32) -> Result<Vec<Forum>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Forums, &db).await?;
+    let forums = helper::get_all_forums(&db).await?;
+    Ok(forums
+        .iter()
+        .filter(|forum| forum.parent_forum_id == Some(forum_id))
+        .filter(|forum| helper::can_see_forum(&forums, forum.id, user.as_ref()))
+        .cloned()
+        .collect())
+}
+
 /// Looks up if the given `thread_id` exists in the database and returns the [`Thread`] if so
+///
+/// For threads that were [merged][thread_tools::merge_threads], the thread they were merged into
+/// is returned instead
 #[server]
 pub async fn get_thread(thread_id: u32) -> Result<Thread, ApiError> {
     let db = helper::get_db()?;
     // tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-    helper::get_thread(thread_id, db).await
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    let thread = match helper::get_thread(thread_id, db.clone()).await {
+        Err(ApiError::NotFound(..)) => match thread_tools::redirect_target(thread_id, &db).await? {
+            Some(target_thread_id) => helper::get_thread(target_thread_id, db.clone()).await?,
+            None => return Err(ApiError::NotFound("thread".into(), thread_id)),
+        },
+        result => result?,
+    };
+    if thread.pending_review {
+        return Err(ApiError::NotFound("thread".into(), thread_id));
+    }
+    if thread.deleted_at.is_some()
+        && user
+            .as_ref()
+            .is_none_or(|user| user.role < user::Role::Moderator)
+    {
+        return Err(ApiError::NotFound("thread".into(), thread_id));
+    }
+    if helper::ensure_can_see_forum(thread.forum_id, user.as_ref(), &db)
+        .await
+        .is_err()
+    {
+        return Err(ApiError::NotFound("thread".into(), thread_id));
+    }
+    Ok(thread)
 }
 
 /// Fetches all [`Threads`][Thread] with their [`Post`]count and its latest [`Post`]
 /// of a given [`Forum`] from the database in id-descending order
+///
+/// With `unsolved_only`, solved threads are left out (for helpers in support forums)
+///
+/// With `deleted_only`, only [soft-deleted][Thread::deleted_at] threads are returned,
+/// which requires [`Role::Moderator`][user::Role::Moderator]. They're left out otherwise
+///
+/// Errors with [`ApiError::NotFound`] if the forum isn't visible to the logged-in
+/// [`User`][user::User], see [`Forum::read_role`]
 #[server]
-pub async fn get_threads(forum_id: u32) -> Result<Vec<(Thread, u64, Post)>, ApiError> {
+pub async fn get_threads(
+    forum_id: u32,
+    unsolved_only: bool,
+    #[server(default)] deleted_only: bool,
+) -> Result<Vec<(Thread, u64, Post)>, ApiError> {
     let db = helper::get_db()?;
     // tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
     let post_col = Post::collection(&db);
     let thread_col = Thread::collection(&db);
 
+    let user = helper::get_reader(GuestCapability::Forums, &db).await?;
+    helper::ensure_can_see_forum(forum_id, user.as_ref(), &db).await?;
+
+    let mut filter = bson::doc! {"forum_id": forum_id, "pending_review": {"$ne": true}};
+    if deleted_only {
+        helper::require_role(&db, user::Role::Moderator).await?;
+        filter.insert("deleted_at", bson::doc! {"$ne": bson::Bson::Null});
+    } else {
+        filter.insert("deleted_at", bson::Bson::Null);
+    }
+    if unsolved_only {
+        filter.insert("solved_at", bson::Bson::Null);
+    }
+
     let mut threads = vec![];
     let mut threads_cursor = thread_col
-        .find(bson::doc! {"forum_id": forum_id})
+        .find(filter)
         // descending
         .sort(bson::doc! {"id": -1})
         .await?;
@@ -244,7 +1199,11 @@ pub async fn get_threads(forum_id: u32) -> Result<Vec<(Thread, u64, Post)>, ApiE
     while threads_cursor.advance().await? {
         let thread = threads_cursor.deserialize_current()?;
         let post_count = post_col
-            .count_documents(bson::doc! {"thread_id": thread.id})
+            .count_documents(bson::doc! {
+                "thread_id": thread.id,
+                "pending_review": {"$ne": true},
+                "deleted_at": null,
+            })
             .await?;
         let latest_post = helper::get_post(thread.latest_post_id, db.clone()).await?;
         threads.push((thread, post_count, latest_post));
@@ -255,164 +1214,1025 @@ pub async fn get_threads(forum_id: u32) -> Result<Vec<(Thread, u64, Post)>, ApiE
 
 /// Tries to create a [`Thread`] within the given forum and with a [`Post`] of `post_content`
 ///
-/// Will error if `subject` or `post_content` are empty
+/// Will error if `subject` or `post_content` are empty or too long (see [`validation`]),
+/// if a guest's `challenge_response` doesn't pass the [`challenge`],
+/// if the logged-in [`User`][user::User] is banned or muted, can't post in the forum
+/// (see [`Forum::read_role`] and [`Forum::read_only`]) or doesn't meet
+/// the forum's posting requirements, if the [`word_filter`] rejects the text
+/// or if the [`attachments`] can't be claimed
 ///
-/// Returns the `thread_id` of the created [`Thread`]
+/// Returns the `thread_id` of the created [`Thread`],
+/// or [`ApiError::HeldForReview`] if the [`word_filter`] or the [`spam`] heuristics hold it
+/// for review
 #[server]
 pub async fn create_thread(
     forum_id: u32,
     subject: String,
     post_content: String,
+    #[server(default)] attachment_ids: Vec<u32>,
+    #[server(default)] challenge_response: String,
 ) -> Result<u32, ApiError> {
-    if subject.is_empty() {
-        return Err(ApiError::EmptySubject);
+    let db = helper::get_db()?;
+    let author = helper::get_current_user(&db).await?;
+    challenge::verify(author.as_ref(), &challenge_response, &db).await?;
+    helper::create_thread_as(
+        author,
+        forum_id,
+        subject,
+        post_content,
+        &attachment_ids,
+        &db,
+    )
+    .await
+}
+
+/// Fetches the latest `num` [`Posts`][Post] from the database in id-descending order,
+/// each with its author
+///
+/// Leaves out the forums the logged-in [`User`][user::User] hid,
+/// see [`Preferences::hidden_forum_ids`][user::Preferences::hidden_forum_ids],
+/// those they can't see, see [`Forum::read_role`], and staff-only ones,
+/// see [`get_latest_staff_posts`]
+#[server]
+pub async fn get_latest_posts(num: i64) -> Result<Vec<PostWithAuthor>, ApiError> {
+    use visibility::Listing;
+
+    let db = helper::get_db()?;
+    // tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    let mut filter = bson::doc! {"pending_review": {"$ne": true}, "thread_deleted": {"$ne": true}};
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    let forums = helper::get_all_forums(&db).await?;
+    let mut hidden_forum_ids: Vec<u32> = forums
+        .iter()
+        .filter(|forum| !visibility::is_listed(&forums, forum.id, user.as_ref(), Listing::Public))
+        .map(|forum| forum.id)
+        .collect();
+    hidden_forum_ids.extend(
+        user.map(|user| user.preferences.hidden_forum_ids)
+            .unwrap_or_default(),
+    );
+    if !hidden_forum_ids.is_empty() {
+        let mut hidden_thread_ids = vec![];
+        let mut thread_cursor = Thread::collection(&db)
+            .find(bson::doc! {"forum_id": {"$in": hidden_forum_ids}})
+            .await?;
+        while thread_cursor.advance().await? {
+            hidden_thread_ids.push(thread_cursor.deserialize_current()?.id);
+        }
+        filter.insert("thread_id", bson::doc! {"$nin": hidden_thread_ids});
+    }
+
+    // descending
+    helper::find_posts_with_authors(filter, bson::doc! {"id": -1}, Some(num), &db).await
+}
+
+/// Fetches the latest `num` [`Posts`][Post] in [staff-only][Forum::is_staff_only] forums
+/// in id-descending order, each with its author
+///
+/// They're left out of [`get_latest_posts`], so internal discussions aren't mixed with public ones
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::Forbidden`] if not a moderator
+#[server]
+pub async fn get_latest_staff_posts(num: i64) -> Result<Vec<PostWithAuthor>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_role(&db, user::Role::Moderator).await?;
+    let forums = helper::get_all_forums(&db).await?;
+    let forum_ids = visibility::listed_forum_ids(&forums, Some(&user), visibility::Listing::Staff);
+    let thread_ids = Thread::collection(&db)
+        .distinct("id", bson::doc! {"forum_id": {"$in": forum_ids}})
+        .await?;
+
+    helper::find_posts_with_authors(
+        bson::doc! {
+            "thread_id": {"$in": thread_ids},
+            "pending_review": {"$ne": true},
+            "thread_deleted": {"$ne": true},
+        },
+        // descending
+        bson::doc! {"id": -1},
+        Some(num),
+        &db,
+    )
+    .await
+}
+
+/// How many [`Threads`][Thread] [`search_threads`] returns at most
+pub const SEARCH_RESULTS: i64 = 10;
+
+/// Returns the newest [`Threads`][Thread] the user can see whose subject contains `query`,
+/// ignoring case, at most [`SEARCH_RESULTS`]
+///
+/// Staff-only forums aren't searched, see [`visibility`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptySearchQuery`] if `query` is blank
+/// - [`ApiError::LoginRequired`] if guests can't see threads
+#[server]
+pub async fn search_threads(query: String) -> Result<Vec<Thread>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(ApiError::EmptySearchQuery);
+    }
+
+    let forums = helper::get_all_forums(&db).await?;
+    let forum_ids =
+        visibility::listed_forum_ids(&forums, user.as_ref(), visibility::Listing::Public);
+    let mut cursor = Thread::collection(&db)
+        .find(bson::doc! {
+            "forum_id": {"$in": forum_ids},
+            "subject": {"$regex": helper::escape_regex(query), "$options": "i"},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .sort(bson::doc! {"id": -1})
+        .limit(SEARCH_RESULTS)
+        .await?;
+    let mut threads = vec![];
+    while cursor.advance().await? {
+        threads.push(cursor.deserialize_current()?);
     }
-    if post_content.is_empty() {
-        return Err(ApiError::EmptyContent);
+    Ok(threads)
+}
+
+/// Amount of all visible [`Threads`][Thread] and [`Posts`][Post] on the forum,
+/// used for the "new since last visit" badges in the navbar
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivitySummary {
+    pub thread_count: u64,
+    pub post_count: u64,
+}
+
+/// Returns the [`ActivitySummary`] of the whole forum, cached
+#[server]
+pub async fn get_activity_summary() -> Result<ActivitySummary, ApiError> {
+    let db = helper::get_db()?;
+    match use_context::<cache::Caches>() {
+        Some(caches) => caches.activity_summary(&db).await,
+        None => helper::count_activity(&db, false).await,
     }
+}
 
+/// Fetches a certain thread's [`Posts`][Post] from the databse in id-ascending order,
+/// each with its author
+#[server]
+pub async fn get_posts_from_thread(thread_id: u32) -> Result<Vec<PostWithAuthor>, ApiError> {
     let db = helper::get_db()?;
+    // tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    if let Ok(thread) = helper::get_thread(thread_id, db.clone()).await {
+        if helper::ensure_can_see_forum(thread.forum_id, user.as_ref(), &db)
+            .await
+            .is_err()
+        {
+            return Err(ApiError::NotFound("thread".into(), thread_id));
+        }
+    }
+    helper::find_posts_with_authors(
+        bson::doc! {"thread_id": thread_id, "pending_review": {"$ne": true}},
+        // ascending
+        bson::doc! {"id": 1},
+        None,
+        &db,
+    )
+    .await
+}
 
-    let _ = helper::get_forum(forum_id, db.clone()).await?;
+/// How many posts are loaded on each side of a linked [`Post`] at first,
+/// and how many more every time the [`PostWindow`] is expanded
+pub const POST_WINDOW_RADIUS: u32 = 10;
+/// Most posts loaded on one side of a [`PostWindow`]
+pub const MAX_POST_WINDOW_RADIUS: u32 = 500;
 
-    let counter_col = Counter::collection(&db);
-    let thread_id = helper::get_and_increment_id_of("thread", counter_col.clone()).await?;
+/// A contiguous part of the posts of a [`Thread`], see [`get_posts_around`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct PostWindow {
+    /// In id-ascending order
+    pub posts: Vec<PostWithAuthor>,
+    /// Whether there are posts before the first one
+    pub has_earlier: bool,
+    /// Whether there are posts after the last one
+    pub has_later: bool,
+}
 
-    let post_col = Post::collection(&db);
-    let post_id = helper::get_and_increment_id_of("post", counter_col).await?;
-    let new_post = Post {
-        id: post_id,
-        content: post_content,
-        created_at: jiff::Timestamp::now(),
+/// Fetches the [`Post`] with `post_id` with up to `before` posts before and up to `after`
+/// posts after it in the given [`Thread`], each with its author
+///
+/// Used for links to a post in a long thread, so it's shown without loading every post first.
+/// Both sides are capped at [`MAX_POST_WINDOW_RADIUS`]
+#[server]
+pub async fn get_posts_around(
+    thread_id: u32,
+    post_id: u32,
+    before: u32,
+    after: u32,
+) -> Result<PostWindow, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if helper::ensure_can_see_forum(thread.forum_id, user.as_ref(), &db)
+        .await
+        .is_err()
+    {
+        return Err(ApiError::NotFound("thread".into(), thread_id));
+    }
+    let before = before.min(MAX_POST_WINDOW_RADIUS);
+    let after = after.min(MAX_POST_WINDOW_RADIUS);
+    let visible = bson::doc! {"thread_id": thread_id, "pending_review": {"$ne": true}};
+
+    // one more than asked for on each side tells if there are further posts
+    let mut filter = visible.clone();
+    filter.insert("id", bson::doc! {"$lt": post_id});
+    // descending, so the closest ones are taken
+    let mut earlier = helper::find_posts_with_authors(
+        filter,
+        bson::doc! {"id": -1},
+        Some(i64::from(before) + 1),
+        &db,
+    )
+    .await?;
+    let before = usize::try_from(before).unwrap_or(usize::MAX);
+    let has_earlier = earlier.len() > before;
+    earlier.truncate(before);
+    earlier.reverse();
+
+    // the post itself is the first one
+    let mut filter = visible;
+    filter.insert("id", bson::doc! {"$gte": post_id});
+    let mut later = helper::find_posts_with_authors(
+        filter,
+        bson::doc! {"id": 1},
+        Some(i64::from(after) + 2),
+        &db,
+    )
+    .await?;
+    let after = usize::try_from(after).unwrap_or(usize::MAX);
+    let has_later = later.len() > after + 1;
+    later.truncate(after + 1);
+
+    earlier.append(&mut later);
+    Ok(PostWindow {
+        posts: earlier,
+        has_earlier,
+        has_later,
+    })
+}
+
+/// Where a [`Post`] is shown, see [`get_post_location`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct PostLocation {
+    pub thread_id: u32,
+    /// 1-based position among the visible posts of the thread
+    pub position: u64,
+}
+
+/// Finds the [`Thread`] a [`Post`] is in and its position there, used for permalinks
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` isn't in use or the post is hidden,
+///   i.e. held for review or in a deleted thread (for non-moderators)
+#[server]
+pub async fn get_post_location(post_id: u32) -> Result<PostLocation, ApiError> {
+    let db = helper::get_db()?;
+    let post = helper::get_post(post_id, db.clone()).await?;
+    if post.pending_review {
+        return Err(ApiError::NotFound("post".into(), post_id));
+    }
+    let thread = match get_thread(post.thread_id).await {
+        Err(ApiError::NotFound(..)) => return Err(ApiError::NotFound("post".into(), post_id)),
+        result => result?,
+    };
+
+    let position = Post::collection(&db)
+        .count_documents(bson::doc! {
+            "thread_id": thread.id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+            "id": {"$lte": post_id},
+        })
+        .await?;
+    Ok(PostLocation {
+        thread_id: thread.id,
+        position,
+    })
+}
+
+/// Renders `content` like [`markdown::render`][crate::markdown::render] does,
+/// for previewing it in the composer before submitting
+#[server]
+#[allow(clippy::unused_async, reason = "server functions have to be async")]
+pub async fn render_markdown_preview(content: String) -> Result<String, ApiError> {
+    Ok(crate::markdown::render(&content))
+}
+
+/// Creates a post in the given [`Thread`], optionally quoting the post `quote_post_id`
+///
+/// Returns the created post, so clients can show it without fetching the whole thread again
+///
+/// # Errors
+///
+/// - [`ApiError::ChallengeFailed`] if a guest's `challenge_response` doesn't pass the [`challenge`]
+/// - [`ApiError::Validation`] if `content` is empty or too long, see [`validation`]
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use, or `quote_post_id` isn't or can't be seen
+/// - [`ApiError::NotFound`] / [`ApiError::TooManyAttachments`] if the
+///   [`attachments`] can't be claimed, see [`attachments::claim`]
+/// - [`ApiError::ThreadLocked`] if the [`Thread`] is locked
+/// - [`ApiError::ForumReadOnly`] if the [`Forum`] is [read-only][Forum::read_only]
+/// - [`ApiError::PostingRequirementsNotMet`] if the logged-in [`User`][user::User]
+///   is too new to post in the [`Forum`]
+/// - [`ApiError::Banned`] or [`ApiError::Muted`] if the logged-in [`User`][user::User]
+///   is currently sanctioned
+/// - [`ApiError::ContentBlocked`] if the [`word_filter`] rejects `content`
+/// - [`ApiError::HeldForReview`] if the [`word_filter`] or the [`spam`] heuristics hold it
+///   for review (the post is saved though)
+#[server]
+pub async fn create_post(
+    thread_id: u32,
+    content: String,
+    quote_post_id: Option<u32>,
+    #[server(default)] attachment_ids: Vec<u32>,
+    #[server(default)] challenge_response: String,
+) -> Result<PostWithAuthor, ApiError> {
+    let db = helper::get_db()?;
+    let author = helper::get_current_user(&db).await?;
+    challenge::verify(author.as_ref(), &challenge_response, &db).await?;
+    let post_id = helper::create_post_as(
+        author,
         thread_id,
+        content,
+        quote_post_id,
+        &attachment_ids,
+        &db,
+    )
+    .await?;
+
+    helper::find_posts_with_authors(bson::doc! {"id": post_id}, bson::doc! {}, Some(1), &db)
+        .await?
+        .pop()
+        .ok_or(ApiError::NotFound("post".into(), post_id))
+}
+
+/// Replaces the content of the given [`Post`], keeping the old one as a [`PostRevision`]
+///
+/// Only the author, moderators and for [wiki posts][Thread::wiki_post] its editors may edit a post.
+/// The origin post can also be edited by the [`Thread::co_author_ids`].
+/// Authors can only edit within the
+/// [edit window][site_settings::SiteSettings::edit_window_minutes], collaborators anytime.
+/// Moderators can edit any post anytime, but edits of someone else's post or after the window
+/// are [staff edits][Post::staff_edit] and need a `reason`, which is ignored otherwise.
+/// [`Quotes`][Quote] of it are marked as [`Quote::source_edited`]
+///
+/// # Errors
+///
+/// - [`ApiError::Validation`] if `content` is empty or too long, see [`validation`]
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::EditWindowExpired`] if the author edits after the edit window
+/// - [`ApiError::EditReasonRequired`] / [`ApiError::EditReasonTooLong`] if the `reason`
+///   of a staff edit is empty or longer than [`MAX_EDIT_REASON_LEN`]
+/// - [`ApiError::Banned`] or [`ApiError::Muted`] if the logged-in [`User`][user::User]
+///   is currently sanctioned
+/// - [`ApiError::ContentBlocked`] if the [`word_filter`] rejects `content`
+/// - [`ApiError::HeldForReview`] if the [`word_filter`] holds `content` for review
+///   (the edit is saved though)
+#[server]
+pub async fn edit_post(
+    post_id: u32,
+    content: String,
+    #[server(default)] reason: String,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let settings = site_settings::load_settings(&db).await?;
+    let content = validation::validate(&content, Field::Content, &settings.length_limits)?;
+    let user = helper::require_user(&db).await?;
+    helper::ensure_can_post(Some(&user))?;
+
+    let post = helper::get_post(post_id, db.clone()).await?;
+    let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+    let edit_window = settings.edit_window_minutes;
+    let now = jiff::Timestamp::now();
+    let window_expired = edit_window.is_some_and(|minutes| {
+        now.duration_since(post.created_at) > jiff::SignedDuration::from_mins(i64::from(minutes))
+    });
+    let may_edit = thread.can_edit_as_collaborator(post_id, &user)
+        || (post.author_id == Some(user.id) && !window_expired);
+
+    let edit_reason = if may_edit {
+        None
+    } else if user.role >= user::Role::Moderator {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(ApiError::EditReasonRequired);
+        }
+        if reason.chars().count() > MAX_EDIT_REASON_LEN {
+            return Err(ApiError::EditReasonTooLong);
+        }
+        Some(reason.to_string())
+    } else {
+        return Err(match edit_window {
+            Some(minutes) if post.author_id == Some(user.id) => {
+                ApiError::EditWindowExpired(minutes)
+            }
+            _ => ApiError::Forbidden,
+        });
     };
-    post_col.insert_one(&new_post).await?;
 
-    let thread_col = Thread::collection(&db);
-    let new_thread = Thread {
-        id: thread_id,
-        origin_post_id: post_id,
-        subject,
-        forum_id,
-        latest_post_id: post_id,
+    let rules = word_filter::load_rules(&db).await?;
+    let (content, pending_review) = helper::run_word_filter(&rules, &content)?;
+    let pending_review = pending_review
+        && !groups::has_permission(Some(&user), groups::GroupPermission::SkipReview, &db).await?;
+
+    let old = PostRevision {
+        post_id,
+        revision: post.revision,
+        content: post.content,
+        edited_by: post.edited_by,
+        staff_edit: post.staff_edit,
+        edit_reason: post.edit_reason,
+        replaced_at: now,
     };
-    thread_col.insert_one(&new_thread).await?;
+    PostRevision::collection(&db).insert_one(&old).await?;
 
-    let category_col = Category::collection(&db);
-    // thx gippity?
-    let _ = category_col
+    thread_references::record(post_id, post.thread_id, &content, &db).await?;
+
+    let post_col = Post::collection(&db);
+    post_col
         .update_one(
-            bson::doc! {"forums.id": forum_id},
-            bson::doc! {"$set": {"forums.$.latest_thread_id": thread_id}},
+            bson::doc! {"id": post_id},
+            bson::doc! {
+                "$set": {
+                    "content_hash": spam::content_hash(&content),
+                    "content": content,
+                    "revision": post.revision + 1,
+                    "edited_at": bson::DateTime::from_millis(now.as_millisecond()),
+                    "edited_by": user.id,
+                    "staff_edit": edit_reason.is_some(),
+                    "edit_reason": edit_reason.as_deref(),
+                    "pending_review": pending_review,
+                }
+            },
+        )
+        .await?;
+    post_col
+        .update_many(
+            bson::doc! {"quote.post_id": post_id},
+            bson::doc! {"$set": {"quote.source_edited": true}},
         )
         .await?;
 
-    Ok(thread_id)
+    // held posts don't count
+    if pending_review != post.pending_review {
+        cache::invalidate_forums(&[thread.forum_id], &db).await?;
+    }
+    events::emit(
+        events::DomainEvent::PostEdited {
+            post_id,
+            editor_id: user.id,
+            reason: edit_reason.as_deref(),
+        },
+        &db,
+    );
+
+    if pending_review {
+        return Err(ApiError::HeldForReview);
+    }
+    Ok(())
 }
 
-/// Fetches the latest `num` [`Posts`][Post] from the database in id-descending order
+/// Fetches the previous versions of the given [`Post`], newest first
+///
+/// Only available for [wiki posts][Thread::wiki_post] and to the author and moderators
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+/// - [`ApiError::Forbidden`] if not permitted
 #[server]
-pub async fn get_latest_posts(num: i64) -> Result<Vec<Post>, ApiError> {
+pub async fn get_post_revisions(post_id: u32) -> Result<Vec<PostRevision>, ApiError> {
     let db = helper::get_db()?;
-    // tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-    let post_col = Post::collection(&db);
-    let mut posts = vec![];
-    let mut post_cursor = post_col
-        .find(bson::doc! {})
-        // descending
-        .sort(bson::doc! {"id":-1})
-        .limit(num)
+    helper::get_reader(GuestCapability::Threads, &db).await?;
+    let post = helper::get_post(post_id, db.clone()).await?;
+    let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+
+    let is_wiki_post = thread.wiki_post && thread.origin_post_id == post_id;
+    if !is_wiki_post {
+        let user = helper::require_user(&db).await?;
+        if post.author_id != Some(user.id) && user.role < user::Role::Moderator {
+            return Err(ApiError::Forbidden);
+        }
+    }
+
+    let mut cursor = PostRevision::collection(&db)
+        .find(bson::doc! {"post_id": post_id})
+        .sort(bson::doc! {"revision": -1})
         .await?;
-    while post_cursor.advance().await? {
-        posts.push(post_cursor.deserialize_current()?);
+    let mut revisions = vec![];
+    while cursor.advance().await? {
+        revisions.push(cursor.deserialize_current()?);
     }
-    Ok(posts)
+
+    Ok(revisions)
 }
 
-/// Fetches a certain thread's [`Posts`][Post] from the databse in id-ascending order
+/// Turns the origin post of a [`Thread`] into a [wiki post][Thread::wiki_post] or back,
+/// editable by the users in the comma-separated `editor_names`
+///
+/// Only the thread author and moderators may do this
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::UnknownUsername`] if one of the `editor_names` isn't in use
 #[server]
-pub async fn get_posts_from_thread(thread_id: u32) -> Result<Vec<Post>, ApiError> {
+pub async fn set_wiki_post(
+    thread_id: u32,
+    #[server(default)] wiki_post: bool,
+    #[server(default)] editor_names: String,
+) -> Result<(), ApiError> {
     let db = helper::get_db()?;
-    // tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-    let post_col = Post::collection(&db);
-    let mut posts = vec![];
-    let mut post_cursor = post_col
-        .find(bson::doc! {"thread_id": thread_id})
-        // ascending
-        .sort(bson::doc! {"id": 1})
-        .await?;
-    while post_cursor.advance().await? {
-        posts.push(post_cursor.deserialize_current()?);
+    let user = helper::require_user(&db).await?;
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if thread.author_id != Some(user.id) && user.role < user::Role::Moderator {
+        return Err(ApiError::Forbidden);
     }
-    Ok(posts)
+
+    let editor_ids = helper::get_user_ids_by_names(&editor_names, &db).await?;
+    Thread::collection(&db)
+        .update_one(
+            bson::doc! {"id": thread_id},
+            bson::doc! {"$set": {"wiki_post": wiki_post, "wiki_editor_ids": editor_ids}},
+        )
+        .await?;
+
+    Ok(())
 }
 
-/// Creates a post in the given [`Thread`]
+/// Replaces the [`Thread::co_author_ids`] with the users in the comma-separated `co_author_names`,
+/// who may then edit the origin post and subject, see [`rename_thread`]
+///
+/// Only the thread author and moderators may do this
 ///
 /// # Errors
 ///
-/// - [`ApiError::EmptyContent`] if `content` is empty
-/// - [`ApiError::NotFound`] if `thread_id` isn't in use
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::UnknownUsername`] if one of the `co_author_names` isn't in use
 #[server]
-pub async fn create_post(thread_id: u32, content: String) -> Result<(), ApiError> {
-    if content.is_empty() {
-        return Err(ApiError::EmptyContent);
+pub async fn set_thread_co_authors(
+    thread_id: u32,
+    #[server(default)] co_author_names: String,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if thread.author_id != Some(user.id) && user.role < user::Role::Moderator {
+        return Err(ApiError::Forbidden);
     }
 
-    let db = helper::get_db()?;
+    let mut co_author_ids = helper::get_user_ids_by_names(&co_author_names, &db).await?;
+    // the author can edit anyway
+    co_author_ids.retain(|id| Some(*id) != thread.author_id);
+    Thread::collection(&db)
+        .update_one(
+            bson::doc! {"id": thread_id},
+            bson::doc! {"$set": {"co_author_ids": co_author_ids}},
+        )
+        .await?;
 
+    Ok(())
+}
+
+/// Changes the subject of a [`Thread`]
+///
+/// Only the author, its [co-authors][Thread::co_author_ids] and moderators may do this
+///
+/// # Errors
+///
+/// - [`ApiError::Validation`] if `subject` is empty or too long, see [`validation`]
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::Banned`] or [`ApiError::Muted`] if the logged-in [`User`][user::User]
+///   is currently sanctioned
+/// - [`ApiError::ContentBlocked`] if the [`word_filter`] rejects `subject`
+/// - [`ApiError::HeldForReview`] if the [`word_filter`] holds `subject` for review,
+///   which hides the whole thread until it's approved (the subject is saved though)
+#[server]
+pub async fn rename_thread(thread_id: u32, subject: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let limits = site_settings::load_settings(&db).await?.length_limits;
+    let subject = validation::validate(&subject, Field::Subject, &limits)?;
+    let user = helper::require_user(&db).await?;
+    helper::ensure_can_post(Some(&user))?;
     let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if thread.author_id != Some(user.id)
+        && user.role < user::Role::Moderator
+        && !thread.is_co_author(&user)
+    {
+        return Err(ApiError::Forbidden);
+    }
 
-    let counter_col = Counter::collection(&db);
-    let id = helper::get_and_increment_id_of("post", counter_col).await?;
+    let rules = word_filter::load_rules(&db).await?;
+    let (subject, held) = helper::run_word_filter(&rules, &subject)?;
+    let held = held
+        && !groups::has_permission(Some(&user), groups::GroupPermission::SkipReview, &db).await?;
+    let pending_review = thread.pending_review || held;
 
-    let post_col = Post::collection(&db);
+    Thread::collection(&db)
+        .update_one(
+            bson::doc! {"id": thread_id},
+            bson::doc! {"$set": {"subject": subject, "pending_review": pending_review}},
+        )
+        .await?;
 
-    let new_post = Post {
-        id,
-        content,
+    // held threads don't count
+    if pending_review != thread.pending_review {
+        cache::invalidate_forums(&[thread.forum_id], &db).await?;
+    }
+
+    if held {
+        return Err(ApiError::HeldForReview);
+    }
+    Ok(())
+}
+
+/// Thanks the author of the given [`Post`], increasing both the post's
+/// and the author's thanks counter
+///
+/// Returns the new thanks count of the post
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::Forbidden`] if the post has no author or it's your own post
+/// - [`ApiError::AlreadyThanked`] if you already thanked this post
+#[server]
+pub async fn thank_post(post_id: u32) -> Result<u32, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let post = helper::get_post(post_id, db.clone()).await?;
+    let Some(author_id) = post.author_id else {
+        return Err(ApiError::Forbidden);
+    };
+    if author_id == user.id {
+        return Err(ApiError::Forbidden);
+    }
+
+    let thanks_col = Thanks::collection(&db);
+    if thanks_col
+        .find_one(bson::doc! {"post_id": post_id, "from_user_id": user.id})
+        .await?
+        .is_some()
+    {
+        return Err(ApiError::AlreadyThanked);
+    }
+
+    let thanks = Thanks {
+        post_id,
+        from_user_id: user.id,
+        to_user_id: author_id,
         created_at: jiff::Timestamp::now(),
-        thread_id,
     };
+    thanks_col.insert_one(&thanks).await?;
 
-    post_col.insert_one(&new_post).await?;
+    Post::collection(&db)
+        .update_one(
+            bson::doc! {"id": post_id},
+            bson::doc! {"$inc": {"thanks": 1}},
+        )
+        .await?;
+    user::User::collection(&db)
+        .update_one(
+            bson::doc! {"id": author_id},
+            bson::doc! {"$inc": {"thanks_received": 1}},
+        )
+        .await?;
 
-    let thread_col = Thread::collection(&db);
-    let _ = thread_col
+    Ok(post.thanks + 1)
+}
+
+/// Marks a [`Thread`] as solved or unsolved
+///
+/// Only the thread author and moderators can do this,
+/// and only in forums with [`Forum::solved_workflow`] enabled
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::SolvingDisabled`] if the forum doesn't use the solved workflow
+/// - [`ApiError::ThreadLocked`] if the thread is locked
+#[server]
+pub async fn set_thread_solved(thread_id: u32, solved: bool) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if thread.author_id != Some(user.id) && user.role < user::Role::Moderator {
+        return Err(ApiError::Forbidden);
+    }
+
+    let (forum, _) = helper::get_forum(thread.forum_id, db.clone()).await?;
+    if !forum.solved_workflow {
+        return Err(ApiError::SolvingDisabled);
+    }
+    // moderators can still unsolve auto-locked threads
+    if thread.is_locked(&forum, jiff::Timestamp::now()) && user.role < user::Role::Moderator {
+        return Err(ApiError::ThreadLocked);
+    }
+
+    let solved_at =
+        solved.then(|| bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond()));
+    Thread::collection(&db)
         .update_one(
             bson::doc! {"id": thread_id},
-            bson::doc! {"$set": {"latest_post_id": id}},
+            bson::doc! {"$set": {"solved_at": solved_at}},
         )
         .await?;
+    events::emit(
+        events::DomainEvent::ThreadSolved {
+            thread_id,
+            solved,
+            user_id: user.id,
+        },
+        &db,
+    );
 
-    let category_col = Category::collection(&db);
-    let _ = category_col
+    Ok(())
+}
+
+/// Updates the solved workflow settings of a [`Forum`]
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+#[server]
+pub async fn update_forum_solved_workflow(
+    forum_id: u32,
+    #[server(default)] solved_workflow: bool,
+    lock_solved_after_hours: Option<u32>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+    let _ = helper::get_forum(forum_id, db.clone()).await?;
+
+    Category::collection(&db)
         .update_one(
-            bson::doc! {"forums.id": thread.forum_id},
-            bson::doc! {"$set": {"forums.$.latest_thread_id": thread_id}},
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {
+                "forums.$.solved_workflow": solved_workflow,
+                "forums.$.lock_solved_after_hours": lock_solved_after_hours,
+            }},
         )
         .await?;
 
     Ok(())
 }
 
-/// Returns the number of [`Thread`]s and [`Post`]s in the given [`Forum`]
+/// Updates the description and icon of a [`Forum`], empty values remove them
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+///
+/// # Errors
+///
+/// - [`ApiError::ForumDescriptionTooLong`] if `description` is longer than [`MAX_FORUM_DESCRIPTION_LEN`]
+/// - [`ApiError::ForumIconTooLong`] if `icon` is longer than [`MAX_FORUM_ICON_LEN`]
+#[server]
+pub async fn update_forum_metadata(
+    forum_id: u32,
+    description: Option<String>,
+    icon: Option<String>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+    let _ = helper::get_forum(forum_id, db.clone()).await?;
+
+    // empty form fields arrive as empty strings
+    let non_empty = |field: Option<String>| {
+        field
+            .map(|field| field.trim().to_string())
+            .filter(|field| !field.is_empty())
+    };
+    let (description, icon) = (non_empty(description), non_empty(icon));
+    if description
+        .as_ref()
+        .is_some_and(|description| description.chars().count() > MAX_FORUM_DESCRIPTION_LEN)
+    {
+        return Err(ApiError::ForumDescriptionTooLong);
+    }
+    if icon
+        .as_ref()
+        .is_some_and(|icon| icon.chars().count() > MAX_FORUM_ICON_LEN)
+    {
+        return Err(ApiError::ForumIconTooLong);
+    }
+
+    Category::collection(&db)
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {
+                "forums.$.description": description,
+                "forums.$.icon": icon,
+            }},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Updates the description of the [`Category`] named `category`, an empty value removes it
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+///
+/// # Errors
+///
+/// - [`ApiError::UnknownCategory`] if there is no category named `category`
+/// - [`ApiError::CategoryDescriptionTooLong`] if `description` is longer than [`MAX_CATEGORY_DESCRIPTION_LEN`]
+#[server]
+pub async fn update_category_description(
+    category: String,
+    description: Option<String>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+
+    // empty form fields arrive as empty strings
+    let description = description
+        .map(|description| description.trim().to_string())
+        .filter(|description| !description.is_empty());
+    if description
+        .as_ref()
+        .is_some_and(|description| description.chars().count() > MAX_CATEGORY_DESCRIPTION_LEN)
+    {
+        return Err(ApiError::CategoryDescriptionTooLong);
+    }
+
+    let result = Category::collection(&db)
+        .update_one(
+            bson::doc! {"name": &category},
+            bson::doc! {"$set": {"description": description}},
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(ApiError::UnknownCategory(category));
+    }
+
+    Ok(())
+}
+
+/// Moves a [`Forum`] below `parent_forum_id`, or to the top level if [`None`]
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if one of the forums doesn't exist
+/// - [`ApiError::InvalidParentForum`] if the parent is in another [`Category`],
+///   the forum itself or one of its subforums
+#[server]
+pub async fn update_forum_parent(
+    forum_id: u32,
+    parent_forum_id: Option<u32>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+    let (forum, category_name) = helper::get_forum(forum_id, db.clone()).await?;
+
+    if let Some(parent_forum_id) = parent_forum_id {
+        let (_, parent_category_name) = helper::get_forum(parent_forum_id, db.clone()).await?;
+        let forums = helper::get_all_forums(&db).await?;
+        // also prevents cycles
+        if parent_category_name != category_name
+            || helper::forum_subtree_ids(&forums, forum_id).contains(&parent_forum_id)
+        {
+            return Err(ApiError::InvalidParentForum);
+        }
+    }
+
+    Category::collection(&db)
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {"forums.$.parent_forum_id": parent_forum_id}},
+        )
+        .await?;
+
+    // the counts of the old and new parents change
+    let mut affected = vec![forum_id];
+    affected.extend(forum.parent_forum_id);
+    affected.extend(parent_forum_id);
+    cache::invalidate_forums(&affected, &db).await
+}
+
+/// Updates who can see and post in a [`Forum`], see [`Forum::read_role`] and [`Forum::read_only`]
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+#[server]
+pub async fn update_forum_permissions(
+    forum_id: u32,
+    read_role: Option<user::Role>,
+    #[server(default)] read_only: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+    let _ = helper::get_forum(forum_id, db.clone()).await?;
+
+    let Ok(read_role) = bson::to_bson(&read_role) else {
+        unreachable!("a Role is always serializable")
+    };
+    Category::collection(&db)
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {
+                "forums.$.read_role": read_role,
+                "forums.$.read_only": read_only,
+            }},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Updates the posting requirements for new members of a [`Forum`]
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+#[server]
+pub async fn update_forum_posting_requirements(
+    forum_id: u32,
+    min_account_age_hours: Option<u32>,
+    min_post_count: Option<u32>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+    let _ = helper::get_forum(forum_id, db.clone()).await?;
+
+    Category::collection(&db)
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {
+                "forums.$.min_account_age_hours": min_account_age_hours,
+                "forums.$.min_post_count": min_post_count,
+            }},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Updates the activity caps of a [`Forum`], see [`Forum::max_threads_per_day`]
+/// and [`Forum::max_posts_per_hour`]
+///
+/// Caps of 0 are the same as none, read-only forums are for keeping users from posting
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+#[server]
+pub async fn update_forum_activity_caps(
+    forum_id: u32,
+    max_threads_per_day: Option<u32>,
+    max_posts_per_hour: Option<u32>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+    let _ = helper::get_forum(forum_id, db.clone()).await?;
+
+    Category::collection(&db)
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {
+                "forums.$.max_threads_per_day": max_threads_per_day.filter(|&max| max > 0),
+                "forums.$.max_posts_per_hour": max_posts_per_hour.filter(|&max| max > 0),
+            }},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the number of [`Thread`]s and [`Post`]s in the given [`Forum`] and its subforums
 ///
 /// First value is the [`Thread`] count, second value is the [`Post`] count
 #[server]
 pub async fn count_threads_and_posts_of_forum(forum_id: u32) -> Result<(u64, u64), ApiError> {
     let db = helper::get_db()?;
     // tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-    helper::count_threads_and_posts_of_forum(forum_id, db).await
+    match use_context::<cache::Caches>() {
+        Some(caches) => caches.forum_summary(forum_id, &db).await,
+        None => helper::count_threads_and_posts_of_forum(forum_id, db, false).await,
+    }
 }
 
 /// Returns the most recently created [`Post`] with the [`Thread`] it's in
 /// of the given `thread_id`
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use or the thread isn't visible,
+///   see [`helper::get_visible_thread`]
 #[server]
 pub async fn get_latest_post_and_thread(thread_id: u32) -> Result<(Post, Thread), ApiError> {
     let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
 
-    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    let thread = helper::get_visible_thread(thread_id, user.as_ref(), &db).await?;
     let post = helper::get_post(thread.latest_post_id, db.clone()).await?;
 
     Ok((post, thread))
@@ -446,4 +2266,39 @@ pub mod jiff_timestamp_as_bson_datetime {
         let datetime = bson::DateTime::from_millis(val.as_millisecond());
         datetime.serialize(serializer)
     }
+
+    /// Same as the parent module, but for optional [`jiff::Timestamp`]s
+    ///
+    /// Use together with `#[serde(default)]` so that missing fields deserialize to [`None`]
+    pub mod optional {
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        /// Deserializes an optional [`jiff::Timestamp`] from an optional [`bson::DateTime`].
+        #[allow(clippy::missing_errors_doc)]
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<jiff::Timestamp>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let datetime = Option::<bson::DateTime>::deserialize(deserializer)?;
+            Ok(datetime.map(|datetime| {
+                let Ok(timestamp) = jiff::Timestamp::from_millisecond(datetime.timestamp_millis())
+                else {
+                    unreachable!(
+                        "a bson DateTime in ms shouldn't be out of range for jiff timestamp creation"
+                    )
+                };
+                timestamp
+            }))
+        }
+
+        /// Serializes an optional [`jiff::Timestamp`] as an optional [`bson::DateTime`].
+        #[allow(clippy::missing_errors_doc, clippy::ref_option)]
+        pub fn serialize<S: Serializer>(
+            val: &Option<jiff::Timestamp>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            val.map(|val| bson::DateTime::from_millis(val.as_millisecond()))
+                .serialize(serializer)
+        }
+    }
 }
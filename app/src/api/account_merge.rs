@@ -0,0 +1,307 @@
+//! Admin tool for merging a duplicate [`User`] account into the primary one,
+//! e.g. after an import or when someone registered twice
+//!
+//! Everything the duplicate wrote or received is reassigned to the primary account.
+//! The duplicate itself is kept so that old references still resolve,
+//! but it can't log in anymore and points to the primary with [`User::merged_into`]
+
+use super::ApiError;
+use super::user::User;
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, Post, PostRevision, Thanks, Thread,
+    attachments::Attachment,
+    bson, helper,
+    my_content::WatchedThread,
+    reactions::Reaction,
+    subscriptions::WatchedForum,
+    user::{self, Role},
+    wiki::WikiRevision,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The result of [`merge_accounts`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MergeReport {
+    pub duplicate: User,
+    pub primary: User,
+    /// Number of posts written by the duplicate
+    pub post_count: u64,
+    /// Number of threads started by the duplicate
+    pub thread_count: u64,
+    /// Number of thanks given and received by the duplicate
+    pub thanks_count: u64,
+    /// `false` for dry runs
+    pub applied: bool,
+}
+
+/// Finds the [`User`] called `name`
+#[cfg(feature = "ssr")]
+async fn find_by_name(name: &str, db: &mongodb::Database) -> Result<User, ApiError> {
+    let name = name.trim();
+    User::collection(db)
+        .find_one(bson::doc! {"name": name})
+        .await?
+        .ok_or_else(|| ApiError::UnknownUsername(name.to_string()))
+}
+
+/// Reassigns all content, thanks and groups of `duplicate` to `primary`
+#[cfg(feature = "ssr")]
+async fn reassign(
+    duplicate: &User,
+    primary: &User,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let (from, to) = (duplicate.id, primary.id);
+
+    let post_col = Post::collection(db);
+    post_col
+        .update_many(
+            bson::doc! {"author_id": from},
+            bson::doc! {"$set": {"author_id": to}},
+        )
+        .await?;
+    post_col
+        .update_many(
+            bson::doc! {"edited_by": from},
+            bson::doc! {"$set": {"edited_by": to}},
+        )
+        .await?;
+    PostRevision::collection(db)
+        .update_many(
+            bson::doc! {"edited_by": from},
+            bson::doc! {"$set": {"edited_by": to}},
+        )
+        .await?;
+
+    let thread_col = Thread::collection(db);
+    thread_col
+        .update_many(
+            bson::doc! {"author_id": from},
+            bson::doc! {"$set": {"author_id": to}},
+        )
+        .await?;
+    // can't $addToSet and $pull the same array in one update
+    thread_col
+        .update_many(
+            bson::doc! {"wiki_editor_ids": from},
+            bson::doc! {"$addToSet": {"wiki_editor_ids": to}},
+        )
+        .await?;
+    thread_col
+        .update_many(
+            bson::doc! {"wiki_editor_ids": from},
+            bson::doc! {"$pull": {"wiki_editor_ids": from}},
+        )
+        .await?;
+    thread_col
+        .update_many(
+            bson::doc! {"co_author_ids": from},
+            bson::doc! {"$addToSet": {"co_author_ids": to}},
+        )
+        .await?;
+    thread_col
+        .update_many(
+            bson::doc! {"co_author_ids": from},
+            bson::doc! {"$pull": {"co_author_ids": from}},
+        )
+        .await?;
+
+    let thanks_col = Thanks::collection(db);
+    // every post can only be thanked once per user
+    let already_thanked = thanks_col
+        .distinct("post_id", bson::doc! {"from_user_id": to})
+        .await?;
+    let duplicate_thanks = bson::doc! {"from_user_id": from, "post_id": {"$in": already_thanked}};
+    // the dropped thanks don't count anymore, the duplicate's own ones are moved below
+    let mut dropped_of_duplicate = 0;
+    let mut cursor = thanks_col.find(duplicate_thanks.clone()).await?;
+    while cursor.advance().await? {
+        let thanks: Thanks = cursor.deserialize_current()?;
+        post_col
+            .update_one(
+                bson::doc! {"id": thanks.post_id},
+                bson::doc! {"$inc": {"thanks": -1}},
+            )
+            .await?;
+        if thanks.to_user_id == from {
+            dropped_of_duplicate += 1;
+        } else {
+            User::collection(db)
+                .update_one(
+                    bson::doc! {"id": thanks.to_user_id},
+                    bson::doc! {"$inc": {"thanks_received": -1}},
+                )
+                .await?;
+        }
+    }
+    thanks_col.delete_many(duplicate_thanks).await?;
+    thanks_col
+        .update_many(
+            bson::doc! {"from_user_id": from},
+            bson::doc! {"$set": {"from_user_id": to}},
+        )
+        .await?;
+    thanks_col
+        .update_many(
+            bson::doc! {"to_user_id": from},
+            bson::doc! {"$set": {"to_user_id": to}},
+        )
+        .await?;
+
+    let reaction_col = Reaction::collection(db);
+    // every post can only get each reaction once per user
+    let mut already_reacted = std::collections::HashMap::<u32, Vec<u32>>::new();
+    let mut cursor = reaction_col.find(bson::doc! {"user_id": to}).await?;
+    while cursor.advance().await? {
+        let reaction: Reaction = cursor.deserialize_current()?;
+        already_reacted
+            .entry(reaction.option_id)
+            .or_default()
+            .push(reaction.post_id);
+    }
+    for (option_id, post_ids) in already_reacted {
+        reaction_col
+            .delete_many(bson::doc! {
+                "user_id": from,
+                "option_id": option_id,
+                "post_id": {"$in": post_ids},
+            })
+            .await?;
+    }
+    reaction_col
+        .update_many(
+            bson::doc! {"user_id": from},
+            bson::doc! {"$set": {"user_id": to}},
+        )
+        .await?;
+
+    let watched_col = WatchedThread::collection(db);
+    // the primary's watches of threads and forums win, keeping their level
+    let already_watched = watched_col
+        .distinct("thread_id", bson::doc! {"user_id": to})
+        .await?;
+    watched_col
+        .delete_many(bson::doc! {"user_id": from, "thread_id": {"$in": already_watched}})
+        .await?;
+    watched_col
+        .update_many(
+            bson::doc! {"user_id": from},
+            bson::doc! {"$set": {"user_id": to}},
+        )
+        .await?;
+
+    let forum_watch_col = WatchedForum::collection(db);
+    let already_watched = forum_watch_col
+        .distinct("forum_id", bson::doc! {"user_id": to})
+        .await?;
+    forum_watch_col
+        .delete_many(bson::doc! {"user_id": from, "forum_id": {"$in": already_watched}})
+        .await?;
+    forum_watch_col
+        .update_many(
+            bson::doc! {"user_id": from},
+            bson::doc! {"$set": {"user_id": to}},
+        )
+        .await?;
+
+    WikiRevision::collection(db)
+        .update_many(
+            bson::doc! {"author_id": from},
+            bson::doc! {"$set": {"author_id": to}},
+        )
+        .await?;
+    Attachment::collection(db)
+        .update_many(
+            bson::doc! {"uploader_id": from},
+            bson::doc! {"$set": {"uploader_id": to}},
+        )
+        .await?;
+
+    let moved_thanks = duplicate
+        .thanks_received
+        .saturating_sub(dropped_of_duplicate);
+    let user_col = User::collection(db);
+    user_col
+        .update_one(
+            bson::doc! {"id": to},
+            bson::doc! {
+                "$inc": {"thanks_received": moved_thanks},
+                "$addToSet": {"group_ids": {"$each": &duplicate.group_ids}},
+            },
+        )
+        .await?;
+    user_col
+        .update_one(
+            bson::doc! {"id": from},
+            bson::doc! {"$set": {
+                "merged_into": to,
+                "thanks_received": 0,
+                "group_ids": [],
+                "primary_group_id": null,
+            }},
+        )
+        .await?;
+    user::disable_login(from, db).await
+}
+
+/// Merges the account called `duplicate_name` into the one called `primary_name`
+///
+/// Reassigns posts, threads, edits, thanks, wiki revisions, attachments and groups,
+/// then disables the duplicate. Only previews what would be reassigned unless `apply` is set
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::UnknownUsername`] if one of the names isn't in use
+/// - [`ApiError::MergeAccountIntoSelf`] if both are the same account
+/// - [`ApiError::AccountAlreadyMerged`] if one of them already got merged into another one
+#[server]
+pub async fn merge_accounts(
+    duplicate_name: String,
+    primary_name: String,
+    #[server(default)] apply: bool,
+) -> Result<MergeReport, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let duplicate = find_by_name(&duplicate_name, &db).await?;
+    let primary = find_by_name(&primary_name, &db).await?;
+    if duplicate.id == primary.id {
+        return Err(ApiError::MergeAccountIntoSelf);
+    }
+    if let Some(merged) = [&duplicate, &primary]
+        .into_iter()
+        .find(|user| user.merged_into.is_some())
+    {
+        return Err(ApiError::AccountAlreadyMerged(merged.name.clone()));
+    }
+
+    let by_author = bson::doc! {"author_id": duplicate.id};
+    let post_count = Post::collection(&db)
+        .count_documents(by_author.clone())
+        .await?;
+    let thread_count = Thread::collection(&db).count_documents(by_author).await?;
+    let thanks_count = Thanks::collection(&db)
+        .count_documents(bson::doc! {"$or": [
+            {"from_user_id": duplicate.id},
+            {"to_user_id": duplicate.id},
+        ]})
+        .await?;
+
+    if apply {
+        reassign(&duplicate, &primary, &db).await?;
+    }
+
+    Ok(MergeReport {
+        duplicate,
+        primary,
+        post_count,
+        thread_count,
+        thanks_count,
+        applied: apply,
+    })
+}
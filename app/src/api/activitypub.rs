@@ -0,0 +1,309 @@
+//! Read-only [ActivityPub](https://www.w3.org/TR/activitypub/) federation
+//!
+//! Every public [`Forum`] is a `Group` actor that fediverse software like Mastodon can look up
+//! via `WebFinger` as `forum-{id}@{host}`. Its outbox lists the newest [`Threads`][Thread],
+//! which are `Page` objects, while the replies in them are `Note` objects.
+//! Both state the [`ContentLicense`] of the posts as schema.org `license`
+//! and Dublin Core `rights`, if one is set.
+//!
+//! Only enabled if the `PUBLIC_URL` env var (e.g. `https://forum.example`) is set,
+//! since ids have to be absolute urls.
+//! Accepting follows and federated replies needs signed deliveries to and from
+//! the actor's inbox, which is left for later
+
+use super::{
+    ApiError, Forum, GetCollection, Post, Thread, bson, helper,
+    site_settings::{self, ContentLicense, GuestCapability},
+    visibility::{self, Listing},
+};
+
+use axum::extract::{Path, Query};
+use axum::response::{IntoResponse, Response};
+use http::{StatusCode, header};
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+/// How many of the newest threads the outbox of a [`Forum`] lists
+pub const OUTBOX_LEN: i64 = 20;
+
+const ACTIVITY_JSON: &str = "application/activity+json";
+const ACTIVITY_STREAMS: &str = "https://www.w3.org/ns/activitystreams";
+const PUBLIC: &str = "https://www.w3.org/ns/activitystreams#Public";
+
+fn actor_id(base: &str, forum_id: u32) -> String {
+    format!("{base}/ap/forum/{forum_id}")
+}
+
+fn thread_object_id(base: &str, thread_id: u32) -> String {
+    format!("{base}/ap/thread/{thread_id}")
+}
+
+fn post_object_id(base: &str, post_id: u32) -> String {
+    format!("{base}/ap/post/{post_id}")
+}
+
+/// The `@context` of objects, which also defines the properties [`with_license`] adds
+fn object_context() -> Value {
+    json!([
+        ACTIVITY_STREAMS,
+        {
+            "schema": "http://schema.org/",
+            "license": {"@id": "schema:license", "@type": "@id"},
+            "dcterms": "http://purl.org/dc/terms/",
+            "rights": "dcterms:rights",
+        },
+    ])
+}
+
+/// Adds the `license`, a link to the license text (or its name without one),
+/// and the `rights` notice to `object` if a [`ContentLicense`] is set
+fn with_license(mut object: Value, license: Option<&ContentLicense>) -> Value {
+    if let (Some(license), Some(fields)) = (license, object.as_object_mut()) {
+        let link = license.url.clone().unwrap_or_else(|| license.name.clone());
+        fields.insert("license".into(), Value::String(link));
+        fields.insert("rights".into(), Value::String(license.notice()));
+    }
+    object
+}
+
+/// Whether the [`Thread`] may be shown to the public
+fn is_public(thread: &Thread) -> bool {
+    !thread.pending_review && thread.deleted_at.is_none()
+}
+
+/// The `Group` actor of a [`Forum`]
+fn forum_actor(forum: &Forum, base: &str) -> Value {
+    let id = actor_id(base, forum.id);
+    json!({
+        "@context": ACTIVITY_STREAMS,
+        "type": "Group",
+        "id": id,
+        "preferredUsername": format!("forum-{}", forum.id),
+        "name": forum.name,
+        "summary": forum.description.as_deref().unwrap_or_default(),
+        "url": format!("{base}/forum/{}", forum.id),
+        "inbox": format!("{id}/inbox"),
+        "outbox": format!("{id}/outbox"),
+    })
+}
+
+/// The `Page` object of a [`Thread`], with its origin [`Post`] as content
+fn thread_object(
+    thread: &Thread,
+    origin_post: &Post,
+    license: Option<&ContentLicense>,
+    base: &str,
+) -> Value {
+    let object = json!({
+        "@context": object_context(),
+        "type": "Page",
+        "id": thread_object_id(base, thread.id),
+        "attributedTo": actor_id(base, thread.forum_id),
+        "name": thread.subject,
+        "content": crate::markdown::render(&origin_post.content),
+        "mediaType": "text/html",
+        "url": format!("{base}/thread/{}", thread.id),
+        "published": origin_post.created_at.to_string(),
+        "to": [PUBLIC],
+        "cc": [actor_id(base, thread.forum_id)],
+    });
+    with_license(object, license)
+}
+
+/// The `Note` object of a reply [`Post`] in the given [`Thread`]
+fn post_object(
+    post: &Post,
+    thread: &Thread,
+    license: Option<&ContentLicense>,
+    base: &str,
+) -> Value {
+    let object = json!({
+        "@context": object_context(),
+        "type": "Note",
+        "id": post_object_id(base, post.id),
+        "attributedTo": actor_id(base, thread.forum_id),
+        "inReplyTo": thread_object_id(base, thread.id),
+        "content": crate::markdown::render(&post.content),
+        "mediaType": "text/html",
+        "url": format!("{base}/post/{}", post.id),
+        "published": post.created_at.to_string(),
+        "to": [PUBLIC],
+    });
+    with_license(object, license)
+}
+
+/// Turns the result of a handler into a JSON response with the given content type
+fn respond(result: Result<Option<Value>, ApiError>, content_type: &'static str) -> Response {
+    match result {
+        Ok(Some(value)) => {
+            ([(header::CONTENT_TYPE, content_type)], value.to_string()).into_response()
+        }
+        Ok(None) | Err(ApiError::NotFound(..) | ApiError::LoginRequired) => {
+            StatusCode::NOT_FOUND.into_response()
+        }
+        Err(err) => {
+            tracing::warn!("serving ActivityPub object failed: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Query of a `WebFinger` lookup, e.g. `resource=acct:forum-3@forum.example`
+#[derive(Deserialize, Debug)]
+pub struct WebfingerQuery {
+    pub resource: String,
+}
+
+/// Axum handler for `GET /.well-known/webfinger`, resolving `acct:forum-{id}@{host}`
+/// to the actor of the [`Forum`]
+pub async fn webfinger(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    Query(query): Query<WebfingerQuery>,
+) -> Response {
+    let lookup = async {
+        let Some(base) = helper::public_url() else {
+            return Ok(None);
+        };
+        helper::ensure_guest_can(GuestCapability::Threads, None, &db).await?;
+        let host = base
+            .split_once("://")
+            .map_or(base.as_str(), |(_, host)| host);
+        let Some(forum_id) = query
+            .resource
+            .strip_prefix("acct:forum-")
+            .and_then(|account| account.strip_suffix(&format!("@{host}")))
+            .and_then(|id| id.parse::<u32>().ok())
+        else {
+            return Ok(None);
+        };
+        visibility::ensure_listed(forum_id, None, Listing::Public, &db).await?;
+        let (forum, _) = helper::get_forum(forum_id, db).await?;
+        Ok(Some(json!({
+            "subject": query.resource,
+            "links": [{
+                "rel": "self",
+                "type": ACTIVITY_JSON,
+                "href": actor_id(&base, forum.id),
+            }],
+        })))
+    };
+    respond(lookup.await, "application/jrd+json")
+}
+
+/// Axum handler for `GET /ap/forum/{id}`, the actor of a [`Forum`]
+pub async fn actor(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    Path(forum_id): Path<u32>,
+) -> Response {
+    let lookup = async {
+        let Some(base) = helper::public_url() else {
+            return Ok(None);
+        };
+        helper::ensure_guest_can(GuestCapability::Threads, None, &db).await?;
+        visibility::ensure_listed(forum_id, None, Listing::Public, &db).await?;
+        let (forum, _) = helper::get_forum(forum_id, db).await?;
+        Ok(Some(forum_actor(&forum, &base)))
+    };
+    respond(lookup.await, ACTIVITY_JSON)
+}
+
+/// Axum handler for `GET /ap/forum/{id}/outbox`, the newest [`Threads`][Thread]
+/// of a [`Forum`] as `Create` activities
+pub async fn outbox(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    Path(forum_id): Path<u32>,
+) -> Response {
+    let lookup = async {
+        let Some(base) = helper::public_url() else {
+            return Ok(None);
+        };
+        helper::ensure_guest_can(GuestCapability::Threads, None, &db).await?;
+        visibility::ensure_listed(forum_id, None, Listing::Public, &db).await?;
+        let (forum, _) = helper::get_forum(forum_id, db.clone()).await?;
+        let actor = actor_id(&base, forum.id);
+        let license = site_settings::load_settings(&db).await?.content_license;
+
+        let mut items = vec![];
+        let mut cursor = Thread::collection(&db)
+            .find(bson::doc! {
+                "forum_id": forum.id,
+                "pending_review": {"$ne": true},
+                "deleted_at": null,
+            })
+            .sort(bson::doc! {"id": -1})
+            .limit(OUTBOX_LEN)
+            .await?;
+        while cursor.advance().await? {
+            let thread: Thread = cursor.deserialize_current()?;
+            let origin_post = helper::get_post(thread.origin_post_id, db.clone()).await?;
+            let object = thread_object(&thread, &origin_post, license.as_ref(), &base);
+            items.push(json!({
+                "type": "Create",
+                "id": format!("{}/activity", thread_object_id(&base, thread.id)),
+                "actor": actor,
+                "published": origin_post.created_at.to_string(),
+                "to": [PUBLIC],
+                "object": object,
+            }));
+        }
+
+        Ok(Some(json!({
+            "@context": ACTIVITY_STREAMS,
+            "type": "OrderedCollection",
+            "id": format!("{actor}/outbox"),
+            "totalItems": items.len(),
+            "orderedItems": items,
+        })))
+    };
+    respond(lookup.await, ACTIVITY_JSON)
+}
+
+/// Axum handler for `GET /ap/thread/{id}`, the `Page` object of a [`Thread`]
+pub async fn thread(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    Path(thread_id): Path<u32>,
+) -> Response {
+    let lookup = async {
+        let Some(base) = helper::public_url() else {
+            return Ok(None);
+        };
+        helper::ensure_guest_can(GuestCapability::Threads, None, &db).await?;
+        let thread = helper::get_thread(thread_id, db.clone()).await?;
+        if !is_public(&thread) {
+            return Ok(None);
+        }
+        visibility::ensure_listed(thread.forum_id, None, Listing::Public, &db).await?;
+        let origin_post = helper::get_post(thread.origin_post_id, db.clone()).await?;
+        let license = site_settings::load_settings(&db).await?.content_license;
+        let object = thread_object(&thread, &origin_post, license.as_ref(), &base);
+        Ok(Some(object))
+    };
+    respond(lookup.await, ACTIVITY_JSON)
+}
+
+/// Axum handler for `GET /ap/post/{id}`, the `Note` object of a reply [`Post`]
+///
+/// Origin posts are part of their thread's `Page` object instead
+pub async fn post(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    Path(post_id): Path<u32>,
+) -> Response {
+    let lookup = async {
+        let Some(base) = helper::public_url() else {
+            return Ok(None);
+        };
+        helper::ensure_guest_can(GuestCapability::Threads, None, &db).await?;
+        let post = helper::get_post(post_id, db.clone()).await?;
+        let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+        if post.pending_review || !is_public(&thread) {
+            return Ok(None);
+        }
+        visibility::ensure_listed(thread.forum_id, None, Listing::Public, &db).await?;
+        let license = site_settings::load_settings(&db).await?.content_license;
+        if thread.origin_post_id == post.id {
+            return Ok(Some(thread_object(&thread, &post, license.as_ref(), &base)));
+        }
+        Ok(Some(post_object(&post, &thread, license.as_ref(), &base)))
+    };
+    respond(lookup.await, ACTIVITY_JSON)
+}
@@ -0,0 +1,261 @@
+//! Cookie-less aggregate analytics: page views and unique visitors per day
+//!
+//! Off by default, admins switch it on in the [`AnalyticsSettings`].
+//! Visitors are told apart by a hash of their IP address and user agent, salted with
+//! a random value that's only kept in memory and replaced every day (UTC).
+//! So nobody can be recognized across days and nothing about individuals is stored,
+//! only the daily totals in [`DailyVisits`].
+//! After a restart the salt is new, so visitors of that day may be counted twice
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, bson, helper, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How many days [`get_daily_visits`] returns
+pub const DAYS_SHOWN: usize = 30;
+
+/// Whether page views are counted, a single document edited by admins
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AnalyticsSettings {
+    pub enabled: bool,
+}
+impl CollectionName for AnalyticsSettings {
+    fn collection_name() -> &'static str {
+        "analytics_settings"
+    }
+}
+
+/// Totals of a single day
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DailyVisits {
+    /// UTC date, e.g. `2025-06-30`
+    pub day: String,
+    /// Pages served, including reloads
+    pub views: u32,
+    /// Different visitors on that day
+    pub visitors: u32,
+}
+impl CollectionName for DailyVisits {
+    fn collection_name() -> &'static str {
+        "analytics"
+    }
+}
+
+/// Checks if `user_agent` belongs to a crawler, whose views aren't counted
+///
+/// # Example
+///
+/// ```
+/// use app::api::analytics::is_bot;
+///
+/// assert!(is_bot("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)"));
+/// assert!(is_bot(""));
+/// assert!(!is_bot("Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0"));
+/// ```
+#[must_use]
+pub fn is_bot(user_agent: &str) -> bool {
+    let user_agent = user_agent.to_lowercase();
+    user_agent.is_empty()
+        || ["bot", "crawl", "spider", "slurp", "curl", "wget"]
+            .iter()
+            .any(|marker| user_agent.contains(marker))
+}
+
+/// Visitors seen today, told apart by salted hashes
+#[cfg(feature = "ssr")]
+#[derive(Debug)]
+struct Today {
+    day: jiff::civil::Date,
+    salt: [u8; 32],
+    visitors: std::collections::HashSet<[u8; 32]>,
+}
+
+/// Whether analytics are enabled and who was seen today, shared by all requests, cheap to clone
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug)]
+pub struct Analytics {
+    db: mongodb::Database,
+    enabled: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    today: std::sync::Arc<std::sync::Mutex<Option<Today>>>,
+}
+#[cfg(feature = "ssr")]
+impl Analytics {
+    /// Sets up counting with the saved [`AnalyticsSettings`],
+    /// counting nothing if they can't be loaded
+    pub async fn load(db: mongodb::Database) -> Self {
+        let enabled = match load_settings(&db).await {
+            Ok(settings) => settings.enabled,
+            Err(err) => {
+                tracing::warn!("loading analytics settings failed: {err}");
+                false
+            }
+        };
+        Self {
+            db,
+            enabled: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(enabled)),
+            today: std::sync::Arc::default(),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Records `visitor` on `day`, returns whether they weren't seen that day yet
+    ///
+    /// A new day gets a new salt and forgets everyone seen the day before
+    fn record(&self, visitor: &str, day: jiff::civil::Date) -> bool {
+        use sha2::Digest;
+
+        // the state is always valid, so poisoning doesn't matter
+        let mut today = self
+            .today
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        today.take_if(|today| today.day != day);
+        let today = today.get_or_insert_with(|| Today {
+            day,
+            salt: rand::random(),
+            visitors: std::collections::HashSet::new(),
+        });
+        let hash = sha2::Sha256::new()
+            .chain_update(today.salt)
+            .chain_update(visitor)
+            .finalize()
+            .into();
+        today.visitors.insert(hash)
+    }
+}
+
+/// Middleware counting successfully served HTML pages in [`DailyVisits`] if analytics are enabled
+///
+/// Only `GET` requests are counted, crawlers (see [`is_bot`]) aren't.
+/// Requests without an IP address count as views but not as visitors
+#[cfg(feature = "ssr")]
+pub async fn count(
+    axum::Extension(analytics): axum::Extension<Analytics>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use http::header;
+
+    if !analytics.is_enabled() || req.method() != http::Method::GET {
+        return next.run(req).await;
+    }
+    let headers = req.headers();
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|header| header.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let ip = helper::client_ip_of(headers, req.extensions());
+
+    let res = next.run(req).await;
+    let is_page = res.status().is_success()
+        && res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|header| header.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("text/html"));
+    if !is_page || is_bot(&user_agent) {
+        return res;
+    }
+
+    let day = jiff::Timestamp::now()
+        .to_zoned(jiff::tz::TimeZone::UTC)
+        .date();
+    let new_visitor = ip.is_some_and(|ip| analytics.record(&format!("{ip}\n{user_agent}"), day));
+    tokio::spawn(async move {
+        let update = DailyVisits::collection(&analytics.db)
+            .update_one(
+                bson::doc! {"day": day.to_string()},
+                bson::doc! {"$inc": {"views": 1, "visitors": i32::from(new_visitor)}},
+            )
+            .upsert(true)
+            .await;
+        if let Err(err) = update {
+            tracing::warn!("counting page view failed: {err}");
+        }
+    });
+    res
+}
+
+/// Loads the [`AnalyticsSettings`], disabled if an admin never changed them
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn load_settings(db: &mongodb::Database) -> Result<AnalyticsSettings, ApiError> {
+    let settings = AnalyticsSettings::collection(db)
+        .find_one(bson::doc! {})
+        .await?;
+    Ok(settings.unwrap_or_default())
+}
+
+/// Fetches the [`AnalyticsSettings`]
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_analytics_settings() -> Result<AnalyticsSettings, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+    load_settings(&db).await
+}
+
+/// Switches counting on or off, effective immediately
+///
+/// Already counted days are kept when switching off
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn update_analytics_settings(#[server(default)] enabled: bool) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    AnalyticsSettings::collection(&db)
+        .replace_one(bson::doc! {}, AnalyticsSettings { enabled })
+        .upsert(true)
+        .await?;
+    if let Some(analytics) = use_context::<Analytics>() {
+        analytics
+            .enabled
+            .store(enabled, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    Ok(())
+}
+
+/// Returns the [`DailyVisits`] of the last [`DAYS_SHOWN`] days something was counted on,
+/// the newest first
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_daily_visits() -> Result<Vec<DailyVisits>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    // concurrent upserts of a new day can insert it more than once, those are summed up
+    let mut days: std::collections::BTreeMap<String, DailyVisits> =
+        std::collections::BTreeMap::new();
+    let mut cursor = DailyVisits::collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"day": -1})
+        .await?;
+    while cursor.advance().await? {
+        let visits: DailyVisits = cursor.deserialize_current()?;
+        if let Some(day) = days.get_mut(&visits.day) {
+            day.views += visits.views;
+            day.visitors += visits.visitors;
+        } else if days.len() < DAYS_SHOWN {
+            days.insert(visits.day.clone(), visits);
+        } else {
+            break;
+        }
+    }
+
+    Ok(days.into_values().rev().collect())
+}
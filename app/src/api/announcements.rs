@@ -0,0 +1,197 @@
+//! Announcements shown as banners on top of the home page and [`Forums`][super::Forum]
+//!
+//! Written in Markdown by admins, they're shown between their start and end
+//! either everywhere or in a single forum, see [`Announcement::forum_id`]
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{Counter, GetCollection, bson, helper, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How prominently an [`Announcement`] is shown
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    /// Can't be dismissed
+    Critical,
+}
+
+/// A message from the admins shown as a banner, written in Markdown
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Announcement {
+    pub id: u32,
+    /// Shown on the home page and in every forum if [`None`],
+    /// otherwise only in the [`Forum`][super::Forum] with this id
+    pub forum_id: Option<u32>,
+    pub content: String,
+    pub severity: Severity,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub starts_at: jiff::Timestamp,
+    /// Shown until removed if [`None`]
+    #[serde(default, with = "super::jiff_timestamp_as_bson_datetime::optional")]
+    pub ends_at: Option<jiff::Timestamp>,
+}
+impl Announcement {
+    /// Whether it's shown at `now`
+    #[must_use]
+    pub fn is_active(&self, now: jiff::Timestamp) -> bool {
+        self.starts_at <= now && self.ends_at.is_none_or(|ends_at| now < ends_at)
+    }
+}
+impl CollectionName for Announcement {
+    fn collection_name() -> &'static str {
+        "announcements"
+    }
+}
+
+/// An [`Announcement`] with its content rendered to HTML, ready to be displayed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenderedAnnouncement {
+    pub announcement: Announcement,
+    pub html: String,
+}
+
+/// Fetches the [`Announcements`][Announcement] currently shown in the given [`Forum`][super::Forum]
+/// or on the home page if [`None`], the most severe ones first
+#[server]
+pub async fn get_active_announcements(
+    forum_id: Option<u32>,
+) -> Result<Vec<RenderedAnnouncement>, ApiError> {
+    let db = helper::get_db()?;
+
+    let now = jiff::Timestamp::now();
+    let forum_ids: Vec<Option<u32>> = match forum_id {
+        Some(forum_id) => vec![None, Some(forum_id)],
+        None => vec![None],
+    };
+    let mut announcements = vec![];
+    let mut cursor = Announcement::collection(&db)
+        .find(bson::doc! {"forum_id": {"$in": forum_ids}})
+        .sort(bson::doc! {"starts_at": -1})
+        .await?;
+    while cursor.advance().await? {
+        let announcement: Announcement = cursor.deserialize_current()?;
+        if announcement.is_active(now) {
+            announcements.push(announcement);
+        }
+    }
+    announcements.sort_by_key(|announcement| std::cmp::Reverse(announcement.severity));
+
+    Ok(announcements
+        .into_iter()
+        .map(|announcement| RenderedAnnouncement {
+            html: crate::markdown::render(&announcement.content),
+            announcement,
+        })
+        .collect())
+}
+
+/// Fetches all [`Announcements`][Announcement], including past and future ones, newest first
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_all_announcements() -> Result<Vec<Announcement>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let mut announcements = vec![];
+    let mut cursor = Announcement::collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"starts_at": -1})
+        .await?;
+    while cursor.advance().await? {
+        announcements.push(cursor.deserialize_current()?);
+    }
+
+    Ok(announcements)
+}
+
+/// Creates an [`Announcement`], or replaces the one with `announcement_id` if given
+///
+/// `starts_at` and `ends_at` are read in `time_zone`, see [`crate::time::parse_in_user_tz`].
+/// An empty `starts_at` means right away, an empty `ends_at` never
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptyContent`] if `content` is empty
+/// - [`ApiError::NotFound`] if `forum_id` or `announcement_id` aren't in use
+/// - [`ApiError::InvalidAnnouncementDates`] if a date can't be parsed or it ends before it starts
+#[server]
+pub async fn save_announcement(
+    announcement_id: Option<u32>,
+    forum_id: Option<u32>,
+    content: String,
+    severity: Severity,
+    starts_at: Option<String>,
+    ends_at: Option<String>,
+    time_zone: String,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let content = content.trim().to_string();
+    if content.is_empty() {
+        return Err(ApiError::EmptyContent);
+    }
+    if let Some(forum_id) = forum_id {
+        let _ = helper::get_forum(forum_id, db.clone()).await?;
+    }
+    let parse = |date: Option<String>| match date {
+        Some(date) => crate::time::parse_in_user_tz(&date, &time_zone)
+            .map(Some)
+            .ok_or(ApiError::InvalidAnnouncementDates),
+        None => Ok(None),
+    };
+    let starts_at = parse(starts_at)?.unwrap_or_else(jiff::Timestamp::now);
+    let ends_at = parse(ends_at)?;
+    if ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+        return Err(ApiError::InvalidAnnouncementDates);
+    }
+
+    let announcement_col = Announcement::collection(&db);
+    let id = if let Some(id) = announcement_id {
+        let existing = announcement_col.find_one(bson::doc! {"id": id}).await?;
+        if existing.is_none() {
+            return Err(ApiError::NotFound("announcement".into(), id));
+        }
+        id
+    } else {
+        let counter_col = Counter::collection(&db);
+        helper::get_and_increment_id_of("announcement", counter_col).await?
+    };
+    let announcement = Announcement {
+        id,
+        forum_id,
+        content,
+        severity,
+        starts_at,
+        ends_at,
+    };
+    announcement_col
+        .replace_one(bson::doc! {"id": id}, announcement)
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes the [`Announcement`] with the given id
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn delete_announcement(announcement_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    Announcement::collection(&db)
+        .delete_one(bson::doc! {"id": announcement_id})
+        .await?;
+
+    Ok(())
+}
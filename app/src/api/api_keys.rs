@@ -0,0 +1,205 @@
+//! Keys for the [JSON API][super::rest], created by users for their bots and clients
+//!
+//! Requests authenticate with `Authorization: Bearer {key}` and act as the owner of the key.
+//! The key itself is only shown once when it's created, only its [hash][hash_key] is stored,
+//! so a leaked database doesn't leak working keys
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, bson, helper, user::User};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maximum amount of keys a single [`User`][super::user::User] can have
+pub const MAX_API_KEYS: u64 = 10;
+/// Maximum amount of characters of [`ApiKey::name`]
+pub const MAX_API_KEY_NAME_LEN: usize = 50;
+
+/// A key for the [JSON API][super::rest] belonging to a [`User`][super::user::User]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    pub id: u32,
+    pub user_id: u32,
+    /// Chosen by the user to tell their keys apart, e.g. the name of the bot
+    pub name: String,
+    /// See [`hash_key`]
+    pub key_hash: String,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+    #[serde(default, with = "super::jiff_timestamp_as_bson_datetime::optional")]
+    pub last_used_at: Option<jiff::Timestamp>,
+}
+impl CollectionName for ApiKey {
+    fn collection_name() -> &'static str {
+        "api_keys"
+    }
+}
+
+/// What the owner sees of an [`ApiKey`] after its creation, everything but the key itself
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKeyInfo {
+    pub id: u32,
+    pub name: String,
+    pub created_at: jiff::Timestamp,
+    pub last_used_at: Option<jiff::Timestamp>,
+}
+impl From<ApiKey> for ApiKeyInfo {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            name: key.name,
+            created_at: key.created_at,
+            last_used_at: key.last_used_at,
+        }
+    }
+}
+
+/// Hex encoded SHA-256 of `key`, which is all that's stored of an [`ApiKey`]
+///
+/// Keys are random tokens, so they don't need a slow password hash
+///
+/// # Example
+///
+/// ```
+/// use app::api::api_keys::hash_key;
+///
+/// assert_eq!(
+///     hash_key("abc"),
+///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+/// );
+/// ```
+#[must_use]
+pub fn hash_key(key: &str) -> String {
+    use sha2::Digest;
+    use std::fmt::Write;
+
+    sha2::Sha256::digest(key)
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Returns the [`User`] owning the [`ApiKey`] in the `Authorization: Bearer` header
+/// and records that the key was used
+///
+/// # Errors
+///
+/// * [`ApiError::InvalidApiKey`] if there's no key in `headers`, it doesn't exist
+///   or its owner was deleted or [merged][super::account_merge] into another account
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn user_from_headers(
+    headers: &http::HeaderMap,
+    db: &mongodb::Database,
+) -> Result<User, ApiError> {
+    let key = headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(ApiError::InvalidApiKey)?;
+
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    let Some(api_key) = ApiKey::collection(db)
+        .find_one_and_update(
+            bson::doc! {"key_hash": hash_key(key)},
+            bson::doc! {"$set": {"last_used_at": now}},
+        )
+        .await?
+    else {
+        return Err(ApiError::InvalidApiKey);
+    };
+    // keys of deleted accounts are as good as revoked
+    let user = helper::get_user(api_key.user_id, db)
+        .await
+        .map_err(|_| ApiError::InvalidApiKey)?;
+    // their keys are deleted when merging, but a duplicate must never act again
+    if user.merged_into.is_some() {
+        return Err(ApiError::InvalidApiKey);
+    }
+    Ok(user)
+}
+
+/// Creates a new [`ApiKey`] for the logged-in [`User`][super::user::User] and returns the key,
+/// which can't be looked up again later
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::InvalidApiKeyName`] if `name` is empty or longer than [`MAX_API_KEY_NAME_LEN`]
+/// - [`ApiError::TooManyApiKeys`] if the user already has [`MAX_API_KEYS`]
+#[server]
+pub async fn create_api_key(name: String) -> Result<String, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_API_KEY_NAME_LEN {
+        return Err(ApiError::InvalidApiKeyName);
+    }
+    let key_col = ApiKey::collection(&db);
+    if key_col
+        .count_documents(bson::doc! {"user_id": user.id})
+        .await?
+        >= MAX_API_KEYS
+    {
+        return Err(ApiError::TooManyApiKeys);
+    }
+
+    let counter_col = super::Counter::collection(&db);
+    let id = helper::get_and_increment_id_of("api_key", counter_col).await?;
+    let key = helper::random_token();
+    let api_key = ApiKey {
+        id,
+        user_id: user.id,
+        name,
+        key_hash: hash_key(&key),
+        created_at: jiff::Timestamp::now(),
+        last_used_at: None,
+    };
+    key_col.insert_one(&api_key).await?;
+
+    Ok(key)
+}
+
+/// Fetches the [`ApiKeys`][ApiKey] of the logged-in [`User`][super::user::User], oldest first
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn get_api_keys() -> Result<Vec<ApiKeyInfo>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let mut keys = vec![];
+    let mut cursor = ApiKey::collection(&db)
+        .find(bson::doc! {"user_id": user.id})
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    while cursor.advance().await? {
+        let key: ApiKey = cursor.deserialize_current()?;
+        keys.push(key.into());
+    }
+
+    Ok(keys)
+}
+
+/// Deletes an [`ApiKey`] of the logged-in [`User`][super::user::User]
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn revoke_api_key(key_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    ApiKey::collection(&db)
+        .delete_one(bson::doc! {"id": key_id, "user_id": user.id})
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,460 @@
+//! File attachments of [`Posts`][super::Post]
+//!
+//! Files are uploaded before the post exists through the plain axum route `upload`
+//! and saved in `attachment_dir`. Creating the post then claims them by id.
+//! They are served by `download` with the sniffed MIME type, never the one the client claimed.
+//!
+//! Attachments of posts guests can't see are only served with a short-lived signature in the url,
+//! added whenever posts are sent to a client, see [`UrlSignature`]. Copying such a link
+//! out of a private forum stops working after one to two hours, see [`SIGNED_URL_SECS`]
+
+use super::CollectionName;
+#[cfg(feature = "ssr")]
+use super::{
+    ApiError, Counter, GetCollection, PostWithAuthor, bson, helper, reactions::ReactionOption,
+    site_settings, user::User, webhooks,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Maximum size of a single attachment in bytes (5 MiB)
+pub const MAX_ATTACHMENT_SIZE: usize = 5 * 1024 * 1024;
+/// Maximum number of attachments of a single [`Post`][super::Post]
+pub const MAX_ATTACHMENTS_PER_POST: usize = 5;
+/// Signed urls work for at least this and at most twice this many seconds,
+/// see [`UrlSignature`]
+pub const SIGNED_URL_SECS: i64 = 60 * 60;
+
+/// Proves that the url of an [`AttachmentRef`] was handed out by the server recently,
+/// required for attachments of posts guests can't see
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UrlSignature {
+    /// Unix timestamp in seconds
+    pub expires: i64,
+    /// Hex HMAC-SHA256 of the attachment id and `expires`
+    pub sig: String,
+}
+
+/// What a [`Post`][super::Post] saves about each of its attachments
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AttachmentRef {
+    pub id: u32,
+    pub file_name: String,
+    pub mime: String,
+    /// Only set on the way to clients, never stored, see [`sign_urls`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<UrlSignature>,
+}
+impl AttachmentRef {
+    /// Route the file is served at, with the [`signature`][Self::signature] if there is one
+    #[must_use]
+    pub fn url(&self) -> String {
+        match &self.signature {
+            Some(signature) => format!(
+                "/attachments/{}?expires={}&sig={}",
+                self.id, signature.expires, signature.sig
+            ),
+            None => format!("/attachments/{}", self.id),
+        }
+    }
+
+    /// Whether it can be displayed inline
+    #[must_use]
+    pub fn is_image(&self) -> bool {
+        self.mime.starts_with("image/")
+    }
+}
+
+/// An uploaded file, stored under its id in `attachment_dir`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attachment {
+    pub id: u32,
+    pub file_name: String,
+    pub mime: String,
+    /// In bytes
+    pub size: u64,
+    pub uploader_id: u32,
+    /// [`None`] until a post claims it
+    pub post_id: Option<u32>,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+impl Attachment {
+    #[must_use]
+    pub fn to_ref(&self) -> AttachmentRef {
+        AttachmentRef {
+            id: self.id,
+            file_name: self.file_name.clone(),
+            mime: self.mime.clone(),
+            signature: None,
+        }
+    }
+}
+impl CollectionName for Attachment {
+    fn collection_name() -> &'static str {
+        "attachments"
+    }
+}
+
+/// Detects the MIME type of a file from its first bytes
+///
+/// Returns [`None`] for everything that isn't an allowed type
+/// (PNG, JPEG, GIF, WebP and PDF)
+///
+/// # Example
+///
+/// ```
+/// use app::api::attachments::sniff_mime;
+///
+/// assert_eq!(sniff_mime(b"%PDF-1.7 ..."), Some("application/pdf"));
+/// assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+/// assert_eq!(sniff_mime(b"<html><script>"), None);
+/// ```
+#[must_use]
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: [(&[u8], &str); 5] = [
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+    ];
+
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return Some("image/webp");
+    }
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+        .map(|(_, mime)| *mime)
+}
+
+/// Directory the files are stored in, set with the `ATTACHMENT_DIR` env var
+/// (defaults to `attachments`)
+#[cfg(feature = "ssr")]
+#[must_use]
+pub fn attachment_dir() -> std::path::PathBuf {
+    std::env::var_os("ATTACHMENT_DIR").map_or_else(|| "attachments".into(), Into::into)
+}
+
+/// Key of the url signatures, set with the `ATTACHMENT_URL_SECRET` env var
+///
+/// Without it a random one is used, so signed urls stop working after a restart
+/// and only work on the instance that handed them out
+#[cfg(feature = "ssr")]
+fn url_secret() -> &'static str {
+    static SECRET: std::sync::LazyLock<String> = std::sync::LazyLock::new(|| {
+        std::env::var("ATTACHMENT_URL_SECRET")
+            .ok()
+            .filter(|secret| !secret.is_empty())
+            .unwrap_or_else(helper::random_token)
+    });
+    &SECRET
+}
+
+/// The signature of the attachment `id` that works until `expires`
+#[cfg(feature = "ssr")]
+fn signature(id: u32, expires: i64) -> String {
+    webhooks::sign(url_secret(), format!("{id}:{expires}").as_bytes())
+}
+
+/// Adds a [`UrlSignature`] to the attachments of all `posts`
+///
+/// The expiry is rounded up to a multiple of [`SIGNED_URL_SECS`], so the urls stay the same
+/// for a while and browsers can cache the files
+#[cfg(feature = "ssr")]
+pub(super) fn sign_urls(posts: &mut [PostWithAuthor]) {
+    let now = jiff::Timestamp::now().as_second();
+    let expires = (now / SIGNED_URL_SECS + 2) * SIGNED_URL_SECS;
+    for attachment in posts.iter_mut().flat_map(|post| &mut post.post.attachments) {
+        attachment.signature = Some(UrlSignature {
+            expires,
+            sig: signature(attachment.id, expires),
+        });
+    }
+}
+
+/// Whether `attachment` belongs to a post guests can't see,
+/// images of [reaction options][super::reactions::ReactionOption] don't
+///
+/// Unclaimed uploads are only served to their uploader, see [`is_reaction_image`]
+#[cfg(feature = "ssr")]
+async fn is_private(attachment: &Attachment, db: &mongodb::Database) -> Result<bool, ApiError> {
+    let Some(post_id) = attachment.post_id else {
+        return Ok(false);
+    };
+    if !site_settings::load_settings(db).await?.guest_access.threads {
+        return Ok(true);
+    }
+    let post = helper::get_post(post_id, db.clone()).await?;
+    let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+    let forums = helper::get_all_forums(db).await?;
+    Ok(post.pending_review
+        || thread.deleted_at.is_some()
+        || !helper::can_see_forum(&forums, thread.forum_id, None))
+}
+
+/// Whether the attachment with `id` is the image of a
+/// [reaction option][super::reactions::ReactionOption], which never belongs to a post
+/// but is shown to everyone, unlike other unclaimed uploads
+#[cfg(feature = "ssr")]
+async fn is_reaction_image(id: u32, db: &mongodb::Database) -> Result<bool, ApiError> {
+    let option = ReactionOption::collection(db)
+        .find_one(bson::doc! {"image.id": id})
+        .await?;
+    Ok(option.is_some())
+}
+
+/// Keeps only the last path segment of an uploaded file name without control characters
+#[cfg(feature = "ssr")]
+fn clean_file_name(name: Option<&str>) -> String {
+    let name: String = name
+        .unwrap_or_default()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .take(100)
+        .collect();
+    if name.trim().is_empty() {
+        "file".to_string()
+    } else {
+        name
+    }
+}
+
+/// Reads the multipart field `file` of an upload, rejecting it if it's bigger than `max_size`
+///
+/// Returns the cleaned up file name and the content
+#[cfg(feature = "ssr")]
+pub(super) async fn read_file_field(
+    multipart: &mut axum::extract::Multipart,
+    max_size: usize,
+) -> Result<(String, Vec<u8>), ApiError> {
+    let invalid =
+        |err: axum::extract::multipart::MultipartError| ApiError::InvalidUpload(err.body_text());
+
+    let mut field = loop {
+        let Some(field) = multipart.next_field().await.map_err(invalid)? else {
+            return Err(ApiError::InvalidUpload("missing file field".into()));
+        };
+        if field.name() == Some("file") {
+            break field;
+        }
+    };
+    let file_name = clean_file_name(field.file_name());
+
+    let mut data = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(invalid)? {
+        if data.len() + chunk.len() > max_size {
+            return Err(ApiError::AttachmentTooLarge);
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok((file_name, data))
+}
+
+/// Turns an [`ApiError`] of an upload handler into a fitting status and its message
+#[cfg(feature = "ssr")]
+pub(super) fn upload_error_response(err: &ApiError) -> (http::StatusCode, String) {
+    use http::StatusCode;
+
+    let status = match err {
+        ApiError::NotLoggedIn => StatusCode::UNAUTHORIZED,
+        ApiError::Banned { .. } | ApiError::Muted { .. } => StatusCode::FORBIDDEN,
+        ApiError::AttachmentTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+        ApiError::AttachmentTypeNotAllowed => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        ApiError::InvalidUpload(_) | ApiError::ImageProcessing(_) => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, err.to_string())
+}
+
+/// Validates and stores an uploaded attachment
+#[cfg(feature = "ssr")]
+async fn store_upload(
+    db: &mongodb::Database,
+    headers: &http::HeaderMap,
+    multipart: &mut axum::extract::Multipart,
+) -> Result<AttachmentRef, ApiError> {
+    let user = helper::require_user_from_headers(headers, db).await?;
+    helper::ensure_can_post(Some(&user))?;
+
+    let (file_name, data) = read_file_field(multipart, MAX_ATTACHMENT_SIZE).await?;
+    let mime = sniff_mime(&data).ok_or(ApiError::AttachmentTypeNotAllowed)?;
+
+    let id = helper::get_and_increment_id_of("attachment", Counter::collection(db)).await?;
+
+    let dir = attachment_dir();
+    let storage_err = |err: std::io::Error| ApiError::Storage(err.to_string());
+    tokio::fs::create_dir_all(&dir).await.map_err(storage_err)?;
+    tokio::fs::write(dir.join(id.to_string()), &data)
+        .await
+        .map_err(storage_err)?;
+
+    let attachment = Attachment {
+        id,
+        file_name,
+        mime: mime.to_string(),
+        size: data.len() as u64,
+        uploader_id: user.id,
+        post_id: None,
+        created_at: jiff::Timestamp::now(),
+    };
+    Attachment::collection(db).insert_one(&attachment).await?;
+
+    Ok(attachment.to_ref())
+}
+
+/// Axum handler for `POST /attachments`, expects the file in the multipart field `file`
+///
+/// Requires being logged in. Responds with the [`AttachmentRef`] as JSON
+///
+/// # Errors
+///
+/// Responds with an error status and the [`ApiError`] message as text
+#[cfg(feature = "ssr")]
+pub async fn upload(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    mut multipart: axum::extract::Multipart,
+) -> Result<axum::Json<AttachmentRef>, (http::StatusCode, String)> {
+    store_upload(&db, &headers, &mut multipart)
+        .await
+        .map(axum::Json)
+        .map_err(|err| upload_error_response(&err))
+}
+
+/// Query of `GET /attachments/{id}`, the parts of a [`UrlSignature`]
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, Debug, Default)]
+pub struct SignatureQuery {
+    pub expires: Option<i64>,
+    pub sig: Option<String>,
+}
+#[cfg(feature = "ssr")]
+impl SignatureQuery {
+    /// Whether it's an unexpired signature of the attachment `id`
+    fn is_valid(&self, id: u32) -> bool {
+        let (Some(expires), Some(sig)) = (self.expires, self.sig.as_deref()) else {
+            return false;
+        };
+        expires > jiff::Timestamp::now().as_second()
+            && helper::secrets_match(sig, &signature(id, expires))
+    }
+}
+
+/// Axum handler for `GET /attachments/{id}`, serves the file inline
+///
+/// Attachments of posts guests can't see need a valid [`UrlSignature`] in the query
+/// and unclaimed uploads the session of their uploader,
+/// responds with `404` otherwise, as if they didn't exist
+#[cfg(feature = "ssr")]
+pub async fn download(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    axum::extract::Path(id): axum::extract::Path<u32>,
+    axum::extract::Query(query): axum::extract::Query<SignatureQuery>,
+    headers: http::HeaderMap,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use http::{StatusCode, header};
+
+    let Ok(Some(attachment)) = Attachment::collection(&db)
+        .find_one(bson::doc! {"id": id})
+        .await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let unclaimed =
+        attachment.post_id.is_none() && !is_reaction_image(id, &db).await.unwrap_or(false);
+    let private = if unclaimed {
+        // only previewed by the uploader while writing the post, ids are easy to guess
+        let viewer = helper::require_user_from_headers(&headers, &db).await;
+        if !viewer.is_ok_and(|viewer| viewer.id == attachment.uploader_id) {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        true
+    } else {
+        // e.g. the post was deleted, then only a signature handed out before still works
+        let private = is_private(&attachment, &db).await.unwrap_or(true);
+        if private && !query.is_valid(id) {
+            return StatusCode::NOT_FOUND.into_response();
+        }
+        private
+    };
+    let Ok(data) = tokio::fs::read(attachment_dir().join(id.to_string())).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    // ids are never reused, but shared caches mustn't keep private files
+    let cache_control = if private {
+        format!("private, max-age={SIGNED_URL_SECS}")
+    } else {
+        "public, max-age=31536000, immutable".to_string()
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, attachment.mime),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", attachment.file_name),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        data,
+    )
+        .into_response()
+}
+
+/// Assigns the given unclaimed uploads of `uploader` to the post `post_id`,
+/// which is about to be created, all or none of them
+///
+/// # Errors
+///
+/// - [`ApiError::TooManyAttachments`] if there are more than [`MAX_ATTACHMENTS_PER_POST`]
+/// - [`ApiError::NotLoggedIn`] if there are attachments but no `uploader`
+/// - [`ApiError::NotFound`] if an attachment doesn't exist, isn't from `uploader`
+///   or already belongs to a post
+#[cfg(feature = "ssr")]
+pub(super) async fn claim(
+    attachment_ids: &[u32],
+    uploader: Option<&User>,
+    post_id: u32,
+    db: &mongodb::Database,
+) -> Result<Vec<AttachmentRef>, ApiError> {
+    if attachment_ids.is_empty() {
+        return Ok(vec![]);
+    }
+    if attachment_ids.len() > MAX_ATTACHMENTS_PER_POST {
+        return Err(ApiError::TooManyAttachments);
+    }
+    let uploader = uploader.ok_or(ApiError::NotLoggedIn)?;
+
+    let attachment_col = Attachment::collection(db);
+    let mut refs = Vec::with_capacity(attachment_ids.len());
+    for &id in attachment_ids {
+        // claimed in the same update it's checked to be unclaimed in, so posts saved at the same
+        // time can't both get it
+        let claimed = attachment_col
+            .find_one_and_update(
+                bson::doc! {"id": id, "uploader_id": uploader.id, "post_id": null},
+                bson::doc! {"$set": {"post_id": post_id}},
+            )
+            .await?;
+        let Some(attachment) = claimed else {
+            // the post won't be created, so the uploads claimed so far are free again
+            attachment_col
+                .update_many(
+                    bson::doc! {"post_id": post_id},
+                    bson::doc! {"$set": {"post_id": null}},
+                )
+                .await?;
+            return Err(ApiError::NotFound("attachment".into(), id));
+        };
+        refs.push(attachment.to_ref());
+    }
+
+    Ok(refs)
+}
@@ -0,0 +1,198 @@
+//! Profile pictures of [`Users`][User]
+//!
+//! Uploaded like [`attachments`][super::attachments] through the plain axum route `upload`.
+//! The image is cropped to a square and saved once per [`AvatarSize`] as PNG,
+//! so that serving them with `download` never needs any processing
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{GetCollection, attachments, bson, helper, user::User};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The sizes avatars are stored in
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AvatarSize {
+    /// 32px, next to posts and threads
+    Small,
+    /// 128px, on the profile
+    Large,
+}
+impl AvatarSize {
+    pub const ALL: [Self; 2] = [Self::Small, Self::Large];
+
+    /// Width and height in pixels
+    #[must_use]
+    pub fn px(self) -> u32 {
+        match self {
+            Self::Small => 32,
+            Self::Large => 128,
+        }
+    }
+
+    /// Looks up the size with the given width in pixels
+    #[must_use]
+    pub fn from_px(px: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|size| size.px() == px)
+    }
+}
+
+/// Directory the avatars are stored in, set with the `AVATAR_DIR` env var
+/// (defaults to `avatars`)
+#[cfg(feature = "ssr")]
+#[must_use]
+pub fn avatar_dir() -> std::path::PathBuf {
+    std::env::var_os("AVATAR_DIR").map_or_else(|| "avatars".into(), Into::into)
+}
+
+/// Path of the avatar of `user_id` in the given size
+#[cfg(feature = "ssr")]
+fn avatar_path(user_id: u32, size: AvatarSize) -> std::path::PathBuf {
+    avatar_dir().join(format!("{user_id}-{}.png", size.px()))
+}
+
+/// Crops the image to a square and encodes it as PNG once per [`AvatarSize`]
+///
+/// Blocking, so run it with [`tokio::task::spawn_blocking`]
+#[cfg(feature = "ssr")]
+fn resize_avatar(data: &[u8]) -> Result<Vec<(AvatarSize, Vec<u8>)>, ApiError> {
+    use image::{ImageFormat, ImageReader, imageops::FilterType};
+    use std::io::Cursor;
+
+    let processing_err = |err: image::ImageError| ApiError::ImageProcessing(err.to_string());
+
+    // guards against tiny files decoding to gigantic images
+    let mut limits = image::Limits::default();
+    limits.max_image_width = Some(8192);
+    limits.max_image_height = Some(8192);
+
+    let mut reader = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()
+        .map_err(|err| ApiError::ImageProcessing(err.to_string()))?;
+    reader.limits(limits);
+    let image = reader.decode().map_err(processing_err)?;
+
+    AvatarSize::ALL
+        .into_iter()
+        .map(|size| {
+            let resized = image.resize_to_fill(size.px(), size.px(), FilterType::Lanczos3);
+            let mut png = Vec::new();
+            resized
+                .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+                .map_err(processing_err)?;
+            Ok((size, png))
+        })
+        .collect()
+}
+
+/// Validates, resizes and stores an uploaded avatar, returning the new [`User::avatar_version`]
+#[cfg(feature = "ssr")]
+async fn store_avatar(
+    db: &mongodb::Database,
+    headers: &http::HeaderMap,
+    multipart: &mut axum::extract::Multipart,
+) -> Result<u32, ApiError> {
+    let user = helper::require_user_from_headers(headers, db).await?;
+
+    let (_, data) =
+        attachments::read_file_field(multipart, attachments::MAX_ATTACHMENT_SIZE).await?;
+    if !attachments::sniff_mime(&data).is_some_and(|mime| mime.starts_with("image/")) {
+        return Err(ApiError::AttachmentTypeNotAllowed);
+    }
+
+    let variants = tokio::task::spawn_blocking(move || resize_avatar(&data))
+        .await
+        .map_err(|err| ApiError::ImageProcessing(err.to_string()))??;
+
+    let storage_err = |err: std::io::Error| ApiError::Storage(err.to_string());
+    tokio::fs::create_dir_all(avatar_dir())
+        .await
+        .map_err(storage_err)?;
+    for (size, png) in variants {
+        tokio::fs::write(avatar_path(user.id, size), png)
+            .await
+            .map_err(storage_err)?;
+    }
+
+    let version = user.avatar_version.unwrap_or_default() + 1;
+    User::collection(db)
+        .update_one(
+            bson::doc! {"id": user.id},
+            bson::doc! {"$set": {"avatar_version": version}},
+        )
+        .await?;
+
+    Ok(version)
+}
+
+/// Axum handler for `POST /avatars`, expects the image in the multipart field `file`
+///
+/// Replaces the avatar of the logged-in [`User`]. Responds with the new
+/// [`User::avatar_version`] as JSON
+///
+/// # Errors
+///
+/// Responds with an error status and the [`ApiError`] message as text
+#[cfg(feature = "ssr")]
+pub async fn upload(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    mut multipart: axum::extract::Multipart,
+) -> Result<axum::Json<u32>, (http::StatusCode, String)> {
+    store_avatar(&db, &headers, &mut multipart)
+        .await
+        .map(axum::Json)
+        .map_err(|err| attachments::upload_error_response(&err))
+}
+
+/// Axum handler for `GET /avatars/{user_id}/{px}`
+#[cfg(feature = "ssr")]
+pub async fn download(
+    axum::extract::Path((user_id, px)): axum::extract::Path<(u32, u32)>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use http::{StatusCode, header};
+
+    let Some(size) = AvatarSize::from_px(px) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(data) = tokio::fs::read(avatar_path(user_id, size)).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+            // the url changes with every new avatar, see `User::avatar_url`
+            (header::CACHE_CONTROL, "public, max-age=31536000, immutable"),
+        ],
+        data,
+    )
+        .into_response()
+}
+
+/// Removes the avatar of the logged-in [`User`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn remove_avatar() -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    User::collection(&db)
+        .update_one(
+            bson::doc! {"id": user.id},
+            bson::doc! {"$set": {"avatar_version": null}},
+        )
+        .await?;
+    for size in AvatarSize::ALL {
+        // already gone is fine
+        let _ = tokio::fs::remove_file(avatar_path(user.id, size)).await;
+    }
+
+    Ok(())
+}
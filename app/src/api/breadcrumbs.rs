@@ -0,0 +1,138 @@
+//! The chain of links above a forum or thread page, category → forum → subforums → thread
+//!
+//! A [`Category`] document holds all its forums including the subforums, which are always in the
+//! same category as their parent, so the whole chain is resolved with a single query
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{
+    Category, CollectionName, GetCollection, Thread, bson, helper, site_settings::GuestCapability,
+    user,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A single link of the breadcrumbs
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Crumb {
+    pub label: String,
+    pub href: String,
+}
+
+/// Turns a [`Category`][super::Category] name into the id of its section on the forum index,
+/// see <https://developer.mozilla.org/en-US/docs/Web/HTML/Global_attributes/id>
+///
+/// # Example
+///
+/// ```
+/// use app::api::breadcrumbs::category_anchor;
+///
+/// assert_eq!(category_anchor("Off Topic  Stuff"), "OffTopicStuff");
+/// ```
+#[must_use]
+pub fn category_anchor(name: &str) -> String {
+    name.split_ascii_whitespace().collect()
+}
+
+/// The crumbs of `category` down to the [`Forum`][super::Forum] with `forum_id`
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if the forum isn't in `category` or `user` can't see it
+#[cfg(feature = "ssr")]
+fn forum_chain(
+    category: Category,
+    forum_id: u32,
+    user: Option<&user::User>,
+) -> Result<Vec<Crumb>, ApiError> {
+    if !helper::can_see_forum(&category.forums, forum_id, user) {
+        return Err(ApiError::NotFound("forum".into(), forum_id));
+    }
+    let mut crumbs = vec![Crumb {
+        href: format!("/forum#{}", category_anchor(&category.name)),
+        label: category.name,
+    }];
+    crumbs.extend(
+        helper::forum_ancestry(&category.forums, forum_id)
+            .into_iter()
+            .map(|forum| Crumb {
+                label: forum.name,
+                href: format!("/forum/{}", forum.id),
+            }),
+    );
+    Ok(crumbs)
+}
+
+/// Returns the breadcrumbs of a [`Forum`][super::Forum], from its category down to itself
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `forum_id` isn't in use or the user can't see it
+/// - [`ApiError::LoginRequired`] if guests can't see forums
+#[server]
+pub async fn get_forum_breadcrumbs(forum_id: u32) -> Result<Vec<Crumb>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Forums, &db).await?;
+
+    let category = Category::collection(&db)
+        .find_one(bson::doc! {"forums.id": forum_id})
+        .await?
+        .ok_or(ApiError::NotFound("forum".into(), forum_id))?;
+
+    forum_chain(category, forum_id, user.as_ref())
+}
+
+/// Returns the breadcrumbs of a [`Thread`], from the category of its forum down to itself
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use or the user can't see it,
+///   the same as for [`get_thread`][super::get_thread]
+/// - [`ApiError::LoginRequired`] if guests can't see threads
+#[server]
+pub async fn get_thread_breadcrumbs(thread_id: u32) -> Result<Vec<Crumb>, ApiError> {
+    #[derive(Deserialize)]
+    struct Joined {
+        thread: Thread,
+        category: Category,
+    }
+
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+
+    let pipeline = vec![
+        bson::doc! {"$match": {"id": thread_id}},
+        bson::doc! {"$lookup": {
+            "from": Category::collection_name(),
+            "localField": "forum_id",
+            "foreignField": "forums.id",
+            "as": "categories",
+        }},
+        bson::doc! {"$project": {"_id": 0, "thread": "$$ROOT", "category": {"$first": "$categories"}}},
+        bson::doc! {"$unset": "thread.categories"},
+    ];
+    let mut cursor = Thread::collection(&db)
+        .aggregate(pipeline)
+        .with_type::<Joined>()
+        .await?;
+    let not_found = || ApiError::NotFound("thread".into(), thread_id);
+    if !cursor.advance().await? {
+        return Err(not_found());
+    }
+    let Joined { thread, category } = cursor.deserialize_current()?;
+
+    let is_moderator = user
+        .as_ref()
+        .is_some_and(|user| user.role >= user::Role::Moderator);
+    if thread.pending_review || (thread.deleted_at.is_some() && !is_moderator) {
+        return Err(not_found());
+    }
+    let mut crumbs =
+        forum_chain(category, thread.forum_id, user.as_ref()).map_err(|_| not_found())?;
+    crumbs.push(Crumb {
+        label: thread.display_subject(),
+        href: format!("/thread/{}", thread.id),
+    });
+    Ok(crumbs)
+}
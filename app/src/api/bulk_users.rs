@@ -0,0 +1,292 @@
+//! Admin tools for finding [`Users`][User] by their [`AccountDetails`] or registration
+//! date and applying a [`BulkAction`] to all of them at once, e.g. after a spam wave
+//!
+//! Every action can be run as a dry run first to preview who would be affected.
+//! Only [`Role::Member`]s are ever matched, staff accounts are left alone
+
+use super::ApiError;
+use super::user::{AccountDetails, User};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, Post, Thread, bson, cache,
+    groups::UserGroup,
+    helper, mod_reasons,
+    user::{self, Role, Sanction},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What to do with all matched [`Users`][User]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BulkAction {
+    /// Sets [`User::verified`]
+    Verify,
+    /// Permanently bans them
+    Ban,
+    /// Deletes the accounts with all their [`Posts`][super::Post] and [`Threads`][super::Thread]
+    DeleteSpam,
+    /// Makes the given [`UserGroup`][super::groups::UserGroup] their only and primary group
+    MoveToGroup,
+}
+
+/// A matched [`User`] with their private details, if there are any
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MatchedUser {
+    pub user: User,
+    pub details: Option<AccountDetails>,
+}
+
+/// The result of [`bulk_user_action`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BulkReport {
+    pub users: Vec<MatchedUser>,
+    /// Number of posts written by the matched users
+    pub post_count: u64,
+    /// Number of threads started by the matched users
+    pub thread_count: u64,
+    /// `false` for dry runs
+    pub applied: bool,
+}
+
+/// Parses a `YYYY-MM-DD` date as the start of that day in UTC
+#[cfg(feature = "ssr")]
+fn parse_date(date: &str) -> Result<jiff::Timestamp, ApiError> {
+    date.parse::<jiff::civil::Date>()
+        .and_then(|date| date.to_zoned(jiff::tz::TimeZone::UTC))
+        .map(|zoned| zoned.timestamp())
+        .map_err(|_| ApiError::InvalidDate(date.to_string()))
+}
+
+/// Finds all [`Users`][User] with [`Role::Member`] matching every given filter
+///
+/// `email` matches anywhere in the address, `ip` matches its start so that
+/// whole ranges like `203.0.113.` can be found. The registration window includes
+/// both `registered_from` and `registered_to` (`YYYY-MM-DD`)
+#[cfg(feature = "ssr")]
+async fn find_users(
+    email: Option<String>,
+    ip: Option<String>,
+    registered_from: Option<String>,
+    registered_to: Option<String>,
+    db: &mongodb::Database,
+) -> Result<Vec<MatchedUser>, ApiError> {
+    // empty form fields arrive as empty strings
+    let non_empty = |field: Option<String>| {
+        field
+            .map(|field| field.trim().to_string())
+            .filter(|field| !field.is_empty())
+    };
+    let (email, ip) = (non_empty(email), non_empty(ip));
+    let (registered_from, registered_to) = (non_empty(registered_from), non_empty(registered_to));
+    if email.is_none() && ip.is_none() && registered_from.is_none() && registered_to.is_none() {
+        return Err(ApiError::EmptyFilter);
+    }
+
+    let details_col = AccountDetails::collection(db);
+    let Ok(member) = bson::to_bson(&Role::Member) else {
+        unreachable!("a unit enum is always serializable")
+    };
+    let mut user_filter = bson::doc! {"role": member};
+
+    if email.is_some() || ip.is_some() {
+        let mut details_filter = bson::doc! {};
+        if let Some(email) = email {
+            details_filter.insert(
+                "email",
+                bson::doc! {"$regex": helper::escape_regex(&email), "$options": "i"},
+            );
+        }
+        if let Some(ip) = ip {
+            details_filter.insert(
+                "registration_ip",
+                bson::doc! {"$regex": format!("^{}", helper::escape_regex(&ip))},
+            );
+        }
+        let mut details_cursor = details_col.find(details_filter).await?;
+        let mut user_ids = vec![];
+        while details_cursor.advance().await? {
+            let details: AccountDetails = details_cursor.deserialize_current()?;
+            user_ids.push(details.user_id);
+        }
+        user_filter.insert("id", bson::doc! {"$in": user_ids});
+    }
+
+    let mut created_at = bson::doc! {};
+    if let Some(from) = registered_from {
+        let from = parse_date(&from)?;
+        created_at.insert("$gte", bson::DateTime::from_millis(from.as_millisecond()));
+    }
+    if let Some(to) = registered_to {
+        // inclusive, so up to the start of the next day
+        let to = parse_date(&to)? + jiff::SignedDuration::from_hours(24);
+        created_at.insert("$lt", bson::DateTime::from_millis(to.as_millisecond()));
+    }
+    if !created_at.is_empty() {
+        user_filter.insert("created_at", created_at);
+    }
+
+    let mut user_cursor = User::collection(db)
+        .find(user_filter)
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    let mut users = vec![];
+    while user_cursor.advance().await? {
+        let user: User = user_cursor.deserialize_current()?;
+        let details = details_col
+            .find_one(bson::doc! {"user_id": user.id})
+            .await?;
+        users.push(MatchedUser { user, details });
+    }
+
+    Ok(users)
+}
+
+/// Deletes everything the given users wrote: their [`Threads`][Thread] with all
+/// posts in them and their posts in other threads
+#[cfg(feature = "ssr")]
+async fn delete_content(user_ids: &[u32], db: &mongodb::Database) -> Result<(), ApiError> {
+    let thread_col = Thread::collection(db);
+    let post_col = Post::collection(db);
+
+    let mut forum_ids = vec![];
+    let mut thread_ids = vec![];
+    let mut thread_cursor = thread_col
+        .find(bson::doc! {"author_id": {"$in": user_ids}})
+        .await?;
+    while thread_cursor.advance().await? {
+        let thread: Thread = thread_cursor.deserialize_current()?;
+        thread_ids.push(thread.id);
+        forum_ids.push(thread.forum_id);
+    }
+
+    // posts in other threads change which post is the latest one there
+    let mut post_cursor = post_col
+        .find(bson::doc! {"author_id": {"$in": user_ids}})
+        .await?;
+    while post_cursor.advance().await? {
+        let post: Post = post_cursor.deserialize_current()?;
+        let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+        forum_ids.push(thread.forum_id);
+    }
+    forum_ids.sort_unstable();
+    forum_ids.dedup();
+
+    post_col
+        .delete_many(bson::doc! {"$or": [
+            {"author_id": {"$in": user_ids}},
+            {"thread_id": {"$in": &thread_ids}},
+        ]})
+        .await?;
+    thread_col
+        .delete_many(bson::doc! {"id": {"$in": &thread_ids}})
+        .await?;
+
+    helper::refresh_latest_ids(&forum_ids, db).await?;
+    cache::invalidate_forums(&forum_ids, db).await
+}
+
+/// Applies `action` to all [`Users`][User] matching the filters (see [`find_users`])
+///
+/// Only previews the affected users and content unless `apply` is set.
+/// `reason` and the [`ModReason`][super::mod_reasons::ModReason] with `reason_id` are used
+/// for [`BulkAction::Ban`], `group_id` for [`BulkAction::MoveToGroup`]
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptyFilter`] if no filter is given
+/// - [`ApiError::InvalidDate`] if a date isn't `YYYY-MM-DD`
+/// - [`ApiError::NoGroupSelected`] / [`ApiError::NotFound`] if moving to a group
+///   without a valid `group_id`
+/// - [`ApiError::NotFound`] if banning with a reason that can't be picked
+#[allow(clippy::too_many_arguments, reason = "every form field is an argument")]
+#[server]
+pub async fn bulk_user_action(
+    email: Option<String>,
+    ip: Option<String>,
+    registered_from: Option<String>,
+    registered_to: Option<String>,
+    action: BulkAction,
+    #[server(default)] reason: String,
+    #[server(default)] reason_id: Option<u32>,
+    group_id: Option<u32>,
+    #[server(default)] apply: bool,
+) -> Result<BulkReport, ApiError> {
+    let db = helper::get_db()?;
+    let admin = helper::require_role(&db, Role::Admin).await?;
+
+    if action == BulkAction::MoveToGroup {
+        let group_id = group_id.ok_or(ApiError::NoGroupSelected)?;
+        if UserGroup::collection(&db)
+            .find_one(bson::doc! {"id": group_id})
+            .await?
+            .is_none()
+        {
+            return Err(ApiError::NotFound("user group".into(), group_id));
+        }
+    }
+    if action == BulkAction::Ban {
+        mod_reasons::check_reason(reason_id, &db).await?;
+    }
+
+    let users = find_users(email, ip, registered_from, registered_to, &db).await?;
+    let user_ids: Vec<u32> = users.iter().map(|matched| matched.user.id).collect();
+
+    let by_author = bson::doc! {"author_id": {"$in": &user_ids}};
+    let post_count = Post::collection(&db)
+        .count_documents(by_author.clone())
+        .await?;
+    let thread_count = Thread::collection(&db).count_documents(by_author).await?;
+
+    if apply && !user_ids.is_empty() {
+        let user_col = User::collection(&db);
+        let by_id = bson::doc! {"id": {"$in": &user_ids}};
+        match action {
+            BulkAction::Verify => {
+                user_col
+                    .update_many(by_id, bson::doc! {"$set": {"verified": true}})
+                    .await?;
+            }
+            BulkAction::Ban => {
+                let reason = if reason.trim().is_empty() {
+                    "Spam".to_string()
+                } else {
+                    reason
+                };
+                let ban = Sanction {
+                    reason,
+                    reason_id,
+                    until: None,
+                    issued_by: admin.id,
+                };
+                let Ok(ban) = bson::to_bson(&ban) else {
+                    unreachable!("a Sanction is always serializable")
+                };
+                user_col
+                    .update_many(by_id, bson::doc! {"$set": {"ban": ban}})
+                    .await?;
+            }
+            BulkAction::DeleteSpam => {
+                delete_content(&user_ids, &db).await?;
+                user::delete_accounts(&user_ids, &db).await?;
+            }
+            BulkAction::MoveToGroup => {
+                user_col
+                    .update_many(
+                        by_id,
+                        bson::doc! {"$set": {"group_ids": [group_id], "primary_group_id": group_id}},
+                    )
+                    .await?;
+            }
+        }
+    }
+
+    Ok(BulkReport {
+        users,
+        post_count,
+        thread_count,
+        applied: apply,
+    })
+}
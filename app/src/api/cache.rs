@@ -0,0 +1,179 @@
+//! In-memory caches of expensive queries, shared by all requests through leptos context
+//!
+//! Writes invalidate the affected entries, which are then recomputed right away
+//! in the background ("warmed up"), so the first visitor after a write
+//! doesn't have to wait for the cold query.
+//! All entries are also warmed up once on startup, see [`Caches::warm_up`]
+
+use super::{ActivitySummary, ApiError, Category, Database, GetCollection, bson, helper};
+
+use leptos::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Thread and post count of a [`Forum`][super::Forum],
+/// see [`helper::count_threads_and_posts_of_forum`]
+pub type ForumSummary = (u64, u64);
+
+/// All caches, cheap to clone
+#[derive(Clone, Debug, Default)]
+pub struct Caches {
+    forum_summaries: Arc<RwLock<HashMap<u32, ForumSummary>>>,
+    activity_summary: Arc<RwLock<Option<ActivitySummary>>>,
+}
+impl Caches {
+    /// Returns the cached [`ForumSummary`] of `forum_id`, computing it on a miss
+    ///
+    /// # Errors
+    ///
+    /// * [`ApiError::Db`] if the db connection fails in any way
+    pub async fn forum_summary(
+        &self,
+        forum_id: u32,
+        db: &Database,
+    ) -> Result<ForumSummary, ApiError> {
+        if let Some(summary) = self.read_summaries().get(&forum_id) {
+            return Ok(*summary);
+        }
+        self.refresh_forum_summary(forum_id, db, false).await
+    }
+
+    /// Recomputes and caches the [`ForumSummary`] of `forum_id`,
+    /// from the primary if `fresh`, see [`helper::count_threads_and_posts_of_forum`]
+    async fn refresh_forum_summary(
+        &self,
+        forum_id: u32,
+        db: &Database,
+        fresh: bool,
+    ) -> Result<ForumSummary, ApiError> {
+        let summary = helper::count_threads_and_posts_of_forum(forum_id, db.clone(), fresh).await?;
+        self.forum_summaries
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(forum_id, summary);
+        Ok(summary)
+    }
+
+    fn read_summaries(&self) -> std::sync::RwLockReadGuard<'_, HashMap<u32, ForumSummary>> {
+        // the map is always left in a valid state, so poisoning doesn't matter
+        self.forum_summaries
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Returns the cached [`ActivitySummary`], computing it on a miss
+    ///
+    /// # Errors
+    ///
+    /// * [`ApiError::Db`] if the db connection fails in any way
+    pub async fn activity_summary(&self, db: &Database) -> Result<ActivitySummary, ApiError> {
+        let cached = *self
+            .activity_summary
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match cached {
+            Some(summary) => Ok(summary),
+            None => self.refresh_activity_summary(db, false).await,
+        }
+    }
+
+    /// Recomputes and caches the [`ActivitySummary`],
+    /// from the primary if `fresh`, see [`helper::count_activity`]
+    async fn refresh_activity_summary(
+        &self,
+        db: &Database,
+        fresh: bool,
+    ) -> Result<ActivitySummary, ApiError> {
+        let summary = helper::count_activity(db, fresh).await?;
+        *self
+            .activity_summary
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(summary);
+        Ok(summary)
+    }
+
+    /// Drops the cached summaries of the given forums and the [`ActivitySummary`]
+    /// and warms them up again in the background
+    pub fn invalidate_forums(&self, forum_ids: &[u32], db: &Database) {
+        *self
+            .activity_summary
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+        {
+            let mut summaries = self
+                .forum_summaries
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            for forum_id in forum_ids {
+                summaries.remove(forum_id);
+            }
+        }
+
+        let (caches, db, forum_ids) = (self.clone(), db.clone(), forum_ids.to_vec());
+        tokio::spawn(async move {
+            if let Err(err) = caches.refresh_activity_summary(&db, true).await {
+                tracing::warn!("warming up activity summary failed: {err}");
+            }
+            for forum_id in forum_ids {
+                // a replica might not have the write yet
+                if let Err(err) = caches.refresh_forum_summary(forum_id, &db, true).await {
+                    tracing::warn!("warming up summary of forum {forum_id} failed: {err}");
+                }
+            }
+        });
+    }
+
+    /// Fills all caches, meant to be run on startup
+    ///
+    /// # Errors
+    ///
+    /// * [`ApiError::Db`] if the db connection fails in any way
+    pub async fn warm_up(&self, db: &Database) -> Result<(), ApiError> {
+        self.refresh_activity_summary(db, false).await?;
+        let mut cursor = Category::collection(db).find(bson::doc! {}).await?;
+        while cursor.advance().await? {
+            let category: Category = cursor.deserialize_current()?;
+            for forum in category.forums {
+                self.refresh_forum_summary(forum.id, db, false).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Invalidates the summaries of the given forums and the forums above them
+/// in the [`Caches`] in context, if there are any
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn invalidate_forums(forum_ids: &[u32], db: &Database) -> Result<(), ApiError> {
+    match use_context::<Caches>() {
+        Some(caches) => invalidate_forums_in(&caches, forum_ids, db).await,
+        None => Ok(()),
+    }
+}
+
+/// Invalidates the summaries of the given forums and the forums above them in `caches`,
+/// for plain axum handlers without leptos context
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn invalidate_forums_in(
+    caches: &Caches,
+    forum_ids: &[u32],
+    db: &Database,
+) -> Result<(), ApiError> {
+    // counts include the subforums
+    let forums = helper::get_all_forums(db).await?;
+    let mut affected: Vec<u32> = forum_ids
+        .iter()
+        .flat_map(|&forum_id| helper::forum_ancestry(&forums, forum_id))
+        .map(|forum| forum.id)
+        .collect();
+    affected.sort_unstable();
+    affected.dedup();
+    caches.invalidate_forums(&affected, db);
+    Ok(())
+}
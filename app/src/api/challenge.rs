@@ -0,0 +1,372 @@
+//! Challenges guests have to pass before [creating threads][super::create_thread]
+//! or [posts][super::create_post], keeping spam bots out of instances that let guests write
+//!
+//! Admins pick the [`ChallengeProvider`] in the
+//! [`SiteSettings`][super::site_settings::SiteSettings]: a CAPTCHA of hCaptcha or
+//! Cloudflare Turnstile, whose secret key is read from the `CHALLENGE_SECRET_KEY` env var,
+//! or a proof of work solved by the browser without any third party.
+//! The composers render it with `ChallengeWidget`, which submits the answer as
+//! [`CHALLENGE_FIELD`], and the server functions check it with [`verify`].
+//! Logged-in [`Users`][super::user::User] never get a challenge
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{
+    CollectionName, DbErrorCode, GetCollection, bson, helper, site_settings, user::User, webhooks,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Name of the form field and server function argument the answer is submitted as
+pub const CHALLENGE_FIELD: &str = "challenge_response";
+/// How long a proof of work nonce can be solved
+#[cfg(feature = "ssr")]
+const NONCE_MINUTES: i64 = 30;
+/// How long the CAPTCHA provider may take to answer
+#[cfg(feature = "ssr")]
+const VERIFY_TIMEOUT_SECS: u64 = 10;
+
+/// How guests prove they're human, set by admins
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub enum ChallengeProvider {
+    #[default]
+    Off,
+    /// Finding a number whose hash with a nonce starts with `difficulty` zero bits,
+    /// see [`solves`]
+    ProofOfWork {
+        difficulty: u8,
+    },
+    HCaptcha {
+        site_key: String,
+    },
+    Turnstile {
+        site_key: String,
+    },
+}
+impl ChallengeProvider {
+    /// Default difficulty of the proof of work, a fraction of a second in browsers
+    pub const DEFAULT_DIFFICULTY: u8 = 16;
+    /// Highest difficulty admins can set, already taking several seconds
+    pub const MAX_DIFFICULTY: u8 = 24;
+
+    #[must_use]
+    pub const fn kind(&self) -> ChallengeKind {
+        match self {
+            Self::Off => ChallengeKind::Off,
+            Self::ProofOfWork { .. } => ChallengeKind::ProofOfWork,
+            Self::HCaptcha { .. } => ChallengeKind::HCaptcha,
+            Self::Turnstile { .. } => ChallengeKind::Turnstile,
+        }
+    }
+}
+
+/// A [`ChallengeProvider`] without its settings
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChallengeKind {
+    Off,
+    ProofOfWork,
+    HCaptcha,
+    Turnstile,
+}
+impl ChallengeKind {
+    pub const ALL: [Self; 4] = [
+        Self::Off,
+        Self::ProofOfWork,
+        Self::HCaptcha,
+        Self::Turnstile,
+    ];
+
+    /// Translation key of the name of the provider for the admin area
+    #[must_use]
+    pub const fn label_key(self) -> &'static str {
+        match self {
+            Self::Off => "challenge.provider.off",
+            Self::ProofOfWork => "challenge.provider.proof_of_work",
+            Self::HCaptcha => "challenge.provider.h_captcha",
+            Self::Turnstile => "challenge.provider.turnstile",
+        }
+    }
+}
+
+/// A [`ChallengeProvider::ProofOfWork`] nonce that was already solved, kept until it expires
+/// so the same solution can't be submitted over and over
+///
+/// Nonces that are only issued aren't saved, see [`issue_pow_nonce`]
+#[cfg(feature = "ssr")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct RedeemedNonce {
+    nonce: String,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    expires_at: jiff::Timestamp,
+}
+#[cfg(feature = "ssr")]
+impl CollectionName for RedeemedNonce {
+    fn collection_name() -> &'static str {
+        "redeemed_pow_nonces"
+    }
+}
+
+/// Creates the indexes of the redeemed nonces:
+/// each can only be redeemed once and mongodb drops them when they expire
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn create_indexes(db: &mongodb::Database) -> Result<(), ApiError> {
+    use mongodb::{IndexModel, options::IndexOptions};
+
+    RedeemedNonce::collection(db)
+        .create_indexes([
+            IndexModel::builder()
+                .keys(bson::doc! {"nonce": 1})
+                .options(IndexOptions::builder().unique(true).build())
+                .build(),
+            IndexModel::builder()
+                .keys(bson::doc! {"expires_at": 1})
+                .options(
+                    IndexOptions::builder()
+                        .expire_after(std::time::Duration::ZERO)
+                        .build(),
+                )
+                .build(),
+        ])
+        .await?;
+    Ok(())
+}
+
+/// Key of the nonce signatures, set with the `CHALLENGE_NONCE_SECRET` env var
+///
+/// Without it a random one is used, so nonces stop working after a restart
+/// and only work on the instance that handed them out
+#[cfg(feature = "ssr")]
+fn nonce_secret() -> &'static str {
+    static SECRET: std::sync::LazyLock<String> = std::sync::LazyLock::new(|| {
+        std::env::var("CHALLENGE_NONCE_SECRET")
+            .ok()
+            .filter(|secret| !secret.is_empty())
+            .unwrap_or_else(helper::random_token)
+    });
+    &SECRET
+}
+
+/// The signature of the random part of a nonce that works until `expires`
+#[cfg(feature = "ssr")]
+fn nonce_signature(random: &str, expires: i64) -> String {
+    webhooks::sign(nonce_secret(), format!("{random}.{expires}").as_bytes())
+}
+
+/// Returns when `nonce` expires if it was signed by [`issue_pow_nonce`]
+#[cfg(feature = "ssr")]
+fn nonce_expiry(nonce: &str) -> Option<jiff::Timestamp> {
+    let (random, rest) = nonce.split_once('.')?;
+    let (expires, sig) = rest.split_once('.')?;
+    let expires = expires.parse().ok()?;
+    helper::secrets_match(sig, &nonce_signature(random, expires))
+        .then(|| jiff::Timestamp::from_second(expires).ok())
+        .flatten()
+}
+
+/// Counts the zero bits `hash` starts with
+///
+/// # Example
+///
+/// ```
+/// use app::api::challenge::leading_zero_bits;
+///
+/// assert_eq!(leading_zero_bits(&[0, 0, 0b0001_0000, 0xff]), 19);
+/// assert_eq!(leading_zero_bits(&[0xff]), 0);
+/// assert_eq!(leading_zero_bits(&[0, 0]), 16);
+/// ```
+#[must_use]
+pub fn leading_zero_bits(hash: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// Whether the SHA-256 of `{nonce}:{counter}` starts with `difficulty` zero bits
+#[must_use]
+pub fn solves(nonce: &str, counter: u64, difficulty: u8) -> bool {
+    use sha2::Digest;
+
+    let hash = sha2::Sha256::digest(format!("{nonce}:{counter}"));
+    leading_zero_bits(&hash) >= u32::from(difficulty)
+}
+
+/// Finds the first counter that [`solves`] the proof of work of `nonce`,
+/// taking about `2^difficulty` hashes
+///
+/// # Example
+///
+/// ```
+/// use app::api::challenge::{solve, solves};
+///
+/// let counter = solve("c0ffee", 8);
+/// assert!(solves("c0ffee", counter, 8));
+/// assert!((0..counter).all(|counter| !solves("c0ffee", counter, 8)));
+/// ```
+#[must_use]
+pub fn solve(nonce: &str, difficulty: u8) -> u64 {
+    (0..)
+        .find(|counter| solves(nonce, *counter, difficulty))
+        .unwrap_or_default()
+}
+
+/// What the `ChallengeWidget` has to show for the current visitor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Challenge {
+    /// Logged in or challenges are off
+    None,
+    /// Solve a nonce of [`issue_pow_nonce`]
+    ProofOfWork {
+        difficulty: u8,
+    },
+    HCaptcha {
+        site_key: String,
+    },
+    Turnstile {
+        site_key: String,
+    },
+}
+
+/// Returns the [`Challenge`] the current visitor has to pass to write
+#[server]
+pub async fn get_challenge() -> Result<Challenge, ApiError> {
+    let db = helper::get_db()?;
+    if helper::get_current_user(&db).await?.is_some() {
+        return Ok(Challenge::None);
+    }
+    let challenge = match site_settings::load_settings(&db).await?.challenge {
+        ChallengeProvider::Off => Challenge::None,
+        ChallengeProvider::ProofOfWork { difficulty } => Challenge::ProofOfWork { difficulty },
+        ChallengeProvider::HCaptcha { site_key } => Challenge::HCaptcha { site_key },
+        ChallengeProvider::Turnstile { site_key } => Challenge::Turnstile { site_key },
+    };
+    Ok(challenge)
+}
+
+/// Returns a new nonce for a [`Challenge::ProofOfWork`], which can be solved once
+/// within 30 minutes and is answered as `{nonce}:{counter}`, see [`solve`]
+///
+/// The nonce is `{random}.{expires}.{signature}`, so nothing is saved until it's redeemed
+#[server]
+pub async fn issue_pow_nonce() -> Result<String, ApiError> {
+    let random = helper::random_token();
+    let expires =
+        (jiff::Timestamp::now() + jiff::SignedDuration::from_mins(NONCE_MINUTES)).as_second();
+    Ok(format!(
+        "{random}.{expires}.{}",
+        nonce_signature(&random, expires)
+    ))
+}
+
+/// Answer of the `siteverify` endpoints of hCaptcha and Turnstile
+#[cfg(feature = "ssr")]
+#[derive(Deserialize)]
+struct SiteVerify {
+    success: bool,
+}
+
+/// Asks the CAPTCHA provider at `url` whether `response` is a valid answer
+#[cfg(feature = "ssr")]
+async fn verify_captcha(url: &str, response: &str) -> Result<bool, String> {
+    static CLIENT: std::sync::LazyLock<reqwest::Client> = std::sync::LazyLock::new(|| {
+        reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(VERIFY_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default()
+    });
+
+    let Some(secret) = std::env::var("CHALLENGE_SECRET_KEY")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+    else {
+        return Err("CHALLENGE_SECRET_KEY isn't set".to_string());
+    };
+    let mut form = vec![("secret", secret), ("response", response.to_string())];
+    if let Some(ip) = helper::get_client_ip() {
+        form.push(("remoteip", ip));
+    }
+    let verdict: SiteVerify = CLIENT
+        .post(url)
+        .form(&form)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| err.to_string())?
+        .json()
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(verdict.success)
+}
+
+/// Checks that `response` passes the [`ChallengeProvider`], unless `author` is logged in
+///
+/// # Errors
+///
+/// * [`ApiError::ChallengeFailed`] if it doesn't, or the provider can't be asked
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn verify(
+    author: Option<&User>,
+    response: &str,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    if author.is_some() {
+        return Ok(());
+    }
+    let url = match site_settings::load_settings(db).await?.challenge {
+        ChallengeProvider::Off => return Ok(()),
+        ChallengeProvider::ProofOfWork { difficulty } => {
+            let Some((nonce, counter)) = response.split_once(':') else {
+                return Err(ApiError::ChallengeFailed);
+            };
+            let Ok(counter) = counter.parse() else {
+                return Err(ApiError::ChallengeFailed);
+            };
+            let Some(expires_at) = nonce_expiry(nonce) else {
+                return Err(ApiError::ChallengeFailed);
+            };
+            if expires_at <= jiff::Timestamp::now() || !solves(nonce, counter, difficulty) {
+                return Err(ApiError::ChallengeFailed);
+            }
+            let redeemed = RedeemedNonce {
+                nonce: nonce.to_string(),
+                expires_at,
+            };
+            // single use, the unique index rejects nonces that were already redeemed
+            return match RedeemedNonce::collection(db).insert_one(&redeemed).await {
+                Ok(_) => Ok(()),
+                Err(err) => match ApiError::from(err) {
+                    ApiError::Db {
+                        code: DbErrorCode::DuplicateKey,
+                        ..
+                    } => Err(ApiError::ChallengeFailed),
+                    err => Err(err),
+                },
+            };
+        }
+        ChallengeProvider::HCaptcha { .. } => "https://api.hcaptcha.com/siteverify",
+        ChallengeProvider::Turnstile { .. } => {
+            "https://challenges.cloudflare.com/turnstile/v0/siteverify"
+        }
+    };
+    if response.is_empty() {
+        return Err(ApiError::ChallengeFailed);
+    }
+    match verify_captcha(url, response).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(ApiError::ChallengeFailed),
+        Err(err) => {
+            tracing::warn!("verifying a CAPTCHA failed: {err}");
+            Err(ApiError::ChallengeFailed)
+        }
+    }
+}
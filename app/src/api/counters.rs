@@ -0,0 +1,185 @@
+//! Admin overview of the [`Counters`][super::Counter] handing out ids, see [`helper::get_and_increment_id_of`]
+//!
+//! A counter whose sequence is below the highest id actually in use ("behind"),
+//! e.g. after restoring a backup or importing data by hand, would hand out ids that are taken.
+//! [`resync_counter`] fixes that by raising the sequence to the highest id.
+//! Counters are never lowered, ids of deleted things may still be referenced elsewhere
+//!
+//! [`helper::get_and_increment_id_of`]: super::helper::get_and_increment_id_of
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{
+    CollectionName, Counter, GetCollection, Post, Thread, announcements, api_keys, attachments,
+    bson, forum_archive, groups, helper, mod_log, mod_reasons, reactions, scheduling, user,
+    webhooks, word_filter,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Counters that are shown even before their first use, with the collection they hand out ids for
+#[cfg(feature = "ssr")]
+fn known_counters() -> [(&'static str, &'static str); 14] {
+    [
+        ("post", Post::collection_name()),
+        ("thread", Thread::collection_name()),
+        ("user", user::User::collection_name()),
+        ("user_group", groups::UserGroup::collection_name()),
+        ("attachment", attachments::Attachment::collection_name()),
+        ("announcement", announcements::Announcement::collection_name()),
+        ("scheduled_post", scheduling::ScheduledPost::collection_name()),
+        ("word_filter_rule", word_filter::WordFilterRule::collection_name()),
+        ("reaction_option", reactions::ReactionOption::collection_name()),
+        ("forum_archive", forum_archive::ForumArchive::collection_name()),
+        ("api_key", api_keys::ApiKey::collection_name()),
+        ("webhook", webhooks::Webhook::collection_name()),
+        ("mod_log_export", mod_log::ModLogExport::collection_name()),
+        ("mod_reason", mod_reasons::ModReason::collection_name()),
+    ]
+}
+
+/// A [`Counter`][super::Counter] compared to the ids in use
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CounterStatus {
+    /// Name of the counter, e.g. `post`
+    pub category: String,
+    /// The last handed out id, 0 if it wasn't used yet
+    pub sequence: u32,
+    /// Highest id in the counted collection, [`None`] if it's empty
+    /// or the counter isn't known to belong to a collection
+    pub max_id: Option<u32>,
+}
+impl CounterStatus {
+    /// Whether the next id handed out could already be in use
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::counters::CounterStatus;
+    ///
+    /// let status = |sequence, max_id| CounterStatus { category: "post".into(), sequence, max_id };
+    ///
+    /// assert!(status(5, Some(7)).is_behind());
+    /// // deleted posts leave gaps, that's fine
+    /// assert!(!status(9, Some(7)).is_behind());
+    /// assert!(!status(0, None).is_behind());
+    /// ```
+    #[must_use]
+    pub fn is_behind(&self) -> bool {
+        self.max_id.is_some_and(|max_id| self.sequence < max_id)
+    }
+}
+
+/// Just the id of a document
+#[cfg(feature = "ssr")]
+#[derive(Deserialize)]
+struct IdOnly {
+    id: u32,
+}
+
+/// Returns the highest `id` in the collection with the given name
+#[cfg(feature = "ssr")]
+async fn max_id_in(collection: &str, db: &mongodb::Database) -> Result<Option<u32>, ApiError> {
+    let doc = db
+        .collection::<IdOnly>(collection)
+        .find_one(bson::doc! {})
+        .sort(bson::doc! {"id": -1})
+        .projection(bson::doc! {"id": 1})
+        .await?;
+    Ok(doc.map(|doc| doc.id))
+}
+
+/// Returns the [`CounterStatus`] of `category`
+#[cfg(feature = "ssr")]
+async fn status_of(category: &str, db: &mongodb::Database) -> Result<CounterStatus, ApiError> {
+    let sequence = Counter::collection(db)
+        .find_one(bson::doc! {"category": category})
+        .await?
+        .map_or(0, |counter| counter.sequence);
+    let max_id = match known_counters()
+        .into_iter()
+        .find(|(known, _)| *known == category)
+    {
+        Some((_, collection)) => max_id_in(collection, db).await?,
+        None => None,
+    };
+
+    Ok(CounterStatus {
+        category: category.to_string(),
+        sequence,
+        max_id,
+    })
+}
+
+/// Fetches the [`CounterStatus`] of every counter in use and all known ones
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+#[server]
+pub async fn get_counters() -> Result<Vec<CounterStatus>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+
+    let mut categories: Vec<String> = known_counters()
+        .into_iter()
+        .map(|(category, _)| category.to_string())
+        .collect();
+    let mut cursor = Counter::collection(&db).find(bson::doc! {}).await?;
+    while cursor.advance().await? {
+        let counter: Counter = cursor.deserialize_current()?;
+        if !categories.contains(&counter.category) {
+            categories.push(counter.category);
+        }
+    }
+    categories.sort_unstable();
+
+    let mut counters = vec![];
+    for category in categories {
+        counters.push(status_of(&category, &db).await?);
+    }
+
+    Ok(counters)
+}
+
+/// Raises the sequence of the counter `category` to the highest id in use
+///
+/// Only applied if the sequence is still `expected_sequence`, i.e. what the admin saw
+///
+/// Requires [`Role::Admin`][user::Role::Admin]
+///
+/// # Errors
+///
+/// - [`ApiError::UnknownCounter`] if `category` doesn't belong to a known collection
+/// - [`ApiError::CounterNotBehind`] if there's nothing to fix
+/// - [`ApiError::CounterChanged`] if the sequence isn't `expected_sequence` anymore
+#[server]
+pub async fn resync_counter(category: String, expected_sequence: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, user::Role::Admin).await?;
+
+    if !known_counters()
+        .iter()
+        .any(|(known, _)| *known == category)
+    {
+        return Err(ApiError::UnknownCounter(category));
+    }
+    let status = status_of(&category, &db).await?;
+    let Some(max_id) = status.max_id.filter(|_| status.is_behind()) else {
+        return Err(ApiError::CounterNotBehind(category));
+    };
+    if status.sequence != expected_sequence {
+        return Err(ApiError::CounterChanged(category));
+    }
+
+    // $max, so ids handed out in the meantime are never undone
+    Counter::collection(&db)
+        .update_one(
+            bson::doc! {"category": &category},
+            bson::doc! {"$max": {"sequence": max_id}},
+        )
+        .upsert(true)
+        .await?;
+    tracing::info!("resynced counter {category} from {expected_sequence} to {max_id}");
+
+    Ok(())
+}
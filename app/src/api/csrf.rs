@@ -0,0 +1,181 @@
+//! Protection against cross-site request forgery, i.e. other sites making a member's browser
+//! submit forms to the forum with their session
+//!
+//! The shell issues a random token per browser with [`current_token`], kept in the
+//! [`CSRF_COOKIE`] and rendered into the page, where the client reads it. A script of the shell
+//! sends it along with every same-origin request as the [`CSRF_HEADER`], so every server
+//! function and upload is covered without taking it as an argument. Forms submitted without
+//! JavaScript send it as [`CSRF_FIELD`] instead, which `CsrfField` adds to an `ActionForm`.
+//! The [`check`] middleware rejects requests of logged-in browsers without a matching token.
+//! Other sites can't read the cookie or the page, so they can't send one
+
+#[cfg(feature = "ssr")]
+use super::{ApiError, helper};
+
+#[cfg(feature = "ssr")]
+use leptos::prelude::*;
+
+/// Name of the cookie holding the token
+pub const CSRF_COOKIE: &str = "dafoerum_csrf";
+/// Name of the meta tag the shell renders the token into
+pub const CSRF_META_NAME: &str = "csrf-token";
+/// Name of the form field the token is submitted as without JavaScript
+pub const CSRF_FIELD: &str = "csrf_token";
+/// Name of the header the shell's script sends the token in
+pub const CSRF_HEADER: &str = "x-csrf-token";
+/// How long the [`CSRF_COOKIE`] is kept, pages older than that have to be reloaded
+#[cfg(feature = "ssr")]
+const CSRF_COOKIE_MAX_AGE_DAYS: i64 = 365;
+/// Largest form body searched for the [`CSRF_FIELD`], axum's default body limit
+#[cfg(feature = "ssr")]
+const MAX_FORM_BYTES: usize = 2 * 1024 * 1024;
+
+/// The token of the current request, provided as context so it's only issued once
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug)]
+struct CsrfToken(String);
+
+/// Returns the token of this browser, on the server issuing a new one in the [`CSRF_COOKIE`]
+/// if it has none yet and on the client reading it from the [`CSRF_META_NAME`] meta tag
+#[must_use]
+pub fn current_token() -> String {
+    #[cfg(feature = "ssr")]
+    {
+        if let Some(CsrfToken(token)) = use_context::<CsrfToken>() {
+            return token;
+        }
+        let existing = use_context::<http::request::Parts>()
+            .and_then(|parts| helper::cookie_from_headers(&parts.headers, CSRF_COOKIE))
+            .filter(|token| {
+                !token.is_empty() && token.chars().all(|char| char.is_ascii_hexdigit())
+            });
+        let token = existing.unwrap_or_else(|| {
+            let token = helper::random_token();
+            set_cookie(&token);
+            token
+        });
+        provide_context(CsrfToken(token.clone()));
+        token
+    }
+
+    #[cfg(not(feature = "ssr"))]
+    {
+        leptos::prelude::document()
+            .query_selector(&format!("meta[name={CSRF_META_NAME}]"))
+            .ok()
+            .flatten()
+            .and_then(|meta| meta.get_attribute("content"))
+            .unwrap_or_default()
+    }
+}
+
+/// Sets the [`CSRF_COOKIE`] to `token`
+#[cfg(feature = "ssr")]
+fn set_cookie(token: &str) {
+    let cookie = format!(
+        "{CSRF_COOKIE}={token}; Path=/; HttpOnly; SameSite=Lax; Max-Age={}",
+        CSRF_COOKIE_MAX_AGE_DAYS * 24 * 60 * 60
+    );
+    let Ok(cookie) = http::HeaderValue::from_str(&cookie) else {
+        unreachable!("token is hex, so the cookie is a valid header value")
+    };
+    if let Some(res) = use_context::<leptos_axum::ResponseOptions>() {
+        res.append_header(http::header::SET_COOKIE, cookie);
+    }
+}
+
+/// Whether `POST`s to `path` can change state with the session cookie and need the token:
+/// server functions and uploads, but not the [REST API][super::rest], which only takes
+/// API keys. The service worker's JSON requests can't be forged by other sites' forms
+///
+/// # Example
+///
+/// ```
+/// use app::api::csrf::needs_token;
+///
+/// assert!(needs_token("/api/create_post12345"));
+/// assert!(needs_token("/attachments"));
+/// assert!(!needs_token("/api/v1/threads"));
+/// assert!(!needs_token("/inbound-email"));
+/// ```
+#[must_use]
+pub fn needs_token(path: &str) -> bool {
+    (path.starts_with("/api/") && !path.starts_with("/api/v1/"))
+        || matches!(path, "/attachments" | "/avatars")
+}
+
+/// The [`CSRF_FIELD`] of the url-encoded form `body`, tokens are hex so they aren't encoded
+///
+/// # Example
+///
+/// ```
+/// use app::api::csrf::form_token;
+///
+/// assert_eq!(form_token(b"content=Hi&csrf_token=c0ffee"), Some("c0ffee"));
+/// assert_eq!(form_token(b"content=csrf_token%3Dc0ffee"), None);
+/// ```
+#[must_use]
+pub fn form_token(body: &[u8]) -> Option<&str> {
+    std::str::from_utf8(body)
+        .ok()?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(name, _)| *name == CSRF_FIELD)
+        .map(|(_, token)| token)
+}
+
+/// Middleware rejecting `POST`s that [need the token][needs_token] with
+/// [`ApiError::CsrfInvalid`] unless they carry the one of the [`CSRF_COOKIE`],
+/// in the [`CSRF_HEADER`] or as the [`CSRF_FIELD`] of a form
+///
+/// Requests without a session aren't checked, since they act as a guest
+/// which anyone can do without a forged request
+#[cfg(feature = "ssr")]
+pub async fn check(
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use leptos::server_fn::error::FromServerFnError;
+
+    if req.method() != http::Method::POST
+        || !needs_token(req.uri().path())
+        || helper::session_token_from_headers(req.headers()).is_none()
+    {
+        return next.run(req).await;
+    }
+    let expected = helper::cookie_from_headers(req.headers(), CSRF_COOKIE).unwrap_or_default();
+    let matches = |token: &str| !expected.is_empty() && helper::secrets_match(token, &expected);
+
+    let header = req
+        .headers()
+        .get(CSRF_HEADER)
+        .and_then(|token| token.to_str().ok());
+    if header.is_some_and(matches) {
+        return next.run(req).await;
+    }
+    let is_form = req
+        .headers()
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("application/x-www-form-urlencoded"));
+    if header.is_none() && is_form {
+        let (parts, body) = req.into_parts();
+        let body = axum::body::to_bytes(body, MAX_FORM_BYTES)
+            .await
+            .unwrap_or_default();
+        if form_token(&body).is_some_and(matches) {
+            return next
+                .run(axum::extract::Request::from_parts(parts, body.into()))
+                .await;
+        }
+    }
+
+    // encoded like errors of server functions, so the client shows it
+    (
+        http::StatusCode::FORBIDDEN,
+        [(http::header::CONTENT_TYPE, "application/json")],
+        ApiError::CsrfInvalid.ser(),
+    )
+        .into_response()
+}
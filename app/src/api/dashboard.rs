@@ -0,0 +1,167 @@
+//! The panels of the home page dashboard: active threads, newest members and forum statistics
+//!
+//! Active threads are limited to the forums the visitor can see,
+//! the statistics are totals of the whole forum like the [`ActivitySummary`][super::ActivitySummary]
+
+use super::{ApiError, Thread, groups::Member};
+#[cfg(feature = "ssr")]
+use super::{
+    CollectionName, GetCollection, Post, bson,
+    groups::UserGroup,
+    helper,
+    site_settings::GuestCapability,
+    user::User,
+    visibility::{self, Listing},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How many days back posts count towards [`ActiveThread::recent_posts`]
+pub const ACTIVE_DAYS: i64 = 7;
+/// How many entries the list panels show
+pub const PANEL_LEN: i64 = 5;
+
+/// A [`Thread`] with how many posts it got within the last [`ACTIVE_DAYS`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ActiveThread {
+    pub thread: Thread,
+    pub recent_posts: u32,
+}
+
+/// Totals shown in the statistics panel
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForumStats {
+    pub thread_count: u64,
+    pub post_count: u64,
+    pub member_count: u64,
+    /// Posts written within the last 24 hours
+    pub posts_last_day: u64,
+}
+
+/// Ids of the [`Forums`][super::Forum] the logged-in user (or guest) can see threads of,
+/// without staff-only ones
+#[cfg(feature = "ssr")]
+async fn visible_forum_ids(db: &mongodb::Database) -> Result<Vec<u32>, ApiError> {
+    let user = helper::get_reader(GuestCapability::Threads, db).await?;
+    let forums = helper::get_all_forums(db).await?;
+    Ok(visibility::listed_forum_ids(
+        &forums,
+        user.as_ref(),
+        Listing::Public,
+    ))
+}
+
+/// `now` minus `hours` as [`bson::DateTime`], for comparing with stored timestamps
+#[cfg(feature = "ssr")]
+fn hours_ago(hours: i64) -> bson::DateTime {
+    let since = jiff::Timestamp::now() - jiff::SignedDuration::from_hours(hours);
+    bson::DateTime::from_millis(since.as_millisecond())
+}
+
+/// Returns the [`PANEL_LEN`] threads with the most posts within the last [`ACTIVE_DAYS`],
+/// ties broken by the newest post
+#[server]
+pub async fn get_active_threads() -> Result<Vec<ActiveThread>, ApiError> {
+    let db = helper::get_db()?;
+    let forum_ids = visible_forum_ids(&db).await?;
+
+    let pipeline = vec![
+        bson::doc! {"$match": {
+            "created_at": {"$gte": hours_ago(ACTIVE_DAYS * 24)},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+            "thread_deleted": {"$ne": true},
+        }},
+        bson::doc! {"$group": {
+            "_id": "$thread_id",
+            "recent_posts": {"$sum": 1},
+            "latest_post_id": {"$max": "$id"},
+        }},
+        bson::doc! {"$lookup": {
+            "from": Thread::collection_name(),
+            "localField": "_id",
+            "foreignField": "id",
+            "as": "thread",
+        }},
+        bson::doc! {"$unwind": "$thread"},
+        bson::doc! {"$match": {
+            "thread.forum_id": {"$in": forum_ids},
+            "thread.pending_review": {"$ne": true},
+            "thread.deleted_at": null,
+        }},
+        bson::doc! {"$sort": {"recent_posts": -1, "latest_post_id": -1}},
+        bson::doc! {"$limit": PANEL_LEN},
+        bson::doc! {"$project": {"_id": 0, "thread": 1, "recent_posts": 1}},
+    ];
+    let mut threads = vec![];
+    let mut cursor = Post::replica_collection(&db)
+        .aggregate(pipeline)
+        .with_type::<ActiveThread>()
+        .await?;
+    while cursor.advance().await? {
+        threads.push(cursor.deserialize_current()?);
+    }
+
+    Ok(threads)
+}
+
+/// Returns the [`PANEL_LEN`] most recently registered [`Users`][User]
+/// with their primary group, leaving out merged accounts
+#[server]
+pub async fn get_newest_members() -> Result<Vec<Member>, ApiError> {
+    let db = helper::get_db()?;
+    helper::get_reader(GuestCapability::Profiles, &db).await?;
+
+    let pipeline = vec![
+        bson::doc! {"$match": {"merged_into": null}},
+        bson::doc! {"$sort": {"created_at": -1}},
+        bson::doc! {"$limit": PANEL_LEN},
+        bson::doc! {"$lookup": {
+            "from": UserGroup::collection_name(),
+            "localField": "primary_group_id",
+            "foreignField": "id",
+            "as": "groups",
+        }},
+        // users without a primary group leave it out
+        bson::doc! {"$project": {"_id": 0, "user": "$$ROOT", "primary_group": {"$first": "$groups"}}},
+        bson::doc! {"$unset": "user.groups"},
+    ];
+    let mut members = vec![];
+    let mut cursor = User::collection(&db)
+        .aggregate(pipeline)
+        .with_type::<Member>()
+        .await?;
+    while cursor.advance().await? {
+        members.push(cursor.deserialize_current()?);
+    }
+
+    Ok(members)
+}
+
+/// Returns the [`ForumStats`], the thread and post counts are the cached
+/// [`ActivitySummary`][super::ActivitySummary]
+#[server]
+pub async fn get_forum_stats() -> Result<ForumStats, ApiError> {
+    let db = helper::get_db()?;
+
+    let activity = super::get_activity_summary().await?;
+    let member_count = User::replica_collection(&db)
+        .count_documents(bson::doc! {"merged_into": null})
+        .await?;
+    let posts_last_day = Post::replica_collection(&db)
+        .count_documents(bson::doc! {
+            "created_at": {"$gte": hours_ago(24)},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+            "thread_deleted": {"$ne": true},
+        })
+        .await?;
+
+    Ok(ForumStats {
+        thread_count: activity.thread_count,
+        post_count: activity.post_count,
+        member_count,
+        posts_last_day,
+    })
+}
@@ -0,0 +1,133 @@
+//! Threads embedded on other sites, e.g. as the comment section of a blog post
+//!
+//! A blog embeds the [`Thread`][super::Thread] with an iframe of its [`embed_path`],
+//! which shows its posts without the navigation around them:
+//!
+//! ```html
+//! <iframe src="https://forum.example/embed/thread/12" width="100%" height="600"></iframe>
+//! ```
+//!
+//! Only the origins admins allowed in [`SiteSettings::embed_origins`] may do so,
+//! the [`set_frame_ancestors`] middleware tells browsers which ones these are.
+//! Other pages can only be embedded by the forum itself.
+//!
+//! Browsers don't send the `SameSite=Lax` session and CSRF cookies to frames on other sites,
+//! so embedded threads are always seen as a guest. Instead of a composer that can't work,
+//! they link to the thread on the forum for logging in and replying
+//!
+//! [`SiteSettings::embed_origins`]: super::site_settings::SiteSettings::embed_origins
+
+#[cfg(feature = "ssr")]
+use super::site_settings;
+
+/// What the paths of embeddable pages start with
+pub const EMBED_PATH_PREFIX: &str = "/embed/";
+
+/// Path of the embeddable version of the [`Thread`][super::Thread] with `thread_id`,
+/// e.g. `/embed/thread/12`
+#[must_use]
+pub fn embed_path(thread_id: u32) -> String {
+    format!("{EMBED_PATH_PREFIX}thread/{thread_id}")
+}
+
+/// Checks if `path` is of an embeddable page, which is shown without navigation
+///
+/// # Example
+///
+/// ```
+/// use app::api::embed::is_embed_path;
+///
+/// assert!(is_embed_path("/embed/thread/12"));
+/// assert!(!is_embed_path("/thread/12"));
+/// ```
+#[must_use]
+pub fn is_embed_path(path: &str) -> bool {
+    path.starts_with(EMBED_PATH_PREFIX)
+}
+
+/// Normalizes `input` to an origin like `https://blog.example:8080` as browsers send it,
+/// [`None`] if it isn't one
+///
+/// # Example
+///
+/// ```
+/// use app::api::embed::normalize_origin;
+///
+/// assert_eq!(
+///     normalize_origin(" https://Blog.Example/ ").as_deref(),
+///     Some("https://blog.example")
+/// );
+/// assert_eq!(
+///     normalize_origin("http://localhost:8080").as_deref(),
+///     Some("http://localhost:8080")
+/// );
+/// assert_eq!(normalize_origin("blog.example"), None);
+/// assert_eq!(normalize_origin("https://blog.example/posts"), None);
+/// assert_eq!(normalize_origin("https://*.example"), None);
+/// assert_eq!(normalize_origin("https://a.example 'unsafe-inline'"), None);
+/// ```
+#[must_use]
+pub fn normalize_origin(input: &str) -> Option<String> {
+    let input = input.trim().trim_end_matches('/').to_lowercase();
+    let (scheme, host) = input.split_once("://")?;
+    let is_host_char = |char: char| char.is_ascii_alphanumeric() || matches!(char, '.' | '-' | ':');
+    if !matches!(scheme, "http" | "https") || host.is_empty() || !host.chars().all(is_host_char) {
+        return None;
+    }
+    Some(format!("{scheme}://{host}"))
+}
+
+/// The `Content-Security-Policy` deciding who may embed a page: the forum itself
+/// and, for embeddable pages, `origins`
+///
+/// # Example
+///
+/// ```
+/// use app::api::embed::frame_ancestors;
+///
+/// assert_eq!(frame_ancestors(&[]), "frame-ancestors 'self'");
+/// assert_eq!(
+///     frame_ancestors(&["https://a.example".to_string(), "https://b.example".to_string()]),
+///     "frame-ancestors 'self' https://a.example https://b.example"
+/// );
+/// ```
+#[must_use]
+pub fn frame_ancestors(origins: &[String]) -> String {
+    let mut policy = "frame-ancestors 'self'".to_string();
+    for origin in origins {
+        policy.push(' ');
+        policy.push_str(origin);
+    }
+    policy
+}
+
+/// Middleware setting the [`frame_ancestors`] of every response that doesn't have one yet,
+/// only embeddable pages allow the [`SiteSettings::embed_origins`]
+///
+/// [`SiteSettings::embed_origins`]: super::site_settings::SiteSettings::embed_origins
+#[cfg(feature = "ssr")]
+pub async fn set_frame_ancestors(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let origins = if is_embed_path(req.uri().path()) {
+        match site_settings::load_settings(&db).await {
+            Ok(settings) => settings.embed_origins,
+            Err(err) => {
+                tracing::warn!("loading the origins allowed to embed threads failed: {err}");
+                vec![]
+            }
+        }
+    } else {
+        vec![]
+    };
+
+    let mut res = next.run(req).await;
+    if let Ok(policy) = http::HeaderValue::from_str(&frame_ancestors(&origins)) {
+        res.headers_mut()
+            .entry(http::header::CONTENT_SECURITY_POLICY)
+            .or_insert(policy);
+    }
+    res
+}
@@ -0,0 +1,365 @@
+//! Domain events: what happened on the forum, as emitted by server functions through [`emit`]
+//!
+//! Every event is logged with structured fields under the [`TARGET`] target,
+//! e.g. shown with `RUST_LOG=dafoerum::events=info`, and handed to whatever
+//! reacts to it, currently the [`webhooks`], the [`mod_log`] and [`web_push`] notifications
+
+use super::{
+    Database, Forum, Post, Thread, mod_log,
+    user::User,
+    web_push,
+    webhooks::{self, WebhookEvent},
+};
+
+/// Tracing target of all events
+pub const TARGET: &str = "dafoerum::events";
+
+/// Something that happened, with who did it
+///
+/// Created threads and posts are only emitted once they're visible,
+/// i.e. after a moderator approved them if they were held for review
+#[derive(Debug, Clone, Copy)]
+pub enum DomainEvent<'a> {
+    ThreadCreated {
+        forum: &'a Forum,
+        thread: &'a Thread,
+        post: &'a Post,
+        author: Option<&'a User>,
+    },
+    PostCreated {
+        forum: &'a Forum,
+        thread: &'a Thread,
+        post: &'a Post,
+        author: Option<&'a User>,
+    },
+    /// `reason` is only given for [staff edits][Post::staff_edit]
+    PostEdited {
+        post_id: u32,
+        editor_id: u32,
+        reason: Option<&'a str>,
+    },
+    /// `reason` may be empty
+    PostFlagged {
+        post_id: u32,
+        user_id: u32,
+        reason: &'a str,
+    },
+    FlagsDismissed {
+        post_id: u32,
+        moderator_id: u32,
+    },
+    FlaggedPostHeld {
+        post_id: u32,
+        moderator_id: u32,
+    },
+    PostApproved {
+        post_id: u32,
+        moderator_id: u32,
+    },
+    PostRejected {
+        post_id: u32,
+        moderator_id: u32,
+    },
+    /// `reason_id` is of the [`ModReason`][super::mod_reasons::ModReason] picked, if any
+    PostDeleted {
+        post_id: u32,
+        thread_id: u32,
+        moderator_id: u32,
+        reason_id: Option<u32>,
+    },
+    /// `reason_id` is of the [`ModReason`][super::mod_reasons::ModReason] picked, if any
+    PostMoved {
+        post_id: u32,
+        source_thread_id: u32,
+        target_thread_id: u32,
+        moderator_id: u32,
+        reason_id: Option<u32>,
+    },
+    ThreadSolved {
+        thread_id: u32,
+        solved: bool,
+        user_id: u32,
+    },
+    /// `reason_id` is of the [`ModReason`][super::mod_reasons::ModReason] picked, if any
+    ThreadDeleted {
+        thread_id: u32,
+        moderator_id: u32,
+        reason_id: Option<u32>,
+    },
+    ThreadRestored {
+        thread_id: u32,
+        moderator_id: u32,
+    },
+    ThreadsMerged {
+        source_thread_id: u32,
+        target_thread_id: u32,
+        moderator_id: u32,
+    },
+    ThreadSplit {
+        source_thread_id: u32,
+        new_thread_id: u32,
+        moderator_id: u32,
+    },
+    UserRegistered {
+        user_id: u32,
+    },
+    /// Permanent if `until` is [`None`]
+    UserBanned {
+        user_id: u32,
+        moderator_id: u32,
+        until: Option<jiff::Timestamp>,
+        reason: &'a str,
+        reason_id: Option<u32>,
+    },
+    UserUnbanned {
+        user_id: u32,
+        moderator_id: u32,
+    },
+    /// Permanent if `until` is [`None`]
+    UserMuted {
+        user_id: u32,
+        moderator_id: u32,
+        until: Option<jiff::Timestamp>,
+        reason: &'a str,
+        reason_id: Option<u32>,
+    },
+    UserUnmuted {
+        user_id: u32,
+        moderator_id: u32,
+    },
+    /// Warned about the post with `post_id`, `note` may be empty
+    UserWarned {
+        user_id: u32,
+        post_id: u32,
+        moderator_id: u32,
+        note: &'a str,
+        reason_id: Option<u32>,
+    },
+}
+impl DomainEvent<'_> {
+    /// Name of the event in logs, e.g. `post_created`
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::ThreadCreated { .. } => "thread_created",
+            Self::PostCreated { .. } => "post_created",
+            Self::PostEdited { .. } => "post_edited",
+            Self::PostFlagged { .. } => "post_flagged",
+            Self::FlagsDismissed { .. } => "flags_dismissed",
+            Self::FlaggedPostHeld { .. } => "flagged_post_held",
+            Self::PostApproved { .. } => "post_approved",
+            Self::PostRejected { .. } => "post_rejected",
+            Self::PostDeleted { .. } => "post_deleted",
+            Self::PostMoved { .. } => "post_moved",
+            Self::ThreadSolved { .. } => "thread_solved",
+            Self::ThreadDeleted { .. } => "thread_deleted",
+            Self::ThreadRestored { .. } => "thread_restored",
+            Self::ThreadsMerged { .. } => "threads_merged",
+            Self::ThreadSplit { .. } => "thread_split",
+            Self::UserRegistered { .. } => "user_registered",
+            Self::UserBanned { .. } => "user_banned",
+            Self::UserUnbanned { .. } => "user_unbanned",
+            Self::UserMuted { .. } => "user_muted",
+            Self::UserUnmuted { .. } => "user_unmuted",
+            Self::UserWarned { .. } => "user_warned",
+        }
+    }
+}
+
+/// Logs `event` with structured fields
+fn log(event: DomainEvent<'_>) {
+    let name = event.name();
+    match event {
+        DomainEvent::ThreadCreated {
+            forum,
+            thread,
+            post,
+            author,
+        }
+        | DomainEvent::PostCreated {
+            forum,
+            thread,
+            post,
+            author,
+        } => tracing::info!(
+            target: TARGET,
+            event = name,
+            forum_id = forum.id,
+            thread_id = thread.id,
+            post_id = post.id,
+            author_id = ?author.map(|author| author.id),
+        ),
+        DomainEvent::PostEdited {
+            post_id,
+            editor_id,
+            reason,
+        } => tracing::info!(target: TARGET, event = name, post_id, editor_id, reason),
+        DomainEvent::PostFlagged {
+            post_id,
+            user_id,
+            reason,
+        } => tracing::info!(target: TARGET, event = name, post_id, user_id, reason),
+        DomainEvent::PostApproved {
+            post_id,
+            moderator_id,
+        }
+        | DomainEvent::PostRejected {
+            post_id,
+            moderator_id,
+        }
+        | DomainEvent::FlagsDismissed {
+            post_id,
+            moderator_id,
+        }
+        | DomainEvent::FlaggedPostHeld {
+            post_id,
+            moderator_id,
+        } => tracing::info!(target: TARGET, event = name, post_id, moderator_id),
+        DomainEvent::PostDeleted {
+            post_id,
+            thread_id,
+            moderator_id,
+            reason_id,
+        } => tracing::info!(
+            target: TARGET,
+            event = name,
+            post_id,
+            thread_id,
+            moderator_id,
+            reason_id = ?reason_id,
+        ),
+        DomainEvent::PostMoved {
+            post_id,
+            source_thread_id,
+            target_thread_id,
+            moderator_id,
+            reason_id,
+        } => tracing::info!(
+            target: TARGET,
+            event = name,
+            post_id,
+            source_thread_id,
+            target_thread_id,
+            moderator_id,
+            reason_id = ?reason_id,
+        ),
+        DomainEvent::ThreadSolved {
+            thread_id,
+            solved,
+            user_id,
+        } => tracing::info!(target: TARGET, event = name, thread_id, solved, user_id),
+        DomainEvent::ThreadDeleted {
+            thread_id,
+            moderator_id,
+            reason_id,
+        } => tracing::info!(
+            target: TARGET,
+            event = name,
+            thread_id,
+            moderator_id,
+            reason_id = ?reason_id,
+        ),
+        DomainEvent::ThreadRestored {
+            thread_id,
+            moderator_id,
+        } => tracing::info!(target: TARGET, event = name, thread_id, moderator_id),
+        DomainEvent::ThreadsMerged {
+            source_thread_id,
+            target_thread_id,
+            moderator_id,
+        } => tracing::info!(
+            target: TARGET,
+            event = name,
+            source_thread_id,
+            target_thread_id,
+            moderator_id,
+        ),
+        DomainEvent::ThreadSplit {
+            source_thread_id,
+            new_thread_id,
+            moderator_id,
+        } => tracing::info!(
+            target: TARGET,
+            event = name,
+            source_thread_id,
+            new_thread_id,
+            moderator_id,
+        ),
+        DomainEvent::UserRegistered { user_id } => {
+            tracing::info!(target: TARGET, event = name, user_id);
+        }
+        DomainEvent::UserBanned {
+            user_id,
+            moderator_id,
+            until,
+            reason,
+            reason_id,
+        }
+        | DomainEvent::UserMuted {
+            user_id,
+            moderator_id,
+            until,
+            reason,
+            reason_id,
+        } => tracing::info!(
+            target: TARGET,
+            event = name,
+            user_id,
+            moderator_id,
+            until = ?until,
+            reason,
+            reason_id = ?reason_id,
+        ),
+        DomainEvent::UserUnbanned {
+            user_id,
+            moderator_id,
+        }
+        | DomainEvent::UserUnmuted {
+            user_id,
+            moderator_id,
+        } => tracing::info!(target: TARGET, event = name, user_id, moderator_id),
+        DomainEvent::UserWarned {
+            user_id,
+            post_id,
+            moderator_id,
+            note,
+            reason_id,
+        } => tracing::info!(
+            target: TARGET,
+            event = name,
+            user_id,
+            post_id,
+            moderator_id,
+            note,
+            reason_id = ?reason_id,
+        ),
+    }
+}
+
+/// Logs `event` and hands it to everything reacting to it, which happens in the background
+pub fn emit(event: DomainEvent<'_>, db: &Database) {
+    log(event);
+    mod_log::record(event, db);
+
+    match event {
+        DomainEvent::ThreadCreated {
+            forum,
+            thread,
+            post,
+            author,
+        } => {
+            webhooks::fire(WebhookEvent::NewThread, forum, thread, post, author, db);
+            web_push::notify_thread(forum, thread, post, author, db);
+        }
+        DomainEvent::PostCreated {
+            forum,
+            thread,
+            post,
+            author,
+        } => {
+            webhooks::fire(WebhookEvent::NewPost, forum, thread, post, author, db);
+            web_push::notify_reply(forum, thread, post, author, db);
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,235 @@
+//! RSS feeds of new [`Threads`][super::Thread] in a [`Forum`][super::Forum]
+//! and new [`Posts`][super::Post] in a thread
+//!
+//! Served by the plain axum routes `forum_feed` and `thread_feed`, see [`FeedScope::url`].
+//! Forums that aren't public (see [`Forum::read_role`][super::Forum::read_role]) have no feeds,
+//! neither has any forum if guests can't see threads, see [`GuestAccess`][super::site_settings::GuestAccess].
+//! Links in the feeds are absolute if the `PUBLIC_URL` env var is set, relative otherwise.
+//! The [`ContentLicense`][super::site_settings::ContentLicense] is stated as `<copyright>`
+
+#[cfg(feature = "ssr")]
+use super::{
+    ApiError, GetCollection, Post, Thread, bson, helper,
+    site_settings::{self, ContentLicense, GuestCapability},
+    visibility::{self, Listing},
+};
+
+/// Number of items in a feed
+pub const FEED_LEN: i64 = 20;
+
+/// What a feed contains
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeedScope {
+    /// Newest threads in the [`Forum`][super::Forum] with this id
+    Forum(u32),
+    /// Newest posts in the [`Thread`][super::Thread] with this id
+    Thread(u32),
+}
+impl FeedScope {
+    /// Path of the feed, e.g. `/feed/forum/3`
+    #[must_use]
+    pub fn url(self) -> String {
+        match self {
+            Self::Forum(forum_id) => format!("/feed/forum/{forum_id}"),
+            Self::Thread(thread_id) => format!("/feed/thread/{thread_id}"),
+        }
+    }
+}
+
+/// Escapes the characters that have a special meaning in XML
+///
+/// # Example
+///
+/// ```
+/// use app::api::feeds::escape_xml;
+///
+/// assert_eq!(escape_xml("Tom & <Jerry>"), "Tom &amp; &lt;Jerry&gt;");
+/// assert_eq!(escape_xml("\"it's\""), "&quot;it&apos;s&quot;");
+/// ```
+#[must_use]
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(char),
+        }
+    }
+    escaped
+}
+
+/// An entry of a feed
+#[cfg(feature = "ssr")]
+struct FeedItem {
+    title: String,
+    link: String,
+    /// Rendered HTML, escaped when writing the feed
+    description: String,
+    published: jiff::Timestamp,
+}
+
+/// Writes an RSS 2.0 channel with the given items
+#[cfg(feature = "ssr")]
+fn rss_channel(
+    title: &str,
+    link: &str,
+    description: &str,
+    license: Option<&ContentLicense>,
+    items: &[FeedItem],
+) -> String {
+    use std::fmt::Write;
+
+    let date = |timestamp: jiff::Timestamp| timestamp.strftime("%a, %d %b %Y %T +0000");
+    let mut rss =
+        String::from(r#"<?xml version="1.0" encoding="UTF-8"?><rss version="2.0"><channel>"#);
+    let _ = write!(
+        rss,
+        "<title>{}</title><link>{}</link><description>{}</description>",
+        escape_xml(title),
+        escape_xml(link),
+        escape_xml(description)
+    );
+    if let Some(license) = license {
+        let _ = write!(
+            rss,
+            "<copyright>{}</copyright>",
+            escape_xml(&license.notice())
+        );
+    }
+    for item in items {
+        let _ = write!(
+            rss,
+            "<item><title>{}</title><link>{link}</link><guid>{link}</guid>\
+             <description>{}</description><pubDate>{}</pubDate></item>",
+            escape_xml(&item.title),
+            escape_xml(&item.description),
+            date(item.published),
+            link = escape_xml(&item.link),
+        );
+    }
+    rss.push_str("</channel></rss>");
+    rss
+}
+
+/// Turns the result of a feed handler into a response
+#[cfg(feature = "ssr")]
+fn respond(result: Result<String, ApiError>) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use http::StatusCode;
+
+    match result {
+        Ok(rss) => (
+            [(
+                http::header::CONTENT_TYPE,
+                "application/rss+xml; charset=utf-8",
+            )],
+            rss,
+        )
+            .into_response(),
+        Err(ApiError::NotFound(..) | ApiError::LoginRequired) => {
+            StatusCode::NOT_FOUND.into_response()
+        }
+        Err(err) => {
+            tracing::warn!("serving feed failed: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Axum handler for `GET /feed/forum/{id}`, the newest public threads of a
+/// [`Forum`][super::Forum] with their origin post
+#[cfg(feature = "ssr")]
+pub async fn forum_feed(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    axum::extract::Path(forum_id): axum::extract::Path<u32>,
+) -> axum::response::Response {
+    let feed = async {
+        let base = helper::public_url().unwrap_or_default();
+        helper::ensure_guest_can(GuestCapability::Threads, None, &db).await?;
+        visibility::ensure_listed(forum_id, None, Listing::Public, &db).await?;
+        let (forum, _) = helper::get_forum(forum_id, db.clone()).await?;
+
+        let mut items = vec![];
+        let mut cursor = Thread::collection(&db)
+            .find(bson::doc! {
+                "forum_id": forum.id,
+                "pending_review": {"$ne": true},
+                "deleted_at": null,
+            })
+            .sort(bson::doc! {"id": -1})
+            .limit(FEED_LEN)
+            .await?;
+        while cursor.advance().await? {
+            let thread: Thread = cursor.deserialize_current()?;
+            let origin_post = helper::get_post(thread.origin_post_id, db.clone()).await?;
+            items.push(FeedItem {
+                title: thread.display_subject(),
+                link: format!("{base}/thread/{}", thread.id),
+                description: crate::markdown::render(&origin_post.content),
+                published: origin_post.created_at,
+            });
+        }
+
+        let license = site_settings::load_settings(&db).await?.content_license;
+        Ok(rss_channel(
+            &forum.name,
+            &format!("{base}/forum/{}", forum.id),
+            forum.description.as_deref().unwrap_or_default(),
+            license.as_ref(),
+            &items,
+        ))
+    };
+    respond(feed.await)
+}
+
+/// Axum handler for `GET /feed/thread/{id}`, the newest visible posts of a
+/// [`Thread`][super::Thread]
+#[cfg(feature = "ssr")]
+pub async fn thread_feed(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    axum::extract::Path(thread_id): axum::extract::Path<u32>,
+) -> axum::response::Response {
+    let feed = async {
+        let base = helper::public_url().unwrap_or_default();
+        helper::ensure_guest_can(GuestCapability::Threads, None, &db).await?;
+        let thread = helper::get_thread(thread_id, db.clone()).await?;
+        if thread.pending_review || thread.deleted_at.is_some() {
+            return Err(ApiError::NotFound("thread".into(), thread_id));
+        }
+        visibility::ensure_listed(thread.forum_id, None, Listing::Public, &db).await?;
+
+        let mut items = vec![];
+        let mut cursor = Post::collection(&db)
+            .find(bson::doc! {
+                "thread_id": thread.id,
+                "pending_review": {"$ne": true},
+                "deleted_at": null,
+            })
+            .sort(bson::doc! {"id": -1})
+            .limit(FEED_LEN)
+            .await?;
+        while cursor.advance().await? {
+            let post: Post = cursor.deserialize_current()?;
+            items.push(FeedItem {
+                title: format!("Post #{} in {}", post.id, thread.display_subject()),
+                link: format!("{base}/post/{}", post.id),
+                description: crate::markdown::render(&post.content),
+                published: post.created_at,
+            });
+        }
+
+        let license = site_settings::load_settings(&db).await?.content_license;
+        Ok(rss_channel(
+            &thread.display_subject(),
+            &format!("{base}/thread/{}", thread.id),
+            &format!("New posts in {}", thread.display_subject()),
+            license.as_ref(),
+            &items,
+        ))
+    };
+    respond(feed.await)
+}
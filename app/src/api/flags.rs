@@ -0,0 +1,396 @@
+//! Flagging [`Posts`][Post] for moderators to look at
+//!
+//! Every [`Flag`] weighs as much as its reporter's [`TrustLevel`] is worth in the [`FlagSettings`].
+//! Once the flags of a post weigh at least [`FlagSettings::threshold`], the post is
+//! [collapsed][Post::collapsed] until a moderator reviews it, so that the community can deal with
+//! obvious spam or abuse while no staff is online
+
+use super::user::User;
+use super::{ApiError, CollectionName, Post, Thread};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, bson, cache,
+    events::{self, DomainEvent},
+    helper,
+    user::Role,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maximum amount of characters of [`Flag::reason`]
+pub const MAX_FLAG_REASON_LEN: usize = 200;
+/// Accounts younger than this many days are [`TrustLevel::New`]
+pub const NEW_ACCOUNT_DAYS: i64 = 7;
+/// Users with at least this many visible posts are [`TrustLevel::Trusted`]
+pub const TRUSTED_POST_COUNT: u64 = 50;
+
+/// How much the [`Flags`][Flag] of a [`User`] count
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TrustLevel {
+    /// Younger than [`NEW_ACCOUNT_DAYS`]
+    New,
+    Member,
+    /// [Verified][User::verified] or with at least [`TRUSTED_POST_COUNT`] posts
+    Trusted,
+    /// Moderators and admins
+    Staff,
+}
+impl TrustLevel {
+    /// The trust level of `user` with `post_count` visible posts as of `now`
+    #[must_use]
+    pub fn of(user: &User, post_count: u64, now: jiff::Timestamp) -> Self {
+        if user.role >= super::user::Role::Moderator {
+            Self::Staff
+        } else if user.verified || post_count >= TRUSTED_POST_COUNT {
+            Self::Trusted
+        } else if now.duration_since(user.created_at).as_hours() < NEW_ACCOUNT_DAYS * 24 {
+            Self::New
+        } else {
+            Self::Member
+        }
+    }
+}
+
+/// Configures how much [`Flags`][Flag] weigh and when posts are collapsed,
+/// a single document edited by admins
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagSettings {
+    /// Posts are collapsed once their flags weigh at least this much
+    pub threshold: u32,
+    pub new_weight: u32,
+    pub member_weight: u32,
+    pub trusted_weight: u32,
+    pub staff_weight: u32,
+}
+impl Default for FlagSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 6,
+            new_weight: 1,
+            member_weight: 2,
+            trusted_weight: 3,
+            // a single moderator is enough
+            staff_weight: 6,
+        }
+    }
+}
+impl FlagSettings {
+    /// How much a flag of someone with the given [`TrustLevel`] weighs
+    #[must_use]
+    pub fn weight(&self, level: TrustLevel) -> u32 {
+        match level {
+            TrustLevel::New => self.new_weight,
+            TrustLevel::Member => self.member_weight,
+            TrustLevel::Trusted => self.trusted_weight,
+            TrustLevel::Staff => self.staff_weight,
+        }
+    }
+}
+impl CollectionName for FlagSettings {
+    fn collection_name() -> &'static str {
+        "flag_settings"
+    }
+}
+
+/// A [`User`] reporting a [`Post`], at most one per user and post
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Flag {
+    pub post_id: u32,
+    pub reporter_id: u32,
+    /// Optional, at most [`MAX_FLAG_REASON_LEN`] characters
+    pub reason: String,
+    /// From the [`FlagSettings`] at the time of flagging
+    pub weight: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+impl CollectionName for Flag {
+    fn collection_name() -> &'static str {
+        "flags"
+    }
+}
+
+/// A [`Flag`] with the name of its reporter, for moderators
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FlagEntry {
+    pub reporter_name: String,
+    pub reason: String,
+    pub weight: u32,
+}
+
+/// A flagged [`Post`] with the [`Thread`] it's in and all its flags
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FlaggedItem {
+    pub post: Post,
+    pub thread: Thread,
+    pub total_weight: u32,
+    pub flags: Vec<FlagEntry>,
+}
+
+/// Loads the [`FlagSettings`], the defaults if an admin never changed them
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn load_settings(db: &mongodb::Database) -> Result<FlagSettings, ApiError> {
+    let settings = FlagSettings::collection(db).find_one(bson::doc! {}).await?;
+    Ok(settings.unwrap_or_default())
+}
+
+/// Fetches the [`FlagSettings`]
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_flag_settings() -> Result<FlagSettings, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+    load_settings(&db).await
+}
+
+/// Replaces the [`FlagSettings`]
+///
+/// Already collapsed posts stay collapsed, the new values only apply to new flags
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidFlagThreshold`] if `threshold` is `0`
+#[server]
+pub async fn update_flag_settings(
+    threshold: u32,
+    new_weight: u32,
+    member_weight: u32,
+    trusted_weight: u32,
+    staff_weight: u32,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    if threshold == 0 {
+        return Err(ApiError::InvalidFlagThreshold);
+    }
+    let settings = FlagSettings {
+        threshold,
+        new_weight,
+        member_weight,
+        trusted_weight,
+        staff_weight,
+    };
+    FlagSettings::collection(&db)
+        .replace_one(bson::doc! {}, settings)
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Flags the [`Post`] as the logged-in [`User`], replacing their earlier flag of it,
+/// and collapses it if its flags now weigh at least [`FlagSettings::threshold`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+/// - [`ApiError::Forbidden`] if it's their own post
+/// - [`ApiError::FlagReasonTooLong`] if `reason` is longer than [`MAX_FLAG_REASON_LEN`]
+#[server]
+pub async fn flag_post(post_id: u32, #[server(default)] reason: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let reason = reason.trim().to_string();
+    if reason.chars().count() > MAX_FLAG_REASON_LEN {
+        return Err(ApiError::FlagReasonTooLong);
+    }
+    let post = helper::get_post(post_id, db.clone()).await?;
+    let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+    helper::ensure_can_see_forum(thread.forum_id, Some(&user), &db).await?;
+    if post.author_id == Some(user.id) {
+        return Err(ApiError::Forbidden);
+    }
+
+    let post_count = Post::collection(&db)
+        .count_documents(bson::doc! {
+            "author_id": user.id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .await?;
+    let settings = load_settings(&db).await?;
+    let flag = Flag {
+        post_id,
+        reporter_id: user.id,
+        reason,
+        weight: settings.weight(TrustLevel::of(&user, post_count, jiff::Timestamp::now())),
+        created_at: jiff::Timestamp::now(),
+    };
+    let flag_col = Flag::collection(&db);
+    flag_col
+        .replace_one(
+            bson::doc! {"post_id": post_id, "reporter_id": user.id},
+            &flag,
+        )
+        .upsert(true)
+        .await?;
+
+    let mut total_weight = 0;
+    let mut cursor = flag_col.find(bson::doc! {"post_id": post_id}).await?;
+    while cursor.advance().await? {
+        let flag: Flag = cursor.deserialize_current()?;
+        total_weight += flag.weight;
+    }
+    if total_weight >= settings.threshold && !post.collapsed {
+        Post::collection(&db)
+            .update_one(
+                bson::doc! {"id": post_id},
+                bson::doc! {"$set": {"collapsed": true}},
+            )
+            .await?;
+    }
+    events::emit(
+        DomainEvent::PostFlagged {
+            post_id,
+            user_id: user.id,
+            reason: &flag.reason,
+        },
+        &db,
+    );
+
+    Ok(())
+}
+
+/// Fetches all flagged [`Posts`][Post] as [`FlaggedItems`][FlaggedItem],
+/// collapsed ones first, then by their flags' weight
+///
+/// Requires [`Role::Moderator`]
+#[server]
+pub async fn get_flagged_posts() -> Result<Vec<FlaggedItem>, ApiError> {
+    use std::collections::BTreeMap;
+
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    let mut flags: BTreeMap<u32, Vec<Flag>> = BTreeMap::new();
+    let mut cursor = Flag::collection(&db).find(bson::doc! {}).await?;
+    while cursor.advance().await? {
+        let flag: Flag = cursor.deserialize_current()?;
+        flags.entry(flag.post_id).or_default().push(flag);
+    }
+
+    let mut items = vec![];
+    for (post_id, flags) in flags {
+        // the post or its thread may have been deleted since
+        let Ok(post) = helper::get_post(post_id, db.clone()).await else {
+            continue;
+        };
+        let Ok(thread) = helper::get_thread(post.thread_id, db.clone()).await else {
+            continue;
+        };
+        let mut entries = vec![];
+        for flag in &flags {
+            let reporter_name = match helper::get_user(flag.reporter_id, &db).await {
+                Ok(reporter) => reporter.name,
+                Err(ApiError::NotFound(..)) => "Deleted user".to_string(),
+                Err(err) => return Err(err),
+            };
+            entries.push(FlagEntry {
+                reporter_name,
+                reason: flag.reason.clone(),
+                weight: flag.weight,
+            });
+        }
+        items.push(FlaggedItem {
+            total_weight: flags.iter().map(|flag| flag.weight).sum(),
+            post,
+            thread,
+            flags: entries,
+        });
+    }
+    items.sort_by_key(|item| (!item.post.collapsed, std::cmp::Reverse(item.total_weight)));
+
+    Ok(items)
+}
+
+/// Removes all [`Flags`][Flag] of the [`Post`] and expands it again
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+#[server]
+pub async fn dismiss_flags(post_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+
+    helper::get_post(post_id, db.clone()).await?;
+    Flag::collection(&db)
+        .delete_many(bson::doc! {"post_id": post_id})
+        .await?;
+    Post::collection(&db)
+        .update_one(
+            bson::doc! {"id": post_id},
+            bson::doc! {"$set": {"collapsed": false}},
+        )
+        .await?;
+    events::emit(
+        DomainEvent::FlagsDismissed {
+            post_id,
+            moderator_id: moderator.id,
+        },
+        &db,
+    );
+
+    Ok(())
+}
+
+/// Takes a flagged [`Post`] off the forum by holding it for review,
+/// moving it to the [`moderation`][super::moderation] queue
+///
+/// Its [`Flags`][Flag] are removed, it's either approved or rejected from there on.
+/// If it's the origin post of a [`Thread`], the thread is held too
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+#[server]
+pub async fn hold_flagged_post(post_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+
+    let post = helper::get_post(post_id, db.clone()).await?;
+    let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+    Flag::collection(&db)
+        .delete_many(bson::doc! {"post_id": post_id})
+        .await?;
+    Post::collection(&db)
+        .update_one(
+            bson::doc! {"id": post_id},
+            bson::doc! {"$set": {"collapsed": false, "pending_review": true}},
+        )
+        .await?;
+    if thread.origin_post_id == post_id {
+        Thread::collection(&db)
+            .update_one(
+                bson::doc! {"id": thread.id},
+                bson::doc! {"$set": {"pending_review": true}},
+            )
+            .await?;
+    }
+    helper::refresh_latest_ids(&[thread.forum_id], &db).await?;
+    cache::invalidate_forums(&[thread.forum_id], &db).await?;
+    events::emit(
+        DomainEvent::FlaggedPostHeld {
+            post_id,
+            moderator_id: moderator.id,
+        },
+        &db,
+    );
+
+    Ok(())
+}
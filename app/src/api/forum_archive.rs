@@ -0,0 +1,391 @@
+//! Read-only snapshots of deleted [`Forums`][super::Forum]
+//!
+//! Deleting a forum with threads in it first saves everything in it as a [`ForumArchive`]:
+//! a JSON file for re-importing or processing and a static HTML page for reading,
+//! both stored next to the attachments (see [`archive_dir`]). Both state the
+//! [`ContentLicense`][super::site_settings::ContentLicense] the posts were published under.
+//! Only then are its threads and posts purged from the db.
+//! Admins can download the archives through the plain axum route `download`
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    Category, Forum, GetCollection, Post, Thread,
+    announcements::Announcement,
+    bson, cache,
+    forum_rules::RulesAcknowledgement,
+    helper,
+    site_settings::{self, ContentLicense},
+    user::Role,
+    user::User,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How an archive can be downloaded
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Json,
+    Html,
+}
+impl ArchiveFormat {
+    pub const ALL: [Self; 2] = [Self::Json, Self::Html];
+
+    /// File extension, also used in the download route
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+
+    /// Parses an [`ArchiveFormat::extension`]
+    #[must_use]
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+
+    #[must_use]
+    pub fn mime(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+}
+
+/// Record of a deleted [`Forum`][super::Forum] whose content was saved to files
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ForumArchive {
+    pub id: u32,
+    pub forum_id: u32,
+    pub forum_name: String,
+    pub thread_count: u64,
+    pub post_count: u64,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub archived_at: jiff::Timestamp,
+    /// The admin who deleted the forum
+    pub archived_by: u32,
+}
+impl ForumArchive {
+    /// Route the archive is downloaded from in the given format, e.g. `/archives/3/json`
+    #[must_use]
+    pub fn url(&self, format: ArchiveFormat) -> String {
+        format!("/archives/{}/{}", self.id, format.extension())
+    }
+}
+impl CollectionName for ForumArchive {
+    fn collection_name() -> &'static str {
+        "forum_archives"
+    }
+}
+
+/// A [`Thread`][super::Thread] with all its [`Posts`][super::Post] as saved in the JSON file
+#[cfg(feature = "ssr")]
+#[derive(Serialize, Debug)]
+struct ThreadSnapshot {
+    thread: Thread,
+    posts: Vec<Post>,
+}
+
+/// Everything saved about a deleted [`Forum`] in the JSON file
+#[cfg(feature = "ssr")]
+#[derive(Serialize, Debug)]
+struct ForumSnapshot {
+    forum: Forum,
+    category_name: String,
+    /// Names of the authors by their id
+    authors: std::collections::BTreeMap<u32, String>,
+    threads: Vec<ThreadSnapshot>,
+    /// At the time of archiving
+    license: Option<ContentLicense>,
+}
+
+/// Directory the archive files are stored in, `archives` in the
+/// [`attachment_dir`][super::attachments::attachment_dir]
+#[cfg(feature = "ssr")]
+#[must_use]
+pub fn archive_dir() -> std::path::PathBuf {
+    super::attachments::attachment_dir().join("archives")
+}
+
+/// Path of the file of the archive with `id` in the given format
+#[cfg(feature = "ssr")]
+fn archive_path(id: u32, format: ArchiveFormat) -> std::path::PathBuf {
+    archive_dir().join(format!("{id}.{}", format.extension()))
+}
+
+/// Renders `snapshot` as a self-contained HTML page
+#[cfg(feature = "ssr")]
+fn render_html(snapshot: &ForumSnapshot) -> String {
+    use super::feeds::escape_xml as escape;
+    use std::fmt::Write;
+
+    let author_name = |author_id: Option<u32>| match author_id {
+        Some(id) => snapshot
+            .authors
+            .get(&id)
+            .map_or_else(|| format!("user #{id}"), |name| escape(name)),
+        None => "Anonymous".to_string(),
+    };
+
+    let forum_name = escape(&snapshot.forum.name);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{forum_name} (archived)</title>\n</head>\n<body>\n\
+         <h1>{} / {forum_name}</h1>\n",
+        escape(&snapshot.category_name),
+    );
+    if let Some(description) = &snapshot.forum.description {
+        let _ = writeln!(html, "<p>{}</p>", escape(description));
+    }
+    for ThreadSnapshot { thread, posts } in &snapshot.threads {
+        let _ = writeln!(
+            html,
+            "<section id=\"thread-{}\">\n<h2>{}</h2>",
+            thread.id,
+            escape(&thread.display_subject()),
+        );
+        for post in posts {
+            let _ = writeln!(
+                html,
+                "<article id=\"post-{}\">\n<p><b>{}</b> at {}</p>\n{}\n</article>",
+                post.id,
+                author_name(post.author_id),
+                post.created_at,
+                crate::markdown::render(&post.content),
+            );
+        }
+        html.push_str("</section>\n");
+    }
+    if let Some(license) = &snapshot.license {
+        let name = match &license.url {
+            Some(url) => format!("<a href=\"{}\">{}</a>", escape(url), escape(&license.name)),
+            None => escape(&license.name),
+        };
+        let _ = write!(html, "<footer>\n<p>Licensed under {name}");
+        if !license.attribution.is_empty() {
+            let _ = write!(html, ", attribution: {}", escape(&license.attribution));
+        }
+        html.push_str("</p>\n</footer>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Saves all threads and posts of `forum` as a [`ForumArchive`] and returns its id
+#[cfg(feature = "ssr")]
+async fn archive(
+    forum: Forum,
+    category_name: String,
+    archived_by: &User,
+    db: &mongodb::Database,
+) -> Result<u32, ApiError> {
+    let mut threads: Vec<Thread> = vec![];
+    let mut cursor = Thread::collection(db)
+        .find(bson::doc! {"forum_id": forum.id})
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    while cursor.advance().await? {
+        threads.push(cursor.deserialize_current()?);
+    }
+
+    let post_col = Post::collection(db);
+    let mut author_ids = vec![];
+    let mut snapshots = Vec::with_capacity(threads.len());
+    let mut post_count = 0;
+    for thread in threads {
+        let mut posts = vec![];
+        let mut cursor = post_col
+            .find(bson::doc! {"thread_id": thread.id})
+            .sort(bson::doc! {"id": 1})
+            .await?;
+        while cursor.advance().await? {
+            let post: Post = cursor.deserialize_current()?;
+            author_ids.extend(post.author_id);
+            posts.push(post);
+        }
+        post_count += posts.len() as u64;
+        snapshots.push(ThreadSnapshot { thread, posts });
+    }
+
+    author_ids.sort_unstable();
+    author_ids.dedup();
+    let mut authors = std::collections::BTreeMap::new();
+    let mut cursor = User::collection(db)
+        .find(bson::doc! {"id": {"$in": author_ids}})
+        .await?;
+    while cursor.advance().await? {
+        let user: User = cursor.deserialize_current()?;
+        authors.insert(user.id, user.name);
+    }
+
+    let forum_id = forum.id;
+    let forum_name = forum.name.clone();
+    let thread_count = snapshots.len() as u64;
+    let snapshot = ForumSnapshot {
+        forum,
+        category_name,
+        authors,
+        threads: snapshots,
+        license: site_settings::load_settings(db).await?.content_license,
+    };
+    let json = serde_json::to_string_pretty(&snapshot)
+        .map_err(|err| ApiError::Storage(err.to_string()))?;
+    let html = render_html(&snapshot);
+
+    let counter_col = super::Counter::collection(db);
+    let id = helper::get_and_increment_id_of("forum_archive", counter_col).await?;
+    let storage_err = |err: std::io::Error| ApiError::Storage(err.to_string());
+    tokio::fs::create_dir_all(archive_dir())
+        .await
+        .map_err(storage_err)?;
+    tokio::fs::write(archive_path(id, ArchiveFormat::Json), json)
+        .await
+        .map_err(storage_err)?;
+    tokio::fs::write(archive_path(id, ArchiveFormat::Html), html)
+        .await
+        .map_err(storage_err)?;
+
+    let archive = ForumArchive {
+        id,
+        forum_id,
+        forum_name,
+        thread_count,
+        post_count,
+        archived_at: jiff::Timestamp::now(),
+        archived_by: archived_by.id,
+    };
+    ForumArchive::collection(db).insert_one(&archive).await?;
+
+    Ok(id)
+}
+
+/// Deletes a [`Forum`][super::Forum] with all its threads and posts
+///
+/// If there are any threads, they're saved as a [`ForumArchive`] first,
+/// whose id is returned. Nothing is deleted if archiving fails
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `forum_id` isn't in use
+/// - [`ApiError::ForumHasSubforums`] if other forums are below it
+/// - [`ApiError::Storage`] if the archive files can't be written
+#[server]
+pub async fn delete_forum(forum_id: u32) -> Result<Option<u32>, ApiError> {
+    let db = helper::get_db()?;
+    let admin = helper::require_role(&db, Role::Admin).await?;
+    let (forum, category_name) = helper::get_forum(forum_id, db.clone()).await?;
+
+    let forums = helper::get_all_forums(&db).await?;
+    if forums
+        .iter()
+        .any(|forum| forum.parent_forum_id == Some(forum_id))
+    {
+        return Err(ApiError::ForumHasSubforums);
+    }
+
+    let thread_col = Thread::collection(&db);
+    let mut thread_ids = vec![];
+    let mut cursor = thread_col.find(bson::doc! {"forum_id": forum_id}).await?;
+    while cursor.advance().await? {
+        let thread: Thread = cursor.deserialize_current()?;
+        thread_ids.push(thread.id);
+    }
+    let archive_id = if thread_ids.is_empty() {
+        None
+    } else {
+        Some(archive(forum, category_name, &admin, &db).await?)
+    };
+
+    Post::collection(&db)
+        .delete_many(bson::doc! {"thread_id": {"$in": thread_ids}})
+        .await?;
+    thread_col
+        .delete_many(bson::doc! {"forum_id": forum_id})
+        .await?;
+    RulesAcknowledgement::collection(&db)
+        .delete_many(bson::doc! {"forum_id": forum_id})
+        .await?;
+    Announcement::collection(&db)
+        .delete_many(bson::doc! {"forum_id": forum_id})
+        .await?;
+    // while it still exists, so the forums above it are recounted too
+    cache::invalidate_forums(&[forum_id], &db).await?;
+    Category::collection(&db)
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$pull": {"forums": {"id": forum_id}}},
+        )
+        .await?;
+
+    Ok(archive_id)
+}
+
+/// Fetches all [`ForumArchives`][ForumArchive], newest first
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_forum_archives() -> Result<Vec<ForumArchive>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let mut archives = vec![];
+    let mut cursor = ForumArchive::collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"id": -1})
+        .await?;
+    while cursor.advance().await? {
+        archives.push(cursor.deserialize_current()?);
+    }
+
+    Ok(archives)
+}
+
+/// Axum handler for `GET /archives/{id}/{format}`, serves an archive file as a download
+///
+/// Requires [`Role::Admin`]
+#[cfg(feature = "ssr")]
+pub async fn download(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    axum::extract::Path((id, extension)): axum::extract::Path<(u32, String)>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use http::{StatusCode, header};
+
+    match helper::require_user_from_headers(&headers, &db).await {
+        Ok(user) if user.role >= Role::Admin => {}
+        Ok(_) => return StatusCode::FORBIDDEN.into_response(),
+        Err(ApiError::NotLoggedIn) => return StatusCode::UNAUTHORIZED.into_response(),
+        Err(err) => {
+            tracing::warn!("serving archive failed: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+    let Some(format) = ArchiveFormat::from_extension(&extension) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let Ok(data) = tokio::fs::read(archive_path(id, format)).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, format.mime().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"forum-archive-{id}.{extension}\""),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        data,
+    )
+        .into_response()
+}
@@ -0,0 +1,166 @@
+//! Rules of a [`Forum`][super::Forum] that have to be acknowledged before posting there
+//!
+//! Written in Markdown by admins as [`Forum::rules`][super::Forum::rules].
+//! Every logged-in [`User`] has to acknowledge them once before their first thread or post
+//! in the forum (tracked in [`RulesAcknowledgement`]), which is enforced by
+//! [`ensure_acknowledged`] when creating threads and posts.
+//! Editing the rules doesn't ask for another acknowledgement
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    Category, Forum, GetCollection, bson, helper, site_settings::GuestCapability, user::Role,
+    user::User,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maximum amount of characters of [`Forum::rules`][super::Forum::rules]
+pub const MAX_RULES_LEN: usize = 5000;
+
+/// Records that a [`User`][super::user::User] acknowledged the rules of a
+/// [`Forum`][super::Forum]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RulesAcknowledgement {
+    pub forum_id: u32,
+    pub user_id: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub acknowledged_at: jiff::Timestamp,
+}
+impl CollectionName for RulesAcknowledgement {
+    fn collection_name() -> &'static str {
+        "rules_acknowledgements"
+    }
+}
+
+/// Checks whether `user` has to acknowledge the rules of the [`Forum`] before posting there
+///
+/// Forums without rules and moderators never need an acknowledgement
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn needs_acknowledgement(
+    forum: &Forum,
+    user: Option<&User>,
+    db: &mongodb::Database,
+) -> Result<bool, ApiError> {
+    if forum.rules.is_none() {
+        return Ok(false);
+    }
+    let Some(user) = user else {
+        return Ok(true);
+    };
+    if user.role >= Role::Moderator {
+        return Ok(false);
+    }
+    let acknowledgement = RulesAcknowledgement::collection(db)
+        .find_one(bson::doc! {"forum_id": forum.id, "user_id": user.id})
+        .await?;
+    Ok(acknowledgement.is_none())
+}
+
+/// Makes sure `user` acknowledged the rules of the [`Forum`], if it has any
+///
+/// # Errors
+///
+/// * [`ApiError::NotLoggedIn`] if the forum has rules and `user` is [`None`]
+/// * [`ApiError::RulesNotAcknowledged`] if `user` hasn't acknowledged them yet
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn ensure_acknowledged(
+    forum: &Forum,
+    user: Option<&User>,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    if !needs_acknowledgement(forum, user, db).await? {
+        return Ok(());
+    }
+    match user {
+        Some(_) => Err(ApiError::RulesNotAcknowledged),
+        None => Err(ApiError::NotLoggedIn),
+    }
+}
+
+/// Returns the rules of the [`Forum`] rendered as HTML if the logged-in [`User`][super::user::User]
+/// still has to acknowledge them, [`None`] if they can post right away
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `forum_id` isn't in use or the forum is hidden from the user
+#[server]
+pub async fn get_unacknowledged_rules(forum_id: u32) -> Result<Option<String>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Forums, &db).await?;
+    helper::ensure_can_see_forum(forum_id, user.as_ref(), &db).await?;
+    let (forum, _) = helper::get_forum(forum_id, db.clone()).await?;
+
+    if !needs_acknowledgement(&forum, user.as_ref(), &db).await? {
+        return Ok(None);
+    }
+    Ok(forum.rules.as_deref().map(crate::markdown::render))
+}
+
+/// Records that the logged-in [`User`][super::user::User] acknowledged the rules of the
+/// [`Forum`][super::Forum]
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if `forum_id` isn't in use or the forum is hidden from the user
+#[server]
+pub async fn acknowledge_forum_rules(forum_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    helper::ensure_can_see_forum(forum_id, Some(&user), &db).await?;
+
+    let acknowledgement = RulesAcknowledgement {
+        forum_id,
+        user_id: user.id,
+        acknowledged_at: jiff::Timestamp::now(),
+    };
+    RulesAcknowledgement::collection(&db)
+        .replace_one(
+            bson::doc! {"forum_id": forum_id, "user_id": user.id},
+            acknowledgement,
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Replaces the rules of a [`Forum`][super::Forum], empty ones remove them
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::RulesTooLong`] if `rules` are longer than [`MAX_RULES_LEN`]
+#[server]
+pub async fn update_forum_rules(forum_id: u32, rules: Option<String>) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+    let _ = helper::get_forum(forum_id, db.clone()).await?;
+
+    let rules = rules
+        .map(|rules| rules.trim().to_string())
+        .filter(|rules| !rules.is_empty());
+    if rules
+        .as_ref()
+        .is_some_and(|rules| rules.chars().count() > MAX_RULES_LEN)
+    {
+        return Err(ApiError::RulesTooLong);
+    }
+
+    Category::collection(&db)
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {"forums.$.rules": rules}},
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,310 @@
+//! [`UserGroups`][UserGroup] managed by admins
+//!
+//! A [`User`] can be in any number of groups, each granting its [`GroupPermissions`][GroupPermission].
+//! The primary group decides how the name is displayed, i.e. its color and badge
+
+use super::user::User;
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{Counter, GetCollection, bson, helper, site_settings::GuestCapability, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Extra permissions a [`UserGroup`] grants its members
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupPermission {
+    /// Ignores [`Forum::min_account_age_hours`][super::Forum::min_account_age_hours]
+    /// and [`Forum::min_post_count`][super::Forum::min_post_count]
+    BypassPostingRequirements,
+    /// Content held by the [`word filter`][super::word_filter::FilterAction::Hold]
+    /// is published right away
+    SkipReview,
+}
+impl GroupPermission {
+    pub const ALL: [Self; 2] = [Self::BypassPostingRequirements, Self::SkipReview];
+
+    /// Translation key of the human-readable description for the admin area
+    #[must_use]
+    pub fn description_key(self) -> &'static str {
+        match self {
+            Self::BypassPostingRequirements => "group_permission.bypass_posting_requirements",
+            Self::SkipReview => "group_permission.skip_review",
+        }
+    }
+}
+
+/// A group of [`Users`][User], e.g. "Veterans" or "Contributors"
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserGroup {
+    pub id: u32,
+    pub name: String,
+    /// Color of the names of [`Users`][User] with this as their primary group, `#rrggbb`
+    pub color: String,
+    /// Short text shown next to the names, e.g. "VIP"
+    pub badge: Option<String>,
+    #[serde(default)]
+    pub permissions: Vec<GroupPermission>,
+}
+impl CollectionName for UserGroup {
+    fn collection_name() -> &'static str {
+        "user_groups"
+    }
+}
+
+/// A [`User`] together with their primary [`UserGroup`] for displaying their name
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Member {
+    pub user: User,
+    pub primary_group: Option<UserGroup>,
+}
+
+/// Checks whether `color` is a hex color of the form `#rrggbb`
+///
+/// # Example
+///
+/// ```
+/// use app::api::groups::is_hex_color;
+///
+/// assert!(is_hex_color("#7e22ce"));
+/// assert!(!is_hex_color("7e22ce"));
+/// assert!(!is_hex_color("#fff"));
+/// assert!(!is_hex_color("red; display: none"));
+/// ```
+#[must_use]
+pub fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Returns whether any of the [`UserGroups`][UserGroup] of `user` grants `permission`
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn has_permission(
+    user: Option<&User>,
+    permission: GroupPermission,
+    db: &mongodb::Database,
+) -> Result<bool, ApiError> {
+    let Some(user) = user.filter(|user| !user.group_ids.is_empty()) else {
+        return Ok(false);
+    };
+    let Ok(permission) = bson::to_bson(&permission) else {
+        unreachable!("a unit enum is always serializable")
+    };
+    let count = UserGroup::collection(db)
+        .count_documents(bson::doc! {"id": {"$in": &user.group_ids}, "permissions": permission})
+        .await?;
+    Ok(count > 0)
+}
+
+/// Looks up the primary [`UserGroup`] of `user`
+#[cfg(feature = "ssr")]
+async fn to_member(user: User, db: &mongodb::Database) -> Result<Member, ApiError> {
+    let primary_group = match user.primary_group_id {
+        Some(group_id) => {
+            UserGroup::collection(db)
+                .find_one(bson::doc! {"id": group_id})
+                .await?
+        }
+        None => None,
+    };
+    Ok(Member {
+        user,
+        primary_group,
+    })
+}
+
+/// Returns all [`UserGroups`][UserGroup] ordered by id
+#[server]
+pub async fn get_user_groups() -> Result<Vec<UserGroup>, ApiError> {
+    let db = helper::get_db()?;
+
+    let mut cursor = UserGroup::collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    let mut groups = vec![];
+    while cursor.advance().await? {
+        groups.push(cursor.deserialize_current()?);
+    }
+
+    Ok(groups)
+}
+
+/// Returns the [`User`] with the given `user_id` with their primary [`UserGroup`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `user_id` isn't in use
+#[server]
+pub async fn get_member(user_id: u32) -> Result<Member, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_user(user_id, &db).await?;
+    to_member(user, &db).await
+}
+
+/// Returns all [`Users`][User] ordered by name with their primary [`UserGroups`][UserGroup]
+#[server]
+pub async fn get_members() -> Result<Vec<Member>, ApiError> {
+    let db = helper::get_db()?;
+    helper::get_reader(GuestCapability::Profiles, &db).await?;
+
+    let groups = get_user_groups().await?;
+    let mut cursor = User::replica_collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"name": 1})
+        .await?;
+    let mut members = vec![];
+    while cursor.advance().await? {
+        let user: User = cursor.deserialize_current()?;
+        let primary_group = user
+            .primary_group_id
+            .and_then(|group_id| groups.iter().find(|group| group.id == group_id).cloned());
+        members.push(Member {
+            user,
+            primary_group,
+        });
+    }
+
+    Ok(members)
+}
+
+/// Creates a new [`UserGroup`] or updates the one with `group_id`
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptyGroupName`] if `name` is empty
+/// - [`ApiError::InvalidColor`] if `color` isn't `#rrggbb`
+/// - [`ApiError::NotFound`] if `group_id` isn't in use
+#[server]
+pub async fn save_user_group(
+    group_id: Option<u32>,
+    name: String,
+    color: String,
+    badge: Option<String>,
+    #[server(default)] permissions: Vec<GroupPermission>,
+) -> Result<u32, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::EmptyGroupName);
+    }
+    let color = color.trim().to_lowercase();
+    if !is_hex_color(&color) {
+        return Err(ApiError::InvalidColor(color));
+    }
+    // empty form fields arrive as empty strings
+    let badge = badge
+        .map(|badge| badge.trim().to_string())
+        .filter(|badge| !badge.is_empty());
+
+    let group_col = UserGroup::collection(&db);
+    let id = match group_id {
+        Some(id) => {
+            if group_col.find_one(bson::doc! {"id": id}).await?.is_none() {
+                return Err(ApiError::NotFound("user group".into(), id));
+            }
+            id
+        }
+        None => helper::get_and_increment_id_of("user_group", Counter::collection(&db)).await?,
+    };
+
+    let group = UserGroup {
+        id,
+        name,
+        color,
+        badge,
+        permissions,
+    };
+    group_col
+        .replace_one(bson::doc! {"id": id}, &group)
+        .upsert(true)
+        .await?;
+
+    Ok(id)
+}
+
+/// Deletes the [`UserGroup`] with `group_id` and removes all [`Users`][User] from it
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn delete_user_group(group_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    UserGroup::collection(&db)
+        .delete_one(bson::doc! {"id": group_id})
+        .await?;
+
+    let user_col = User::collection(&db);
+    user_col
+        .update_many(
+            bson::doc! {"group_ids": group_id},
+            bson::doc! {"$pull": {"group_ids": group_id}},
+        )
+        .await?;
+    user_col
+        .update_many(
+            bson::doc! {"primary_group_id": group_id},
+            bson::doc! {"$set": {"primary_group_id": null}},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the [`UserGroups`][UserGroup] of the [`User`] with `user_id`
+///
+/// The `primary_group_id` has to be one of the `group_ids`
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if the user or one of the groups doesn't exist
+/// - [`ApiError::PrimaryGroupNotJoined`] if the user isn't in the primary group
+#[server]
+pub async fn set_user_groups(
+    user_id: u32,
+    #[server(default)] group_ids: Vec<u32>,
+    primary_group_id: Option<u32>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    helper::get_user(user_id, &db).await?;
+
+    let mut group_ids = group_ids;
+    group_ids.sort_unstable();
+    group_ids.dedup();
+    let group_col = UserGroup::collection(&db);
+    for &group_id in &group_ids {
+        if group_col
+            .find_one(bson::doc! {"id": group_id})
+            .await?
+            .is_none()
+        {
+            return Err(ApiError::NotFound("user group".into(), group_id));
+        }
+    }
+    if primary_group_id.is_some_and(|primary| !group_ids.contains(&primary)) {
+        return Err(ApiError::PrimaryGroupNotJoined);
+    }
+
+    User::collection(&db)
+        .update_one(
+            bson::doc! {"id": user_id},
+            bson::doc! {"$set": {"group_ids": group_ids, "primary_group_id": primary_group_id}},
+        )
+        .await?;
+
+    Ok(())
+}
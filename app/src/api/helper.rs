@@ -4,9 +4,25 @@
 //! are also API endpoints (`#[server]`)
 
 use super::{
-    ApiError, Category, Collection, Counter, Database, Forum, GetCollection, Post, Thread, bson,
+    ActivitySummary, ApiError, Category, Collection, CollectionName, Counter, Database,
+    DbOperation, Forum, GetCollection, Post, PostWithAuthor, Quote, Thread, attachments, bson,
+    cache,
+    events::{self, DomainEvent},
+    forum_rules,
+    groups::{self, GroupPermission},
+    link_previews, read_markers,
+    site_settings::{self, GuestCapability},
+    spam, thread_references,
+    user::{Role, SESSION_MAX_AGE_DAYS, Session, User},
+    validation::{self, Field},
+    word_filter::{self, Verdict, WordFilterRule},
 };
 use leptos::prelude::*;
+use mongodb::options::{ReadPreference, SelectionCriteria};
+use std::sync::LazyLock;
+
+/// Name of the cookie holding the [`Session`] token
+pub const SESSION_COOKIE: &str = "dafoerum_session";
 
 /// Gives access to the [`Database`]
 ///
@@ -19,17 +35,79 @@ pub fn get_db() -> Result<Database, ApiError> {
     use_context::<Database>().ok_or(ApiError::DbNotInContext)
 }
 
+/// Read preference for heavy read-only queries, see [`GetCollection::replica_collection`]
+///
+/// Set with the `MONGO_HEAVY_READ_PREFERENCE` env var to one of `primary`, `primaryPreferred`,
+/// `secondary`, `secondaryPreferred` or `nearest`. Defaults to `secondaryPreferred`,
+/// which also works without a replica set by falling back to the primary
+pub fn heavy_read_preference() -> SelectionCriteria {
+    static PREFERENCE: LazyLock<ReadPreference> = LazyLock::new(|| {
+        let mode = std::env::var("MONGO_HEAVY_READ_PREFERENCE").unwrap_or_default();
+        match mode.as_str() {
+            "primary" => ReadPreference::Primary,
+            "primaryPreferred" => ReadPreference::PrimaryPreferred { options: None },
+            "secondary" => ReadPreference::Secondary { options: None },
+            "nearest" => ReadPreference::Nearest { options: None },
+            "" | "secondaryPreferred" => ReadPreference::SecondaryPreferred { options: None },
+            unknown => {
+                tracing::warn!("unknown MONGO_HEAVY_READ_PREFERENCE {unknown:?}, using primary");
+                ReadPreference::Primary
+            }
+        }
+    });
+    SelectionCriteria::ReadPreference(PREFERENCE.clone())
+}
+
+/// Base url of the forum without a trailing slash, set with the `PUBLIC_URL` env var,
+/// e.g. `https://forum.example`
+///
+/// Needed wherever absolute urls are, like in feeds or for federation
+#[must_use]
+pub fn public_url() -> Option<String> {
+    std::env::var("PUBLIC_URL")
+        .ok()
+        .map(|url| url.trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// Generates a random 256-bit token as 64 hex characters, e.g. for [`Sessions`][Session]
+#[must_use]
+pub fn random_token() -> String {
+    use std::fmt::Write;
+
+    rand::random::<[u8; 32]>()
+        .iter()
+        .fold(String::with_capacity(64), |mut token, byte| {
+            let _ = write!(token, "{byte:02x}");
+            token
+        })
+}
+
+/// Compares two secrets in constant time, so their content can't be guessed by timing
+#[must_use]
+pub fn secrets_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
 /// Looks up the current sequence of a post/thread/..., increments it and returns the incremented value
 ///
 /// Required when creating new such element
 ///
-/// # Panics
-///
-/// Will panic if the given `category` doesn't exist in the [`Collection<Counter>`] of the database
+/// If the given `category` doesn't exist in the [`Collection<Counter>`] yet,
+/// it will be created, i.e. the first id is `1`
 ///
 /// # Errors
 ///
 /// * [`ApiError::Db`] if the db connection fails in any way
+#[allow(
+    clippy::missing_panics_doc,
+    reason = "the counter is upserted, so a document is always returned"
+)]
 pub async fn get_and_increment_id_of(
     category: &'static str,
     counter_col: Collection<Counter>,
@@ -39,23 +117,29 @@ pub async fn get_and_increment_id_of(
             bson::doc! {"category": category},
             bson::doc! {"$inc": {"sequence": 1}},
         )
+        .upsert(true)
+        .return_document(mongodb::options::ReturnDocument::After)
         .await?
-        // maybe later: create counter if missing instead of panicking
-        .expect("counter should exist in db")
+        .expect("counter is upserted")
         .sequence;
-    Ok(current_id + 1)
+    Ok(current_id)
 }
 
 /// Queries database to check if a [`Post`] with the given `post_id` exists
 /// and returns it.
 ///
+/// Soft-deleted posts count as gone, see [`Post::deleted_at`]
+///
 /// # Errors
 ///
-/// * [`ApiError::NotFound`] if the `post_id` is not in the db
+/// * [`ApiError::NotFound`] if the `post_id` is not in the db or the post was deleted
 /// * [`ApiError::Db`] if the db connection fails in any way
 pub async fn get_post(post_id: u32, db: Database) -> Result<Post, ApiError> {
     let post_col = Post::collection(&db);
-    let post = post_col.find_one(bson::doc! {"id": post_id}).await?;
+    let post = post_col
+        .find_one(bson::doc! {"id": post_id, "deleted_at": null})
+        .await
+        .map_err(|err| ApiError::from(err).in_context(DbOperation::Read, "post", Some(post_id)))?;
 
     // invariant: if post is saved in database, the thread it is in must also exist
 
@@ -71,7 +155,12 @@ pub async fn get_post(post_id: u32, db: Database) -> Result<Post, ApiError> {
 /// * [`ApiError::Db`] if the db connection fails in any way
 pub async fn get_thread(thread_id: u32, db: Database) -> Result<Thread, ApiError> {
     let thread_col = Thread::collection(&db);
-    let thread = thread_col.find_one(bson::doc! {"id": thread_id}).await?;
+    let thread = thread_col
+        .find_one(bson::doc! {"id": thread_id})
+        .await
+        .map_err(|err| {
+            ApiError::from(err).in_context(DbOperation::Read, "thread", Some(thread_id))
+        })?;
 
     // invariant: if thread is saved in database, the forum it is in must also exist
 
@@ -93,7 +182,10 @@ pub async fn get_forum(forum_id: u32, db: Database) -> Result<(Forum, String), A
     let category_col = Category::collection(&db);
     let category = category_col
         .find_one(bson::doc! {"forums.id": forum_id})
-        .await?;
+        .await
+        .map_err(|err| {
+            ApiError::from(err).in_context(DbOperation::Read, "forum", Some(forum_id))
+        })?;
 
     // easier than dealing with projections in mongodb and Rust (maybe someday I'm skilled enough)
     let Some(category) = category else {
@@ -111,34 +203,217 @@ pub async fn get_forum(forum_id: u32, db: Database) -> Result<(Forum, String), A
     Ok((forum, category.name))
 }
 
-/// Queries the databse for the amount of [`Thread`]s and [`Post`]s for the given `forum_id`
+/// Returns all [`Forums`][Forum] of all [`Categories`][Category], including subforums
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn get_all_forums(db: &Database) -> Result<Vec<Forum>, ApiError> {
+    let mut forums = vec![];
+    let mut cursor = Category::collection(db).find(bson::doc! {}).await?;
+    while cursor.advance().await? {
+        let category: Category = cursor.deserialize_current()?;
+        forums.extend(category.forums);
+    }
+    Ok(forums)
+}
+
+/// Returns the ids of the [`Forum`] with `forum_id` and of all forums below it in `forums`
+#[must_use]
+pub fn forum_subtree_ids(forums: &[Forum], forum_id: u32) -> Vec<u32> {
+    let mut ids = vec![forum_id];
+    // breadth-first, `ids` grows while iterating
+    let mut i = 0;
+    while let Some(&parent) = ids.get(i) {
+        ids.extend(
+            forums
+                .iter()
+                .filter(|forum| forum.parent_forum_id == Some(parent) && !ids.contains(&forum.id))
+                .map(|forum| forum.id)
+                .collect::<Vec<_>>(),
+        );
+        i += 1;
+    }
+    ids
+}
+
+/// Returns the [`Forum`] with `forum_id` and all forums above it in `forums`, the top-level one first
+///
+/// Empty if `forum_id` isn't in `forums`
+#[must_use]
+pub fn forum_ancestry(forums: &[Forum], forum_id: u32) -> Vec<Forum> {
+    let mut ancestry = vec![];
+    let mut next = Some(forum_id);
+    while let Some(forum) = next.and_then(|id| forums.iter().find(|forum| forum.id == id)) {
+        // guards against cycles in broken data
+        if ancestry.iter().any(|seen: &Forum| seen.id == forum.id) {
+            break;
+        }
+        ancestry.push(forum.clone());
+        next = forum.parent_forum_id;
+    }
+    ancestry.reverse();
+    ancestry
+}
+
+/// Checks if `user` (anonymous if [`None`]) may see the [`Forum`] with `forum_id` in `forums`,
+/// i.e. if it and all forums above it are [visible][Forum::is_visible_to] to them
+#[must_use]
+pub fn can_see_forum(forums: &[Forum], forum_id: u32, user: Option<&User>) -> bool {
+    let ancestry = forum_ancestry(forums, forum_id);
+    !ancestry.is_empty() && ancestry.iter().all(|forum| forum.is_visible_to(user))
+}
+
+/// Ensures that `user` (anonymous if [`None`]) may see the [`Forum`] with `forum_id`,
+/// see [`can_see_forum`]
+///
+/// # Errors
+///
+/// * [`ApiError::NotFound`] if the forum doesn't exist or isn't visible to `user`,
+///   so that hidden forums can't be told apart from nonexistent ones
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn ensure_can_see_forum(
+    forum_id: u32,
+    user: Option<&User>,
+    db: &Database,
+) -> Result<(), ApiError> {
+    let forums = get_all_forums(db).await?;
+    if can_see_forum(&forums, forum_id, user) {
+        Ok(())
+    } else {
+        Err(ApiError::NotFound("forum".into(), forum_id))
+    }
+}
+
+/// Fetches the [`Thread`] with `thread_id` if `user` (anonymous if [`None`]) may read it,
+/// i.e. it isn't held for review, is only [soft-deleted][Thread::deleted_at] for moderators
+/// and they can see its forum, see [`can_see_forum`]
+///
+/// # Errors
+///
+/// * [`ApiError::NotFound`] if the thread doesn't exist or isn't visible to `user`,
+///   so that hidden threads can't be told apart from nonexistent ones
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn get_visible_thread(
+    thread_id: u32,
+    user: Option<&User>,
+    db: &Database,
+) -> Result<Thread, ApiError> {
+    let thread = get_thread(thread_id, db.clone()).await?;
+    let deleted_for_user =
+        thread.deleted_at.is_some() && user.is_none_or(|user| user.role < Role::Moderator);
+    if thread.pending_review
+        || deleted_for_user
+        || ensure_can_see_forum(thread.forum_id, user, db)
+            .await
+            .is_err()
+    {
+        return Err(ApiError::NotFound("thread".into(), thread_id));
+    }
+    Ok(thread)
+}
+
+/// Ensures that `user` (anonymous if [`None`]) may create threads and posts in `forum`,
+/// i.e. they can see it and it isn't [read-only][Forum::read_only] unless they're a moderator
+///
+/// Posting requirements are checked separately, see [`ensure_meets_forum_requirements`]
+///
+/// # Errors
+///
+/// * [`ApiError::NotFound`] if the forum isn't visible to `user`
+/// * [`ApiError::ForumReadOnly`] if the forum is read-only
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn ensure_can_post_in_forum(
+    forum: &Forum,
+    user: Option<&User>,
+    db: &Database,
+) -> Result<(), ApiError> {
+    ensure_can_see_forum(forum.id, user, db).await?;
+    if forum.read_only && user.is_none_or(|user| user.role < Role::Moderator) {
+        return Err(ApiError::ForumReadOnly);
+    }
+    Ok(())
+}
+
+/// Queries the databse for the amount of [`Thread`]s and [`Post`]s for the given `forum_id`,
+/// including all of its subforums
 ///
 /// Doesn't check for [`Forum`] existence, will probably return `0` for such
 ///
 /// First value is the [`Thread`] count, second value is the [`Post`] count
 ///
+/// Reads from a replica (see [`GetCollection::replica_collection`]) unless `fresh` is set,
+/// which is needed right after writes
+///
 /// # Errors
 ///
 /// * [`ApiError::Db`] if the db connection fails in any way
 pub async fn count_threads_and_posts_of_forum(
     forum_id: u32,
     db: Database,
+    fresh: bool,
 ) -> Result<(u64, u64), ApiError> {
-    let thread_col = Thread::collection(&db);
+    let forum_ids = forum_subtree_ids(&get_all_forums(&db).await?, forum_id);
+
+    let (thread_col, post_col) = if fresh {
+        (Thread::collection(&db), Post::collection(&db))
+    } else {
+        (
+            Thread::replica_collection(&db),
+            Post::replica_collection(&db),
+        )
+    };
     let mut thread_ids = vec![];
-    let mut threads_cursor = thread_col.find(bson::doc! {"forum_id": forum_id}).await?;
+    let mut threads_cursor = thread_col
+        .find(bson::doc! {
+            "forum_id": {"$in": &forum_ids},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .await?;
     while threads_cursor.advance().await? {
         thread_ids.push(threads_cursor.deserialize_current()?.id);
     }
 
-    let post_col = Post::collection(&db);
     let post_count = post_col
-        .count_documents(bson::doc! {"thread_id": {"$in": &thread_ids}})
+        .count_documents(bson::doc! {
+            "thread_id": {"$in": &thread_ids},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
         .await?;
 
     Ok((thread_ids.len() as u64, post_count))
 }
 
+/// Counts all visible [`Thread`]s and [`Post`]s
+///
+/// Reads from a replica (see [`GetCollection::replica_collection`]) unless `fresh` is set,
+/// which is needed right after writes
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn count_activity(db: &Database, fresh: bool) -> Result<ActivitySummary, ApiError> {
+    let (thread_col, post_col) = if fresh {
+        (Thread::collection(db), Post::collection(db))
+    } else {
+        (Thread::replica_collection(db), Post::replica_collection(db))
+    };
+    Ok(ActivitySummary {
+        thread_count: thread_col
+            .count_documents(bson::doc! {"pending_review": {"$ne": true}, "deleted_at": null})
+            .await?,
+        post_count: post_col
+            .count_documents(bson::doc! {
+                "pending_review": {"$ne": true},
+                "deleted_at": null,
+                "thread_deleted": {"$ne": true},
+            })
+            .await?,
+    })
+}
+
 /// Queries the databse for the amount of [`Post`]s for the given `thread_id`
 ///
 /// Doesn't check for [`Thread`] existence, will probably return `0` for such
@@ -147,9 +422,956 @@ pub async fn count_threads_and_posts_of_forum(
 ///
 /// * [`ApiError::Db`] if the db connection fails in any way
 pub async fn count_posts_of(thread_id: u32, db: Database) -> Result<u64, ApiError> {
-    let post_col = Post::collection(&db);
+    let post_col = Post::replica_collection(&db);
     let count = post_col
-        .count_documents(bson::doc! {"thread_id": thread_id})
+        .count_documents(bson::doc! {
+            "thread_id": thread_id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
         .await?;
     Ok(count)
 }
+
+/// Reads the [`Session`] token from the cookies of the current request
+///
+/// Returns [`None`] if there's no request in context (shouldn't happen) or no session cookie
+pub fn get_session_token() -> Option<String> {
+    let parts = use_context::<http::request::Parts>()?;
+    session_token_from_headers(&parts.headers)
+}
+
+/// Reads the [`Session`] token from the cookies in the given `headers`
+///
+/// For plain axum handlers outside of leptos, use [`get_session_token`] otherwise
+pub fn session_token_from_headers(headers: &http::HeaderMap) -> Option<String> {
+    cookie_from_headers(headers, SESSION_COOKIE)
+}
+
+/// Reads the value of the cookie called `cookie_name` from the given `headers`
+pub fn cookie_from_headers(headers: &http::HeaderMap, cookie_name: &str) -> Option<String> {
+    headers
+        .get_all(http::header::COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == cookie_name)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Reads the IP address of the client of the current request, see [`client_ip_of`]
+pub fn get_client_ip() -> Option<String> {
+    let parts = use_context::<http::request::Parts>()?;
+    client_ip_of(&parts.headers, &parts.extensions)
+}
+
+/// IP addresses of the reverse proxies in front of dafoerum, set with the `TRUSTED_PROXIES`
+/// env var as a comma separated list, e.g. `127.0.0.1,::1`
+///
+/// Without it no proxy is trusted and clients are only identified by their connection
+fn trusted_proxies() -> &'static [std::net::IpAddr] {
+    static PROXIES: LazyLock<Vec<std::net::IpAddr>> = LazyLock::new(|| {
+        std::env::var("TRUSTED_PROXIES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|proxy| !proxy.is_empty())
+            .filter_map(|proxy| {
+                let ip = proxy.parse().ok();
+                if ip.is_none() {
+                    tracing::warn!("ignoring TRUSTED_PROXIES entry {proxy:?}, not an IP address");
+                }
+                ip
+            })
+            .collect()
+    });
+    &PROXIES
+}
+
+/// Reads the IP address of the client from the `headers` and `extensions` of a request
+///
+/// That's the peer of the connection axum saved as [`ConnectInfo`][axum::extract::ConnectInfo],
+/// unless it's one of the [`trusted_proxies`]. Then `X-Forwarded-For` is followed from the right
+/// to the first hop that isn't a trusted proxy, since everything left of it could be made up
+/// by the client. Trusted proxies only setting `X-Real-IP` are believed too.
+/// [`None`] if the server wasn't started with the connect info.
+///
+/// For plain axum handlers and middleware outside of leptos, use [`get_client_ip`] otherwise
+pub fn client_ip_of(headers: &http::HeaderMap, extensions: &http::Extensions) -> Option<String> {
+    use axum::extract::ConnectInfo;
+    use std::net::{IpAddr, SocketAddr};
+
+    let ConnectInfo(peer) = extensions.get::<ConnectInfo<SocketAddr>>()?;
+    let peer = peer.ip().to_canonical();
+    let trusted = trusted_proxies();
+    if !trusted.contains(&peer) {
+        return Some(peer.to_string());
+    }
+
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .flat_map(|header| header.split(','))
+        .collect();
+    if hops.is_empty() {
+        let real = headers
+            .get("x-real-ip")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok());
+        return Some(real.unwrap_or(peer).to_string());
+    }
+    let mut client = peer;
+    // each proxy appends who connected to it, so only the right end can be trusted
+    for hop in hops.into_iter().rev() {
+        if !trusted.contains(&client) {
+            break;
+        }
+        let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = hop.to_canonical();
+    }
+    Some(client.to_string())
+}
+
+/// Looks up the [`User`] belonging to the [`Session`] of the current request
+///
+/// Returns [`None`] if not logged in or the session doesn't exist (anymore)
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn get_current_user(db: &Database) -> Result<Option<User>, ApiError> {
+    let Some(token) = get_session_token() else {
+        return Ok(None);
+    };
+    get_user_by_session(&token, db).await
+}
+
+/// Looks up the logged-in [`User`] like [`get_current_user`] for reading something
+/// that needs `capability`, which guests need to be granted, see
+/// [`GuestAccess`][site_settings::GuestAccess]
+///
+/// # Errors
+///
+/// * [`ApiError::LoginRequired`] if not logged in and guests lack `capability`
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn get_reader(
+    capability: GuestCapability,
+    db: &Database,
+) -> Result<Option<User>, ApiError> {
+    let user = get_current_user(db).await?;
+    ensure_guest_can(capability, user.as_ref(), db).await?;
+    Ok(user)
+}
+
+/// Ensures that `user` may read something that needs `capability`,
+/// which logged-in users always may, see [`GuestAccess`][site_settings::GuestAccess]
+///
+/// Feeds, federation and the like are always read as a guest, i.e. with `user` being [`None`]
+///
+/// # Errors
+///
+/// * [`ApiError::LoginRequired`] if `user` is [`None`] and guests lack `capability`
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn ensure_guest_can(
+    capability: GuestCapability,
+    user: Option<&User>,
+    db: &Database,
+) -> Result<(), ApiError> {
+    if user.is_some() {
+        return Ok(());
+    }
+    let guest_access = site_settings::load_settings(db).await?.guest_access;
+    if guest_access.allows(capability) {
+        Ok(())
+    } else {
+        Err(ApiError::LoginRequired)
+    }
+}
+
+/// Looks up the [`User`] belonging to the [`Session`] with the given `token`
+///
+/// Returns [`None`] if the session doesn't exist (anymore) or is older than
+/// [`SESSION_MAX_AGE_DAYS`], in which case it's deleted
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn get_user_by_session(token: &str, db: &Database) -> Result<Option<User>, ApiError> {
+    let session_col = Session::collection(db);
+    let Some(session) = session_col.find_one(bson::doc! {"token": token}).await? else {
+        return Ok(None);
+    };
+    // the cookie expires with the same max age, but a copied token must not outlive it
+    let max_age = jiff::SignedDuration::from_hours(SESSION_MAX_AGE_DAYS * 24);
+    if session.created_at + max_age <= jiff::Timestamp::now() {
+        session_col.delete_one(bson::doc! {"token": token}).await?;
+        return Ok(None);
+    }
+
+    let user_col = User::collection(db);
+    let user = user_col
+        .find_one(bson::doc! {"id": session.user_id})
+        .await?;
+    Ok(user)
+}
+
+/// Like [`require_user`], but for plain axum handlers outside of leptos
+///
+/// # Errors
+///
+/// * [`ApiError::NotLoggedIn`] if there's no valid [`Session`] cookie in `headers`
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn require_user_from_headers(
+    headers: &http::HeaderMap,
+    db: &Database,
+) -> Result<User, ApiError> {
+    let token = session_token_from_headers(headers).ok_or(ApiError::NotLoggedIn)?;
+    get_user_by_session(&token, db)
+        .await?
+        .ok_or(ApiError::NotLoggedIn)
+}
+
+/// Like [`get_current_user`], but errors if not logged in
+///
+/// # Errors
+///
+/// * [`ApiError::NotLoggedIn`] if there's no valid [`Session`]
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn require_user(db: &Database) -> Result<User, ApiError> {
+    get_current_user(db).await?.ok_or(ApiError::NotLoggedIn)
+}
+
+/// Like [`require_user`], but also checks that the [`User`] has at least the given [`Role`]
+///
+/// # Errors
+///
+/// * [`ApiError::NotLoggedIn`] if there's no valid [`Session`]
+/// * [`ApiError::Forbidden`] if the [`User`]'s [`Role`] is lower than `role`
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn require_role(db: &Database, role: Role) -> Result<User, ApiError> {
+    let user = require_user(db).await?;
+    if user.role < role {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(user)
+}
+
+/// Checks whether the given [`User`] is allowed to create posts and threads
+///
+/// Anonymous posting (`user` is [`None`]) is allowed
+///
+/// # Errors
+///
+/// * [`ApiError::Banned`] if the [`User`] is currently banned
+/// * [`ApiError::Muted`] if the [`User`] is currently muted
+pub fn ensure_can_post(user: Option<&User>) -> Result<(), ApiError> {
+    let Some(user) = user else {
+        return Ok(());
+    };
+    let now = jiff::Timestamp::now();
+
+    if let Some(ban) = user.ban.as_ref().filter(|ban| ban.is_active(now)) {
+        return Err(ApiError::Banned {
+            until: ban.until,
+            reason: ban.reason.clone(),
+        });
+    }
+    if let Some(mute) = user.mute.as_ref().filter(|mute| mute.is_active(now)) {
+        return Err(ApiError::Muted {
+            until: mute.until,
+            reason: mute.reason.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks whether the given [`User`] meets the posting requirements of the [`Forum`],
+/// i.e. [`Forum::min_account_age_hours`] and [`Forum::min_post_count`]
+///
+/// Moderators and groups with [`GroupPermission::BypassPostingRequirements`] are exempt.
+/// Anonymous posting is only allowed in forums without requirements
+///
+/// # Errors
+///
+/// * [`ApiError::NotLoggedIn`] if the forum has requirements and `user` is [`None`]
+/// * [`ApiError::PostingRequirementsNotMet`] if the [`User`] is too new
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn ensure_meets_forum_requirements(
+    forum: &Forum,
+    user: Option<&User>,
+    db: &Database,
+) -> Result<(), ApiError> {
+    if forum.min_account_age_hours.is_none() && forum.min_post_count.is_none() {
+        return Ok(());
+    }
+    let Some(user) = user else {
+        return Err(ApiError::NotLoggedIn);
+    };
+    if user.role >= Role::Moderator
+        || groups::has_permission(Some(user), GroupPermission::BypassPostingRequirements, db)
+            .await?
+    {
+        return Ok(());
+    }
+
+    let not_met = ApiError::PostingRequirementsNotMet {
+        min_account_age_hours: forum.min_account_age_hours,
+        min_post_count: forum.min_post_count,
+    };
+
+    if let Some(min_hours) = forum.min_account_age_hours {
+        let age_hours = jiff::Timestamp::now()
+            .duration_since(user.created_at)
+            .as_hours();
+        if age_hours < i64::from(min_hours) {
+            return Err(not_met);
+        }
+    }
+    if let Some(min_count) = forum.min_post_count {
+        let post_count = Post::collection(db)
+            .count_documents(bson::doc! {
+                "author_id": user.id,
+                "pending_review": {"$ne": true},
+                "deleted_at": null,
+            })
+            .await?;
+        if post_count < u64::from(min_count) {
+            return Err(not_met);
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether `user` stays within the activity caps of the [`Forum`] by writing another post
+/// and, if `new_thread`, creating another thread, see [`Forum::max_threads_per_day`]
+/// and [`Forum::max_posts_per_hour`]
+///
+/// Moderators are exempt. Guests can't be told apart, so they can't post in forums with caps
+///
+/// # Errors
+///
+/// * [`ApiError::NotLoggedIn`] if the forum has caps and `user` is [`None`]
+/// * [`ApiError::ThreadCapReached`] / [`ApiError::PostCapReached`] if a cap is reached,
+///   with the time the next thread or post is possible
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn ensure_within_activity_caps(
+    forum: &Forum,
+    user: Option<&User>,
+    new_thread: bool,
+    db: &Database,
+) -> Result<(), ApiError> {
+    let max_threads = forum.max_threads_per_day.filter(|_| new_thread);
+    if max_threads.is_none() && forum.max_posts_per_hour.is_none() {
+        return Ok(());
+    }
+    let Some(user) = user else {
+        return Err(ApiError::NotLoggedIn);
+    };
+    if user.role >= Role::Moderator {
+        return Ok(());
+    }
+
+    if let Some(max_threads_per_day) = max_threads {
+        // threads are as old as their origin post
+        let origin_post_ids = Thread::collection(db)
+            .distinct(
+                "origin_post_id",
+                bson::doc! {"forum_id": forum.id, "author_id": user.id},
+            )
+            .await?;
+        let posts = bson::doc! {"id": {"$in": origin_post_ids}};
+        let window = jiff::SignedDuration::from_hours(24);
+        if let Some(retry_at) = cap_reached(posts, max_threads_per_day, window, db).await? {
+            return Err(ApiError::ThreadCapReached {
+                max_threads_per_day,
+                retry_at,
+            });
+        }
+    }
+    if let Some(max_posts_per_hour) = forum.max_posts_per_hour {
+        let thread_ids = Thread::collection(db)
+            .distinct("id", bson::doc! {"forum_id": forum.id})
+            .await?;
+        let posts = bson::doc! {"author_id": user.id, "thread_id": {"$in": thread_ids}};
+        let window = jiff::SignedDuration::from_hours(1);
+        if let Some(retry_at) = cap_reached(posts, max_posts_per_hour, window, db).await? {
+            return Err(ApiError::PostCapReached {
+                max_posts_per_hour,
+                retry_at,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks if `max` [`Posts`][Post] matching `filter` were written within the last `window`
+///
+/// Returns when the next one is possible if so, i.e. when enough of them left the window
+async fn cap_reached(
+    mut filter: bson::Document,
+    max: u32,
+    window: jiff::SignedDuration,
+    db: &Database,
+) -> Result<Option<jiff::Timestamp>, ApiError> {
+    let now = jiff::Timestamp::now();
+    let since = now - window;
+    filter.insert(
+        "created_at",
+        bson::doc! {"$gte": bson::DateTime::from_millis(since.as_millisecond())},
+    );
+    let count = Post::collection(db).count_documents(filter.clone()).await?;
+    if count < u64::from(max) {
+        return Ok(None);
+    }
+
+    // a lowered cap needs more than the oldest one to leave the window
+    let blocking = Post::collection(db)
+        .find_one(filter)
+        .sort(bson::doc! {"created_at": 1})
+        .skip(count - u64::from(max))
+        .await?;
+    let written_at = blocking.map_or(now, |post| post.created_at);
+    Ok(Some(written_at + window))
+}
+
+/// Recomputes [`Thread::latest_post_id`] of all [`Threads`][Thread] in the given
+/// [`Forums`][Forum] and their [`Forum::latest_thread_id`] from the visible posts
+///
+/// Needed after deleting posts that might have been the latest ones.
+/// A forum without threads gets `0` as its latest thread
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn refresh_latest_ids(forum_ids: &[u32], db: &Database) -> Result<(), ApiError> {
+    let thread_col = Thread::collection(db);
+    let post_col = Post::collection(db);
+
+    for &forum_id in forum_ids {
+        let mut thread_cursor = thread_col
+            .find(bson::doc! {
+                "forum_id": forum_id,
+                "pending_review": {"$ne": true},
+                "deleted_at": null,
+            })
+            .await?;
+        let mut threads = vec![];
+        while thread_cursor.advance().await? {
+            threads.push(thread_cursor.deserialize_current()?);
+        }
+
+        // (post id, thread id)
+        let mut latest_in_forum = (0, 0);
+        for thread in threads {
+            let Some(latest_post) = post_col
+                .find_one(bson::doc! {
+                    "thread_id": thread.id,
+                    "pending_review": {"$ne": true},
+                    "deleted_at": null,
+                })
+                .sort(bson::doc! {"id": -1})
+                .await?
+            else {
+                continue;
+            };
+            if latest_post.id != thread.latest_post_id {
+                thread_col
+                    .update_one(
+                        bson::doc! {"id": thread.id},
+                        bson::doc! {"$set": {"latest_post_id": latest_post.id}},
+                    )
+                    .await?;
+            }
+            latest_in_forum = latest_in_forum.max((latest_post.id, thread.id));
+        }
+
+        Category::collection(db)
+            .update_one(
+                bson::doc! {"forums.id": forum_id},
+                bson::doc! {"$set": {"forums.$.latest_thread_id": latest_in_forum.1}},
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Points `thread` at its latest visible [`Post`] again after one of its posts appeared or
+/// disappeared and, if that changed it, its forum at the forum's latest thread
+///
+/// Only looks at `thread` and sorts the threads of its forum in the db,
+/// unlike [`refresh_latest_ids`] querying every thread of the forum on its own
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn refresh_latest_of_thread(thread: &Thread, db: &Database) -> Result<(), ApiError> {
+    let latest_post_id = Post::collection(db)
+        .find_one(bson::doc! {
+            "thread_id": thread.id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .sort(bson::doc! {"id": -1})
+        .await?
+        .map_or(thread.origin_post_id, |post| post.id);
+    if latest_post_id == thread.latest_post_id {
+        return Ok(());
+    }
+    Thread::collection(db)
+        .update_one(
+            bson::doc! {"id": thread.id},
+            bson::doc! {"$set": {"latest_post_id": latest_post_id}},
+        )
+        .await?;
+    refresh_latest_of_forum(thread.forum_id, db).await
+}
+
+/// Points the forum with `forum_id` at its visible [`Thread`] with the latest activity,
+/// e.g. after a thread appeared or disappeared
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn refresh_latest_of_forum(forum_id: u32, db: &Database) -> Result<(), ApiError> {
+    let latest_thread_id = Thread::collection(db)
+        .find_one(bson::doc! {
+            "forum_id": forum_id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .sort(bson::doc! {"latest_post_id": -1})
+        .await?
+        .map_or(0, |latest| latest.id);
+    Category::collection(db)
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {"forums.$.latest_thread_id": latest_thread_id}},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Queries database to check if a [`User`] with the given `user_id` exists
+/// and returns it.
+///
+/// # Errors
+///
+/// * [`ApiError::NotFound`] if the `user_id` is not in the db
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn get_user(user_id: u32, db: &Database) -> Result<User, ApiError> {
+    let user_col = User::collection(db);
+    let user = user_col
+        .find_one(bson::doc! {"id": user_id})
+        .await
+        .map_err(|err| ApiError::from(err).in_context(DbOperation::Read, "user", Some(user_id)))?;
+
+    user.ok_or(ApiError::NotFound("user".into(), user_id))
+}
+
+/// Finds the [`Posts`][Post] matching `filter` in `sort` order, each joined with its author
+/// by a single `$lookup` instead of querying every author on its own
+///
+/// Soft-deleted posts are always left out, see [`Post::deleted_at`]
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn find_posts_with_authors(
+    mut filter: bson::Document,
+    sort: bson::Document,
+    limit: Option<i64>,
+    db: &Database,
+) -> Result<Vec<PostWithAuthor>, ApiError> {
+    /// What the pipeline returns, the primary group is looked up afterwards
+    #[derive(serde::Deserialize)]
+    struct Joined {
+        post: Post,
+        #[serde(default)]
+        author: Option<User>,
+    }
+
+    filter.insert("deleted_at", bson::Bson::Null);
+    let mut pipeline = vec![bson::doc! {"$match": filter}, bson::doc! {"$sort": sort}];
+    if let Some(limit) = limit {
+        pipeline.push(bson::doc! {"$limit": limit});
+    }
+    pipeline.extend([
+        bson::doc! {"$lookup": {
+            "from": User::collection_name(),
+            "localField": "author_id",
+            "foreignField": "id",
+            "as": "authors",
+        }},
+        // deleted authors leave `author` out
+        bson::doc! {"$project": {"_id": 0, "post": "$$ROOT", "author": {"$first": "$authors"}}},
+        bson::doc! {"$unset": "post.authors"},
+    ]);
+
+    // there are only a few groups, so they're matched here instead of in another `$lookup`
+    let mut user_groups = vec![];
+    let mut cursor = groups::UserGroup::collection(db)
+        .find(bson::doc! {})
+        .await?;
+    while cursor.advance().await? {
+        user_groups.push(cursor.deserialize_current()?);
+    }
+    let mut posts = vec![];
+    let mut cursor = Post::collection(db)
+        .aggregate(pipeline)
+        .with_type::<Joined>()
+        .await?;
+    while cursor.advance().await? {
+        let Joined { post, author } = cursor.deserialize_current()?;
+        let author = author.map(|user| {
+            let primary_group = user
+                .primary_group_id
+                .and_then(|group_id| {
+                    user_groups
+                        .iter()
+                        .find(|group| group.id == group_id)
+                        .cloned()
+                });
+            groups::Member {
+                user,
+                primary_group,
+            }
+        });
+        posts.push(PostWithAuthor {
+            html: crate::markdown::render(&post.content),
+            post,
+            author,
+        });
+    }
+    attachments::sign_urls(&mut posts);
+    Ok(posts)
+}
+
+/// Looks up the ids of the [`Users`][User] in the comma-separated `names`,
+/// sorted and without duplicates
+///
+/// # Errors
+///
+/// * [`ApiError::UnknownUsername`] if one of the names isn't in use
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn get_user_ids_by_names(names: &str, db: &Database) -> Result<Vec<u32>, ApiError> {
+    let user_col = User::collection(db);
+    let mut ids = vec![];
+    for name in names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let Some(user) = user_col.find_one(bson::doc! {"name": name}).await? else {
+            return Err(ApiError::UnknownUsername(name.to_string()));
+        };
+        ids.push(user.id);
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    Ok(ids)
+}
+
+/// Escapes `text` for literal use in a mongodb `$regex`
+#[must_use]
+pub fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Runs `text` through the [`word_filter`] and returns the (possibly masked) text
+/// and whether it has to be held for review
+///
+/// # Errors
+///
+/// * [`ApiError::ContentBlocked`] if a [`word_filter::FilterAction::Reject`] rule matches
+pub fn run_word_filter(rules: &[WordFilterRule], text: &str) -> Result<(String, bool), ApiError> {
+    match word_filter::apply(rules, text) {
+        Verdict::Allow(text) => Ok((text, false)),
+        Verdict::Hold(text) => Ok((text, true)),
+        Verdict::Reject(word) => Err(ApiError::ContentBlocked(word)),
+    }
+}
+
+/// Creates a thread as `author` (anonymous if [`None`]) in the given [`Forum`]
+/// and returns its id, see [`create_thread`][super::create_thread]
+///
+/// # Errors
+///
+/// Same as [`create_thread`][super::create_thread]
+pub async fn create_thread_as(
+    author: Option<User>,
+    forum_id: u32,
+    subject: String,
+    post_content: String,
+    attachment_ids: &[u32],
+    db: &Database,
+) -> Result<u32, ApiError> {
+    let limits = site_settings::load_settings(db).await?.length_limits;
+    let subject = validation::validate(&subject, Field::Subject, &limits)?;
+    let post_content = validation::validate(&post_content, Field::Content, &limits)?;
+
+    ensure_can_post(author.as_ref())?;
+    let author_id = author.as_ref().map(|author| author.id);
+
+    let rules = word_filter::load_rules(db).await?;
+    let (subject, subject_held) = run_word_filter(&rules, &subject)?;
+    let (post_content, content_held) = run_word_filter(&rules, &post_content)?;
+    let spam_score = spam::held_score(author.as_ref(), &post_content, db).await?;
+    let pending_review = subject_held || content_held || spam_score.is_some();
+    let pending_review = pending_review
+        && !groups::has_permission(author.as_ref(), groups::GroupPermission::SkipReview, db)
+            .await?;
+
+    let (forum, _) = get_forum(forum_id, db.clone()).await?;
+    ensure_can_post_in_forum(&forum, author.as_ref(), db).await?;
+    ensure_meets_forum_requirements(&forum, author.as_ref(), db).await?;
+    ensure_within_activity_caps(&forum, author.as_ref(), true, db).await?;
+    forum_rules::ensure_acknowledged(&forum, author.as_ref(), db).await?;
+
+    let counter_col = Counter::collection(db);
+    let thread_id = get_and_increment_id_of("thread", counter_col.clone()).await?;
+
+    let post_col = Post::collection(db);
+    let post_id = get_and_increment_id_of("post", counter_col).await?;
+    let attachments = attachments::claim(attachment_ids, author.as_ref(), post_id, db).await?;
+    let new_post = Post {
+        id: post_id,
+        content_hash: spam::content_hash(&post_content),
+        content: post_content,
+        created_at: jiff::Timestamp::now(),
+        thread_id,
+        author_id,
+        pending_review,
+        spam_score: spam_score.filter(|_| pending_review),
+        collapsed: false,
+        thanks: 0,
+        revision: 0,
+        edited_at: None,
+        edited_by: None,
+        staff_edit: false,
+        edit_reason: None,
+        highlighted: false,
+        thread_deleted: false,
+        deleted_at: None,
+        deleted_by: None,
+        quote: None,
+        attachments,
+        link_previews: vec![],
+    };
+    post_col.insert_one(&new_post).await?;
+    link_previews::fetch_in_background(post_id, &new_post.content, db);
+    thread_references::record(post_id, thread_id, &new_post.content, db).await?;
+
+    let thread_col = Thread::collection(db);
+    let new_thread = Thread {
+        id: thread_id,
+        origin_post_id: post_id,
+        subject,
+        forum_id,
+        latest_post_id: post_id,
+        author_id,
+        pending_review,
+        locked: false,
+        solved_at: None,
+        wiki_post: false,
+        wiki_editor_ids: vec![],
+        co_author_ids: vec![],
+        deleted_at: None,
+        tags: vec![],
+        prefix: None,
+        listing: None,
+    };
+    thread_col.insert_one(&new_thread).await?;
+    if let Some(author_id) = author_id {
+        read_markers::mark_read(author_id, thread_id, post_id, db).await?;
+    }
+
+    if pending_review {
+        return Err(ApiError::HeldForReview);
+    }
+
+    let category_col = Category::collection(db);
+    // thx gippity?
+    let _ = category_col
+        .update_one(
+            bson::doc! {"forums.id": forum_id},
+            bson::doc! {"$set": {"forums.$.latest_thread_id": thread_id}},
+        )
+        .await?;
+    cache::invalidate_forums(&[forum_id], db).await?;
+    events::emit(
+        DomainEvent::ThreadCreated {
+            forum: &forum,
+            thread: &new_thread,
+            post: &new_post,
+            author: author.as_ref(),
+        },
+        db,
+    );
+
+    Ok(thread_id)
+}
+
+/// Copies the [`Post`] with `post_id` into a [`Quote`] for a new post of `author`
+/// (anonymous if [`None`])
+///
+/// # Errors
+///
+/// * [`ApiError::NotFound`] if the post doesn't exist, is held for review or soft-deleted,
+///   its thread is held or soft-deleted or `author` can't see its forum,
+///   so hidden posts can't be probed
+/// * [`ApiError::Db`] if the db connection fails in any way
+async fn quote_of(post_id: u32, author: Option<&User>, db: &Database) -> Result<Quote, ApiError> {
+    let not_found = || ApiError::NotFound("post".into(), post_id);
+    // soft-deleted posts are already not found here
+    let source = get_post(post_id, db.clone()).await?;
+    if source.pending_review || source.thread_deleted {
+        return Err(not_found());
+    }
+    let thread = get_thread(source.thread_id, db.clone()).await?;
+    if thread.pending_review || thread.deleted_at.is_some() {
+        return Err(not_found());
+    }
+    let forums = get_all_forums(db).await?;
+    if !can_see_forum(&forums, thread.forum_id, author) {
+        return Err(not_found());
+    }
+
+    Ok(Quote {
+        post_id: source.id,
+        revision: source.revision,
+        content: source.content,
+        source_edited: false,
+    })
+}
+
+/// Creates a post as `author` (anonymous if [`None`]) in the given [`Thread`]
+/// and returns its id, see [`create_post`][super::create_post]
+///
+/// # Errors
+///
+/// Same as [`create_post`][super::create_post]
+pub async fn create_post_as(
+    author: Option<User>,
+    thread_id: u32,
+    content: String,
+    quote_post_id: Option<u32>,
+    attachment_ids: &[u32],
+    db: &Database,
+) -> Result<u32, ApiError> {
+    let limits = site_settings::load_settings(db).await?.length_limits;
+    let content = validation::validate(&content, Field::Content, &limits)?;
+    ensure_can_post(author.as_ref())?;
+
+    let rules = word_filter::load_rules(db).await?;
+    let (content, pending_review) = run_word_filter(&rules, &content)?;
+    let spam_score = spam::held_score(author.as_ref(), &content, db).await?;
+    let pending_review = (pending_review || spam_score.is_some())
+        && !groups::has_permission(author.as_ref(), groups::GroupPermission::SkipReview, db)
+            .await?;
+
+    // held and hidden threads can't be replied to, deleted ones not even by moderators
+    let thread = get_visible_thread(thread_id, author.as_ref(), db).await?;
+    if thread.deleted_at.is_some() {
+        return Err(ApiError::NotFound("thread".into(), thread_id));
+    }
+    let (forum, _) = get_forum(thread.forum_id, db.clone()).await?;
+    ensure_can_post_in_forum(&forum, author.as_ref(), db).await?;
+    if thread.is_locked(&forum, jiff::Timestamp::now()) {
+        return Err(ApiError::ThreadLocked);
+    }
+    ensure_meets_forum_requirements(&forum, author.as_ref(), db).await?;
+    ensure_within_activity_caps(&forum, author.as_ref(), false, db).await?;
+    forum_rules::ensure_acknowledged(&forum, author.as_ref(), db).await?;
+
+    let quote = match quote_post_id {
+        Some(quote_post_id) => Some(quote_of(quote_post_id, author.as_ref(), db).await?),
+        None => None,
+    };
+
+    let counter_col = Counter::collection(db);
+    let id = get_and_increment_id_of("post", counter_col).await?;
+    let attachments = attachments::claim(attachment_ids, author.as_ref(), id, db).await?;
+
+    let post_col = Post::collection(db);
+
+    let new_post = Post {
+        id,
+        content_hash: spam::content_hash(&content),
+        content,
+        created_at: jiff::Timestamp::now(),
+        thread_id,
+        author_id: author.as_ref().map(|author| author.id),
+        pending_review,
+        spam_score: spam_score.filter(|_| pending_review),
+        collapsed: false,
+        thanks: 0,
+        revision: 0,
+        edited_at: None,
+        edited_by: None,
+        staff_edit: false,
+        edit_reason: None,
+        highlighted: false,
+        thread_deleted: false,
+        deleted_at: None,
+        deleted_by: None,
+        quote,
+        attachments,
+        link_previews: vec![],
+    };
+
+    post_col.insert_one(&new_post).await?;
+    link_previews::fetch_in_background(id, &new_post.content, db);
+    thread_references::record(id, thread_id, &new_post.content, db).await?;
+    // nobody has to read their own posts
+    if let Some(author) = &author {
+        read_markers::mark_read(author.id, thread_id, id, db).await?;
+    }
+
+    if pending_review {
+        return Err(ApiError::HeldForReview);
+    }
+
+    let thread_col = Thread::collection(db);
+    let _ = thread_col
+        .update_one(
+            bson::doc! {"id": thread_id},
+            bson::doc! {"$set": {"latest_post_id": id}},
+        )
+        .await?;
+
+    let category_col = Category::collection(db);
+    let _ = category_col
+        .update_one(
+            bson::doc! {"forums.id": thread.forum_id},
+            bson::doc! {"$set": {"forums.$.latest_thread_id": thread_id}},
+        )
+        .await?;
+    cache::invalidate_forums(&[thread.forum_id], db).await?;
+    events::emit(
+        DomainEvent::PostCreated {
+            forum: &forum,
+            thread: &thread,
+            post: &new_post,
+            author: author.as_ref(),
+        },
+        db,
+    );
+
+    Ok(id)
+}
@@ -0,0 +1,272 @@
+//! Replying to [`Threads`][super::Thread] by email
+//!
+//! Notification emails about a single thread are sent with a reply address unique to the user
+//! and thread as `Reply-To`, see [`reply_address`]. The email provider forwards replies to that
+//! address to the plain axum route `receive`, which posts them as the owner of the address.
+//!
+//! Spoofed emails are rejected in four ways: the webhook requires the shared secret
+//! `INBOUND_EMAIL_SECRET`, the [`ReplyToken`] in the address can't be guessed,
+//! the provider has to have verified the sender by SPF or DKIM, see [`is_authenticated`],
+//! and the sender has to match the email the user registered with
+
+use super::CollectionName;
+#[cfg(feature = "ssr")]
+use super::{
+    ApiError, GetCollection, bson, cache, helper,
+    user::{AccountDetails, User},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Maps the token in a reply address to the [`User`][super::user::User]
+/// and [`Thread`][super::Thread] it belongs to
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReplyToken {
+    pub token: String,
+    pub user_id: u32,
+    pub thread_id: u32,
+}
+impl CollectionName for ReplyToken {
+    fn collection_name() -> &'static str {
+        "reply_tokens"
+    }
+}
+
+/// An inbound email as posted by the email provider's webhook,
+/// with the headers already decoded to plain text
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InboundEmail {
+    /// e.g. `reply+3f2a…@forum.example` or `Forum <reply+3f2a…@forum.example>`
+    pub to: String,
+    pub from: String,
+    /// Plain text body, including the quoted notification
+    pub text: String,
+    /// The provider's SPF verdict for the domain of `from`, e.g. `pass` or `fail`
+    #[serde(default)]
+    pub spf: String,
+    /// The provider's DKIM verdict for the domain of `from`, e.g. `pass` or `fail`
+    #[serde(default)]
+    pub dkim: String,
+}
+
+/// Whether the provider verified that `email` was sent by the domain of its `from`,
+/// which is only a header anyone can write otherwise
+///
+/// # Example
+///
+/// ```
+/// use app::api::inbound_email::{InboundEmail, is_authenticated};
+///
+/// let email = InboundEmail {
+///     to: "reply+ab12@forum.example".into(),
+///     from: "tom@example.com".into(),
+///     text: "Thanks".into(),
+///     spf: "softfail".into(),
+///     dkim: "Pass".into(),
+/// };
+/// assert!(is_authenticated(&email));
+/// assert!(!is_authenticated(&InboundEmail { dkim: String::new(), ..email }));
+/// ```
+#[must_use]
+pub fn is_authenticated(email: &InboundEmail) -> bool {
+    [&email.spf, &email.dkim]
+        .iter()
+        .any(|verdict| verdict.trim().eq_ignore_ascii_case("pass"))
+}
+
+/// Extracts the bare, lowercase address from a header like `Name <user@example.com>`
+///
+/// # Example
+///
+/// ```
+/// use app::api::inbound_email::address_of;
+///
+/// assert_eq!(address_of("Tom <Tom@Example.com>"), "tom@example.com");
+/// assert_eq!(address_of(" tom@example.com "), "tom@example.com");
+/// ```
+#[must_use]
+pub fn address_of(header: &str) -> String {
+    let address = match (header.find('<'), header.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &header[start + 1..end],
+        _ => header,
+    };
+    address.trim().to_lowercase()
+}
+
+/// Extracts the [`ReplyToken`] from a reply address like `reply+{token}@forum.example`
+///
+/// # Example
+///
+/// ```
+/// use app::api::inbound_email::token_from_address;
+///
+/// assert_eq!(token_from_address("Forum <reply+ab12@forum.example>").as_deref(), Some("ab12"));
+/// assert_eq!(token_from_address("admin@forum.example"), None);
+/// assert_eq!(token_from_address("reply+@forum.example"), None);
+/// ```
+#[must_use]
+pub fn token_from_address(to: &str) -> Option<String> {
+    let address = address_of(to);
+    let (local, _domain) = address.split_once('@')?;
+    let token = local.strip_prefix("reply+")?;
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// Cuts off the quoted notification and signature from the body of a reply
+///
+/// # Example
+///
+/// ```
+/// use app::api::inbound_email::strip_quoted_reply;
+///
+/// let text = "Sounds good!\n\nOn Mon, 3 Mar 2025, Forum wrote:\n> New post in ...";
+/// assert_eq!(strip_quoted_reply(text), "Sounds good!");
+/// assert_eq!(strip_quoted_reply("Thanks\n-- \nTom"), "Thanks");
+/// ```
+#[must_use]
+pub fn strip_quoted_reply(text: &str) -> String {
+    let reply: Vec<&str> = text
+        .lines()
+        .take_while(|line| {
+            let line = line.trim_end();
+            let attribution = line.starts_with("On ") && line.ends_with("wrote:");
+            !(line.starts_with('>') || attribution || line == "--")
+        })
+        .collect();
+    reply.join("\n").trim().to_string()
+}
+
+/// Domain reply addresses are on, set with the `INBOUND_EMAIL_DOMAIN` env var
+///
+/// [`None`] if replying by email is disabled
+#[cfg(feature = "ssr")]
+fn inbound_domain() -> Option<String> {
+    std::env::var("INBOUND_EMAIL_DOMAIN")
+        .ok()
+        .filter(|domain| !domain.is_empty())
+}
+
+/// Returns the address `user_id` can reply to `thread_id` at,
+/// creating its [`ReplyToken`] on first use
+///
+/// [`None`] if replying by email is disabled
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn reply_address(
+    user_id: u32,
+    thread_id: u32,
+    db: &mongodb::Database,
+) -> Result<Option<String>, ApiError> {
+    let Some(domain) = inbound_domain() else {
+        return Ok(None);
+    };
+    let token_col = ReplyToken::collection(db);
+    if let Some(existing) = token_col
+        .find_one(bson::doc! {"user_id": user_id, "thread_id": thread_id})
+        .await?
+    {
+        return Ok(Some(format!("reply+{}@{domain}", existing.token)));
+    }
+
+    let token = helper::random_token();
+    token_col
+        .insert_one(ReplyToken {
+            token: token.clone(),
+            user_id,
+            thread_id,
+        })
+        .await?;
+    Ok(Some(format!("reply+{token}@{domain}")))
+}
+
+/// Posts the reply in `email` as the owner of its [`ReplyToken`], returning the post's id
+/// and the [`Forum`][super::Forum] it's in
+#[cfg(feature = "ssr")]
+async fn post_reply(email: &InboundEmail, db: &mongodb::Database) -> Result<(u32, u32), ApiError> {
+    if !is_authenticated(email) {
+        return Err(ApiError::SenderMismatch);
+    }
+    let Some(token) = token_from_address(&email.to) else {
+        return Err(ApiError::InvalidReplyAddress);
+    };
+    let Some(reply_token) = ReplyToken::collection(db)
+        .find_one(bson::doc! {"token": &token})
+        .await?
+    else {
+        return Err(ApiError::InvalidReplyAddress);
+    };
+
+    let user: User = helper::get_user(reply_token.user_id, db).await?;
+    let registered_email = AccountDetails::collection(db)
+        .find_one(bson::doc! {"user_id": user.id})
+        .await?
+        .and_then(|details| details.email)
+        .map(|email| address_of(&email));
+    if user.merged_into.is_some() || registered_email != Some(address_of(&email.from)) {
+        return Err(ApiError::SenderMismatch);
+    }
+
+    // the reply address outlives access to the forum, so check it like in the browser
+    let thread = helper::get_visible_thread(reply_token.thread_id, Some(&user), db).await?;
+    let content = strip_quoted_reply(&email.text);
+    let post_id = helper::create_post_as(Some(user), thread.id, content, None, &[], db).await?;
+    Ok((post_id, thread.forum_id))
+}
+
+/// Axum handler for `POST /inbound-email`, expects an [`InboundEmail`] as JSON
+/// and the secret as `Authorization: Bearer {INBOUND_EMAIL_SECRET}`
+///
+/// Responds with the id of the created post as JSON
+///
+/// # Errors
+///
+/// Responds with an error status and the [`ApiError`] message as text,
+/// `404` if replying by email is disabled
+#[cfg(feature = "ssr")]
+pub async fn receive(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    axum::Extension(caches): axum::Extension<cache::Caches>,
+    headers: http::HeaderMap,
+    axum::Json(email): axum::Json<InboundEmail>,
+) -> Result<axum::Json<u32>, (http::StatusCode, String)> {
+    use http::StatusCode;
+
+    let secret = std::env::var("INBOUND_EMAIL_SECRET").unwrap_or_default();
+    if secret.is_empty() || inbound_domain().is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "replying by email is disabled".into(),
+        ));
+    }
+    let given = headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if !helper::secrets_match(given, &secret) {
+        return Err((StatusCode::UNAUTHORIZED, "invalid webhook secret".into()));
+    }
+
+    let result = post_reply(&email, &db).await;
+    let (post_id, forum_id) = result.map_err(|err| {
+        let status = match err {
+            ApiError::InvalidReplyAddress | ApiError::NotFound(..) => StatusCode::NOT_FOUND,
+            ApiError::SenderMismatch | ApiError::Banned { .. } | ApiError::Muted { .. } => {
+                StatusCode::FORBIDDEN
+            }
+            ApiError::Db { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            // e.g. empty, locked or held for review, retrying won't help
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        };
+        (status, err.to_string())
+    })?;
+    // the caches aren't in leptos context for plain axum handlers
+    if let Err(err) = cache::invalidate_forums_in(&caches, &[forum_id], &db).await {
+        tracing::warn!("invalidating caches after inbound email failed: {err}");
+    }
+
+    Ok(axum::Json(post_id))
+}
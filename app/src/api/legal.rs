@@ -0,0 +1,243 @@
+//! Legal pages like the Terms of Service, editable by admins
+//!
+//! Every edit creates a new [`LegalPage`] version, older ones are kept.
+//! If a version requires acceptance, logged-in [`Users`][super::user::User]
+//! have to accept it once (tracked in [`LegalAcceptance`])
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, bson, helper, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The kinds of legal pages, each served at its own stable route
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegalPageKind {
+    Terms,
+    Privacy,
+    Imprint,
+}
+impl LegalPageKind {
+    pub const ALL: [Self; 3] = [Self::Terms, Self::Privacy, Self::Imprint];
+
+    /// Route of the page, e.g. `/terms`
+    #[must_use]
+    pub fn path(self) -> &'static str {
+        match self {
+            Self::Terms => "/terms",
+            Self::Privacy => "/privacy",
+            Self::Imprint => "/imprint",
+        }
+    }
+
+    /// Translation key of the human-readable title of the page
+    #[must_use]
+    pub fn title_key(self) -> &'static str {
+        match self {
+            Self::Terms => "legal.terms",
+            Self::Privacy => "legal.privacy",
+            Self::Imprint => "legal.imprint",
+        }
+    }
+
+    /// For use in db queries
+    #[cfg(feature = "ssr")]
+    fn to_bson(self) -> bson::Bson {
+        let Ok(kind) = bson::to_bson(&self) else {
+            unreachable!("a unit enum is always serializable")
+        };
+        kind
+    }
+}
+
+/// A single version of a legal page, written in Markdown
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LegalPage {
+    pub kind: LegalPageKind,
+    /// Starts at `1` and increments with every edit
+    pub version: u32,
+    pub content: String,
+    /// Whether logged-in users have to accept this version before continuing
+    pub requires_acceptance: bool,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+    /// Id of the admin who wrote this version
+    pub author_id: u32,
+}
+impl CollectionName for LegalPage {
+    fn collection_name() -> &'static str {
+        "legal_pages"
+    }
+}
+
+/// Records that a [`User`][super::user::User] accepted a certain [`LegalPage`] version
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LegalAcceptance {
+    pub user_id: u32,
+    pub kind: LegalPageKind,
+    pub version: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub accepted_at: jiff::Timestamp,
+}
+impl CollectionName for LegalAcceptance {
+    fn collection_name() -> &'static str {
+        "legal_acceptances"
+    }
+}
+
+/// A [`LegalPage`] with its content rendered to HTML, ready to be displayed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenderedLegalPage {
+    pub page: LegalPage,
+    pub html: String,
+}
+
+/// Returns the latest version of the given [`LegalPageKind`]
+#[cfg(feature = "ssr")]
+async fn get_latest(
+    kind: LegalPageKind,
+    db: &mongodb::Database,
+) -> Result<Option<LegalPage>, ApiError> {
+    let page = LegalPage::collection(db)
+        .find_one(bson::doc! {"kind": kind.to_bson()})
+        .sort(bson::doc! {"version": -1})
+        .await?;
+    Ok(page)
+}
+
+/// Fetches the given `version` (or the latest if [`None`]) of a legal page rendered to HTML
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if no such version exists (`0` for "no version at all")
+#[server]
+pub async fn get_legal_page(
+    kind: LegalPageKind,
+    version: Option<u32>,
+) -> Result<RenderedLegalPage, ApiError> {
+    let db = helper::get_db()?;
+
+    let page = match version {
+        None => get_latest(kind, &db).await?,
+        Some(version) => {
+            LegalPage::collection(&db)
+                .find_one(bson::doc! {"kind": kind.to_bson(), "version": version})
+                .await?
+        }
+    };
+    let Some(page) = page else {
+        return Err(ApiError::NotFound(
+            "legal page version".into(),
+            version.unwrap_or_default(),
+        ));
+    };
+
+    let html = crate::markdown::render(&page.content);
+    Ok(RenderedLegalPage { page, html })
+}
+
+/// Saves `content` as a new version of the given legal page
+///
+/// Requires [`Role::Admin`]
+///
+/// Returns the new version number
+///
+/// # Errors
+///
+/// - [`ApiError::EmptyContent`] if `content` is empty
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+#[server]
+pub async fn update_legal_page(
+    kind: LegalPageKind,
+    content: String,
+    #[server(default)] requires_acceptance: bool,
+) -> Result<u32, ApiError> {
+    if content.trim().is_empty() {
+        return Err(ApiError::EmptyContent);
+    }
+
+    let db = helper::get_db()?;
+    let admin = helper::require_role(&db, Role::Admin).await?;
+
+    let version = get_latest(kind, &db)
+        .await?
+        .map_or(1, |latest| latest.version + 1);
+
+    let page = LegalPage {
+        kind,
+        version,
+        content,
+        requires_acceptance,
+        created_at: jiff::Timestamp::now(),
+        author_id: admin.id,
+    };
+    LegalPage::collection(&db).insert_one(&page).await?;
+
+    Ok(version)
+}
+
+/// Returns the latest versions of legal pages the logged-in user still has to accept
+///
+/// Always empty when not logged in
+#[server]
+pub async fn get_pending_legal_pages() -> Result<Vec<LegalPage>, ApiError> {
+    let db = helper::get_db()?;
+    let Some(user) = helper::get_current_user(&db).await? else {
+        return Ok(vec![]);
+    };
+
+    let acceptance_col = LegalAcceptance::collection(&db);
+    let mut pending = vec![];
+    for kind in LegalPageKind::ALL {
+        let Some(page) = get_latest(kind, &db).await? else {
+            continue;
+        };
+        if !page.requires_acceptance {
+            continue;
+        }
+        let accepted = acceptance_col
+            .find_one(
+                bson::doc! {"user_id": user.id, "kind": kind.to_bson(), "version": page.version},
+            )
+            .await?
+            .is_some();
+        if !accepted {
+            pending.push(page);
+        }
+    }
+
+    Ok(pending)
+}
+
+/// Records that the logged-in user accepted the given `version` of a legal page
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if the version doesn't exist
+#[server]
+pub async fn accept_legal_page(kind: LegalPageKind, version: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    if LegalPage::collection(&db)
+        .find_one(bson::doc! {"kind": kind.to_bson(), "version": version})
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::NotFound("legal page version".into(), version));
+    }
+
+    let acceptance = LegalAcceptance {
+        user_id: user.id,
+        kind,
+        version,
+        accepted_at: jiff::Timestamp::now(),
+    };
+    LegalAcceptance::collection(&db)
+        .insert_one(&acceptance)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,494 @@
+//! Preview cards of links in posts, built from the `OpenGraph` metadata of the linked page
+//!
+//! Once a [`Post`][super::Post] is created, the bare urls in it (see [`find_bare_urls`])
+//! are fetched in the background and the resulting [`LinkPreviews`][LinkPreview] are stored
+//! on the post, so showing it never waits for other sites.
+//!
+//! Fetching urls chosen by users is a way into the server's network, so only `http(s)` urls
+//! resolving exclusively to [public addresses][is_public_ip] are fetched. The connection is
+//! pinned to the checked addresses and redirects are followed by hand, checking every hop.
+//! Responses have to arrive within [`FETCH_TIMEOUT_SECS`] and only the first
+//! [`MAX_BODY_BYTES`] are read. Results, failures included, are cached for
+//! [`CACHE_HOURS`] in the [`CachedLinkPreview`] collection
+
+use super::CollectionName;
+#[cfg(feature = "ssr")]
+use super::{ApiError, GetCollection, Post, bson};
+
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// How many links of a post get a preview at most
+pub const MAX_PREVIEWS_PER_POST: usize = 3;
+/// How long fetching a page may take, redirects included
+pub const FETCH_TIMEOUT_SECS: u64 = 5;
+/// How many redirects are followed
+pub const MAX_REDIRECTS: usize = 3;
+/// How much of a page is read, the metadata is in its head anyway
+pub const MAX_BODY_BYTES: usize = 256 * 1024;
+/// How long a fetched page (or the failure to fetch it) is reused
+pub const CACHE_HOURS: i64 = 24;
+/// Longest [`LinkPreview::title`] in characters, longer ones are cut off
+pub const MAX_TITLE_LEN: usize = 150;
+/// Longest [`LinkPreview::description`] in characters, longer ones are cut off
+pub const MAX_DESCRIPTION_LEN: usize = 300;
+
+/// What's shown of a linked page below the post
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LinkPreview {
+    /// The link as written in the post
+    pub url: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Absolute `https://` url of the page's `og:image`
+    #[serde(default)]
+    pub image_url: Option<String>,
+    /// `og:site_name`, the host if the page doesn't have one
+    #[serde(default)]
+    pub site_name: Option<String>,
+}
+
+/// The last attempt to fetch `url`, see the [module docs][self]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CachedLinkPreview {
+    pub url: String,
+    /// [`None`] if fetching failed or the page has no title
+    pub preview: Option<LinkPreview>,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub fetched_at: jiff::Timestamp,
+}
+impl CollectionName for CachedLinkPreview {
+    fn collection_name() -> &'static str {
+        "link_previews"
+    }
+}
+
+/// Finds the urls written as they are in `content`, i.e. not as the target of a Markdown link
+/// and not in code. Trailing punctuation isn't part of an url, duplicates are dropped and only
+/// the first [`MAX_PREVIEWS_PER_POST`] are returned
+///
+/// # Example
+///
+/// ```
+/// use app::api::link_previews::find_bare_urls;
+///
+/// let content = "See https://example.com/a, [this](https://example.com/b) \
+///     and `https://example.com/c` (or <https://example.com/d>)\n\
+///     ```\nhttps://example.com/e\n```\nhttps://example.com/a";
+/// assert_eq!(
+///     find_bare_urls(content),
+///     vec!["https://example.com/a", "https://example.com/d"]
+/// );
+/// ```
+#[must_use]
+pub fn find_bare_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = vec![];
+    let mut in_code_block = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        for word in line.split_whitespace() {
+            let word = word
+                .trim_start_matches(['(', '<'])
+                .trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '>', '"', '\'']);
+            let is_url = ["https://", "http://"]
+                .iter()
+                .any(|scheme| word.len() > scheme.len() && word.starts_with(scheme));
+            if is_url && !urls.iter().any(|url| url == word) {
+                urls.push(word.to_string());
+                if urls.len() == MAX_PREVIEWS_PER_POST {
+                    return urls;
+                }
+            }
+        }
+    }
+    urls
+}
+
+/// Checks if `ip` can be reached from the internet, i.e. it's none of the loopback, private,
+/// link-local, shared, documentation, multicast or reserved addresses.
+/// Pages are only fetched from these, so links can't reach the server's own network
+///
+/// # Example
+///
+/// ```
+/// use app::api::link_previews::is_public_ip;
+///
+/// assert!(is_public_ip("93.184.215.14".parse().unwrap()));
+/// assert!(is_public_ip("2606:2800:21f:cb07:6820:80da:af6b:8b2c".parse().unwrap()));
+/// assert!(!is_public_ip("127.0.0.1".parse().unwrap()));
+/// assert!(!is_public_ip("10.1.2.3".parse().unwrap()));
+/// assert!(!is_public_ip("169.254.169.254".parse().unwrap()));
+/// assert!(!is_public_ip("100.64.0.1".parse().unwrap()));
+/// assert!(!is_public_ip("::1".parse().unwrap()));
+/// assert!(!is_public_ip("fd00::1".parse().unwrap()));
+/// assert!(!is_public_ip("::ffff:192.168.0.1".parse().unwrap()));
+/// ```
+#[must_use]
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_ipv4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ipv4(ip),
+            None => {
+                let is_documentation = ip.segments()[..2] == [0x2001, 0x0db8];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+                    || is_documentation)
+            }
+        },
+    }
+}
+
+fn is_public_ipv4(ip: Ipv4Addr) -> bool {
+    let [first, second, ..] = ip.octets();
+    // 100.64.0.0/10, used by carrier-grade NAT
+    let is_shared = first == 100 && (64..128).contains(&second);
+    !(ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        || is_shared
+        // 0.0.0.0/8 and 240.0.0.0/4
+        || first == 0
+        || first >= 240)
+}
+
+/// Reads the `OpenGraph` metadata of the page at `url` from its `html`,
+/// falling back to the `<title>` and the `description` meta tag.
+/// [`None`] if the page has no title at all
+///
+/// [`LinkPreview::image_url`] is taken as it is, it still has to be made absolute
+///
+/// # Example
+///
+/// ```
+/// use app::api::link_previews::parse_open_graph;
+///
+/// let html = r#"<html><head><title>Fallback</title>
+///     <META property="og:title" content="Tom &amp; Jerry">
+///     <meta name='description' content='A "classic"' />
+///     <meta property="og:image" content="/cover.png"></head></html>"#;
+/// let preview = parse_open_graph(html, "https://example.com").unwrap();
+/// assert_eq!(preview.title, "Tom & Jerry");
+/// assert_eq!(preview.description.as_deref(), Some("A \"classic\""));
+/// assert_eq!(preview.image_url.as_deref(), Some("/cover.png"));
+///
+/// let html = "<title>\n  Just a  title </title>";
+/// assert_eq!(parse_open_graph(html, "https://example.com").unwrap().title, "Just a title");
+/// assert!(parse_open_graph("<p>nothing</p>", "https://example.com").is_none());
+/// ```
+#[must_use]
+pub fn parse_open_graph(html: &str, url: &str) -> Option<LinkPreview> {
+    // ASCII lowercasing keeps the byte positions, so they can be used on `html` too
+    let lower = html.to_ascii_lowercase();
+
+    let mut title = None;
+    let mut description = None;
+    let mut image_url = None;
+    let mut site_name = None;
+    let mut fallback_description = None;
+    for (start, _) in lower.match_indices("<meta") {
+        let rest = &html[start + "<meta".len()..];
+        if !rest.starts_with(|char: char| char.is_ascii_whitespace()) {
+            continue;
+        }
+        let attributes = attributes(rest);
+        let value_of = |name: &str| {
+            attributes
+                .iter()
+                .find(|(attribute, _)| attribute == name)
+                .map(|(_, value)| value.clone())
+        };
+        let (Some(key), Some(content)) = (
+            value_of("property").or_else(|| value_of("name")),
+            value_of("content"),
+        ) else {
+            continue;
+        };
+        let field = match key.to_ascii_lowercase().as_str() {
+            "og:title" => &mut title,
+            "og:description" => &mut description,
+            "og:image" => &mut image_url,
+            "og:site_name" => &mut site_name,
+            "description" => &mut fallback_description,
+            _ => continue,
+        };
+        field.get_or_insert(content);
+    }
+
+    let title = title.or_else(|| {
+        let start = lower.find("<title")?;
+        let start = start + lower[start..].find('>')? + 1;
+        let end = start + lower[start..].find("</title")?;
+        Some(decode_entities(&html[start..end]))
+    })?;
+    let title = truncate(&title, MAX_TITLE_LEN);
+    if title.is_empty() {
+        return None;
+    }
+    let description = description
+        .or(fallback_description)
+        .map(|description| truncate(&description, MAX_DESCRIPTION_LEN))
+        .filter(|description| !description.is_empty());
+
+    Some(LinkPreview {
+        url: url.to_string(),
+        title,
+        description,
+        image_url: image_url.filter(|image_url| !image_url.is_empty()),
+        site_name: site_name.filter(|site_name| !site_name.is_empty()),
+    })
+}
+
+/// Parses the attributes at the start of `tag`, which begins right after the tag's name,
+/// into lowercase names and decoded values, stopping at its end
+fn attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = vec![];
+    let mut rest = tag;
+    loop {
+        rest = rest.trim_start_matches(|char: char| char.is_ascii_whitespace() || char == '/');
+        let name_end = rest
+            .find(|char: char| char.is_ascii_whitespace() || char == '=' || char == '>')
+            .unwrap_or(rest.len());
+        // the end of the tag or something broken
+        if name_end == 0 {
+            return attributes;
+        }
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = "";
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after[1..];
+                    let end = inner.find(quote).unwrap_or(inner.len());
+                    value = &inner[..end];
+                    rest = inner.get(end + 1..).unwrap_or_default();
+                }
+                _ => {
+                    let end = after
+                        .find(|char: char| char.is_ascii_whitespace() || char == '>')
+                        .unwrap_or(after.len());
+                    value = &after[..end];
+                    rest = &after[end..];
+                }
+            }
+        }
+        attributes.push((name, decode_entities(value)));
+    }
+}
+
+/// Decodes the few HTML entities that commonly show up in titles and descriptions
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&nbsp;", " ")
+        // last, so e.g. `&amp;lt;` stays `&lt;`
+        .replace("&amp;", "&")
+}
+
+/// Collapses the whitespace of `text` and cuts it off after `max` characters
+fn truncate(text: &str, max: usize) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", text[..end].trim_end()),
+        None => text,
+    }
+}
+
+/// Fetches the previews of the [bare urls][find_bare_urls] in `content` in the background
+/// and stores them on the [`Post`] with `post_id`
+#[cfg(feature = "ssr")]
+pub fn fetch_in_background(post_id: u32, content: &str, db: &mongodb::Database) {
+    let urls = find_bare_urls(content);
+    if urls.is_empty() {
+        return;
+    }
+
+    let db = db.clone();
+    tokio::spawn(async move {
+        if let Err(err) = store_previews(post_id, &urls, &db).await {
+            tracing::warn!("storing link previews of post {post_id} failed: {err}");
+        }
+    });
+}
+
+#[cfg(feature = "ssr")]
+async fn store_previews(
+    post_id: u32,
+    urls: &[String],
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let mut previews = vec![];
+    for url in urls {
+        if let Some(preview) = cached_preview(url, db).await? {
+            previews.push(preview);
+        }
+    }
+    if previews.is_empty() {
+        return Ok(());
+    }
+
+    let Ok(previews) = bson::to_bson(&previews) else {
+        unreachable!("LinkPreviews are always serializable")
+    };
+    Post::collection(db)
+        .update_one(
+            bson::doc! {"id": post_id},
+            bson::doc! {"$set": {"link_previews": previews}},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the [`LinkPreview`] of `url` from the cache, fetching it if it's missing or expired
+#[cfg(feature = "ssr")]
+async fn cached_preview(
+    url: &str,
+    db: &mongodb::Database,
+) -> Result<Option<LinkPreview>, ApiError> {
+    use jiff::ToSpan;
+
+    let cache_col = CachedLinkPreview::collection(db);
+    let now = jiff::Timestamp::now();
+    let fresh_since = now - CACHE_HOURS.hours();
+    let fresh_since = bson::DateTime::from_millis(fresh_since.as_millisecond());
+    let cached = cache_col
+        .find_one(bson::doc! {"url": url, "fetched_at": {"$gte": fresh_since}})
+        .await?;
+    if let Some(cached) = cached {
+        return Ok(cached.preview);
+    }
+
+    let timeout = std::time::Duration::from_secs(FETCH_TIMEOUT_SECS);
+    let preview = match tokio::time::timeout(timeout, fetch(url)).await {
+        Ok(Ok(preview)) => Some(preview),
+        Ok(Err(err)) => {
+            tracing::info!("no link preview of {url}: {err}");
+            None
+        }
+        Err(_) => {
+            tracing::info!("no link preview of {url}: timed out");
+            None
+        }
+    };
+    let cached = CachedLinkPreview {
+        url: url.to_string(),
+        preview: preview.clone(),
+        fetched_at: now,
+    };
+    cache_col
+        .replace_one(bson::doc! {"url": url}, cached)
+        .upsert(true)
+        .await?;
+
+    Ok(preview)
+}
+
+/// Fetches the page at `url` and reads its [`LinkPreview`], following redirects by hand
+/// so that every hop is checked, see the [module docs][self]
+///
+/// Returns why it failed otherwise
+#[cfg(feature = "ssr")]
+async fn fetch(url: &str) -> Result<LinkPreview, String> {
+    let mut target = reqwest::Url::parse(url).map_err(|err| err.to_string())?;
+    for _ in 0..=MAX_REDIRECTS {
+        let client = pinned_client(&target).await?;
+        let mut res = client
+            .get(target.clone())
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        if res.status().is_redirection() {
+            let location = res
+                .headers()
+                .get(http::header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .ok_or("redirected without a location")?;
+            target = target.join(location).map_err(|err| err.to_string())?;
+            continue;
+        }
+        if !res.status().is_success() {
+            return Err(format!("responded with {}", res.status()));
+        }
+        let is_html = res
+            .headers()
+            .get(http::header::CONTENT_TYPE)
+            .and_then(|content_type| content_type.to_str().ok())
+            .is_some_and(|content_type| content_type.starts_with("text/html"));
+        if !is_html {
+            return Err("not an HTML page".into());
+        }
+
+        let mut body = vec![];
+        while let Some(chunk) = res.chunk().await.map_err(|err| err.to_string())? {
+            body.extend_from_slice(&chunk);
+            if body.len() >= MAX_BODY_BYTES {
+                break;
+            }
+        }
+        let html = String::from_utf8_lossy(&body);
+        let mut preview = parse_open_graph(&html, url).ok_or("the page has no title")?;
+        // relative images are relative to where the page ended up, only https ones are shown
+        // so they don't turn the page into mixed content
+        preview.image_url = preview
+            .image_url
+            .and_then(|image_url| target.join(&image_url).ok())
+            .filter(|image_url| image_url.scheme() == "https")
+            .map(String::from);
+        preview.site_name = preview
+            .site_name
+            .or_else(|| target.host_str().map(String::from));
+        return Ok(preview);
+    }
+    Err("too many redirects".into())
+}
+
+/// Builds a client that connects to the host of `url` only through the addresses checked
+/// with [`is_public_ip`], so a second DNS lookup can't point it elsewhere
+#[cfg(feature = "ssr")]
+async fn pinned_client(url: &reqwest::Url) -> Result<reqwest::Client, String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("unsupported scheme {}", url.scheme()));
+    }
+    let host = url.host_str().ok_or("no host")?;
+    let port = url.port_or_known_default().ok_or("no port")?;
+    let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((host, port))
+        .await
+        .map_err(|err| err.to_string())?
+        .collect();
+    // all of them, otherwise a host could list a public and a private address
+    if addrs.is_empty() || !addrs.iter().all(|addr| is_public_ip(addr.ip())) {
+        return Err(format!("{host} doesn't resolve to public addresses only"));
+    }
+
+    reqwest::Client::builder()
+        .resolve_to_addrs(host, &addrs)
+        .redirect(reqwest::redirect::Policy::none())
+        .connect_timeout(std::time::Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .timeout(std::time::Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .user_agent("Dafoerum-LinkPreviews")
+        .build()
+        .map_err(|err| err.to_string())
+}
@@ -0,0 +1,229 @@
+//! Emails about logins from new devices
+//!
+//! A device is the pair of IP address and user agent a [`User`] logged in or registered from,
+//! remembered as a [`KnownDevice`] by its hash. Logging in from a device the user hasn't used
+//! before emails them the details of the login with a link to undo it, see [`LoginAlert`].
+//! One click there ends all of their [`Sessions`][super::user::Session] and sets a new password,
+//! see [`revoke_login`]
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, bson, helper, mailer,
+    user::{self, AccountDetails, User},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How long the link in a [`LoginAlert`] email works
+pub const ALERT_LINK_DAYS: i64 = 7;
+
+/// A device a [`User`][super::user::User] has logged in from before
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct KnownDevice {
+    pub user_id: u32,
+    /// Hex SHA-256 of the IP address and user agent, so neither is stored here
+    pub device_hash: String,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub last_login_at: jiff::Timestamp,
+}
+impl CollectionName for KnownDevice {
+    fn collection_name() -> &'static str {
+        "known_devices"
+    }
+}
+
+/// A login from a new device that was emailed to the [`User`][super::user::User],
+/// whose [`token`][LoginAlert::token] is the secret part of the link undoing it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LoginAlert {
+    /// Random, see [`helper::random_token`]
+    pub token: String,
+    pub user_id: u32,
+    pub ip: Option<String>,
+    pub user_agent: String,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub logged_in_at: jiff::Timestamp,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub expires_at: jiff::Timestamp,
+}
+impl CollectionName for LoginAlert {
+    fn collection_name() -> &'static str {
+        "login_alerts"
+    }
+}
+
+/// Path of the page undoing a [`LoginAlert`], e.g. `/account/revoke/3f2a...`
+#[must_use]
+pub fn revoke_path(token: &str) -> String {
+    format!("/account/revoke/{token}")
+}
+
+/// Hex SHA-256 of `ip` and `user_agent`, identifying a [`KnownDevice`]
+#[cfg(feature = "ssr")]
+fn device_hash(ip: Option<&str>, user_agent: &str) -> String {
+    use sha2::Digest;
+    use std::fmt::Write;
+
+    sha2::Sha256::new()
+        .chain_update(ip.unwrap_or_default())
+        .chain_update("\n")
+        .chain_update(user_agent)
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut hash, byte| {
+            let _ = write!(hash, "{byte:02x}");
+            hash
+        })
+}
+
+/// Remembers the device of the current request as a [`KnownDevice`] of `user`
+///
+/// If it's new, `alert` is set and `user` has an email, a [`LoginAlert`] is emailed
+/// to them in the background
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub(super) async fn record_login(
+    user: &User,
+    alert: bool,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let user_agent = use_context::<http::request::Parts>()
+        .and_then(|parts| {
+            parts
+                .headers
+                .get(http::header::USER_AGENT)
+                .and_then(|header| header.to_str().ok())
+                .map(str::to_string)
+        })
+        .unwrap_or_default();
+    let ip = helper::get_client_ip();
+    let now = jiff::Timestamp::now();
+
+    let result = KnownDevice::collection(db)
+        .update_one(
+            bson::doc! {
+                "user_id": user.id,
+                "device_hash": device_hash(ip.as_deref(), &user_agent),
+            },
+            bson::doc! {"$set": {"last_login_at": bson::DateTime::from_millis(now.as_millisecond())}},
+        )
+        .upsert(true)
+        .await?;
+    // only set if the device wasn't known yet
+    if !alert || result.upserted_id.is_none() {
+        return Ok(());
+    }
+    let email = AccountDetails::collection(db)
+        .find_one(bson::doc! {"user_id": user.id})
+        .await?
+        .and_then(|details| details.email);
+    let Some(email) = email else {
+        return Ok(());
+    };
+
+    let login_alert = LoginAlert {
+        token: helper::random_token(),
+        user_id: user.id,
+        ip,
+        user_agent,
+        logged_in_at: now,
+        expires_at: now + jiff::SignedDuration::from_hours(ALERT_LINK_DAYS * 24),
+    };
+    LoginAlert::collection(db).insert_one(&login_alert).await?;
+
+    let email = alert_email(&email, &user.name, &login_alert);
+    tokio::spawn(async move {
+        if let Err(err) = mailer::send(&email).await {
+            tracing::warn!("sending a login alert to {} failed: {err}", email.to);
+        }
+    });
+    Ok(())
+}
+
+/// The email telling `name` about the login of `alert`
+#[cfg(feature = "ssr")]
+fn alert_email(to: &str, name: &str, alert: &LoginAlert) -> mailer::Email {
+    let link = format!(
+        "{}{}",
+        helper::public_url().unwrap_or_default(),
+        revoke_path(&alert.token)
+    );
+    let text = format!(
+        "Hi {name},\n\n\
+         someone just logged in to your account from a device you haven't used before:\n\n\
+         Time: {time}\n\
+         IP address: {ip}\n\
+         Browser: {browser}\n\n\
+         If this was you, you can ignore this email.\n\
+         If it wasn't, open this link to log out everywhere and choose a new password:\n\
+         {link}\n\n\
+         The link works for {ALERT_LINK_DAYS} days.\n",
+        time = alert.logged_in_at.strftime("%F %R UTC"),
+        ip = alert.ip.as_deref().unwrap_or("unknown"),
+        browser = if alert.user_agent.is_empty() {
+            "unknown"
+        } else {
+            &alert.user_agent
+        },
+    );
+    mailer::Email::new(to, "New login to your Dafoerum account".to_string(), text)
+}
+
+/// Looks up the unexpired [`LoginAlert`] with `token`
+///
+/// # Errors
+///
+/// * [`ApiError::InvalidLoginAlertLink`] if there is none
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+async fn find_alert(token: &str, db: &mongodb::Database) -> Result<LoginAlert, ApiError> {
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    LoginAlert::collection(db)
+        .find_one(bson::doc! {"token": token, "expires_at": {"$gt": now}})
+        .await?
+        .ok_or(ApiError::InvalidLoginAlertLink)
+}
+
+/// Returns the [`LoginAlert`] a link leads to, anyone with the link can see it
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidLoginAlertLink`] if the link is unknown, expired or already used
+#[server]
+pub async fn get_login_alert(token: String) -> Result<LoginAlert, ApiError> {
+    let db = helper::get_db()?;
+    find_alert(&token, &db).await
+}
+
+/// Undoes the login of a [`LoginAlert`]: ends all sessions of its user, including the one of
+/// this browser, sets `password` as their new password and forgets their [`KnownDevices`][KnownDevice],
+/// so the next login from anywhere is emailed again
+///
+/// All links of the user's other alerts stop working, too
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidLoginAlertLink`] if the link is unknown, expired or already used
+/// - [`ApiError::PasswordTooShort`] if `password` is shorter than [`user::MIN_PASSWORD_LEN`]
+#[server]
+pub async fn revoke_login(token: String, password: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let alert = find_alert(&token, &db).await?;
+    if password.chars().count() < user::MIN_PASSWORD_LEN {
+        return Err(ApiError::PasswordTooShort);
+    }
+
+    user::reset_password(alert.user_id, &password, &db).await?;
+    let by_user_id = bson::doc! {"user_id": alert.user_id};
+    KnownDevice::collection(&db)
+        .delete_many(by_user_id.clone())
+        .await?;
+    LoginAlert::collection(&db).delete_many(by_user_id).await?;
+
+    Ok(())
+}
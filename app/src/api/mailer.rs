@@ -0,0 +1,92 @@
+//! Sending emails through the email provider's HTTP API
+//!
+//! Each [`Email`] is posted as JSON to the `OUTBOUND_EMAIL_URL` env var with
+//! `Authorization: Bearer {OUTBOUND_EMAIL_TOKEN}`, sent from the `EMAIL_FROM` address.
+//! Emails about a single thread can be answered, see [`Email::reply_to`].
+//! Without `OUTBOUND_EMAIL_URL` sending emails is disabled and they're only logged,
+//! so instances without an email provider keep working
+
+use serde::Serialize;
+
+/// How long the provider may take to accept an email
+const SEND_TIMEOUT_SECS: u64 = 10;
+
+/// An email as posted to the provider
+#[derive(Serialize, Debug, Clone)]
+pub struct Email {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    /// Plain text body
+    pub text: String,
+    /// Where answers go instead of `from`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_to: Option<String>,
+}
+impl Email {
+    /// An email to `to` from the `EMAIL_FROM` address,
+    /// `dafoerum@localhost` if it isn't set
+    #[must_use]
+    pub fn new(to: &str, subject: String, text: String) -> Self {
+        let from = std::env::var("EMAIL_FROM")
+            .ok()
+            .filter(|from| !from.is_empty())
+            .unwrap_or_else(|| "dafoerum@localhost".to_string());
+        Self {
+            from,
+            to: to.to_string(),
+            subject,
+            text,
+            reply_to: None,
+        }
+    }
+
+    /// Sends answers to `reply_to` instead of `from`, usually the
+    /// [reply address][super::inbound_email::reply_address] of a thread
+    #[must_use]
+    pub fn reply_to(mut self, reply_to: Option<String>) -> Self {
+        self.reply_to = reply_to;
+        self
+    }
+}
+
+/// Sends `email` through the provider, see the [module docs][self]
+///
+/// # Errors
+///
+/// Returns why the provider didn't accept the email
+pub async fn send(email: &Email) -> Result<(), String> {
+    static CLIENT: std::sync::LazyLock<reqwest::Client> = std::sync::LazyLock::new(|| {
+        reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(SEND_TIMEOUT_SECS))
+            .user_agent("Dafoerum-Mailer")
+            .build()
+            .unwrap_or_default()
+    });
+
+    let Some(url) = std::env::var("OUTBOUND_EMAIL_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+    else {
+        tracing::info!(
+            "sending emails is disabled, not sending {:?} to {}",
+            email.subject,
+            email.to
+        );
+        return Ok(());
+    };
+    let token = std::env::var("OUTBOUND_EMAIL_TOKEN").unwrap_or_default();
+
+    let res = CLIENT
+        .post(url)
+        .bearer_auth(token)
+        .json(email)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    if res.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("responded with {}", res.status()))
+    }
+}
@@ -0,0 +1,227 @@
+//! Marketplace threads: a [`Thread`] can carry a [`Listing`] of something for sale,
+//! with a price, a location and whether it's still available
+//!
+//! The listing is shown as a card above the origin post. Marking it as sold sets the
+//! [`Thread::prefix`] to [`SOLD_PREFIX`] and can lock the thread, so no more offers come in
+
+use super::{
+    ApiError, Thread,
+    user::{Role, User},
+};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, bson, helper};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a [`Listing::location`] in characters
+pub const MAX_LOCATION_LEN: usize = 100;
+/// The [`Thread::prefix`] of sold listings
+pub const SOLD_PREFIX: &str = "Sold";
+
+/// Whether the item of a [`Listing`] can still be bought
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListingStatus {
+    #[default]
+    Available,
+    Sold,
+}
+impl ListingStatus {
+    /// Translation key of the human-readable name of the status
+    #[must_use]
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Available => "listing.status.available",
+            Self::Sold => "listing.status.sold",
+        }
+    }
+}
+
+/// The structured fields of a marketplace [`Thread`], see the [module docs][self]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    /// In cents, see [`parse_price`]
+    pub price_cents: u64,
+    pub location: String,
+    #[serde(default)]
+    pub status: ListingStatus,
+}
+impl Listing {
+    /// The price as written by users, see [`format_price`]
+    #[must_use]
+    pub fn price(&self) -> String {
+        format_price(self.price_cents)
+    }
+
+    /// Checks if `user` may change this listing of `thread`, i.e. its author, a co-author
+    /// or a moderator
+    #[must_use]
+    pub fn can_be_managed_by(thread: &Thread, user: &User) -> bool {
+        thread.author_id == Some(user.id)
+            || thread.is_co_author(user)
+            || user.role >= Role::Moderator
+    }
+}
+
+/// Parses a price like `12`, `12.5` or `12,50` into cents,
+/// [`None`] if it isn't a non-negative amount with at most two decimals
+///
+/// # Example
+///
+/// ```
+/// use app::api::marketplace::parse_price;
+///
+/// assert_eq!(parse_price("12"), Some(1200));
+/// assert_eq!(parse_price(" 12.5 "), Some(1250));
+/// assert_eq!(parse_price("0,99"), Some(99));
+/// assert_eq!(parse_price("1.999"), None);
+/// assert_eq!(parse_price("-1"), None);
+/// assert_eq!(parse_price("."), None);
+/// ```
+#[must_use]
+pub fn parse_price(price: &str) -> Option<u64> {
+    let price = price.trim();
+    let (whole, fraction) = price.split_once(['.', ',']).unwrap_or((price, ""));
+    let is_number = |digits: &str| digits.chars().all(|char| char.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > 2
+        || !is_number(whole)
+        || !is_number(fraction)
+    {
+        return None;
+    }
+
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().ok()?
+    };
+    // "5" are 50 cents
+    let cents: u64 = format!("{fraction:0<2}").parse().ok()?;
+    whole.checked_mul(100)?.checked_add(cents)
+}
+
+/// Formats `cents` like `12.50`, see [`parse_price`]
+///
+/// # Example
+///
+/// ```
+/// use app::api::marketplace::format_price;
+///
+/// assert_eq!(format_price(1250), "12.50");
+/// assert_eq!(format_price(7), "0.07");
+/// ```
+#[must_use]
+pub fn format_price(cents: u64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Returns the [`Thread`] with `thread_id` if the logged-in [`User`] may manage its listing
+#[cfg(feature = "ssr")]
+async fn get_managed_thread(thread_id: u32, db: &mongodb::Database) -> Result<Thread, ApiError> {
+    let user = helper::require_user(db).await?;
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if !Listing::can_be_managed_by(&thread, &user) {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(thread)
+}
+
+/// Turns the [`Thread`] with `thread_id` into a marketplace thread or updates its [`Listing`],
+/// keeping its [`ListingStatus`]
+///
+/// Only the author, its [co-authors][Thread::co_author_ids] and moderators may do this
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use
+/// - [`ApiError::InvalidPrice`] if `price` can't be [parsed][parse_price]
+/// - [`ApiError::InvalidListingLocation`] if `location` is empty or longer than
+///   [`MAX_LOCATION_LEN`]
+#[server]
+pub async fn set_listing(thread_id: u32, price: String, location: String) -> Result<(), ApiError> {
+    let price_cents = parse_price(&price).ok_or(ApiError::InvalidPrice(price))?;
+    let location = location.trim().to_string();
+    if location.is_empty() || location.chars().count() > MAX_LOCATION_LEN {
+        return Err(ApiError::InvalidListingLocation);
+    }
+
+    let db = helper::get_db()?;
+    let thread = get_managed_thread(thread_id, &db).await?;
+    let listing = Listing {
+        price_cents,
+        location,
+        status: thread
+            .listing
+            .map(|listing| listing.status)
+            .unwrap_or_default(),
+    };
+    let Ok(listing) = bson::to_bson(&listing) else {
+        unreachable!("a Listing is always serializable")
+    };
+    Thread::collection(&db)
+        .update_one(
+            bson::doc! {"id": thread_id},
+            bson::doc! {"$set": {"listing": listing}},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Turns the marketplace [`Thread`] with `thread_id` back into a normal one
+///
+/// Only the author, its [co-authors][Thread::co_author_ids] and moderators may do this
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use
+#[server]
+pub async fn remove_listing(thread_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    get_managed_thread(thread_id, &db).await?;
+    Thread::collection(&db)
+        .update_one(
+            bson::doc! {"id": thread_id},
+            bson::doc! {"$unset": {"listing": ""}},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Marks the [`Listing`] of the [`Thread`] with `thread_id` as [sold][ListingStatus::Sold],
+/// setting the prefix to [`SOLD_PREFIX`] and, with `lock`, locking the thread
+///
+/// Only the author, its [co-authors][Thread::co_author_ids] and moderators may do this
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use or the thread has no listing
+#[server]
+pub async fn mark_listing_sold(
+    thread_id: u32,
+    #[server(default)] lock: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let thread = get_managed_thread(thread_id, &db).await?;
+    if thread.listing.is_none() {
+        return Err(ApiError::NotFound("listing".into(), thread_id));
+    }
+
+    let Ok(status) = bson::to_bson(&ListingStatus::Sold) else {
+        unreachable!("a ListingStatus is always serializable")
+    };
+    let mut update = bson::doc! {"listing.status": status, "prefix": SOLD_PREFIX};
+    if lock {
+        update.insert("locked", true);
+    }
+    Thread::collection(&db)
+        .update_one(bson::doc! {"id": thread_id}, bson::doc! {"$set": update})
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+//! Table of contents of very large [`Threads`][super::Thread] ("megathreads")
+//!
+//! Once a thread has at least [`MIN_POSTS_FOR_INDEX`] posts, its index lists the posts
+//! highlighted by moderators and the most thanked ones, so readers don't have to
+//! scroll through hundreds of posts to find the important ones
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{GetCollection, Post, bson, helper, site_settings::GuestCapability, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Threads with fewer visible posts don't get an index
+pub const MIN_POSTS_FOR_INDEX: u64 = 50;
+/// How many of the most thanked posts are listed besides the highlighted ones
+pub const MOST_THANKED_IN_INDEX: i64 = 10;
+/// Maximum amount of characters of [`IndexEntry::excerpt`]
+const EXCERPT_LEN: usize = 80;
+
+/// A [`Post`][super::Post] listed in the index of a megathread
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IndexEntry {
+    pub post_id: u32,
+    pub author_id: Option<u32>,
+    /// Beginning of the first line of the post
+    pub excerpt: String,
+    pub thanks: u32,
+    /// Whether a moderator highlighted the post, see [`set_post_highlighted`]
+    pub highlighted: bool,
+}
+
+/// Shortens the first line of `content` to at most `max_chars` characters, adding `…` if cut
+///
+/// # Example
+///
+/// ```
+/// use app::api::megathread::excerpt;
+///
+/// assert_eq!(excerpt("Rules\nBe nice", 10), "Rules");
+/// assert_eq!(excerpt("A very long first line", 6), "A very…");
+/// assert_eq!(excerpt("  \n\nSecond", 10), "Second");
+/// ```
+#[must_use]
+pub fn excerpt(content: &str, max_chars: usize) -> String {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if line.chars().count() <= max_chars {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(max_chars).collect();
+    format!("{}…", cut.trim_end())
+}
+
+/// Builds the index of the given [`Thread`][super::Thread] from the highlighted
+/// and the most thanked posts, in thread order
+///
+/// Empty for threads with fewer than [`MIN_POSTS_FOR_INDEX`] visible posts
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use or the thread isn't visible,
+///   see [`helper::get_visible_thread`][super::helper::get_visible_thread]
+#[server]
+pub async fn get_thread_index(thread_id: u32) -> Result<Vec<IndexEntry>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    helper::get_visible_thread(thread_id, user.as_ref(), &db).await?;
+    let post_col = Post::collection(&db);
+
+    let visible = bson::doc! {
+        "thread_id": thread_id,
+        "pending_review": {"$ne": true},
+        "deleted_at": null,
+    };
+    if post_col.count_documents(visible.clone()).await? < MIN_POSTS_FOR_INDEX {
+        return Ok(vec![]);
+    }
+
+    let mut posts: Vec<Post> = vec![];
+    let mut highlighted_filter = visible.clone();
+    highlighted_filter.insert("highlighted", true);
+    let mut cursor = post_col.find(highlighted_filter).await?;
+    while cursor.advance().await? {
+        posts.push(cursor.deserialize_current()?);
+    }
+    let mut thanked_filter = visible;
+    thanked_filter.insert("thanks", bson::doc! {"$gt": 0});
+    let mut cursor = post_col
+        .find(thanked_filter)
+        .sort(bson::doc! {"thanks": -1, "id": 1})
+        .limit(MOST_THANKED_IN_INDEX)
+        .await?;
+    while cursor.advance().await? {
+        let post: Post = cursor.deserialize_current()?;
+        if !posts.iter().any(|listed| listed.id == post.id) {
+            posts.push(post);
+        }
+    }
+    posts.sort_unstable_by_key(|post| post.id);
+
+    Ok(posts
+        .into_iter()
+        .map(|post| IndexEntry {
+            post_id: post.id,
+            author_id: post.author_id,
+            excerpt: excerpt(&post.content, EXCERPT_LEN),
+            thanks: post.thanks,
+            highlighted: post.highlighted,
+        })
+        .collect())
+}
+
+/// Highlights a [`Post`][super::Post] in the index of its [`Thread`][super::Thread]
+/// or removes the highlight
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+#[server]
+pub async fn set_post_highlighted(
+    post_id: u32,
+    #[server(default)] highlighted: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+    let _ = helper::get_post(post_id, db.clone()).await?;
+
+    Post::collection(&db)
+        .update_one(
+            bson::doc! {"id": post_id},
+            bson::doc! {"$set": {"highlighted": highlighted}},
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+//! Messages from the server as a key with parameters, so clients can show them in the user's language
+//!
+//! Every [`ApiError`] has a [`Message`], see [`ApiError::message`]. Its key is derived from the
+//! name of the variant, e.g. `error.thread_locked`, and its parameters from the fields, which
+//! are sent to the client anyway, so new variants get a message without further work.
+//! Limits that are only part of the English text, like maximum lengths, are added as `min` or `max`.
+//! The English [`ApiError::user_message`] stays as [`Message::fallback`] for keys a translation
+//! doesn't know
+
+use super::{
+    ApiError, MAX_CATEGORY_DESCRIPTION_LEN, MAX_EDIT_REASON_LEN, MAX_FORUM_DESCRIPTION_LEN,
+    MAX_FORUM_ICON_LEN, api_keys, attachments, flags, forum_rules, marketplace, mod_reasons,
+    previews, thread_tools, user, user_import, validation,
+};
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A translatable message, see the [module docs][self]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    /// E.g. `error.not_found`
+    pub key: String,
+    /// Fields of struct variants by name and of tuple variants by position, e.g. `0`.
+    /// Missing optional values are empty
+    pub params: BTreeMap<String, String>,
+    /// The English text
+    pub fallback: String,
+}
+impl Message {
+    /// Replaces every `{name}` in `template` with the parameter `name`,
+    /// placeholders without a parameter are kept as they are
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::ApiError;
+    ///
+    /// let message = ApiError::NotFound("thread".into(), 3).message();
+    /// assert_eq!(message.fill("Es gibt kein {0} #{1}"), "Es gibt kein thread #3");
+    /// assert_eq!(message.fill("{0} {unknown}"), "thread {unknown}");
+    /// ```
+    #[must_use]
+    pub fn fill(&self, template: &str) -> String {
+        let mut filled = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            filled.push_str(&rest[..start]);
+            let after = &rest[start + 1..];
+            let param = after
+                .find('}')
+                .and_then(|end| Some((self.params.get(&after[..end])?, end)));
+            if let Some((value, end)) = param {
+                filled.push_str(value);
+                rest = &after[end + 1..];
+            } else {
+                filled.push('{');
+                rest = after;
+            }
+        }
+        filled.push_str(rest);
+        filled
+    }
+}
+
+/// Turns a `CamelCase` variant name into `snake_case`
+///
+/// # Example
+///
+/// ```
+/// use app::api::messages::snake_case;
+///
+/// assert_eq!(snake_case("ThreadLocked"), "thread_locked");
+/// assert_eq!(snake_case("InvalidApiKey"), "invalid_api_key");
+/// assert_eq!(snake_case("Db"), "db");
+/// ```
+#[must_use]
+pub fn snake_case(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, char) in name.chars().enumerate() {
+        if char.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(char.to_lowercase());
+        } else {
+            snake.push(char);
+        }
+    }
+    snake
+}
+
+/// Text of a single parameter, strings without quotes and [`None`] as nothing
+fn param_text(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+impl ApiError {
+    /// Returns the [`Message`] of this error, see the [module docs][self]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::ApiError;
+    ///
+    /// let message = ApiError::PrefixTooLong.message();
+    /// assert_eq!(message.key, "error.prefix_too_long");
+    /// assert_eq!(message.params["max"], "16");
+    /// assert_eq!(message.fallback, "prefix can be at most 16 characters long");
+    /// ```
+    #[must_use]
+    pub fn message(&self) -> Message {
+        let Ok(value) = serde_json::to_value(self) else {
+            unreachable!("an ApiError is always serializable")
+        };
+        let mut params = BTreeMap::new();
+        // serde's default representation: unit variants are their name,
+        // all others an object with the name as the only key
+        let variant = match value {
+            serde_json::Value::Object(object) => {
+                let Some((variant, fields)) = object.into_iter().next() else {
+                    unreachable!("an enum variant always has a name")
+                };
+                match fields {
+                    serde_json::Value::Object(fields) => {
+                        for (name, value) in fields {
+                            params.insert(name, param_text(value));
+                        }
+                    }
+                    serde_json::Value::Array(fields) => {
+                        for (i, value) in fields.into_iter().enumerate() {
+                            params.insert(i.to_string(), param_text(value));
+                        }
+                    }
+                    field => {
+                        params.insert("0".to_string(), param_text(field));
+                    }
+                }
+                variant
+            }
+            other => param_text(other),
+        };
+        if let Some((name, limit)) = self.limit() {
+            params.insert(name.to_string(), limit.to_string());
+        }
+
+        Message {
+            key: format!("error.{}", snake_case(&variant)),
+            params,
+            fallback: self.user_message(),
+        }
+    }
+
+    /// The text shown to users: the [`Display`][std::fmt::Display] text, except for failures
+    /// on the server's side, whose details only belong in the logs
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::{ApiError, DbErrorCode};
+    ///
+    /// let err = ApiError::Db {
+    ///     code: DbErrorCode::Unavailable,
+    ///     context: None,
+    ///     detail: "connection refused".into(),
+    /// };
+    /// assert_eq!(
+    ///     err.user_message(),
+    ///     "something went wrong on our side (unavailable), please try again later"
+    /// );
+    /// assert_eq!(ApiError::ThreadLocked.user_message(), "this thread is locked");
+    /// ```
+    #[must_use]
+    pub fn user_message(&self) -> String {
+        let code = match self {
+            Self::Db { code, .. } => code.as_str(),
+            Self::ServerFn(_) => "server_fn",
+            Self::DbNotInContext => "db_not_in_context",
+            Self::Storage(_) => "storage",
+            Self::Dummy => "dummy",
+            _ => return self.to_string(),
+        };
+        format!("something went wrong on our side ({code}), please try again later")
+    }
+
+    /// The limit mentioned in the text of the error but not part of it, with its parameter name
+    fn limit(&self) -> Option<(&'static str, u64)> {
+        let (name, limit) = match self {
+            Self::PasswordTooShort => ("min", user::MIN_PASSWORD_LEN),
+            Self::AttachmentTooLarge => ("max", attachments::MAX_ATTACHMENT_SIZE / 1024 / 1024),
+            Self::TooManyAttachments => ("max", attachments::MAX_ATTACHMENTS_PER_POST),
+            Self::FlagReasonTooLong => ("max", flags::MAX_FLAG_REASON_LEN),
+            Self::EditReasonTooLong => ("max", MAX_EDIT_REASON_LEN),
+            Self::RulesTooLong => ("max", forum_rules::MAX_RULES_LEN),
+            Self::InvalidApiKeyName => ("max", api_keys::MAX_API_KEY_NAME_LEN),
+            Self::TooManyImportRows => ("max", user_import::MAX_IMPORT_ROWS),
+            Self::InvalidModReasonName => ("max", mod_reasons::MAX_MOD_REASON_NAME_LEN),
+            Self::TooManyApiKeys => return Some(("max", api_keys::MAX_API_KEYS)),
+            Self::InvalidPreviewDuration => {
+                return Some(("max", u64::from(previews::MAX_PREVIEW_HOURS)));
+            }
+            Self::Validation {
+                rule: validation::Rule::MaxLength(max),
+                ..
+            } => return Some(("max", u64::from(*max))),
+            Self::ForumDescriptionTooLong => ("max", MAX_FORUM_DESCRIPTION_LEN),
+            Self::ForumIconTooLong => ("max", MAX_FORUM_ICON_LEN),
+            Self::CategoryDescriptionTooLong => ("max", MAX_CATEGORY_DESCRIPTION_LEN),
+            Self::InvalidTag(_) => ("max", thread_tools::MAX_TAG_LEN),
+            Self::TooManyTags => ("max", thread_tools::MAX_TAGS),
+            Self::PrefixTooLong => ("max", thread_tools::MAX_PREFIX_LEN),
+            Self::InvalidListingLocation => ("max", marketplace::MAX_LOCATION_LEN),
+            _ => return None,
+        };
+        Some((name, u64::try_from(limit).unwrap_or(u64::MAX)))
+    }
+}
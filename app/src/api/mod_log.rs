@@ -0,0 +1,776 @@
+//! The moderation log: every moderation action and report, e.g. for transparency reports
+//!
+//! Entries are recorded from the [`DomainEvents`][super::events::DomainEvent] as they're emitted,
+//! reports are kept even after their [`Flags`][super::flags::Flag] got dismissed.
+//! Admins can export the log filtered by date, moderator, action and
+//! [reason][super::mod_reasons] as CSV or JSON.
+//! The file is written by a background task, its [`ModLogExport`] shows when it's ready
+//! to be downloaded through the plain axum route `download`
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    Counter, GetCollection, bson,
+    events::DomainEvent,
+    helper,
+    mod_reasons::ModReason,
+    user::{Role, User},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What a [`ModLogEntry`] is about
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModAction {
+    /// A user reported a post, the only action not done by staff
+    PostFlagged,
+    FlagsDismissed,
+    FlaggedPostHeld,
+    PostApproved,
+    PostRejected,
+    /// A [staff edit][super::Post::staff_edit]
+    StaffEdit,
+    PostDeleted,
+    PostMoved,
+    ThreadDeleted,
+    ThreadRestored,
+    ThreadsMerged,
+    ThreadSplit,
+    UserBanned,
+    UserUnbanned,
+    UserMuted,
+    UserUnmuted,
+    UserWarned,
+}
+impl ModAction {
+    pub const ALL: [Self; 17] = [
+        Self::PostFlagged,
+        Self::FlagsDismissed,
+        Self::FlaggedPostHeld,
+        Self::PostApproved,
+        Self::PostRejected,
+        Self::StaffEdit,
+        Self::PostDeleted,
+        Self::PostMoved,
+        Self::ThreadDeleted,
+        Self::ThreadRestored,
+        Self::ThreadsMerged,
+        Self::ThreadSplit,
+        Self::UserBanned,
+        Self::UserUnbanned,
+        Self::UserMuted,
+        Self::UserUnmuted,
+        Self::UserWarned,
+    ];
+
+    /// Name of the action in exports, e.g. `post_flagged`
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::PostFlagged => "post_flagged",
+            Self::FlagsDismissed => "flags_dismissed",
+            Self::FlaggedPostHeld => "flagged_post_held",
+            Self::PostApproved => "post_approved",
+            Self::PostRejected => "post_rejected",
+            Self::StaffEdit => "staff_edit",
+            Self::PostDeleted => "post_deleted",
+            Self::PostMoved => "post_moved",
+            Self::ThreadDeleted => "thread_deleted",
+            Self::ThreadRestored => "thread_restored",
+            Self::ThreadsMerged => "threads_merged",
+            Self::ThreadSplit => "thread_split",
+            Self::UserBanned => "user_banned",
+            Self::UserUnbanned => "user_unbanned",
+            Self::UserMuted => "user_muted",
+            Self::UserUnmuted => "user_unmuted",
+            Self::UserWarned => "user_warned",
+        }
+    }
+
+    /// Translation key of the name of the action for the admin area
+    #[must_use]
+    pub const fn label_key(self) -> &'static str {
+        match self {
+            Self::PostFlagged => "mod_action.post_flagged",
+            Self::FlagsDismissed => "mod_action.flags_dismissed",
+            Self::FlaggedPostHeld => "mod_action.flagged_post_held",
+            Self::PostApproved => "mod_action.post_approved",
+            Self::PostRejected => "mod_action.post_rejected",
+            Self::StaffEdit => "mod_action.staff_edit",
+            Self::PostDeleted => "mod_action.post_deleted",
+            Self::PostMoved => "mod_action.post_moved",
+            Self::ThreadDeleted => "mod_action.thread_deleted",
+            Self::ThreadRestored => "mod_action.thread_restored",
+            Self::ThreadsMerged => "mod_action.threads_merged",
+            Self::ThreadSplit => "mod_action.thread_split",
+            Self::UserBanned => "mod_action.user_banned",
+            Self::UserUnbanned => "mod_action.user_unbanned",
+            Self::UserMuted => "mod_action.user_muted",
+            Self::UserUnmuted => "mod_action.user_unmuted",
+            Self::UserWarned => "mod_action.user_warned",
+        }
+    }
+}
+
+/// A single moderation action or report
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModLogEntry {
+    pub action: ModAction,
+    /// The moderator, or the reporter of a [`ModAction::PostFlagged`]
+    pub actor_id: u32,
+    #[serde(default)]
+    pub post_id: Option<u32>,
+    #[serde(default)]
+    pub thread_id: Option<u32>,
+    /// The thread merged into, split off to or a post was moved to
+    #[serde(default)]
+    pub other_thread_id: Option<u32>,
+    /// The sanctioned or warned [`User`][super::user::User]
+    #[serde(default)]
+    pub user_id: Option<u32>,
+    /// Why it was done, if a reason was given
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// The [`ModReason`][super::mod_reasons::ModReason] picked by the moderator
+    #[serde(default)]
+    pub reason_id: Option<u32>,
+    /// When a ban or mute ends, [`None`] if it's permanent
+    #[serde(default, with = "super::jiff_timestamp_as_bson_datetime::optional")]
+    pub until: Option<jiff::Timestamp>,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+impl CollectionName for ModLogEntry {
+    fn collection_name() -> &'static str {
+        "mod_log"
+    }
+}
+
+/// File format of a [`ModLogExport`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+impl ExportFormat {
+    pub const ALL: [Self; 2] = [Self::Csv, Self::Json];
+
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+
+    #[must_use]
+    pub const fn mime(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// Which [`ModLogEntries`][ModLogEntry] go into a [`ModLogExport`], everything if left empty
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ModLogFilter {
+    #[serde(default, with = "super::jiff_timestamp_as_bson_datetime::optional")]
+    pub since: Option<jiff::Timestamp>,
+    /// Exclusive
+    #[serde(default, with = "super::jiff_timestamp_as_bson_datetime::optional")]
+    pub until: Option<jiff::Timestamp>,
+    /// Matched against [`ModLogEntry::actor_id`]
+    #[serde(default)]
+    pub moderator_ids: Vec<u32>,
+    #[serde(default)]
+    pub action: Option<ModAction>,
+    /// Matched against [`ModLogEntry::reason_id`]
+    #[serde(default)]
+    pub reason_id: Option<u32>,
+}
+
+/// How far a [`ModLogExport`] got
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ExportStatus {
+    /// The file is still being written
+    Running,
+    Done {
+        entry_count: u64,
+    },
+    Failed(String),
+}
+
+/// An export of the moderation log requested by an admin
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModLogExport {
+    pub id: u32,
+    pub format: ExportFormat,
+    pub filter: ModLogFilter,
+    pub requested_by: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub requested_at: jiff::Timestamp,
+    pub status: ExportStatus,
+}
+impl ModLogExport {
+    /// Route the file is downloaded from, e.g. `/exports/mod-log/3`
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("/exports/mod-log/{}", self.id)
+    }
+}
+impl CollectionName for ModLogExport {
+    fn collection_name() -> &'static str {
+        "mod_log_exports"
+    }
+}
+
+/// Quotes `field` for a CSV file if it contains commas, quotes or line breaks.
+/// Fields starting like a spreadsheet formula are prefixed with `'`,
+/// since reasons are written by users
+///
+/// # Example
+///
+/// ```
+/// use app::api::mod_log::csv_field;
+///
+/// assert_eq!(csv_field("spam"), "spam");
+/// assert_eq!(csv_field("spam, again"), "\"spam, again\"");
+/// assert_eq!(csv_field("said \"hi\""), "\"said \"\"hi\"\"\"");
+/// assert_eq!(csv_field("=1+1"), "'=1+1");
+/// ```
+#[must_use]
+pub fn csv_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@']) {
+        format!("'{field}")
+    } else {
+        field.to_string()
+    };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+/// The [`ModLogEntry`] recorded for `event`, [`None`] if it's no moderation action or report
+#[cfg(feature = "ssr")]
+fn entry_of(event: DomainEvent<'_>) -> Option<ModLogEntry> {
+    let entry = |action, actor_id| ModLogEntry {
+        action,
+        actor_id,
+        post_id: None,
+        thread_id: None,
+        other_thread_id: None,
+        user_id: None,
+        reason: None,
+        reason_id: None,
+        until: None,
+        created_at: jiff::Timestamp::now(),
+    };
+    let reason = |reason: &str| Some(reason.to_string()).filter(|reason| !reason.is_empty());
+    let post = |action, post_id, moderator_id| ModLogEntry {
+        post_id: Some(post_id),
+        ..entry(action, moderator_id)
+    };
+    let thread = |action, thread_id, other_thread_id, moderator_id| ModLogEntry {
+        thread_id: Some(thread_id),
+        other_thread_id,
+        ..entry(action, moderator_id)
+    };
+    let sanction = |action, user_id, moderator_id, until, sanction_reason: &str| ModLogEntry {
+        user_id: Some(user_id),
+        until,
+        reason: reason(sanction_reason),
+        ..entry(action, moderator_id)
+    };
+
+    let entry = match event {
+        DomainEvent::PostFlagged {
+            post_id,
+            user_id,
+            reason: flag_reason,
+        } => ModLogEntry {
+            reason: reason(flag_reason),
+            ..post(ModAction::PostFlagged, post_id, user_id)
+        },
+        DomainEvent::FlagsDismissed {
+            post_id,
+            moderator_id,
+        } => post(ModAction::FlagsDismissed, post_id, moderator_id),
+        DomainEvent::FlaggedPostHeld {
+            post_id,
+            moderator_id,
+        } => post(ModAction::FlaggedPostHeld, post_id, moderator_id),
+        DomainEvent::PostApproved {
+            post_id,
+            moderator_id,
+        } => post(ModAction::PostApproved, post_id, moderator_id),
+        DomainEvent::PostRejected {
+            post_id,
+            moderator_id,
+        } => post(ModAction::PostRejected, post_id, moderator_id),
+        DomainEvent::PostEdited {
+            post_id,
+            editor_id,
+            reason: Some(edit_reason),
+        } => ModLogEntry {
+            reason: reason(edit_reason),
+            ..post(ModAction::StaffEdit, post_id, editor_id)
+        },
+        DomainEvent::PostDeleted {
+            post_id,
+            thread_id,
+            moderator_id,
+            reason_id,
+        } => ModLogEntry {
+            thread_id: Some(thread_id),
+            reason_id,
+            ..post(ModAction::PostDeleted, post_id, moderator_id)
+        },
+        DomainEvent::PostMoved {
+            post_id,
+            source_thread_id,
+            target_thread_id,
+            moderator_id,
+            reason_id,
+        } => ModLogEntry {
+            post_id: Some(post_id),
+            reason_id,
+            ..thread(
+                ModAction::PostMoved,
+                source_thread_id,
+                Some(target_thread_id),
+                moderator_id,
+            )
+        },
+        DomainEvent::ThreadDeleted {
+            thread_id,
+            moderator_id,
+            reason_id,
+        } => ModLogEntry {
+            reason_id,
+            ..thread(ModAction::ThreadDeleted, thread_id, None, moderator_id)
+        },
+        DomainEvent::ThreadRestored {
+            thread_id,
+            moderator_id,
+        } => thread(ModAction::ThreadRestored, thread_id, None, moderator_id),
+        DomainEvent::ThreadsMerged {
+            source_thread_id,
+            target_thread_id,
+            moderator_id,
+        } => thread(
+            ModAction::ThreadsMerged,
+            source_thread_id,
+            Some(target_thread_id),
+            moderator_id,
+        ),
+        DomainEvent::ThreadSplit {
+            source_thread_id,
+            new_thread_id,
+            moderator_id,
+        } => thread(
+            ModAction::ThreadSplit,
+            source_thread_id,
+            Some(new_thread_id),
+            moderator_id,
+        ),
+        DomainEvent::UserBanned {
+            user_id,
+            moderator_id,
+            until,
+            reason,
+            reason_id,
+        } => ModLogEntry {
+            reason_id,
+            ..sanction(ModAction::UserBanned, user_id, moderator_id, until, reason)
+        },
+        DomainEvent::UserUnbanned {
+            user_id,
+            moderator_id,
+        } => sanction(ModAction::UserUnbanned, user_id, moderator_id, None, ""),
+        DomainEvent::UserMuted {
+            user_id,
+            moderator_id,
+            until,
+            reason,
+            reason_id,
+        } => ModLogEntry {
+            reason_id,
+            ..sanction(ModAction::UserMuted, user_id, moderator_id, until, reason)
+        },
+        DomainEvent::UserUnmuted {
+            user_id,
+            moderator_id,
+        } => sanction(ModAction::UserUnmuted, user_id, moderator_id, None, ""),
+        DomainEvent::UserWarned {
+            user_id,
+            post_id,
+            moderator_id,
+            note,
+            reason_id,
+        } => ModLogEntry {
+            post_id: Some(post_id),
+            reason_id,
+            ..sanction(ModAction::UserWarned, user_id, moderator_id, None, note)
+        },
+        DomainEvent::ThreadCreated { .. }
+        | DomainEvent::PostCreated { .. }
+        | DomainEvent::PostEdited { reason: None, .. }
+        | DomainEvent::ThreadSolved { .. }
+        | DomainEvent::UserRegistered { .. } => return None,
+    };
+    Some(entry)
+}
+
+/// Records `event` in the moderation log in the background, if it's a moderation action or report
+#[cfg(feature = "ssr")]
+pub fn record(event: DomainEvent<'_>, db: &mongodb::Database) {
+    let Some(entry) = entry_of(event) else {
+        return;
+    };
+    let db = db.clone();
+    tokio::spawn(async move {
+        if let Err(err) = ModLogEntry::collection(&db).insert_one(&entry).await {
+            tracing::warn!(
+                "recording {} in the moderation log failed: {err}",
+                entry.action.name()
+            );
+        }
+    });
+}
+
+/// Directory the export files are stored in, `exports` in the
+/// [`attachment_dir`][super::attachments::attachment_dir]
+#[cfg(feature = "ssr")]
+#[must_use]
+pub fn export_dir() -> std::path::PathBuf {
+    super::attachments::attachment_dir().join("exports")
+}
+
+/// Path of the file of `export`
+#[cfg(feature = "ssr")]
+fn export_path(export: &ModLogExport) -> std::path::PathBuf {
+    export_dir().join(format!(
+        "mod-log-{}.{}",
+        export.id,
+        export.format.extension()
+    ))
+}
+
+/// A [`ModLogEntry`] as written to the file, with the names of the users involved
+#[cfg(feature = "ssr")]
+#[derive(Serialize, Debug)]
+struct ExportRow {
+    created_at: String,
+    action: &'static str,
+    actor_id: u32,
+    actor_name: Option<String>,
+    post_id: Option<u32>,
+    thread_id: Option<u32>,
+    other_thread_id: Option<u32>,
+    user_id: Option<u32>,
+    user_name: Option<String>,
+    until: Option<String>,
+    reason: Option<String>,
+    reason_id: Option<u32>,
+    reason_name: Option<String>,
+}
+
+/// Renders `rows` as CSV with a header line
+#[cfg(feature = "ssr")]
+fn render_csv(rows: &[ExportRow]) -> String {
+    use std::fmt::Write;
+
+    let number = |id: Option<u32>| id.map(|id| id.to_string()).unwrap_or_default();
+    let text = |text: &Option<String>| text.as_deref().map(csv_field).unwrap_or_default();
+    let mut csv = String::from(
+        "created_at,action,actor_id,actor_name,post_id,thread_id,other_thread_id,user_id,user_name,until,reason,reason_id,reason_name\n",
+    );
+    for row in rows {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            row.created_at,
+            row.action,
+            row.actor_id,
+            text(&row.actor_name),
+            number(row.post_id),
+            number(row.thread_id),
+            number(row.other_thread_id),
+            number(row.user_id),
+            text(&row.user_name),
+            text(&row.until),
+            text(&row.reason),
+            number(row.reason_id),
+            text(&row.reason_name),
+        );
+    }
+    csv
+}
+
+/// Writes the file of `export` and returns how many entries it contains
+#[cfg(feature = "ssr")]
+async fn write_export(export: &ModLogExport, db: &mongodb::Database) -> Result<u64, ApiError> {
+    let ModLogFilter {
+        since,
+        until,
+        moderator_ids,
+        action,
+        reason_id,
+    } = &export.filter;
+    let as_bson =
+        |timestamp: jiff::Timestamp| bson::DateTime::from_millis(timestamp.as_millisecond());
+    let mut created_at = bson::doc! {};
+    if let Some(since) = since {
+        created_at.insert("$gte", as_bson(*since));
+    }
+    if let Some(until) = until {
+        created_at.insert("$lt", as_bson(*until));
+    }
+    let mut filter = bson::doc! {};
+    if !created_at.is_empty() {
+        filter.insert("created_at", created_at);
+    }
+    if !moderator_ids.is_empty() {
+        filter.insert("actor_id", bson::doc! {"$in": moderator_ids.clone()});
+    }
+    if let Some(action) = action {
+        let Ok(action) = bson::to_bson(action) else {
+            unreachable!("a ModAction is always serializable")
+        };
+        filter.insert("action", action);
+    }
+    if let Some(reason_id) = reason_id {
+        filter.insert("reason_id", *reason_id);
+    }
+
+    let mut entries: Vec<ModLogEntry> = vec![];
+    let mut cursor = ModLogEntry::replica_collection(db)
+        .find(filter)
+        .sort(bson::doc! {"created_at": 1})
+        .await?;
+    while cursor.advance().await? {
+        entries.push(cursor.deserialize_current()?);
+    }
+
+    let mut user_ids: Vec<u32> = entries
+        .iter()
+        .flat_map(|entry| [Some(entry.actor_id), entry.user_id])
+        .flatten()
+        .collect();
+    user_ids.sort_unstable();
+    user_ids.dedup();
+    let mut names = std::collections::BTreeMap::new();
+    let mut cursor = User::replica_collection(db)
+        .find(bson::doc! {"id": {"$in": user_ids}})
+        .await?;
+    while cursor.advance().await? {
+        let user: User = cursor.deserialize_current()?;
+        names.insert(user.id, user.name);
+    }
+    let mut reason_names = std::collections::BTreeMap::new();
+    let mut cursor = ModReason::replica_collection(db)
+        .find(bson::doc! {})
+        .await?;
+    while cursor.advance().await? {
+        let reason: ModReason = cursor.deserialize_current()?;
+        reason_names.insert(reason.id, reason.name);
+    }
+
+    let rows: Vec<ExportRow> = entries
+        .into_iter()
+        .map(|entry| ExportRow {
+            created_at: entry.created_at.to_string(),
+            action: entry.action.name(),
+            actor_id: entry.actor_id,
+            actor_name: names.get(&entry.actor_id).cloned(),
+            post_id: entry.post_id,
+            thread_id: entry.thread_id,
+            other_thread_id: entry.other_thread_id,
+            user_id: entry.user_id,
+            user_name: entry.user_id.and_then(|id| names.get(&id).cloned()),
+            until: entry.until.map(|until| until.to_string()),
+            reason: entry.reason,
+            reason_id: entry.reason_id,
+            reason_name: entry
+                .reason_id
+                .and_then(|id| reason_names.get(&id).cloned()),
+        })
+        .collect();
+    let contents = match export.format {
+        ExportFormat::Csv => render_csv(&rows),
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&rows).map_err(|err| ApiError::Storage(err.to_string()))?
+        }
+    };
+
+    let storage_err = |err: std::io::Error| ApiError::Storage(err.to_string());
+    tokio::fs::create_dir_all(export_dir())
+        .await
+        .map_err(storage_err)?;
+    tokio::fs::write(export_path(export), contents)
+        .await
+        .map_err(storage_err)?;
+
+    Ok(rows.len() as u64)
+}
+
+/// Starts exporting the moderation log as a [`ModLogExport`] in the background
+/// and returns its id
+///
+/// `since` and `until` are read in `time_zone`, see [`crate::time::parse_in_user_tz`].
+/// `moderator_names` are comma-separated, empty ones and an empty `action` or `reason_id`
+/// mean all
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidExportDates`] if a date can't be parsed or `until` isn't after `since`
+/// - [`ApiError::UnknownUsername`] if one of the `moderator_names` isn't in use
+#[server]
+pub async fn export_mod_log(
+    since: Option<String>,
+    until: Option<String>,
+    #[server(default)] moderator_names: String,
+    action: Option<ModAction>,
+    reason_id: Option<u32>,
+    format: ExportFormat,
+    time_zone: String,
+) -> Result<u32, ApiError> {
+    let db = helper::get_db()?;
+    let admin = helper::require_role(&db, Role::Admin).await?;
+
+    let parse = |date: Option<String>| match date.filter(|date| !date.is_empty()) {
+        Some(date) => crate::time::parse_in_user_tz(&date, &time_zone)
+            .map(Some)
+            .ok_or(ApiError::InvalidExportDates),
+        None => Ok(None),
+    };
+    let (since, until) = (parse(since)?, parse(until)?);
+    if since
+        .zip(until)
+        .is_some_and(|(since, until)| until <= since)
+    {
+        return Err(ApiError::InvalidExportDates);
+    }
+    let moderator_ids = helper::get_user_ids_by_names(&moderator_names, &db).await?;
+
+    let id = helper::get_and_increment_id_of("mod_log_export", Counter::collection(&db)).await?;
+    let export = ModLogExport {
+        id,
+        format,
+        filter: ModLogFilter {
+            since,
+            until,
+            moderator_ids,
+            action,
+            reason_id,
+        },
+        requested_by: admin.id,
+        requested_at: jiff::Timestamp::now(),
+        status: ExportStatus::Running,
+    };
+    ModLogExport::collection(&db).insert_one(&export).await?;
+
+    tokio::spawn(async move {
+        let status = match write_export(&export, &db).await {
+            Ok(entry_count) => ExportStatus::Done { entry_count },
+            Err(err) => {
+                tracing::warn!("moderation log export #{} failed: {err}", export.id);
+                ExportStatus::Failed(err.to_string())
+            }
+        };
+        let Ok(status) = bson::to_bson(&status) else {
+            unreachable!("an ExportStatus is always serializable")
+        };
+        let result = ModLogExport::collection(&db)
+            .update_one(
+                bson::doc! {"id": export.id},
+                bson::doc! {"$set": {"status": status}},
+            )
+            .await;
+        if let Err(err) = result {
+            tracing::warn!("saving the status of export #{} failed: {err}", export.id);
+        }
+    });
+
+    Ok(id)
+}
+
+/// Fetches all [`ModLogExports`][ModLogExport], newest first
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_mod_log_exports() -> Result<Vec<ModLogExport>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let mut exports = vec![];
+    let mut cursor = ModLogExport::collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"id": -1})
+        .await?;
+    while cursor.advance().await? {
+        exports.push(cursor.deserialize_current()?);
+    }
+
+    Ok(exports)
+}
+
+/// Axum handler for `GET /exports/mod-log/{id}`, serves the file of a finished
+/// [`ModLogExport`] as a download
+///
+/// Requires [`Role::Admin`]
+#[cfg(feature = "ssr")]
+pub async fn download(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    axum::extract::Path(id): axum::extract::Path<u32>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use http::{StatusCode, header};
+
+    match helper::require_user_from_headers(&headers, &db).await {
+        Ok(user) if user.role >= Role::Admin => {}
+        Ok(_) => return StatusCode::FORBIDDEN.into_response(),
+        Err(ApiError::NotLoggedIn) => return StatusCode::UNAUTHORIZED.into_response(),
+        Err(err) => {
+            tracing::warn!("serving export failed: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+    let export = match ModLogExport::collection(&db)
+        .find_one(bson::doc! {"id": id})
+        .await
+    {
+        Ok(Some(export)) if matches!(export.status, ExportStatus::Done { .. }) => export,
+        Ok(_) => return StatusCode::NOT_FOUND.into_response(),
+        Err(err) => {
+            tracing::warn!("serving export failed: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let Ok(data) = tokio::fs::read(export_path(&export)).await else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    (
+        [
+            (header::CONTENT_TYPE, export.format.mime().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"moderation-log-{id}.{}\"",
+                    export.format.extension()
+                ),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        data,
+    )
+        .into_response()
+}
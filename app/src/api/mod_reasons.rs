@@ -0,0 +1,226 @@
+//! The taxonomy of moderation reasons, e.g. spam, off-topic or harassment
+//!
+//! Admins manage the [`ModReasons`][ModReason] moderators pick from when deleting threads,
+//! banning or muting users and in the [post shortcuts][super::mod_shortcuts]. The picked reason is recorded in the
+//! [moderation log][super::mod_log] along with the free-text one, so
+//! [`get_mod_reason_stats`] can tell consistently why actions were taken.
+//! Reasons are archived instead of deleted, since old log entries still reference them
+
+use super::mod_log::ModAction;
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{Counter, GetCollection, bson, helper, mod_log::ModLogEntry, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a [`ModReason::name`] in characters
+pub const MAX_MOD_REASON_NAME_LEN: usize = 40;
+
+/// A reason moderators can pick for their actions
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModReason {
+    pub id: u32,
+    /// Unique, e.g. `Spam`
+    pub name: String,
+    /// When to pick it, shown to moderators
+    #[serde(default)]
+    pub description: String,
+    /// Archived reasons can't be picked anymore, but stay in the statistics
+    #[serde(default)]
+    pub archived: bool,
+}
+impl ModReason {
+    /// Whether the name or description contain `query`, ignoring case.
+    /// Everything matches an empty query
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::mod_reasons::ModReason;
+    ///
+    /// let reason = ModReason {
+    ///     id: 1,
+    ///     name: "Off-topic".into(),
+    ///     description: "Doesn't belong in this forum".into(),
+    ///     archived: false,
+    /// };
+    /// assert!(reason.matches("TOPIC"));
+    /// assert!(reason.matches(" forum "));
+    /// assert!(reason.matches(""));
+    /// assert!(!reason.matches("spam"));
+    /// ```
+    #[must_use]
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.trim().to_lowercase();
+        self.name.to_lowercase().contains(&query)
+            || self.description.to_lowercase().contains(&query)
+    }
+}
+impl CollectionName for ModReason {
+    fn collection_name() -> &'static str {
+        "mod_reasons"
+    }
+}
+
+/// How often a [`ModReason`] was given for a [`ModAction`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ModReasonStat {
+    pub reason_id: u32,
+    pub action: ModAction,
+    pub count: u32,
+}
+
+/// Checks that `reason_id`, if given, is of a [`ModReason`] that can be picked
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if the reason doesn't exist or is archived
+#[cfg(feature = "ssr")]
+pub async fn check_reason(reason_id: Option<u32>, db: &mongodb::Database) -> Result<(), ApiError> {
+    let Some(reason_id) = reason_id else {
+        return Ok(());
+    };
+    let reason = ModReason::collection(db)
+        .find_one(bson::doc! {"id": reason_id, "archived": {"$ne": true}})
+        .await?;
+    match reason {
+        Some(_) => Ok(()),
+        None => Err(ApiError::NotFound("moderation reason".into(), reason_id)),
+    }
+}
+
+/// Fetches the [`ModReasons`][ModReason] sorted by name whose name or description
+/// contain `query` (see [`ModReason::matches`]), archived ones only with `include_archived`
+///
+/// Requires [`Role::Moderator`]
+#[server]
+pub async fn get_mod_reasons(
+    #[server(default)] query: String,
+    #[server(default)] include_archived: bool,
+) -> Result<Vec<ModReason>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    let filter = if include_archived {
+        bson::doc! {}
+    } else {
+        bson::doc! {"archived": {"$ne": true}}
+    };
+    let mut reasons = vec![];
+    let mut cursor = ModReason::collection(&db)
+        .find(filter)
+        .sort(bson::doc! {"name": 1})
+        .await?;
+    while cursor.advance().await? {
+        let reason: ModReason = cursor.deserialize_current()?;
+        if reason.matches(&query) {
+            reasons.push(reason);
+        }
+    }
+
+    Ok(reasons)
+}
+
+/// Adds a new [`ModReason`] and returns its id
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidModReasonName`] if `name` is empty or longer than
+///   [`MAX_MOD_REASON_NAME_LEN`]
+/// - [`ApiError::ModReasonExists`] if another reason has the same name, ignoring case
+#[server]
+pub async fn create_mod_reason(
+    name: String,
+    #[server(default)] description: String,
+) -> Result<u32, ApiError> {
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_MOD_REASON_NAME_LEN {
+        return Err(ApiError::InvalidModReasonName);
+    }
+
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let same_name = ModReason::collection(&db)
+        .find_one(bson::doc! {
+            "name": {"$regex": format!("^{}$", helper::escape_regex(&name)), "$options": "i"},
+        })
+        .await?;
+    if same_name.is_some() {
+        return Err(ApiError::ModReasonExists(name));
+    }
+
+    let id = helper::get_and_increment_id_of("mod_reason", Counter::collection(&db)).await?;
+    let reason = ModReason {
+        id,
+        name,
+        description: description.trim().to_string(),
+        archived: false,
+    };
+    ModReason::collection(&db).insert_one(&reason).await?;
+
+    Ok(id)
+}
+
+/// Archives or unarchives the [`ModReason`] with the given `reason_id`
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `reason_id` isn't in use
+#[server]
+pub async fn set_mod_reason_archived(reason_id: u32, archived: bool) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let result = ModReason::collection(&db)
+        .update_one(
+            bson::doc! {"id": reason_id},
+            bson::doc! {"$set": {"archived": archived}},
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(ApiError::NotFound("moderation reason".into(), reason_id));
+    }
+
+    Ok(())
+}
+
+/// Counts the [`ModLogEntries`][ModLogEntry] per [`ModReason`] and [`ModAction`],
+/// most frequent first
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_mod_reason_stats() -> Result<Vec<ModReasonStat>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let pipeline = vec![
+        bson::doc! {"$match": {"reason_id": {"$ne": null}}},
+        bson::doc! {"$group": {
+            "_id": {"reason_id": "$reason_id", "action": "$action"},
+            "count": {"$sum": 1},
+        }},
+        bson::doc! {"$project": {
+            "_id": 0,
+            "reason_id": "$_id.reason_id",
+            "action": "$_id.action",
+            "count": 1,
+        }},
+        bson::doc! {"$sort": {"count": -1, "reason_id": 1}},
+    ];
+    let mut stats = vec![];
+    let mut cursor = ModLogEntry::replica_collection(&db)
+        .aggregate(pipeline)
+        .with_type::<ModReasonStat>()
+        .await?;
+    while cursor.advance().await? {
+        stats.push(cursor.deserialize_current()?);
+    }
+
+    Ok(stats)
+}
@@ -0,0 +1,290 @@
+//! Moderator shortcuts on single [`Posts`][super::Post]: deleting one, warning its author or
+//! moving it to another [`Thread`][super::Thread], each in a single call of [`moderate_post`]
+//!
+//! Every shortcut takes a preset [`ModReason`][super::mod_reasons::ModReason] and is recorded
+//! in the [moderation log][super::mod_log], so cleaning up after e.g. a spam wave takes
+//! a few clicks per post while staying as traceable as the full tools
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, Post, Thread, bson, cache,
+    events::{self, DomainEvent},
+    helper, mailer,
+    mod_reasons::{self, ModReason},
+    thread_tools,
+    user::{AccountDetails, Role, User},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What [`moderate_post`] does to a post
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostShortcut {
+    /// Soft-deletes it, or its thread if it's the origin post
+    Delete,
+    /// Records a [`Warning`] for its author and emails them
+    WarnAuthor,
+    /// Moves it to another thread, where it's placed by date like merged posts
+    Move,
+}
+impl PostShortcut {
+    pub const ALL: [Self; 3] = [Self::Delete, Self::WarnAuthor, Self::Move];
+
+    /// Translation key of the name of the shortcut for its button
+    #[must_use]
+    pub const fn label_key(self) -> &'static str {
+        match self {
+            Self::Delete => "post_shortcut.delete",
+            Self::WarnAuthor => "post_shortcut.warn_author",
+            Self::Move => "post_shortcut.move",
+        }
+    }
+}
+
+/// A warning a moderator gave a [`User`][super::user::User] for one of their posts,
+/// see [`PostShortcut::WarnAuthor`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Warning {
+    pub user_id: u32,
+    pub post_id: u32,
+    /// The [`ModReason`][super::mod_reasons::ModReason] picked by the moderator
+    #[serde(default)]
+    pub reason_id: Option<u32>,
+    /// Written by the moderator, may be empty
+    #[serde(default)]
+    pub note: String,
+    /// Id of the moderator
+    pub issued_by: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+impl CollectionName for Warning {
+    fn collection_name() -> &'static str {
+        "warnings"
+    }
+}
+
+/// Applies `shortcut` to the [`Post`][super::Post] with `post_id`, recording it in the
+/// moderation log with the [`ModReason`][super::mod_reasons::ModReason] with `reason_id`.
+/// `note` is only sent along with [`PostShortcut::WarnAuthor`], `target_thread_id` is
+/// only needed for [`PostShortcut::Move`]
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` or `target_thread_id` isn't in use
+///   or the reason can't be picked
+/// - [`ApiError::NoAuthorToWarn`] if warning the author of a post of a guest or deleted user
+/// - [`ApiError::Forbidden`] if warning a moderator or admin
+/// - [`ApiError::InvalidMove`] if moving without a target, into the same thread
+///   or the origin post of a thread
+#[server]
+pub async fn moderate_post(
+    post_id: u32,
+    shortcut: PostShortcut,
+    #[server(default)] reason_id: Option<u32>,
+    #[server(default)] note: String,
+    #[server(default)] target_thread_id: Option<u32>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+    mod_reasons::check_reason(reason_id, &db).await?;
+
+    let post = helper::get_post(post_id, db.clone()).await?;
+    let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+    match shortcut {
+        PostShortcut::Delete => delete_post(&post, &thread, moderator.id, reason_id, &db).await,
+        PostShortcut::WarnAuthor => {
+            warn_author(&post, &moderator, reason_id, note.trim(), &db).await
+        }
+        PostShortcut::Move => {
+            let Some(target_thread_id) = target_thread_id else {
+                return Err(ApiError::InvalidMove);
+            };
+            move_post(
+                &post,
+                &thread,
+                target_thread_id,
+                moderator.id,
+                reason_id,
+                &db,
+            )
+            .await
+        }
+    }
+}
+
+/// Soft-deletes `post`, or its `thread` instead if it's the origin post
+#[cfg(feature = "ssr")]
+async fn delete_post(
+    post: &Post,
+    thread: &Thread,
+    moderator_id: u32,
+    reason_id: Option<u32>,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    // the thread would be left without a first post
+    if thread.origin_post_id == post.id {
+        return thread_tools::set_thread_deleted(thread.id, Some(now), reason_id).await;
+    }
+
+    Post::collection(db)
+        .update_one(
+            bson::doc! {"id": post.id},
+            bson::doc! {"$set": {"deleted_at": now, "deleted_by": moderator_id}},
+        )
+        .await?;
+    helper::refresh_latest_of_thread(thread, db).await?;
+    cache::invalidate_forums(&[thread.forum_id], db).await?;
+    events::emit(
+        DomainEvent::PostDeleted {
+            post_id: post.id,
+            thread_id: thread.id,
+            moderator_id,
+            reason_id,
+        },
+        db,
+    );
+
+    Ok(())
+}
+
+/// Records a [`Warning`] for the author of `post` and emails it to them in the background
+#[cfg(feature = "ssr")]
+async fn warn_author(
+    post: &Post,
+    moderator: &User,
+    reason_id: Option<u32>,
+    note: &str,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let Some(author_id) = post.author_id else {
+        return Err(ApiError::NoAuthorToWarn);
+    };
+    let author = match helper::get_user(author_id, db).await {
+        Ok(author) => author,
+        Err(ApiError::NotFound(..)) => return Err(ApiError::NoAuthorToWarn),
+        Err(err) => return Err(err),
+    };
+    // like sanctions, moderators can't warn each other
+    if author.role >= moderator.role {
+        return Err(ApiError::Forbidden);
+    }
+
+    let warning = Warning {
+        user_id: author.id,
+        post_id: post.id,
+        reason_id,
+        note: note.to_string(),
+        issued_by: moderator.id,
+        created_at: jiff::Timestamp::now(),
+    };
+    Warning::collection(db).insert_one(&warning).await?;
+    events::emit(
+        DomainEvent::UserWarned {
+            user_id: author.id,
+            post_id: post.id,
+            moderator_id: moderator.id,
+            note,
+            reason_id,
+        },
+        db,
+    );
+
+    let email = AccountDetails::collection(db)
+        .find_one(bson::doc! {"user_id": author.id})
+        .await?
+        .and_then(|details| details.email);
+    let Some(email) = email else {
+        return Ok(());
+    };
+    let reason = match reason_id {
+        Some(reason_id) => ModReason::collection(db)
+            .find_one(bson::doc! {"id": reason_id})
+            .await?
+            .map(|reason| reason.name),
+        None => None,
+    };
+    let email = warning_email(&email, &author.name, &warning, reason.as_deref());
+    tokio::spawn(async move {
+        if let Err(err) = mailer::send(&email).await {
+            tracing::warn!("sending a warning to {} failed: {err}", email.to);
+        }
+    });
+
+    Ok(())
+}
+
+/// The email telling `name` about `warning`, `reason` being the name of its [`ModReason`]
+#[cfg(feature = "ssr")]
+fn warning_email(to: &str, name: &str, warning: &Warning, reason: Option<&str>) -> mailer::Email {
+    let link = format!(
+        "{}/post/{}",
+        helper::public_url().unwrap_or_default(),
+        warning.post_id
+    );
+    let reason = reason
+        .map(|reason| format!("Reason: {reason}\n"))
+        .unwrap_or_default();
+    let note = if warning.note.is_empty() {
+        String::new()
+    } else {
+        format!("{}\n", warning.note)
+    };
+    let text = format!(
+        "Hi {name},\n\n\
+         a moderator warned you about this post of yours:\n\
+         {link}\n\n\
+         {reason}{note}\n\
+         Please keep to the rules of the forum, \
+         further violations may get your account muted or banned.\n",
+    );
+    mailer::Email::new(to, "A warning from the moderators".to_string(), text)
+}
+
+/// Moves `post` out of `thread` into the thread with `target_thread_id`
+#[cfg(feature = "ssr")]
+async fn move_post(
+    post: &Post,
+    thread: &Thread,
+    target_thread_id: u32,
+    moderator_id: u32,
+    reason_id: Option<u32>,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    // the origin post only moves with its thread, see `merge_threads`
+    if thread.origin_post_id == post.id || target_thread_id == thread.id {
+        return Err(ApiError::InvalidMove);
+    }
+    let target = helper::get_thread(target_thread_id, db.clone()).await?;
+
+    Post::collection(db)
+        .update_one(
+            bson::doc! {"id": post.id},
+            bson::doc! {"$set": {
+                "thread_id": target.id,
+                "thread_deleted": target.deleted_at.is_some(),
+            }},
+        )
+        .await?;
+
+    let forum_ids = [thread.forum_id, target.forum_id];
+    helper::refresh_latest_ids(&forum_ids, db).await?;
+    cache::invalidate_forums(&forum_ids, db).await?;
+    events::emit(
+        DomainEvent::PostMoved {
+            post_id: post.id,
+            source_thread_id: thread.id,
+            target_thread_id: target.id,
+            moderator_id,
+            reason_id,
+        },
+        db,
+    );
+
+    Ok(())
+}
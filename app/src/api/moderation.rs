@@ -0,0 +1,345 @@
+//! The moderation queue: [`Posts`][Post] (and with their origin post also [`Threads`][Thread])
+//! held for review, e.g. by the [`word_filter`][super::word_filter] or the [`spam`][super::spam]
+//! heuristics
+//!
+//! Held posts can be assigned to a moderator with a [`ReviewAssignment`],
+//! and show how long they've been waiting, see [`QueueAge`]
+
+use super::user::User;
+use super::{ApiError, CollectionName, Post, Thread};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, bson, cache, helper,
+    user::Role,
+    events::{self, DomainEvent},
+};
+
+use jiff::{Timestamp, ToSpan};
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Held posts waiting longer than this many hours are [`QueueAge::Ageing`]
+pub const AGEING_AFTER_HOURS: i64 = 24;
+/// Held posts waiting longer than this many hours are [`QueueAge::Overdue`]
+pub const OVERDUE_AFTER_HOURS: i64 = 72;
+
+/// How long a held [`Post`] has been waiting for review
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QueueAge {
+    Fresh,
+    /// Waiting longer than [`AGEING_AFTER_HOURS`]
+    Ageing,
+    /// Waiting longer than [`OVERDUE_AFTER_HOURS`]
+    Overdue,
+}
+impl QueueAge {
+    /// Classifies a post held since `held_since` as of `now`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::moderation::QueueAge;
+    /// use jiff::{Timestamp, ToSpan};
+    ///
+    /// let now: Timestamp = "2025-03-10T12:00:00Z".parse().unwrap();
+    /// assert_eq!(QueueAge::of(now - 2.hours(), now), QueueAge::Fresh);
+    /// assert_eq!(QueueAge::of(now - 30.hours(), now), QueueAge::Ageing);
+    /// assert_eq!(QueueAge::of(now - 100.hours(), now), QueueAge::Overdue);
+    /// ```
+    #[must_use]
+    pub fn of(held_since: Timestamp, now: Timestamp) -> Self {
+        if held_since < now - OVERDUE_AFTER_HOURS.hours() {
+            Self::Overdue
+        } else if held_since < now - AGEING_AFTER_HOURS.hours() {
+            Self::Ageing
+        } else {
+            Self::Fresh
+        }
+    }
+}
+
+/// Assigns a held [`Post`] to the moderator who is going to review it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReviewAssignment {
+    pub post_id: u32,
+    pub moderator_id: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub assigned_at: Timestamp,
+}
+impl CollectionName for ReviewAssignment {
+    fn collection_name() -> &'static str {
+        "review_assignments"
+    }
+}
+
+/// A [`Post`] held for review with its [`Thread`] and who's assigned to it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HeldItem {
+    pub post: Post,
+    pub thread: Thread,
+    pub assignee: Option<User>,
+}
+
+/// Numbers of the moderation queue for the admin dashboard
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct QueueSummary {
+    pub held: u32,
+    /// Held posts no moderator is assigned to
+    pub unclaimed: u32,
+    /// Held posts waiting longer than [`OVERDUE_AFTER_HOURS`]
+    pub overdue: u32,
+}
+
+/// Fetches all [`Posts`][Post] held for review with the [`Thread`] they're in
+/// and their assignee, oldest first
+///
+/// Requires [`Role::Moderator`]
+#[server]
+pub async fn get_held_posts() -> Result<Vec<HeldItem>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    let assignment_col = ReviewAssignment::collection(&db);
+    let mut held = vec![];
+    let mut post_cursor = Post::collection(&db)
+        .find(bson::doc! {"pending_review": true, "deleted_at": null})
+        // ascending
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    while post_cursor.advance().await? {
+        let post: Post = post_cursor.deserialize_current()?;
+        let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+        let assignee = match assignment_col
+            .find_one(bson::doc! {"post_id": post.id})
+            .await?
+        {
+            Some(assignment) => Some(helper::get_user(assignment.moderator_id, &db).await?),
+            None => None,
+        };
+        held.push(HeldItem {
+            post,
+            thread,
+            assignee,
+        });
+    }
+
+    Ok(held)
+}
+
+/// Counts the held, unclaimed and overdue [`Posts`][Post] in the queue
+///
+/// Requires [`Role::Moderator`]
+#[server]
+pub async fn get_queue_summary() -> Result<QueueSummary, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    let mut assigned = vec![];
+    let mut cursor = ReviewAssignment::collection(&db)
+        .find(bson::doc! {})
+        .await?;
+    while cursor.advance().await? {
+        let assignment: ReviewAssignment = cursor.deserialize_current()?;
+        assigned.push(assignment.post_id);
+    }
+
+    let now = Timestamp::now();
+    let mut summary = QueueSummary::default();
+    let mut post_cursor = Post::collection(&db)
+        .find(bson::doc! {"pending_review": true, "deleted_at": null})
+        .await?;
+    while post_cursor.advance().await? {
+        let post: Post = post_cursor.deserialize_current()?;
+        summary.held += 1;
+        if !assigned.contains(&post.id) {
+            summary.unclaimed += 1;
+        }
+        if QueueAge::of(post.created_at, now) == QueueAge::Overdue {
+            summary.overdue += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Assigns the held [`Post`] to the moderator called `moderator_name`,
+/// or to nobody if it's empty
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+/// - [`ApiError::Forbidden`] if the post isn't held
+/// - [`ApiError::UnknownUsername`] if there's no moderator called `moderator_name`
+#[server]
+pub async fn assign_held_post(post_id: u32, moderator_name: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    let post = helper::get_post(post_id, db.clone()).await?;
+    if !post.pending_review {
+        return Err(ApiError::Forbidden);
+    }
+
+    let assignment_col = ReviewAssignment::collection(&db);
+    let moderator_name = moderator_name.trim();
+    if moderator_name.is_empty() {
+        assignment_col
+            .delete_one(bson::doc! {"post_id": post_id})
+            .await?;
+        return Ok(());
+    }
+    let Some(moderator) = User::collection(&db)
+        .find_one(bson::doc! {"name": moderator_name})
+        .await?
+        .filter(|user| user.role >= Role::Moderator)
+    else {
+        return Err(ApiError::UnknownUsername(moderator_name.to_string()));
+    };
+
+    assignment_col
+        .update_one(
+            bson::doc! {"post_id": post_id},
+            bson::doc! {"$set": {
+                "moderator_id": moderator.id,
+                "assigned_at": bson::DateTime::now(),
+            }},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Removes the [`ReviewAssignment`] of a post that left the queue
+#[cfg(feature = "ssr")]
+async fn remove_assignment(post_id: u32, db: &mongodb::Database) -> Result<(), ApiError> {
+    ReviewAssignment::collection(db)
+        .delete_one(bson::doc! {"post_id": post_id})
+        .await?;
+    Ok(())
+}
+
+/// Makes a held [`Post`] visible
+///
+/// If it's the origin post of a held [`Thread`], the thread is approved too
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+/// - [`ApiError::Forbidden`] if the post isn't held (anymore)
+#[server]
+pub async fn approve_post(post_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+
+    let post = helper::get_post(post_id, db.clone()).await?;
+    if !post.pending_review {
+        return Err(ApiError::Forbidden);
+    }
+    let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+
+    // only one of two moderators approving at once announces the post
+    let approved = Post::collection(&db)
+        .update_one(
+            bson::doc! {"id": post_id, "pending_review": true},
+            bson::doc! {"$set": {"pending_review": false}},
+        )
+        .await?;
+    if approved.modified_count == 0 {
+        return Err(ApiError::Forbidden);
+    }
+
+    // held posts don't count as activity, so now they do
+    helper::refresh_latest_of_thread(&thread, &db).await?;
+    if thread.origin_post_id == post_id {
+        Thread::collection(&db)
+            .update_one(
+                bson::doc! {"id": thread.id},
+                bson::doc! {"$set": {"pending_review": false}},
+            )
+            .await?;
+        // the whole thread just appeared
+        helper::refresh_latest_of_forum(thread.forum_id, &db).await?;
+    }
+    cache::invalidate_forums(&[thread.forum_id], &db).await?;
+    remove_assignment(post_id, &db).await?;
+
+    events::emit(
+        DomainEvent::PostApproved {
+            post_id,
+            moderator_id: moderator.id,
+        },
+        &db,
+    );
+    // only now others get to see it
+    let (forum, _) = helper::get_forum(thread.forum_id, db.clone()).await?;
+    let author = match post.author_id {
+        Some(author_id) => helper::get_user(author_id, &db).await.ok(),
+        None => None,
+    };
+    let (forum, thread, post, author) = (&forum, &thread, &post, author.as_ref());
+    let event = if thread.origin_post_id == post_id {
+        DomainEvent::ThreadCreated {
+            forum,
+            thread,
+            post,
+            author,
+        }
+    } else {
+        DomainEvent::PostCreated {
+            forum,
+            thread,
+            post,
+            author,
+        }
+    };
+    events::emit(event, &db);
+
+    Ok(())
+}
+
+/// Deletes a held [`Post`] for good
+///
+/// If it's the origin post of a [`Thread`], the thread is deleted too
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+/// - [`ApiError::Forbidden`] if the post isn't held, use
+///   [`moderate_post`][super::mod_shortcuts::moderate_post] for those
+#[server]
+pub async fn reject_post(post_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+
+    let post = helper::get_post(post_id, db.clone()).await?;
+    if !post.pending_review {
+        return Err(ApiError::Forbidden);
+    }
+    let thread = helper::get_thread(post.thread_id, db.clone()).await?;
+
+    Post::collection(&db)
+        .delete_one(bson::doc! {"id": post_id})
+        .await?;
+    if thread.origin_post_id == post_id {
+        Thread::collection(&db)
+            .delete_one(bson::doc! {"id": thread.id})
+            .await?;
+    }
+    remove_assignment(post_id, &db).await?;
+    events::emit(
+        DomainEvent::PostRejected {
+            post_id,
+            moderator_id: moderator.id,
+        },
+        &db,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,237 @@
+//! The [`Threads`][Thread] of the logged-in [`User`][super::user::User] for the "My content" page:
+//! the ones they created, posted in or watch
+//!
+//! Watching a thread is a bookmark, remembered as a [`WatchedThread`], see [`set_thread_watched`].
+//! Watched threads and forums are managed on the subscriptions page, see [`super::subscriptions`]
+
+use super::{ApiError, CollectionName, Thread, subscriptions::WatchLevel};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, Post, bson, helper,
+    visibility::{self, Listing},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How many [`Threads`][Thread] a page of [`get_my_threads`] has
+pub const MY_THREADS_PER_PAGE: u64 = 20;
+
+/// Which [`Threads`][Thread] of the user [`get_my_threads`] lists
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MyThreadsKind {
+    /// Started by the user
+    #[default]
+    Created,
+    /// With at least one post of the user, including the ones they started
+    Participated,
+    /// Watched by the user, see [`set_thread_watched`]
+    Watched,
+}
+impl MyThreadsKind {
+    pub const ALL: [Self; 3] = [Self::Created, Self::Participated, Self::Watched];
+
+    /// Translation key of the human-readable name of the list
+    #[must_use]
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Created => "my_threads.kind.created",
+            Self::Participated => "my_threads.kind.participated",
+            Self::Watched => "my_threads.kind.watched",
+        }
+    }
+}
+
+/// The order of the [`Threads`][Thread] of [`get_my_threads`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MyThreadsOrder {
+    /// Most recent post first
+    #[default]
+    LatestActivity,
+    /// Least recent post first
+    OldestActivity,
+}
+impl MyThreadsOrder {
+    pub const ALL: [Self; 2] = [Self::LatestActivity, Self::OldestActivity];
+
+    /// Translation key of the human-readable name of the order
+    #[must_use]
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::LatestActivity => "my_threads.order.latest_activity",
+            Self::OldestActivity => "my_threads.order.oldest_activity",
+        }
+    }
+
+    /// For use in db queries, post ids grow with time so the latest post id is the latest activity
+    #[cfg(feature = "ssr")]
+    fn sort(self) -> bson::Document {
+        match self {
+            Self::LatestActivity => bson::doc! {"latest_post_id": -1},
+            Self::OldestActivity => bson::doc! {"latest_post_id": 1},
+        }
+    }
+}
+
+/// A [`Thread`] watched by a [`User`][super::user::User]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WatchedThread {
+    pub user_id: u32,
+    pub thread_id: u32,
+    /// What they're notified about, see [`super::subscriptions`]
+    #[serde(default)]
+    pub level: WatchLevel,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub watched_at: jiff::Timestamp,
+}
+impl CollectionName for WatchedThread {
+    fn collection_name() -> &'static str {
+        "watched_threads"
+    }
+}
+
+/// A page of [`Threads`][Thread] returned by [`get_my_threads`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MyThreadsPage {
+    pub threads: Vec<Thread>,
+    /// Starting at 1
+    pub page: u64,
+    /// Amount of threads on all pages
+    pub total: u64,
+}
+impl MyThreadsPage {
+    /// Amount of pages, at least 1 so an empty list still has a page
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::my_content::{MY_THREADS_PER_PAGE, MyThreadsPage};
+    ///
+    /// let page = |total| MyThreadsPage { threads: vec![], page: 1, total };
+    /// assert_eq!(page(0).page_count(), 1);
+    /// assert_eq!(page(MY_THREADS_PER_PAGE).page_count(), 1);
+    /// assert_eq!(page(MY_THREADS_PER_PAGE + 1).page_count(), 2);
+    /// ```
+    #[must_use]
+    pub fn page_count(&self) -> u64 {
+        self.total.div_ceil(MY_THREADS_PER_PAGE).max(1)
+    }
+}
+
+/// Watches the [`Thread`] with `thread_id` for the logged-in [`User`][super::user::User]
+/// or stops watching it
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use
+#[server]
+pub async fn set_thread_watched(
+    thread_id: u32,
+    #[server(default)] watched: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+
+    let by_user_and_thread = bson::doc! {"user_id": user.id, "thread_id": thread.id};
+    if watched {
+        let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+        WatchedThread::collection(&db)
+            .update_one(
+                by_user_and_thread,
+                bson::doc! {"$setOnInsert": {"watched_at": now}},
+            )
+            .upsert(true)
+            .await?;
+    } else {
+        WatchedThread::collection(&db)
+            .delete_one(by_user_and_thread)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether the logged-in [`User`][super::user::User] watches the [`Thread`]
+/// with `thread_id`, [`None`] if not logged in
+#[server]
+pub async fn is_thread_watched(thread_id: u32) -> Result<Option<bool>, ApiError> {
+    let db = helper::get_db()?;
+    let Some(user) = helper::get_current_user(&db).await? else {
+        return Ok(None);
+    };
+    let watched = WatchedThread::collection(&db)
+        .find_one(bson::doc! {"user_id": user.id, "thread_id": thread_id})
+        .await?
+        .is_some();
+    Ok(Some(watched))
+}
+
+/// Returns the `page` (starting at 1) of the [`Threads`][Thread] of `kind` of the logged-in
+/// [`User`][super::user::User] in `order`, [`MY_THREADS_PER_PAGE`] at a time
+///
+/// Threads they can't see anymore, e.g. deleted ones or ones in forums they lost access to,
+/// are left out
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn get_my_threads(
+    kind: MyThreadsKind,
+    order: MyThreadsOrder,
+    page: u64,
+) -> Result<MyThreadsPage, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let page = page.max(1);
+
+    let mut filter = match kind {
+        MyThreadsKind::Created => bson::doc! {"author_id": user.id},
+        MyThreadsKind::Participated => {
+            let thread_ids = Post::collection(&db)
+                .distinct(
+                    "thread_id",
+                    bson::doc! {
+                        "author_id": user.id,
+                        "pending_review": {"$ne": true},
+                        "deleted_at": null,
+                    },
+                )
+                .await?;
+            bson::doc! {"id": {"$in": thread_ids}}
+        }
+        MyThreadsKind::Watched => {
+            let thread_ids = WatchedThread::collection(&db)
+                .distinct("thread_id", bson::doc! {"user_id": user.id})
+                .await?;
+            bson::doc! {"id": {"$in": thread_ids}}
+        }
+    };
+    let forums = helper::get_all_forums(&db).await?;
+    let forum_ids = visibility::listed_forum_ids(&forums, Some(&user), Listing::All);
+    filter.insert("forum_id", bson::doc! {"$in": forum_ids});
+    filter.insert("pending_review", bson::doc! {"$ne": true});
+    filter.insert("deleted_at", bson::Bson::Null);
+
+    let total = Thread::collection(&db)
+        .count_documents(filter.clone())
+        .await?;
+    let mut cursor = Thread::collection(&db)
+        .find(filter)
+        .sort(order.sort())
+        .skip((page - 1).saturating_mul(MY_THREADS_PER_PAGE))
+        .limit(i64::try_from(MY_THREADS_PER_PAGE).unwrap_or(i64::MAX))
+        .await?;
+    let mut threads = vec![];
+    while cursor.advance().await? {
+        threads.push(cursor.deserialize_current()?);
+    }
+
+    Ok(MyThreadsPage {
+        threads,
+        page,
+        total,
+    })
+}
@@ -0,0 +1,222 @@
+//! Preview images of [`Threads`][super::Thread] for shared links, see [`thread_image_path`]
+//!
+//! The card shows the forum name, the subject and the post count, drawn with
+//! the 8x8 pixel font of `font8x8` scaled up, so no font files are needed.
+//! Like feeds, only threads of public forums have one
+
+#[cfg(feature = "ssr")]
+use super::{ApiError, GetCollection, Post, bson, helper, site_settings::GuestCapability};
+
+/// Width of the card in pixels, as recommended for OpenGraph images
+pub const WIDTH: u32 = 1200;
+/// Height of the card in pixels
+pub const HEIGHT: u32 = 630;
+/// Space around the text in pixels
+const MARGIN: u32 = 80;
+/// Scale of the subject, i.e. its characters are 8 times this pixels wide
+const SUBJECT_SCALE: u32 = 6;
+/// Maximum lines of the subject
+const SUBJECT_LINES: usize = 4;
+
+/// Path of the preview image of a [`Thread`][super::Thread], e.g. `/og/thread/3.png`
+#[must_use]
+pub fn thread_image_path(thread_id: u32) -> String {
+    format!("/og/thread/{thread_id}.png")
+}
+
+/// Splits `text` into at most `max_lines` lines of at most `max_chars` characters,
+/// breaking at spaces where possible and ending with `...` if it doesn't fit
+///
+/// # Example
+///
+/// ```
+/// use app::api::og_image::wrap;
+///
+/// assert_eq!(wrap("Hello there, how are you?", 12, 3), ["Hello there,", "how are you?"]);
+/// assert_eq!(wrap("Supercalifragilistic", 8, 3), ["Supercal", "ifragili", "stic"]);
+/// assert_eq!(wrap("one two three four", 7, 2), ["one two", "thre..."]);
+/// ```
+#[must_use]
+pub fn wrap(text: &str, max_chars: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let line_len = line.chars().count();
+        if line_len > 0 && line_len + 1 + word.len() <= max_chars {
+            line.push(' ');
+            line.extend(&word);
+            continue;
+        }
+        if line_len > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+        // words too long for a line of their own are split
+        while word.len() > max_chars {
+            lines.push(word.drain(..max_chars).collect());
+        }
+        line.extend(word);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            let kept: String = last.chars().take(max_chars.saturating_sub(3)).collect();
+            *last = format!("{kept}...");
+        }
+    }
+    lines
+}
+
+/// Returns the 8x8 bitmap of `char`, one byte per row with the leftmost pixel in the lowest bit
+#[cfg(feature = "ssr")]
+fn glyph(char: char) -> [u8; 8] {
+    use font8x8::UnicodeFonts;
+
+    font8x8::BASIC_FONTS
+        .get(char)
+        .or_else(|| font8x8::LATIN_FONTS.get(char))
+        .or_else(|| font8x8::BASIC_FONTS.get('?'))
+        .unwrap_or_default()
+}
+
+/// Draws `text` in a single line with its top left corner at `x`, `y`,
+/// cutting off what doesn't fit on the card
+#[cfg(feature = "ssr")]
+fn draw_text(
+    image: &mut image::RgbImage,
+    text: &str,
+    (x, y): (u32, u32),
+    scale: u32,
+    color: [u8; 3],
+) {
+    let char_width = 8 * scale;
+    for (i, char) in (0..).zip(text.chars()) {
+        let char_x = x + i * char_width;
+        if char_x + char_width > WIDTH {
+            break;
+        }
+        for (row, bits) in (0..).zip(glyph(char)) {
+            for col in 0..8 {
+                if (bits >> col) & 1 == 0 {
+                    continue;
+                }
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let (px, py) = (char_x + col * scale + dx, y + row * scale + dy);
+                        if py < HEIGHT {
+                            image.put_pixel(px, py, image::Rgb(color));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Renders the card of a thread and encodes it as PNG
+///
+/// Blocking, so run it with [`tokio::task::spawn_blocking`]
+#[cfg(feature = "ssr")]
+fn render_card(forum_name: &str, subject: &str, post_count: u64) -> Result<Vec<u8>, ApiError> {
+    use std::io::Cursor;
+
+    // purple-950, purple-300 and purple-100 of the site
+    const BACKGROUND: [u8; 3] = [59, 7, 100];
+    const MUTED: [u8; 3] = [216, 180, 254];
+    const TEXT: [u8; 3] = [243, 232, 255];
+
+    let mut image = image::RgbImage::from_pixel(WIDTH, HEIGHT, image::Rgb(BACKGROUND));
+
+    draw_text(&mut image, forum_name, (MARGIN, MARGIN), 4, MUTED);
+
+    let max_chars =
+        usize::try_from((WIDTH - 2 * MARGIN) / (8 * SUBJECT_SCALE)).unwrap_or_default();
+    let line_height = 8 * SUBJECT_SCALE + 16;
+    for (i, line) in (0..).zip(wrap(subject, max_chars, SUBJECT_LINES)) {
+        let y = MARGIN + 80 + i * line_height;
+        draw_text(&mut image, &line, (MARGIN, y), SUBJECT_SCALE, TEXT);
+    }
+
+    let footer_y = HEIGHT - MARGIN - 32;
+    let posts = if post_count == 1 {
+        "1 post".to_string()
+    } else {
+        format!("{post_count} posts")
+    };
+    draw_text(&mut image, &posts, (MARGIN, footer_y), 4, MUTED);
+    let brand = "Dafoerum";
+    let brand_x = WIDTH - MARGIN - 32 * u32::try_from(brand.len()).unwrap_or_default();
+    draw_text(&mut image, brand, (brand_x, footer_y), 4, TEXT);
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|err| ApiError::ImageProcessing(err.to_string()))?;
+    Ok(png)
+}
+
+/// Looks up what's shown on the card of a public thread and renders it
+#[cfg(feature = "ssr")]
+async fn thread_card(thread_id: u32, db: &mongodb::Database) -> Result<Vec<u8>, ApiError> {
+    helper::ensure_guest_can(GuestCapability::Threads, None, db).await?;
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if thread.pending_review || thread.deleted_at.is_some() {
+        return Err(ApiError::NotFound("thread".into(), thread_id));
+    }
+    helper::ensure_can_see_forum(thread.forum_id, None, db).await?;
+    let (forum, _) = helper::get_forum(thread.forum_id, db.clone()).await?;
+    let post_count = Post::collection(db)
+        .count_documents(bson::doc! {
+            "thread_id": thread.id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .await?;
+
+    let subject = thread.display_subject();
+    tokio::task::spawn_blocking(move || render_card(&forum.name, &subject, post_count))
+        .await
+        .map_err(|err| ApiError::ImageProcessing(err.to_string()))?
+}
+
+/// Axum handler for `GET /og/thread/{id}.png`, see [`thread_image_path`]
+///
+/// The path is matched as `/og/thread/{file}`, since the router can't split `{id}.png`
+#[cfg(feature = "ssr")]
+pub async fn thread_image(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    axum::extract::Path(file): axum::extract::Path<String>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use http::{StatusCode, header};
+
+    let Some(thread_id) = file
+        .strip_suffix(".png")
+        .and_then(|id| id.parse::<u32>().ok())
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match thread_card(thread_id, &db).await {
+        Ok(png) => (
+            [
+                (header::CONTENT_TYPE, "image/png"),
+                // the post count changes, but it doesn't have to be exact
+                (header::CACHE_CONTROL, "public, max-age=3600"),
+            ],
+            png,
+        )
+            .into_response(),
+        Err(ApiError::NotFound(..) | ApiError::LoginRequired) => {
+            StatusCode::NOT_FOUND.into_response()
+        }
+        Err(err) => {
+            tracing::warn!("rendering preview image of thread {thread_id} failed: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,227 @@
+//! Checklist guiding new [`Users`][super::user::User] through their first steps
+//!
+//! Steps are detected from what the user did (e.g. uploading an avatar) whenever the checklist
+//! is loaded and then kept in their [`OnboardingProgress`], so undoing something later
+//! doesn't uncheck it again. Only accounts younger than [`ONBOARDING_DAYS`] see it
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, Post, Thread, bson, forum_rules::RulesAcknowledgement, helper,
+    legal::LegalAcceptance,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Accounts older than this many days don't get the checklist anymore
+pub const ONBOARDING_DAYS: i64 = 30;
+
+/// A single item of the checklist
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnboardingStep {
+    SetAvatar,
+    /// Creating any thread
+    IntroduceYourself,
+    /// Acknowledging the rules of a forum or accepting the Terms of Service,
+    /// can also be checked off by hand
+    ReadRules,
+    /// Replying in a thread
+    FirstPost,
+}
+impl OnboardingStep {
+    pub const ALL: [Self; 4] = [
+        Self::SetAvatar,
+        Self::IntroduceYourself,
+        Self::ReadRules,
+        Self::FirstPost,
+    ];
+
+    /// Translation key of what the user is asked to do
+    #[must_use]
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::SetAvatar => "onboarding.step.set_avatar",
+            Self::IntroduceYourself => "onboarding.step.introduce_yourself",
+            Self::ReadRules => "onboarding.step.read_rules",
+            Self::FirstPost => "onboarding.step.first_post",
+        }
+    }
+
+    /// Where the step can be done
+    #[must_use]
+    pub fn link(self) -> &'static str {
+        match self {
+            Self::SetAvatar => "/profile",
+            Self::IntroduceYourself | Self::FirstPost => "/forum",
+            Self::ReadRules => "/terms",
+        }
+    }
+}
+
+/// The checklist state of a [`User`][super::user::User]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct OnboardingProgress {
+    pub user_id: u32,
+    /// In the order they were completed
+    #[serde(default)]
+    pub completed: Vec<OnboardingStep>,
+    /// Dismissed checklists aren't shown anymore
+    #[serde(default)]
+    pub dismissed: bool,
+}
+impl OnboardingProgress {
+    /// Whether every [`OnboardingStep`] is completed
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        OnboardingStep::ALL
+            .iter()
+            .all(|step| self.completed.contains(step))
+    }
+}
+impl CollectionName for OnboardingProgress {
+    fn collection_name() -> &'static str {
+        "onboarding"
+    }
+}
+
+/// Checks whether `user` did `step`
+#[cfg(feature = "ssr")]
+async fn detect(
+    step: OnboardingStep,
+    user: &super::user::User,
+    db: &mongodb::Database,
+) -> Result<bool, ApiError> {
+    let done = match step {
+        OnboardingStep::SetAvatar => user.avatar_version.is_some(),
+        OnboardingStep::IntroduceYourself => Thread::collection(db)
+            .find_one(bson::doc! {"author_id": user.id})
+            .await?
+            .is_some(),
+        OnboardingStep::ReadRules => {
+            RulesAcknowledgement::collection(db)
+                .find_one(bson::doc! {"user_id": user.id})
+                .await?
+                .is_some()
+                || LegalAcceptance::collection(db)
+                    .find_one(bson::doc! {"user_id": user.id})
+                    .await?
+                    .is_some()
+        }
+        OnboardingStep::FirstPost => {
+            // every thread has an origin post by its author, more posts are replies
+            let threads = Thread::collection(db)
+                .count_documents(bson::doc! {"author_id": user.id})
+                .await?;
+            let posts = Post::collection(db)
+                .count_documents(bson::doc! {"author_id": user.id})
+                .await?;
+            posts > threads
+        }
+    };
+    Ok(done)
+}
+
+/// Saves `progress`, creating it if it's the first time
+#[cfg(feature = "ssr")]
+async fn save(progress: &OnboardingProgress, db: &mongodb::Database) -> Result<(), ApiError> {
+    OnboardingProgress::collection(db)
+        .replace_one(bson::doc! {"user_id": progress.user_id}, progress)
+        .upsert(true)
+        .await?;
+    Ok(())
+}
+
+/// Returns the [`OnboardingProgress`] of the logged-in [`User`][super::user::User],
+/// checking off the steps they did since it was last loaded
+///
+/// [`None`] if not logged in or the account is older than [`ONBOARDING_DAYS`]
+#[server]
+pub async fn get_onboarding() -> Result<Option<OnboardingProgress>, ApiError> {
+    let db = helper::get_db()?;
+    let Some(user) = helper::get_current_user(&db).await? else {
+        return Ok(None);
+    };
+    let age_days = jiff::Timestamp::now()
+        .duration_since(user.created_at)
+        .as_hours()
+        / 24;
+    if age_days >= ONBOARDING_DAYS {
+        return Ok(None);
+    }
+
+    let mut progress = OnboardingProgress::collection(&db)
+        .find_one(bson::doc! {"user_id": user.id})
+        .await?
+        .unwrap_or(OnboardingProgress {
+            user_id: user.id,
+            ..Default::default()
+        });
+    if progress.dismissed {
+        return Ok(Some(progress));
+    }
+
+    let mut changed = false;
+    for step in OnboardingStep::ALL {
+        if !progress.completed.contains(&step) && detect(step, &user, &db).await? {
+            progress.completed.push(step);
+            changed = true;
+        }
+    }
+    if changed {
+        save(&progress, &db).await?;
+    }
+
+    Ok(Some(progress))
+}
+
+/// Checks off [`OnboardingStep::ReadRules`] for the logged-in [`User`][super::user::User]
+///
+/// The other steps are only checked off by doing them
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::Forbidden`] for any other step
+#[server]
+pub async fn complete_onboarding_step(step: OnboardingStep) -> Result<(), ApiError> {
+    if step != OnboardingStep::ReadRules {
+        return Err(ApiError::Forbidden);
+    }
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let Ok(step) = bson::to_bson(&step) else {
+        unreachable!("a unit enum is always serializable")
+    };
+    OnboardingProgress::collection(&db)
+        .update_one(
+            bson::doc! {"user_id": user.id},
+            bson::doc! {"$addToSet": {"completed": step}},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Hides the checklist of the logged-in [`User`][super::user::User] for good
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn dismiss_onboarding() -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    OnboardingProgress::collection(&db)
+        .update_one(
+            bson::doc! {"user_id": user.id},
+            bson::doc! {"$set": {"dismissed": true}},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
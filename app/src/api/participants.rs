@@ -0,0 +1,77 @@
+//! Who took part in a [`Thread`][super::Thread], shown in a panel above its posts
+
+use super::{ApiError, groups::Member};
+#[cfg(feature = "ssr")]
+use super::{CollectionName, GetCollection, Post, bson, groups::UserGroup, helper, user::User};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A [`Member`] who posted in a [`Thread`][super::Thread]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Participant {
+    pub member: Member,
+    /// Visible posts of theirs in the thread
+    pub post_count: u32,
+    pub first_post_id: u32,
+}
+
+/// Returns everyone who posted in the [`Thread`][super::Thread] with `thread_id`,
+/// in the order they first did so, counting only visible posts.
+/// Anonymous posts and deleted accounts are left out
+///
+/// # Errors
+///
+/// Same as [`get_thread`][super::get_thread]
+#[server]
+pub async fn get_thread_participants(thread_id: u32) -> Result<Vec<Participant>, ApiError> {
+    // checks if the thread can be seen and follows merges
+    let thread = super::get_thread(thread_id).await?;
+    let db = helper::get_db()?;
+
+    let pipeline = vec![
+        bson::doc! {"$match": {
+            "thread_id": thread.id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+            "author_id": {"$ne": null},
+        }},
+        bson::doc! {"$group": {
+            "_id": "$author_id",
+            "post_count": {"$sum": 1},
+            "first_post_id": {"$min": "$id"},
+        }},
+        bson::doc! {"$sort": {"first_post_id": 1}},
+        bson::doc! {"$lookup": {
+            "from": User::collection_name(),
+            "localField": "_id",
+            "foreignField": "id",
+            "as": "user",
+        }},
+        // deleted accounts
+        bson::doc! {"$unwind": "$user"},
+        bson::doc! {"$lookup": {
+            "from": UserGroup::collection_name(),
+            "localField": "user.primary_group_id",
+            "foreignField": "id",
+            "as": "groups",
+        }},
+        // users without a primary group leave it out
+        bson::doc! {"$project": {
+            "_id": 0,
+            "member": {"user": "$user", "primary_group": {"$first": "$groups"}},
+            "post_count": 1,
+            "first_post_id": 1,
+        }},
+    ];
+    let mut participants = vec![];
+    let mut cursor = Post::collection(&db)
+        .aggregate(pipeline)
+        .with_type::<Participant>()
+        .await?;
+    while cursor.advance().await? {
+        participants.push(cursor.deserialize_current()?);
+    }
+
+    Ok(participants)
+}
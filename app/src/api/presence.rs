@@ -0,0 +1,142 @@
+//! Who's online: when each visitor was last seen
+//!
+//! Every request is recorded by the [`track`] middleware in the in-memory [`Presence`],
+//! keyed by the [`Session`][super::user::Session] token for logged-in users
+//! and by IP address for guests.
+//! Nothing is persisted, so after a restart everyone is offline until their next request
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, bson, helper,
+    user::{Session, User},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Visitors seen within this many minutes count as online
+pub const ONLINE_WINDOW_MINUTES: i64 = 5;
+
+/// Who was active within the last [`ONLINE_WINDOW_MINUTES`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct OnlineUsers {
+    /// Names of the logged-in users, sorted
+    pub names: Vec<String>,
+    /// Visitors that aren't logged in
+    pub guest_count: u32,
+}
+
+/// Someone making requests
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Visitor {
+    /// Logged in with this [`Session`] token
+    Session(String),
+    /// Not logged in, with this IP address
+    Guest(String),
+}
+
+/// When each visitor was last seen, shared by all requests, cheap to clone
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug, Default)]
+pub struct Presence {
+    last_seen:
+        std::sync::Arc<std::sync::RwLock<std::collections::HashMap<Visitor, jiff::Timestamp>>>,
+}
+#[cfg(feature = "ssr")]
+impl Presence {
+    /// Start of the window visitors count as online in
+    fn window_start(now: jiff::Timestamp) -> jiff::Timestamp {
+        now - jiff::SignedDuration::from_mins(ONLINE_WINDOW_MINUTES)
+    }
+
+    /// Marks `visitor` as seen `now`, forgetting everyone who's offline
+    /// whenever a new visitor shows up so the map doesn't grow forever
+    fn record(&self, visitor: Visitor, now: jiff::Timestamp) {
+        // the map is always left in a valid state, so poisoning doesn't matter
+        let mut last_seen = self
+            .last_seen
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if last_seen.insert(visitor, now).is_none() {
+            let start = Self::window_start(now);
+            last_seen.retain(|_, seen| *seen >= start);
+        }
+    }
+
+    /// Returns the [`Session`] tokens of the online logged-in visitors
+    /// and the amount of online guests
+    fn online(&self, now: jiff::Timestamp) -> (Vec<String>, u32) {
+        let start = Self::window_start(now);
+        let last_seen = self
+            .last_seen
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut tokens = vec![];
+        let mut guest_count = 0;
+        for (visitor, seen) in last_seen.iter() {
+            if *seen < start {
+                continue;
+            }
+            match visitor {
+                Visitor::Session(token) => tokens.push(token.clone()),
+                Visitor::Guest(_) => guest_count += 1,
+            }
+        }
+        (tokens, guest_count)
+    }
+}
+
+/// Middleware recording every request in the [`Presence`]
+///
+/// Requests without a session cookie or IP address aren't recorded
+#[cfg(feature = "ssr")]
+pub async fn track(
+    axum::Extension(presence): axum::Extension<Presence>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let headers = req.headers();
+    let visitor = helper::session_token_from_headers(headers)
+        .map(Visitor::Session)
+        .or_else(|| helper::client_ip_of(headers, req.extensions()).map(Visitor::Guest));
+    if let Some(visitor) = visitor {
+        presence.record(visitor, jiff::Timestamp::now());
+    }
+    next.run(req).await
+}
+
+/// Returns the [`OnlineUsers`] active within the last [`ONLINE_WINDOW_MINUTES`]
+///
+/// Users logged in on several devices are only listed once.
+/// Sessions that don't exist anymore, e.g. after logging out, are left out
+#[server]
+pub async fn get_online_users() -> Result<OnlineUsers, ApiError> {
+    let db = helper::get_db()?;
+    let Some(presence) = use_context::<Presence>() else {
+        return Ok(OnlineUsers::default());
+    };
+    let (tokens, guest_count) = presence.online(jiff::Timestamp::now());
+
+    let mut user_ids = vec![];
+    let mut cursor = Session::collection(&db)
+        .find(bson::doc! {"token": {"$in": tokens}})
+        .await?;
+    while cursor.advance().await? {
+        let session: Session = cursor.deserialize_current()?;
+        user_ids.push(session.user_id);
+    }
+
+    let mut names = vec![];
+    let mut cursor = User::collection(&db)
+        .find(bson::doc! {"id": {"$in": user_ids}})
+        .sort(bson::doc! {"name": 1})
+        .await?;
+    while cursor.advance().await? {
+        let user: User = cursor.deserialize_current()?;
+        names.push(user.name);
+    }
+
+    Ok(OnlineUsers { names, guest_count })
+}
@@ -0,0 +1,178 @@
+//! Secret links showing a [`ScheduledPost`] before it's published,
+//! e.g. so co-organizers can review an announcement
+//!
+//! Anyone with the link can see the post without logging in until the [`PreviewLink`] expires.
+//! Links stop working as soon as the post is published or cancelled
+
+use super::{ApiError, CollectionName, scheduling::ScheduledTarget};
+#[cfg(feature = "ssr")]
+use super::{
+    Forum, GetCollection, Thread, bson, helper, scheduling::ScheduledPost, user::Role,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How long a [`PreviewLink`] works if nothing else is chosen
+pub const DEFAULT_PREVIEW_HOURS: u32 = 72;
+/// Longest a [`PreviewLink`] can work, 30 days
+pub const MAX_PREVIEW_HOURS: u32 = 30 * 24;
+
+/// Path of the preview page of a [`PreviewLink`], e.g. `/preview/3f2a...`
+#[must_use]
+pub fn preview_path(token: &str) -> String {
+    format!("/preview/{token}")
+}
+
+/// A secret link to a [`ScheduledPost`], created by staff
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PreviewLink {
+    /// Random, see [`helper::random_token`]
+    pub token: String,
+    pub scheduled_id: u32,
+    pub created_by: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub expires_at: jiff::Timestamp,
+}
+impl CollectionName for PreviewLink {
+    fn collection_name() -> &'static str {
+        "preview_links"
+    }
+}
+
+/// What's shown to someone opening a [`PreviewLink`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Preview {
+    pub target: ScheduledTarget,
+    /// Name of the [`Forum`][super::Forum] of a new thread
+    /// or the subject of the [`Thread`][super::Thread] of a reply
+    pub target_name: String,
+    pub author_name: String,
+    pub content: String,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub publish_at: jiff::Timestamp,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub expires_at: jiff::Timestamp,
+}
+
+/// Creates a [`PreviewLink`] to a [`ScheduledPost`] valid for `valid_hours`,
+/// [`DEFAULT_PREVIEW_HOURS`] if [`None`], and returns its url.
+/// The url is absolute if the `PUBLIC_URL` env var is set
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `scheduled_id` isn't in use, e.g. it's already published
+/// - [`ApiError::InvalidPreviewDuration`] if `valid_hours` is `0` or above [`MAX_PREVIEW_HOURS`]
+#[server]
+pub async fn create_preview_link(
+    scheduled_id: u32,
+    valid_hours: Option<u32>,
+) -> Result<String, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_role(&db, Role::Moderator).await?;
+
+    let valid_hours = valid_hours.unwrap_or(DEFAULT_PREVIEW_HOURS);
+    if !(1..=MAX_PREVIEW_HOURS).contains(&valid_hours) {
+        return Err(ApiError::InvalidPreviewDuration);
+    }
+    ScheduledPost::collection(&db)
+        .find_one(bson::doc! {"id": scheduled_id})
+        .await?
+        .ok_or(ApiError::NotFound("scheduled post".into(), scheduled_id))?;
+
+    let now = jiff::Timestamp::now();
+    let link_col = PreviewLink::collection(&db);
+    // nobody can use expired links anymore, so this is a good time to forget them
+    link_col
+        .delete_many(bson::doc! {
+            "expires_at": {"$lte": bson::DateTime::from_millis(now.as_millisecond())},
+        })
+        .await?;
+    let link = PreviewLink {
+        token: helper::random_token(),
+        scheduled_id,
+        created_by: user.id,
+        expires_at: now + jiff::SignedDuration::from_hours(i64::from(valid_hours)),
+    };
+    link_col.insert_one(&link).await?;
+
+    Ok(format!(
+        "{}{}",
+        helper::public_url().unwrap_or_default(),
+        preview_path(&link.token)
+    ))
+}
+
+/// Invalidates all [`PreviewLinks`][PreviewLink] to a [`ScheduledPost`]
+///
+/// Requires [`Role::Moderator`]
+#[server]
+pub async fn revoke_preview_links(scheduled_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+    delete_links_of(scheduled_id, &db).await
+}
+
+/// Deletes all [`PreviewLinks`][PreviewLink] to the [`ScheduledPost`] with `scheduled_id`,
+/// e.g. after it got published
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn delete_links_of(scheduled_id: u32, db: &mongodb::Database) -> Result<(), ApiError> {
+    PreviewLink::collection(db)
+        .delete_many(bson::doc! {"scheduled_id": scheduled_id})
+        .await?;
+    Ok(())
+}
+
+/// Returns the [`Preview`] of the [`ScheduledPost`] the [`PreviewLink`] with `token` leads to
+///
+/// Doesn't require being logged in, the token is the permission
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidPreviewLink`] if the link doesn't exist, expired
+///   or its post is already published or cancelled
+#[server]
+pub async fn get_preview(token: String) -> Result<Preview, ApiError> {
+    let db = helper::get_db()?;
+
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    let link = PreviewLink::collection(&db)
+        .find_one(bson::doc! {"token": token, "expires_at": {"$gt": now}})
+        .await?
+        .ok_or(ApiError::InvalidPreviewLink)?;
+    let scheduled = ScheduledPost::collection(&db)
+        .find_one(bson::doc! {"id": link.scheduled_id})
+        .await?
+        .ok_or(ApiError::InvalidPreviewLink)?;
+
+    let target_name = match &scheduled.target {
+        ScheduledTarget::Thread { forum_id, .. } => Forum::collection(&db)
+            .find_one(bson::doc! {"id": forum_id})
+            .await?
+            .map(|forum| forum.name),
+        ScheduledTarget::Reply { thread_id } => Thread::collection(&db)
+            .find_one(bson::doc! {"id": thread_id})
+            .await?
+            .map(|thread| thread.display_subject()),
+    };
+    let author_name = match helper::get_user(scheduled.author_id, &db).await {
+        Ok(author) => author.name,
+        Err(ApiError::NotFound(..)) => "Deleted user".to_string(),
+        Err(err) => return Err(err),
+    };
+
+    Ok(Preview {
+        target: scheduled.target,
+        target_name: target_name.unwrap_or_default(),
+        author_name,
+        content: scheduled.content,
+        publish_at: scheduled.publish_at,
+        expires_at: link.expires_at,
+    })
+}
@@ -0,0 +1,201 @@
+//! Public profiles of members: the posts they wrote and the threads they started,
+//! a page at a time
+//!
+//! Like other listings spanning several forums, only [public listings][Listing::Public]
+//! are drawn from, so a profile doesn't reveal activity in forums the reader can't see
+//! or in staff-only ones
+
+use super::{ApiError, Post, Thread};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, bson, helper,
+    site_settings::GuestCapability,
+    user::User,
+    visibility::{self, Listing},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How many posts or threads a page of a profile shows
+pub const PROFILE_ITEMS_PER_PAGE: u64 = 10;
+
+/// A page of the posts or threads of a member, see [`get_posts_by_user`]
+/// and [`get_threads_by_user`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProfilePage<T> {
+    pub items: Vec<T>,
+    /// Starting at 1
+    pub page: u64,
+    /// Amount of items on all pages
+    pub total: u64,
+}
+impl<T> ProfilePage<T> {
+    /// Amount of pages, at least 1 so an empty list still has a page
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::profiles::{PROFILE_ITEMS_PER_PAGE, ProfilePage};
+    ///
+    /// let page = |total| ProfilePage::<u32> { items: vec![], page: 1, total };
+    /// assert_eq!(page(0).page_count(), 1);
+    /// assert_eq!(page(PROFILE_ITEMS_PER_PAGE).page_count(), 1);
+    /// assert_eq!(page(PROFILE_ITEMS_PER_PAGE + 1).page_count(), 2);
+    /// ```
+    #[must_use]
+    pub fn page_count(&self) -> u64 {
+        self.total.div_ceil(PROFILE_ITEMS_PER_PAGE).max(1)
+    }
+}
+
+/// A [`Post`] with the subject of its [`Thread`], so it can be listed on its own
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PostInThread {
+    pub post: Post,
+    /// See [`Thread::display_subject`]
+    pub thread_subject: String,
+}
+
+/// Ids of the threads in forums that don't belong in [public listings][Listing::Public]
+/// for `user`
+#[cfg(feature = "ssr")]
+async fn unlisted_thread_ids(
+    user: Option<&User>,
+    db: &mongodb::Database,
+) -> Result<Vec<u32>, ApiError> {
+    let forums = helper::get_all_forums(db).await?;
+    let unlisted_forum_ids: Vec<u32> = forums
+        .iter()
+        .filter(|forum| !visibility::is_listed(&forums, forum.id, user, Listing::Public))
+        .map(|forum| forum.id)
+        .collect();
+    if unlisted_forum_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut thread_ids = vec![];
+    let mut cursor = Thread::collection(db)
+        .find(bson::doc! {"forum_id": {"$in": unlisted_forum_ids}})
+        .await?;
+    while cursor.advance().await? {
+        thread_ids.push(cursor.deserialize_current()?.id);
+    }
+    Ok(thread_ids)
+}
+
+/// Returns the `page` (starting at 1) of the [`Posts`][Post] written by the member with
+/// `user_id`, newest first, [`PROFILE_ITEMS_PER_PAGE`] at a time
+///
+/// Posts the logged-in [`User`][super::user::User] can't see, e.g. held ones
+/// or ones in hidden forums, are left out
+///
+/// # Errors
+///
+/// - [`ApiError::LoginRequired`] if guests may not see profiles or threads
+/// - [`ApiError::NotFound`] if `user_id` isn't in use
+#[server]
+pub async fn get_posts_by_user(
+    user_id: u32,
+    page: u64,
+) -> Result<ProfilePage<PostInThread>, ApiError> {
+    let db = helper::get_db()?;
+    let reader = helper::get_reader(GuestCapability::Profiles, &db).await?;
+    helper::ensure_guest_can(GuestCapability::Threads, reader.as_ref(), &db).await?;
+    let author = helper::get_user(user_id, &db).await?;
+    let page = page.max(1);
+
+    let mut filter = bson::doc! {
+        "author_id": author.id,
+        "pending_review": {"$ne": true},
+        "deleted_at": null,
+        "thread_deleted": {"$ne": true},
+    };
+    let unlisted_thread_ids = unlisted_thread_ids(reader.as_ref(), &db).await?;
+    if !unlisted_thread_ids.is_empty() {
+        filter.insert("thread_id", bson::doc! {"$nin": unlisted_thread_ids});
+    }
+
+    let total = Post::collection(&db)
+        .count_documents(filter.clone())
+        .await?;
+    let mut posts: Vec<Post> = vec![];
+    let mut cursor = Post::collection(&db)
+        .find(filter)
+        .sort(bson::doc! {"id": -1})
+        .skip((page - 1).saturating_mul(PROFILE_ITEMS_PER_PAGE))
+        .limit(i64::try_from(PROFILE_ITEMS_PER_PAGE).unwrap_or(i64::MAX))
+        .await?;
+    while cursor.advance().await? {
+        posts.push(cursor.deserialize_current()?);
+    }
+
+    let thread_ids: Vec<u32> = posts.iter().map(|post| post.thread_id).collect();
+    let mut threads: Vec<Thread> = vec![];
+    let mut cursor = Thread::collection(&db)
+        .find(bson::doc! {"id": {"$in": thread_ids}})
+        .await?;
+    while cursor.advance().await? {
+        threads.push(cursor.deserialize_current()?);
+    }
+    let items = posts
+        .into_iter()
+        .map(|post| {
+            let thread_subject = threads
+                .iter()
+                .find(|thread| thread.id == post.thread_id)
+                .map(Thread::display_subject)
+                .unwrap_or_default();
+            PostInThread {
+                post,
+                thread_subject,
+            }
+        })
+        .collect();
+
+    Ok(ProfilePage { items, page, total })
+}
+
+/// Returns the `page` (starting at 1) of the [`Threads`][Thread] started by the member with
+/// `user_id`, newest first, [`PROFILE_ITEMS_PER_PAGE`] at a time
+///
+/// Threads the logged-in [`User`][super::user::User] can't see, e.g. deleted ones
+/// or ones in hidden forums, are left out
+///
+/// # Errors
+///
+/// - [`ApiError::LoginRequired`] if guests may not see profiles or threads
+/// - [`ApiError::NotFound`] if `user_id` isn't in use
+#[server]
+pub async fn get_threads_by_user(user_id: u32, page: u64) -> Result<ProfilePage<Thread>, ApiError> {
+    let db = helper::get_db()?;
+    let reader = helper::get_reader(GuestCapability::Profiles, &db).await?;
+    helper::ensure_guest_can(GuestCapability::Threads, reader.as_ref(), &db).await?;
+    let author = helper::get_user(user_id, &db).await?;
+    let page = page.max(1);
+
+    let forums = helper::get_all_forums(&db).await?;
+    let forum_ids = visibility::listed_forum_ids(&forums, reader.as_ref(), Listing::Public);
+    let filter = bson::doc! {
+        "author_id": author.id,
+        "forum_id": {"$in": forum_ids},
+        "pending_review": {"$ne": true},
+        "deleted_at": null,
+    };
+
+    let total = Thread::collection(&db)
+        .count_documents(filter.clone())
+        .await?;
+    let mut items = vec![];
+    let mut cursor = Thread::collection(&db)
+        .find(filter)
+        .sort(bson::doc! {"id": -1})
+        .skip((page - 1).saturating_mul(PROFILE_ITEMS_PER_PAGE))
+        .limit(i64::try_from(PROFILE_ITEMS_PER_PAGE).unwrap_or(i64::MAX))
+        .await?;
+    while cursor.advance().await? {
+        items.push(cursor.deserialize_current()?);
+    }
+
+    Ok(ProfilePage { items, page, total })
+}
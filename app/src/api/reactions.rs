@@ -0,0 +1,335 @@
+//! Emoji reactions on [`Posts`][super::Post]
+//!
+//! Which reactions are available is configured by admins as [`ReactionOptions`][ReactionOption]:
+//! their order, custom uploaded images and which of them are enabled.
+//! Options can be marked as negative (e.g. 👎) to disable them all at once
+
+use super::attachments::AttachmentRef;
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    Counter, GetCollection, Post, attachments::Attachment, bson, helper,
+    site_settings::GuestCapability, user::Role,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A reaction users can pick for a [`Post`][super::Post]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReactionOption {
+    pub id: u32,
+    /// The emoji itself, or the name of the [`image`][ReactionOption::image]
+    pub emoji: String,
+    /// Custom uploaded image shown instead of the emoji
+    #[serde(default)]
+    pub image: Option<AttachmentRef>,
+    /// Options are shown by ascending order
+    pub order: u32,
+    /// e.g. 👎, so that these can be disabled all at once
+    #[serde(default)]
+    pub negative: bool,
+    /// Disabled options can't be picked and their reactions are hidden
+    pub enabled: bool,
+}
+impl CollectionName for ReactionOption {
+    fn collection_name() -> &'static str {
+        "reaction_options"
+    }
+}
+
+/// Records that a [`User`][super::user::User] reacted to a [`Post`][super::Post]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Reaction {
+    pub post_id: u32,
+    pub user_id: u32,
+    pub option_id: u32,
+}
+impl CollectionName for Reaction {
+    fn collection_name() -> &'static str {
+        "reactions"
+    }
+}
+
+/// How often a [`Post`][super::Post] got a [`ReactionOption`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReactionCount {
+    pub option_id: u32,
+    pub count: u32,
+    /// Whether the logged-in user is one of them
+    pub reacted: bool,
+}
+
+/// The [`ReactionCounts`][ReactionCount] of a single [`Post`][super::Post]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PostReactions {
+    pub post_id: u32,
+    pub counts: Vec<ReactionCount>,
+}
+
+/// Loads the [`ReactionOptions`][ReactionOption] sorted by their order,
+/// only the enabled ones unless `include_disabled`
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn load_options(
+    include_disabled: bool,
+    db: &mongodb::Database,
+) -> Result<Vec<ReactionOption>, ApiError> {
+    let filter = if include_disabled {
+        bson::doc! {}
+    } else {
+        bson::doc! {"enabled": true}
+    };
+    let mut options = vec![];
+    let mut cursor = ReactionOption::collection(db)
+        .find(filter)
+        .sort(bson::doc! {"order": 1, "id": 1})
+        .await?;
+    while cursor.advance().await? {
+        options.push(cursor.deserialize_current()?);
+    }
+    Ok(options)
+}
+
+/// Fetches the enabled [`ReactionOptions`][ReactionOption] for the reaction picker
+#[server]
+pub async fn get_reaction_options() -> Result<Vec<ReactionOption>, ApiError> {
+    let db = helper::get_db()?;
+    load_options(false, &db).await
+}
+
+/// Fetches all [`ReactionOptions`][ReactionOption], including disabled ones
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_all_reaction_options() -> Result<Vec<ReactionOption>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+    load_options(true, &db).await
+}
+
+/// Adds a new enabled [`ReactionOption`] at the end and returns its id
+///
+/// If `image_attachment_id` is given, the uploaded image is shown instead of `emoji`,
+/// which then is its name
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptyContent`] if `emoji` is empty
+/// - [`ApiError::NotFound`] if the attachment doesn't exist or isn't an image
+#[server]
+pub async fn add_reaction_option(
+    emoji: String,
+    #[server(default)] negative: bool,
+    image_attachment_id: Option<u32>,
+) -> Result<u32, ApiError> {
+    let emoji = emoji.trim().to_string();
+    if emoji.is_empty() {
+        return Err(ApiError::EmptyContent);
+    }
+
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let image = match image_attachment_id {
+        Some(attachment_id) => {
+            let attachment = Attachment::collection(&db)
+                .find_one(bson::doc! {"id": attachment_id})
+                .await?
+                .map(|attachment| attachment.to_ref())
+                .filter(AttachmentRef::is_image);
+            let Some(image) = attachment else {
+                return Err(ApiError::NotFound("image".into(), attachment_id));
+            };
+            Some(image)
+        }
+        None => None,
+    };
+
+    let counter_col = Counter::collection(&db);
+    let id = helper::get_and_increment_id_of("reaction_option", counter_col).await?;
+
+    let option = ReactionOption {
+        id,
+        emoji,
+        image,
+        order: id,
+        negative,
+        enabled: true,
+    };
+    ReactionOption::collection(&db).insert_one(&option).await?;
+
+    Ok(id)
+}
+
+/// Changes the order and flags of the [`ReactionOption`] with the given `option_id`
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `option_id` isn't in use
+#[server]
+pub async fn update_reaction_option(
+    option_id: u32,
+    order: u32,
+    #[server(default)] negative: bool,
+    #[server(default)] enabled: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let result = ReactionOption::collection(&db)
+        .update_one(
+            bson::doc! {"id": option_id},
+            bson::doc! {"$set": {"order": order, "negative": negative, "enabled": enabled}},
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(ApiError::NotFound("reaction".into(), option_id));
+    }
+
+    Ok(())
+}
+
+/// Enables or disables all negative [`ReactionOptions`][ReactionOption] at once
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn set_negative_reactions_enabled(enabled: bool) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    ReactionOption::collection(&db)
+        .update_many(
+            bson::doc! {"negative": true},
+            bson::doc! {"$set": {"enabled": enabled}},
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes the [`ReactionOption`] with the given `option_id` and all [`Reactions`][Reaction] with it
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn delete_reaction_option(option_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    ReactionOption::collection(&db)
+        .delete_one(bson::doc! {"id": option_id})
+        .await?;
+    Reaction::collection(&db)
+        .delete_many(bson::doc! {"option_id": option_id})
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the [`PostReactions`] of every [`Post`] in the given [`Thread`][super::Thread]
+/// that has any, counting only enabled [`ReactionOptions`][ReactionOption]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use or the thread isn't visible,
+///   see [`helper::get_visible_thread`][super::helper::get_visible_thread]
+#[server]
+pub async fn get_thread_reactions(thread_id: u32) -> Result<Vec<PostReactions>, ApiError> {
+    use std::collections::BTreeMap;
+
+    let db = helper::get_db()?;
+    // not being logged in is fine, just nothing is marked as reacted then
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    helper::get_visible_thread(thread_id, user.as_ref(), &db).await?;
+    let user_id = user.map(|user| user.id);
+
+    let mut post_ids = vec![];
+    let mut cursor = Post::collection(&db)
+        .find(bson::doc! {
+            "thread_id": thread_id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .await?;
+    while cursor.advance().await? {
+        let post: Post = cursor.deserialize_current()?;
+        post_ids.push(post.id);
+    }
+    let option_ids: Vec<u32> = load_options(false, &db)
+        .await?
+        .into_iter()
+        .map(|option| option.id)
+        .collect();
+
+    let mut counts: BTreeMap<u32, BTreeMap<u32, ReactionCount>> = BTreeMap::new();
+    let mut cursor = Reaction::collection(&db)
+        .find(bson::doc! {"post_id": {"$in": post_ids}, "option_id": {"$in": option_ids}})
+        .await?;
+    while cursor.advance().await? {
+        let reaction: Reaction = cursor.deserialize_current()?;
+        let count = counts
+            .entry(reaction.post_id)
+            .or_default()
+            .entry(reaction.option_id)
+            .or_insert(ReactionCount {
+                option_id: reaction.option_id,
+                count: 0,
+                reacted: false,
+            });
+        count.count += 1;
+        count.reacted |= Some(reaction.user_id) == user_id;
+    }
+
+    Ok(counts
+        .into_iter()
+        .map(|(post_id, counts)| PostReactions {
+            post_id,
+            counts: counts.into_values().collect(),
+        })
+        .collect())
+}
+
+/// Adds the logged-in [`User`][super::user::User]'s reaction with the given option to the
+/// [`Post`], or removes it if they already reacted with it
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::UnknownReaction`] if the option doesn't exist or is disabled
+/// - [`ApiError::NotFound`] if `post_id` isn't in use
+#[server]
+pub async fn toggle_reaction(post_id: u32, option_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    if ReactionOption::collection(&db)
+        .find_one(bson::doc! {"id": option_id, "enabled": true})
+        .await?
+        .is_none()
+    {
+        return Err(ApiError::UnknownReaction(option_id));
+    }
+    let post = helper::get_post(post_id, db.clone()).await?;
+
+    let reaction_col = Reaction::collection(&db);
+    let filter = bson::doc! {"post_id": post.id, "user_id": user.id, "option_id": option_id};
+    let removed = reaction_col.delete_one(filter).await?;
+    if removed.deleted_count == 0 {
+        reaction_col
+            .insert_one(Reaction {
+                post_id: post.id,
+                user_id: user.id,
+                option_id,
+            })
+            .await?;
+    }
+
+    Ok(())
+}
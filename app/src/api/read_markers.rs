@@ -0,0 +1,123 @@
+//! Per-user read markers of [`Threads`][Thread], used for showing which threads
+//! have unread [`Posts`][super::Post] and jumping to the first unread one
+//!
+//! A thread the [`User`][super::user::User] never opened has no marker
+//! and counts as unread as a whole
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, Post, Thread, bson, helper};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Up to which [`Post`][super::Post] a [`User`][super::user::User] has read a [`Thread`][super::Thread]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReadMarker {
+    pub user_id: u32,
+    pub thread_id: u32,
+    /// Posts with a higher id are unread
+    pub last_read_post_id: u32,
+}
+impl CollectionName for ReadMarker {
+    fn collection_name() -> &'static str {
+        "read_markers"
+    }
+}
+
+/// Moves the [`ReadMarker`] of `user_id` in `thread_id` forward to `post_id`,
+/// never backwards
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn mark_read(
+    user_id: u32,
+    thread_id: u32,
+    post_id: u32,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    ReadMarker::collection(db)
+        .update_one(
+            bson::doc! {"user_id": user_id, "thread_id": thread_id},
+            bson::doc! {"$max": {"last_read_post_id": post_id}},
+        )
+        .upsert(true)
+        .await?;
+    Ok(())
+}
+
+/// Marks the whole [`Thread`] as read by the logged-in [`User`][super::user::User]
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use
+#[server]
+pub async fn mark_thread_read(thread_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    mark_read(user.id, thread.id, thread.latest_post_id, &db).await
+}
+
+/// Returns the [`ReadMarkers`][ReadMarker] of the logged-in [`User`][super::user::User]
+/// for the [`Threads`][Thread] of the given [`Forum`][super::Forum]
+///
+/// [`None`] if not logged in
+#[server]
+pub async fn get_read_markers(forum_id: u32) -> Result<Option<Vec<ReadMarker>>, ApiError> {
+    let db = helper::get_db()?;
+    let Some(user) = helper::get_current_user(&db).await? else {
+        return Ok(None);
+    };
+
+    let mut thread_ids = vec![];
+    let mut cursor = Thread::collection(&db)
+        .find(bson::doc! {"forum_id": forum_id})
+        .await?;
+    while cursor.advance().await? {
+        let thread: Thread = cursor.deserialize_current()?;
+        thread_ids.push(thread.id);
+    }
+
+    let mut markers = vec![];
+    let mut cursor = ReadMarker::collection(&db)
+        .find(bson::doc! {"user_id": user.id, "thread_id": {"$in": thread_ids}})
+        .await?;
+    while cursor.advance().await? {
+        markers.push(cursor.deserialize_current()?);
+    }
+    Ok(Some(markers))
+}
+
+/// Returns the id of the first [`Post`] in the [`Thread`] the logged-in
+/// [`User`][super::user::User] hasn't read yet
+///
+/// [`None`] if not logged in, everything is read or the thread was never opened,
+/// since it's read from the start then anyway
+#[server]
+pub async fn get_first_unread_post(thread_id: u32) -> Result<Option<u32>, ApiError> {
+    let db = helper::get_db()?;
+    let Some(user) = helper::get_current_user(&db).await? else {
+        return Ok(None);
+    };
+    let Some(marker) = ReadMarker::collection(&db)
+        .find_one(bson::doc! {"user_id": user.id, "thread_id": thread_id})
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    let first_unread = Post::collection(&db)
+        .find_one(bson::doc! {
+            "thread_id": thread_id,
+            "id": {"$gt": marker.last_read_post_id},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    Ok(first_unread.map(|post| post.id))
+}
@@ -0,0 +1,485 @@
+//! Versioned JSON API for bots and third-party clients that don't speak the server function protocol
+//!
+//! Mounted at `/api/v1` by [`router`]. Every request needs an [`ApiKey`][super::api_keys::ApiKey]
+//! and acts as its owner, so it sees and may do the same as them in the browser.
+//! Lists are paginated with the `page` (starting at 1) and `per_page` query parameters
+//! and wrapped in a [`Page`], which also states the
+//! [`ContentLicense`][super::site_settings::ContentLicense] of the posts.
+//! Errors are responded with a fitting status and an [`ErrorBody`]
+//!
+//! | Route                              | Response                       |
+//! |------------------------------------|--------------------------------|
+//! | `GET /forums`                      | all visible [`ApiForums`][ApiForum] |
+//! | `GET /forums/{id}/threads`         | [`Page`] of [`ApiThreads`][ApiThread], newest first |
+//! | `POST /forums/{id}/threads`        | [`Created`] from a [`NewThread`] |
+//! | `GET /threads/{id}`                | [`ApiThread`]                  |
+//! | `GET /threads/{id}/posts`          | [`Page`] of [`ApiPosts`][ApiPost], oldest first |
+//! | `POST /threads/{id}/posts`         | [`Created`] from a [`NewPost`] |
+//! | `GET /search?q=`                   | [`Page`] of [`ApiThreads`][ApiThread] whose subject contains `q` |
+
+#[cfg(feature = "ssr")]
+use super::{
+    ApiError, Forum, GetCollection, Post, Thread, api_keys, bson, cache, helper, site_settings,
+    user::User,
+    visibility::{self, Listing},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Items per page if `per_page` isn't given
+pub const DEFAULT_PER_PAGE: u64 = 20;
+/// Maximum items per page
+pub const MAX_PER_PAGE: u64 = 100;
+
+/// A page of a paginated list
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Starting at 1
+    pub page: u64,
+    pub per_page: u64,
+    /// Number of items on all pages
+    pub total: u64,
+    /// What reusing the posts is subject to, [`None`] if no license is stated
+    pub license: Option<super::site_settings::ContentLicense>,
+}
+
+/// Pagination query parameters
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct Pagination {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+impl Pagination {
+    /// The requested page, at least 1
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::rest::Pagination;
+    ///
+    /// assert_eq!(Pagination { page: Some(0), per_page: None }.page(), 1);
+    /// assert_eq!(Pagination { page: Some(3), per_page: None }.page(), 3);
+    /// ```
+    #[must_use]
+    pub fn page(self) -> u64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// The requested items per page, between 1 and [`MAX_PER_PAGE`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::rest::{DEFAULT_PER_PAGE, MAX_PER_PAGE, Pagination};
+    ///
+    /// assert_eq!(Pagination::default().per_page(), DEFAULT_PER_PAGE);
+    /// assert_eq!(Pagination { page: None, per_page: Some(1000) }.per_page(), MAX_PER_PAGE);
+    /// ```
+    #[must_use]
+    pub fn per_page(self) -> u64 {
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
+    }
+
+    /// Number of items on the pages before the requested one
+    #[must_use]
+    pub fn skip(self) -> u64 {
+        (self.page() - 1).saturating_mul(self.per_page())
+    }
+}
+
+/// A [`Forum`][super::Forum] as returned by the API
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiForum {
+    pub id: u32,
+    pub name: String,
+    pub category: String,
+    pub description: Option<String>,
+    pub parent_forum_id: Option<u32>,
+    pub read_only: bool,
+}
+
+/// A [`Thread`][super::Thread] as returned by the API
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiThread {
+    pub id: u32,
+    pub forum_id: u32,
+    /// Including the prefix, see [`Thread::display_subject`][super::Thread::display_subject]
+    pub subject: String,
+    pub author_id: Option<u32>,
+    pub origin_post_id: u32,
+    pub latest_post_id: u32,
+    pub locked: bool,
+    pub solved_at: Option<jiff::Timestamp>,
+    pub tags: Vec<String>,
+}
+
+/// A [`Post`][super::Post] as returned by the API, with its content in Markdown
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiPost {
+    pub id: u32,
+    pub thread_id: u32,
+    pub author_id: Option<u32>,
+    pub content: String,
+    pub created_at: jiff::Timestamp,
+    pub edited_at: Option<jiff::Timestamp>,
+}
+
+/// Body of `POST /forums/{id}/threads`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewThread {
+    pub subject: String,
+    /// Markdown content of the origin post
+    pub content: String,
+}
+
+/// Body of `POST /threads/{id}/posts`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NewPost {
+    /// Markdown
+    pub content: String,
+}
+
+/// Response of the `POST` routes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Created {
+    pub id: u32,
+}
+
+/// Query of `GET /search`, paginated like [`Pagination`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchQuery {
+    pub q: String,
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+/// Response of failed requests
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorBody {
+    pub error: String,
+}
+
+#[cfg(feature = "ssr")]
+impl ApiThread {
+    fn new(thread: &Thread) -> Self {
+        Self {
+            id: thread.id,
+            forum_id: thread.forum_id,
+            subject: thread.display_subject(),
+            author_id: thread.author_id,
+            origin_post_id: thread.origin_post_id,
+            latest_post_id: thread.latest_post_id,
+            locked: thread.locked,
+            solved_at: thread.solved_at,
+            tags: thread.tags.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl From<Post> for ApiPost {
+    fn from(post: Post) -> Self {
+        Self {
+            id: post.id,
+            thread_id: post.thread_id,
+            author_id: post.author_id,
+            content: post.content,
+            created_at: post.created_at,
+            edited_at: post.edited_at,
+        }
+    }
+}
+
+/// An [`ApiError`] responded as JSON with a fitting status
+#[cfg(feature = "ssr")]
+struct RestError(ApiError);
+#[cfg(feature = "ssr")]
+impl From<ApiError> for RestError {
+    fn from(err: ApiError) -> Self {
+        Self(err)
+    }
+}
+#[cfg(feature = "ssr")]
+impl From<mongodb::error::Error> for RestError {
+    fn from(err: mongodb::error::Error) -> Self {
+        Self(err.into())
+    }
+}
+#[cfg(feature = "ssr")]
+impl axum::response::IntoResponse for RestError {
+    fn into_response(self) -> axum::response::Response {
+        use http::StatusCode;
+
+        let status = match self.0 {
+            ApiError::InvalidApiKey | ApiError::NotLoggedIn | ApiError::LoginRequired => {
+                StatusCode::UNAUTHORIZED
+            }
+            ApiError::Forbidden
+            | ApiError::Banned { .. }
+            | ApiError::Muted { .. }
+            | ApiError::ForumReadOnly
+            | ApiError::RulesNotAcknowledged
+            | ApiError::PostingRequirementsNotMet { .. } => StatusCode::FORBIDDEN,
+            ApiError::ThreadCapReached { .. } | ApiError::PostCapReached { .. } => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            ApiError::NotFound(..) => StatusCode::NOT_FOUND,
+            // created, but not visible yet
+            ApiError::HeldForReview => StatusCode::ACCEPTED,
+            ApiError::Db { .. } | ApiError::Storage(_) => {
+                tracing::warn!("JSON API request failed: {}", self.0);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        };
+        let body = ErrorBody {
+            error: self.0.to_string(),
+        };
+        (status, axum::Json(body)).into_response()
+    }
+}
+
+#[cfg(feature = "ssr")]
+type RestResult<T> = Result<axum::Json<T>, RestError>;
+
+/// Returns the [`Forums`][Forum] `user` may see, without staff-only ones,
+/// see [`visibility::is_listed`]
+#[cfg(feature = "ssr")]
+async fn visible_forums(
+    user: &User,
+    db: &mongodb::Database,
+) -> Result<Vec<(Forum, String)>, ApiError> {
+    let forums = helper::get_all_forums(db).await?;
+    let mut visible = vec![];
+    let mut cursor = super::Category::collection(db).find(bson::doc! {}).await?;
+    while cursor.advance().await? {
+        let category: super::Category = cursor.deserialize_current()?;
+        for forum in category.forums {
+            if visibility::is_listed(&forums, forum.id, Some(user), Listing::Public) {
+                visible.push((forum, category.name.clone()));
+            }
+        }
+    }
+    Ok(visible)
+}
+
+/// Returns a [`Page`] of the threads matching `filter`, newest first
+#[cfg(feature = "ssr")]
+async fn thread_page(
+    mut filter: bson::Document,
+    pagination: Pagination,
+    db: &mongodb::Database,
+) -> Result<Page<ApiThread>, ApiError> {
+    filter.insert("pending_review", bson::doc! {"$ne": true});
+    filter.insert("deleted_at", bson::Bson::Null);
+
+    let thread_col = Thread::collection(db);
+    let total = thread_col.count_documents(filter.clone()).await?;
+    let mut items = vec![];
+    let mut cursor = thread_col
+        .find(filter)
+        .sort(bson::doc! {"id": -1})
+        .skip(pagination.skip())
+        .limit(i64::try_from(pagination.per_page()).unwrap_or(i64::MAX))
+        .await?;
+    while cursor.advance().await? {
+        let thread: Thread = cursor.deserialize_current()?;
+        items.push(ApiThread::new(&thread));
+    }
+
+    Ok(Page {
+        items,
+        page: pagination.page(),
+        per_page: pagination.per_page(),
+        total,
+        license: site_settings::load_settings(db).await?.content_license,
+    })
+}
+
+/// Returns the [`Thread`] if `user` may see it
+#[cfg(feature = "ssr")]
+async fn visible_thread(
+    thread_id: u32,
+    user: &User,
+    db: &mongodb::Database,
+) -> Result<Thread, ApiError> {
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if thread.pending_review || thread.deleted_at.is_some() {
+        return Err(ApiError::NotFound("thread".into(), thread_id));
+    }
+    helper::ensure_can_see_forum(thread.forum_id, Some(user), db).await?;
+    Ok(thread)
+}
+
+/// `GET /forums`
+#[cfg(feature = "ssr")]
+async fn list_forums(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+) -> RestResult<Vec<ApiForum>> {
+    let user = api_keys::user_from_headers(&headers, &db).await?;
+    let forums = visible_forums(&user, &db)
+        .await?
+        .into_iter()
+        .map(|(forum, category)| ApiForum {
+            id: forum.id,
+            name: forum.name,
+            category,
+            description: forum.description,
+            parent_forum_id: forum.parent_forum_id,
+            read_only: forum.read_only,
+        })
+        .collect();
+    Ok(axum::Json(forums))
+}
+
+/// `GET /forums/{id}/threads`
+#[cfg(feature = "ssr")]
+async fn list_threads(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    axum::extract::Path(forum_id): axum::extract::Path<u32>,
+    axum::extract::Query(pagination): axum::extract::Query<Pagination>,
+) -> RestResult<Page<ApiThread>> {
+    let user = api_keys::user_from_headers(&headers, &db).await?;
+    helper::ensure_can_see_forum(forum_id, Some(&user), &db).await?;
+    let page = thread_page(bson::doc! {"forum_id": forum_id}, pagination, &db).await?;
+    Ok(axum::Json(page))
+}
+
+/// `POST /forums/{id}/threads`
+#[cfg(feature = "ssr")]
+async fn create_thread(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    axum::Extension(caches): axum::Extension<cache::Caches>,
+    headers: http::HeaderMap,
+    axum::extract::Path(forum_id): axum::extract::Path<u32>,
+    axum::Json(new): axum::Json<NewThread>,
+) -> RestResult<Created> {
+    let user = api_keys::user_from_headers(&headers, &db).await?;
+    helper::ensure_can_see_forum(forum_id, Some(&user), &db).await?;
+    let id =
+        helper::create_thread_as(Some(user), forum_id, new.subject, new.content, &[], &db).await?;
+    // the caches aren't in leptos context for plain axum handlers
+    cache::invalidate_forums_in(&caches, &[forum_id], &db).await?;
+    Ok(axum::Json(Created { id }))
+}
+
+/// `GET /threads/{id}`
+#[cfg(feature = "ssr")]
+async fn get_thread(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    axum::extract::Path(thread_id): axum::extract::Path<u32>,
+) -> RestResult<ApiThread> {
+    let user = api_keys::user_from_headers(&headers, &db).await?;
+    let thread = visible_thread(thread_id, &user, &db).await?;
+    Ok(axum::Json(ApiThread::new(&thread)))
+}
+
+/// `GET /threads/{id}/posts`
+#[cfg(feature = "ssr")]
+async fn list_posts(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    axum::extract::Path(thread_id): axum::extract::Path<u32>,
+    axum::extract::Query(pagination): axum::extract::Query<Pagination>,
+) -> RestResult<Page<ApiPost>> {
+    let user = api_keys::user_from_headers(&headers, &db).await?;
+    let thread = visible_thread(thread_id, &user, &db).await?;
+
+    let post_col = Post::collection(&db);
+    let filter = bson::doc! {
+        "thread_id": thread.id,
+        "pending_review": {"$ne": true},
+        "deleted_at": null,
+    };
+    let total = post_col.count_documents(filter.clone()).await?;
+    let mut items = vec![];
+    let mut cursor = post_col
+        .find(filter)
+        .sort(bson::doc! {"id": 1})
+        .skip(pagination.skip())
+        .limit(i64::try_from(pagination.per_page()).unwrap_or(i64::MAX))
+        .await?;
+    while cursor.advance().await? {
+        let post: Post = cursor.deserialize_current()?;
+        items.push(post.into());
+    }
+
+    Ok(axum::Json(Page {
+        items,
+        page: pagination.page(),
+        per_page: pagination.per_page(),
+        total,
+        license: site_settings::load_settings(&db).await?.content_license,
+    }))
+}
+
+/// `POST /threads/{id}/posts`
+#[cfg(feature = "ssr")]
+async fn create_post(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    axum::Extension(caches): axum::Extension<cache::Caches>,
+    headers: http::HeaderMap,
+    axum::extract::Path(thread_id): axum::extract::Path<u32>,
+    axum::Json(new): axum::Json<NewPost>,
+) -> RestResult<Created> {
+    let user = api_keys::user_from_headers(&headers, &db).await?;
+    let thread = visible_thread(thread_id, &user, &db).await?;
+    let id = helper::create_post_as(Some(user), thread.id, new.content, None, &[], &db).await?;
+    // the caches aren't in leptos context for plain axum handlers
+    cache::invalidate_forums_in(&caches, &[thread.forum_id], &db).await?;
+    Ok(axum::Json(Created { id }))
+}
+
+/// `GET /search`
+#[cfg(feature = "ssr")]
+async fn search(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<SearchQuery>,
+) -> RestResult<Page<ApiThread>> {
+    let user = api_keys::user_from_headers(&headers, &db).await?;
+    let q = query.q.trim();
+    if q.is_empty() {
+        return Err(ApiError::EmptySearchQuery.into());
+    }
+
+    let forum_ids: Vec<u32> = visible_forums(&user, &db)
+        .await?
+        .into_iter()
+        .map(|(forum, _)| forum.id)
+        .collect();
+    let filter = bson::doc! {
+        "forum_id": {"$in": forum_ids},
+        "subject": {"$regex": helper::escape_regex(q), "$options": "i"},
+    };
+    let pagination = Pagination {
+        page: query.page,
+        per_page: query.per_page,
+    };
+    let page = thread_page(filter, pagination, &db).await?;
+    Ok(axum::Json(page))
+}
+
+/// Routes of the JSON API, meant to be nested at `/api/v1`
+///
+/// Needs the [`mongodb::Database`] and [`cache::Caches`] as [`axum::Extension`]s
+#[cfg(feature = "ssr")]
+pub fn router<S: Clone + Send + Sync + 'static>() -> axum::Router<S> {
+    use axum::routing::get;
+
+    axum::Router::new()
+        .route("/forums", get(list_forums))
+        .route(
+            "/forums/{id}/threads",
+            get(list_threads).post(create_thread),
+        )
+        .route("/threads/{id}", get(get_thread))
+        .route("/threads/{id}/posts", get(list_posts).post(create_post))
+        .route("/search", get(search))
+}
@@ -0,0 +1,306 @@
+//! Threads and posts written by staff ahead of time and published later
+//!
+//! Until they go live, [`ScheduledPosts`][ScheduledPost] are kept in their own collection,
+//! so nothing else has to know about them. Only staff can see, edit and cancel them,
+//! others can only see them through [preview links][super::previews].
+//! [`run_publisher`] checks for due ones every [`PUBLISH_INTERVAL_SECS`] and creates them
+//! like any other thread or post of their author
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{Counter, GetCollection, bson, helper, previews, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How often [`run_publisher`] looks for due [`ScheduledPosts`][ScheduledPost]
+pub const PUBLISH_INTERVAL_SECS: u64 = 60;
+
+/// What a [`ScheduledPost`] becomes once published
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ScheduledTarget {
+    /// A new [`Thread`][super::Thread] in the [`Forum`][super::Forum] with this id
+    Thread { forum_id: u32, subject: String },
+    /// A reply in the [`Thread`][super::Thread] with this id
+    Reply { thread_id: u32 },
+}
+
+/// A thread or post waiting to be published
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledPost {
+    pub id: u32,
+    pub author_id: u32,
+    pub target: ScheduledTarget,
+    pub content: String,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub publish_at: jiff::Timestamp,
+    /// Why publishing failed, it's not retried until it's edited
+    #[serde(default)]
+    pub error: Option<String>,
+}
+impl CollectionName for ScheduledPost {
+    fn collection_name() -> &'static str {
+        "scheduled_posts"
+    }
+}
+
+/// Parses the `publish_at` of a form in the user's `time_zone`, see [`crate::time::parse_in_user_tz`]
+///
+/// # Errors
+///
+/// * [`ApiError::InvalidPublishTime`] if it can't be parsed or isn't in the future
+#[cfg(feature = "ssr")]
+fn parse_publish_at(publish_at: &str, time_zone: &str) -> Result<jiff::Timestamp, ApiError> {
+    crate::time::parse_in_user_tz(publish_at, time_zone)
+        .filter(|publish_at| *publish_at > jiff::Timestamp::now())
+        .ok_or_else(|| ApiError::InvalidPublishTime(publish_at.to_string()))
+}
+
+/// Inserts a new [`ScheduledPost`] by `author_id` and returns its id
+#[cfg(feature = "ssr")]
+async fn schedule(
+    author_id: u32,
+    target: ScheduledTarget,
+    content: String,
+    publish_at: &str,
+    time_zone: &str,
+    db: &mongodb::Database,
+) -> Result<u32, ApiError> {
+    if content.trim().is_empty() {
+        return Err(ApiError::EmptyContent);
+    }
+    let publish_at = parse_publish_at(publish_at, time_zone)?;
+
+    let counter_col = Counter::collection(db);
+    let id = helper::get_and_increment_id_of("scheduled_post", counter_col).await?;
+    let scheduled = ScheduledPost {
+        id,
+        author_id,
+        target,
+        content,
+        publish_at,
+        error: None,
+    };
+    ScheduledPost::collection(db).insert_one(&scheduled).await?;
+
+    Ok(id)
+}
+
+/// Schedules a new [`Thread`][super::Thread] in the given [`Forum`][super::Forum]
+/// to be published at `publish_at` (in `time_zone`)
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptySubject`] or [`ApiError::EmptyContent`] if they're empty
+/// - [`ApiError::NotFound`] if `forum_id` isn't in use
+/// - [`ApiError::InvalidPublishTime`] if `publish_at` can't be parsed or isn't in the future
+#[server]
+pub async fn schedule_thread(
+    forum_id: u32,
+    subject: String,
+    content: String,
+    publish_at: String,
+    time_zone: String,
+) -> Result<u32, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_role(&db, Role::Moderator).await?;
+    let _ = helper::get_forum(forum_id, db.clone()).await?;
+
+    let subject = subject.trim().to_string();
+    if subject.is_empty() {
+        return Err(ApiError::EmptySubject);
+    }
+    let target = ScheduledTarget::Thread { forum_id, subject };
+    schedule(user.id, target, content, &publish_at, &time_zone, &db).await
+}
+
+/// Schedules a reply in the given [`Thread`][super::Thread]
+/// to be published at `publish_at` (in `time_zone`)
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptyContent`] if `content` is empty
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use
+/// - [`ApiError::InvalidPublishTime`] if `publish_at` can't be parsed or isn't in the future
+#[server]
+pub async fn schedule_reply(
+    thread_id: u32,
+    content: String,
+    publish_at: String,
+    time_zone: String,
+) -> Result<u32, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_role(&db, Role::Moderator).await?;
+    let _ = helper::get_thread(thread_id, db.clone()).await?;
+
+    let target = ScheduledTarget::Reply { thread_id };
+    schedule(user.id, target, content, &publish_at, &time_zone, &db).await
+}
+
+/// Fetches all [`ScheduledPosts`][ScheduledPost] of all staff, the next one first
+///
+/// Requires [`Role::Moderator`]
+#[server]
+pub async fn get_scheduled_posts() -> Result<Vec<ScheduledPost>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    let mut scheduled = vec![];
+    let mut cursor = ScheduledPost::collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"publish_at": 1})
+        .await?;
+    while cursor.advance().await? {
+        scheduled.push(cursor.deserialize_current()?);
+    }
+
+    Ok(scheduled)
+}
+
+/// Changes the content, subject (only for new threads) and publication time
+/// of a [`ScheduledPost`], which also retries it if publishing failed before
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `scheduled_id` isn't in use
+/// - [`ApiError::EmptySubject`] or [`ApiError::EmptyContent`] if they're empty
+/// - [`ApiError::InvalidPublishTime`] if `publish_at` can't be parsed or isn't in the future
+#[server]
+pub async fn edit_scheduled_post(
+    scheduled_id: u32,
+    subject: Option<String>,
+    content: String,
+    publish_at: String,
+    time_zone: String,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    let scheduled_col = ScheduledPost::collection(&db);
+    let Some(mut scheduled) = scheduled_col
+        .find_one(bson::doc! {"id": scheduled_id})
+        .await?
+    else {
+        return Err(ApiError::NotFound("scheduled post".into(), scheduled_id));
+    };
+
+    if content.trim().is_empty() {
+        return Err(ApiError::EmptyContent);
+    }
+    if let ScheduledTarget::Thread {
+        subject: old_subject,
+        ..
+    } = &mut scheduled.target
+    {
+        let subject = subject.unwrap_or_default().trim().to_string();
+        if subject.is_empty() {
+            return Err(ApiError::EmptySubject);
+        }
+        *old_subject = subject;
+    }
+    scheduled.content = content;
+    scheduled.publish_at = parse_publish_at(&publish_at, &time_zone)?;
+    scheduled.error = None;
+
+    scheduled_col
+        .replace_one(bson::doc! {"id": scheduled_id}, &scheduled)
+        .await?;
+
+    Ok(())
+}
+
+/// Deletes a [`ScheduledPost`] before it's published
+///
+/// Requires [`Role::Moderator`]
+#[server]
+pub async fn cancel_scheduled_post(scheduled_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    ScheduledPost::collection(&db)
+        .delete_one(bson::doc! {"id": scheduled_id})
+        .await?;
+    previews::delete_links_of(scheduled_id, &db).await
+}
+
+/// Publishes a single [`ScheduledPost`] as its author
+#[cfg(feature = "ssr")]
+async fn publish(scheduled: ScheduledPost, db: &mongodb::Database) -> Result<(), ApiError> {
+    let author = helper::get_user(scheduled.author_id, db).await?;
+    let result = match scheduled.target {
+        ScheduledTarget::Thread { forum_id, subject } => {
+            helper::create_thread_as(Some(author), forum_id, subject, scheduled.content, &[], db)
+                .await
+                .map(|_| ())
+        }
+        ScheduledTarget::Reply { thread_id } => {
+            helper::create_post_as(Some(author), thread_id, scheduled.content, None, &[], db)
+                .await
+                .map(|_| ())
+        }
+    };
+    match result {
+        // it's published, only waiting for approval
+        Ok(()) | Err(ApiError::HeldForReview) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Publishes all due [`ScheduledPosts`][ScheduledPost] that didn't fail before
+///
+/// Failed ones keep their [`ScheduledPost::error`] until staff edits or cancels them
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn publish_due(db: &mongodb::Database) -> Result<(), ApiError> {
+    let scheduled_col = ScheduledPost::collection(db);
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    let mut due = vec![];
+    let mut cursor = scheduled_col
+        .find(bson::doc! {"publish_at": {"$lte": now}, "error": null})
+        .await?;
+    while cursor.advance().await? {
+        due.push(cursor.deserialize_current()?);
+    }
+
+    for scheduled in due {
+        let id = scheduled.id;
+        match publish(scheduled, db).await {
+            Ok(()) => {
+                scheduled_col.delete_one(bson::doc! {"id": id}).await?;
+                previews::delete_links_of(id, db).await?;
+            }
+            Err(err) => {
+                tracing::warn!("publishing scheduled post {id} failed: {err}");
+                scheduled_col
+                    .update_one(
+                        bson::doc! {"id": id},
+                        bson::doc! {"$set": {"error": err.to_string()}},
+                    )
+                    .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs [`publish_due`] every [`PUBLISH_INTERVAL_SECS`] forever, meant to be spawned on startup
+#[cfg(feature = "ssr")]
+pub async fn run_publisher(db: mongodb::Database) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(PUBLISH_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(err) = publish_due(&db).await {
+            tracing::warn!("publishing scheduled posts failed: {err}");
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Search engine support: the `/sitemap.xml` of all public pages
+//! and the descriptions and images used in `<meta>` and OpenGraph tags
+//!
+//! Only public content is listed, i.e. no forums with a [read role][super::Forum::read_role]
+//! and no held or deleted threads.
+//! Links in the sitemap are absolute if the `PUBLIC_URL` env var is set, relative otherwise
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, Post, Thread, bson,
+    feeds::escape_xml,
+    helper, site_settings,
+    visibility::{self, Listing},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a description, search engines cut off longer ones anyway
+pub const DESCRIPTION_LEN: usize = 160;
+/// Maximum number of urls in a sitemap, see <https://www.sitemaps.org/protocol.html>
+pub const MAX_SITEMAP_URLS: usize = 50_000;
+
+/// Turns Markdown into a single line of plain text of at most `max_len` characters,
+/// cut off with `…` if it's longer
+///
+/// Only the most common Markdown syntax is removed, it doesn't have to be perfect
+///
+/// # Example
+///
+/// ```
+/// use app::api::seo::excerpt;
+///
+/// assert_eq!(excerpt("# Hi\n\nThis is **bold** and `code`.", 100), "Hi This is bold and code.");
+/// assert_eq!(excerpt("> quoted [link](https://example.com)", 100), "quoted link");
+/// assert_eq!(excerpt("one two three", 8), "one two…");
+/// ```
+#[must_use]
+pub fn excerpt(markdown: &str, max_len: usize) -> String {
+    let mut plain = String::with_capacity(markdown.len());
+    let mut chars = markdown.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '#' | '*' | '_' | '`' | '>' | '[' | '~' => {}
+            // drops the url of a link
+            ']' if chars.peek() == Some(&'(') => {
+                for char in chars.by_ref() {
+                    if char == ')' {
+                        break;
+                    }
+                }
+            }
+            ']' => {}
+            _ => plain.push(char),
+        }
+    }
+    let words: Vec<&str> = plain.split_whitespace().collect();
+
+    let mut excerpt = String::new();
+    for word in words {
+        let len = excerpt.chars().count();
+        let separator = usize::from(len > 0);
+        // leave room for the …
+        if len + separator + word.chars().count() >= max_len {
+            excerpt.push('…');
+            return excerpt;
+        }
+        if separator > 0 {
+            excerpt.push(' ');
+        }
+        excerpt.push_str(word);
+    }
+    excerpt
+}
+
+/// What's put into the `<meta>` tags of a [`Thread`][super::Thread]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ThreadMeta {
+    /// The [`excerpt`] of its origin post
+    pub description: String,
+    /// Url of its [preview image][super::og_image], absolute if the `PUBLIC_URL` env var is set
+    pub image_url: String,
+}
+
+/// Returns the [`ThreadMeta`] of a [`Thread`][super::Thread]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if the thread doesn't exist or isn't visible
+#[server]
+pub async fn get_thread_meta(thread_id: u32) -> Result<ThreadMeta, ApiError> {
+    let db = helper::get_db()?;
+    // same visibility as the thread itself
+    let thread = super::get_thread(thread_id).await?;
+    let origin_post = helper::get_post(thread.origin_post_id, db).await?;
+    Ok(ThreadMeta {
+        description: excerpt(&origin_post.content, DESCRIPTION_LEN),
+        image_url: format!(
+            "{}{}",
+            helper::public_url().unwrap_or_default(),
+            super::og_image::thread_image_path(thread.id)
+        ),
+    })
+}
+
+/// Writes a sitemap with the given paths and when they were last modified
+#[cfg(feature = "ssr")]
+fn sitemap_xml(base: &str, urls: &[(String, Option<jiff::Timestamp>)]) -> String {
+    use std::fmt::Write;
+
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#,
+    );
+    for (path, last_modified) in urls {
+        let _ = write!(xml, "<url><loc>{}</loc>", escape_xml(&format!("{base}{path}")));
+        if let Some(last_modified) = last_modified {
+            let _ = write!(
+                xml,
+                "<lastmod>{}</lastmod>",
+                last_modified.strftime("%Y-%m-%d")
+            );
+        }
+        xml.push_str("</url>");
+    }
+    xml.push_str("</urlset>");
+    xml
+}
+
+/// Collects the paths of all pages guests can see, the newest threads first
+#[cfg(feature = "ssr")]
+async fn public_urls(
+    db: &mongodb::Database,
+) -> Result<Vec<(String, Option<jiff::Timestamp>)>, ApiError> {
+    let guest_access = site_settings::load_settings(db).await?.guest_access;
+    let forums = helper::get_all_forums(db).await?;
+    let public_forum_ids = visibility::listed_forum_ids(&forums, None, Listing::Public);
+
+    let mut urls = vec![("/".to_string(), None)];
+    if guest_access.forums {
+        urls.push(("/forum".to_string(), None));
+        urls.extend(
+            public_forum_ids
+                .iter()
+                .map(|forum_id| (format!("/forum/{forum_id}"), None)),
+        );
+    }
+    if !guest_access.threads {
+        return Ok(urls);
+    }
+
+    let mut threads = vec![];
+    let mut cursor = Thread::collection(db)
+        .find(bson::doc! {
+            "forum_id": {"$in": public_forum_ids.clone()},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .sort(bson::doc! {"latest_post_id": -1})
+        .limit(i64::try_from(MAX_SITEMAP_URLS.saturating_sub(urls.len())).unwrap_or(i64::MAX))
+        .await?;
+    while cursor.advance().await? {
+        let thread: Thread = cursor.deserialize_current()?;
+        threads.push(thread);
+    }
+
+    // a thread was last modified when its latest post was written
+    let latest_post_ids: Vec<u32> = threads.iter().map(|thread| thread.latest_post_id).collect();
+    let mut written_at = std::collections::HashMap::new();
+    let mut cursor = Post::collection(db)
+        .find(bson::doc! {"id": {"$in": latest_post_ids}})
+        .await?;
+    while cursor.advance().await? {
+        let post: Post = cursor.deserialize_current()?;
+        written_at.insert(post.id, post.created_at);
+    }
+    urls.extend(threads.iter().map(|thread| {
+        (
+            format!("/thread/{}", thread.id),
+            written_at.get(&thread.latest_post_id).copied(),
+        )
+    }));
+
+    Ok(urls)
+}
+
+/// Axum handler for `GET /sitemap.xml`
+#[cfg(feature = "ssr")]
+pub async fn sitemap(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use http::StatusCode;
+
+    match public_urls(&db).await {
+        Ok(urls) => {
+            let base = helper::public_url().unwrap_or_default();
+            (
+                [(http::header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+                sitemap_xml(&base, &urls),
+            )
+                .into_response()
+        }
+        Err(err) => {
+            tracing::warn!("serving sitemap failed: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
@@ -0,0 +1,450 @@
+//! Settings of the whole instance, a single document edited by admins
+//!
+//! Unlike other settings they're readable by everyone, since they decide how pages are shown.
+//! [`GuestAccess`] decides whether the instance is public, private or something in between,
+//! the [`ContentLicense`] under which terms posts may be reused
+
+use super::{
+    ApiError, CollectionName,
+    challenge::{ChallengeKind, ChallengeProvider},
+    validation::LengthLimits,
+};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, bson, embed, helper, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What's shown at `/`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HomeRoute {
+    /// The welcome page
+    #[default]
+    Welcome,
+    /// Redirects to the index of all forums at `/forum`
+    Forums,
+    /// Redirects to the latest posts at `/latest`
+    Latest,
+}
+impl HomeRoute {
+    pub const ALL: [Self; 3] = [Self::Welcome, Self::Forums, Self::Latest];
+
+    /// Path `/` redirects to, [`None`] if the welcome page is shown there
+    #[must_use]
+    pub const fn redirect_path(self) -> Option<&'static str> {
+        match self {
+            Self::Welcome => None,
+            Self::Forums => Some("/forum"),
+            Self::Latest => Some("/latest"),
+        }
+    }
+
+    /// Translation key of the name of the page for the admin area
+    #[must_use]
+    pub const fn label_key(self) -> &'static str {
+        match self {
+            Self::Welcome => "home_route.welcome",
+            Self::Forums => "home_route.forums",
+            Self::Latest => "home_route.latest",
+        }
+    }
+}
+
+/// Something guests, i.e. visitors who aren't logged in, may be allowed to see
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuestCapability {
+    /// The forum index and the thread lists of forums
+    Forums,
+    /// Threads with their posts, the latest posts, searching threads, wiki pages and feeds
+    Threads,
+    /// The member list and the newest members
+    Profiles,
+}
+impl GuestCapability {
+    pub const ALL: [Self; 3] = [Self::Forums, Self::Threads, Self::Profiles];
+
+    /// Name of the form field in the admin area
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Forums => "forums",
+            Self::Threads => "threads",
+            Self::Profiles => "profiles",
+        }
+    }
+
+    /// Translation key of what guests can see with it, for the admin area
+    #[must_use]
+    pub const fn label_key(self) -> &'static str {
+        match self {
+            Self::Forums => "guest_capability.forums",
+            Self::Threads => "guest_capability.threads",
+            Self::Profiles => "guest_capability.profiles",
+        }
+    }
+}
+
+/// Which [`GuestCapabilities`][GuestCapability] guests have, enforced by
+/// [`helper::get_reader`] and [`helper::ensure_guest_can`].
+/// Logged-in users can always see everything their role allows
+///
+/// Everything is public by default
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuestAccess {
+    pub forums: bool,
+    pub threads: bool,
+    pub profiles: bool,
+}
+impl GuestAccess {
+    /// Guests can see everything
+    pub const PUBLIC: Self = Self {
+        forums: true,
+        threads: true,
+        profiles: true,
+    };
+    /// Guests have to log in to see anything but the home page
+    pub const PRIVATE: Self = Self {
+        forums: false,
+        threads: false,
+        profiles: false,
+    };
+
+    /// Checks if guests may use `capability`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::site_settings::{GuestAccess, GuestCapability};
+    ///
+    /// assert!(GuestAccess::PUBLIC.allows(GuestCapability::Threads));
+    /// assert!(!GuestAccess::PRIVATE.allows(GuestCapability::Forums));
+    /// ```
+    #[must_use]
+    pub const fn allows(self, capability: GuestCapability) -> bool {
+        match capability {
+            GuestCapability::Forums => self.forums,
+            GuestCapability::Threads => self.threads,
+            GuestCapability::Profiles => self.profiles,
+        }
+    }
+}
+impl Default for GuestAccess {
+    fn default() -> Self {
+        Self::PUBLIC
+    }
+}
+
+/// The license posts are published under, shown in the footer and embedded wherever posts
+/// leave the forum: the [REST API][super::rest], the [thread meta][super::thread_meta],
+/// ActivityPub objects, the [feeds][super::feeds] and the [forum archives][super::forum_archive]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct ContentLicense {
+    /// E.g. `CC BY-SA 4.0`
+    pub name: String,
+    /// Of the full license text
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Who has to be credited, e.g. `the authors of the posts`, may be empty
+    #[serde(default)]
+    pub attribution: String,
+}
+impl ContentLicense {
+    /// The license as a line of plain text, e.g. for the `<copyright>` of feeds
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::site_settings::ContentLicense;
+    ///
+    /// let license = ContentLicense {
+    ///     name: "CC BY-SA 4.0".into(),
+    ///     url: Some("https://creativecommons.org/licenses/by-sa/4.0/".into()),
+    ///     attribution: "The authors of the posts".into(),
+    /// };
+    /// assert_eq!(
+    ///     license.notice(),
+    ///     "Licensed under CC BY-SA 4.0 (https://creativecommons.org/licenses/by-sa/4.0/), \
+    ///      attribution: The authors of the posts"
+    /// );
+    /// let bare = ContentLicense { name: "CC0 1.0".into(), url: None, attribution: String::new() };
+    /// assert_eq!(bare.notice(), "Licensed under CC0 1.0");
+    /// ```
+    #[must_use]
+    pub fn notice(&self) -> String {
+        let mut notice = format!("Licensed under {}", self.name);
+        if let Some(url) = &self.url {
+            notice = format!("{notice} ({url})");
+        }
+        if !self.attribution.is_empty() {
+            notice = format!("{notice}, attribution: {}", self.attribution);
+        }
+        notice
+    }
+}
+
+/// Settings of the whole instance, the defaults until an admin changes them
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SiteSettings {
+    #[serde(default)]
+    pub home_route: HomeRoute,
+    /// For how many minutes after posting authors can edit their [`Posts`][super::Post],
+    /// [`None`] for no limit. Moderators can always edit, see [`edit_post`][super::edit_post]
+    #[serde(default)]
+    pub edit_window_minutes: Option<u32>,
+    #[serde(default)]
+    pub guest_access: GuestAccess,
+    /// Origins like `https://blog.example` allowed to embed threads, see [`super::embed`]
+    #[serde(default)]
+    pub embed_origins: Vec<String>,
+    /// How long subjects and posts may be, see [`super::validation`]
+    #[serde(default)]
+    pub length_limits: LengthLimits,
+    /// What guests have to pass to write, see [`super::challenge`]
+    #[serde(default)]
+    pub challenge: ChallengeProvider,
+    /// [`None`] if no license is stated
+    #[serde(default)]
+    pub content_license: Option<ContentLicense>,
+}
+impl CollectionName for SiteSettings {
+    fn collection_name() -> &'static str {
+        "site_settings"
+    }
+}
+
+/// Loads the [`SiteSettings`], the defaults if an admin never changed them
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn load_settings(db: &mongodb::Database) -> Result<SiteSettings, ApiError> {
+    let settings = SiteSettings::collection(db).find_one(bson::doc! {}).await?;
+    Ok(settings.unwrap_or_default())
+}
+
+/// Fetches the [`SiteSettings`]
+#[server]
+pub async fn get_site_settings() -> Result<SiteSettings, ApiError> {
+    let db = helper::get_db()?;
+    load_settings(&db).await
+}
+
+/// Sets what's shown at `/`
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn update_home_route(home_route: HomeRoute) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let Ok(home_route) = bson::to_bson(&home_route) else {
+        unreachable!("a HomeRoute is always serializable")
+    };
+    SiteSettings::collection(&db)
+        .update_one(
+            bson::doc! {},
+            bson::doc! {"$set": {"home_route": home_route}},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets for how many minutes authors can edit their posts, [`None`] or `0` for no limit
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn update_edit_window(edit_window_minutes: Option<u32>) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let edit_window_minutes = edit_window_minutes.filter(|minutes| *minutes > 0);
+    SiteSettings::collection(&db)
+        .update_one(
+            bson::doc! {},
+            bson::doc! {"$set": {"edit_window_minutes": edit_window_minutes}},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets what guests can see, see [`GuestAccess`]
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn update_guest_access(
+    #[server(default)] forums: bool,
+    #[server(default)] threads: bool,
+    #[server(default)] profiles: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let guest_access = GuestAccess {
+        forums,
+        threads,
+        profiles,
+    };
+    let Ok(guest_access) = bson::to_bson(&guest_access) else {
+        unreachable!("a GuestAccess is always serializable")
+    };
+    SiteSettings::collection(&db)
+        .update_one(
+            bson::doc! {},
+            bson::doc! {"$set": {"guest_access": guest_access}},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the origins allowed to embed threads, one per line, see [`super::embed`]
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidOrigin`] if a line isn't an origin like `https://blog.example`
+#[server]
+pub async fn update_embed_origins(embed_origins: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let mut origins: Vec<String> = vec![];
+    for line in embed_origins.lines().filter(|line| !line.trim().is_empty()) {
+        let origin = embed::normalize_origin(line)
+            .ok_or_else(|| ApiError::InvalidOrigin(line.to_string()))?;
+        if !origins.contains(&origin) {
+            origins.push(origin);
+        }
+    }
+    SiteSettings::collection(&db)
+        .update_one(
+            bson::doc! {},
+            bson::doc! {"$set": {"embed_origins": origins}},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the [`LengthLimits`] in characters, each at least `1`
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn update_length_limits(
+    max_subject_len: u32,
+    max_content_len: u32,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let limits = LengthLimits {
+        max_subject_len: max_subject_len.max(1),
+        max_content_len: max_content_len.max(1),
+    };
+    let Ok(limits) = bson::to_bson(&limits) else {
+        unreachable!("LengthLimits are always serializable")
+    };
+    SiteSettings::collection(&db)
+        .update_one(
+            bson::doc! {},
+            bson::doc! {"$set": {"length_limits": limits}},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the [`ChallengeProvider`] guests have to pass to write.
+/// `difficulty` is only used by [`ChallengeKind::ProofOfWork`] and clamped to
+/// `1..=`[`MAX_DIFFICULTY`][ChallengeProvider::MAX_DIFFICULTY], `site_key` only by CAPTCHAs
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::MissingSiteKey`] if a CAPTCHA is picked with an empty `site_key`
+#[server]
+pub async fn update_challenge(
+    kind: ChallengeKind,
+    #[server(default)] site_key: String,
+    difficulty: Option<u8>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let site_key = site_key.trim().to_string();
+    if matches!(kind, ChallengeKind::HCaptcha | ChallengeKind::Turnstile) && site_key.is_empty() {
+        return Err(ApiError::MissingSiteKey);
+    }
+    let challenge = match kind {
+        ChallengeKind::Off => ChallengeProvider::Off,
+        ChallengeKind::ProofOfWork => ChallengeProvider::ProofOfWork {
+            difficulty: difficulty
+                .unwrap_or(ChallengeProvider::DEFAULT_DIFFICULTY)
+                .clamp(1, ChallengeProvider::MAX_DIFFICULTY),
+        },
+        ChallengeKind::HCaptcha => ChallengeProvider::HCaptcha { site_key },
+        ChallengeKind::Turnstile => ChallengeProvider::Turnstile { site_key },
+    };
+    let Ok(challenge) = bson::to_bson(&challenge) else {
+        unreachable!("a ChallengeProvider is always serializable")
+    };
+    SiteSettings::collection(&db)
+        .update_one(bson::doc! {}, bson::doc! {"$set": {"challenge": challenge}})
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the [`ContentLicense`] of the posts, an empty `name` removes it
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidLicenseUrl`] if `url` is neither empty nor an `http(s)://` url
+#[server]
+pub async fn update_content_license(
+    name: String,
+    #[server(default)] url: String,
+    #[server(default)] attribution: String,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let url = Some(url.trim().to_string()).filter(|url| !url.is_empty());
+    let invalid_url = url.as_ref().filter(|url| {
+        !(url.starts_with("https://") || url.starts_with("http://")) || url.contains(' ')
+    });
+    if let Some(url) = invalid_url {
+        return Err(ApiError::InvalidLicenseUrl(url.clone()));
+    }
+    let name = name.trim().to_string();
+    let license = (!name.is_empty()).then(|| ContentLicense {
+        name,
+        url,
+        attribution: attribution.trim().to_string(),
+    });
+    let Ok(license) = bson::to_bson(&license) else {
+        unreachable!("a ContentLicense is always serializable")
+    };
+    SiteSettings::collection(&db)
+        .update_one(
+            bson::doc! {},
+            bson::doc! {"$set": {"content_license": license}},
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
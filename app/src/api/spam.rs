@@ -0,0 +1,247 @@
+//! Spam heuristics scoring new [`Posts`][Post] before they're saved
+//!
+//! Every post gets a score from its [`SpamSignals`]: how many links it has, whether its author
+//! posted the same content lately and how many posts they wrote in the last
+//! [`VELOCITY_MINUTES`]. Posts scoring at least [`SpamSettings::threshold`] are held in the
+//! [moderation queue][super::moderation], invisible to others until a moderator approves them
+//! there. Users allowed to [skip review][super::groups::GroupPermission::SkipReview] never are
+
+#[cfg(feature = "ssr")]
+use super::Post;
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, bson, helper, user::Role, user::User};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Window in minutes in which the posts of an author count towards their velocity
+pub const VELOCITY_MINUTES: i64 = 10;
+/// Window in hours in which identical content of the same author counts as a duplicate
+pub const DUPLICATE_HOURS: i64 = 24;
+
+/// Configures how much each of the [`SpamSignals`] weighs and when posts are held,
+/// a single document edited by admins
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpamSettings {
+    /// Posts are held once they score at least this much, `0` turns the heuristics off
+    pub threshold: u32,
+    /// Per link
+    pub link_weight: u32,
+    /// If the author posted the same content within [`DUPLICATE_HOURS`]
+    pub duplicate_weight: u32,
+    /// Per post within [`VELOCITY_MINUTES`] beyond the [`free_posts`][Self::free_posts]
+    pub velocity_weight: u32,
+    /// How many posts within [`VELOCITY_MINUTES`] don't count towards the velocity
+    pub free_posts: u32,
+}
+impl Default for SpamSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 10,
+            link_weight: 2,
+            duplicate_weight: 6,
+            velocity_weight: 2,
+            free_posts: 3,
+        }
+    }
+}
+impl SpamSettings {
+    /// The spam score of a post with the given [`SpamSignals`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::spam::{SpamSettings, SpamSignals};
+    ///
+    /// let settings = SpamSettings::default();
+    /// let quiet = SpamSignals { links: 1, duplicate: false, recent_posts: 2 };
+    /// assert_eq!(settings.score(&quiet), 2);
+    /// let flood = SpamSignals { links: 2, duplicate: true, recent_posts: 5 };
+    /// assert_eq!(settings.score(&flood), 4 + 6 + 4);
+    /// ```
+    #[must_use]
+    pub const fn score(&self, signals: &SpamSignals) -> u32 {
+        let links = self.link_weight.saturating_mul(signals.links);
+        let duplicate = if signals.duplicate {
+            self.duplicate_weight
+        } else {
+            0
+        };
+        let velocity = self
+            .velocity_weight
+            .saturating_mul(signals.recent_posts.saturating_sub(self.free_posts));
+        links.saturating_add(duplicate).saturating_add(velocity)
+    }
+}
+impl CollectionName for SpamSettings {
+    fn collection_name() -> &'static str {
+        "spam_settings"
+    }
+}
+
+/// What the [`SpamSettings`] score a new post by
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SpamSignals {
+    /// See [`count_links`]
+    pub links: u32,
+    /// Whether the same author posted the same content within [`DUPLICATE_HOURS`],
+    /// any guest for guests
+    pub duplicate: bool,
+    /// Posts of the same author within [`VELOCITY_MINUTES`], of all guests for guests
+    pub recent_posts: u32,
+}
+
+/// Counts the `http://` and `https://` links in `content`
+///
+/// # Example
+///
+/// ```
+/// use app::api::spam::count_links;
+///
+/// assert_eq!(count_links("see [this](https://a.example) and http://b.example"), 2);
+/// assert_eq!(count_links("no links, just a.example"), 0);
+/// ```
+#[must_use]
+pub fn count_links(content: &str) -> u32 {
+    let links = content.matches("http://").count() + content.matches("https://").count();
+    u32::try_from(links).unwrap_or(u32::MAX)
+}
+
+/// Hex SHA-256 of `content` without surrounding whitespace, saved as
+/// [`Post::content_hash`][super::Post::content_hash] so duplicates are found by an index
+///
+/// # Example
+///
+/// ```
+/// use app::api::spam::content_hash;
+///
+/// assert_eq!(content_hash("Buy now!"), content_hash(" Buy now!\n"));
+/// assert_ne!(content_hash("Buy now!"), content_hash("buy now!"));
+/// assert_eq!(content_hash("").len(), 64);
+/// ```
+#[must_use]
+pub fn content_hash(content: &str) -> String {
+    use sha2::Digest;
+    use std::fmt::Write;
+
+    sha2::Sha256::digest(content.trim())
+        .iter()
+        .fold(String::with_capacity(64), |mut hash, byte| {
+            let _ = write!(hash, "{byte:02x}");
+            hash
+        })
+}
+
+/// Creates the index [`held_score`] looks up duplicates and the velocity of authors with
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn create_indexes(db: &mongodb::Database) -> Result<(), ApiError> {
+    let index = mongodb::IndexModel::builder()
+        .keys(bson::doc! {"author_id": 1, "content_hash": 1, "created_at": -1})
+        .build();
+    Post::collection(db).create_index(index).await?;
+    Ok(())
+}
+
+/// Loads the [`SpamSettings`], the defaults if an admin never changed them
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn load_settings(db: &mongodb::Database) -> Result<SpamSettings, ApiError> {
+    let settings = SpamSettings::collection(db).find_one(bson::doc! {}).await?;
+    Ok(settings.unwrap_or_default())
+}
+
+/// Scores `content` about to be posted by `author` (a guest if [`None`]) and returns
+/// the score if it reaches the [`SpamSettings::threshold`], i.e. if the post has to be held
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn held_score(
+    author: Option<&User>,
+    content: &str,
+    db: &mongodb::Database,
+) -> Result<Option<u32>, ApiError> {
+    let settings = load_settings(db).await?;
+    if settings.threshold == 0 {
+        return Ok(None);
+    }
+
+    let now = jiff::Timestamp::now();
+    let since = |duration: jiff::SignedDuration| {
+        bson::DateTime::from_millis((now - duration).as_millisecond())
+    };
+    let author_id = author.map(|author| author.id);
+    let post_col = Post::collection(db);
+    let duplicate = post_col
+        .find_one(bson::doc! {
+            "author_id": author_id,
+            "content_hash": content_hash(content),
+            "created_at": {"$gte": since(jiff::SignedDuration::from_hours(DUPLICATE_HOURS))},
+        })
+        .await?
+        .is_some();
+    let recent_posts = post_col
+        .count_documents(bson::doc! {
+            "author_id": author_id,
+            "created_at": {"$gte": since(jiff::SignedDuration::from_mins(VELOCITY_MINUTES))},
+        })
+        .await?;
+
+    let signals = SpamSignals {
+        links: count_links(content),
+        duplicate,
+        recent_posts: u32::try_from(recent_posts).unwrap_or(u32::MAX),
+    };
+    let score = settings.score(&signals);
+    Ok((score >= settings.threshold).then_some(score))
+}
+
+/// Fetches the [`SpamSettings`]
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn get_spam_settings() -> Result<SpamSettings, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+    load_settings(&db).await
+}
+
+/// Replaces the [`SpamSettings`], a `threshold` of `0` turns the heuristics off
+///
+/// Already held posts stay held, the new values only apply to new posts
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn update_spam_settings(
+    threshold: u32,
+    link_weight: u32,
+    duplicate_weight: u32,
+    velocity_weight: u32,
+    free_posts: u32,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let settings = SpamSettings {
+        threshold,
+        link_weight,
+        duplicate_weight,
+        velocity_weight,
+        free_posts,
+    };
+    SpamSettings::collection(&db)
+        .replace_one(bson::doc! {}, settings)
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
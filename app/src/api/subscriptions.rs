@@ -0,0 +1,349 @@
+//! Everything a [`User`][super::user::User] watches: [threads][WatchedThread] and
+//! [forums][WatchedForum], each with a [`WatchLevel`] deciding what they're notified about
+//!
+//! The subscriptions page lists them all with their last activity, see [`get_subscriptions`],
+//! where they can be unwatched in bulk. Notifications are [push
+//! notifications][super::web_push], muted threads also stay out of the
+//! [digests][super::watch_digests]
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, Post, Thread, bson, helper, my_content::WatchedThread};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What watching a [thread][WatchedThread] or [forum][WatchedForum] notifies about
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WatchLevel {
+    /// Every reply in a thread, every new thread and reply in a forum
+    #[default]
+    AllPosts,
+    /// Only the first reply since last reading a thread, only new threads in a forum
+    FirstPost,
+    /// Nothing, not even about threads the user started
+    Muted,
+}
+impl WatchLevel {
+    pub const ALL: [Self; 3] = [Self::AllPosts, Self::FirstPost, Self::Muted];
+
+    /// Translation key of the human-readable name of the level
+    #[must_use]
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::AllPosts => "watch_level.all_posts",
+            Self::FirstPost => "watch_level.first_post",
+            Self::Muted => "watch_level.muted",
+        }
+    }
+
+    /// For use in db queries
+    #[cfg(feature = "ssr")]
+    pub(super) fn to_bson(self) -> bson::Bson {
+        let Ok(level) = bson::to_bson(&self) else {
+            unreachable!("a unit enum is always serializable")
+        };
+        level
+    }
+}
+
+/// A [`Forum`][super::Forum] watched by a [`User`][super::user::User]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WatchedForum {
+    pub user_id: u32,
+    pub forum_id: u32,
+    #[serde(default)]
+    pub level: WatchLevel,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub watched_at: jiff::Timestamp,
+}
+impl CollectionName for WatchedForum {
+    fn collection_name() -> &'static str {
+        "watched_forums"
+    }
+}
+
+/// A watched [`Thread`][super::Thread] on the subscriptions page
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadSubscription {
+    pub thread_id: u32,
+    /// See [`Thread::display_subject`][super::Thread::display_subject]
+    pub subject: String,
+    pub level: WatchLevel,
+    pub watched_at: jiff::Timestamp,
+    /// When its latest post was written
+    pub last_activity: Option<jiff::Timestamp>,
+}
+
+/// A watched [`Forum`][super::Forum] on the subscriptions page
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ForumSubscription {
+    pub forum_id: u32,
+    pub name: String,
+    pub level: WatchLevel,
+    pub watched_at: jiff::Timestamp,
+    /// When the latest post of its latest thread was written
+    pub last_activity: Option<jiff::Timestamp>,
+}
+
+/// Everything the logged-in [`User`][super::user::User] watches, latest activity first
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Subscriptions {
+    pub threads: Vec<ThreadSubscription>,
+    pub forums: Vec<ForumSubscription>,
+}
+
+/// When the [`Post`][super::Post] with `post_id` was written, [`None`] if it's gone
+#[cfg(feature = "ssr")]
+async fn created_at(
+    post_id: u32,
+    db: &mongodb::Database,
+) -> Result<Option<jiff::Timestamp>, ApiError> {
+    let post = Post::collection(db)
+        .find_one(bson::doc! {"id": post_id})
+        .await?;
+    Ok(post.map(|post| post.created_at))
+}
+
+/// Fetches the [`Subscriptions`] of the logged-in [`User`][super::user::User], leaving out
+/// what they can't see (anymore), e.g. deleted threads or forums they lost access to
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn get_subscriptions() -> Result<Subscriptions, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let forums = helper::get_all_forums(&db).await?;
+
+    let mut watched_threads: Vec<WatchedThread> = vec![];
+    let mut cursor = WatchedThread::collection(&db)
+        .find(bson::doc! {"user_id": user.id})
+        .await?;
+    while cursor.advance().await? {
+        watched_threads.push(cursor.deserialize_current()?);
+    }
+    let thread_ids: Vec<u32> = watched_threads
+        .iter()
+        .map(|watched| watched.thread_id)
+        .collect();
+    let mut threads = vec![];
+    let mut cursor = Thread::collection(&db)
+        .find(bson::doc! {
+            "id": {"$in": thread_ids},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .sort(bson::doc! {"latest_post_id": -1})
+        .await?;
+    while cursor.advance().await? {
+        let thread: Thread = cursor.deserialize_current()?;
+        if !helper::can_see_forum(&forums, thread.forum_id, Some(&user)) {
+            continue;
+        }
+        let Some(watched) = watched_threads
+            .iter()
+            .find(|watched| watched.thread_id == thread.id)
+        else {
+            continue;
+        };
+        threads.push(ThreadSubscription {
+            thread_id: thread.id,
+            subject: thread.display_subject(),
+            level: watched.level,
+            watched_at: watched.watched_at,
+            last_activity: created_at(thread.latest_post_id, &db).await?,
+        });
+    }
+
+    let mut watched_forums: Vec<WatchedForum> = vec![];
+    let mut cursor = WatchedForum::collection(&db)
+        .find(bson::doc! {"user_id": user.id})
+        .await?;
+    while cursor.advance().await? {
+        watched_forums.push(cursor.deserialize_current()?);
+    }
+    let mut forum_subscriptions = vec![];
+    for watched in watched_forums {
+        let Some(forum) = forums.iter().find(|forum| forum.id == watched.forum_id) else {
+            continue;
+        };
+        if !helper::can_see_forum(&forums, forum.id, Some(&user)) {
+            continue;
+        }
+        let latest_thread = Thread::collection(&db)
+            .find_one(bson::doc! {"id": forum.latest_thread_id})
+            .await?;
+        let last_activity = match latest_thread {
+            Some(thread) => created_at(thread.latest_post_id, &db).await?,
+            None => None,
+        };
+        forum_subscriptions.push(ForumSubscription {
+            forum_id: forum.id,
+            name: forum.name.clone(),
+            level: watched.level,
+            watched_at: watched.watched_at,
+            last_activity,
+        });
+    }
+    forum_subscriptions.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+
+    Ok(Subscriptions {
+        threads,
+        forums: forum_subscriptions,
+    })
+}
+
+/// Watches the [`Forum`][super::Forum] with `forum_id` for the logged-in
+/// [`User`][super::user::User] or stops watching it
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if `forum_id` isn't in use or they can't see it
+#[server]
+pub async fn set_forum_watched(
+    forum_id: u32,
+    #[server(default)] watched: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let forums = helper::get_all_forums(&db).await?;
+    if !helper::can_see_forum(&forums, forum_id, Some(&user)) {
+        return Err(ApiError::NotFound("forum".into(), forum_id));
+    }
+
+    let by_user_and_forum = bson::doc! {"user_id": user.id, "forum_id": forum_id};
+    if watched {
+        let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+        WatchedForum::collection(&db)
+            .update_one(
+                by_user_and_forum,
+                bson::doc! {"$setOnInsert": {"watched_at": now}},
+            )
+            .upsert(true)
+            .await?;
+    } else {
+        WatchedForum::collection(&db)
+            .delete_one(by_user_and_forum)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Returns whether the logged-in [`User`][super::user::User] watches the
+/// [`Forum`][super::Forum] with `forum_id`,
+/// [`None`] if not logged in
+#[server]
+pub async fn is_forum_watched(forum_id: u32) -> Result<Option<bool>, ApiError> {
+    let db = helper::get_db()?;
+    let Some(user) = helper::get_current_user(&db).await? else {
+        return Ok(None);
+    };
+    let watched = WatchedForum::collection(&db)
+        .find_one(bson::doc! {"user_id": user.id, "forum_id": forum_id})
+        .await?
+        .is_some();
+    Ok(Some(watched))
+}
+
+/// Sets the [`WatchLevel`] of a [`Thread`][super::Thread] the logged-in
+/// [`User`][super::user::User] watches
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if they don't watch the thread with `thread_id`
+#[server]
+pub async fn set_thread_watch_level(thread_id: u32, level: WatchLevel) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let result = WatchedThread::collection(&db)
+        .update_one(
+            bson::doc! {"user_id": user.id, "thread_id": thread_id},
+            bson::doc! {"$set": {"level": level.to_bson()}},
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(ApiError::NotFound("watched thread".into(), thread_id));
+    }
+
+    Ok(())
+}
+
+/// Sets the [`WatchLevel`] of a [`Forum`][super::Forum] the logged-in
+/// [`User`][super::user::User] watches
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if they don't watch the forum with `forum_id`
+#[server]
+pub async fn set_forum_watch_level(forum_id: u32, level: WatchLevel) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let result = WatchedForum::collection(&db)
+        .update_one(
+            bson::doc! {"user_id": user.id, "forum_id": forum_id},
+            bson::doc! {"$set": {"level": level.to_bson()}},
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(ApiError::NotFound("watched forum".into(), forum_id));
+    }
+
+    Ok(())
+}
+
+/// Stops watching the [`Threads`][super::Thread] with `thread_ids` and the
+/// [`Forums`][super::Forum] with `forum_ids` for the logged-in [`User`][super::user::User]
+/// and returns how many were watched
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn unwatch(
+    #[server(default)] thread_ids: Vec<u32>,
+    #[server(default)] forum_ids: Vec<u32>,
+) -> Result<u64, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let threads = WatchedThread::collection(&db)
+        .delete_many(bson::doc! {"user_id": user.id, "thread_id": {"$in": thread_ids}})
+        .await?;
+    let forums = WatchedForum::collection(&db)
+        .delete_many(bson::doc! {"user_id": user.id, "forum_id": {"$in": forum_ids}})
+        .await?;
+
+    Ok(threads.deleted_count + forums.deleted_count)
+}
+
+/// Ids of the users watching the [`Forum`][super::Forum] with `forum_id` at one of `levels`
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn forum_watchers(
+    forum_id: u32,
+    levels: &[WatchLevel],
+    db: &mongodb::Database,
+) -> Result<Vec<u32>, ApiError> {
+    let mut user_ids = vec![];
+    let mut cursor = WatchedForum::collection(db)
+        .find(bson::doc! {"forum_id": forum_id})
+        .await?;
+    while cursor.advance().await? {
+        let watched: WatchedForum = cursor.deserialize_current()?;
+        if levels.contains(&watched.level) {
+            user_ids.push(watched.user_id);
+        }
+    }
+    Ok(user_ids)
+}
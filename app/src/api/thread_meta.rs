@@ -0,0 +1,100 @@
+//! Compact facts about a [`Thread`][super::Thread] for bots and link-preview services
+//!
+//! [`get_thread_meta`] answers `GET /api/thread_meta?thread_id=12` without an
+//! [`ApiKey`][super::api_keys::ApiKey], as a guest unless a session cookie is sent,
+//! and is much cheaper than fetching the posts. Responses may be cached for [`CACHE_MAX_AGE_SECS`],
+//! only privately if they're of a logged-in [`User`][super::user::User].
+//! Like the [REST API][super::rest], they state the
+//! [`ContentLicense`][super::site_settings::ContentLicense] of the posts
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{GetCollection, Post, bson, helper, site_settings};
+
+use leptos::prelude::*;
+use leptos::server_fn::codec::GetUrl;
+use serde::{Deserialize, Serialize};
+
+/// How long responses of [`get_thread_meta`] may be cached
+pub const CACHE_MAX_AGE_SECS: u32 = 60;
+
+/// What bots want to know about a [`Thread`][super::Thread]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadMeta {
+    pub id: u32,
+    /// With the prefixes, e.g. `[Solved] [Guide] Getting started`
+    pub subject: String,
+    pub forum_id: u32,
+    pub forum_name: String,
+    /// Absolute if the `PUBLIC_URL` env var is set
+    pub url: String,
+    /// Visible posts including the origin post
+    pub post_count: u64,
+    pub tags: Vec<String>,
+    /// Also if it has been solved for long enough,
+    /// see [`Thread::is_locked`][super::Thread::is_locked]
+    pub locked: bool,
+    pub solved: bool,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+    /// When the latest post was written
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub last_activity_at: jiff::Timestamp,
+    /// What reusing the posts is subject to, [`None`] if no license is stated
+    pub license: Option<super::site_settings::ContentLicense>,
+}
+
+/// Returns the [`ThreadMeta`] of the thread with `thread_id`, following merges
+///
+/// # Errors
+///
+/// Same as [`get_thread`][super::get_thread]
+#[server(input = GetUrl, endpoint = "thread_meta")]
+pub async fn get_thread_meta(thread_id: u32) -> Result<ThreadMeta, ApiError> {
+    // checks if the thread can be seen
+    let thread = super::get_thread(thread_id).await?;
+    let db = helper::get_db()?;
+    let (forum, _) = helper::get_forum(thread.forum_id, db.clone()).await?;
+
+    let post_count = Post::collection(&db)
+        .count_documents(bson::doc! {
+            "thread_id": thread.id,
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .await?;
+    let origin_post = helper::get_post(thread.origin_post_id, db.clone()).await?;
+    let latest_post = helper::get_post(thread.latest_post_id, db.clone()).await?;
+    let license = site_settings::load_settings(&db).await?.content_license;
+
+    let cache_control = if helper::get_session_token().is_some() {
+        format!("private, max-age={CACHE_MAX_AGE_SECS}")
+    } else {
+        format!("public, max-age={CACHE_MAX_AGE_SECS}")
+    };
+    let res = expect_context::<leptos_axum::ResponseOptions>();
+    if let Ok(cache_control) = http::HeaderValue::from_str(&cache_control) {
+        res.insert_header(http::header::CACHE_CONTROL, cache_control);
+    }
+    // guests and members may see different threads
+    res.insert_header(http::header::VARY, http::HeaderValue::from_static("Cookie"));
+
+    Ok(ThreadMeta {
+        id: thread.id,
+        subject: thread.display_subject(),
+        forum_id: forum.id,
+        url: format!(
+            "{}/thread/{}",
+            helper::public_url().unwrap_or_default(),
+            thread.id
+        ),
+        post_count,
+        locked: thread.is_locked(&forum, jiff::Timestamp::now()),
+        solved: thread.solved_at.is_some(),
+        tags: thread.tags,
+        forum_name: forum.name,
+        created_at: origin_post.created_at,
+        last_activity_at: latest_post.created_at,
+        license,
+    })
+}
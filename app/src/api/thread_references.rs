@@ -0,0 +1,208 @@
+//! Backreferences between threads: a [`Post`] linking to another [`Thread`] on this forum
+//! makes the linked thread list it as "mentioned in", like issue cross-references
+//! on code forges
+//!
+//! Only the linking post and the linked thread are stored in a [`ThreadReference`].
+//! Which thread the post is in is looked up when listing the [`ThreadMentions`][ThreadMention],
+//! so they follow posts into [merged or split][super::thread_tools] threads
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, Post, Thread, bson, helper, site_settings::GuestCapability};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How many threads a single post can reference at most, links after these are ignored
+pub const MAX_REFERENCES_PER_POST: usize = 10;
+
+/// The [`Post`] with `source_post_id` links to the [`Thread`] with `target_thread_id`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadReference {
+    pub source_post_id: u32,
+    pub target_thread_id: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+impl CollectionName for ThreadReference {
+    fn collection_name() -> &'static str {
+        "thread_references"
+    }
+}
+
+/// A [`Thread`] mentioning another one, as listed below it
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadMention {
+    pub thread_id: u32,
+    /// See [`Thread::display_subject`]
+    pub subject: String,
+    /// The first post of the thread with a link
+    pub post_id: u32,
+}
+
+/// Finds the ids of the threads linked in `content`, either relative like `/thread/12`
+/// or absolute on `public_url` (without a trailing slash), in order and without duplicates
+///
+/// # Example
+///
+/// ```
+/// use app::api::thread_references::find_thread_links;
+///
+/// let content = "see [this](/thread/12) and https://forum.example/thread/7#post-3, \
+///     not /thread/12 again";
+/// assert_eq!(find_thread_links(content, Some("https://forum.example")), vec![12, 7]);
+/// assert_eq!(find_thread_links(content, None), vec![12]);
+///
+/// let content = "https://elsewhere.example/thread/3 /threads/4 /thread/x";
+/// assert!(find_thread_links(content, Some("https://forum.example")).is_empty());
+/// ```
+#[must_use]
+pub fn find_thread_links(content: &str, public_url: Option<&str>) -> Vec<u32> {
+    let mut thread_ids: Vec<u32> = vec![];
+    for (start, path) in content.match_indices("/thread/") {
+        let before = &content[..start];
+        let is_relative = before
+            .chars()
+            .next_back()
+            .is_none_or(|char| char.is_whitespace() || matches!(char, '(' | '<' | '[' | '"'));
+        let is_absolute = public_url.is_some_and(|public_url| before.ends_with(public_url));
+        if !(is_relative || is_absolute) {
+            continue;
+        }
+
+        let id = content[start + path.len()..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        match id.parse() {
+            Ok(id) if !thread_ids.contains(&id) => thread_ids.push(id),
+            _ => {}
+        }
+    }
+    thread_ids
+}
+
+/// Records the threads linked in `content` of the [`Post`] with `post_id` in the [`Thread`]
+/// with `thread_id`, replacing those of a previous version of the post
+///
+/// Links to the post's own thread and to threads that don't exist are left out
+#[cfg(feature = "ssr")]
+pub async fn record(
+    post_id: u32,
+    thread_id: u32,
+    content: &str,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let linked_ids = find_thread_links(content, helper::public_url().as_deref())
+        .into_iter()
+        .filter(|id| *id != thread_id)
+        .take(MAX_REFERENCES_PER_POST)
+        .collect::<Vec<_>>();
+    let mut target_ids: Vec<u32> = vec![];
+    if !linked_ids.is_empty() {
+        let mut cursor = Thread::collection(db)
+            .find(bson::doc! {"id": {"$in": linked_ids}})
+            .await?;
+        while cursor.advance().await? {
+            let thread: Thread = cursor.deserialize_current()?;
+            target_ids.push(thread.id);
+        }
+    }
+
+    let reference_col = ThreadReference::collection(db);
+    reference_col
+        .delete_many(bson::doc! {
+            "source_post_id": post_id,
+            "target_thread_id": {"$nin": target_ids.clone()},
+        })
+        .await?;
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    for target_thread_id in target_ids {
+        // keeps when it was first linked if an edit keeps the link
+        reference_col
+            .update_one(
+                bson::doc! {"source_post_id": post_id, "target_thread_id": target_thread_id},
+                bson::doc! {"$setOnInsert": {"created_at": now}},
+            )
+            .upsert(true)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Fetches the threads mentioning the [`Thread`] with `thread_id`, in the order they first
+/// did so
+///
+/// Only visible posts in threads the logged-in [`User`][super::user::User] can see
+/// are taken into account
+#[server]
+pub async fn get_thread_mentions(thread_id: u32) -> Result<Vec<ThreadMention>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+
+    let mut post_ids: Vec<u32> = vec![];
+    let mut cursor = ThreadReference::collection(&db)
+        .find(bson::doc! {"target_thread_id": thread_id})
+        .await?;
+    while cursor.advance().await? {
+        let reference: ThreadReference = cursor.deserialize_current()?;
+        post_ids.push(reference.source_post_id);
+    }
+    if post_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut posts: Vec<Post> = vec![];
+    let mut cursor = Post::collection(&db)
+        .find(bson::doc! {
+            "id": {"$in": post_ids},
+            "thread_id": {"$ne": thread_id},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+            "thread_deleted": {"$ne": true},
+        })
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    while cursor.advance().await? {
+        posts.push(cursor.deserialize_current()?);
+    }
+    let mut thread_ids = posts.iter().map(|post| post.thread_id).collect::<Vec<_>>();
+    thread_ids.sort_unstable();
+    thread_ids.dedup();
+
+    let mut threads: Vec<Thread> = vec![];
+    let mut cursor = Thread::collection(&db)
+        .find(bson::doc! {
+            "id": {"$in": thread_ids},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .await?;
+    while cursor.advance().await? {
+        threads.push(cursor.deserialize_current()?);
+    }
+
+    let forums = helper::get_all_forums(&db).await?;
+    let mut mentions: Vec<ThreadMention> = vec![];
+    for post in posts {
+        if mentions
+            .iter()
+            .any(|mention| mention.thread_id == post.thread_id)
+        {
+            continue;
+        }
+        let Some(thread) = threads.iter().find(|thread| thread.id == post.thread_id) else {
+            continue;
+        };
+        if helper::can_see_forum(&forums, thread.forum_id, user.as_ref()) {
+            mentions.push(ThreadMention {
+                thread_id: thread.id,
+                subject: thread.display_subject(),
+                post_id: post.id,
+            });
+        }
+    }
+
+    Ok(mentions)
+}
@@ -0,0 +1,411 @@
+//! Moderator tools for [`Threads`][Thread]: merging two threads into one,
+//! splitting posts off into a new thread, soft-deleting threads
+//! and editing the tags and prefixes of many threads at once
+//!
+//! Merged threads leave a [`ThreadRedirect`] behind, so links to them keep working
+
+use super::{ApiError, CollectionName, Thread};
+#[cfg(feature = "ssr")]
+use super::{
+    Counter, GetCollection, Post, bson, cache,
+    events::{self, DomainEvent},
+    helper, mod_reasons,
+    user::Role,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Left behind by a [`Thread`] merged into another one, see [`merge_threads`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadRedirect {
+    /// Id of the merged thread, which doesn't exist anymore
+    pub thread_id: u32,
+    pub target_thread_id: u32,
+}
+impl CollectionName for ThreadRedirect {
+    fn collection_name() -> &'static str {
+        "thread_redirects"
+    }
+}
+
+/// Returns the [`Thread`] a merged thread with `thread_id` ended up in, if it was merged
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn redirect_target(
+    thread_id: u32,
+    db: &mongodb::Database,
+) -> Result<Option<u32>, ApiError> {
+    let redirect = ThreadRedirect::collection(db)
+        .find_one(bson::doc! {"thread_id": thread_id})
+        .await?;
+    Ok(redirect.map(|redirect| redirect.target_thread_id))
+}
+
+/// Moves all [`Posts`][Post] of the source [`Thread`] into the target thread
+/// and deletes the source, leaving a [`ThreadRedirect`] behind. Returns the target's id
+///
+/// Posts stay ordered by id, so they're interleaved with the target's posts by date
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if one of the threads doesn't exist
+/// - [`ApiError::MergeIntoSelf`] if both are the same thread
+#[server]
+pub async fn merge_threads(source_thread_id: u32, target_thread_id: u32) -> Result<u32, ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+
+    if source_thread_id == target_thread_id {
+        return Err(ApiError::MergeIntoSelf);
+    }
+    let source = helper::get_thread(source_thread_id, db.clone()).await?;
+    let target = helper::get_thread(target_thread_id, db.clone()).await?;
+
+    Post::collection(&db)
+        .update_many(
+            bson::doc! {"thread_id": source.id},
+            bson::doc! {"$set": {
+                "thread_id": target.id,
+                "thread_deleted": target.deleted_at.is_some(),
+            }},
+        )
+        .await?;
+    Thread::collection(&db)
+        .delete_one(bson::doc! {"id": source.id})
+        .await?;
+
+    let redirect_col = ThreadRedirect::collection(&db);
+    // threads merged into the source earlier end up in the target as well
+    redirect_col
+        .update_many(
+            bson::doc! {"target_thread_id": source.id},
+            bson::doc! {"$set": {"target_thread_id": target.id}},
+        )
+        .await?;
+    redirect_col
+        .insert_one(ThreadRedirect {
+            thread_id: source.id,
+            target_thread_id: target.id,
+        })
+        .await?;
+
+    let forum_ids = [source.forum_id, target.forum_id];
+    helper::refresh_latest_ids(&forum_ids, &db).await?;
+    cache::invalidate_forums(&forum_ids, &db).await?;
+    events::emit(
+        DomainEvent::ThreadsMerged {
+            source_thread_id: source.id,
+            target_thread_id: target.id,
+            moderator_id: moderator.id,
+        },
+        &db,
+    );
+
+    Ok(target.id)
+}
+
+/// Moves the given [`Posts`][Post] into a new [`Thread`] with `new_subject`
+/// in the same [`Forum`][super::Forum], returning its id
+///
+/// The earliest of them becomes the origin post of the new thread
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptySubject`] if `new_subject` is empty
+/// - [`ApiError::NotFound`] if one of the posts doesn't exist
+/// - [`ApiError::InvalidSplit`] if no posts are given, they're from different threads
+///   or include the origin post of their thread
+#[server]
+pub async fn split_thread(
+    #[server(default)] post_ids: Vec<u32>,
+    new_subject: String,
+) -> Result<u32, ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+
+    let new_subject = new_subject.trim().to_string();
+    if new_subject.is_empty() {
+        return Err(ApiError::EmptySubject);
+    }
+    let mut post_ids = post_ids;
+    post_ids.sort_unstable();
+    post_ids.dedup();
+    let Some(&origin_post_id) = post_ids.first() else {
+        return Err(ApiError::InvalidSplit);
+    };
+
+    let mut thread_id = None;
+    for &post_id in &post_ids {
+        let post = helper::get_post(post_id, db.clone()).await?;
+        if thread_id.is_some_and(|thread_id| thread_id != post.thread_id) {
+            return Err(ApiError::InvalidSplit);
+        }
+        thread_id = Some(post.thread_id);
+    }
+    let Some(thread_id) = thread_id else {
+        unreachable!("there is at least one post")
+    };
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    if post_ids.contains(&thread.origin_post_id) {
+        return Err(ApiError::InvalidSplit);
+    }
+    let origin_post = helper::get_post(origin_post_id, db.clone()).await?;
+
+    let new_thread_id = helper::get_and_increment_id_of("thread", Counter::collection(&db)).await?;
+    Thread::collection(&db)
+        .insert_one(Thread {
+            id: new_thread_id,
+            origin_post_id,
+            forum_id: thread.forum_id,
+            subject: new_subject,
+            // fixed by `refresh_latest_ids` below
+            latest_post_id: origin_post_id,
+            author_id: origin_post.author_id,
+            pending_review: origin_post.pending_review,
+            locked: false,
+            solved_at: None,
+            wiki_post: false,
+            wiki_editor_ids: vec![],
+            co_author_ids: vec![],
+            deleted_at: None,
+            tags: vec![],
+            prefix: None,
+            listing: None,
+        })
+        .await?;
+    Post::collection(&db)
+        .update_many(
+            bson::doc! {"id": {"$in": &post_ids}},
+            bson::doc! {"$set": {"thread_id": new_thread_id, "thread_deleted": false}},
+        )
+        .await?;
+
+    helper::refresh_latest_ids(&[thread.forum_id], &db).await?;
+    cache::invalidate_forums(&[thread.forum_id], &db).await?;
+    events::emit(
+        DomainEvent::ThreadSplit {
+            source_thread_id: thread.id,
+            new_thread_id,
+            moderator_id: moderator.id,
+        },
+        &db,
+    );
+
+    Ok(new_thread_id)
+}
+
+/// Soft-deletes a [`Thread`], hiding it and its [`Posts`][Post] from everyone but moderators
+///
+/// `reason_id` is of the [`ModReason`][super::mod_reasons::ModReason] for the moderation log
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use or the reason can't be picked
+#[server]
+pub async fn delete_thread(
+    thread_id: u32,
+    #[server(default)] reason_id: Option<u32>,
+) -> Result<(), ApiError> {
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    set_thread_deleted(thread_id, Some(now), reason_id).await
+}
+
+/// Restores a soft-deleted [`Thread`], see [`delete_thread`]
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if `thread_id` isn't in use
+#[server]
+pub async fn restore_thread(thread_id: u32) -> Result<(), ApiError> {
+    set_thread_deleted(thread_id, None, None).await
+}
+
+/// Sets [`Thread::deleted_at`] and cascades it to the [`Posts`][Post] of the thread
+///
+/// Requires [`Role::Moderator`]
+#[cfg(feature = "ssr")]
+pub(super) async fn set_thread_deleted(
+    thread_id: u32,
+    deleted_at: Option<bson::DateTime>,
+    reason_id: Option<u32>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+    let thread = helper::get_thread(thread_id, db.clone()).await?;
+    mod_reasons::check_reason(reason_id, &db).await?;
+
+    Thread::collection(&db)
+        .update_one(
+            bson::doc! {"id": thread_id},
+            bson::doc! {"$set": {"deleted_at": deleted_at}},
+        )
+        .await?;
+    Post::collection(&db)
+        .update_many(
+            bson::doc! {"thread_id": thread_id},
+            bson::doc! {"$set": {"thread_deleted": deleted_at.is_some()}},
+        )
+        .await?;
+
+    helper::refresh_latest_ids(&[thread.forum_id], &db).await?;
+    cache::invalidate_forums(&[thread.forum_id], &db).await?;
+
+    let moderator_id = moderator.id;
+    let event = if deleted_at.is_some() {
+        DomainEvent::ThreadDeleted {
+            thread_id,
+            moderator_id,
+            reason_id,
+        }
+    } else {
+        DomainEvent::ThreadRestored {
+            thread_id,
+            moderator_id,
+        }
+    };
+    events::emit(event, &db);
+    Ok(())
+}
+
+/// Maximum length of a tag in characters
+pub const MAX_TAG_LEN: usize = 24;
+/// Maximum number of tags of a [`Thread`]
+pub const MAX_TAGS: usize = 5;
+/// Maximum length of a [`Thread::prefix`] in characters
+pub const MAX_PREFIX_LEN: usize = 16;
+
+/// Trims and lowercases `tag`, turning spaces into dashes
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidTag`] if the tag is empty, longer than [`MAX_TAG_LEN`]
+///   or contains something other than letters, digits and dashes
+///
+/// # Example
+///
+/// ```
+/// use app::api::thread_tools::normalize_tag;
+///
+/// assert_eq!(normalize_tag("  Bug Report ").unwrap(), "bug-report");
+/// assert!(normalize_tag("").is_err());
+/// assert!(normalize_tag("c++").is_err());
+/// ```
+pub fn normalize_tag(tag: &str) -> Result<String, ApiError> {
+    let normalized = tag.trim().to_lowercase().replace(' ', "-");
+    let valid = !normalized.is_empty()
+        && normalized.chars().count() <= MAX_TAG_LEN
+        && normalized
+            .chars()
+            .all(|char| char.is_alphanumeric() || char == '-');
+    if valid {
+        Ok(normalized)
+    } else {
+        Err(ApiError::InvalidTag(tag.trim().to_string()))
+    }
+}
+
+/// Outcome of a batch operation for a single [`Thread`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThreadResult {
+    pub thread_id: u32,
+    pub result: Result<(), ApiError>,
+}
+
+/// Adds `add_tags` to and removes `remove_tags` from all [`Threads`][Thread] with `thread_ids`,
+/// and sets their [`Thread::prefix`] to `prefix` or removes it with `clear_prefix`
+///
+/// Tags are normalized with [`normalize_tag`] first.
+/// Each thread is updated on its own, so one that doesn't exist or would end up with
+/// too many tags doesn't stop the others; see the returned [`ThreadResults`][ThreadResult]
+///
+/// Requires [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NoThreadsSelected`] if `thread_ids` is empty
+/// - [`ApiError::InvalidTag`] if one of the tags isn't valid
+/// - [`ApiError::PrefixTooLong`] if `prefix` is longer than [`MAX_PREFIX_LEN`]
+#[server]
+pub async fn bulk_edit_thread_tags(
+    #[server(default)] thread_ids: Vec<u32>,
+    #[server(default)] add_tags: Vec<String>,
+    #[server(default)] remove_tags: Vec<String>,
+    prefix: Option<String>,
+    #[server(default)] clear_prefix: bool,
+) -> Result<Vec<ThreadResult>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Moderator).await?;
+
+    let mut thread_ids = thread_ids;
+    thread_ids.sort_unstable();
+    thread_ids.dedup();
+    if thread_ids.is_empty() {
+        return Err(ApiError::NoThreadsSelected);
+    }
+    let add_tags = add_tags
+        .iter()
+        .map(|tag| normalize_tag(tag))
+        .collect::<Result<Vec<_>, _>>()?;
+    let remove_tags = remove_tags
+        .iter()
+        .map(|tag| normalize_tag(tag))
+        .collect::<Result<Vec<_>, _>>()?;
+    let prefix = prefix
+        .map(|prefix| prefix.trim().to_string())
+        .filter(|prefix| !prefix.is_empty());
+    if prefix
+        .as_ref()
+        .is_some_and(|prefix| prefix.chars().count() > MAX_PREFIX_LEN)
+    {
+        return Err(ApiError::PrefixTooLong);
+    }
+
+    let mut results = Vec::with_capacity(thread_ids.len());
+    for thread_id in thread_ids {
+        let result = async {
+            let thread = helper::get_thread(thread_id, db.clone()).await?;
+
+            let mut tags = thread.tags;
+            tags.retain(|tag| !remove_tags.contains(tag));
+            for tag in &add_tags {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+            if tags.len() > MAX_TAGS {
+                return Err(ApiError::TooManyTags);
+            }
+            let prefix = if clear_prefix {
+                None
+            } else {
+                prefix.clone().or(thread.prefix)
+            };
+
+            Thread::collection(&db)
+                .update_one(
+                    bson::doc! {"id": thread_id},
+                    bson::doc! {"$set": {"tags": tags, "prefix": prefix}},
+                )
+                .await?;
+            Ok(())
+        };
+        results.push(ThreadResult {
+            thread_id,
+            result: result.await,
+        });
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,120 @@
+//! "2 people are writing a reply…": who's typing in a [`Thread`][super::Thread] right now
+//!
+//! Composers ping [`ping_typing`] while being typed in, at most every [`PING_INTERVAL_SECS`],
+//! and threads poll [`get_typing_count`] just as often. Pings are kept in the in-memory
+//! [`Typing`] and count for [`TYPING_TIMEOUT_SECS`], so whoever stops typing drops out
+//! on their own. Only logged-in users are counted, and only in threads they can see
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{helper, site_settings::GuestCapability};
+use crate::i18n::{Locale, translate_count};
+
+use leptos::prelude::*;
+
+/// How often a composer pings and a thread asks who's typing
+pub const PING_INTERVAL_SECS: u64 = 3;
+/// How long a ping counts, a few intervals so a slow request doesn't make someone flicker
+pub const TYPING_TIMEOUT_SECS: i64 = 8;
+
+/// When each user last typed in each thread, shared by all requests, cheap to clone
+#[cfg(feature = "ssr")]
+#[derive(Clone, Debug, Default)]
+pub struct Typing {
+    /// Keyed by thread and user id
+    pings:
+        std::sync::Arc<std::sync::RwLock<std::collections::HashMap<(u32, u32), jiff::Timestamp>>>,
+}
+#[cfg(feature = "ssr")]
+impl Typing {
+    /// Start of the window pings count in
+    fn window_start(now: jiff::Timestamp) -> jiff::Timestamp {
+        now - jiff::SignedDuration::from_secs(TYPING_TIMEOUT_SECS)
+    }
+
+    /// Marks `user_id` as typing in `thread_id` `now`, forgetting expired pings
+    /// whenever someone starts typing so the map doesn't grow forever
+    fn record(&self, thread_id: u32, user_id: u32, now: jiff::Timestamp) {
+        // the map is always left in a valid state, so poisoning doesn't matter
+        let mut pings = self
+            .pings
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if pings.insert((thread_id, user_id), now).is_none() {
+            let start = Self::window_start(now);
+            pings.retain(|_, pinged| *pinged >= start);
+        }
+    }
+
+    /// Amount of users typing in `thread_id`, without `except`
+    fn count(&self, thread_id: u32, except: Option<u32>, now: jiff::Timestamp) -> u32 {
+        let start = Self::window_start(now);
+        let pings = self
+            .pings
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let count = pings
+            .iter()
+            .filter(|((thread, user), pinged)| {
+                *thread == thread_id && Some(*user) != except && **pinged >= start
+            })
+            .count();
+        u32::try_from(count).unwrap_or(u32::MAX)
+    }
+}
+
+/// Text shown below the composer in `locale`, [`None`] if nobody else is typing
+///
+/// # Example
+///
+/// ```
+/// use app::api::typing::typing_text;
+/// use app::i18n::Locale;
+///
+/// assert_eq!(typing_text(Locale::En, 0), None);
+/// assert_eq!(typing_text(Locale::En, 1).as_deref(), Some("1 person is writing a reply…"));
+/// assert_eq!(typing_text(Locale::De, 2).as_deref(), Some("2 Personen schreiben eine Antwort…"));
+/// ```
+#[must_use]
+pub fn typing_text(locale: Locale, count: u32) -> Option<String> {
+    (count > 0).then(|| translate_count(locale, "thread.typing", i64::from(count)))
+}
+
+/// Records that the logged-in user is typing a reply in the thread with `thread_id`
+///
+/// Does nothing for guests
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if the thread doesn't exist or isn't visible to the user
+#[server]
+pub async fn ping_typing(thread_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let (Some(user), Some(typing)) = (
+        helper::get_current_user(&db).await?,
+        use_context::<Typing>(),
+    ) else {
+        return Ok(());
+    };
+    helper::get_visible_thread(thread_id, Some(&user), &db).await?;
+    typing.record(thread_id, user.id, jiff::Timestamp::now());
+    Ok(())
+}
+
+/// Returns how many other users typed in the thread with `thread_id`
+/// within the last [`TYPING_TIMEOUT_SECS`]
+///
+/// # Errors
+///
+/// - [`ApiError::LoginRequired`] if guests may not see threads
+/// - [`ApiError::NotFound`] if the thread doesn't exist or isn't visible to the user
+#[server]
+pub async fn get_typing_count(thread_id: u32) -> Result<u32, ApiError> {
+    let db = helper::get_db()?;
+    let Some(typing) = use_context::<Typing>() else {
+        return Ok(0);
+    };
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    helper::get_visible_thread(thread_id, user.as_ref(), &db).await?;
+    Ok(typing.count(thread_id, user.map(|user| user.id), jiff::Timestamp::now()))
+}
@@ -0,0 +1,326 @@
+//! Threads nobody replied to yet, for members who like to help answering them
+//!
+//! They're listed on their own page, for all forums or a single one, and members can opt in
+//! to an [`UnansweredDigest`] emailing them the list once a week. [`run_digest_sender`]
+//! checks for due digests every [`DIGEST_CHECK_INTERVAL_SECS`].
+//! Like other listings spanning several forums, only [public listings][Listing::Public]
+//! are drawn from
+
+use super::{ApiError, CollectionName, Thread};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, Post, bson, helper, inbound_email, mailer,
+    site_settings::GuestCapability,
+    user::{AccountDetails, User},
+    visibility::{self, Listing},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How old a thread has to be by default before it counts as unanswered
+pub const DEFAULT_MIN_AGE_HOURS: u32 = 24;
+/// Older threads are still listed, asking for more is just a year
+pub const MAX_MIN_AGE_HOURS: u32 = 365 * 24;
+/// How many unanswered threads are listed at most, the ones waiting the longest first
+pub const MAX_UNANSWERED_THREADS: i64 = 50;
+/// How much time passes between two [`UnansweredDigests`][UnansweredDigest] of a member
+pub const DIGEST_INTERVAL_DAYS: i64 = 7;
+/// How often [`run_digest_sender`] looks for due digests
+pub const DIGEST_CHECK_INTERVAL_SECS: u64 = 60 * 60;
+
+/// A [`Thread`] without replies, with when it was started
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnansweredThread {
+    pub thread: Thread,
+    pub forum_name: String,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+
+/// A member opted in to a weekly email of the unanswered threads
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UnansweredDigest {
+    pub user_id: u32,
+    /// [`None`] until the first digest was sent
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime::optional", default)]
+    pub last_sent_at: Option<jiff::Timestamp>,
+}
+impl CollectionName for UnansweredDigest {
+    fn collection_name() -> &'static str {
+        "unanswered_digests"
+    }
+}
+
+/// Returns the threads in `forum_ids` without replies that were started more than
+/// `min_age_hours` ago and aren't locked, solved or by `except_author_id`,
+/// at most [`MAX_UNANSWERED_THREADS`] and the oldest first
+#[cfg(feature = "ssr")]
+async fn find_unanswered(
+    forum_ids: Vec<u32>,
+    min_age_hours: u32,
+    except_author_id: Option<u32>,
+    db: &mongodb::Database,
+) -> Result<Vec<(Thread, jiff::Timestamp)>, ApiError> {
+    let cutoff =
+        jiff::Timestamp::now() - jiff::SignedDuration::from_hours(i64::from(min_age_hours));
+    let cutoff = bson::DateTime::from_millis(cutoff.as_millisecond());
+    let mut filter = bson::doc! {
+        "forum_id": {"$in": forum_ids},
+        "pending_review": {"$ne": true},
+        "deleted_at": null,
+        "locked": {"$ne": true},
+        "solved_at": null,
+        // only the origin post so far
+        "$expr": {"$eq": ["$latest_post_id", "$origin_post_id"]},
+    };
+    if let Some(author_id) = except_author_id {
+        filter.insert("author_id", bson::doc! {"$ne": author_id});
+    }
+    let pipeline = vec![
+        bson::doc! {"$match": filter},
+        bson::doc! {"$lookup": {
+            "from": Post::collection_name(),
+            "localField": "origin_post_id",
+            "foreignField": "id",
+            "as": "origin_post",
+        }},
+        bson::doc! {"$unwind": "$origin_post"},
+        bson::doc! {"$match": {
+            "origin_post.created_at": {"$lte": cutoff},
+        }},
+        bson::doc! {"$sort": {"origin_post.created_at": 1}},
+        bson::doc! {"$limit": MAX_UNANSWERED_THREADS},
+        bson::doc! {"$project": {"origin_post": 0}},
+    ];
+    let mut threads: Vec<Thread> = vec![];
+    let mut cursor = Thread::collection(db)
+        .aggregate(pipeline)
+        .with_type::<Thread>()
+        .await?;
+    while cursor.advance().await? {
+        threads.push(cursor.deserialize_current()?);
+    }
+
+    let origin_post_ids: Vec<u32> = threads.iter().map(|thread| thread.origin_post_id).collect();
+    let mut origin_posts: Vec<Post> = vec![];
+    let mut cursor = Post::collection(db)
+        .find(bson::doc! {"id": {"$in": origin_post_ids}})
+        .await?;
+    while cursor.advance().await? {
+        origin_posts.push(cursor.deserialize_current()?);
+    }
+    Ok(threads
+        .into_iter()
+        .filter_map(|thread| {
+            let origin_post = origin_posts
+                .iter()
+                .find(|post| post.id == thread.origin_post_id)?;
+            Some((thread, origin_post.created_at))
+        })
+        .collect())
+}
+
+/// Returns the threads nobody replied to that were started more than `min_age_hours` ago,
+/// in the [`Forum`][super::Forum] with `forum_id` or in all forums, the oldest first.
+/// Locked and solved threads are left out, `min_age_hours` is capped at [`MAX_MIN_AGE_HOURS`]
+///
+/// # Errors
+///
+/// - [`ApiError::LoginRequired`] if guests may not see threads
+/// - [`ApiError::NotFound`] if the forum doesn't exist or can't be seen
+#[server]
+pub async fn get_unanswered_threads(
+    forum_id: Option<u32>,
+    min_age_hours: u32,
+) -> Result<Vec<UnansweredThread>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::get_reader(GuestCapability::Threads, &db).await?;
+    let forums = helper::get_all_forums(&db).await?;
+    let forum_ids = match forum_id {
+        Some(forum_id) => {
+            helper::ensure_can_see_forum(forum_id, user.as_ref(), &db).await?;
+            vec![forum_id]
+        }
+        None => visibility::listed_forum_ids(&forums, user.as_ref(), Listing::Public),
+    };
+
+    let min_age_hours = min_age_hours.min(MAX_MIN_AGE_HOURS);
+    let threads = find_unanswered(forum_ids, min_age_hours, None, &db).await?;
+    Ok(threads
+        .into_iter()
+        .map(|(thread, created_at)| UnansweredThread {
+            forum_name: forums
+                .iter()
+                .find(|forum| forum.id == thread.forum_id)
+                .map(|forum| forum.name.clone())
+                .unwrap_or_default(),
+            thread,
+            created_at,
+        })
+        .collect())
+}
+
+/// Checks if the logged-in [`User`][super::user::User] gets an [`UnansweredDigest`],
+/// [`None`] for guests
+#[server]
+pub async fn get_unanswered_digest() -> Result<Option<bool>, ApiError> {
+    let db = helper::get_db()?;
+    let Some(user) = helper::get_current_user(&db).await? else {
+        return Ok(None);
+    };
+    let digest = UnansweredDigest::collection(&db)
+        .find_one(bson::doc! {"user_id": user.id})
+        .await?;
+    Ok(Some(digest.is_some()))
+}
+
+/// Opts the logged-in [`User`][super::user::User] in to or out of an [`UnansweredDigest`].
+/// The first one is sent within [`DIGEST_CHECK_INTERVAL_SECS`] if they have an email
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn set_unanswered_digest(enabled: bool) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let digest_col = UnansweredDigest::collection(&db);
+    let filter = bson::doc! {"user_id": user.id};
+    if enabled {
+        // keeps when the last one was sent if already opted in
+        digest_col
+            .update_one(filter, bson::doc! {"$setOnInsert": {"last_sent_at": null}})
+            .upsert(true)
+            .await?;
+    } else {
+        digest_col.delete_one(filter).await?;
+    }
+    Ok(())
+}
+
+/// The digest email telling `user` about `threads`, answers go to `reply_to`
+#[cfg(feature = "ssr")]
+fn digest_email(
+    to: &str,
+    user: &User,
+    threads: &[(Thread, jiff::Timestamp)],
+    reply_to: Option<String>,
+) -> mailer::Email {
+    let public_url = helper::public_url().unwrap_or_default();
+    let list = threads
+        .iter()
+        .map(|(thread, created_at)| {
+            format!(
+                "- {subject} (since {date})\n  {public_url}/thread/{id}\n",
+                subject = thread.display_subject(),
+                date = created_at.strftime("%F"),
+                id = thread.id,
+            )
+        })
+        .collect::<String>();
+    let reply_hint = if reply_to.is_some() {
+        "Answer this email to reply in the thread.\n"
+    } else {
+        ""
+    };
+    let text = format!(
+        "Hi {name},\n\n\
+         these threads are still waiting for their first reply, maybe you can help:\n\n\
+         {list}\n\
+         {reply_hint}\
+         You get this email every {DIGEST_INTERVAL_DAYS} days because you opted in to it.\n\
+         To stop it, turn it off on {public_url}/unanswered\n",
+        name = user.name,
+    );
+    mailer::Email::new(to, "Threads waiting for an answer".to_string(), text).reply_to(reply_to)
+}
+
+/// Emails all due [`UnansweredDigests`][UnansweredDigest], skipping members without
+/// an email or unanswered threads until the next one is due
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn send_due_digests(db: &mongodb::Database) -> Result<(), ApiError> {
+    let digest_col = UnansweredDigest::collection(db);
+    let now = jiff::Timestamp::now();
+    let due_before = now - jiff::SignedDuration::from_hours(DIGEST_INTERVAL_DAYS * 24);
+    let mut due: Vec<UnansweredDigest> = vec![];
+    let mut cursor = digest_col
+        .find(bson::doc! {"$or": [
+            {"last_sent_at": null},
+            {"last_sent_at": {"$lte": bson::DateTime::from_millis(due_before.as_millisecond())}},
+        ]})
+        .await?;
+    while cursor.advance().await? {
+        due.push(cursor.deserialize_current()?);
+    }
+    if due.is_empty() {
+        return Ok(());
+    }
+
+    let forums = helper::get_all_forums(db).await?;
+    let sent_at = bson::DateTime::from_millis(now.as_millisecond());
+    for digest in due {
+        digest_col
+            .update_one(
+                bson::doc! {"user_id": digest.user_id},
+                bson::doc! {"$set": {"last_sent_at": sent_at}},
+            )
+            .await?;
+        let user = match helper::get_user(digest.user_id, db).await {
+            Ok(user) => user,
+            // deleted accounts
+            Err(ApiError::NotFound(..)) => {
+                digest_col
+                    .delete_one(bson::doc! {"user_id": digest.user_id})
+                    .await?;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        let email = AccountDetails::collection(db)
+            .find_one(bson::doc! {"user_id": user.id})
+            .await?
+            .and_then(|details| details.email);
+        let Some(email) = email else {
+            continue;
+        };
+
+        let forum_ids = visibility::listed_forum_ids(&forums, Some(&user), Listing::Public);
+        let threads = find_unanswered(forum_ids, DEFAULT_MIN_AGE_HOURS, Some(user.id), db).await?;
+        if threads.is_empty() {
+            continue;
+        }
+        // an answer can only be posted in one thread
+        let reply_to = match threads.as_slice() {
+            [(single, _)] => inbound_email::reply_address(user.id, single.id, db).await?,
+            _ => None,
+        };
+        let email = digest_email(&email, &user, &threads, reply_to);
+        if let Err(err) = mailer::send(&email).await {
+            tracing::warn!(
+                "sending an unanswered threads digest to {} failed: {err}",
+                email.to
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs [`send_due_digests`] every [`DIGEST_CHECK_INTERVAL_SECS`] forever,
+/// meant to be spawned on startup
+#[cfg(feature = "ssr")]
+pub async fn run_digest_sender(db: mongodb::Database) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(DIGEST_CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(err) = send_due_digests(&db).await {
+            tracing::warn!("sending unanswered threads digests failed: {err}");
+        }
+    }
+}
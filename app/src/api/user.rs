@@ -0,0 +1,810 @@
+//! [`User`] accounts, their [`Sessions`][Session] and moderation of them
+//!
+//! Posting without being logged in is still possible,
+//! but sanctions like bans and mutes can only be enforced on [`Users`][User]
+
+use super::{ApiError, CollectionName, avatars::AvatarSize};
+#[cfg(feature = "ssr")]
+use super::{
+    Counter, DbErrorCode, GetCollection, bson,
+    events::{self, DomainEvent},
+    helper, login_alerts, mod_reasons,
+    site_settings::GuestCapability,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Minimum amount of characters of a password
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// How long a [`Session`] and its cookie stay valid
+#[cfg(feature = "ssr")]
+pub const SESSION_MAX_AGE_DAYS: i64 = 30;
+
+/// Permission level of a [`User`]
+///
+/// Ordered from least to most privileged, so you can compare with `<`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Role {
+    #[default]
+    Member,
+    Moderator,
+    Admin,
+}
+
+/// A ban or mute of a [`User`] issued by a moderator
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sanction {
+    pub reason: String,
+    /// The [`ModReason`][super::mod_reasons::ModReason] picked by the moderator
+    #[serde(default)]
+    pub reason_id: Option<u32>,
+    /// [`None`] means permanent
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime::optional", default)]
+    pub until: Option<jiff::Timestamp>,
+    /// Id of the [`User`] who issued this sanction
+    pub issued_by: u32,
+}
+impl Sanction {
+    /// Checks if the sanction is still in effect at the given point in time
+    #[must_use]
+    pub fn is_active(&self, now: jiff::Timestamp) -> bool {
+        self.until.is_none_or(|until| until > now)
+    }
+}
+
+/// Personal settings of a [`User`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Preferences {
+    /// [`Forums`][super::Forum] left out of the latest posts, see [`set_forum_hidden`]
+    #[serde(default)]
+    pub hidden_forum_ids: Vec<u32>,
+    /// IANA name of the time zone timestamps are shown in, see [`set_time_zone`]
+    ///
+    /// [`None`] to use the one detected by the browser
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    /// Names of the [`Categories`][super::Category] collapsed on the forum index,
+    /// see [`set_category_collapsed`]
+    #[serde(default)]
+    pub collapsed_categories: Vec<String>,
+}
+
+/// Represents a registered user
+///
+/// The password hash is stored separately in [`Credentials`]
+/// so that this can be sent to the client safely
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct User {
+    pub id: u32,
+    pub name: String,
+    #[serde(default)]
+    pub role: Role,
+    /// Will be de/serialized as [`bson::DateTime`] for communication with the db
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+    /// A banned user can't post or create threads
+    #[serde(default)]
+    pub ban: Option<Sanction>,
+    /// A muted user can't post or create threads,
+    /// meant for shorter "cool down" periods than a [`ban`][User::ban]
+    #[serde(default)]
+    pub mute: Option<Sanction>,
+    /// How often other users thanked this user for their posts
+    #[serde(default)]
+    pub thanks_received: u32,
+    /// Set by admins to mark the account as a real person
+    #[serde(default)]
+    pub verified: bool,
+    /// Ids of the [`UserGroups`][super::groups::UserGroup] this user is in
+    #[serde(default)]
+    pub group_ids: Vec<u32>,
+    /// The group deciding how the name is displayed, one of [`User::group_ids`]
+    #[serde(default)]
+    pub primary_group_id: Option<u32>,
+    /// Increased with every uploaded avatar, [`None`] if the user has none
+    #[serde(default)]
+    pub avatar_version: Option<u32>,
+    #[serde(default)]
+    pub preferences: Preferences,
+    /// Set once this duplicate account got merged into the one with this id,
+    /// see [`account_merge`][super::account_merge]
+    #[serde(default)]
+    pub merged_into: Option<u32>,
+}
+impl User {
+    /// Route the avatar is served at, [`None`] if the user has none
+    ///
+    /// Contains [`User::avatar_version`] so that browsers can cache it forever
+    #[must_use]
+    pub fn avatar_url(&self, size: AvatarSize) -> Option<String> {
+        self.avatar_version
+            .map(|version| format!("/avatars/{}/{}?v={version}", self.id, size.px()))
+    }
+}
+impl CollectionName for User {
+    fn collection_name() -> &'static str {
+        "users"
+    }
+}
+
+/// What anyone who may see profiles may know about a [`User`],
+/// i.e. without their [`Preferences`] and the details of their sanctions
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PublicUser {
+    pub id: u32,
+    pub name: String,
+    pub role: Role,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+    /// Whether a [`User::ban`] is in effect
+    pub banned: bool,
+    /// Whether a [`User::mute`] is in effect
+    pub muted: bool,
+    pub thanks_received: u32,
+    pub verified: bool,
+    pub group_ids: Vec<u32>,
+    pub primary_group_id: Option<u32>,
+    pub avatar_version: Option<u32>,
+    pub merged_into: Option<u32>,
+}
+impl From<User> for PublicUser {
+    fn from(user: User) -> Self {
+        let now = jiff::Timestamp::now();
+        Self {
+            id: user.id,
+            name: user.name,
+            role: user.role,
+            created_at: user.created_at,
+            banned: user.ban.is_some_and(|ban| ban.is_active(now)),
+            muted: user.mute.is_some_and(|mute| mute.is_active(now)),
+            thanks_received: user.thanks_received,
+            verified: user.verified,
+            group_ids: user.group_ids,
+            primary_group_id: user.primary_group_id,
+            avatar_version: user.avatar_version,
+            merged_into: user.merged_into,
+        }
+    }
+}
+
+/// Private details of a [`User`] collected on registration,
+/// only ever sent to admins (see [`bulk_users`][super::bulk_users])
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountDetails {
+    pub user_id: u32,
+    pub email: Option<String>,
+    pub registration_ip: Option<String>,
+}
+impl CollectionName for AccountDetails {
+    fn collection_name() -> &'static str {
+        "account_details"
+    }
+}
+
+/// The password hash of a [`User`], never leaves the server
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Credentials {
+    user_id: u32,
+    password_hash: String,
+}
+impl CollectionName for Credentials {
+    fn collection_name() -> &'static str {
+        "credentials"
+    }
+}
+
+/// A logged-in browser, identified by the token saved in a cookie
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Session {
+    pub token: String,
+    pub user_id: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+impl CollectionName for Session {
+    fn collection_name() -> &'static str {
+        "sessions"
+    }
+}
+
+/// Creates the unique index on the names of [`Users`][User], which [`insert_user`] relies on,
+/// and lets the db delete [`Sessions`][Session] older than [`SESSION_MAX_AGE_DAYS`]
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way,
+///   e.g. if existing users already share a name
+#[cfg(feature = "ssr")]
+pub async fn create_indexes(db: &mongodb::Database) -> Result<(), ApiError> {
+    use mongodb::{IndexModel, options::IndexOptions};
+
+    let unique_name = IndexModel::builder()
+        .keys(bson::doc! {"name": 1})
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    User::collection(db).create_index(unique_name).await?;
+
+    let max_age = jiff::SignedDuration::from_hours(SESSION_MAX_AGE_DAYS * 24);
+    let expiry = IndexModel::builder()
+        .keys(bson::doc! {"created_at": 1})
+        .options(
+            IndexOptions::builder()
+                .expire_after(max_age.unsigned_abs())
+                .build(),
+        )
+        .build();
+    Session::collection(db).create_index(expiry).await?;
+    Ok(())
+}
+
+/// Inserts `user`, whose name is checked by the unique index of [`create_indexes`]
+/// instead of looking it up first, which would race with concurrent registrations
+///
+/// # Errors
+///
+/// * [`ApiError::UsernameTaken`] if `user`'s name is already in use
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+async fn insert_user(user: &User, db: &mongodb::Database) -> Result<(), ApiError> {
+    match User::collection(db).insert_one(user).await {
+        Ok(_) => Ok(()),
+        Err(err) => match ApiError::from(err) {
+            ApiError::Db {
+                code: DbErrorCode::DuplicateKey,
+                ..
+            } => Err(ApiError::UsernameTaken(user.name.clone())),
+            err => Err(err),
+        },
+    }
+}
+
+/// Creates a new [`Session`] for the given `user_id` and sets its cookie in the response
+#[cfg(feature = "ssr")]
+async fn start_session(user_id: u32, db: &mongodb::Database) -> Result<(), ApiError> {
+    let token = helper::random_token();
+
+    let session = Session {
+        token: token.clone(),
+        user_id,
+        created_at: jiff::Timestamp::now(),
+    };
+    Session::collection(db).insert_one(&session).await?;
+
+    set_session_cookie(&token, SESSION_MAX_AGE_DAYS * 24 * 60 * 60);
+    Ok(())
+}
+
+/// Sets the [`Session`] cookie, a `max_age` of `0` removes it
+#[cfg(feature = "ssr")]
+fn set_session_cookie(token: &str, max_age: i64) {
+    let cookie = format!(
+        "{}={token}; Path=/; HttpOnly; SameSite=Lax; Max-Age={max_age}",
+        helper::SESSION_COOKIE
+    );
+    let Ok(cookie) = http::HeaderValue::from_str(&cookie) else {
+        unreachable!("token is hex, so the cookie is a valid header value")
+    };
+    expect_context::<leptos_axum::ResponseOptions>()
+        .append_header(http::header::SET_COOKIE, cookie);
+}
+
+/// Hashes `password` with argon2 and a random salt for [`Credentials`]
+#[cfg(feature = "ssr")]
+fn hash_password(password: &str) -> String {
+    use argon2::password_hash::{PasswordHasher, SaltString};
+
+    let Ok(salt) = SaltString::encode_b64(&rand::random::<[u8; 16]>()) else {
+        unreachable!("16 bytes are a valid salt length")
+    };
+    let Ok(password_hash) = argon2::Argon2::default().hash_password(password.as_bytes(), &salt)
+    else {
+        unreachable!("default argon2 params with a generated salt shouldn't fail")
+    };
+    password_hash.to_string()
+}
+
+/// Registers a new [`User`] with the [`Role::Member`] and logs them in
+///
+/// # Errors
+///
+/// - [`ApiError::EmptyUsername`] if `name` is empty
+/// - [`ApiError::PasswordTooShort`] if `password` is shorter than [`MIN_PASSWORD_LEN`]
+/// - [`ApiError::UsernameTaken`] if `name` is already in use
+#[server]
+pub async fn register(
+    name: String,
+    password: String,
+    email: Option<String>,
+) -> Result<User, ApiError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::EmptyUsername);
+    }
+    if password.chars().count() < MIN_PASSWORD_LEN {
+        return Err(ApiError::PasswordTooShort);
+    }
+
+    let db = helper::get_db()?;
+    let counter_col = Counter::collection(&db);
+    let id = helper::get_and_increment_id_of("user", counter_col).await?;
+
+    let user = User {
+        id,
+        name,
+        role: Role::Member,
+        created_at: jiff::Timestamp::now(),
+        ban: None,
+        mute: None,
+        thanks_received: 0,
+        verified: false,
+        group_ids: vec![],
+        primary_group_id: None,
+        avatar_version: None,
+        preferences: Preferences::default(),
+        merged_into: None,
+    };
+    insert_user(&user, &db).await?;
+
+    let credentials = Credentials {
+        user_id: id,
+        password_hash: hash_password(&password),
+    };
+    Credentials::collection(&db)
+        .insert_one(&credentials)
+        .await?;
+
+    let details = AccountDetails {
+        user_id: id,
+        email: email
+            .map(|email| email.trim().to_lowercase())
+            .filter(|email| !email.is_empty()),
+        registration_ip: helper::get_client_ip(),
+    };
+    AccountDetails::collection(&db).insert_one(&details).await?;
+
+    start_session(id, &db).await?;
+    // the device registered from is known, so logging in from it again isn't alerted
+    login_alerts::record_login(&user, false, &db).await?;
+    events::emit(DomainEvent::UserRegistered { user_id: id }, &db);
+
+    Ok(user)
+}
+
+/// Logs in as the [`User`] with the given `name` by starting a new [`Session`]
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidCredentials`] if the user doesn't exist or the password is wrong
+#[server]
+pub async fn login(name: String, password: String) -> Result<User, ApiError> {
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+
+    let db = helper::get_db()?;
+
+    let user_col = User::collection(&db);
+    let Some(user) = user_col.find_one(bson::doc! {"name": name.trim()}).await? else {
+        return Err(ApiError::InvalidCredentials);
+    };
+
+    let credentials_col = Credentials::collection(&db);
+    let Some(credentials) = credentials_col
+        .find_one(bson::doc! {"user_id": user.id})
+        .await?
+    else {
+        return Err(ApiError::InvalidCredentials);
+    };
+
+    let Ok(hash) = PasswordHash::new(&credentials.password_hash) else {
+        return Err(ApiError::InvalidCredentials);
+    };
+    if argon2::Argon2::default()
+        .verify_password(password.as_bytes(), &hash)
+        .is_err()
+    {
+        return Err(ApiError::InvalidCredentials);
+    }
+
+    start_session(user.id, &db).await?;
+    login_alerts::record_login(&user, true, &db).await?;
+
+    Ok(user)
+}
+
+/// Ends the current [`Session`] and removes its cookie
+#[server]
+pub async fn logout() -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+
+    if let Some(token) = helper::get_session_token() {
+        Session::collection(&db)
+            .delete_one(bson::doc! {"token": token})
+            .await?;
+    }
+    set_session_cookie("", 0);
+
+    Ok(())
+}
+
+/// Returns the logged-in [`User`] or [`None`] if not logged in
+#[server]
+pub async fn get_current_user() -> Result<Option<User>, ApiError> {
+    let db = helper::get_db()?;
+    helper::get_current_user(&db).await
+}
+
+/// Hides the [`Forum`][super::Forum] with `forum_id` from the latest posts
+/// of the logged-in [`User`] or shows it again
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::NotFound`] if `forum_id` isn't in use
+#[server]
+pub async fn set_forum_hidden(
+    forum_id: u32,
+    #[server(default)] hidden: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    helper::get_forum(forum_id, db.clone()).await?;
+
+    let update = if hidden {
+        bson::doc! {"$addToSet": {"preferences.hidden_forum_ids": forum_id}}
+    } else {
+        bson::doc! {"$pull": {"preferences.hidden_forum_ids": forum_id}}
+    };
+    User::collection(&db)
+        .update_one(bson::doc! {"id": user.id}, update)
+        .await?;
+
+    Ok(())
+}
+
+/// Collapses the section of the [`Category`][super::Category] named `category` on the forum index
+/// of the logged-in [`User`] or expands it again
+///
+/// Names of categories that don't exist are ignored when showing the index,
+/// so they aren't checked here
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn set_category_collapsed(
+    category: String,
+    #[server(default)] collapsed: bool,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let update = if collapsed {
+        bson::doc! {"$addToSet": {"preferences.collapsed_categories": category}}
+    } else {
+        bson::doc! {"$pull": {"preferences.collapsed_categories": category}}
+    };
+    User::collection(&db)
+        .update_one(bson::doc! {"id": user.id}, update)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets the time zone of the logged-in [`User`], see [`Preferences::time_zone`]
+///
+/// An empty `time_zone` resets it to the one detected by the browser.
+/// Returns the stored time zone
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::UnknownTimeZone`] if `time_zone` isn't an IANA time zone name
+#[server]
+pub async fn set_time_zone(time_zone: String) -> Result<Option<String>, ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+
+    let time_zone = Some(time_zone.trim().to_string()).filter(|name| !name.is_empty());
+    if let Some(name) = &time_zone {
+        jiff::tz::TimeZone::get(name).map_err(|_| ApiError::UnknownTimeZone(name.clone()))?;
+    }
+    User::collection(&db)
+        .update_one(
+            bson::doc! {"id": user.id},
+            bson::doc! {"$set": {"preferences.time_zone": &time_zone}},
+        )
+        .await?;
+
+    Ok(time_zone)
+}
+
+/// Looks up if the given `user_id` exists in the database and returns the [`PublicUser`] if so
+///
+/// # Errors
+///
+/// - [`ApiError::LoginRequired`] if guests may not see profiles
+/// - [`ApiError::NotFound`] if `user_id` isn't in use
+#[server]
+pub async fn get_user(user_id: u32) -> Result<PublicUser, ApiError> {
+    let db = helper::get_db()?;
+    helper::get_reader(GuestCapability::Profiles, &db).await?;
+    Ok(helper::get_user(user_id, &db).await?.into())
+}
+
+/// Deletes the given [`Users`][User] with their [`Credentials`], [`Sessions`][Session]
+/// and [`AccountDetails`], but not their content
+#[cfg(feature = "ssr")]
+pub(super) async fn delete_accounts(
+    user_ids: &[u32],
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let by_user_id = bson::doc! {"user_id": {"$in": user_ids}};
+    Credentials::collection(db)
+        .delete_many(by_user_id.clone())
+        .await?;
+    Session::collection(db)
+        .delete_many(by_user_id.clone())
+        .await?;
+    AccountDetails::collection(db)
+        .delete_many(by_user_id)
+        .await?;
+    User::collection(db)
+        .delete_many(bson::doc! {"id": {"$in": user_ids}})
+        .await?;
+    Ok(())
+}
+
+/// Ends all [`Sessions`][Session] of the given `user_id` and removes its [`Credentials`]
+/// and [`ApiKeys`][super::api_keys::ApiKey], so that nobody can act as that user anymore
+#[cfg(feature = "ssr")]
+pub(super) async fn disable_login(user_id: u32, db: &mongodb::Database) -> Result<(), ApiError> {
+    let by_user_id = bson::doc! {"user_id": user_id};
+    Credentials::collection(db)
+        .delete_one(by_user_id.clone())
+        .await?;
+    Session::collection(db)
+        .delete_many(by_user_id.clone())
+        .await?;
+    super::api_keys::ApiKey::collection(db)
+        .delete_many(by_user_id)
+        .await?;
+    Ok(())
+}
+
+/// Sets a new `password` for the given `user_id` and ends all of their [`Sessions`][Session],
+/// removing the cookie of the current one
+///
+/// Users whose login was [disabled][disable_login] stay disabled
+#[cfg(feature = "ssr")]
+pub(super) async fn reset_password(
+    user_id: u32,
+    password: &str,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let by_user_id = bson::doc! {"user_id": user_id};
+    Credentials::collection(db)
+        .update_one(
+            by_user_id.clone(),
+            bson::doc! {"$set": {"password_hash": hash_password(password)}},
+        )
+        .await?;
+    Session::collection(db).delete_many(by_user_id).await?;
+    set_session_cookie("", 0);
+    Ok(())
+}
+
+/// Creates a [`User`] without a password, who chooses one through an
+/// [`Invitation`][super::user_import::Invitation], in the [`UserGroup`][super::groups::UserGroup]
+/// with `group_id` if given
+#[cfg(feature = "ssr")]
+pub(super) async fn create_invited_user(
+    name: String,
+    email: String,
+    group_id: Option<u32>,
+    db: &mongodb::Database,
+) -> Result<User, ApiError> {
+    let id = helper::get_and_increment_id_of("user", Counter::collection(db)).await?;
+    let user = User {
+        id,
+        name,
+        role: Role::Member,
+        created_at: jiff::Timestamp::now(),
+        ban: None,
+        mute: None,
+        thanks_received: 0,
+        verified: false,
+        group_ids: group_id.into_iter().collect(),
+        primary_group_id: group_id,
+        avatar_version: None,
+        preferences: Preferences::default(),
+        merged_into: None,
+    };
+    insert_user(&user, db).await?;
+
+    let details = AccountDetails {
+        user_id: id,
+        email: Some(email),
+        registration_ip: None,
+    };
+    AccountDetails::collection(db).insert_one(&details).await?;
+    events::emit(DomainEvent::UserRegistered { user_id: id }, db);
+
+    Ok(user)
+}
+
+/// Sets the first `password` of a [`User`] created by [`create_invited_user`]
+/// and logs them in
+#[cfg(feature = "ssr")]
+pub(super) async fn set_first_password(
+    user: &User,
+    password: &str,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    Credentials::collection(db)
+        .update_one(
+            bson::doc! {"user_id": user.id},
+            bson::doc! {"$set": {"password_hash": hash_password(password)}},
+        )
+        .upsert(true)
+        .await?;
+    start_session(user.id, db).await?;
+    login_alerts::record_login(user, false, db).await
+}
+
+/// Which field of the [`User`] a [`Sanction`] is saved in
+#[cfg(feature = "ssr")]
+#[derive(Clone, Copy)]
+enum SanctionKind {
+    Ban,
+    Mute,
+}
+#[cfg(feature = "ssr")]
+impl SanctionKind {
+    fn field(self) -> &'static str {
+        match self {
+            Self::Ban => "ban",
+            Self::Mute => "mute",
+        }
+    }
+}
+
+/// Sets or lifts (`reason` being [`None`]) a [`Sanction`] of a [`User`]
+///
+/// Moderators can't sanction other moderators or admins, only admins can
+#[cfg(feature = "ssr")]
+async fn set_sanction(
+    user_id: u32,
+    kind: SanctionKind,
+    duration_hours: Option<u32>,
+    reason: Option<String>,
+    reason_id: Option<u32>,
+) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let moderator = helper::require_role(&db, Role::Moderator).await?;
+
+    let target = helper::get_user(user_id, &db).await?;
+    if target.role >= moderator.role {
+        return Err(ApiError::Forbidden);
+    }
+    mod_reasons::check_reason(reason_id, &db).await?;
+
+    let sanction = reason.map(|reason| Sanction {
+        reason,
+        reason_id,
+        until: duration_hours.map(|hours| {
+            jiff::Timestamp::now() + jiff::SignedDuration::from_hours(i64::from(hours))
+        }),
+        issued_by: moderator.id,
+    });
+    let event = {
+        let moderator_id = moderator.id;
+        match (kind, &sanction) {
+            (SanctionKind::Ban, Some(sanction)) => DomainEvent::UserBanned {
+                user_id,
+                moderator_id,
+                until: sanction.until,
+                reason: &sanction.reason,
+                reason_id: sanction.reason_id,
+            },
+            (SanctionKind::Ban, None) => DomainEvent::UserUnbanned {
+                user_id,
+                moderator_id,
+            },
+            (SanctionKind::Mute, Some(sanction)) => DomainEvent::UserMuted {
+                user_id,
+                moderator_id,
+                until: sanction.until,
+                reason: &sanction.reason,
+                reason_id: sanction.reason_id,
+            },
+            (SanctionKind::Mute, None) => DomainEvent::UserUnmuted {
+                user_id,
+                moderator_id,
+            },
+        }
+    };
+    let Ok(sanction_bson) = bson::to_bson(&sanction) else {
+        unreachable!("a Sanction is always serializable")
+    };
+
+    User::collection(&db)
+        .update_one(
+            bson::doc! {"id": user_id},
+            bson::doc! {"$set": {kind.field(): sanction_bson}},
+        )
+        .await?;
+    events::emit(event, &db);
+
+    Ok(())
+}
+
+/// Bans the [`User`] with the given `user_id` for `duration_hours` (permanently if [`None`])
+///
+/// `reason_id` is of the [`ModReason`][super::mod_reasons::ModReason] for the moderation log
+///
+/// Requires at least [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::NotFound`] if `user_id` isn't in use or the reason can't be picked
+#[server]
+pub async fn ban_user(
+    user_id: u32,
+    duration_hours: Option<u32>,
+    reason: String,
+    #[server(default)] reason_id: Option<u32>,
+) -> Result<(), ApiError> {
+    set_sanction(
+        user_id,
+        SanctionKind::Ban,
+        duration_hours,
+        Some(reason),
+        reason_id,
+    )
+    .await
+}
+
+/// Lifts the ban of the [`User`] with the given `user_id`
+///
+/// Requires at least [`Role::Moderator`]
+#[server]
+pub async fn unban_user(user_id: u32) -> Result<(), ApiError> {
+    set_sanction(user_id, SanctionKind::Ban, None, None, None).await
+}
+
+/// Mutes the [`User`] with the given `user_id` for `duration_hours` (permanently if [`None`])
+///
+/// `reason_id` is of the [`ModReason`][super::mod_reasons::ModReason] for the moderation log
+///
+/// Requires at least [`Role::Moderator`]
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] / [`ApiError::Forbidden`] if not permitted
+/// - [`ApiError::NotFound`] if `user_id` isn't in use or the reason can't be picked
+#[server]
+pub async fn mute_user(
+    user_id: u32,
+    duration_hours: Option<u32>,
+    reason: String,
+    #[server(default)] reason_id: Option<u32>,
+) -> Result<(), ApiError> {
+    set_sanction(
+        user_id,
+        SanctionKind::Mute,
+        duration_hours,
+        Some(reason),
+        reason_id,
+    )
+    .await
+}
+
+/// Lifts the mute of the [`User`] with the given `user_id`
+///
+/// Requires at least [`Role::Moderator`]
+#[server]
+pub async fn unmute_user(user_id: u32) -> Result<(), ApiError> {
+    set_sanction(user_id, SanctionKind::Mute, None, None, None).await
+}
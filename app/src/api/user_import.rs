@@ -0,0 +1,372 @@
+//! Admin import of [`Users`][super::user::User] from a CSV file, e.g. for communities
+//! migrating from a mailing list or another platform
+//!
+//! Every line is `username,email,group`, the group being the name of a
+//! [`UserGroup`][super::groups::UserGroup] or empty, and a header line is skipped.
+//! Imported accounts have no password yet: each gets an [`Invitation`] email
+//! with a link to choose one, see [`accept_invitation`].
+//! Like the [bulk user tools][super::bulk_users], an import is previewed first
+
+use super::user::User;
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, bson,
+    groups::UserGroup,
+    helper, mailer,
+    user::{self, AccountDetails, Role},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How long the link in an [`Invitation`] email works
+pub const INVITATION_DAYS: i64 = 14;
+/// Maximum number of users imported at once
+pub const MAX_IMPORT_ROWS: usize = 1000;
+
+/// A line of the imported CSV
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ImportRow {
+    /// 1-based, as shown by editors
+    pub line: usize,
+    pub username: String,
+    pub email: String,
+    /// Name of the [`UserGroup`][super::groups::UserGroup] to put the user in
+    pub group: Option<String>,
+}
+
+/// Outcome of the import of a single [`ImportRow`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportResult {
+    pub line: usize,
+    pub username: String,
+    pub result: Result<(), ApiError>,
+}
+
+/// What [`import_users`] did or would do
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImportReport {
+    pub results: Vec<ImportResult>,
+    /// `false` for a dry run
+    pub applied: bool,
+}
+
+/// An imported account waiting for its first password,
+/// whose [`token`][Invitation::token] is the secret part of the emailed link
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Invitation {
+    /// Random, see [`helper::random_token`][super::helper::random_token]
+    pub token: String,
+    pub user_id: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub expires_at: jiff::Timestamp,
+}
+impl CollectionName for Invitation {
+    fn collection_name() -> &'static str {
+        "invitations"
+    }
+}
+
+/// Path of the page accepting an [`Invitation`], e.g. `/account/invitation/3f2a...`
+#[must_use]
+pub fn invitation_path(token: &str) -> String {
+    format!("/account/invitation/{token}")
+}
+
+/// Splits a CSV `line` into its trimmed fields, which may be quoted with `"`
+///
+/// # Example
+///
+/// ```
+/// use app::api::user_import::split_csv_line;
+///
+/// assert_eq!(split_csv_line("alice, alice@example.com ,"), ["alice", "alice@example.com", ""]);
+/// assert_eq!(
+///     split_csv_line(r#""Smith, Bob",bob@example.com,"The ""Regulars""""#),
+///     ["Smith, Bob", "bob@example.com", "The \"Regulars\""]
+/// );
+/// ```
+#[must_use]
+pub fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            char => field.push(char),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Parses the lines of `csv` into [`ImportRows`][ImportRow], skipping empty lines and a header
+/// starting with `username`. Emails are lowercased, missing fields are empty
+///
+/// # Example
+///
+/// ```
+/// use app::api::user_import::{ImportRow, parse_rows};
+///
+/// let rows = parse_rows("username,email,group\nalice,Alice@Example.com,Regulars\n\nbob\n");
+/// assert_eq!(
+///     rows,
+///     [
+///         ImportRow {
+///             line: 2,
+///             username: "alice".into(),
+///             email: "alice@example.com".into(),
+///             group: Some("Regulars".into()),
+///         },
+///         ImportRow { line: 4, username: "bob".into(), email: String::new(), group: None },
+///     ]
+/// );
+/// ```
+#[must_use]
+pub fn parse_rows(csv: &str) -> Vec<ImportRow> {
+    csv.lines()
+        .enumerate()
+        .filter(|(i, line)| {
+            !line.trim().is_empty() && !(*i == 0 && line.trim().starts_with("username"))
+        })
+        .map(|(i, line)| {
+            let mut fields = split_csv_line(line).into_iter();
+            ImportRow {
+                line: i + 1,
+                username: fields.next().unwrap_or_default(),
+                email: fields.next().unwrap_or_default().to_lowercase(),
+                group: fields.next().filter(|group| !group.is_empty()),
+            }
+        })
+        .collect()
+}
+
+/// Whether `email` looks like an address: something, `@`, and a domain with a dot
+///
+/// # Example
+///
+/// ```
+/// use app::api::user_import::looks_like_email;
+///
+/// assert!(looks_like_email("alice@example.com"));
+/// assert!(!looks_like_email("alice@localhost"));
+/// assert!(!looks_like_email("alice example.com"));
+/// assert!(!looks_like_email("@example.com"));
+/// assert!(!looks_like_email("a@b@example.com"));
+/// ```
+#[must_use]
+pub fn looks_like_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+        && !email.chars().any(char::is_whitespace)
+}
+
+/// Checks `row` and returns the id of its [`UserGroup`], `accepted` being the valid rows
+/// before, whose usernames and emails are taken, too
+#[cfg(feature = "ssr")]
+async fn check_row(
+    row: &ImportRow,
+    accepted: &[&ImportRow],
+    db: &mongodb::Database,
+) -> Result<Option<u32>, ApiError> {
+    if row.username.is_empty() {
+        return Err(ApiError::EmptyUsername);
+    }
+    if !looks_like_email(&row.email) {
+        return Err(ApiError::InvalidEmail(row.email.clone()));
+    }
+    let name_taken = accepted
+        .iter()
+        .any(|earlier| earlier.username.eq_ignore_ascii_case(&row.username))
+        || User::collection(db)
+            .find_one(bson::doc! {"name": &row.username})
+            .await?
+            .is_some();
+    if name_taken {
+        return Err(ApiError::UsernameTaken(row.username.clone()));
+    }
+    let email_taken = accepted.iter().any(|earlier| earlier.email == row.email)
+        || AccountDetails::collection(db)
+            .find_one(bson::doc! {"email": &row.email})
+            .await?
+            .is_some();
+    if email_taken {
+        return Err(ApiError::EmailTaken(row.email.clone()));
+    }
+
+    let Some(group) = &row.group else {
+        return Ok(None);
+    };
+    let group = UserGroup::collection(db)
+        .find_one(bson::doc! {
+            "name": {"$regex": format!("^{}$", helper::escape_regex(group)), "$options": "i"},
+        })
+        .await?
+        .ok_or_else(|| ApiError::UnknownGroup(group.clone()))?;
+    Ok(Some(group.id))
+}
+
+/// Creates the account of `row` and emails its [`Invitation`] in the background
+#[cfg(feature = "ssr")]
+async fn import_row(
+    row: &ImportRow,
+    group_id: Option<u32>,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let user =
+        user::create_invited_user(row.username.clone(), row.email.clone(), group_id, db).await?;
+
+    let invitation = Invitation {
+        token: helper::random_token(),
+        user_id: user.id,
+        expires_at: jiff::Timestamp::now() + jiff::SignedDuration::from_hours(INVITATION_DAYS * 24),
+    };
+    Invitation::collection(db).insert_one(&invitation).await?;
+
+    let email = invitation_email(&row.email, &user.name, &invitation.token);
+    tokio::spawn(async move {
+        if let Err(err) = mailer::send(&email).await {
+            tracing::warn!("sending an invitation to {} failed: {err}", email.to);
+        }
+    });
+    Ok(())
+}
+
+/// The email inviting `name` to choose a password with the link of `token`
+#[cfg(feature = "ssr")]
+fn invitation_email(to: &str, name: &str, token: &str) -> mailer::Email {
+    let link = format!(
+        "{}{}",
+        helper::public_url().unwrap_or_default(),
+        invitation_path(token)
+    );
+    let text = format!(
+        "Hi {name},\n\n\
+         an account called {name} has been created for you on our forum.\n\
+         Open this link to choose your password and log in:\n\
+         {link}\n\n\
+         The link works for {INVITATION_DAYS} days.\n",
+    );
+    mailer::Email::new(to, "You're invited to Dafoerum".to_string(), text)
+}
+
+/// Imports the [`Users`][User] of the `csv` (see the [module docs][self]), sending each an
+/// [`Invitation`] email to choose a password
+///
+/// Only previews which lines would be imported unless `apply` is set.
+/// Every line is checked on its own, so an invalid one doesn't stop the others;
+/// see the [`ImportResults`][ImportResult]
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::NothingToImport`] if `csv` has no lines
+/// - [`ApiError::TooManyImportRows`] if it has more than [`MAX_IMPORT_ROWS`]
+#[server]
+pub async fn import_users(
+    csv: String,
+    #[server(default)] apply: bool,
+) -> Result<ImportReport, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let rows = parse_rows(&csv);
+    if rows.is_empty() {
+        return Err(ApiError::NothingToImport);
+    }
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(ApiError::TooManyImportRows);
+    }
+
+    let mut accepted = vec![];
+    let mut results = vec![];
+    for row in &rows {
+        let result = match check_row(row, &accepted, &db).await {
+            Ok(group_id) => {
+                accepted.push(row);
+                if apply {
+                    import_row(row, group_id, &db).await
+                } else {
+                    Ok(())
+                }
+            }
+            Err(err) => Err(err),
+        };
+        results.push(ImportResult {
+            line: row.line,
+            username: row.username.clone(),
+            result,
+        });
+    }
+
+    Ok(ImportReport {
+        results,
+        applied: apply,
+    })
+}
+
+/// Looks up the unexpired [`Invitation`] with `token`
+///
+/// # Errors
+///
+/// * [`ApiError::InvalidInvitationLink`] if there is none
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+async fn find_invitation(token: &str, db: &mongodb::Database) -> Result<Invitation, ApiError> {
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    Invitation::collection(db)
+        .find_one(bson::doc! {"token": token, "expires_at": {"$gt": now}})
+        .await?
+        .ok_or(ApiError::InvalidInvitationLink)
+}
+
+/// Returns the invited [`User`], anyone with the link can see it
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidInvitationLink`] if the link is unknown, expired or already used
+#[server]
+pub async fn get_invitation(token: String) -> Result<User, ApiError> {
+    let db = helper::get_db()?;
+    let invitation = find_invitation(&token, &db).await?;
+    helper::get_user(invitation.user_id, &db).await
+}
+
+/// Sets `password` as the first password of the invited [`User`] and logs them in,
+/// the link stops working afterwards
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidInvitationLink`] if the link is unknown, expired or already used
+/// - [`ApiError::PasswordTooShort`] if `password` is shorter than [`user::MIN_PASSWORD_LEN`]
+#[server]
+pub async fn accept_invitation(token: String, password: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let invitation = find_invitation(&token, &db).await?;
+    if password.chars().count() < user::MIN_PASSWORD_LEN {
+        return Err(ApiError::PasswordTooShort);
+    }
+
+    let user = helper::get_user(invitation.user_id, &db).await?;
+    user::set_first_password(&user, &password, &db).await?;
+    Invitation::collection(&db)
+        .delete_many(bson::doc! {"user_id": invitation.user_id})
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,145 @@
+//! Uniform cleanup and length limits of what members write
+//!
+//! Text is [sanitized][sanitize] before it's checked: control characters are stripped,
+//! line breaks normalized and surrounding whitespace trimmed, single-line fields like the
+//! subject lose their line breaks too. Then it has to fit the [`LengthLimits`] admins set in
+//! the [`SiteSettings`][super::site_settings::SiteSettings]. Violations are
+//! [`ApiError::Validation`] naming the [`Field`], so forms can show them below it
+
+use super::ApiError;
+
+use serde::{Deserialize, Serialize};
+
+/// A field members write into
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    /// Of a [`Thread`][super::Thread]
+    Subject,
+    /// Of a [`Post`][super::Post]
+    Content,
+}
+impl Field {
+    /// Line breaks are turned into spaces in single-line fields
+    #[must_use]
+    pub const fn is_single_line(self) -> bool {
+        matches!(self, Self::Subject)
+    }
+}
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Subject => "subject",
+            Self::Content => "content",
+        })
+    }
+}
+
+/// What a [`Field`] has to fulfill
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Rule {
+    Required,
+    /// At most this many characters
+    MaxLength(u32),
+}
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Required => f.write_str("can't be empty"),
+            Self::MaxLength(max) => write!(f, "can be at most {max} characters long"),
+        }
+    }
+}
+
+/// Maximum lengths of the [`Fields`][Field] in characters, set by admins
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthLimits {
+    pub max_subject_len: u32,
+    pub max_content_len: u32,
+}
+impl Default for LengthLimits {
+    fn default() -> Self {
+        Self {
+            max_subject_len: 200,
+            max_content_len: 20_000,
+        }
+    }
+}
+impl LengthLimits {
+    #[must_use]
+    pub const fn max_len(&self, field: Field) -> u32 {
+        match field {
+            Field::Subject => self.max_subject_len,
+            Field::Content => self.max_content_len,
+        }
+    }
+}
+
+/// Strips control characters but line breaks and tabs, normalizes line breaks to `\n`
+/// and trims surrounding whitespace. Single-line fields get spaces instead of line breaks
+///
+/// # Example
+///
+/// ```
+/// use app::api::validation::{Field, sanitize};
+///
+/// assert_eq!(sanitize("  Hello\u{0}\r\nworld\t\u{7} ", Field::Content), "Hello\nworld");
+/// assert_eq!(sanitize("Two\nlines", Field::Subject), "Two lines");
+/// assert_eq!(sanitize("\u{1b}[31m red", Field::Subject), "[31m red");
+/// ```
+#[must_use]
+pub fn sanitize(text: &str, field: Field) -> String {
+    let sanitized: String = text
+        .replace("\r\n", "\n")
+        .chars()
+        .filter_map(|char| match char {
+            '\n' | '\t' if field.is_single_line() => Some(' '),
+            '\n' | '\t' => Some(char),
+            char if char.is_control() => None,
+            char => Some(char),
+        })
+        .collect();
+    sanitized.trim().to_string()
+}
+
+/// Returns the [sanitized][sanitize] `text` if it fulfills the [`Rules`][Rule] of `field`
+///
+/// # Errors
+///
+/// * [`ApiError::Validation`] with the first [`Rule`] that `text` breaks
+///
+/// # Example
+///
+/// ```
+/// use app::api::ApiError;
+/// use app::api::validation::{Field, LengthLimits, Rule, validate};
+///
+/// let limits = LengthLimits { max_subject_len: 5, ..LengthLimits::default() };
+/// assert_eq!(validate(" Hi ", Field::Subject, &limits).unwrap(), "Hi");
+/// assert!(matches!(
+///     validate(" \u{0} ", Field::Subject, &limits),
+///     Err(ApiError::Validation { field: Field::Subject, rule: Rule::Required })
+/// ));
+/// assert!(matches!(
+///     validate("Hello!", Field::Subject, &limits),
+///     Err(ApiError::Validation { field: Field::Subject, rule: Rule::MaxLength(5) })
+/// ));
+/// ```
+pub fn validate(text: &str, field: Field, limits: &LengthLimits) -> Result<String, ApiError> {
+    let text = sanitize(text, field);
+    if text.is_empty() {
+        return Err(ApiError::Validation {
+            field,
+            rule: Rule::Required,
+        });
+    }
+    let max = limits.max_len(field);
+    if text.chars().count() > usize::try_from(max).unwrap_or(usize::MAX) {
+        return Err(ApiError::Validation {
+            field,
+            rule: Rule::MaxLength(max),
+        });
+    }
+    Ok(text)
+}
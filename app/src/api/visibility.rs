@@ -0,0 +1,75 @@
+//! Which [`Forums`][Forum] listings of several forums draw their threads and posts from
+//!
+//! Latest, search, feeds, the sitemap and the dashboard all take their forums from
+//! [`listed_forum_ids`] or check them with [`ensure_listed`], so a forum can't leak through
+//! one of them while being hidden in another.
+//!
+//! [Staff-only][Forum::is_staff_only] forums are for internal discussions. They're left out of
+//! [public listings][Listing::Public] even for staff, who read them in
+//! [their own Latest][super::get_latest_staff_posts] instead
+
+use super::{ApiError, Forum, helper, user::User};
+
+use mongodb::Database;
+
+/// What a listing shows, see [`is_listed`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Listing {
+    /// Everything the user can see, e.g. their own threads
+    All,
+    /// Listings for everyone, without staff-only forums
+    Public,
+    /// Only staff-only forums
+    Staff,
+}
+
+/// Checks if the [`Forum`] with `forum_id` or one above it is [staff-only][Forum::is_staff_only]
+#[must_use]
+pub fn is_in_staff_only(forums: &[Forum], forum_id: u32) -> bool {
+    helper::forum_ancestry(forums, forum_id)
+        .iter()
+        .any(Forum::is_staff_only)
+}
+
+/// Checks if the content of the [`Forum`] with `forum_id` belongs in `listing`
+/// for `user` (anonymous if [`None`]), who has to [see it][helper::can_see_forum] in any case
+#[must_use]
+pub fn is_listed(forums: &[Forum], forum_id: u32, user: Option<&User>, listing: Listing) -> bool {
+    helper::can_see_forum(forums, forum_id, user)
+        && match listing {
+            Listing::All => true,
+            Listing::Public => !is_in_staff_only(forums, forum_id),
+            Listing::Staff => is_in_staff_only(forums, forum_id),
+        }
+}
+
+/// Ids of the `forums` whose content belongs in `listing` for `user`, see [`is_listed`]
+#[must_use]
+pub fn listed_forum_ids(forums: &[Forum], user: Option<&User>, listing: Listing) -> Vec<u32> {
+    forums
+        .iter()
+        .filter(|forum| is_listed(forums, forum.id, user, listing))
+        .map(|forum| forum.id)
+        .collect()
+}
+
+/// Ensures that the content of the [`Forum`] with `forum_id` belongs in `listing` for `user`,
+/// see [`is_listed`]
+///
+/// # Errors
+///
+/// * [`ApiError::NotFound`] if it doesn't, the same as for forums that don't exist
+/// * [`ApiError::Db`] if the db connection fails in any way
+pub async fn ensure_listed(
+    forum_id: u32,
+    user: Option<&User>,
+    listing: Listing,
+    db: &Database,
+) -> Result<(), ApiError> {
+    let forums = helper::get_all_forums(db).await?;
+    if is_listed(&forums, forum_id, user, listing) {
+        Ok(())
+    } else {
+        Err(ApiError::NotFound("forum".into(), forum_id))
+    }
+}
@@ -0,0 +1,393 @@
+//! Emails batching the new posts in the [watched threads][super::my_content::WatchedThread]
+//! of a [`User`][super::user::User], daily or weekly as set in their [`WatchDigest`]
+//!
+//! [`run_digest_sender`] checks for due digests every [`DIGEST_CHECK_INTERVAL_SECS`].
+//! Only users with an email get them, and every digest has a link turning them off
+//! without logging in, see [`unsubscribe_path`]. Digests about a single thread
+//! can be answered to reply in it, see [`reply_address`]
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{
+    GetCollection, Post, Thread, bson, helper,
+    inbound_email::reply_address,
+    mailer,
+    my_content::WatchedThread,
+    subscriptions::WatchLevel,
+    user::{AccountDetails, User},
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How often [`run_digest_sender`] looks for due [`WatchDigests`][WatchDigest]
+pub const DIGEST_CHECK_INTERVAL_SECS: u64 = 60 * 60;
+
+/// How often a [`WatchDigest`] is sent
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DigestFrequency {
+    #[default]
+    Off,
+    Daily,
+    Weekly,
+}
+impl DigestFrequency {
+    pub const ALL: [Self; 3] = [Self::Off, Self::Daily, Self::Weekly];
+
+    /// Translation key of the human-readable name of the frequency
+    #[must_use]
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Off => "digest_frequency.off",
+            Self::Daily => "digest_frequency.daily",
+            Self::Weekly => "digest_frequency.weekly",
+        }
+    }
+
+    /// Days between two digests, [`None`] if none are sent
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::api::watch_digests::DigestFrequency;
+    ///
+    /// assert_eq!(DigestFrequency::Off.interval_days(), None);
+    /// assert_eq!(DigestFrequency::Weekly.interval_days(), Some(7));
+    /// ```
+    #[must_use]
+    pub fn interval_days(self) -> Option<i64> {
+        match self {
+            Self::Off => None,
+            Self::Daily => Some(1),
+            Self::Weekly => Some(7),
+        }
+    }
+
+    /// For use in db queries
+    #[cfg(feature = "ssr")]
+    fn to_bson(self) -> bson::Bson {
+        let Ok(frequency) = bson::to_bson(&self) else {
+            unreachable!("a unit enum is always serializable")
+        };
+        frequency
+    }
+}
+
+/// How often a [`User`][super::user::User] gets a digest of their watched threads
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WatchDigest {
+    pub user_id: u32,
+    pub frequency: DigestFrequency,
+    /// Random, see [`helper::random_token`], the secret part of the unsubscribe link
+    pub unsubscribe_token: String,
+    /// Posts after this are in the next digest, set when turning digests on
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub last_sent_at: jiff::Timestamp,
+}
+impl CollectionName for WatchDigest {
+    fn collection_name() -> &'static str {
+        "watch_digests"
+    }
+}
+
+/// Path of the page turning off the [`WatchDigest`] with `token`,
+/// e.g. `/digest/unsubscribe/3f2a...`
+#[must_use]
+pub fn unsubscribe_path(token: &str) -> String {
+    format!("/digest/unsubscribe/{token}")
+}
+
+/// Returns how often the logged-in [`User`][super::user::User] gets a [`WatchDigest`],
+/// [`None`] if not logged in
+#[server]
+pub async fn get_digest_frequency() -> Result<Option<DigestFrequency>, ApiError> {
+    let db = helper::get_db()?;
+    let Some(user) = helper::get_current_user(&db).await? else {
+        return Ok(None);
+    };
+    let frequency = WatchDigest::collection(&db)
+        .find_one(bson::doc! {"user_id": user.id})
+        .await?
+        .map(|digest| digest.frequency)
+        .unwrap_or_default();
+    Ok(Some(frequency))
+}
+
+/// Sets how often the logged-in [`User`][super::user::User] gets a [`WatchDigest`]
+///
+/// Turning digests on starts them from now, so the first one doesn't contain everything
+/// since they were last turned off
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn set_digest_frequency(frequency: DigestFrequency) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let digest_col = WatchDigest::collection(&db);
+    let by_user = bson::doc! {"user_id": user.id};
+
+    let was_on = digest_col
+        .find_one(by_user.clone())
+        .await?
+        .is_some_and(|digest| digest.frequency != DigestFrequency::Off);
+    let mut set = bson::doc! {"frequency": frequency.to_bson()};
+    if !was_on {
+        let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+        set.insert("last_sent_at", now);
+    }
+    digest_col
+        .update_one(
+            by_user,
+            bson::doc! {
+                "$set": set,
+                "$setOnInsert": {"unsubscribe_token": helper::random_token()},
+            },
+        )
+        .upsert(true)
+        .await?;
+
+    Ok(())
+}
+
+/// Turns off the [`WatchDigest`] with `token`, anyone with the link can do so
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidUnsubscribeLink`] if no digest has the token
+#[server]
+pub async fn unsubscribe_digest(token: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let result = WatchDigest::collection(&db)
+        .update_one(
+            bson::doc! {"unsubscribe_token": token},
+            bson::doc! {"$set": {"frequency": DigestFrequency::Off.to_bson()}},
+        )
+        .await?;
+    if result.matched_count == 0 {
+        return Err(ApiError::InvalidUnsubscribeLink);
+    }
+    Ok(())
+}
+
+/// New posts by others in one watched [`Thread`] since the last digest
+#[cfg(feature = "ssr")]
+struct ThreadActivity {
+    thread: Thread,
+    new_posts: u32,
+    first_new_post_id: u32,
+}
+
+/// Collects the activity in the threads watched by `user` after `since`,
+/// leaving out their own posts, muted threads and threads they can't see (anymore)
+#[cfg(feature = "ssr")]
+async fn find_activity(
+    user: &User,
+    since: jiff::Timestamp,
+    db: &mongodb::Database,
+) -> Result<Vec<ThreadActivity>, ApiError> {
+    let mut watched: Vec<WatchedThread> = vec![];
+    let mut cursor = WatchedThread::collection(db)
+        .find(bson::doc! {"user_id": user.id, "level": {"$ne": WatchLevel::Muted.to_bson()}})
+        .await?;
+    while cursor.advance().await? {
+        watched.push(cursor.deserialize_current()?);
+    }
+    if watched.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let thread_ids: Vec<u32> = watched.iter().map(|watched| watched.thread_id).collect();
+    let mut posts: Vec<Post> = vec![];
+    let mut cursor = Post::collection(db)
+        .find(bson::doc! {
+            "thread_id": {"$in": thread_ids.clone()},
+            "created_at": {"$gt": bson::DateTime::from_millis(since.as_millisecond())},
+            "author_id": {"$ne": user.id},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+            "thread_deleted": {"$ne": true},
+        })
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    while cursor.advance().await? {
+        posts.push(cursor.deserialize_current()?);
+    }
+    if posts.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let forums = helper::get_all_forums(db).await?;
+    let mut activity: Vec<ThreadActivity> = vec![];
+    let mut cursor = Thread::collection(db)
+        .find(bson::doc! {
+            "id": {"$in": thread_ids},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .sort(bson::doc! {"latest_post_id": -1})
+        .await?;
+    while cursor.advance().await? {
+        let thread: Thread = cursor.deserialize_current()?;
+        if !helper::can_see_forum(&forums, thread.forum_id, Some(user)) {
+            continue;
+        }
+        // posts from before the thread was watched don't count
+        let watched_at = watched
+            .iter()
+            .find(|watched| watched.thread_id == thread.id)
+            .map_or(since, |watched| watched.watched_at);
+        let new_posts: Vec<&Post> = posts
+            .iter()
+            .filter(|post| post.thread_id == thread.id && post.created_at > watched_at)
+            .collect();
+        let Some(first_new_post) = new_posts.first() else {
+            continue;
+        };
+        activity.push(ThreadActivity {
+            first_new_post_id: first_new_post.id,
+            new_posts: u32::try_from(new_posts.len()).unwrap_or(u32::MAX),
+            thread,
+        });
+    }
+    Ok(activity)
+}
+
+/// The digest email telling `user` about `activity`, answers go to `reply_to`
+#[cfg(feature = "ssr")]
+fn digest_email(
+    to: &str,
+    user: &User,
+    digest: &WatchDigest,
+    activity: &[ThreadActivity],
+    reply_to: Option<String>,
+) -> mailer::Email {
+    let public_url = helper::public_url().unwrap_or_default();
+    let list = activity
+        .iter()
+        .map(|activity| {
+            format!(
+                "- {subject}: {new_posts} new {posts}\n  {public_url}/post/{id}\n",
+                subject = activity.thread.display_subject(),
+                new_posts = activity.new_posts,
+                posts = if activity.new_posts == 1 {
+                    "post"
+                } else {
+                    "posts"
+                },
+                id = activity.first_new_post_id,
+            )
+        })
+        .collect::<String>();
+    let reply_hint = if reply_to.is_some() {
+        "Answer this email to reply in the thread.\n"
+    } else {
+        ""
+    };
+    let text = format!(
+        "Hi {name},\n\n\
+         this is what happened in the threads you watch:\n\n\
+         {list}\n\
+         {reply_hint}\
+         You can change how often you get this email on your profile.\n\
+         To stop getting it, open this link:\n\
+         {public_url}{unsubscribe}\n",
+        name = user.name,
+        unsubscribe = unsubscribe_path(&digest.unsubscribe_token),
+    );
+    let subject = match digest.frequency {
+        DigestFrequency::Weekly => "Your weekly digest of watched threads",
+        _ => "Your daily digest of watched threads",
+    };
+    mailer::Email::new(to, subject.to_string(), text).reply_to(reply_to)
+}
+
+/// Emails all due [`WatchDigests`][WatchDigest], skipping users without an email
+/// or new posts until the next one is due
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn send_due_digests(db: &mongodb::Database) -> Result<(), ApiError> {
+    let digest_col = WatchDigest::collection(db);
+    let now = jiff::Timestamp::now();
+    let due_conditions = DigestFrequency::ALL
+        .into_iter()
+        .filter_map(|frequency| {
+            let days = frequency.interval_days()?;
+            let due_before = now - jiff::SignedDuration::from_hours(days * 24);
+            Some(bson::doc! {
+                "frequency": frequency.to_bson(),
+                "last_sent_at": {"$lte": bson::DateTime::from_millis(due_before.as_millisecond())},
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut due: Vec<WatchDigest> = vec![];
+    let mut cursor = digest_col.find(bson::doc! {"$or": due_conditions}).await?;
+    while cursor.advance().await? {
+        due.push(cursor.deserialize_current()?);
+    }
+
+    let sent_at = bson::DateTime::from_millis(now.as_millisecond());
+    for digest in due {
+        digest_col
+            .update_one(
+                bson::doc! {"user_id": digest.user_id},
+                bson::doc! {"$set": {"last_sent_at": sent_at}},
+            )
+            .await?;
+        let user = match helper::get_user(digest.user_id, db).await {
+            Ok(user) => user,
+            // deleted accounts
+            Err(ApiError::NotFound(..)) => {
+                digest_col
+                    .delete_one(bson::doc! {"user_id": digest.user_id})
+                    .await?;
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        let email = AccountDetails::collection(db)
+            .find_one(bson::doc! {"user_id": user.id})
+            .await?
+            .and_then(|details| details.email);
+        let Some(email) = email else {
+            continue;
+        };
+
+        let activity = find_activity(&user, digest.last_sent_at, db).await?;
+        if activity.is_empty() {
+            continue;
+        }
+        // an answer can only be posted in one thread
+        let reply_to = match activity.as_slice() {
+            [single] => reply_address(user.id, single.thread.id, db).await?,
+            _ => None,
+        };
+        let email = digest_email(&email, &user, &digest, &activity, reply_to);
+        if let Err(err) = mailer::send(&email).await {
+            tracing::warn!(
+                "sending a watched threads digest to {} failed: {err}",
+                email.to
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs [`send_due_digests`] every [`DIGEST_CHECK_INTERVAL_SECS`] forever,
+/// meant to be spawned on startup
+#[cfg(feature = "ssr")]
+pub async fn run_digest_sender(db: mongodb::Database) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(DIGEST_CHECK_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(err) = send_due_digests(&db).await {
+            tracing::warn!("sending watched threads digests failed: {err}");
+        }
+    }
+}
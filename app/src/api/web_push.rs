@@ -0,0 +1,564 @@
+//! Push notifications about replies, shown by the browser even when the forum isn't open
+//!
+//! Members subscribe in their settings, which registers the [`SERVICE_WORKER_PATH`]
+//! service worker and stores the endpoint of its [`PushSubscription`]. When someone replies
+//! in a thread, [`notify_reply`] stores a [`PendingPush`] for every subscription of its
+//! watchers and its author and pushes to them, signed with the `VAPID_PRIVATE_KEY` env var.
+//! New threads are pushed to the watchers of their forum by [`notify_thread`]. Who is notified
+//! depends on the [`WatchLevels`][super::subscriptions::WatchLevel] they set.
+//!
+//! Pushes carry no payload, so they don't have to be encrypted for each browser:
+//! the service worker fetches what to show from [`PENDING_PUSH_PATH`] with the member's
+//! session cookie. Without `VAPID_PRIVATE_KEY` push notifications are disabled
+//!
+//! A key pair can be generated with e.g. `npx web-push generate-vapid-keys`,
+//! the private key is the base64url encoded 32 byte scalar. `VAPID_SUBJECT` tells
+//! push services whom to contact, e.g. `mailto:admin@forum.example`,
+//! and defaults to the `PUBLIC_URL`
+
+use super::ApiError;
+#[cfg(feature = "ssr")]
+use super::{
+    CollectionName, Forum, GetCollection, Post, Thread, bson, helper,
+    my_content::WatchedThread,
+    read_markers::ReadMarker,
+    subscriptions::{self, WatchLevel},
+    user::User,
+};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Where the service worker receiving pushes is served from, see `public/sw.js`
+pub const SERVICE_WORKER_PATH: &str = "/sw.js";
+/// Where the service worker fetches the next [`PendingPush`] from, see [`take_pending_push`]
+pub const PENDING_PUSH_PATH: &str = "/push/pending";
+/// How long push services keep a push for a browser that's offline
+#[cfg(feature = "ssr")]
+const PUSH_TTL_SECS: u32 = 24 * 60 * 60;
+/// How long push services should accept a VAPID token, at most a day is allowed
+#[cfg(feature = "ssr")]
+const VAPID_TOKEN_HOURS: i64 = 12;
+/// How long a push service may take to accept a push
+#[cfg(feature = "ssr")]
+const SEND_TIMEOUT_SECS: u64 = 10;
+/// Longest body of a notification, the start of the reply
+#[cfg(feature = "ssr")]
+const MAX_BODY_CHARS: usize = 140;
+
+/// Hosts of the push services browsers use, pushes are only sent to these and their
+/// subdomains so endpoints can't point the server at arbitrary urls
+const PUSH_SERVICE_HOSTS: &[&str] = &[
+    "fcm.googleapis.com",
+    "push.services.mozilla.com",
+    "notify.windows.com",
+    "push.apple.com",
+];
+
+/// A browser of a [`User`] subscribed to push notifications
+#[cfg(feature = "ssr")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushSubscription {
+    pub user_id: u32,
+    /// Url of the browser's push service to send pushes to, unique
+    pub endpoint: String,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+#[cfg(feature = "ssr")]
+impl CollectionName for PushSubscription {
+    fn collection_name() -> &'static str {
+        "push_subscriptions"
+    }
+}
+
+/// What a notification shows
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushNotification {
+    pub title: String,
+    pub body: String,
+    /// Opened when the notification is clicked
+    pub url: String,
+}
+
+/// A [`PushNotification`] waiting to be fetched by the service worker of `endpoint`
+#[cfg(feature = "ssr")]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingPush {
+    pub user_id: u32,
+    pub endpoint: String,
+    pub notification: PushNotification,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+#[cfg(feature = "ssr")]
+impl CollectionName for PendingPush {
+    fn collection_name() -> &'static str {
+        "pending_pushes"
+    }
+}
+
+/// Host of `endpoint` if it's an https url of one of the [`PUSH_SERVICE_HOSTS`]
+fn push_service_host(endpoint: &str) -> Option<&str> {
+    let host = endpoint.strip_prefix("https://")?.split('/').next()?;
+    let is_host_char = |char: char| char.is_ascii_alphanumeric() || matches!(char, '.' | '-');
+    if !host.chars().all(is_host_char) {
+        return None;
+    }
+    PUSH_SERVICE_HOSTS
+        .iter()
+        .any(|service| {
+            host == *service
+                || host
+                    .strip_suffix(service)
+                    .is_some_and(|subdomain| subdomain.ends_with('.'))
+        })
+        .then_some(host)
+}
+
+/// Checks if `endpoint` is of a push service the forum sends pushes to
+///
+/// # Example
+///
+/// ```
+/// use app::api::web_push::is_push_service;
+///
+/// assert!(is_push_service("https://fcm.googleapis.com/fcm/send/abc"));
+/// assert!(is_push_service("https://updates.push.services.mozilla.com/wpush/v2/abc"));
+/// assert!(!is_push_service("http://fcm.googleapis.com/fcm/send/abc"));
+/// assert!(!is_push_service("https://evil-fcm.googleapis.com.example/abc"));
+/// assert!(!is_push_service("https://evil.example?.fcm.googleapis.com/abc"));
+/// assert!(!is_push_service("https://localhost:3000/abc"));
+/// ```
+#[must_use]
+pub fn is_push_service(endpoint: &str) -> bool {
+    push_service_host(endpoint).is_some()
+}
+
+/// The `VAPID_PRIVATE_KEY` pushes are signed with, [`None`] if push notifications are disabled
+#[cfg(feature = "ssr")]
+fn vapid_key() -> Option<p256::ecdsa::SigningKey> {
+    use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+    let key = std::env::var("VAPID_PRIVATE_KEY").ok()?;
+    let key = URL_SAFE_NO_PAD
+        .decode(key.trim().trim_end_matches('='))
+        .ok()?;
+    p256::ecdsa::SigningKey::from_slice(&key).ok()
+}
+
+/// The public key of `key` as browsers expect it, the base64url encoded uncompressed point
+#[cfg(feature = "ssr")]
+fn public_key(key: &p256::ecdsa::SigningKey) -> String {
+    use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+
+    URL_SAFE_NO_PAD.encode(key.verifying_key().to_encoded_point(false).as_bytes())
+}
+
+/// The `Authorization` header of a push to `endpoint`: a JWT signed with `key`
+/// and the public key to check it with
+#[cfg(feature = "ssr")]
+fn vapid_authorization(endpoint: &str, key: &p256::ecdsa::SigningKey) -> Option<String> {
+    use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+    use p256::ecdsa::{Signature, signature::Signer};
+
+    let audience = format!("https://{}", push_service_host(endpoint)?);
+    let subject = std::env::var("VAPID_SUBJECT")
+        .ok()
+        .filter(|subject| !subject.is_empty())
+        .or_else(helper::public_url)
+        .unwrap_or_else(|| "mailto:dafoerum@localhost".to_string());
+    let expires_at = jiff::Timestamp::now().as_second() + VAPID_TOKEN_HOURS * 60 * 60;
+
+    let header = URL_SAFE_NO_PAD.encode(r#"{"typ":"JWT","alg":"ES256"}"#);
+    let claims = serde_json::json!({"aud": audience, "exp": expires_at, "sub": subject});
+    let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let unsigned = format!("{header}.{claims}");
+    let signature: Signature = key.sign(unsigned.as_bytes());
+    let token = format!(
+        "{unsigned}.{}",
+        URL_SAFE_NO_PAD.encode(signature.to_bytes())
+    );
+    Some(format!("vapid t={token}, k={}", public_key(key)))
+}
+
+/// Returns the public key browsers subscribe with, [`None`] if push notifications are disabled
+#[server]
+pub async fn get_push_public_key() -> Result<Option<String>, ApiError> {
+    Ok(vapid_key().map(|key| public_key(&key)))
+}
+
+/// Subscribes the browser with the push subscription `endpoint` to push notifications
+/// for the logged-in [`User`][super::user::User]
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::UnsupportedPushService`] if `endpoint` isn't of a
+///   [known push service][is_push_service]
+#[server]
+pub async fn subscribe_push(endpoint: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    if !is_push_service(&endpoint) {
+        return Err(ApiError::UnsupportedPushService);
+    }
+
+    let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+    // a browser someone else logged in to before is theirs now
+    PushSubscription::collection(&db)
+        .update_one(
+            bson::doc! {"endpoint": endpoint.as_str()},
+            bson::doc! {
+                "$set": {"user_id": user.id},
+                "$setOnInsert": {"created_at": now},
+            },
+        )
+        .upsert(true)
+        .await?;
+    Ok(())
+}
+
+/// Unsubscribes the browser with the push subscription `endpoint` of the logged-in
+/// [`User`][super::user::User] from push notifications
+///
+/// # Errors
+///
+/// - [`ApiError::NotLoggedIn`] if not logged in
+#[server]
+pub async fn unsubscribe_push(endpoint: String) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    let filter = bson::doc! {"user_id": user.id, "endpoint": endpoint};
+    PushSubscription::collection(&db)
+        .delete_one(filter.clone())
+        .await?;
+    PendingPush::collection(&db).delete_many(filter).await?;
+    Ok(())
+}
+
+/// Body of a request to [`PENDING_PUSH_PATH`]
+#[cfg(feature = "ssr")]
+#[derive(Deserialize, Debug)]
+pub struct PendingPushRequest {
+    pub endpoint: String,
+}
+
+/// Axum handler for `POST /push/pending`, see [`PENDING_PUSH_PATH`]
+///
+/// Responds with the oldest [`PushNotification`] for the service worker of `endpoint`
+/// of the logged-in member and forgets it, `null` if there's none
+#[cfg(feature = "ssr")]
+pub async fn take_pending_push(
+    axum::Extension(db): axum::Extension<mongodb::Database>,
+    headers: http::HeaderMap,
+    axum::Json(req): axum::Json<PendingPushRequest>,
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+    use http::StatusCode;
+
+    let user = match helper::require_user_from_headers(&headers, &db).await {
+        Ok(user) => user,
+        Err(ApiError::NotLoggedIn) => return StatusCode::UNAUTHORIZED.into_response(),
+        Err(err) => {
+            tracing::warn!("checking the session of a service worker failed: {err}");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    let pending = PendingPush::collection(&db)
+        .find_one_and_delete(bson::doc! {"user_id": user.id, "endpoint": req.endpoint})
+        .sort(bson::doc! {"created_at": 1})
+        .await;
+    match pending {
+        Ok(pending) => axum::Json(pending.map(|pending| pending.notification)).into_response(),
+        Err(err) => {
+            tracing::warn!("taking a pending push of user {} failed: {err}", user.id);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Pushes a [`PushNotification`] about `post` to the watchers and the author of `thread`
+/// who can see `forum`, except the one who wrote it. Happens in the background
+#[cfg(feature = "ssr")]
+pub fn notify_reply(
+    forum: &Forum,
+    thread: &Thread,
+    post: &Post,
+    author: Option<&User>,
+    db: &mongodb::Database,
+) {
+    let Some(key) = vapid_key() else {
+        return;
+    };
+
+    let author_name = author.map_or("Someone", |author| author.name.as_str());
+    let notification = PushNotification {
+        title: format!("{author_name} replied to \"{}\"", thread.display_subject()),
+        body: post.content.chars().take(MAX_BODY_CHARS).collect(),
+        url: format!("/post/{}", post.id),
+    };
+    let (forum_id, thread_id, post_id) = (forum.id, thread.id, post.id);
+    let thread_author_id = thread.author_id;
+    let post_author_id = post.author_id;
+
+    let db = db.clone();
+    tokio::spawn(async move {
+        let recipients = match find_reply_recipients(
+            forum_id,
+            thread_id,
+            post_id,
+            thread_author_id,
+            post_author_id,
+            &db,
+        )
+        .await
+        {
+            Ok(recipients) => recipients,
+            Err(err) => {
+                tracing::warn!("finding whom to push a reply in {thread_id} to failed: {err}");
+                return;
+            }
+        };
+        if let Err(err) = dispatch(&recipients, &notification, &key, &db).await {
+            tracing::warn!("pushing a reply in thread {thread_id} failed: {err}");
+        }
+    });
+}
+
+/// Pushes a [`PushNotification`] about the new `thread` to the watchers of `forum`,
+/// except the one who started it. Happens in the background
+#[cfg(feature = "ssr")]
+pub fn notify_thread(
+    forum: &Forum,
+    thread: &Thread,
+    post: &Post,
+    author: Option<&User>,
+    db: &mongodb::Database,
+) {
+    let Some(key) = vapid_key() else {
+        return;
+    };
+
+    let author_name = author.map_or("Someone", |author| author.name.as_str());
+    let notification = PushNotification {
+        title: format!(
+            "{author_name} started \"{}\" in {}",
+            thread.display_subject(),
+            forum.name
+        ),
+        body: post.content.chars().take(MAX_BODY_CHARS).collect(),
+        url: format!("/thread/{}", thread.id),
+    };
+    let (forum_id, thread_id) = (forum.id, thread.id);
+    let thread_author_id = thread.author_id;
+
+    let db = db.clone();
+    tokio::spawn(async move {
+        let recipients = match find_thread_recipients(forum_id, thread_author_id, &db).await {
+            Ok(recipients) => recipients,
+            Err(err) => {
+                tracing::warn!("finding whom to push thread {thread_id} to failed: {err}");
+                return;
+            }
+        };
+        if let Err(err) = dispatch(&recipients, &notification, &key, &db).await {
+            tracing::warn!("pushing thread {thread_id} failed: {err}");
+        }
+    });
+}
+
+/// Ids of the users to push the reply with `post_id` in the thread with `thread_id` to,
+/// without `post_author_id`. Watchers of the thread go by their [`WatchLevel`], its author
+/// and the watchers of the forum with `forum_id` are notified of all posts unless they watch
+/// the thread themselves
+#[cfg(feature = "ssr")]
+async fn find_reply_recipients(
+    forum_id: u32,
+    thread_id: u32,
+    post_id: u32,
+    thread_author_id: Option<u32>,
+    post_author_id: Option<u32>,
+    db: &mongodb::Database,
+) -> Result<Vec<u32>, ApiError> {
+    let mut user_ids = vec![];
+    // the level of the thread beats being its author or watching its forum
+    let mut thread_watchers = vec![];
+    let mut cursor = WatchedThread::collection(db)
+        .find(bson::doc! {"thread_id": thread_id})
+        .await?;
+    while cursor.advance().await? {
+        let watched: WatchedThread = cursor.deserialize_current()?;
+        thread_watchers.push(watched.user_id);
+        let notify = match watched.level {
+            WatchLevel::AllPosts => true,
+            WatchLevel::FirstPost => {
+                is_first_unread(watched.user_id, thread_id, post_id, db).await?
+            }
+            WatchLevel::Muted => false,
+        };
+        if notify {
+            user_ids.push(watched.user_id);
+        }
+    }
+    let forum_watchers =
+        subscriptions::forum_watchers(forum_id, &[WatchLevel::AllPosts], db).await?;
+    user_ids.extend(
+        thread_author_id
+            .into_iter()
+            .chain(forum_watchers)
+            .filter(|user_id| !thread_watchers.contains(user_id)),
+    );
+    user_ids.retain(|user_id| Some(*user_id) != post_author_id);
+    able_to_see(forum_id, user_ids, db).await
+}
+
+/// Ids of the users to push a new thread in the forum with `forum_id` to,
+/// its watchers without `thread_author_id`
+#[cfg(feature = "ssr")]
+async fn find_thread_recipients(
+    forum_id: u32,
+    thread_author_id: Option<u32>,
+    db: &mongodb::Database,
+) -> Result<Vec<u32>, ApiError> {
+    let levels = [WatchLevel::AllPosts, WatchLevel::FirstPost];
+    let mut user_ids = subscriptions::forum_watchers(forum_id, &levels, db).await?;
+    user_ids.retain(|user_id| Some(*user_id) != thread_author_id);
+    able_to_see(forum_id, user_ids, db).await
+}
+
+/// Whether the post with `post_id` is the first one `user_id` hasn't read
+/// in the thread with `thread_id`, see [`ReadMarker`]
+#[cfg(feature = "ssr")]
+async fn is_first_unread(
+    user_id: u32,
+    thread_id: u32,
+    post_id: u32,
+    db: &mongodb::Database,
+) -> Result<bool, ApiError> {
+    let last_read_post_id = ReadMarker::collection(db)
+        .find_one(bson::doc! {"user_id": user_id, "thread_id": thread_id})
+        .await?
+        .map_or(0, |marker| marker.last_read_post_id);
+    let unread_before = Post::collection(db)
+        .count_documents(bson::doc! {
+            "thread_id": thread_id,
+            "id": {"$gt": last_read_post_id, "$lt": post_id},
+            "pending_review": {"$ne": true},
+            "deleted_at": null,
+        })
+        .await?;
+    Ok(unread_before == 0)
+}
+
+/// Deduplicates `user_ids` and leaves out those who can't see the forum with `forum_id`
+#[cfg(feature = "ssr")]
+async fn able_to_see(
+    forum_id: u32,
+    mut user_ids: Vec<u32>,
+    db: &mongodb::Database,
+) -> Result<Vec<u32>, ApiError> {
+    user_ids.sort_unstable();
+    user_ids.dedup();
+    if user_ids.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let forums = helper::get_all_forums(db).await?;
+    let mut recipients = vec![];
+    let mut cursor = User::collection(db)
+        .find(bson::doc! {"id": {"$in": user_ids}})
+        .await?;
+    while cursor.advance().await? {
+        let user: User = cursor.deserialize_current()?;
+        if helper::can_see_forum(&forums, forum_id, Some(&user)) {
+            recipients.push(user.id);
+        }
+    }
+    Ok(recipients)
+}
+
+/// Stores `notification` for every [`PushSubscription`] of `user_ids` and pushes to them,
+/// forgetting subscriptions their push service doesn't know anymore
+#[cfg(feature = "ssr")]
+async fn dispatch(
+    user_ids: &[u32],
+    notification: &PushNotification,
+    key: &p256::ecdsa::SigningKey,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+    let subscription_col = PushSubscription::collection(db);
+    let mut subscriptions: Vec<PushSubscription> = vec![];
+    let mut cursor = subscription_col
+        .find(bson::doc! {"user_id": {"$in": user_ids.to_vec()}})
+        .await?;
+    while cursor.advance().await? {
+        subscriptions.push(cursor.deserialize_current()?);
+    }
+
+    let pending_col = PendingPush::collection(db);
+    for subscription in subscriptions {
+        pending_col
+            .insert_one(PendingPush {
+                user_id: subscription.user_id,
+                endpoint: subscription.endpoint.clone(),
+                notification: notification.clone(),
+                created_at: jiff::Timestamp::now(),
+            })
+            .await?;
+        match push(&subscription.endpoint, key).await {
+            Ok(true) => {}
+            Ok(false) => {
+                let filter = bson::doc! {"endpoint": subscription.endpoint.as_str()};
+                subscription_col.delete_one(filter.clone()).await?;
+                pending_col.delete_many(filter).await?;
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "pushing to a browser of user {} failed: {err}",
+                    subscription.user_id
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sends an empty push to `endpoint`, `false` if its subscription expired
+///
+/// # Errors
+///
+/// Returns why the push service didn't accept the push
+#[cfg(feature = "ssr")]
+async fn push(endpoint: &str, key: &p256::ecdsa::SigningKey) -> Result<bool, String> {
+    use http::{StatusCode, header};
+
+    static CLIENT: std::sync::LazyLock<reqwest::Client> = std::sync::LazyLock::new(|| {
+        reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(SEND_TIMEOUT_SECS))
+            .user_agent("Dafoerum-Push")
+            .build()
+            .unwrap_or_default()
+    });
+
+    let authorization =
+        vapid_authorization(endpoint, key).ok_or("not a known push service".to_string())?;
+    let res = CLIENT
+        .post(endpoint)
+        .header(header::AUTHORIZATION, authorization)
+        .header(header::CONTENT_LENGTH, "0")
+        .header("TTL", PUSH_TTL_SECS.to_string())
+        .header("Urgency", "normal")
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    match res.status() {
+        StatusCode::NOT_FOUND | StatusCode::GONE => Ok(false),
+        status if status.is_success() => Ok(true),
+        status => Err(format!("responded with {status}")),
+    }
+}
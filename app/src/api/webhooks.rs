@@ -0,0 +1,348 @@
+//! Outgoing webhooks notifying other services, like Discord or Slack, of new threads and posts
+//!
+//! Admins register a [`Webhook`] with the [`WebhookEvents`][WebhookEvent] it's interested in,
+//! optionally only for a single forum, e.g. piping just the new threads of a news forum
+//! into a chat channel.
+//! [`fire`] then `POST`s a [`WebhookPayload`] as JSON to it in the background,
+//! retrying failed deliveries up to [`MAX_ATTEMPTS`] times.
+//! Every request is signed with the webhook's secret as
+//! `X-Dafoerum-Signature: sha256={hex HMAC-SHA256 of the body}`, so receivers can check
+//! that it came from this forum. Nothing from forums guests can't see is sent, also if only
+//! a forum above them has a [read role][super::Forum::read_role], since the receivers are
+//! usually public
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{Counter, Forum, GetCollection, Post, Thread, bson, helper, user::Role, user::User};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How often a delivery is tried before giving up
+pub const MAX_ATTEMPTS: u32 = 3;
+/// How long to wait for a response of the receiver
+pub const DELIVERY_TIMEOUT_SECS: u64 = 10;
+
+/// What a [`Webhook`] can be notified of
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// A new [`Thread`][super::Thread] became visible
+    NewThread,
+    /// A new reply in a [`Thread`][super::Thread] became visible
+    NewPost,
+}
+impl WebhookEvent {
+    /// Name sent in the `X-Dafoerum-Event` header and [`WebhookPayload::event`]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::NewThread => "new_thread",
+            Self::NewPost => "new_post",
+        }
+    }
+}
+
+/// An url that's notified of the given [`WebhookEvents`][WebhookEvent]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Webhook {
+    pub id: u32,
+    pub url: String,
+    /// Key of the HMAC signature of every request
+    pub secret: String,
+    pub events: Vec<WebhookEvent>,
+    /// Only notified of what happens in the [`Forum`][super::Forum] with this id,
+    /// of all forums if [`None`]
+    #[serde(default)]
+    pub forum_id: Option<u32>,
+    /// When the last delivery succeeded
+    #[serde(default, with = "super::jiff_timestamp_as_bson_datetime::optional")]
+    pub last_delivered_at: Option<jiff::Timestamp>,
+    /// Why the last delivery failed after all attempts, [`None`] if it succeeded
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+impl CollectionName for Webhook {
+    fn collection_name() -> &'static str {
+        "webhooks"
+    }
+}
+
+/// What's sent to a [`Webhook`] as JSON
+///
+/// `content` and `text` are the same one-line summary, which is what Discord
+/// and Slack respectively show for their incoming webhooks
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookPayload {
+    /// See [`WebhookEvent::name`]
+    pub event: String,
+    pub forum_id: u32,
+    pub thread_id: u32,
+    pub post_id: u32,
+    pub subject: String,
+    /// Name of the author, [`None`] if anonymous
+    pub author: Option<String>,
+    /// Link to the post, absolute if the `PUBLIC_URL` env var is set
+    pub url: String,
+    /// Markdown content of the post
+    pub post: String,
+    pub content: String,
+    pub text: String,
+}
+
+/// Signs `body` with `secret` using HMAC-SHA256, returned as hex
+#[cfg(feature = "ssr")]
+#[must_use]
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    use hmac::Mac;
+    use std::fmt::Write;
+
+    let Ok(mut mac) = hmac::Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()) else {
+        unreachable!("HMAC accepts keys of any length")
+    };
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Notifies all [`Webhooks`][Webhook] subscribed to `event` of the new `post` in `thread`
+/// in the background, unless guests can't see `forum`, see [`dispatch`]
+#[cfg(feature = "ssr")]
+pub fn fire(
+    event: WebhookEvent,
+    forum: &Forum,
+    thread: &Thread,
+    post: &Post,
+    author: Option<&User>,
+    db: &mongodb::Database,
+) {
+    let url = format!(
+        "{}/post/{}",
+        helper::public_url().unwrap_or_default(),
+        post.id
+    );
+    let author = author.map(|author| author.name.clone());
+    let subject = thread.display_subject();
+    let summary = match event {
+        WebhookEvent::NewThread => format!(
+            "{} started \"{subject}\" in {}: {url}",
+            author.as_deref().unwrap_or("Someone"),
+            forum.name
+        ),
+        WebhookEvent::NewPost => format!(
+            "{} replied to \"{subject}\": {url}",
+            author.as_deref().unwrap_or("Someone")
+        ),
+    };
+    let payload = WebhookPayload {
+        event: event.name().to_string(),
+        forum_id: forum.id,
+        thread_id: thread.id,
+        post_id: post.id,
+        subject,
+        author,
+        url,
+        post: post.content.clone(),
+        content: summary.clone(),
+        text: summary,
+    };
+
+    let db = db.clone();
+    tokio::spawn(async move {
+        if let Err(err) = dispatch(event, &payload, &db).await {
+            tracing::warn!("dispatching {} webhooks failed: {err}", event.name());
+        }
+    });
+}
+
+/// Delivers `payload` to every [`Webhook`] subscribed to `event` in its forum
+/// and records the outcome
+///
+/// Nothing is delivered if guests can't see the forum or any forum above it,
+/// see [`helper::can_see_forum`]
+#[cfg(feature = "ssr")]
+async fn dispatch(
+    event: WebhookEvent,
+    payload: &WebhookPayload,
+    db: &mongodb::Database,
+) -> Result<(), ApiError> {
+    let forums = helper::get_all_forums(db).await?;
+    if !helper::can_see_forum(&forums, payload.forum_id, None) {
+        return Ok(());
+    }
+    let Ok(event_bson) = bson::to_bson(&event) else {
+        unreachable!("a WebhookEvent is always serializable")
+    };
+    let Ok(body) = serde_json::to_vec(payload) else {
+        unreachable!("a WebhookPayload is always serializable")
+    };
+
+    let webhook_col = Webhook::collection(db);
+    let mut webhooks: Vec<Webhook> = vec![];
+    // `null` also matches webhooks added before they could be limited to a forum
+    let mut cursor = webhook_col
+        .find(bson::doc! {
+            "events": event_bson,
+            "forum_id": {"$in": [bson::Bson::Null, payload.forum_id]},
+        })
+        .await?;
+    while cursor.advance().await? {
+        webhooks.push(cursor.deserialize_current()?);
+    }
+
+    for webhook in webhooks {
+        let update = match deliver(&webhook, event, &body).await {
+            Ok(()) => {
+                let now = bson::DateTime::from_millis(jiff::Timestamp::now().as_millisecond());
+                bson::doc! {"$set": {"last_delivered_at": now, "last_error": bson::Bson::Null}}
+            }
+            Err(err) => {
+                tracing::warn!("delivering to webhook {} failed: {err}", webhook.id);
+                bson::doc! {"$set": {"last_error": err}}
+            }
+        };
+        webhook_col
+            .update_one(bson::doc! {"id": webhook.id}, update)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Sends `body` to `webhook`, trying up to [`MAX_ATTEMPTS`] times with growing pauses
+///
+/// Returns why the last attempt failed, if all did
+#[cfg(feature = "ssr")]
+async fn deliver(webhook: &Webhook, event: WebhookEvent, body: &[u8]) -> Result<(), String> {
+    static CLIENT: std::sync::LazyLock<reqwest::Client> = std::sync::LazyLock::new(|| {
+        reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .user_agent("Dafoerum-Webhooks")
+            .build()
+            .unwrap_or_default()
+    });
+
+    let signature = format!("sha256={}", sign(&webhook.secret, body));
+    let mut error = String::new();
+    for attempt in 0..MAX_ATTEMPTS {
+        if attempt > 0 {
+            // 2s, 4s, ...
+            tokio::time::sleep(std::time::Duration::from_secs(1 << attempt)).await;
+        }
+        let res = CLIENT
+            .post(&webhook.url)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header("X-Dafoerum-Event", event.name())
+            .header("X-Dafoerum-Signature", &signature)
+            .body(body.to_vec())
+            .send()
+            .await;
+        match res {
+            Ok(res) if res.status().is_success() => return Ok(()),
+            Ok(res) => error = format!("responded with {}", res.status()),
+            Err(err) => error = err.to_string(),
+        }
+    }
+    Err(error)
+}
+
+/// Fetches all [`Webhooks`][Webhook]
+///
+/// Requires [`Role::Admin`], the secrets shouldn't be public
+#[server]
+pub async fn get_webhooks() -> Result<Vec<Webhook>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let mut webhooks = vec![];
+    let mut cursor = Webhook::collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"id": 1})
+        .await?;
+    while cursor.advance().await? {
+        webhooks.push(cursor.deserialize_current()?);
+    }
+
+    Ok(webhooks)
+}
+
+/// Adds a new [`Webhook`] and returns its id
+///
+/// A random secret is generated if `secret` is empty.
+/// With a `forum_id`, only what happens in that [`Forum`][super::Forum] is sent
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidWebhookUrl`] if `url` isn't an `http(s)://` url
+/// - [`ApiError::NoWebhookEvents`] if neither `new_threads` nor `new_posts` is set
+/// - [`ApiError::NotFound`] if `forum_id` isn't in use
+#[server]
+pub async fn add_webhook(
+    url: String,
+    secret: String,
+    forum_id: Option<u32>,
+    #[server(default)] new_threads: bool,
+    #[server(default)] new_posts: bool,
+) -> Result<u32, ApiError> {
+    let url = url.trim().to_string();
+    if !(url.starts_with("https://") || url.starts_with("http://")) || url.contains(' ') {
+        return Err(ApiError::InvalidWebhookUrl(url));
+    }
+    let mut events = vec![];
+    if new_threads {
+        events.push(WebhookEvent::NewThread);
+    }
+    if new_posts {
+        events.push(WebhookEvent::NewPost);
+    }
+    if events.is_empty() {
+        return Err(ApiError::NoWebhookEvents);
+    }
+    let secret = match secret.trim() {
+        "" => helper::random_token(),
+        secret => secret.to_string(),
+    };
+
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+    if let Some(forum_id) = forum_id {
+        let _ = helper::get_forum(forum_id, db.clone()).await?;
+    }
+
+    let counter_col = Counter::collection(&db);
+    let id = helper::get_and_increment_id_of("webhook", counter_col).await?;
+
+    let webhook = Webhook {
+        id,
+        url,
+        secret,
+        events,
+        forum_id,
+        last_delivered_at: None,
+        last_error: None,
+    };
+    Webhook::collection(&db).insert_one(&webhook).await?;
+
+    Ok(id)
+}
+
+/// Deletes the [`Webhook`] with the given `webhook_id`
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn delete_webhook(webhook_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    Webhook::collection(&db)
+        .delete_one(bson::doc! {"id": webhook_id})
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! A small wiki, editable by every logged-in [`User`][super::user::User]
+//!
+//! Each [`WikiPage`] holds its current content, while every edit is also kept
+//! as a [`WikiRevision`] so that changes can be compared with [`get_wiki_diff`]
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{GetCollection, bson, helper, site_settings::GuestCapability};
+use crate::diff::DiffLine;
+#[cfg(feature = "ssr")]
+use crate::diff::diff_lines;
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The current state of a wiki page, written in Markdown
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WikiPage {
+    /// Unique, used in the route `/wiki/:slug`
+    pub slug: String,
+    pub title: String,
+    pub content: String,
+    /// Number of the latest [`WikiRevision`], starts at `1`
+    pub revision: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub updated_at: jiff::Timestamp,
+}
+impl CollectionName for WikiPage {
+    fn collection_name() -> &'static str {
+        "wiki_pages"
+    }
+}
+
+/// A single saved version of a [`WikiPage`]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WikiRevision {
+    pub slug: String,
+    pub revision: u32,
+    pub title: String,
+    pub content: String,
+    /// Id of the user who saved this revision
+    pub author_id: u32,
+    #[serde(with = "super::jiff_timestamp_as_bson_datetime")]
+    pub created_at: jiff::Timestamp,
+}
+impl CollectionName for WikiRevision {
+    fn collection_name() -> &'static str {
+        "wiki_revisions"
+    }
+}
+
+/// A [`WikiPage`] with its content rendered to HTML, ready to be displayed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RenderedWikiPage {
+    pub page: WikiPage,
+    pub html: String,
+}
+
+/// Checks that a slug is non-empty and only consists of lowercase letters, digits and `-`
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidSlug`] if it doesn't
+#[cfg(feature = "ssr")]
+fn validate_slug(slug: &str) -> Result<(), ApiError> {
+    let valid = !slug.is_empty()
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::InvalidSlug(slug.to_string()))
+    }
+}
+
+/// Returns all wiki pages sorted by their title
+#[server]
+pub async fn get_wiki_pages() -> Result<Vec<WikiPage>, ApiError> {
+    let db = helper::get_db()?;
+    helper::get_reader(GuestCapability::Threads, &db).await?;
+    let mut page_cursor = WikiPage::collection(&db)
+        .find(bson::doc! {})
+        .sort(bson::doc! {"title": 1})
+        .await?;
+
+    let mut pages = vec![];
+    while page_cursor.advance().await? {
+        pages.push(page_cursor.deserialize_current()?);
+    }
+    Ok(pages)
+}
+
+/// Fetches the wiki page with the given `slug` rendered to HTML
+///
+/// # Errors
+///
+/// - [`ApiError::WikiPageNotFound`] if it doesn't exist
+#[server]
+pub async fn get_wiki_page(slug: String) -> Result<RenderedWikiPage, ApiError> {
+    let db = helper::get_db()?;
+    helper::get_reader(GuestCapability::Threads, &db).await?;
+    let Some(page) = WikiPage::collection(&db)
+        .find_one(bson::doc! {"slug": &slug})
+        .await?
+    else {
+        return Err(ApiError::WikiPageNotFound(slug));
+    };
+
+    let html = crate::markdown::render(&page.content);
+    Ok(RenderedWikiPage { page, html })
+}
+
+/// Saves a new revision of the wiki page with the given `slug`,
+/// creating the page if it doesn't exist yet
+///
+/// Returns the slug of the saved page
+///
+/// # Errors
+///
+/// - [`ApiError::InvalidSlug`] if the slug is invalid, see [`validate_slug`]
+/// - [`ApiError::EmptySubject`] / [`ApiError::EmptyContent`] if `title` or `content` is empty
+/// - [`ApiError::NotLoggedIn`] if not logged in
+/// - [`ApiError::Banned`] / [`ApiError::Muted`] if the user may not post
+#[server]
+pub async fn save_wiki_page(
+    slug: String,
+    title: String,
+    content: String,
+) -> Result<String, ApiError> {
+    validate_slug(&slug)?;
+    if title.trim().is_empty() {
+        return Err(ApiError::EmptySubject);
+    }
+    if content.trim().is_empty() {
+        return Err(ApiError::EmptyContent);
+    }
+
+    let db = helper::get_db()?;
+    let user = helper::require_user(&db).await?;
+    helper::ensure_can_post(Some(&user))?;
+
+    let page_col = WikiPage::collection(&db);
+    let revision = page_col
+        .find_one(bson::doc! {"slug": &slug})
+        .await?
+        .map_or(1, |page| page.revision + 1);
+    let now = jiff::Timestamp::now();
+
+    let new_revision = WikiRevision {
+        slug: slug.clone(),
+        revision,
+        title: title.clone(),
+        content: content.clone(),
+        author_id: user.id,
+        created_at: now,
+    };
+    WikiRevision::collection(&db)
+        .insert_one(&new_revision)
+        .await?;
+
+    let page = WikiPage {
+        slug: slug.clone(),
+        title,
+        content,
+        revision,
+        updated_at: now,
+    };
+    page_col
+        .replace_one(bson::doc! {"slug": &slug}, &page)
+        .upsert(true)
+        .await?;
+
+    Ok(slug)
+}
+
+/// Returns all revisions of the given wiki page, newest first
+#[server]
+pub async fn get_wiki_revisions(slug: String) -> Result<Vec<WikiRevision>, ApiError> {
+    let db = helper::get_db()?;
+    helper::get_reader(GuestCapability::Threads, &db).await?;
+    let mut revision_cursor = WikiRevision::collection(&db)
+        .find(bson::doc! {"slug": &slug})
+        .sort(bson::doc! {"revision": -1})
+        .await?;
+
+    let mut revisions = vec![];
+    while revision_cursor.advance().await? {
+        revisions.push(revision_cursor.deserialize_current()?);
+    }
+    Ok(revisions)
+}
+
+/// Compares the content of two revisions of a wiki page line by line
+///
+/// Revision `0` counts as an empty page, so that the first revision can be diffed too
+///
+/// # Errors
+///
+/// - [`ApiError::NotFound`] if one of the revisions doesn't exist
+#[server]
+pub async fn get_wiki_diff(slug: String, from: u32, to: u32) -> Result<Vec<DiffLine>, ApiError> {
+    let db = helper::get_db()?;
+    helper::get_reader(GuestCapability::Threads, &db).await?;
+    let revision_col = WikiRevision::collection(&db);
+
+    let mut contents = vec![];
+    for revision in [from, to] {
+        if revision == 0 {
+            contents.push(String::new());
+            continue;
+        }
+        let Some(found) = revision_col
+            .find_one(bson::doc! {"slug": &slug, "revision": revision})
+            .await?
+        else {
+            return Err(ApiError::NotFound("wiki revision".into(), revision));
+        };
+        contents.push(found.content);
+    }
+
+    Ok(diff_lines(&contents[0], &contents[1]))
+}
@@ -0,0 +1,193 @@
+//! Automatic moderation of post content with admin-managed [`WordFilterRules`][WordFilterRule]
+//!
+//! Every rule has a pattern matched case-insensitively against single words:
+//! `word` matches exactly, `word*` matches words starting with it,
+//! `*word` matches words ending with it and `*word*` matches words containing it
+
+use super::{ApiError, CollectionName};
+#[cfg(feature = "ssr")]
+use super::{Counter, GetCollection, bson, helper, user::Role};
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What happens to content matching a [`WordFilterRule`]
+///
+/// Ordered by severity, the most severe matching action wins
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FilterAction {
+    /// Replace the matched word with asterisks
+    Mask,
+    /// Accept the content, but hide it until a moderator approves it
+    Hold,
+    /// Don't accept the content at all
+    Reject,
+}
+
+/// A blocked word pattern with the [`FilterAction`] to take on a match
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WordFilterRule {
+    pub id: u32,
+    pub pattern: String,
+    pub action: FilterAction,
+}
+impl CollectionName for WordFilterRule {
+    fn collection_name() -> &'static str {
+        "word_filter_rules"
+    }
+}
+impl WordFilterRule {
+    /// Checks if the given `word` matches this rule's pattern, ignoring case
+    #[must_use]
+    pub fn matches(&self, word: &str) -> bool {
+        let pattern = self.pattern.to_lowercase();
+        let word = word.to_lowercase();
+        match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+            (Some(_), Some(_)) => word.contains(pattern.trim_matches('*')),
+            (Some(suffix), None) => word.ends_with(suffix),
+            (None, Some(prefix)) => word.starts_with(prefix),
+            (None, None) => word == pattern,
+        }
+    }
+}
+
+/// The result of running content through [`apply`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Content is fine, possibly with masked words
+    Allow(String),
+    /// Content has to be approved by a moderator first, possibly with masked words
+    Hold(String),
+    /// Content contains the given word of a [`FilterAction::Reject`] rule
+    Reject(String),
+}
+
+/// Runs `text` through all `rules`
+///
+/// # Example
+///
+/// ```
+/// use app::api::word_filter::{FilterAction, Verdict, WordFilterRule, apply};
+///
+/// let rules = [
+///     WordFilterRule { id: 1, pattern: "darn*".into(), action: FilterAction::Mask },
+///     WordFilterRule { id: 2, pattern: "*spam*".into(), action: FilterAction::Reject },
+/// ];
+///
+/// assert_eq!(apply(&rules, "Darnit, hi!"), Verdict::Allow("******, hi!".into()));
+/// assert_eq!(apply(&rules, "buy my SPAMMY stuff"), Verdict::Reject("SPAMMY".into()));
+/// ```
+#[must_use]
+pub fn apply(rules: &[WordFilterRule], text: &str) -> Verdict {
+    let mut output = String::with_capacity(text.len());
+    let mut hold = false;
+
+    let mut rest = text;
+    while !rest.is_empty() {
+        let word_len = rest
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(rest.len());
+        if word_len == 0 {
+            // push the separator as-is
+            let Some(separator) = rest.chars().next() else {
+                break;
+            };
+            output.push(separator);
+            rest = &rest[separator.len_utf8()..];
+            continue;
+        }
+
+        let (word, after) = rest.split_at(word_len);
+        let action = rules
+            .iter()
+            .filter(|rule| rule.matches(word))
+            .map(|rule| rule.action)
+            .max();
+        match action {
+            Some(FilterAction::Reject) => return Verdict::Reject(word.to_string()),
+            Some(FilterAction::Hold) => {
+                hold = true;
+                output.push_str(word);
+            }
+            Some(FilterAction::Mask) => output.extend(word.chars().map(|_| '*')),
+            None => output.push_str(word),
+        }
+        rest = after;
+    }
+
+    if hold {
+        Verdict::Hold(output)
+    } else {
+        Verdict::Allow(output)
+    }
+}
+
+/// Loads all [`WordFilterRules`][WordFilterRule] from the db
+///
+/// # Errors
+///
+/// * [`ApiError::Db`] if the db connection fails in any way
+#[cfg(feature = "ssr")]
+pub async fn load_rules(db: &mongodb::Database) -> Result<Vec<WordFilterRule>, ApiError> {
+    let mut rules = vec![];
+    let mut rule_cursor = WordFilterRule::collection(db).find(bson::doc! {}).await?;
+    while rule_cursor.advance().await? {
+        rules.push(rule_cursor.deserialize_current()?);
+    }
+    Ok(rules)
+}
+
+/// Fetches all [`WordFilterRules`][WordFilterRule]
+///
+/// Requires [`Role::Admin`], the rules shouldn't be public
+#[server]
+pub async fn get_word_filter_rules() -> Result<Vec<WordFilterRule>, ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+    load_rules(&db).await
+}
+
+/// Adds a new [`WordFilterRule`] and returns its id
+///
+/// Requires [`Role::Admin`]
+///
+/// # Errors
+///
+/// - [`ApiError::EmptyContent`] if `pattern` consists only of asterisks or whitespace
+#[server]
+pub async fn add_word_filter_rule(pattern: String, action: FilterAction) -> Result<u32, ApiError> {
+    let pattern = pattern.trim().to_lowercase();
+    if pattern.trim_matches('*').is_empty() {
+        return Err(ApiError::EmptyContent);
+    }
+
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    let counter_col = Counter::collection(&db);
+    let id = helper::get_and_increment_id_of("word_filter_rule", counter_col).await?;
+
+    let rule = WordFilterRule {
+        id,
+        pattern,
+        action,
+    };
+    WordFilterRule::collection(&db).insert_one(&rule).await?;
+
+    Ok(id)
+}
+
+/// Deletes the [`WordFilterRule`] with the given `rule_id`
+///
+/// Requires [`Role::Admin`]
+#[server]
+pub async fn delete_word_filter_rule(rule_id: u32) -> Result<(), ApiError> {
+    let db = helper::get_db()?;
+    helper::require_role(&db, Role::Admin).await?;
+
+    WordFilterRule::collection(&db)
+        .delete_one(bson::doc! {"id": rule_id})
+        .await?;
+
+    Ok(())
+}
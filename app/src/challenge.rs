@@ -0,0 +1,158 @@
+//! The [`ChallengeWidget`] guests solve in the composers, see [`api::challenge`]
+//!
+//! The challenge is only fetched on the client, so nothing is rendered on the server.
+//! A proof of work is solved right away, CAPTCHAs are rendered explicitly through the
+//! JavaScript API of their provider, which calls back into Rust with the answer
+
+use crate::api;
+use crate::t;
+use api::challenge::{CHALLENGE_FIELD, Challenge, solve};
+
+use leptos::html::Div;
+use leptos::{logging, prelude::*, task::spawn_local};
+use web_sys::js_sys::{Array, Function, Object, Reflect};
+use web_sys::wasm_bindgen::{JsCast, JsValue, closure::Closure};
+
+/// Name of the global JavaScript function the CAPTCHA scripts call once loaded
+const CAPTCHA_ONLOAD: &str = "dafoerumCaptchaLoaded";
+
+/// The JavaScript API of a CAPTCHA provider, hCaptcha and Turnstile share its shape
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CaptchaApi {
+    /// Name of the API object in `window`
+    global: &'static str,
+    script: &'static str,
+}
+
+const HCAPTCHA: CaptchaApi = CaptchaApi {
+    global: "hcaptcha",
+    script: "https://js.hcaptcha.com/1/api.js",
+};
+const TURNSTILE: CaptchaApi = CaptchaApi {
+    global: "turnstile",
+    script: "https://challenges.cloudflare.com/turnstile/v0/api.js",
+};
+
+impl CaptchaApi {
+    fn script_src(self) -> String {
+        format!("{}?render=explicit&onload={CAPTCHA_ONLOAD}", self.script)
+    }
+
+    /// The API object, [`None`] until the script is loaded
+    fn get(self) -> Option<JsValue> {
+        Reflect::get(&window(), &self.global.into())
+            .ok()
+            .filter(JsValue::is_object)
+    }
+
+    /// Calls `method` of the API object with `args`
+    fn call(self, method: &str, args: &Array) -> Option<JsValue> {
+        let api = self.get()?;
+        let method: Function = Reflect::get(&api, &method.into()).ok()?.dyn_into().ok()?;
+        method.apply(&api, args).ok()
+    }
+
+    /// Renders the widget into `container`, setting `response` whenever it's solved or expires
+    fn render(self, container: &JsValue, site_key: &str, response: WriteSignal<String>) {
+        let solved = Closure::<dyn Fn(String)>::new(move |answer| response.set(answer));
+        let expired = Closure::<dyn Fn()>::new(move || response.set(String::new()));
+        let params = Object::new();
+        for (key, value) in [
+            ("sitekey", JsValue::from_str(site_key)),
+            ("callback", solved.into_js_value()),
+            ("expired-callback", expired.into_js_value()),
+        ] {
+            let _ = Reflect::set(&params, &key.into(), &value);
+        }
+        self.call("render", &Array::of2(container, &params));
+    }
+}
+
+/// Renders the [`Challenge`] of the current visitor and submits its answer as the hidden
+/// [`CHALLENGE_FIELD`], belongs in the `ActionForm` of every composer guests can use.
+/// Nothing is shown to logged-in users
+///
+/// Answers can only be used once, so a new challenge is started whenever `reset` changes,
+/// e.g. with the version of the form's `ServerAction`
+#[component]
+pub fn ChallengeWidget(#[prop(into)] reset: Signal<usize>) -> impl IntoView {
+    let challenge_res = LocalResource::new(api::challenge::get_challenge);
+    let (response, set_response) = signal(String::new());
+    let container_ref = NodeRef::<Div>::new();
+
+    let challenge = move || match challenge_res.get()? {
+        Ok(challenge) => Some(challenge),
+        Err(err) => {
+            logging::log!("{err:?} - {err}");
+            None
+        }
+    };
+    let captcha = move || match challenge()? {
+        Challenge::HCaptcha { site_key } => Some((HCAPTCHA, site_key)),
+        Challenge::Turnstile { site_key } => Some((TURNSTILE, site_key)),
+        Challenge::None | Challenge::ProofOfWork { .. } => None,
+    };
+
+    // solved in the background, a fraction of a second at the default difficulty
+    Effect::new(move || {
+        reset.track();
+        let Some(Challenge::ProofOfWork { difficulty }) = challenge() else {
+            return;
+        };
+        set_response.set(String::new());
+        spawn_local(async move {
+            match api::challenge::issue_pow_nonce().await {
+                Ok(nonce) => set_response.set(format!("{nonce}:{}", solve(&nonce, difficulty))),
+                Err(err) => logging::log!("{err:?} - {err}"),
+            }
+        });
+    });
+    Effect::new(move || {
+        let Some(container) = container_ref.get() else {
+            return;
+        };
+        let Some((captcha_api, site_key)) = captcha() else {
+            return;
+        };
+        let container = JsValue::from(container);
+        if captcha_api.get().is_some() {
+            captcha_api.render(&container, &site_key, set_response);
+        } else {
+            let onload = Closure::once_into_js(move || {
+                captcha_api.render(&container, &site_key, set_response);
+            });
+            let _ = Reflect::set(&window(), &CAPTCHA_ONLOAD.into(), &onload);
+        }
+    });
+    Effect::watch(
+        move || reset.get(),
+        move |_, _, _| {
+            if let Some((captcha_api, _)) = captcha() {
+                set_response.set(String::new());
+                captcha_api.call("reset", &Array::new());
+            }
+        },
+        false,
+    );
+
+    view! {
+      <input type="hidden" name=CHALLENGE_FIELD prop:value=response />
+      {move || {
+        captcha()
+          .map(|(captcha_api, _)| {
+            view! {
+              // loaded once, later widgets are rendered by the already loaded API
+              <Show when=move || captcha_api.get().is_none()>
+                <script src=captcha_api.script_src() defer></script>
+              </Show>
+              <div class="my-2" node_ref=container_ref></div>
+            }
+          })
+      }}
+      <Show when=move || {
+        matches!(challenge(), Some(Challenge::ProofOfWork { .. })) && response.read().is_empty()
+      }>
+        <p class="text-sm text-gray-500">{t!("challenge.checking")}</p>
+      </Show>
+    }
+}
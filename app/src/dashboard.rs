@@ -0,0 +1,160 @@
+use crate::api;
+use crate::i18n::use_i18n;
+use crate::t;
+use crate::time::LocalTime;
+use crate::user::MemberLabel;
+use api::dashboard::ForumStats;
+
+use leptos::either::{Either, EitherOf3};
+use leptos::{logging, prelude::*};
+
+/// Renders the panels of the home page, each loading on its own
+#[component]
+pub fn Dashboard() -> impl IntoView {
+    view! {
+      <div class="grid grid-cols-1 gap-4 w-full md:grid-cols-3">
+        <DashboardPanel title="dashboard.active_threads">
+          <ActiveThreads />
+        </DashboardPanel>
+        <DashboardPanel title="dashboard.newest_members">
+          <NewestMembers />
+        </DashboardPanel>
+        <DashboardPanel title="dashboard.statistics">
+          <Statistics />
+        </DashboardPanel>
+      </div>
+    }
+}
+
+/// Renders a box of the [`Dashboard`] titled with the [`i18n`][crate::i18n] key `title`
+#[component]
+fn DashboardPanel(title: &'static str, children: Children) -> impl IntoView {
+    view! {
+      <section class="flex flex-col gap-2 p-4 bg-purple-200 rounded-xs">
+        <h2 class="text-xl font-bold text-purple-950">{t!(title)}</h2>
+        {children()}
+      </section>
+    }
+}
+
+/// Renders the threads with the most posts within the last
+/// [`ACTIVE_DAYS`][api::dashboard::ACTIVE_DAYS]
+#[component]
+fn ActiveThreads() -> impl IntoView {
+    let threads_res = Resource::new(|| (), |()| api::dashboard::get_active_threads());
+    let i18n = use_i18n();
+
+    let threads_view = move || {
+        Suspend::new(async move {
+            let threads = match threads_res.await {
+                Ok(threads) => threads,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("dashboard.threads_load_failed")}</p> });
+                }
+            };
+            if threads.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("dashboard.quiet_week")}</p> });
+            }
+            let view = threads
+                .into_iter()
+                .map(|active| {
+                    let recent_posts = i64::from(active.recent_posts);
+                    view! {
+                      <li class="flex justify-between gap-2">
+                        <a
+                          href=format!("/thread/{}", active.thread.id)
+                          class="font-medium text-blue-600 underline truncate hover:no-underline"
+                        >
+                          {active.thread.display_subject()}
+                        </a>
+                        <span class="text-sm whitespace-nowrap">
+                          {move || i18n.t_count("dashboard.recent_posts", recent_posts)}
+                        </span>
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ol class="flex flex-col gap-1">{view}</ol> })
+        })
+    };
+
+    view! { <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{threads_view}</Suspense> }
+}
+
+/// Renders the most recently registered members and when they joined
+#[component]
+fn NewestMembers() -> impl IntoView {
+    let members_res = Resource::new(|| (), |()| api::dashboard::get_newest_members());
+
+    let members_view = move || {
+        Suspend::new(async move {
+            let members = match members_res.await {
+                Ok(members) => members,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("dashboard.members_load_failed")}</p> });
+                }
+            };
+            let view = members
+                .into_iter()
+                .map(|member| {
+                    let joined = member.user.created_at;
+                    view! {
+                      <li class="flex justify-between gap-2">
+                        <MemberLabel member avatar=true />
+                        <span class="text-sm whitespace-nowrap">
+                          <LocalTime timestamp=joined relative=true />
+                        </span>
+                      </li>
+                    }
+                })
+                .collect_view();
+            Either::Right(view! { <ol class="flex flex-col gap-1">{view}</ol> })
+        })
+    };
+
+    view! { <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{members_view}</Suspense> }
+}
+
+/// Renders the [`ForumStats`]
+#[component]
+fn Statistics() -> impl IntoView {
+    let stats_res = Resource::new(|| (), |()| api::dashboard::get_forum_stats());
+
+    let stats_view = move || {
+        Suspend::new(async move {
+            let stats = match stats_res.await {
+                Ok(stats) => stats,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("dashboard.stats_load_failed")}</p> });
+                }
+            };
+            let ForumStats {
+                thread_count,
+                post_count,
+                member_count,
+                posts_last_day,
+            } = stats;
+            let row = |label: &'static str, value: u64| {
+                view! {
+                  <div class="flex justify-between">
+                    <dt>{t!(label)}</dt>
+                    <dd class="font-bold">{value}</dd>
+                  </div>
+                }
+            };
+            Either::Right(view! {
+              <dl class="flex flex-col gap-1">
+                {row("dashboard.stat.threads", thread_count)}
+                {row("dashboard.stat.posts", post_count)}
+                {row("dashboard.stat.members", member_count)}
+                {row("dashboard.stat.posts_today", posts_last_day)}
+              </dl>
+            })
+        })
+    };
+
+    view! { <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{stats_view}</Suspense> }
+}
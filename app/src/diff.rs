@@ -0,0 +1,74 @@
+//! Line diffs between two versions of a text, e.g. of wiki revisions or of a post being edited
+//!
+//! Computed with [`similar`], which works in the browser too, so drafts are diffed live
+//! without asking the server
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Whether a [`DiffLine`] was kept, added or removed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffTag {
+    Equal,
+    Insert,
+    Delete,
+}
+
+/// A single line of a diff, see [`diff_lines`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    pub text: String,
+}
+
+/// Compares `old` and `new` line by line
+///
+/// # Example
+///
+/// ```
+/// use app::diff::{DiffLine, DiffTag, diff_lines};
+///
+/// let lines = diff_lines("a\nb\n", "a\nc\n");
+/// assert_eq!(lines, [
+///     DiffLine { tag: DiffTag::Equal, text: "a".into() },
+///     DiffLine { tag: DiffTag::Delete, text: "b".into() },
+///     DiffLine { tag: DiffTag::Insert, text: "c".into() },
+/// ]);
+/// ```
+#[must_use]
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    similar::TextDiff::from_lines(old, new)
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            tag: match change.tag() {
+                similar::ChangeTag::Equal => DiffTag::Equal,
+                similar::ChangeTag::Insert => DiffTag::Insert,
+                similar::ChangeTag::Delete => DiffTag::Delete,
+            },
+            text: change.to_string_lossy().trim_end_matches('\n').to_string(),
+        })
+        .collect()
+}
+
+/// Renders a line diff with added lines in green and removed lines in red
+#[component]
+pub fn Diff(lines: Vec<DiffLine>) -> impl IntoView {
+    if lines.is_empty() {
+        return None;
+    }
+
+    let view = lines
+        .into_iter()
+        .map(|line| {
+            let (prefix, class) = match line.tag {
+                DiffTag::Equal => ("  ", ""),
+                DiffTag::Insert => ("+ ", "bg-green-100"),
+                DiffTag::Delete => ("- ", "bg-red-100"),
+            };
+            view! { <div class=class>{prefix}{line.text}</div> }
+        })
+        .collect_view();
+    Some(view! {
+      <pre class="overflow-x-auto p-3 mt-2 font-mono text-sm bg-purple-50 rounded-lg">{view}</pre>
+    })
+}
@@ -0,0 +1,140 @@
+//! Autosaved drafts of the post and thread composers
+//!
+//! Drafts are kept in localStorage per [`Thread`][crate::api::Thread] or [`Forum`][crate::api::Forum],
+//! so leaving the page or closing the tab doesn't lose what was written.
+//! They're restored when coming back and removed once the post or thread was created
+
+use crate::t;
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// What was written in a composer so far
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Draft {
+    /// Always empty for replies
+    pub subject: String,
+    pub content: String,
+}
+impl Draft {
+    /// Whether nothing was written, in which case no draft is kept
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.subject.trim().is_empty() && self.content.trim().is_empty()
+    }
+}
+
+/// Which composer a [`Draft`] belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DraftKey {
+    /// Reply in the [`Thread`][crate::api::Thread] with this id
+    Reply { thread_id: u32 },
+    /// New [`Thread`][crate::api::Thread] in the [`Forum`][crate::api::Forum] with this id
+    NewThread { forum_id: u32 },
+}
+impl DraftKey {
+    /// Key of the [`Draft`] in localStorage
+    fn storage_key(self) -> String {
+        match self {
+            Self::Reply { thread_id } => format!("dafoerum_draft_reply_{thread_id}"),
+            Self::NewThread { forum_id } => format!("dafoerum_draft_thread_{forum_id}"),
+        }
+    }
+}
+
+/// Reads the [`Draft`] saved by [`store_draft`], if there is one
+fn load_draft(key: DraftKey) -> Option<Draft> {
+    let storage = window().local_storage().ok().flatten()?;
+    let json = storage.get_item(&key.storage_key()).ok().flatten()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Saves the [`Draft`] in localStorage, or removes it if it's empty
+fn store_draft(key: DraftKey, draft: &Draft) {
+    let Some(storage) = window().local_storage().ok().flatten() else {
+        return;
+    };
+    // storage full or disabled, the draft just won't survive a reload then
+    if draft.is_empty() {
+        let _ = storage.remove_item(&key.storage_key());
+    } else if let Ok(json) = serde_json::to_string(draft) {
+        let _ = storage.set_item(&key.storage_key(), &json);
+    }
+}
+
+/// State of a composer whose inputs are autosaved as a [`Draft`], see [`use_draft`]
+#[derive(Clone, Copy, Debug)]
+pub struct DraftState {
+    key: DraftKey,
+    draft: RwSignal<Draft>,
+}
+impl DraftState {
+    /// Current subject, for the `prop:value` of the subject input
+    #[must_use]
+    pub fn subject(self) -> String {
+        self.draft.with(|draft| draft.subject.clone())
+    }
+
+    /// Current content, for the `prop:value` of the content textarea
+    #[must_use]
+    pub fn content(self) -> String {
+        self.draft.with(|draft| draft.content.clone())
+    }
+
+    /// Whether nothing was written yet
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.draft.with(Draft::is_empty)
+    }
+
+    /// Updates the subject on input and saves the draft
+    pub fn set_subject(self, subject: String) {
+        self.draft.update(|draft| draft.subject = subject);
+        self.draft
+            .with_untracked(|draft| store_draft(self.key, draft));
+    }
+
+    /// Updates the content on input and saves the draft
+    pub fn set_content(self, content: String) {
+        self.draft.update(|draft| draft.content = content);
+        self.draft
+            .with_untracked(|draft| store_draft(self.key, draft));
+    }
+
+    /// Clears the inputs and removes the saved draft,
+    /// e.g. after the post was created
+    pub fn discard(self) {
+        self.draft.set(Draft::default());
+        store_draft(self.key, &Draft::default());
+    }
+}
+
+/// Creates the [`DraftState`] of the composer identified by `key`,
+/// restoring its saved [`Draft`] once hydrated
+pub fn use_draft(key: DraftKey) -> DraftState {
+    let draft = RwSignal::new(Draft::default());
+    // localStorage is only available on the client
+    Effect::new(move || {
+        if let Some(saved) = load_draft(key) {
+            draft.set(saved);
+        }
+    });
+    DraftState { key, draft }
+}
+
+/// Renders a button clearing the composer and its saved [`Draft`],
+/// only shown when something was written
+#[component]
+pub fn DiscardDraftButton(draft: DraftState) -> impl IntoView {
+    view! {
+      <Show when=move || !draft.is_empty()>
+        <button
+          type="button"
+          on:click=move |_| draft.discard()
+          class="text-xs text-red-600 hover:underline hover:cursor-pointer"
+        >
+          {t!("draft.discard")}
+        </button>
+      </Show>
+    }
+}
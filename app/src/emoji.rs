@@ -0,0 +1,158 @@
+//! Emoji shortcodes like `:smile:` and the [`EmojiPicker`] of the Markdown editor
+//!
+//! The picker inserts the emoji itself, shortcodes are replaced while rendering Markdown,
+//! so posts written with shortcodes show emojis too
+
+use crate::t;
+
+use leptos::prelude::*;
+use std::borrow::Cow;
+
+/// The known shortcodes without colons and their emojis, in the order of the picker
+pub const EMOJIS: [(&str, &str); 32] = [
+    ("smile", "😄"),
+    ("grin", "😁"),
+    ("joy", "😂"),
+    ("wink", "😉"),
+    ("blush", "😊"),
+    ("heart_eyes", "😍"),
+    ("sunglasses", "😎"),
+    ("thinking", "🤔"),
+    ("neutral_face", "😐"),
+    ("roll_eyes", "🙄"),
+    ("confused", "😕"),
+    ("cry", "😢"),
+    ("sob", "😭"),
+    ("angry", "😠"),
+    ("scream", "😱"),
+    ("sweat_smile", "😅"),
+    ("innocent", "😇"),
+    ("upside_down", "🙃"),
+    ("thumbsup", "👍"),
+    ("thumbsdown", "👎"),
+    ("clap", "👏"),
+    ("wave", "👋"),
+    ("pray", "🙏"),
+    ("muscle", "💪"),
+    ("eyes", "👀"),
+    ("heart", "❤️"),
+    ("broken_heart", "💔"),
+    ("fire", "🔥"),
+    ("tada", "🎉"),
+    ("rocket", "🚀"),
+    ("100", "💯"),
+    ("warning", "⚠️"),
+];
+
+/// Returns the emoji of `shortcode` (without colons), [`None`] if it isn't known
+///
+/// # Example
+///
+/// ```
+/// use app::emoji::emoji;
+///
+/// assert_eq!(emoji("tada"), Some("🎉"));
+/// assert_eq!(emoji("nope"), None);
+/// ```
+#[must_use]
+pub fn emoji(shortcode: &str) -> Option<&'static str> {
+    EMOJIS
+        .iter()
+        .find(|(code, _)| *code == shortcode)
+        .map(|(_, emoji)| *emoji)
+}
+
+/// Replaces the known shortcodes in `text` with their emojis, unknown ones are kept as they are
+///
+/// # Example
+///
+/// ```
+/// use app::emoji::replace_shortcodes;
+///
+/// assert_eq!(replace_shortcodes("hi :wave: :nope: 12:30"), "hi 👋 :nope: 12:30");
+/// assert_eq!(replace_shortcodes(":fire::fire:"), "🔥🔥");
+/// ```
+#[must_use]
+pub fn replace_shortcodes(text: &str) -> Cow<'_, str> {
+    if !text.contains(':') {
+        return Cow::Borrowed(text);
+    }
+
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(':') {
+        let after = &rest[start + 1..];
+        let replaced = after
+            .find(':')
+            .and_then(|end| emoji(&after[..end]).map(|emoji| (end, emoji)));
+        match replaced {
+            Some((end, emoji)) => {
+                output.push_str(&rest[..start]);
+                output.push_str(emoji);
+                rest = &after[end + 1..];
+            }
+            // the colon might start the next shortcode
+            None => {
+                output.push_str(&rest[..=start]);
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    Cow::Owned(output)
+}
+
+/// Renders a button opening a grid of the [`EMOJIS`], `on_pick` is called with the clicked one
+#[component]
+pub fn EmojiPicker(on_pick: Callback<&'static str>) -> impl IntoView {
+    let open = RwSignal::new(false);
+
+    let emojis = EMOJIS
+        .into_iter()
+        .map(|(shortcode, emoji)| {
+            let title = format!(":{shortcode}:");
+            view! {
+              <button
+                type="button"
+                title=title.clone()
+                aria-label=title
+                on:click=move |_| {
+                  open.set(false);
+                  on_pick.run(emoji);
+                }
+                class="p-1 text-lg rounded-sm hover:bg-purple-200 hover:cursor-pointer"
+              >
+                {emoji}
+              </button>
+            }
+        })
+        .collect_view();
+
+    view! {
+      <div class="relative">
+        <button
+          type="button"
+          title=t!("emoji.picker")
+          aria-label=t!("emoji.picker")
+          aria-expanded=move || open.get().to_string()
+          on:click=move |_| open.update(|open| *open = !*open)
+          class="py-0.5 px-2 text-sm rounded-sm hover:bg-purple-200 hover:cursor-pointer"
+        >
+          "🙂"
+        </button>
+        <div
+          role="dialog"
+          aria-label=t!("emoji.picker")
+          class="grid absolute right-0 top-full z-10 grid-cols-8 gap-1 p-2 w-72 bg-purple-50 rounded-lg border border-purple-400 shadow-lg"
+          class:hidden=move || !open.get()
+          on:keydown=move |ev| {
+            if ev.key() == "Escape" {
+              open.set(false);
+            }
+          }
+        >
+          {emojis}
+        </div>
+      </div>
+    }
+}
@@ -0,0 +1,212 @@
+//! Pages shown instead of the requested one when something goes wrong
+//!
+//! While rendering on the server they also set the HTTP status of the response,
+//! so crawlers and tools get a 404 or 500 instead of a 200 with an error text
+
+use crate::api::{self, ApiError, Thread};
+use crate::i18n::use_i18n;
+use crate::t;
+
+use http::StatusCode;
+use leptos::either::{Either, EitherOf3, EitherOf4};
+use leptos::{logging, prelude::*};
+use leptos_meta::Title;
+use leptos_router::{components::A, hooks::use_location};
+
+/// Sets the status of the server-side rendered response, does nothing in the browser
+fn set_status(status: StatusCode) {
+    #[cfg(feature = "ssr")]
+    if let Some(res) = use_context::<leptos_axum::ResponseOptions>() {
+        res.set_status(status);
+    }
+    #[cfg(not(feature = "ssr"))]
+    let _ = status;
+}
+
+/// The status of a page failing with `err`
+fn status_of(err: &ApiError) -> StatusCode {
+    match err {
+        ApiError::NotFound(..) | ApiError::WikiPageNotFound(_) => StatusCode::NOT_FOUND,
+        ApiError::NotLoggedIn | ApiError::LoginRequired => StatusCode::UNAUTHORIZED,
+        ApiError::Forbidden => StatusCode::FORBIDDEN,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Renders the page for a path that doesn't exist or whose content is gone,
+/// with a [`ThreadSearch`] and links back to the forum
+#[component]
+pub fn NotFound() -> impl IntoView {
+    set_status(StatusCode::NOT_FOUND);
+    let i18n = use_i18n();
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("error_page.not_found.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("error_page.not_found.title")}</h1>
+      <p>{t!("error_page.not_found.text")}</p>
+      <ThreadSearch />
+      <nav class="flex flex-wrap gap-4">
+        <A href="/forum" attr:class="underline hover:no-underline">
+          {t!("error_page.to_forums")}
+        </A>
+        <A href="/latest" attr:class="underline hover:no-underline">
+          {t!("nav.latest")}
+        </A>
+      </nav>
+    }
+}
+
+/// Renders the page for a view that failed with `err`, [`NotFound`] if its content doesn't exist
+#[component]
+pub fn ErrorPage(err: ApiError) -> impl IntoView {
+    let status = status_of(&err);
+    if status == StatusCode::NOT_FOUND {
+        return Either::Left(view! { <NotFound /> });
+    }
+    let i18n = use_i18n();
+    Either::Right(view! { <ErrorView status message=Signal::derive(move || i18n.error(&err)) /> })
+}
+
+/// Renders an error page with `message`, answered with `status`
+#[component]
+fn ErrorView(status: StatusCode, message: Signal<String>) -> impl IntoView {
+    set_status(status);
+    let i18n = use_i18n();
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("error_page.error.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("error_page.error.title")}</h1>
+      <p class="text-lg font-bold text-red-700">{message}</p>
+      {(status == StatusCode::INTERNAL_SERVER_ERROR)
+        .then(|| view! { <p>{t!("error_page.error.text")}</p> })}
+      <A href="/" attr:class="underline hover:no-underline">
+        {t!("error_page.to_home")}
+      </A>
+    }
+}
+
+/// Catches the errors of views in `children` and renders an [`ErrorPage`] for the first one,
+/// a 500 if it isn't an [`ApiError`], until another page is opened
+#[component]
+pub fn PageErrorBoundary(children: Children) -> impl IntoView {
+    let pathname = use_location().pathname;
+
+    view! {
+      <ErrorBoundary fallback=move |errors: ArcRwSignal<Errors>| {
+        {
+          let errors = errors.clone();
+          // the new page might work, so it's rendered again
+          Effect::new(move |prev: Option<String>| {
+            let path = pathname.get();
+            if prev.is_some_and(|prev| prev != path) {
+              errors.set(Errors::default());
+            }
+            path
+          });
+        }
+        move || {
+          let Some((_, err)) = errors.get().into_iter().next() else {
+            return EitherOf3::A(());
+          };
+          logging::log!("{err:?} - {err}");
+          match err.downcast_ref::<ApiError>() {
+            Some(err) => EitherOf3::B(view! { <ErrorPage err=err.clone() /> }),
+            None => {
+              let message = err.to_string();
+              EitherOf3::C(
+                view! {
+                  <ErrorView
+                    status=StatusCode::INTERNAL_SERVER_ERROR
+                    message=Signal::stored(message)
+                  />
+                },
+              )
+            }
+          }
+        }
+      }>{children()}</ErrorBoundary>
+    }
+}
+
+/// Renders a search box listing the [`Threads`][Thread] whose subject contains the query,
+/// see [`api::search_threads`]
+#[component]
+fn ThreadSearch() -> impl IntoView {
+    let i18n = use_i18n();
+    let (draft, set_draft) = signal(String::new());
+    let (query, set_query) = signal(String::new());
+    let results_res = Resource::new(
+        move || query.get(),
+        |query| async move {
+            // nothing searched yet
+            if query.trim().is_empty() {
+                return Ok(None);
+            }
+            api::search_threads(query).await.map(Some)
+        },
+    );
+
+    let results_view = move || {
+        Suspend::new(async move {
+            let threads: Vec<Thread> = match results_res.await {
+                Ok(Some(threads)) => threads,
+                Ok(None) => return EitherOf4::A(()),
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf4::B(view! {
+                      <p class="font-bold text-red-700">{move || i18n.error(&err)}</p>
+                    });
+                }
+            };
+            if threads.is_empty() {
+                return EitherOf4::C(view! { <p>{t!("error_page.no_results")}</p> });
+            }
+            EitherOf4::D(view! {
+              <ul class="list-disc list-inside">
+                {threads
+                  .into_iter()
+                  .map(|thread| {
+                    view! {
+                      <li>
+                        <A
+                          href=format!("/thread/{}", thread.id)
+                          attr:class="underline hover:no-underline"
+                        >
+                          {thread.display_subject()}
+                        </A>
+                      </li>
+                    }
+                  })
+                  .collect_view()}
+              </ul>
+            })
+        })
+    };
+
+    view! {
+      <form
+        role="search"
+        class="flex flex-wrap gap-2 items-center"
+        on:submit=move |ev| {
+          ev.prevent_default();
+          set_query(draft.get());
+        }
+      >
+        <input
+          type="search"
+          required
+          placeholder=move || i18n.t("error_page.search_placeholder")
+          aria-label=move || i18n.t("error_page.search")
+          prop:value=draft
+          on:input:target=move |ev| set_draft(ev.target().value())
+          class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+        />
+        <input
+          type="submit"
+          value=move || i18n.t("error_page.search")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </form>
+      <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{results_view}</Suspense>
+    }
+}
@@ -1,24 +1,43 @@
+pub mod attachment;
 pub mod thread;
 
-use crate::TimeUtils;
+use crate::CsrfField;
+use crate::announcement::AnnouncementBanners;
 use crate::api;
+use crate::challenge::ChallengeWidget;
+use crate::draft::{DiscardDraftButton, DraftKey, use_draft};
+use crate::error_page::ErrorPage;
+use crate::i18n::{Locale, translate, use_i18n};
+use crate::markdown_editor::MarkdownEditor;
+use crate::t;
+use crate::time::LocalTime;
+use crate::use_toasts;
+use crate::user::MemberName;
+use api::breadcrumbs::{Crumb, category_anchor};
+use api::feeds::FeedScope;
+use api::validation::Field;
 use api::{ApiError, Category, Forum, Post, Thread};
 
 use leptos::either::{Either, EitherOf3};
+use leptos::ev::MouseEvent;
 use leptos::html::Dialog;
-use leptos::{logging, prelude::*};
-use leptos_meta::Title;
+use leptos::{logging, prelude::*, task::spawn_local};
+use leptos_meta::{Link, Meta, Title};
 use leptos_router::{
     components::A,
     hooks::{use_navigate, use_params},
     params::Params,
 };
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
 
 /// Renders a list of all [`Forums`][Forum]
 #[component]
 pub fn Forums() -> impl IntoView {
+    let i18n = use_i18n();
     let categories_res: Resource<Result<Vec<Category>, ApiError>> =
         Resource::new(move || (), move |()| api::get_categories());
+    let user_res = Resource::new(move || (), move |()| api::user::get_current_user());
 
     let category_list_view = move || {
         Suspend::new(async move {
@@ -26,62 +45,229 @@ pub fn Forums() -> impl IntoView {
                 Ok(categories) => categories,
                 Err(err) => {
                     logging::log!("{err:?} - {err}");
-                    return Either::Left(view! { <p>"Forums couldn't be loaded!"</p> });
+                    return Either::Left(view! { <p>{t!("forum.forums_load_failed")}</p> });
                 }
             };
+            let user = user_res.await.ok().flatten();
+            let logged_in = user.is_some();
+            let collapsed_categories = user
+                .map(|user| user.preferences.collapsed_categories)
+                .unwrap_or_default();
 
             let view = categories
                 .into_iter()
-                .map(|category| CategoryItem(CategoryItemProps { category }))
+                .map(|category| {
+                    let collapsed = collapsed_categories.contains(&category.name);
+                    CategoryItem(CategoryItemProps {
+                        category,
+                        collapsed,
+                        logged_in,
+                    })
+                })
                 .collect_view();
             Either::Right(view)
         })
     };
 
     view! {
-      <Title text="Forums | Dafoerum" />
+      <Title text=move || format!("{} | Dafoerum", i18n.t("nav.forums")) />
       <Suspense fallback=move || {
-        view! { <p>"Loading forums..."</p> }
+        view! { <p>{t!("forum.loading_forums")}</p> }
       }>
         // NotFoundError: Failed to execute 'insertBefore' on 'Node': The node before which the new node is to be inserted is not a child of this node.
         // when using <For /> and navigating to this page from any other
         // see https://github.com/leptos-rs/leptos/issues/3385
         {category_list_view}
       </Suspense>
+      <OnboardingChecklist />
+      <WhosOnline />
     }
 }
 
-/// Renders a single forum category with its forums as a table
+/// Renders the onboarding checklist of a new user until it's complete or dismissed,
+/// see [`api::onboarding`]
+#[component]
+fn OnboardingChecklist() -> impl IntoView {
+    use api::onboarding::OnboardingStep;
+
+    let complete = ServerAction::<api::onboarding::CompleteOnboardingStep>::new();
+    let dismiss = ServerAction::<api::onboarding::DismissOnboarding>::new();
+    let onboarding_res = Resource::new(
+        move || (complete.version().get(), dismiss.version().get()),
+        |_| api::onboarding::get_onboarding(),
+    );
+
+    let checklist_view = move || {
+        Suspend::new(async move {
+            let progress = match onboarding_res.await {
+                Ok(Some(progress)) if !progress.dismissed && !progress.is_complete() => progress,
+                Ok(_) => return None,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return None;
+                }
+            };
+
+            let done_count = progress.completed.len();
+            let steps = OnboardingStep::ALL
+                .into_iter()
+                .map(|step| {
+                    let done = progress.completed.contains(&step);
+                    let mark_done = (step == OnboardingStep::ReadRules && !done).then(|| {
+                        view! {
+                          <button
+                            on:click=move |_| {
+                              complete.dispatch(api::onboarding::CompleteOnboardingStep { step });
+                            }
+                            class="ml-2 text-xs underline hover:no-underline hover:cursor-pointer"
+                          >
+                            {t!("onboarding.done")}
+                          </button>
+                        }
+                    });
+                    view! {
+                      <li class:line-through=done>
+                        {if done { "\u{2611} " } else { "\u{2610} " }}
+                        <a href=step.link() class="hover:underline">
+                          {t!(step.label_key())}
+                        </a>
+                        {mark_done}
+                      </li>
+                    }
+                })
+                .collect_view();
+
+            Some(view! {
+              <section class="p-4 mb-2 text-sm text-purple-900 bg-purple-100 rounded-xs w-19/20 sm:8/10">
+                <div class="flex justify-between items-center">
+                  <h2 class="text-lg font-bold font-display text-purple-950">
+                    {t!("onboarding.title")}
+                    " "
+                    <span class="text-xs font-normal">
+                      "(" {done_count} "/" {OnboardingStep::ALL.len()} ")"
+                    </span>
+                  </h2>
+                  <button
+                    on:click=move |_| {
+                      dismiss.dispatch(api::onboarding::DismissOnboarding {});
+                    }
+                    class="text-xs underline hover:no-underline hover:cursor-pointer"
+                  >
+                    {t!("onboarding.dismiss")}
+                  </button>
+                </div>
+                <ul class="flex flex-col gap-1 mt-2">{steps}</ul>
+              </section>
+            })
+        })
+    };
+
+    view! { <Suspense>{checklist_view}</Suspense> }
+}
+
+/// Renders a panel with the names of logged-in users and the amount of guests
+/// active within the last few minutes, see [`api::presence`]
 #[component]
-fn CategoryItem(category: Category) -> impl IntoView {
+fn WhosOnline() -> impl IntoView {
+    let online_res = Resource::new(move || (), move |()| api::presence::get_online_users());
+
+    let online_view = move || {
+        Suspend::new(async move {
+            let online = match online_res.await {
+                Ok(online) => online,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("online.load_failed")}</p> });
+                }
+            };
+
+            let total = online.names.len() + online.guest_count as usize;
+            let view = view! {
+              <p>
+                <span class="font-medium">{total}</span>
+                " "
+                {t!("online.summary", members = online.names.len(), guests = online.guest_count)}
+              </p>
+              <p>{online.names.join(", ")}</p>
+            };
+            Either::Right(view)
+        })
+    };
+
+    view! {
+      <section class="p-4 mb-2 text-sm text-purple-900 bg-purple-100 rounded-xs w-19/20 sm:8/10">
+        <h2 class="text-lg font-bold font-display text-purple-950">
+          {t!("online.title")}
+          <span class="text-xs font-normal">
+            " " {t!("online.window", minutes = api::presence::ONLINE_WINDOW_MINUTES)}
+          </span>
+        </h2>
+        <Suspense fallback=move || "\u{2026}".into_view()>{online_view}</Suspense>
+      </section>
+    }
+}
+
+/// Renders a single forum category with its description and forums as a table
+///
+/// The section can be collapsed, which is remembered for logged-in users,
+/// see [`api::user::set_category_collapsed`]
+#[component]
+fn CategoryItem(category: Category, collapsed: bool, logged_in: bool) -> impl IntoView {
+    let set_collapsed = ServerAction::<api::user::SetCategoryCollapsed>::new();
+    // the toggle event also fires for sections rendered open, so only changes are saved
+    let is_collapsed = RwSignal::new(collapsed);
+    let name = category.name.clone();
+    let on_toggle = move |open: bool| {
+        if open != is_collapsed.get_untracked() {
+            return;
+        }
+        is_collapsed.set(!open);
+        if logged_in {
+            set_collapsed.dispatch(api::user::SetCategoryCollapsed {
+                category: name.clone(),
+                collapsed: !open,
+            });
+        }
+    };
+
     view! {
       <section
-        id=clean_name_for_id(&category.name)
+        id=category_anchor(&category.name)
         class="p-4 mb-2 bg-purple-200 shadow-[0_3px_0_theme(colors.purple.300)] rounded-xs w-19/20 sm:8/10"
       >
-        <h2 class="text-2xl font-bold font-display text-purple-950">{category.name.clone()}</h2>
-        <table class="w-full table-fixed">
-          <thead>
-            <tr>
-              <th scope="col" class="w-20">
-                "Forum"
-              </th>
-              <th scope="col" class="w-40">
-                "Last activity"
-              </th>
-              <th scope="col" class="w-15">
-                "#"
-              </th>
-            </tr>
-          </thead>
-          <tbody>
-            {category
-              .forums
-              .into_iter()
-              .map(|forum: Forum| ForumRow(ForumRowProps { forum }))
-              .collect_view()}
-          </tbody>
-        </table>
+        <details open=!collapsed on:toggle:target=move |ev| on_toggle(ev.target().open())>
+          <summary class="hover:cursor-pointer">
+            <h2 class="inline text-2xl font-bold font-display text-purple-950">
+              {category.name.clone()}
+            </h2>
+          </summary>
+          {category
+            .description
+            .map(|description| view! { <p class="mb-2 text-purple-900">{description}</p> })}
+          <table class="w-full table-fixed">
+            <thead>
+              <tr>
+                <th scope="col" class="w-20">
+                  {t!("forum.column.forum")}
+                </th>
+                <th scope="col" class="w-40">
+                  {t!("forum.column.last_activity")}
+                </th>
+                <th scope="col" class="w-15">
+                  "#"
+                </th>
+              </tr>
+            </thead>
+            <tbody>
+              {category
+                .forums
+                .into_iter()
+                .filter(|forum| forum.parent_forum_id.is_none())
+                .map(|forum: Forum| ForumRow(ForumRowProps { forum }))
+                .collect_view()}
+            </tbody>
+          </table>
+        </details>
       </section>
     }
 }
@@ -89,6 +275,7 @@ fn CategoryItem(category: Category) -> impl IntoView {
 /// Renders a table row containing info on a [`Forum`]
 #[component]
 fn ForumRow(forum: Forum) -> impl IntoView {
+    let i18n = use_i18n();
     let latest_thread_res = Resource::new(
         move || (),
         move |()| api::get_latest_post_and_thread(forum.latest_thread_id),
@@ -105,7 +292,7 @@ fn ForumRow(forum: Forum) -> impl IntoView {
                 Ok(counts) => counts,
                 Err(err) => {
                     logging::log!("{err:?} - {err}");
-                    return Either::Left(view! { <p>"Thread couldn't be loaded!"</p> });
+                    return Either::Left(view! { <p>{t!("forum.thread_load_failed")}</p> });
                 }
             };
 
@@ -115,13 +302,12 @@ fn ForumRow(forum: Forum) -> impl IntoView {
                 {..}
                 class="block overflow-hidden w-full underline whitespace-nowrap hover:no-underline overflow-ellipsis"
               >
-                {thread.subject}
+                {thread.display_subject()}
               </A>
               <p>
-                "Last post "
-                <time datetime=post
-                  .created_at
-                  .to_string()>{post.created_at.ago()}" minutes ago"</time>
+                {t!("forum.last_post")}
+                " "
+                <LocalTime timestamp=post.created_at relative=true />
               </p>
             };
             Either::Right(view)
@@ -134,13 +320,13 @@ fn ForumRow(forum: Forum) -> impl IntoView {
                 Ok(counts) => counts,
                 Err(err) => {
                     logging::log!("{err:?} - {err}");
-                    return Either::Left(view! { <p>{err.to_string()}</p> });
+                    return Either::Left(view! { <p>{i18n.error(&err)}</p> });
                 }
             };
 
             let view = view! {
-              <p>"Threads: "<span class="font-medium">{thread_count}</span></p>
-              <p>"Posts: "<span class="font-medium">{post_count}</span></p>
+              <p>{t!("forum.thread_count")} " " <span class="font-medium">{thread_count}</span></p>
+              <p>{t!("forum.post_count")} " " <span class="font-medium">{post_count}</span></p>
             };
             Either::Right(view)
         })
@@ -150,12 +336,14 @@ fn ForumRow(forum: Forum) -> impl IntoView {
       <tr class="text-purple-900 not-last:border-dotted not-last:border-purple-300 not-last:border-b-4">
         <th scope="row" class="text-lg">
           <A
-            href=forum.id.to_string()
+            href=format!("/forum/{}", forum.id)
             {..}
             class="block overflow-hidden w-full font-bold underline whitespace-nowrap hover:no-underline overflow-ellipsis"
           >
+            {forum.icon.map(|icon| format!("{icon} "))}
             {forum.name}
           </A>
+          <p class="text-sm font-normal">{forum.description}</p>
         </th>
 
         <td class="py-2 leading-5">
@@ -168,6 +356,97 @@ fn ForumRow(forum: Forum) -> impl IntoView {
     }
 }
 
+/// Renders the subforums of a [`Forum`] as a table, nothing if there are none
+#[component]
+fn Subforums(forum_id: u32) -> impl IntoView {
+    let subforums_res = Resource::new(move || (), move |()| api::get_subforums(forum_id));
+
+    let subforums_view = move || {
+        Suspend::new(async move {
+            let subforums = match subforums_res.await {
+                Ok(subforums) => subforums,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("forum.subforums_load_failed")}</p> });
+                }
+            };
+            if subforums.is_empty() {
+                return EitherOf3::B(());
+            }
+
+            let rows = subforums
+                .into_iter()
+                .map(|forum| ForumRow(ForumRowProps { forum }))
+                .collect_view();
+            EitherOf3::C(view! {
+              <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+                <h2 class="text-2xl font-bold font-display text-purple-950">
+                  {t!("forum.subforums")}
+                </h2>
+                <table class="w-full table-fixed">
+                  <thead>
+                    <tr>
+                      <th scope="col" class="w-20">
+                        {t!("forum.column.forum")}
+                      </th>
+                      <th scope="col" class="w-40">
+                        {t!("forum.column.last_activity")}
+                      </th>
+                      <th scope="col" class="w-15">
+                        "#"
+                      </th>
+                    </tr>
+                  </thead>
+                  <tbody>{rows}</tbody>
+                </table>
+              </section>
+            })
+        })
+    };
+
+    view! { <Suspense>{subforums_view}</Suspense> }
+}
+
+/// Renders a link to the forum index followed by the `crumbs`,
+/// see [`get_forum_breadcrumbs`][api::breadcrumbs::get_forum_breadcrumbs]
+/// and [`get_thread_breadcrumbs`][api::breadcrumbs::get_thread_breadcrumbs]
+///
+/// The last crumb is the current page
+#[component]
+pub fn Breadcrumbs(crumbs: Vec<Crumb>) -> impl IntoView {
+    let last = crumbs.len().saturating_sub(1);
+    let crumbs_view = crumbs
+        .into_iter()
+        .enumerate()
+        .map(|(i, Crumb { label, href })| {
+            let current = i == last;
+            view! {
+              " -> "
+              <a
+                href=href
+                aria-current=current.then_some("page")
+                class=if current {
+                  "font-medium hover:underline"
+                } else {
+                  "font-medium underline hover:no-underline"
+                }
+              >
+                {label}
+              </a>
+            }
+        })
+        .collect_view();
+
+    view! {
+      <nav aria-label=t!("forum.breadcrumbs") class="mb-2 w-full text-purple-900">
+        <a href="/forum" class="font-medium underline hover:no-underline">
+          {t!("nav.forums")}
+        </a>
+        {crumbs_view}
+      </nav>
+    }
+}
+
 /// Parameters for /forum/:id
 #[derive(Params, PartialEq, Clone, Copy)]
 struct ForumParams {
@@ -177,13 +456,14 @@ struct ForumParams {
 /// Renders the thread list of a [`Forum`]
 #[component]
 pub fn ForumOverview() -> impl IntoView {
-    let title_format = |text| format!("{text} - Forums | Dafoerum");
+    let i18n = use_i18n();
+    let title_format = move |text| format!("{text} - {} | Dafoerum", i18n.t("nav.forums"));
     let params = use_params::<ForumParams>();
     let Ok(ForumParams { id }) = params.get_untracked() else {
         return Either::Left(view! {
-          <h2 class="text-4xl font-bold">"Invalid id!"</h2>
+          <h2 class="text-4xl font-bold">{t!("common.invalid_id")}</h2>
           <a href="/" class="block rounded-sm hover:text-blue-700">
-            <h3 class="text-3xl font-bold">"Go to the frontpage"</h3>
+            <h3 class="text-3xl font-bold">{t!("common.go_to_frontpage")}</h3>
           </a>
         });
     };
@@ -191,14 +471,38 @@ pub fn ForumOverview() -> impl IntoView {
     let create_thread_modal_ref = NodeRef::<Dialog>::new();
 
     let (error, set_error) = signal::<Option<ApiError>>(None);
+    let (unsolved_only, set_unsolved_only) = signal(false);
+    let (deleted_only, set_deleted_only) = signal(false);
 
     let forum_res = Resource::new(move || (), move |()| api::get_forum(id));
+    let crumbs_res = Resource::new(
+        move || (),
+        move |()| api::breadcrumbs::get_forum_breadcrumbs(id),
+    );
+    let set_hidden = ServerAction::<api::user::SetForumHidden>::new();
+    let user_res = Resource::new(
+        move || set_hidden.version().get(),
+        |_| api::user::get_current_user(),
+    );
+    let toasts = use_toasts();
+    Effect::new(move || {
+        if let Some(Err(err)) = set_hidden.value().get() {
+            toasts.push_error(i18n.error(&err));
+        }
+    });
+    let moderating = Signal::derive(move || {
+        user_res
+            .get()
+            .and_then(Result::ok)
+            .flatten()
+            .is_some_and(|user| user.role >= api::user::Role::Moderator)
+    });
     let forum_head_view = move || {
         let Some(forum_res) = forum_res.get() else {
             // necessary check bc <Suspense/> will render children once before resource is loaded
             return EitherOf3::A(view! { <p>"initial"</p> });
         };
-        let (forum, category_name) = match forum_res {
+        let (forum, _) = match forum_res {
             Ok(forum) => {
                 set_error(None);
                 forum
@@ -210,27 +514,43 @@ pub fn ForumOverview() -> impl IntoView {
                 return EitherOf3::B(().into_view());
             }
         };
+        let user = user_res.get().and_then(Result::ok).flatten();
+        let is_moderator = user
+            .as_ref()
+            .is_some_and(|user| user.role >= api::user::Role::Moderator);
+        let hide_form = user.map(|user| {
+            let hidden = user.preferences.hidden_forum_ids.contains(&forum.id);
+            view! {
+              <ActionForm action=set_hidden attr:class="mt-2">
+                <input type="hidden" name="forum_id" value=forum.id />
+                <input type="hidden" name="hidden" value=(!hidden).to_string() />
+                <input
+                  type="submit"
+                  value=t!(if hidden { "forum.show_in_latest" } else { "forum.hide_from_latest" })
+                  class="text-sm text-purple-900 underline hover:no-underline hover:cursor-pointer"
+                />
+              </ActionForm>
+            }
+        });
+        let feed_title = i18n.t_with("forum.feed_title", &[("name", forum.name.clone())]);
         EitherOf3::C(view! {
-          <Title text=forum.name.to_string() formatter=title_format />
-          <nav class="mb-2 w-full text-purple-900">
-            <a href="/forum" class="font-medium underline hover:no-underline">
-              "Forums"
-            </a>
-            " -> "
-            // adding IDs and linking to it sucks in SPA - it will only scroll to it on page refresh
-            <a
-              href=format!("/forum#{}", clean_name_for_id(&category_name))
-              class="font-medium underline hover:no-underline"
-            >
-              {category_name.to_string()}
-            </a>
-            " -> "
-            <a href=format!("/forum/{}", forum.id) class="font-medium hover:underline">
-              {forum.name.to_string()}
-            </a>
-          </nav>
+          <Title text=forum.name.clone() formatter=title_format />
+          <PageMeta
+            title=forum.name.clone()
+            description=forum
+              .description
+              .clone()
+              .unwrap_or_else(|| {
+                i18n.t_with("forum.meta_description", &[("name", forum.name.clone())])
+              })
+          />
+          // linking to the category only scrolls to it on a full page load
+          {move || {
+            crumbs_res.get().and_then(Result::ok).map(|crumbs| view! { <Breadcrumbs crumbs /> })
+          }}
           <div class="flex flex-wrap justify-between mb-2">
             <h1 class="text-3xl font-extrabold md:text-4xl lg:text-5xl text-purple-950 font-display">
+              {forum.icon.map(|icon| format!("{icon} "))}
               {forum.name}
             </h1>
             // https://developer.mozilla.org/en-US/docs/Web/HTML/Element/button#browser_compatibility
@@ -254,29 +574,46 @@ pub fn ForumOverview() -> impl IntoView {
               }
               class="flex justify-center items-center py-1 px-2 text-sm font-bold text-purple-100 bg-purple-800 rounded-full sm:py-2 sm:px-4 md:py-3 md:px-6 md:text-lg lg:text-xl hover:bg-purple-900 hover:cursor-pointer sm:text-md"
             >
-              "Create Thread"
+              {t!("forum.create_thread")}
             </button>
           </div>
-          <p>"Here will come a short description of the forum some day"</p>
+          <p>{forum.description}</p>
+          <FollowViaRss scope=FeedScope::Forum(forum.id) title=feed_title />
+          <WatchForumButton forum_id=forum.id />
+          <Show when=move || forum.solved_workflow>
+            <label class="block mt-2 font-medium text-purple-900">
+              <input
+                type="checkbox"
+                prop:checked=unsolved_only
+                on:change:target=move |ev| set_unsolved_only(ev.target().checked())
+              />
+              " "
+              {t!("forum.unsolved_only")}
+            </label>
+          </Show>
+          <Show when=move || is_moderator>
+            <label class="block mt-2 font-medium text-purple-900">
+              <input
+                type="checkbox"
+                prop:checked=deleted_only
+                on:change:target=move |ev| set_deleted_only(ev.target().checked())
+              />
+              " "
+              {t!("forum.deleted_only")}
+            </label>
+          </Show>
+          {hide_form}
         })
     };
 
     let errored_view = move || {
-        let Some(api_error) = error() else {
-            return Either::Left(view! { <p>"This shouldn't happen!"</p> });
-        };
-
-        let msg = match api_error {
-            ApiError::NotFound(_, id) => {
-                format!("There exists no forum with the id {id}")
-            }
-            _ => format!("Error from server: {api_error}"),
+        let Some(err) = error() else {
+            return Either::Left(view! { <p>{t!("forum.unexpected")}</p> });
         };
-
-        Either::Right(view! { <p class="text-lg font-bold text-red-700">{msg}</p> })
+        Either::Right(view! { <ErrorPage err /> })
     };
     let waiting_view = move || {
-        view! { <p>"Loading the forum..."</p> }
+        view! { <p>{t!("forum.loading")}</p> }
     };
 
     // for future reference: (still not sure though)
@@ -293,10 +630,12 @@ pub fn ForumOverview() -> impl IntoView {
     Either::Right(view! {
       <Suspense fallback=waiting_view>
         <Show when=move || error().is_none() fallback=errored_view>
+          <AnnouncementBanners forum_id=Some(forum_id) />
           <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">{forum_head_view}</section>
+          <Subforums forum_id />
           <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
             <CreateThreadModal id=create_thread_modal_id forum_id create_thread_modal_ref />
-            <ThreadList forum_id />
+            <ThreadList forum_id unsolved_only deleted_only moderating />
           </section>
         </Show>
       </Suspense>
@@ -316,13 +655,21 @@ pub fn CreateThreadModal(
     forum_id: u32,
     create_thread_modal_ref: NodeRef<Dialog>,
 ) -> impl IntoView {
+    let i18n = use_i18n();
     let create_thread = ServerAction::<api::CreateThread>::new();
+    let attachments = RwSignal::new(vec![]);
+    let draft = use_draft(DraftKey::NewThread { forum_id });
+    let previewing = RwSignal::new(false);
 
     // redirect to created thread on thread creation
     Effect::new(move |_| {
         let Some(result) = create_thread.value().get() else {
             return;
         };
+        if let Ok(_) | Err(ApiError::HeldForReview) = result {
+            draft.discard();
+            previewing.set(false);
+        }
         if let Ok(thread_id) = result {
             let navigate = use_navigate();
             let url = format!("/thread/{thread_id}");
@@ -330,32 +677,50 @@ pub fn CreateThreadModal(
         }
     });
 
-    // server-side error handling
-    let form_errored_view = move || {
-        // will be None before first dispatch
-        let Some(val) = create_thread.value().get() else {
-            return Either::Left(().into_view());
-        };
-        // Will be Ok if no errors occured
-        let Err(e) = val else {
-            return Either::Left(().into_view());
-        };
-
-        logging::log!("{e:?} - {e}");
-
-        let msg = match e {
-            ApiError::EmptyContent => "Post content cannot be empty!".into(),
-            ApiError::EmptySubject => "Subject cannot be empty!".into(),
-            _ => e.to_string(),
-        };
-
-        let view = view! {
-          <div class="flex flex-col justify-center items-center p-2 mb-4 text-lg font-bold text-red-700 bg-red-50 border-2 border-red-400">
-            <p class="font-bold">"Error from server:"</p>
-            <p>{msg}</p>
-          </div>
+    // errors of single fields are shown below them, the others as toasts
+    let (subject_error, set_subject_error) = signal::<Option<String>>(None);
+    let (content_error, set_content_error) = signal::<Option<String>>(None);
+    let toasts = use_toasts();
+    Effect::new(move || {
+        let Some(Err(e)) = create_thread.value().get() else {
+            return;
         };
-        Either::Right(view)
+        match e {
+            // shown as banners above the form
+            ApiError::Banned { .. }
+            | ApiError::Muted { .. }
+            | ApiError::PostingRequirementsNotMet { .. } => {}
+            ApiError::HeldForReview => toasts.push_success(i18n.error(&e)),
+            ApiError::Validation { field, .. } => match field {
+                Field::Subject => set_subject_error(Some(i18n.error(&e))),
+                _ => set_content_error(Some(i18n.error(&e))),
+            },
+            _ => {
+                logging::log!("{e:?} - {e}");
+                toasts.push_error(i18n.error(&e));
+            }
+        }
+    });
+    let banner = move || match create_thread.value().get()? {
+        Err(ApiError::Banned { until, reason }) => Some(Either::Left(view! {
+          <SanctionBanner banned=true until reason />
+        })),
+        Err(ApiError::Muted { until, reason }) => Some(Either::Left(view! {
+          <SanctionBanner banned=false until reason />
+        })),
+        Err(ApiError::PostingRequirementsNotMet {
+            min_account_age_hours,
+            min_post_count,
+        }) => Some(Either::Right(view! {
+          <RequirementsBanner min_account_age_hours min_post_count />
+        })),
+        _ => None,
+    };
+    let field_error = |error: ReadSignal<Option<String>>| {
+        move || {
+            error()
+                .map(|error| view! { <p class="mb-2 text-sm font-bold text-red-700">{error}</p> })
+        }
     };
 
     view! {
@@ -365,40 +730,58 @@ pub fn CreateThreadModal(
         // center, but more top on mobile bc of on-screen keyboard
         class="fixed left-1/2 top-1/3 p-4 text-purple-900 bg-purple-50 rounded-xl border-2 border-purple-200 -translate-x-1/2 -translate-y-1/3 sm:top-1/2 sm:p-8 sm:-translate-y-1/2 md:p-12 backdrop:backdrop-blur-[2px] w-sm md:w-md"
       >
-        {form_errored_view}
-        <ActionForm action=create_thread attr:class="w-full">
-          <input class="hidden" name="forum_id" value=forum_id />
-          <label class="font-medium">
-            "Subject"
+        {banner}
+        <ForumRulesGate forum_id>
+          <ActionForm action=create_thread attr:class="w-full">
+            <input class="hidden" name="forum_id" value=forum_id />
+            <CsrfField />
+            <ChallengeWidget reset=create_thread.version() />
+            <label class="font-medium">
+              {t!("forum.subject")}
+              <input
+                name="subject"
+                placeholder=t!("forum.subject_placeholder")
+                required
+                on:input:target=move |ev| {
+                  set_subject_error(None);
+                  draft.set_subject(ev.target().value());
+                }
+                prop:value=move || draft.subject()
+                class="p-2.5 mb-2 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400 placeholder:italic"
+              />
+            </label>
+            {field_error(subject_error)}
+            <label class="font-medium">
+              {t!("forum.content")}
+              <MarkdownEditor
+                name="post_content"
+                value=Signal::derive(move || draft.content())
+                on_input=Callback::new(move |content: String| {
+                  set_content_error(None);
+                  draft.set_content(content);
+                })
+                previewing
+                placeholder="forum.content_placeholder"
+                textarea_class="py-2 px-4 mb-4 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400 placeholder:italic"
+              />
+            </label>
+            {field_error(content_error)}
+            <attachment::AttachmentUploader attachments />
+            <div class="flex justify-end mb-2">
+              <DiscardDraftButton draft />
+            </div>
             <input
-              name="subject"
-              placeholder="Greatest thread ever"
-              required
-              class="p-2.5 mb-2 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400 placeholder:italic"
+              type="submit"
+              value=t!("forum.create_thread")
+              class="flex justify-center items-center py-1 mb-2 w-full font-bold text-purple-100 bg-purple-800 rounded-lg sm:py-2 sm:text-lg md:text-xl hover:bg-purple-900 hover:cursor-pointer text-md"
             />
-          </label>
-          <label class="font-medium">
-            "Content"
-            <textarea
-              name="post_content"
-              rows="5"
-              placeholder="Type here using Markdown (soon\u{2122})..."
-              required
-              wrap="soft"
-              class="py-2 px-4 mb-4 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400 placeholder:italic"
-            ></textarea>
-          </label>
-          <input
-            type="submit"
-            value="Create Thread"
-            class="flex justify-center items-center py-1 mb-2 w-full font-bold text-purple-100 bg-purple-800 rounded-lg sm:py-2 sm:text-lg md:text-xl hover:bg-purple-900 hover:cursor-pointer text-md"
-          />
-        </ActionForm>
+          </ActionForm>
+        </ForumRulesGate>
         // <button commandfor="create-thread-modal" command="close" class="p-2 bg-purple-100">
         <form method="dialog">
           <input
             type="submit"
-            value="Cancel"
+            value=t!("common.cancel")
             class="flex justify-center items-center py-1 mb-2 w-full font-bold text-red-50 bg-red-800 rounded-lg sm:py-2 sm:text-lg md:text-xl hover:bg-red-900 hover:cursor-pointer text-md"
           />
         </form>
@@ -408,10 +791,47 @@ pub fn CreateThreadModal(
 
 /// Renders a list of all [`Threads`][Thread] of a given [`Forum`]
 #[component]
-pub fn ThreadList(forum_id: u32) -> impl IntoView {
-    let threads_res = Resource::new(move || (), move |()| api::get_threads(forum_id));
+pub fn ThreadList(
+    forum_id: u32,
+    /// Leave out solved threads, see [`Forum::solved_workflow`]
+    #[prop(into)]
+    unsolved_only: Signal<bool>,
+    /// Only show soft-deleted threads, for moderators
+    #[prop(into)]
+    deleted_only: Signal<bool>,
+    /// Show checkboxes for selecting threads and the [`BulkTagEditor`]
+    #[prop(into)]
+    moderating: Signal<bool>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let bulk_edit = ServerAction::<api::thread_tools::BulkEditThreadTags>::new();
+    let selected = RwSignal::new(Vec::<u32>::new());
+    let threads_res = Resource::new(
+        move || {
+            (
+                unsolved_only.get(),
+                deleted_only.get(),
+                bulk_edit.version().get(),
+            )
+        },
+        move |(unsolved_only, deleted_only, _)| {
+            api::get_threads(forum_id, unsolved_only, deleted_only)
+        },
+    );
+    let markers_res = Resource::new(
+        move || (),
+        move |()| api::read_markers::get_read_markers(forum_id),
+    );
 
+    // shown as a toast, since loading happens on the server first
     let (error, set_error) = signal::<Option<ApiError>>(None);
+    let toasts = use_toasts();
+    Effect::new(move || {
+        if let Some(err) = error() {
+            let error = i18n.error(&err);
+            toasts.push_error(i18n.t_with("forum.threads_load_failed", &[("error", error)]));
+        }
+    });
 
     let thread_list_view = move || {
         Suspend::new(async move {
@@ -429,14 +849,30 @@ pub fn ThreadList(forum_id: u32) -> impl IntoView {
 
             // show threads with more recent activity first
             threads.sort_unstable_by_key(|(_, _, post)| std::cmp::Reverse(post.created_at));
+            // None if not logged in, then nothing is unread
+            let markers = match markers_res.await {
+                Ok(markers) => markers,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    None
+                }
+            };
 
             let view = threads
                 .into_iter()
                 .map(|(thread, post_count, latest_post)| {
+                    let unread = markers.as_ref().is_some_and(|markers| {
+                        markers
+                            .iter()
+                            .find(|marker| marker.thread_id == thread.id)
+                            .is_none_or(|marker| marker.last_read_post_id < latest_post.id)
+                    });
                     ThreadRow(ThreadRowProps {
                         thread,
                         post_count,
                         latest_post,
+                        unread,
+                        selected: moderating.get().then_some(selected),
                     })
                 })
                 .collect_view();
@@ -445,79 +881,662 @@ pub fn ThreadList(forum_id: u32) -> impl IntoView {
     };
 
     view! {
-      <Show
-        when=move || error().is_none()
-        fallback=move || {
-          view! {
-            <p class="text-lg font-bold text-center text-red-700">
-              "Threads couldn't be loaded due to an error: "
-              {error().unwrap_or_default().to_string()}
-            </p>
-          }
-        }
-      >
-
-        <table class="w-full table-fixed">
-          <thead>
-            <tr>
-              <th scope="col" class="w-40">
-                "Thread"
-              </th>
-              <th scope="col" class="w-20">
-                "Last activity"
-              </th>
-              <th scope="col" class="w-10">
-                "#"
-              </th>
-            </tr>
-          </thead>
-          <tbody>
-            <Suspense fallback=move || {
-              view! {
-                <tr class="text-purple-900">
-                  <th scope="row" colspan="3" class="text-2xl text-center animate-bounce">
-                    "\u{2026}"
-                  </th>
-                </tr>
-              }
-            }>{thread_list_view}</Suspense>
-          </tbody>
-        </table>
+      <Show when=move || moderating.get()>
+        <BulkTagEditor selected bulk_edit />
       </Show>
+      <table class="w-full table-fixed">
+        <thead>
+          <tr>
+            <th scope="col" class="w-40">
+              {t!("forum.column.thread")}
+            </th>
+            <th scope="col" class="w-20">
+              {t!("forum.column.last_activity")}
+            </th>
+            <th scope="col" class="w-10">
+              "#"
+            </th>
+          </tr>
+        </thead>
+        <tbody>
+          <Suspense fallback=move || {
+            view! {
+              <tr class="text-purple-900">
+                <th scope="row" colspan="3" class="text-2xl text-center animate-bounce">
+                  "\u{2026}"
+                </th>
+              </tr>
+            }
+          }>{thread_list_view}</Suspense>
+        </tbody>
+      </table>
     }
 }
 
-/// A table row representing a [`Thread`]
+/// A table row representing a [`Thread`], with its subject in bold if it has `unread` posts
+///
+/// With `selected`, a checkbox adds or removes the thread from it
 #[component]
-fn ThreadRow(thread: Thread, post_count: u64, latest_post: Post) -> impl IntoView {
+fn ThreadRow(
+    thread: Thread,
+    post_count: u64,
+    latest_post: Post,
+    unread: bool,
+    selected: Option<RwSignal<Vec<u32>>>,
+) -> impl IntoView {
+    let weight = if unread { "font-bold" } else { "font-normal" };
+    let thread_id = thread.id;
+    let checkbox = selected.map(|selected| {
+        view! {
+          <input
+            type="checkbox"
+            class="float-left mt-2"
+            prop:checked=move || selected.with(|ids| ids.contains(&thread_id))
+            on:change:target=move |ev| {
+              let checked = ev.target().checked();
+              selected
+                .update(|ids| {
+                  ids.retain(|id| *id != thread_id);
+                  if checked {
+                    ids.push(thread_id);
+                  }
+                });
+            }
+          />
+        }
+    });
+    let tags = thread
+        .tags
+        .iter()
+        .map(|tag| {
+            view! {
+              <span class="py-0.5 px-2 mr-1 text-xs font-normal bg-purple-100 rounded-full">
+                {tag.clone()}
+              </span>
+            }
+        })
+        .collect_view();
     view! {
       <tr class="text-center text-purple-900 not-last:border-dotted not-last:border-purple-300 not-last:border-b-4">
         <th scope="row" class="text-lg">
+          {checkbox}
           <a
             href=format!("/thread/{}", thread.id)
-            class="block overflow-hidden w-full font-bold underline whitespace-nowrap hover:no-underline overflow-ellipsis"
+            class=format!(
+              "block overflow-hidden w-full underline whitespace-nowrap hover:no-underline overflow-ellipsis {weight}",
+            )
           >
-            {thread.subject}
+            {thread.display_subject()}
           </a>
+          <div>{tags}</div>
+          {thread
+            .author_id
+            .map(|user_id| {
+              view! {
+                <span class="text-sm font-normal">
+                  <MemberName user_id avatar=true />
+                </span>
+              }
+            })}
         </th>
 
         <td class="py-2 leading-5 text-center">
-          <time datetime=latest_post.created_at.to_string()>{latest_post.created_at.ago()}</time>
-          " minutes ago"
+          <LocalTime timestamp=latest_post.created_at relative=true />
         </td>
         <td class="py-2 leading-5 text-center">{post_count}</td>
       </tr>
     }
 }
 
-/// Cleans up a [`Category`] name or w/e to make it usable as the id of an HTML element
+/// Renders a form for moderators adding and removing tags or setting the prefix
+/// of all `selected` [`Threads`][Thread] at once, with the outcome for each of them
+#[component]
+fn BulkTagEditor(
+    selected: RwSignal<Vec<u32>>,
+    bulk_edit: ServerAction<api::thread_tools::BulkEditThreadTags>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let add_tags = RwSignal::new(String::new());
+    let remove_tags = RwSignal::new(String::new());
+    let prefix = RwSignal::new(String::new());
+    let clear_prefix = RwSignal::new(false);
+
+    let split_tags = |tags: String| {
+        tags.split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+    let apply = move |ev: leptos::ev::SubmitEvent| {
+        ev.prevent_default();
+        let prefix = prefix.get_untracked();
+        bulk_edit.dispatch(api::thread_tools::BulkEditThreadTags {
+            thread_ids: selected.get_untracked(),
+            add_tags: split_tags(add_tags.get_untracked()),
+            remove_tags: split_tags(remove_tags.get_untracked()),
+            prefix: (!prefix.trim().is_empty()).then_some(prefix),
+            clear_prefix: clear_prefix.get_untracked(),
+        });
+    };
+
+    // clear the form once it went through, keeping failed threads selected to retry them
+    Effect::new(move |_| {
+        let Some(Ok(results)) = bulk_edit.value().get() else {
+            return;
+        };
+        selected.set(
+            results
+                .iter()
+                .filter(|outcome| outcome.result.is_err())
+                .map(|outcome| outcome.thread_id)
+                .collect(),
+        );
+        add_tags.set(String::new());
+        remove_tags.set(String::new());
+        prefix.set(String::new());
+        clear_prefix.set(false);
+    });
+
+    let results_view = move || {
+        bulk_edit.value().get().map(|result| match result {
+            Ok(results) => {
+                let updated = results
+                    .iter()
+                    .filter(|outcome| outcome.result.is_ok())
+                    .count();
+                let failures = results
+                    .into_iter()
+                    .filter_map(|outcome| {
+                        outcome.result.err().map(|err| {
+                            view! {
+                              <li>
+                                {t!("thread.thread_number", id = outcome.thread_id)} ": "
+                                {i18n.error(&err)}
+                              </li>
+                            }
+                        })
+                    })
+                    .collect_view();
+                let updated = i64::try_from(updated).unwrap_or(i64::MAX);
+                Either::Left(view! {
+                  <p>{move || i18n.t_count("forum.bulk.updated", updated)}</p>
+                  <ul class="text-red-700 list-disc list-inside">{failures}</ul>
+                })
+            }
+            Err(err) => Either::Right(view! { <p class="text-red-700">{i18n.error(&err)}</p> }),
+        })
+    };
+
+    let input_class = "py-1 px-2 text-sm bg-white rounded-lg";
+    // type-erased, the nested view type of ForumOverview gets too deep for rustc otherwise
+    view! {
+      <form on:submit=apply class="flex flex-wrap gap-2 items-center mb-2 text-sm text-purple-900">
+        <span class="font-medium">
+          {move || {
+            let count = selected.with(Vec::len).to_string();
+            i18n.t_with("forum.bulk.selected", &[("count", count)])
+          }}
+        </span>
+        <input
+          type="text"
+          placeholder=t!("forum.bulk.add_tags")
+          class=input_class
+          prop:value=add_tags
+          on:input:target=move |ev| add_tags.set(ev.target().value())
+        />
+        <input
+          type="text"
+          placeholder=t!("forum.bulk.remove_tags")
+          class=input_class
+          prop:value=remove_tags
+          on:input:target=move |ev| remove_tags.set(ev.target().value())
+        />
+        <input
+          type="text"
+          placeholder=t!("forum.bulk.prefix")
+          maxlength=api::thread_tools::MAX_PREFIX_LEN
+          class=input_class
+          prop:value=prefix
+          prop:disabled=clear_prefix
+          on:input:target=move |ev| prefix.set(ev.target().value())
+        />
+        <label>
+          <input
+            type="checkbox"
+            prop:checked=clear_prefix
+            on:change:target=move |ev| clear_prefix.set(ev.target().checked())
+          />
+          " "
+          {t!("forum.bulk.remove_prefix")}
+        </label>
+        <input
+          type="submit"
+          value=t!("forum.bulk.apply")
+          prop:disabled=move || selected.with(Vec::is_empty) || bulk_edit.pending().get()
+          class="py-1 px-3 font-bold text-purple-100 bg-purple-800 rounded-full hover:bg-purple-900 hover:cursor-pointer disabled:opacity-50"
+        />
+      </form>
+      <div class="mb-2 text-sm">{results_view}</div>
+    }
+    .into_any()
+}
+
+/// Lists the threads nobody replied to yet, of all forums or a single one,
+/// for members who like to help answering them, see [`api::unanswered`]
+#[component]
+pub fn UnansweredThreads() -> impl IntoView {
+    let i18n = use_i18n();
+    let (forum_id, set_forum_id) = signal::<Option<u32>>(None);
+    let (min_age_hours, set_min_age_hours) = signal(api::unanswered::DEFAULT_MIN_AGE_HOURS);
+    let categories_res = Resource::new(move || (), |()| api::get_categories());
+    let threads_res = Resource::new(
+        move || (forum_id.get(), min_age_hours.get()),
+        |(forum_id, min_age_hours)| {
+            api::unanswered::get_unanswered_threads(forum_id, min_age_hours)
+        },
+    );
+
+    let forum_options = move || {
+        Suspend::new(async move {
+            categories_res
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .flat_map(|category| category.forums)
+                .map(|forum| view! { <option value=forum.id>{forum.name}</option> })
+                .collect_view()
+        })
+    };
+
+    let thread_list_view = move || {
+        Suspend::new(async move {
+            let threads = match threads_res.await {
+                Ok(threads) => threads,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! {
+                      <p>{t!("forum.threads_load_failed", error = i18n.error(&err))}</p>
+                    });
+                }
+            };
+            if threads.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("unanswered.none")}</p> });
+            }
+            let view = threads
+                .into_iter()
+                .map(|unanswered| {
+                    view! {
+                      <li class="p-2 bg-purple-50 rounded-lg">
+                        <a
+                          href=format!("/thread/{}", unanswered.thread.id)
+                          class="font-bold underline hover:no-underline"
+                        >
+                          {unanswered.thread.display_subject()}
+                        </a>
+                        <p class="text-sm">
+                          {t!("unanswered.in_forum", forum = unanswered.forum_name)} ", "
+                          {t!("unanswered.started")} " "
+                          <LocalTime timestamp=unanswered.created_at relative=true />
+                        </p>
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ol class="flex flex-col gap-2 max-w-xl">{view}</ol> })
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("unanswered.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("unanswered.title")}</h1>
+      <p>{t!("unanswered.text")}</p>
+      <div class="flex flex-wrap gap-4 items-center my-2 text-purple-900">
+        <label class="font-medium">
+          {t!("unanswered.forum")}
+          " "
+          <select
+            class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            on:change:target=move |ev| set_forum_id(ev.target().value().parse().ok())
+          >
+            <option value="">{t!("unanswered.all_forums")}</option>
+            <Suspense>{forum_options}</Suspense>
+          </select>
+        </label>
+        <label class="font-medium">
+          {t!("unanswered.older_than")}
+          " "
+          <input
+            type="number"
+            min="0"
+            max=api::unanswered::MAX_MIN_AGE_HOURS
+            class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            prop:value=move || min_age_hours().to_string()
+            on:change:target=move |ev| {
+              if let Ok(hours) = ev.target().value().parse() {
+                set_min_age_hours(hours);
+              }
+            }
+          />
+          " "
+          {t!("unanswered.hours")}
+        </label>
+      </div>
+      <UnansweredDigestButton />
+      <Transition fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{thread_list_view}</Transition>
+    }
+}
+
+/// Renders a button for logged-in members opting in to or out of a weekly email
+/// of the unanswered threads, see [`api::unanswered::UnansweredDigest`]
+#[component]
+fn UnansweredDigestButton() -> impl IntoView {
+    let set_digest = ServerAction::<api::unanswered::SetUnansweredDigest>::new();
+    let digest_res = Resource::new(
+        move || set_digest.version().get(),
+        move |_| api::unanswered::get_unanswered_digest(),
+    );
+
+    let button_view = move || {
+        Suspend::new(async move {
+            // None if not logged in, guests can't be emailed
+            let enabled = digest_res.await.ok().flatten()?;
+            Some(view! {
+              <button
+                type="button"
+                on:click=move |_| {
+                  set_digest
+                    .dispatch(api::unanswered::SetUnansweredDigest {
+                      enabled: !enabled,
+                    });
+                }
+                class="mb-2 text-sm text-purple-900 underline hover:no-underline hover:cursor-pointer"
+              >
+                {t!(if enabled { "unanswered.digest.disable" } else { "unanswered.digest.enable" })}
+              </button>
+            })
+        })
+    };
+
+    view! { <Suspense>{button_view}</Suspense> }
+}
+
+/// Renders "Write" and "Preview" tabs switching a composer between
+/// its inputs and a [`MarkdownPreview`]
+#[component]
+pub fn PreviewTabs(previewing: RwSignal<bool>) -> impl IntoView {
+    let tab = move |label: &'static str, preview: bool| {
+        view! {
+          <button
+            type="button"
+            on:click=move |_| previewing.set(preview)
+            class="py-1 px-3 text-sm rounded-t-lg hover:cursor-pointer"
+            class=(["font-bold", "bg-purple-100"], move || previewing.get() == preview)
+          >
+            {t!(label)}
+          </button>
+        }
+    };
+    view! {
+      <div class="flex gap-1">
+        {tab("composer.write", false)} {tab("composer.preview", true)}
+      </div>
+    }
+}
+
+/// Renders `content` as formatted by [`api::render_markdown_preview`]
+#[component]
+pub fn MarkdownPreview(#[prop(into)] content: Signal<String>) -> impl IntoView {
+    let preview_res = Resource::new(move || content.get(), api::render_markdown_preview);
+
+    let preview_view = move || {
+        Suspend::new(async move {
+            match preview_res.await {
+                Ok(html) if html.is_empty() => EitherOf3::A(view! {
+                  <p class="italic text-gray-500">{t!("composer.nothing_to_preview")}</p>
+                }),
+                Ok(html) => EitherOf3::B(view! {
+                  <article class="markdown" on:click=copy_code inner_html=html></article>
+                }),
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    EitherOf3::C(view! { <p>{t!("composer.preview_load_failed")}</p> })
+                }
+            }
+        })
+    };
+
+    view! {
+      <div class="overflow-auto py-2 px-4 w-full max-h-80 text-sm bg-white rounded-lg min-h-32">
+        <Suspense fallback=|| {
+          view! { <p>{t!("composer.loading_preview")}</p> }
+        }>{preview_view}</Suspense>
+      </div>
+    }
+}
+
+/// Copies the code of a highlighted code block in rendered Markdown
+/// when its copy button was clicked, see `highlight::code_block`
 ///
-/// See <https://developer.mozilla.org/en-US/docs/Web/HTML/Global_attributes/id>
-fn clean_name_for_id(name: &str) -> String {
-    let mut new = String::new();
-    for word in name.split_ascii_whitespace() {
-        new.push_str(word);
+/// The button comes with the rendered HTML, so clicks are caught on the element it's put into
+pub fn copy_code(ev: MouseEvent) {
+    let Some(button) = ev
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .and_then(|target| target.closest("button[data-copy-code]").ok().flatten())
+    else {
+        return;
+    };
+    let Some(code) = button
+        .parent_element()
+        .and_then(|block| block.query_selector("pre").ok().flatten())
+        .and_then(|pre| pre.text_content())
+    else {
+        return;
+    };
+    // the handler has no reactive owner to read the `I18n` from, but the page has its `lang`
+    let locale = document()
+        .document_element()
+        .and_then(|html| html.get_attribute("lang"))
+        .and_then(|lang| Locale::from_tag(&lang))
+        .unwrap_or_default();
+    let promise = window().navigator().clipboard().write_text(&code);
+    spawn_local(async move {
+        match JsFuture::from(promise).await {
+            Ok(_) => button.set_text_content(Some(&translate(locale, "composer.copied", &[]))),
+            Err(err) => logging::log!("{err:?}"),
+        }
+    });
+}
+
+/// Renders a button for watching the [`Forum`] or not anymore, only for logged-in users,
+/// see [`api::subscriptions::set_forum_watched`]
+#[component]
+fn WatchForumButton(forum_id: u32) -> impl IntoView {
+    let set_watched = ServerAction::<api::subscriptions::SetForumWatched>::new();
+    let watched_res = Resource::new(
+        move || set_watched.version().get(),
+        move |_| api::subscriptions::is_forum_watched(forum_id),
+    );
+
+    let button_view = move || {
+        Suspend::new(async move {
+            // None if not logged in, there's nothing to watch for guests
+            let watched = watched_res.await.ok().flatten()?;
+            Some(view! {
+              <button
+                type="button"
+                on:click=move |_| {
+                  set_watched
+                    .dispatch(api::subscriptions::SetForumWatched {
+                      forum_id,
+                      watched: !watched,
+                    });
+                }
+                class="block text-sm text-purple-900 underline hover:no-underline hover:cursor-pointer"
+              >
+                {t!(if watched { "forum.unwatch" } else { "forum.watch" })}
+              </button>
+            })
+        })
+    };
+
+    view! { <Suspense>{button_view}</Suspense> }
+}
+
+/// Adds an RSS autodiscovery link for the feed of `scope` to the page head
+/// and renders a button to follow it
+#[component]
+pub fn FollowViaRss(scope: FeedScope, #[prop(into)] title: String) -> impl IntoView {
+    let url = scope.url();
+    view! {
+      <Link rel="alternate" type_="application/rss+xml" title href=url.clone() />
+      // external so that the router doesn't try to render the feed
+      <a
+        href=url
+        rel="external"
+        class="inline-block py-1 px-3 text-sm font-bold text-orange-50 bg-orange-600 rounded-lg hover:bg-orange-700"
+      >
+        {t!("forum.follow_rss")}
+      </a>
     }
+}
 
-    new
+/// Renders a banner explaining that the logged-in user can't post
+/// because of a ban or mute (see [`ApiError::Banned`] and [`ApiError::Muted`])
+#[component]
+pub fn SanctionBanner(
+    /// Whether it's a ban or a mute
+    banned: bool,
+    /// [`None`] means permanent
+    until: Option<jiff::Timestamp>,
+    reason: String,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let key = if banned {
+        "sanction.banned"
+    } else {
+        "sanction.muted"
+    };
+    let until = until.map_or_else(
+        || i18n.t("sanction.permanently"),
+        |until| {
+            i18n.t_with(
+                "sanction.until",
+                &[("time", until.strftime("%F %R UTC").to_string())],
+            )
+        },
+    );
+
+    view! {
+      <div
+        role="alert"
+        class="flex flex-col p-4 mb-4 w-full text-red-900 bg-red-100 rounded-lg border-2 border-red-400"
+      >
+        <p class="text-lg font-bold">{t!(key, until = until)}</p>
+        <p>{t!("sanction.reason")} " " <span class="italic">{reason}</span></p>
+      </div>
+    }
+}
+
+/// Renders a banner explaining that the logged-in user is too new to post in a [`Forum`]
+/// (see [`ApiError::PostingRequirementsNotMet`])
+#[component]
+pub fn RequirementsBanner(
+    min_account_age_hours: Option<u32>,
+    min_post_count: Option<u32>,
+) -> impl IntoView {
+    let age = min_account_age_hours
+        .map(|hours| view! { <li>{t!("requirements.account_age", hours = hours)}</li> });
+    let posts = min_post_count
+        .map(|count| view! { <li>{t!("requirements.post_count", count = count)}</li> });
+
+    view! {
+      <div
+        role="alert"
+        class="flex flex-col p-4 mb-4 w-full text-purple-900 bg-purple-100 rounded-lg border-2 border-purple-400"
+      >
+        <p class="text-lg font-bold">{t!("requirements.title")}</p>
+        <p>{t!("requirements.text")}</p>
+        <ul class="list-disc list-inside">{age}{posts}</ul>
+        <p>{t!("requirements.until_then")}</p>
+      </div>
+    }
+}
+
+/// Renders the rules of a [`Forum`] until the logged-in user acknowledged them,
+/// and only then the `children`, i.e. the form for posting there
+///
+/// See [`api::forum_rules`]
+#[component]
+pub fn ForumRulesGate(forum_id: u32, children: ChildrenFn) -> impl IntoView {
+    let i18n = use_i18n();
+    let acknowledge = ServerAction::<api::forum_rules::AcknowledgeForumRules>::new();
+    let rules_res = Resource::new(
+        move || acknowledge.version().get(),
+        move |_| api::forum_rules::get_unacknowledged_rules(forum_id),
+    );
+
+    let error = move || {
+        let Some(Err(e)) = acknowledge.value().get() else {
+            return None;
+        };
+        Some(view! { <p class="font-bold text-red-700">{i18n.error(&e)}</p> })
+    };
+
+    let children = StoredValue::new(children);
+    let gate_view = move || {
+        Suspend::new(async move {
+            match rules_res.await {
+                Ok(Some(rules)) => Either::Left(view! {
+                  <div class="flex flex-col gap-2 p-4 mb-4 w-full max-w-md text-purple-900 bg-purple-100 rounded-lg border-2 border-purple-400">
+                    <p class="text-lg font-bold">{t!("forum_rules.title")}</p>
+                    <article class="markdown" inner_html=rules></article>
+                    <p class="text-sm">{t!("forum_rules.text")}</p>
+                    {error}
+                    <ActionForm action=acknowledge>
+                      <input type="hidden" name="forum_id" value=forum_id />
+                      <input
+                        type="submit"
+                        value=t!("forum_rules.acknowledge")
+                        class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                      />
+                    </ActionForm>
+                  </div>
+                }),
+                // posting reports the error itself
+                Ok(None) | Err(_) => Either::Right(children.read_value()()),
+            }
+        })
+    };
+
+    view! { <Suspense>{gate_view}</Suspense> }
+}
+
+/// Adds a `<meta>` description and OpenGraph tags to the page head,
+/// so that search engines and shared links show more than the page title
+#[component]
+pub fn PageMeta(
+    #[prop(into)] title: String,
+    #[prop(into)] description: String,
+    /// Url of a preview image, see [`api::og_image`]
+    #[prop(optional, into)]
+    image: Option<String>,
+) -> impl IntoView {
+    let card = if image.is_some() {
+        "summary_large_image"
+    } else {
+        "summary"
+    };
+    view! {
+      <Meta name="description" content=description.clone() />
+      <Meta property="og:site_name" content="Dafoerum" />
+      <Meta property="og:type" content="website" />
+      <Meta property="og:title" content=title />
+      <Meta property="og:description" content=description />
+      <Meta name="twitter:card" content=card />
+      {image.map(|image| view! { <Meta property="og:image" content=image /> })}
+    }
 }
@@ -0,0 +1,202 @@
+use crate::api;
+use crate::i18n::use_i18n;
+use crate::t;
+use api::attachments::{AttachmentRef, MAX_ATTACHMENTS_PER_POST};
+
+use leptos::{logging, prelude::*, task::spawn_local};
+use serde::de::DeserializeOwned;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+/// Uploads a file to a plain axum upload route like `/attachments` and parses its JSON response
+///
+/// Not a server function, because those can't stream multipart uploads,
+/// see [`api::attachments`]
+pub(crate) async fn upload_file<T: DeserializeOwned>(
+    route: &str,
+    file: web_sys::File,
+) -> Result<T, String> {
+    let js_err = |err: wasm_bindgen::JsValue| format!("{err:?}");
+
+    let form_data = web_sys::FormData::new().map_err(js_err)?;
+    form_data
+        .append_with_blob_and_filename("file", &file, &file.name())
+        .map_err(js_err)?;
+
+    let init = web_sys::RequestInit::new();
+    init.set_method("POST");
+    init.set_body(&form_data);
+    let response = JsFuture::from(window().fetch_with_str_and_init(route, &init))
+        .await
+        .map_err(js_err)?;
+    let response: web_sys::Response = response.dyn_into().map_err(js_err)?;
+
+    let body = JsFuture::from(response.text().map_err(js_err)?)
+        .await
+        .map_err(js_err)?
+        .as_string()
+        .unwrap_or_default();
+    if !response.ok() {
+        // the error message of the server
+        return Err(body);
+    }
+    serde_json::from_str(&body).map_err(|err| err.to_string())
+}
+
+/// Renders a file picker that uploads the chosen files right away
+///
+/// The uploaded files are added to `attachments` and submitted with the
+/// surrounding form as `attachment_ids`
+#[component]
+pub fn AttachmentUploader(attachments: RwSignal<Vec<AttachmentRef>>) -> impl IntoView {
+    let i18n = use_i18n();
+    let (uploading, set_uploading) = signal(false);
+    let (error, set_error) = signal::<Option<String>>(None);
+
+    let on_change = move |ev: web_sys::Event| {
+        let Some(input) = ev
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok())
+        else {
+            return;
+        };
+        let Some(files) = input.files() else {
+            return;
+        };
+        let files: Vec<web_sys::File> = (0..files.length()).filter_map(|i| files.get(i)).collect();
+        // so that choosing the same file again triggers another change
+        input.set_value("");
+
+        set_error(None);
+        set_uploading(true);
+        spawn_local(async move {
+            for file in files {
+                if attachments.with_untracked(Vec::len) >= MAX_ATTACHMENTS_PER_POST {
+                    set_error(Some(i18n.t_with(
+                        "attachment.too_many",
+                        &[("max", MAX_ATTACHMENTS_PER_POST.to_string())],
+                    )));
+                    break;
+                }
+                match upload_file("/attachments", file).await {
+                    Ok(attachment) => attachments.update(|list| list.push(attachment)),
+                    Err(err) => {
+                        logging::log!("{err}");
+                        set_error(Some(err));
+                    }
+                }
+            }
+            set_uploading(false);
+        });
+    };
+
+    let attachment_list = move || {
+        attachments
+            .get()
+            .into_iter()
+            .enumerate()
+            .map(|(i, attachment)| {
+                let id = attachment.id;
+                view! {
+                  <li class="flex gap-2 items-center text-xs text-gray-700">
+                    <input class="hidden" name=format!("attachment_ids[{i}]") value=id />
+                    {attachment.file_name}
+                    <button
+                      type="button"
+                      on:click=move |_| attachments.update(|list| list.retain(|a| a.id != id))
+                      class="text-red-600 hover:underline hover:cursor-pointer"
+                    >
+                      {t!("attachment.remove")}
+                    </button>
+                  </li>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+      <div class="flex flex-col gap-1 py-2 px-3">
+        <label class="text-xs font-medium text-gray-700">
+          {t!("attachment.attach")}
+          " "
+          <input
+            type="file"
+            multiple
+            accept="image/png,image/jpeg,image/gif,image/webp,application/pdf"
+            on:change=on_change
+            class="text-xs"
+          />
+        </label>
+        <Show when=move || uploading()>
+          <p class="text-xs text-gray-500">{t!("attachment.uploading")}</p>
+        </Show>
+        <p class="text-xs text-red-600">{error}</p>
+        <ul>{attachment_list}</ul>
+      </div>
+    }
+}
+
+/// Renders the attachments of a post, images inline with a lightbox on click
+#[component]
+pub fn AttachmentList(attachments: Vec<AttachmentRef>) -> impl IntoView {
+    if attachments.is_empty() {
+        return None;
+    }
+    let (lightbox, set_lightbox) = signal::<Option<String>>(None);
+
+    let (images, files): (Vec<_>, Vec<_>) =
+        attachments.into_iter().partition(AttachmentRef::is_image);
+    let images = images
+        .into_iter()
+        .map(|image| {
+            let url = image.url();
+            let src = url.clone();
+            view! {
+              <button type="button" on:click=move |_| set_lightbox(Some(url.clone()))>
+                <img
+                  src=src
+                  alt=image.file_name
+                  loading="lazy"
+                  class="object-cover h-24 rounded-sm border border-gray-200 hover:cursor-zoom-in"
+                />
+              </button>
+            }
+        })
+        .collect_view();
+    let files = files
+        .into_iter()
+        .map(|file| {
+            let url = file.url();
+            view! {
+              <li>
+                <a
+                  href=url
+                  target="_blank"
+                  class="text-xs font-medium text-blue-600 underline hover:no-underline"
+                >
+                  {file.file_name}
+                </a>
+              </li>
+            }
+        })
+        .collect_view();
+
+    let lightbox_view = move || {
+        lightbox().map(|url| {
+            view! {
+              <div
+                on:click=move |_| set_lightbox(None)
+                class="flex fixed inset-0 z-50 justify-center items-center bg-black/80 hover:cursor-zoom-out"
+              >
+                <img src=url class="max-w-[95vw] max-h-[95vh]" />
+              </div>
+            }
+        })
+    };
+
+    Some(view! {
+      <div class="flex flex-wrap gap-2 mb-3">{images}</div>
+      <ul class="mb-3">{files}</ul>
+      {lightbox_view}
+    })
+}
@@ -1,12 +1,39 @@
-// use crate::TimeUtils;
+use super::attachment::{AttachmentList, AttachmentUploader};
+use super::{
+    Breadcrumbs, FollowViaRss, ForumRulesGate, PageMeta, RequirementsBanner, SanctionBanner,
+};
+use crate::CsrfField;
 use crate::api;
-use api::{ApiError, Post};
+use crate::challenge::ChallengeWidget;
+use crate::diff::{Diff, DiffTag, diff_lines};
+use crate::draft::{DiscardDraftButton, DraftKey, use_draft};
+use crate::error_page::ErrorPage;
+use crate::i18n::use_i18n;
+use crate::markdown_editor::MarkdownEditor;
+use crate::t;
+use crate::time::LocalTime;
+use crate::use_toasts;
+use crate::user::{MemberLabel, MemberName};
+use api::feeds::FeedScope;
+use api::link_previews::LinkPreview;
+use api::marketplace::{Listing, ListingStatus};
+use api::mod_shortcuts::{ModeratePost, PostShortcut};
+use api::reactions::{PostReactions, ReactionOption, ToggleReaction};
+use api::user::Role;
+use api::validation::Field;
+use api::{ApiError, POST_WINDOW_RADIUS, PostWindow, PostWithAuthor, Quote, Thread};
 
-use leptos::either::Either;
+use leptos::either::{Either, EitherOf3};
 use leptos::html::ol;
-use leptos::{logging, prelude::*};
-// use leptos_meta::Title;
-use leptos_router::{hooks::use_params, params::Params};
+use leptos::{logging, prelude::*, task::spawn_local};
+use leptos_meta::Title;
+use leptos_router::{
+    NavigateOptions,
+    components::Redirect,
+    hooks::{use_navigate, use_params, use_query_map},
+    params::Params,
+};
+use wasm_bindgen_futures::JsFuture;
 
 /// Parameters for /thread/:id
 #[derive(Params, PartialEq, Clone, Copy)]
@@ -17,18 +44,61 @@ struct ThreadParams {
 /// Renders the post list of a [`Thread`]
 #[component]
 pub fn ThreadOverview() -> impl IntoView {
+    let i18n = use_i18n();
     let params = use_params::<ThreadParams>();
     let Ok(ThreadParams { id }) = params.get_untracked() else {
         let view = view! {
-          <h2 class="text-4xl font-bold">"Invalid id!"</h2>
+          <h2 class="text-4xl font-bold">{t!("common.invalid_id")}</h2>
           <a href="/" class="block rounded-sm hover:text-blue-700">
-            <h3 class="text-3xl font-bold">"Go to the frontpage"</h3>
+            <h3 class="text-3xl font-bold">{t!("common.go_to_frontpage")}</h3>
           </a>
         };
         return Either::Left(view);
     };
 
-    let thread_res = Resource::new(move || (), move |()| api::get_thread(id));
+    let set_solved = ServerAction::<api::SetThreadSolved>::new();
+    let set_wiki = ServerAction::<api::SetWikiPost>::new();
+    let set_co_authors = ServerAction::<api::SetThreadCoAuthors>::new();
+    let rename = ServerAction::<api::RenameThread>::new();
+    let delete = ServerAction::<api::thread_tools::DeleteThread>::new();
+    let restore = ServerAction::<api::thread_tools::RestoreThread>::new();
+    let set_listing = ServerAction::<api::marketplace::SetListing>::new();
+    let remove_listing = ServerAction::<api::marketplace::RemoveListing>::new();
+    let mark_sold = ServerAction::<api::marketplace::MarkListingSold>::new();
+    let thread_res = Resource::new(
+        move || {
+            (
+                set_solved.version().get(),
+                set_wiki.version().get(),
+                set_co_authors.version().get(),
+                rename.version().get(),
+                delete.version().get(),
+                restore.version().get(),
+                set_listing.version().get(),
+                remove_listing.version().get(),
+                mark_sold.version().get(),
+            )
+        },
+        move |_| api::get_thread(id),
+    );
+    let user_res = Resource::new(move || (), |()| api::user::get_current_user());
+    let first_unread_res = Resource::new(
+        move || (),
+        move |()| api::read_markers::get_first_unread_post(id),
+    );
+    // only marked once the first unread post is known, so it isn't lost
+    let mark_read = ServerAction::<api::read_markers::MarkThreadRead>::new();
+    Effect::new(move || {
+        let logged_in = matches!(user_res.get(), Some(Ok(Some(_))));
+        if logged_in && first_unread_res.get().is_some() {
+            mark_read.dispatch(api::read_markers::MarkThreadRead { thread_id: id });
+        }
+    });
+    let selection = PostSelection {
+        selecting: RwSignal::new(false),
+        selected: RwSignal::new(vec![]),
+    };
+    provide_context(selection);
 
     let thread_head_view = move || {
         Suspend::new(async move {
@@ -36,192 +106,1916 @@ pub fn ThreadOverview() -> impl IntoView {
                 Ok(thread) => thread,
                 Err(err) => {
                     logging::log!("{err:?} - {err}");
-                    let view = view! { <h2 class="text-4xl font-bold">"Error occured! " {format!("{err:?}")}</h2> };
-                    return Either::Left(view);
+                    return EitherOf3::A(view! { <ErrorPage err /> });
                 }
             };
+            // merged into another thread
+            if thread.id != id {
+                return EitherOf3::B(view! { <Redirect path=format!("/thread/{}", thread.id) /> });
+            }
             let forum_res = Resource::new(move || (), move |()| api::get_forum(thread.forum_id));
-            let (forum, category_name) = match forum_res.await {
-                Ok(n) => (n.0, n.1),
+            let forum = match forum_res.await {
+                Ok((forum, _)) => forum,
                 Err(err) => {
                     // will only  occur if forum_id in thread doesn't exist as a forum
                     // => breaks invariant in get_thread
                     logging::log!("{err:?} - {err}");
-                    let view = view! { <h2 class="text-4xl font-bold">"Error occured! " {format!("{err:?}")}</h2> };
-                    return Either::Left(view);
+                    return EitherOf3::A(view! { <ErrorPage err /> });
                 }
             };
+            // not being logged in is fine here
+            let user = user_res.await.ok().flatten();
+            let is_locked = thread.is_locked(&forum, jiff::Timestamp::now());
+            let is_solved = thread.solved_at.is_some();
+            let can_manage = user.as_ref().is_some_and(|user| {
+                thread.author_id == Some(user.id) || user.role >= Role::Moderator
+            });
+            let can_solve = forum.solved_workflow && can_manage;
+            let can_rename =
+                can_manage || user.as_ref().is_some_and(|user| thread.is_co_author(user));
+            let is_moderator = user
+                .as_ref()
+                .is_some_and(|user| user.role >= Role::Moderator);
+            let can_manage_listing = user
+                .as_ref()
+                .is_some_and(|user| Listing::can_be_managed_by(&thread, user));
+            let thread_id = thread.id;
+            let is_deleted = thread.deleted_at.is_some();
+            let first_unread = first_unread_res.await.ok().flatten();
+            // missing meta tags aren't worth an error
+            let meta = api::seo::get_thread_meta(thread.id)
+                .await
+                .unwrap_or_default();
+            // neither are missing breadcrumbs
+            let crumbs = api::breadcrumbs::get_thread_breadcrumbs(thread.id)
+                .await
+                .unwrap_or_default();
+
             let view = view! {
-              <p>
-                <a href="/" class="font-medium text-blue-600 underline hover:no-underline">
-                  "Forum"
-                </a>
-                " -> "
-                {category_name.to_string()}
-                " -> "
-                <a
-                  href=format!("/forum/{}", forum.id)
-                  class="font-medium text-blue-600 underline hover:no-underline"
-                >
-                  {forum.name.to_string()}
-                </a>
-                " -> "
-                <a
-                  href=format!("/thread/{}", thread.id)
-                  class="font-medium text-blue-600 underline hover:no-underline"
+              <Title text=thread.display_subject() formatter=|text| format!("{text} | Dafoerum") />
+              <PageMeta
+                title=thread.display_subject()
+                description=meta.description
+                image=meta.image_url
+              />
+              <Breadcrumbs crumbs />
+              <h2 class="text-4xl font-bold">{thread.display_subject()}</h2>
+              <p>{t!("thread.id", id = thread.id)}</p>
+              <p>{t!("thread.origin_post_id", id = thread.origin_post_id)}</p>
+              <FollowViaRss
+                scope=FeedScope::Thread(thread.id)
+                title=i18n.t_with("thread.feed_title", &[("subject", thread.subject.clone())])
+              />
+              <WatchButton thread_id />
+              {first_unread
+                .map(|post_id| {
+                  view! {
+                    <a
+                      href=format!("#post-{post_id}")
+                      class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900"
+                    >
+                      {t!("thread.jump_to_unread")}
+                    </a>
+                  }
+                })}
+              <Show when=move || is_locked>
+                <p class="font-bold text-purple-900">{t!("thread.locked")}</p>
+              </Show>
+              <Show when=move || is_deleted>
+                <p class="font-bold text-red-700">{t!("thread.deleted")}</p>
+              </Show>
+              <Show when=move || can_solve>
+                <button
+                  on:click=move |_| {
+                    set_solved.dispatch(api::SetThreadSolved { thread_id, solved: !is_solved });
+                  }
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
                 >
-                  {thread.subject.to_string()}
-                </a>
-              </p>
-              <h2 class="text-4xl font-bold">{thread.subject}</h2>
-              <p>"Thread id: "{thread.id}</p>
-              <p>"Origin post id: "{thread.origin_post_id}</p>
+                  {t!(if is_solved { "thread.mark_unsolved" } else { "thread.mark_solved" })}
+                </button>
+              </Show>
+              {can_manage
+                .then(|| {
+                  view! {
+                    <WikiPostSettings set_wiki thread=thread.clone() />
+                    <CoAuthorSettings set_co_authors thread=thread.clone() />
+                  }
+                })}
+              {can_rename
+                .then(|| {
+                  view! { <RenameThreadForm rename thread_id subject=thread.subject.clone() /> }
+                })}
+              {is_moderator
+                .then(|| {
+                  view! { <ThreadModTools thread_id is_deleted selection delete restore /> }
+                })}
+              {can_manage_listing
+                .then(|| {
+                  view! {
+                    <ListingSettings
+                      set_listing
+                      remove_listing
+                      mark_sold
+                      thread_id
+                      listing=thread.listing.clone()
+                    />
+                  }
+                })}
+              {thread.listing.clone().map(|listing| view! { <ListingCard listing /> })}
+              <MentionedIn thread_id />
+              <ParticipantList thread_id />
             };
-            Either::Right(view)
+            EitherOf3::C(view)
         })
     };
 
     let view = view! {
       <Suspense fallback=move || {
-        view! { <p>"Loading thread..."</p> }
-      }>{thread_head_view}<Posts thread_id=id /></Suspense>
+        view! { <p>{t!("thread.loading")}</p> }
+      }>{thread_head_view}<Posts thread_id=id thread_res /></Suspense>
     };
     Either::Right(view)
 }
 
-/// Renders a list of [`Posts`][Post] from the given [`Thread`]
+/// Renders a button for watching the [`Thread`] or not anymore, only for logged-in users,
+/// see [`api::my_content::set_thread_watched`]
 #[component]
-fn Posts(thread_id: u32) -> impl IntoView {
-    // change to readsignal<u32> when implementing multiview (multiple threads at once)?
+fn WatchButton(thread_id: u32) -> impl IntoView {
+    let set_watched = ServerAction::<api::my_content::SetThreadWatched>::new();
+    let watched_res = Resource::new(
+        move || set_watched.version().get(),
+        move |_| api::my_content::is_thread_watched(thread_id),
+    );
 
-    let create_post = ServerAction::<api::CreatePost>::new();
+    let button_view = move || {
+        Suspend::new(async move {
+            // None if not logged in, there's nothing to watch for guests
+            let watched = watched_res.await.ok().flatten()?;
+            Some(view! {
+              <button
+                type="button"
+                on:click=move |_| {
+                  set_watched
+                    .dispatch(api::my_content::SetThreadWatched {
+                      thread_id,
+                      watched: !watched,
+                    });
+                }
+                class="text-sm text-purple-900 underline hover:no-underline hover:cursor-pointer"
+              >
+                {t!(if watched { "thread.unwatch" } else { "thread.watch" })}
+              </button>
+            })
+        })
+    };
 
-    let posts_res = Resource::new(
-        move || create_post.version().get(),
-        move |_| api::get_posts_from_thread(thread_id),
+    view! { <Suspense>{button_view}</Suspense> }
+}
+
+/// Renders the threads linking to this one, see [`api::thread_references`]
+#[component]
+fn MentionedIn(thread_id: u32) -> impl IntoView {
+    let mentions_res = Resource::new(
+        move || (),
+        move |()| api::thread_references::get_thread_mentions(thread_id),
     );
 
-    let post_list_view = move || {
+    let mentions_view = move || {
+        Suspend::new(async move {
+            // missing mentions aren't worth an error
+            let mentions = mentions_res.await.unwrap_or_default();
+            if mentions.is_empty() {
+                return None;
+            }
+            let mentions = mentions
+                .into_iter()
+                .map(|mention| {
+                    view! {
+                      <li>
+                        <a
+                          href=format!("/post/{}", mention.post_id)
+                          class="font-medium text-blue-600 underline hover:no-underline"
+                        >
+                          {mention.subject}
+                        </a>
+                      </li>
+                    }
+                })
+                .collect_view();
+            Some(view! {
+              <section class="p-2 mt-2 max-w-md text-sm bg-purple-50 rounded-lg">
+                <h3 class="font-bold">{t!("thread.mentioned_in")}</h3>
+                <ul class="list-disc list-inside">{mentions}</ul>
+              </section>
+            })
+        })
+    };
+
+    view! { <Suspense>{mentions_view}</Suspense> }
+}
+
+/// Renders a collapsible list of everyone who posted in the thread with their post count,
+/// see [`api::participants`]
+#[component]
+fn ParticipantList(thread_id: u32) -> impl IntoView {
+    let i18n = use_i18n();
+    let participants_res = Resource::new(
+        move || (),
+        move |()| api::participants::get_thread_participants(thread_id),
+    );
+
+    let participants_view = move || {
+        Suspend::new(async move {
+            // missing participants aren't worth an error
+            let participants = participants_res.await.unwrap_or_default();
+            if participants.is_empty() {
+                return None;
+            }
+            let count = participants.len();
+            let participants = participants
+                .into_iter()
+                .map(|participant| {
+                    view! {
+                      <li class="flex gap-2 justify-between">
+                        <MemberLabel member=participant.member avatar=true />
+                        <a
+                          href=format!("/post/{}", participant.first_post_id)
+                          title=t!("thread.first_post_of_participant")
+                          class="text-blue-600 underline hover:no-underline"
+                        >
+                          {move || {
+                            i18n.t_count("thread.participant_posts", participant.post_count.into())
+                          }}
+                        </a>
+                      </li>
+                    }
+                })
+                .collect_view();
+            Some(view! {
+              <details class="p-2 mt-2 max-w-md text-sm bg-purple-50 rounded-lg">
+                <summary class="font-bold hover:cursor-pointer">
+                  {t!("thread.participants", count = count)}
+                </summary>
+                <ul class="flex flex-col gap-1 mt-2">{participants}</ul>
+              </details>
+            })
+        })
+    };
+
+    view! { <Suspense>{participants_view}</Suspense> }
+}
+
+/// Parameters for /post/:id
+#[derive(Params, PartialEq, Clone, Copy)]
+struct PostParams {
+    id: u32,
+}
+
+/// Redirects the permalink of a [`Post`][api::Post] to the post in its [`Thread`]
+#[component]
+pub fn PostPermalink() -> impl IntoView {
+    let params = use_params::<PostParams>();
+    let Ok(PostParams { id }) = params.get_untracked() else {
+        return Either::Left(
+            view! { <h2 class="text-4xl font-bold">{t!("common.invalid_id")}</h2> },
+        );
+    };
+    let location_res = Resource::new(move || (), move |()| api::get_post_location(id));
+
+    let redirect_view = move || {
         Suspend::new(async move {
-            let posts = match posts_res.await {
-                Ok(posts) => posts,
+            match location_res.await {
+                Ok(location) => {
+                    // the thread loads the posts around it first, see `Posts`
+                    let path = format!("/thread/{}?post={id}#post-{id}", location.thread_id);
+                    Either::Left(view! { <Redirect path /> })
+                }
                 Err(err) => {
                     logging::log!("{err:?} - {err}");
-                    return Either::Left(view! { <p>"Posts couldn't be loaded!"</p> });
+                    Either::Right(view! { <p>{t!("thread.post_not_found")}</p> })
+                }
+            }
+        })
+    };
+
+    Either::Right(view! {
+      <Suspense fallback=move || {
+        view! { <p>{t!("thread.loading_post")}</p> }
+      }>{redirect_view}</Suspense>
+    })
+}
+
+/// Renders the posts of a [`Thread`] without the navigation around them,
+/// for embedding it on other sites, see [`api::embed`]
+///
+/// Browsers don't send the `SameSite=Lax` session cookie to frames on other sites,
+/// so there's no composer and logging in and replying open the thread in a new window
+#[component]
+pub fn EmbeddedThread() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params::<ThreadParams>();
+    let Ok(ThreadParams { id }) = params.get_untracked() else {
+        return Either::Left(
+            view! { <h2 class="text-4xl font-bold">{t!("common.invalid_id")}</h2> },
+        );
+    };
+    let thread_res = Resource::new(move || (), move |()| api::get_thread(id));
+
+    let head_view = move || {
+        Suspend::new(async move {
+            let thread = match thread_res.await {
+                Ok(thread) => thread,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{i18n.error(&err)}</p> });
                 }
             };
-            let view = posts
+            // the frame is too small for the whole forum, it's opened next to the site
+            Either::Right(view! {
+              <Title text=thread.display_subject() formatter=|text| format!("{text} | Dafoerum") />
+              <a
+                href=format!("/thread/{}", thread.id)
+                target="_blank"
+                class="self-start text-2xl font-bold underline hover:no-underline"
+              >
+                {thread.display_subject()}
+              </a>
+              <a
+                href=format!("/thread/{}", thread.id)
+                target="_blank"
+                class="self-start text-sm underline hover:no-underline"
+              >
+                {t!("thread.reply_on_forum")}
+              </a>
+            })
+        })
+    };
+
+    Either::Right(view! {
+      <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{head_view}</Suspense>
+      <Posts thread_id=id thread_res embedded=true />
+    })
+}
+
+/// Scrolls to the element the `#` anchor of the url points to, e.g. `#post-3`
+fn scroll_to_anchor() {
+    let Ok(hash) = window().location().hash() else {
+        return;
+    };
+    let Some(id) = hash.strip_prefix('#') else {
+        return;
+    };
+    if let Some(element) = document().get_element_by_id(id) {
+        element.scroll_into_view();
+    }
+}
+
+/// Posts selected by moderators for [splitting][api::thread_tools::split_thread],
+/// provided by [`ThreadOverview`] to its [`PostItems`][PostItem]
+#[derive(Clone, Copy)]
+struct PostSelection {
+    /// Whether the checkboxes are shown
+    selecting: RwSignal<bool>,
+    selected: RwSignal<Vec<u32>>,
+}
+
+/// Renders a search and a select for picking a [`ModReason`][api::mod_reasons::ModReason],
+/// setting `reason_id` to the picked one
+#[component]
+fn ModReasonSelect(reason_id: RwSignal<Option<u32>>) -> impl IntoView {
+    let (reason_query, set_reason_query) = signal(String::new());
+    let reasons_res = Resource::new(
+        move || reason_query.get(),
+        |query| api::mod_reasons::get_mod_reasons(query, false),
+    );
+
+    let reason_options = move || {
+        Suspend::new(async move {
+            reasons_res
+                .await
+                .unwrap_or_default()
                 .into_iter()
-                .map(|post| PostItem(PostItemProps { post }))
-                .collect_view();
-            Either::Right(ol().class("flex flex-col gap-2").child(view))
+                .map(|reason| {
+                    view! {
+                      <option value=reason.id title=reason.description>
+                        {reason.name}
+                      </option>
+                    }
+                })
+                .collect_view()
         })
     };
 
-    // server-side error handling
+    view! {
+      <input
+        type="search"
+        placeholder=t!("thread.search_reasons")
+        on:input:target=move |ev| {
+          reason_id.set(None);
+          set_reason_query(ev.target().value());
+        }
+        class="p-1 w-32 bg-purple-50 rounded-lg border border-purple-400"
+      />
+      <select
+        on:change:target=move |ev| reason_id.set(ev.target().value().parse().ok())
+        class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+      >
+        <option value="">{t!("thread.no_reason")}</option>
+        <Suspense>{reason_options}</Suspense>
+      </select>
+    }
+}
+
+/// Renders the moderator tools for merging the [`Thread`] into another one,
+/// splitting the selected [`Posts`][api::Post] off into a new thread and (soft-)deleting it
+/// with a searchable [`ModReason`][api::mod_reasons::ModReason]
+#[component]
+fn ThreadModTools(
+    thread_id: u32,
+    is_deleted: bool,
+    selection: PostSelection,
+    delete: ServerAction<api::thread_tools::DeleteThread>,
+    restore: ServerAction<api::thread_tools::RestoreThread>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let merge = ServerAction::<api::thread_tools::MergeThreads>::new();
+    let split = ServerAction::<api::thread_tools::SplitThread>::new();
+    let reason_id = RwSignal::<Option<u32>>::new(None);
+
+    // go to where the posts ended up
+    let navigate = use_navigate();
+    Effect::new(move || {
+        if let Some(Ok(target_thread_id)) = merge.value().get() {
+            navigate(
+                &format!("/thread/{target_thread_id}"),
+                NavigateOptions::default(),
+            );
+        }
+    });
+    let navigate = use_navigate();
+    Effect::new(move || {
+        if let Some(Ok(new_thread_id)) = split.value().get() {
+            selection.selected.set(vec![]);
+            selection.selecting.set(false);
+            navigate(
+                &format!("/thread/{new_thread_id}"),
+                NavigateOptions::default(),
+            );
+        }
+    });
+
     let error = move || {
-        // will be None before first dispatch
-        let Some(val) = create_post.value().get() else {
-            return Either::Left(().into_view());
-        };
-        // Will be Ok if no errors occured
-        let Err(e) = val else {
-            return Either::Left(().into_view());
-        };
+        let merge_err = merge.value().get().and_then(Result::err);
+        let split_err = split.value().get().and_then(Result::err);
+        let delete_err = delete.value().get().and_then(Result::err);
+        let restore_err = restore.value().get().and_then(Result::err);
+        merge_err
+            .or(split_err)
+            .or(delete_err)
+            .or(restore_err)
+            .map(|e| i18n.error(&e))
+    };
+    let delete_button = if is_deleted {
+        Either::Left(view! {
+          <button
+            type="button"
+            on:click=move |_| {
+              restore.dispatch(api::thread_tools::RestoreThread { thread_id });
+            }
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          >
+            {t!("thread.restore")}
+          </button>
+        })
+    } else {
+        Either::Right(view! {
+          <div class="flex flex-wrap gap-2 items-center">
+            <ModReasonSelect reason_id />
+            <button
+              type="button"
+              on:click=move |_| {
+                delete
+                  .dispatch(api::thread_tools::DeleteThread {
+                    thread_id,
+                    reason_id: reason_id.get_untracked(),
+                  });
+              }
+              class="py-1 px-4 font-bold text-red-50 bg-red-800 rounded-lg hover:bg-red-900 hover:cursor-pointer"
+            >
+              {t!("thread.delete")}
+            </button>
+          </div>
+        })
+    };
+    let selected_inputs = move || {
+        selection
+            .selected
+            .get()
+            .into_iter()
+            .enumerate()
+            .map(|(i, post_id)| {
+                view! { <input type="hidden" name=format!("post_ids[{i}]") value=post_id /> }
+            })
+            .collect_view()
+    };
 
-        let msg = match e {
-            ApiError::EmptyContent => "Post content cannot be empty!".into(),
-            _ => format!("Error from server: {e}"),
-        };
+    view! {
+      <details class="p-2 mt-2 max-w-md bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("thread.moderation")}</summary>
+        <p class="text-sm text-red-700">{error}</p>
+        <div class="mb-2">{delete_button}</div>
+        <ActionForm action=merge attr:class="flex flex-wrap gap-2 items-center">
+          <input type="hidden" name="source_thread_id" value=thread_id />
+          <label>
+            {t!("thread.merge_into")}
+            <input
+              type="number"
+              min="1"
+              name="target_thread_id"
+              required
+              class="p-1 w-20 bg-purple-50 rounded-lg border border-purple-400"
+            />
+          </label>
+          <input
+            type="submit"
+            value=t!("thread.merge")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <label class="block mt-2">
+          <input
+            type="checkbox"
+            prop:checked=selection.selecting
+            on:change:target=move |ev| selection.selecting.set(ev.target().checked())
+          />
+          " "
+          {t!("thread.select_to_split")}
+        </label>
+        <Show when=move || selection.selecting.get()>
+          <ActionForm action=split attr:class="flex flex-wrap gap-2 items-center">
+            {selected_inputs}
+            <input
+              name="new_subject"
+              required
+              placeholder=t!("thread.split_subject")
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            <input
+              type="submit"
+              value=move || {
+                let count = selection.selected.get().len();
+                i18n.t_count("thread.split", i64::try_from(count).unwrap_or(i64::MAX))
+              }
+              class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+            />
+          </ActionForm>
+        </Show>
+      </details>
+    }
+}
 
-        let view = view! { <p class="text-lg font-bold text-red-700">{msg}</p> };
-        Either::Right(view)
+/// Actions on single [`Posts`][api::Post], provided by [`Posts`] to its [`PostItems`][PostItem]
+///
+/// Not available in e.g. the latest posts list, where the buttons are hidden
+#[derive(Clone, Copy)]
+struct PostActions {
+    /// Sets the post quoted by the reply form
+    set_quoting: WriteSignal<Option<u32>>,
+    edit: ServerAction<api::EditPost>,
+    highlight: ServerAction<api::megathread::SetPostHighlighted>,
+    /// Only used by moderators, see [`PostModMenu`]
+    moderate: ServerAction<ModeratePost>,
+    react: ServerAction<ToggleReaction>,
+    reaction_options: Resource<Result<Vec<ReactionOption>, ApiError>>,
+    reactions: Resource<Result<Vec<PostReactions>, ApiError>>,
+}
+
+/// Renders the settings of the [wiki post][api::Thread::wiki_post] of a [`Thread`]
+/// for its author and moderators
+#[component]
+fn WikiPostSettings(set_wiki: ServerAction<api::SetWikiPost>, thread: Thread) -> impl IntoView {
+    let i18n = use_i18n();
+    let error = move || {
+        set_wiki
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|e| i18n.error(&e))
     };
+    let editors = thread
+        .wiki_editor_ids
+        .into_iter()
+        .map(|user_id| view! { <li><MemberName user_id /></li> })
+        .collect_view();
 
-    let (client_error, set_client_error) = signal("none".to_string());
+    view! {
+      <details class="p-2 mt-2 max-w-md bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("thread.wiki.title")}</summary>
+        <p class="text-sm">{t!("thread.wiki.text")}</p>
+        <p class="text-sm text-red-700">{error}</p>
+        {thread
+          .wiki_post
+          .then(|| {
+            view! {
+              <p class="text-sm font-medium">{t!("thread.wiki.current_editors")}</p>
+              <ul class="text-sm">{editors}</ul>
+            }
+          })}
+        <ActionForm action=set_wiki attr:class="flex flex-col gap-2">
+          <input type="hidden" name="thread_id" value=thread.id />
+          <label>
+            <input type="checkbox" name="wiki_post" value="true" checked=thread.wiki_post />
+            " "
+            {t!("thread.wiki.enable")}
+          </label>
+          <label class="flex flex-col">
+            {t!("thread.wiki.editors")}
+            <input name="editor_names" class="p-1 bg-purple-50 rounded-lg border border-purple-400" />
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+      </details>
+    }
+}
+
+/// Renders the [co-authors][api::Thread::co_author_ids] of a [`Thread`] and a form to replace them
+/// for its author and moderators
+#[component]
+fn CoAuthorSettings(
+    set_co_authors: ServerAction<api::SetThreadCoAuthors>,
+    thread: Thread,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let error = move || {
+        set_co_authors
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|e| i18n.error(&e))
+    };
+    let co_authors = thread
+        .co_author_ids
+        .into_iter()
+        .map(|user_id| view! { <li><MemberName user_id /></li> })
+        .collect_view();
+
+    view! {
+      <details class="p-2 mt-2 max-w-md bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("thread.co_authors.title")}</summary>
+        <p class="text-sm">{t!("thread.co_authors.text")}</p>
+        <p class="text-sm text-red-700">{error}</p>
+        <p class="text-sm font-medium">{t!("thread.co_authors.current")}</p>
+        <ul class="text-sm">{co_authors}</ul>
+        <ActionForm action=set_co_authors attr:class="flex flex-col gap-2">
+          <input type="hidden" name="thread_id" value=thread.id />
+          <label class="flex flex-col">
+            {t!("thread.co_authors.names")}
+            <input
+              name="co_author_names"
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            />
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+      </details>
+    }
+}
+
+/// Renders the price, location and status of a marketplace [`Thread`]
+#[component]
+fn ListingCard(listing: Listing) -> impl IntoView {
+    let is_sold = listing.status == ListingStatus::Sold;
+
+    view! {
+      <section
+        aria-label=t!("listing.title")
+        class="flex flex-wrap gap-4 items-center p-4 mt-2 max-w-md bg-purple-50 rounded-lg border border-purple-400"
+      >
+        <p class="text-2xl font-bold">{listing.price()}</p>
+        <p class="text-sm">{t!("listing.location_of", location = listing.location)}</p>
+        <p
+          class="py-0.5 px-2 text-sm font-bold rounded-lg"
+          class=("bg-green-200", !is_sold)
+          class=("bg-red-200", is_sold)
+        >
+          {t!(listing.status.label_key())}
+        </p>
+      </section>
+    }
+}
+
+/// Renders forms for turning a [`Thread`] into a marketplace thread, changing its [`Listing`],
+/// marking it as sold or removing it, for its author, co-authors and moderators
+#[component]
+fn ListingSettings(
+    set_listing: ServerAction<api::marketplace::SetListing>,
+    remove_listing: ServerAction<api::marketplace::RemoveListing>,
+    mark_sold: ServerAction<api::marketplace::MarkListingSold>,
+    thread_id: u32,
+    listing: Option<Listing>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let error = move || {
+        let set = set_listing.value().get().and_then(Result::err);
+        let remove = remove_listing.value().get().and_then(Result::err);
+        let sold = mark_sold.value().get().and_then(Result::err);
+        set.or(remove).or(sold).map(|e| i18n.error(&e))
+    };
+    let price = listing.as_ref().map(Listing::price).unwrap_or_default();
+    let location = listing
+        .as_ref()
+        .map(|listing| listing.location.clone())
+        .unwrap_or_default();
+    let is_available = listing
+        .as_ref()
+        .is_some_and(|listing| listing.status == ListingStatus::Available);
+    let has_listing = listing.is_some();
+
+    view! {
+      <details class="p-2 mt-2 max-w-md bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("listing.settings")}</summary>
+        <p class="text-sm">{t!("listing.settings_text")}</p>
+        <p class="text-sm text-red-700">{error}</p>
+        <ActionForm action=set_listing attr:class="flex flex-col gap-2">
+          <input type="hidden" name="thread_id" value=thread_id />
+          <label class="flex flex-col">
+            {t!("listing.price")}
+            <input
+              name="price"
+              value=price
+              required
+              inputmode="decimal"
+              placeholder="12.50"
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            />
+          </label>
+          <label class="flex flex-col">
+            {t!("listing.location")}
+            <input
+              name="location"
+              value=location
+              required
+              maxlength=api::marketplace::MAX_LOCATION_LEN
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            />
+          </label>
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+        <Show when=move || is_available>
+          <ActionForm action=mark_sold attr:class="flex flex-col gap-2 mt-2">
+            <input type="hidden" name="thread_id" value=thread_id />
+            <label class="text-sm">
+              <input type="checkbox" name="lock" value="true" />
+              " "
+              {t!("listing.lock_thread")}
+            </label>
+            <input
+              type="submit"
+              value=t!("listing.mark_sold")
+              class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+            />
+          </ActionForm>
+        </Show>
+        <Show when=move || has_listing>
+          <ActionForm action=remove_listing attr:class="mt-2">
+            <input type="hidden" name="thread_id" value=thread_id />
+            <input
+              type="submit"
+              value=t!("listing.remove")
+              class="py-1 px-4 font-bold text-red-700 rounded-lg border border-red-700 hover:bg-red-100 hover:cursor-pointer"
+            />
+          </ActionForm>
+        </Show>
+      </details>
+    }
+}
+
+/// Renders a form for changing the subject of a [`Thread`]
+/// for its author, co-authors and moderators
+#[component]
+fn RenameThreadForm(
+    rename: ServerAction<api::RenameThread>,
+    thread_id: u32,
+    subject: String,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let error = move || {
+        rename
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|e| i18n.error(&e))
+    };
 
     view! {
-      // server-side errors
-      {error}
+      <details class="p-2 mt-2 max-w-md bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("thread.rename")}</summary>
+        <p class="text-sm text-red-700">{error}</p>
+        <ActionForm action=rename attr:class="flex flex-col gap-2">
+          <input type="hidden" name="thread_id" value=thread_id />
+          <input
+            name="subject"
+            value=subject
+            required
+            class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+          />
+          <input
+            type="submit"
+            value=t!("common.save")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+      </details>
+    }
+}
 
-      // client-side errors / validation
-      {move || {
-        if client_error() == "none" {
-          Either::Left(().into_view())
-        } else {
-          let view = view! { <p class="text-lg font-bold text-red-700">{client_error()}</p> };
-          Either::Right(view)
+/// Renders a list of [`Posts`][api::Post] from the given [`Thread`]
+/// and the composer, unless `embedded` on another site
+#[component]
+fn Posts(
+    thread_id: u32,
+    thread_res: Resource<Result<Thread, ApiError>>,
+    #[prop(optional)] embedded: bool,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    // change to readsignal<u32> when implementing multiview (multiple threads at once)?
+
+    let create_post = ServerAction::<api::CreatePost>::new();
+    let edit_post = ServerAction::<api::EditPost>::new();
+    let highlight = ServerAction::<api::megathread::SetPostHighlighted>::new();
+    let moderate = ServerAction::<ModeratePost>::new();
+    let (quoting, set_quoting) = signal::<Option<u32>>(None);
+    let attachments = RwSignal::new(vec![]);
+    let draft = use_draft(DraftKey::Reply { thread_id });
+    let previewing = RwSignal::new(false);
+    let react = ServerAction::<ToggleReaction>::new();
+    let reaction_options = Resource::new(|| (), |()| api::reactions::get_reaction_options());
+    let reactions = Resource::new(
+        move || react.version().get(),
+        move |_| api::reactions::get_thread_reactions(thread_id),
+    );
+    provide_context(PostActions {
+        set_quoting,
+        edit: edit_post,
+        highlight,
+        moderate,
+        react,
+        reaction_options,
+        reactions,
+    });
+
+    // posts created since the list was loaded, appended without loading all posts again
+    let created = RwSignal::new(Vec::<PostWithAuthor>::new());
+    // the quote and attachments belong to the post that was just created
+    Effect::new(move || {
+        if let Some(Ok(post)) = create_post.value().get() {
+            set_quoting(None);
+            attachments.set(vec![]);
+            created.update(|created| created.push(post));
+        }
+    });
+    // the draft is only kept while the post wasn't created
+    Effect::new(move || {
+        if let Some(Ok(_) | Err(ApiError::HeldForReview)) = create_post.value().get() {
+            draft.discard();
+            previewing.set(false);
         }
-      }}
+    });
 
-      // https://flowbite.com/docs/forms/textarea/#comment-box
-      <ActionForm
-        action=create_post
-        on:submit:capture=move |ev| {
-          let post = api::CreatePost::from_event(&ev);
-          let Ok(post) = post else {
+    // there's no push channel, so typing is pinged and who else is typing is polled
+    let last_typing_ping = StoredValue::new(None::<jiff::Timestamp>);
+    let ping_typing = move || {
+        let now = jiff::Timestamp::now();
+        let interval = jiff::SignedDuration::from_secs(
+            i64::try_from(api::typing::PING_INTERVAL_SECS).unwrap_or(i64::MAX),
+        );
+        if last_typing_ping
+            .get_value()
+            .is_some_and(|pinged| now - interval < pinged)
+        {
             return;
-          };
-          if post.content.is_empty() {
-            set_client_error.set("Post content cannot be empty!".to_string());
-            ev.prevent_default();
-          }
         }
-        attr:class="mb-4 w-full max-w-md bg-gray-50 rounded-lg border border-gray-200"
-      >
-        // I hope there's a better way to do this...
-        <input class="hidden" name="thread_id" value=thread_id />
-        <textarea
-          name="content"
-          rows="5"
-          placeholder="Write a post..."
-          required
-          on:input:target=move |ev| {
-            if !ev.target().value().is_empty() {
-              set_client_error.set("none".to_string());
+        last_typing_ping.set_value(Some(now));
+        spawn_local(async move {
+            if let Err(e) = api::typing::ping_typing(thread_id).await {
+                logging::log!("{e:?} - {e}");
             }
+        });
+    };
+    let typing_count = RwSignal::new(0_u32);
+    // effects only run in the browser, so the server doesn't poll
+    Effect::new(move || {
+        let poll = move || {
+            spawn_local(async move {
+                if let Ok(count) = api::typing::get_typing_count(thread_id).await {
+                    typing_count.set(count);
+                }
+            });
+        };
+        poll();
+        let handle = set_interval_with_handle(
+            poll,
+            std::time::Duration::from_secs(api::typing::PING_INTERVAL_SECS),
+        );
+        if let Ok(handle) = handle {
+            on_cleanup(move || handle.clear());
+        }
+    });
+
+    // linked posts (`?post=` from permalinks) are shown with the posts around them first,
+    // more are loaded on request instead of the whole thread at once
+    let target_post = use_query_map()
+        .get_untracked()
+        .get("post")
+        .and_then(|id| id.parse::<u32>().ok());
+    let before = RwSignal::new(POST_WINDOW_RADIUS);
+    let after = RwSignal::new(POST_WINDOW_RADIUS);
+    let posts_res = Resource::new(
+        move || {
+            (
+                edit_post.version().get(),
+                highlight.version().get(),
+                moderate.version().get(),
+                before.get(),
+                after.get(),
+            )
+        },
+        move |(_, _, _, before, after)| async move {
+            match target_post {
+                Some(post_id) => api::get_posts_around(thread_id, post_id, before, after).await,
+                None => api::get_posts_from_thread(thread_id)
+                    .await
+                    .map(|posts| PostWindow {
+                        posts,
+                        ..PostWindow::default()
+                    }),
+            }
+        },
+    );
+    let user_res = Resource::new(move || (), |()| api::user::get_current_user());
+
+    // the posts are loaded after navigating, so the router can't scroll to their anchors
+    let scrolled = StoredValue::new(false);
+    Effect::new(move || {
+        if posts_res.get().is_some() && !scrolled.get_value() {
+            scrolled.set_value(true);
+            request_animation_frame(scroll_to_anchor);
+        }
+    });
+
+    let created_view = move || {
+        // reloading the posts after e.g. an edit includes the created ones
+        let loaded = posts_res
+            .get()
+            .and_then(Result::ok)
+            .map(|window| {
+                window
+                    .posts
+                    .iter()
+                    .map(|post| post.post.id)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let moderating = user_res
+            .get()
+            .and_then(Result::ok)
+            .flatten()
+            .is_some_and(|user| user.role >= Role::Moderator);
+        created
+            .get()
+            .into_iter()
+            .filter(|post| !loaded.contains(&post.post.id))
+            .map(|post| {
+                PostItem(PostItemProps {
+                    post,
+                    editable: true,
+                    wiki: false,
+                    highlightable: moderating,
+                    moderating,
+                })
+            })
+            .collect_view()
+    };
+    // shown while the server hasn't answered, on errors it's gone and the draft is still there
+    let pending_view = move || {
+        create_post.input().get().map(|post| {
+            view! {
+              <li aria-busy="true" class="p-4 bg-purple-50 rounded-lg opacity-60">
+                <p class="mb-2 text-sm italic text-purple-900">{t!("thread.sending")}</p>
+                <p class="whitespace-pre-wrap">{post.content}</p>
+              </li>
+            }
+        })
+    };
+
+    let post_list_view = move || {
+        Suspend::new(async move {
+            let PostWindow {
+                posts,
+                has_earlier,
+                has_later,
+            } = match posts_res.await {
+                Ok(window) => window,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("thread.posts_load_failed")}</p> });
+                }
+            };
+            // not being logged in is fine here
+            let user = user_res.await.ok().flatten();
+            let thread = thread_res.await.ok();
+            let view = posts
+                .into_iter()
+                .map(|post| {
+                    let (post_id, author_id) = (post.post.id, post.post.author_id);
+                    let editable = user.as_ref().is_some_and(|user| {
+                        author_id == Some(user.id)
+                            || user.role >= Role::Moderator
+                            || thread.as_ref().is_some_and(|thread| {
+                                thread.can_edit_as_collaborator(post_id, user)
+                            })
+                    });
+                    let wiki = thread
+                        .as_ref()
+                        .is_some_and(|thread| thread.wiki_post && thread.origin_post_id == post_id);
+                    let moderating = user
+                        .as_ref()
+                        .is_some_and(|user| user.role >= Role::Moderator);
+                    PostItem(PostItemProps {
+                        post,
+                        editable,
+                        wiki,
+                        highlightable: moderating,
+                        moderating,
+                    })
+                })
+                .collect_view();
+            let load_earlier = has_earlier.then(|| {
+                view! {
+                  <button
+                    type="button"
+                    on:click=move |_| before.update(|before| *before += POST_WINDOW_RADIUS)
+                    class="py-1 px-4 mb-2 font-bold text-purple-900 bg-purple-100 rounded-lg hover:bg-purple-200 hover:cursor-pointer"
+                  >
+                    {t!("thread.load_earlier")}
+                  </button>
+                }
+            });
+            let load_later = has_later.then(|| {
+                view! {
+                  <button
+                    type="button"
+                    on:click=move |_| after.update(|after| *after += POST_WINDOW_RADIUS)
+                    class="py-1 px-4 mt-2 font-bold text-purple-900 bg-purple-100 rounded-lg hover:bg-purple-200 hover:cursor-pointer"
+                  >
+                    {t!("thread.load_later")}
+                  </button>
+                }
+            });
+            Either::Right(view! {
+              {load_earlier}
+              {ol().class("flex flex-col gap-2").child((view, created_view, pending_view))}
+              {load_later}
+            })
+        })
+    };
+
+    // set by the validation before submitting and by the server
+    let (content_error, set_content_error) = signal::<Option<String>>(None);
+    let toasts = use_toasts();
+    Effect::new(move || {
+        let Some(Err(e)) = create_post.value().get() else {
+            return;
+        };
+        match e {
+            // shown as banners above the form
+            ApiError::Banned { .. }
+            | ApiError::Muted { .. }
+            | ApiError::PostingRequirementsNotMet { .. } => {}
+            ApiError::HeldForReview => toasts.push_success(i18n.error(&e)),
+            ApiError::Validation {
+                field: Field::Content,
+                ..
+            } => set_content_error(Some(i18n.error(&e))),
+            _ => {
+                logging::log!("{e:?} - {e}");
+                toasts.push_error(i18n.error(&e));
+            }
+        }
+    });
+    let banner = move || match create_post.value().get()? {
+        Err(ApiError::Banned { until, reason }) => Some(Either::Left(view! {
+          <SanctionBanner banned=true until reason />
+        })),
+        Err(ApiError::Muted { until, reason }) => Some(Either::Left(view! {
+          <SanctionBanner banned=false until reason />
+        })),
+        Err(ApiError::PostingRequirementsNotMet {
+            min_account_age_hours,
+            min_post_count,
+        }) => Some(Either::Right(view! {
+          <RequirementsBanner min_account_age_hours min_post_count />
+        })),
+        _ => None,
+    };
+
+    view! {
+      <ThreadIndex thread_id highlight />
+      {banner}
+
+      // frames on other sites are always guests, they reply in a window of the forum
+      {(!embedded)
+        .then(|| {
+          view! {
+            <Suspense>
+              {move || Suspend::new(async move {
+                let forum_id = thread_res.await.ok()?.forum_id;
+                Some(view! {
+                  <ForumRulesGate forum_id>
+                    // https://flowbite.com/docs/forms/textarea/#comment-box
+                    <ActionForm
+                      action=create_post
+                      on:submit:capture=move |ev| {
+                        let post = api::CreatePost::from_event(&ev);
+                        let Ok(post) = post else {
+                          return;
+                        };
+                        if post.content.is_empty() {
+                          set_content_error(Some(i18n.t("thread.content_empty")));
+                          ev.prevent_default();
+                        }
+                      }
+                      attr:class="mb-4 w-full max-w-md bg-gray-50 rounded-lg border border-gray-200"
+                    >
+                      // I hope there's a better way to do this...
+                      <input class="hidden" name="thread_id" value=thread_id />
+                      <CsrfField />
+                      <ChallengeWidget reset=create_post.version() />
+                      <input
+                        class="hidden"
+                        name="quote_post_id"
+                        prop:value=move || quoting().map(|id| id.to_string()).unwrap_or_default()
+                      />
+                      {move || {
+                        quoting()
+                          .map(|id| {
+                            view! {
+                              <p class="flex gap-2 items-center py-1 px-4 text-sm text-gray-700">
+                                {t!("thread.quoting", id = id)}
+                                <button
+                                  type="button"
+                                  on:click=move |_| set_quoting(None)
+                                  class="text-xs text-red-600 hover:underline hover:cursor-pointer"
+                                >
+                                  {t!("thread.cancel_quote")}
+                                </button>
+                              </p>
+                            }
+                          })
+                      }}
+                      <div class="px-2 pt-1">
+                        <MarkdownEditor
+                          name="content"
+                          value=Signal::derive(move || draft.content())
+                          on_input=Callback::new(move |content: String| {
+                            if !content.is_empty() {
+                              set_content_error(None);
+                            }
+                            draft.set_content(content);
+                            ping_typing();
+                          })
+                          previewing
+                          placeholder="thread.composer_placeholder"
+                          textarea_class="py-2 px-4 w-full text-sm text-gray-900 bg-white rounded-t-lg border-0 focus:ring-0 placeholder:italic"
+                        />
+                      </div>
+                      {move || {
+                        content_error()
+                          .map(|error| view! { <p class="px-4 text-sm font-bold text-red-700">{error}</p> })
+                      }}
+                      <AttachmentUploader attachments />
+                      <div class="flex justify-between items-center py-2 px-3 border-t border-gray-200">
+                        <input
+                          type="submit"
+                          value=t!("thread.create_post")
+                          class="inline-flex items-center py-2.5 px-4 text-xs font-medium text-center text-white bg-blue-700 rounded-lg hover:bg-blue-800 focus:ring-4 focus:ring-blue-200"
+                        />
+                        <DiscardDraftButton draft />
+                      </div>
+                    </ActionForm>
+                    {move || {
+                      api::typing::typing_text(i18n.locale(), typing_count.get())
+                        .map(|text| view! { <p class="-mt-3 mb-4 text-sm italic text-gray-500">{text}</p> })
+                    }}
+                  </ForumRulesGate>
+                })
+              })}
+            </Suspense>
           }
-          prop:value=move || { create_post.version().with(move |_| String::new()) }
-          class="py-2 px-4 w-full text-sm text-gray-900 bg-white rounded-t-lg border-0 focus:ring-0 placeholder:italic"
-        ></textarea>
-        <div class="flex justify-between items-center py-2 px-3 border-t border-gray-200">
-          <input
-            type="submit"
-            value="Create Post"
-            class="inline-flex items-center py-2.5 px-4 text-xs font-medium text-center text-white bg-blue-700 rounded-lg hover:bg-blue-800 focus:ring-4 focus:ring-blue-200"
-          />
-        </div>
-      </ActionForm>
-      {post_list_view}
+        })}
+      // keeps the loaded posts while more are loaded, so expanding doesn't jump around
+      <Transition>{post_list_view}</Transition>
     }
 }
 
-/// Renders a list item with a box containing a single [`Post`]
+/// Renders a list item with a box containing a single [`Post`][api::Post] and its author
+///
+/// Quoting, editing (if `editable`) and highlighting for the megathread index
+/// (if `highlightable`) are only offered inside of a thread,
+/// `wiki` posts additionally show their edit history.
+/// When `moderating`, edits can be given a reason for [staff edits][api::Post::staff_edit],
+/// the history of every edited post is shown and the [`PostModMenu`] is offered
 #[component]
-pub fn PostItem(post: Post) -> impl IntoView {
+pub fn PostItem(
+    post: PostWithAuthor,
+    #[prop(optional)] editable: bool,
+    #[prop(optional)] wiki: bool,
+    #[prop(optional)] highlightable: bool,
+    #[prop(optional)] moderating: bool,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let PostWithAuthor { post, author, html } = post;
+    let actions = use_context::<PostActions>();
+    let post_id = post.id;
+
+    let select_checkbox = use_context::<PostSelection>().map(|selection| {
+        let toggle = move |checked: bool| {
+            selection.selected.update(|selected| {
+                selected.retain(|&id| id != post_id);
+                if checked {
+                    selected.push(post_id);
+                }
+            });
+        };
+        view! {
+          <Show when=move || selection.selecting.get()>
+            <input
+              type="checkbox"
+              aria-label=t!("thread.select_post")
+              prop:checked=move || selection.selected.get().contains(&post_id)
+              on:change:target=move |ev| toggle(ev.target().checked())
+              class="mr-2"
+            />
+          </Show>
+        }
+    });
+
+    let quote_button = actions.map(|actions| {
+        view! {
+          <button
+            type="button"
+            on:click=move |_| (actions.set_quoting)(Some(post_id))
+            class="py-1 px-2 text-xs font-medium text-purple-700 rounded-lg border border-purple-300 hover:bg-purple-100 hover:cursor-pointer"
+          >
+            {t!("thread.quote")}
+          </button>
+        }
+    });
+    let highlighted = post.highlighted;
+    let highlight_button = actions.filter(|_| highlightable).map(|actions| {
+        view! {
+          <button
+            type="button"
+            on:click=move |_| {
+              actions
+                .highlight
+                .dispatch(api::megathread::SetPostHighlighted {
+                  post_id,
+                  highlighted: !highlighted,
+                });
+            }
+            class="py-1 px-2 text-xs font-medium text-purple-700 rounded-lg border border-purple-300 hover:bg-purple-100 hover:cursor-pointer"
+          >
+            {t!(if highlighted { "thread.index.remove" } else { "thread.index.add" })}
+          </button>
+        }
+    });
+    let reaction_bar = actions.map(|actions| view! { <ReactionBar post_id actions /> });
+    let flag_button = actions.map(|_| view! { <FlagButton post_id /> });
+    let content = post.content.clone();
+    let edit_view = actions.filter(|_| editable).map(|actions| {
+        let edit_error = move || match actions.edit.value().get() {
+            Some(Err(e)) => Some(i18n.error(&e)),
+            _ => None,
+        };
+        let stored = content.clone();
+        let (draft, set_draft) = signal(content.clone());
+        let changes_view = move || {
+            let lines = diff_lines(&stored, &draft.read());
+            let changed = lines.iter().any(|line| line.tag != DiffTag::Equal);
+            changed.then(|| {
+                view! {
+                  <div class="text-xs">
+                    <p class="font-bold">{t!("thread.changes")}</p>
+                    <Diff lines />
+                  </div>
+                }
+            })
+        };
+        view! {
+          <details class="mt-2 w-full">
+            <summary class="text-xs font-medium text-purple-700 hover:cursor-pointer">
+              {t!("thread.edit")}
+            </summary>
+            <p class="text-xs text-red-600">{edit_error}</p>
+            <ActionForm action=actions.edit attr:class="flex flex-col gap-2">
+              <input class="hidden" name="post_id" value=post_id />
+              <textarea
+                name="content"
+                rows="5"
+                required
+                on:input:target=move |ev| set_draft(ev.target().value())
+                class="py-2 px-4 w-full text-sm text-gray-900 bg-gray-50 rounded-lg border border-gray-200"
+              >
+                {content}
+              </textarea>
+              {changes_view}
+              {moderating
+                .then(|| {
+                  view! {
+                    <input
+                      name="reason"
+                      maxlength=api::MAX_EDIT_REASON_LEN
+                      placeholder=t!("thread.edit_reason")
+                      class="py-1 px-2 w-full text-sm bg-gray-50 rounded-lg border border-gray-200"
+                    />
+                  }
+                })}
+              <input
+                type="submit"
+                value=t!("common.save")
+                class="py-1 px-2 text-xs font-medium text-white bg-blue-700 rounded-lg hover:bg-blue-800"
+              />
+            </ActionForm>
+          </details>
+        }
+    });
+    let edited = match (post.revision > 0, post.edit_reason) {
+        (false, _) => None,
+        (true, Some(reason)) if post.staff_edit => {
+            Some(i18n.t_with("thread.edited_by_staff", &[("reason", reason)]))
+        }
+        (true, _) => Some(i18n.t("thread.edited")),
+    };
+    let history =
+        (wiki || (moderating && post.revision > 0)).then(|| view! { <PostHistory post_id /> });
+    let has_author = post.author_id.is_some();
+    let mod_menu = actions.filter(|_| moderating).map(|actions| {
+        view! { <PostModMenu post_id has_author moderate=actions.moderate /> }
+    });
+
     view! {
-      <li>
+      <li id=format!("post-{post_id}")>
         <article class="p-6 w-full max-w-md bg-white rounded-lg border border-gray-200 shadow-sm0">
           <div class="flex justify-between">
             <h6 class="mb-2 text-xs font-bold tracking-tight text-gray-900">
-              "Posted at "<time datetime=post.date_in_berlin()>{post.date_in_berlin()}</time>
+              {select_checkbox}
+              {t!("thread.posted")}
+              " "
+              <LocalTime timestamp=post.created_at relative=true />
+              {edited.map(|edited| format!(" {edited}"))}
+              {wiki.then(|| format!(" [{}]", i18n.t("thread.wiki_tag")))}
+              {highlighted.then(|| format!(" [{}]", i18n.t("thread.highlighted_tag")))}
+              " "
+              {t!("thread.by")}
+              " "
+              {match (author, post.author_id) {
+                (Some(member), _) => EitherOf3::A(view! { <MemberLabel member avatar=true /> }),
+                (None, Some(_)) => EitherOf3::B(view! { <span>{t!("common.deleted_user")}</span> }),
+                (None, None) => EitherOf3::C(view! { <span>{t!("common.anonymous")}</span> }),
+              }}
             </h6>
             <h6 class="mb-2 text-xs font-bold tracking-tight text-gray-900">
-              "Post #"{post.id}" in "
+              <a href=format!("/post/{}", post.id) class="hover:underline">
+                {t!("thread.post_number", id = post.id)}
+              </a>
+              " "
+              {t!("thread.in")}
+              " "
               <a
                 href=format!("/thread/{}", post.thread_id)
                 class="font-medium text-blue-600 underline hover:no-underline"
               >
-                "Thread #"
-                {post.thread_id}
+                {t!("thread.thread_number", id = post.thread_id)}
               </a>
             </h6>
           </div>
-          // to render newlines
-          <p class="mb-3 font-normal text-gray-700 whitespace-pre-wrap break-words">
-            {post.content}
-          </p>
+          {post.quote.map(|quote| view! { <QuoteBlock quote /> })}
+          {if post.collapsed {
+            Either::Left(
+              view! {
+                <details class="mb-3">
+                  <summary class="text-xs italic text-gray-500 hover:cursor-pointer">
+                    {t!("thread.collapsed")}
+                  </summary>
+                  <div
+                    class="mt-2 font-normal text-gray-700 markdown"
+                    on:click=super::copy_code
+                    inner_html=html
+                  ></div>
+                  <AttachmentList attachments=post.attachments />
+                  <LinkPreviewCards previews=post.link_previews />
+                </details>
+              },
+            )
+          } else {
+            Either::Right(
+              view! {
+                <div
+                  class="mb-3 font-normal text-gray-700 markdown"
+                  on:click=super::copy_code
+                  inner_html=html
+                ></div>
+                <AttachmentList attachments=post.attachments />
+                <LinkPreviewCards previews=post.link_previews />
+              },
+            )
+          }}
+          {reaction_bar}
+          <div class="flex flex-wrap gap-2 items-center">
+            {post.author_id.map(|_| view! { <ThankButton post_id=post.id thanks=post.thanks /> })}
+            {quote_button}
+            {highlight_button}
+            <PermalinkButton post_id />
+          </div>
+          {flag_button}
+          {edit_view}
+          {history}
+          {mod_menu}
         </article>
       </li>
     }
 }
+
+/// Renders the moderator shortcuts of a [`Post`][api::Post] with a preset reason:
+/// deleting it, warning its author and moving it to another thread, see [`api::mod_shortcuts`]
+///
+/// The reasons are only loaded once the menu is opened, not for every post of the thread
+#[component]
+fn PostModMenu(
+    post_id: u32,
+    has_author: bool,
+    moderate: ServerAction<ModeratePost>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let opened = RwSignal::new(false);
+    let reason_id = RwSignal::<Option<u32>>::new(None);
+    let (note, set_note) = signal(String::new());
+    let (target_thread_id, set_target_thread_id) = signal::<Option<u32>>(None);
+    // the action is shared by all posts, only show its result at the clicked one
+    let clicked = RwSignal::new(None::<PostShortcut>);
+    let status = move || match (moderate.value().get(), clicked.get()) {
+        (Some(Err(e)), Some(_)) => Some(Either::Left(view! {
+          <p class="text-xs text-red-600">{i18n.error(&e)}</p>
+        })),
+        (Some(Ok(())), Some(PostShortcut::WarnAuthor)) => Some(Either::Right(view! {
+          <p class="text-xs text-green-700">{t!("thread.author_warned")}</p>
+        })),
+        _ => None,
+    };
+    let run = move |shortcut| {
+        clicked.set(Some(shortcut));
+        moderate.dispatch(ModeratePost {
+            post_id,
+            shortcut,
+            reason_id: reason_id.get_untracked(),
+            note: note.get_untracked(),
+            target_thread_id: target_thread_id.get_untracked(),
+        });
+    };
+
+    let warn_view = has_author.then(|| {
+        view! {
+          <div class="flex flex-wrap gap-2 items-center">
+            <input
+              placeholder=t!("thread.warning_note")
+              on:input:target=move |ev| set_note(ev.target().value())
+              class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+            />
+            <button
+              type="button"
+              on:click=move |_| run(PostShortcut::WarnAuthor)
+              class="py-1 px-2 font-bold text-amber-50 bg-amber-700 rounded-lg hover:bg-amber-800 hover:cursor-pointer"
+            >
+              {t!(PostShortcut::WarnAuthor.label_key())}
+            </button>
+          </div>
+        }
+    });
+
+    view! {
+      <details
+        class="mt-2 w-full text-xs"
+        on:toggle:target=move |ev| {
+          if ev.target().open() {
+            opened.set(true);
+          }
+        }
+      >
+        <summary class="font-medium text-red-700 hover:cursor-pointer">
+          {t!("thread.moderate")}
+        </summary>
+        <Show when=move || opened.get()>
+          {status}
+          <div class="flex flex-col gap-2 mt-2">
+            <div class="flex flex-wrap gap-2 items-center">
+              <ModReasonSelect reason_id />
+              <button
+                type="button"
+                on:click=move |_| run(PostShortcut::Delete)
+                class="py-1 px-2 font-bold text-red-50 bg-red-800 rounded-lg hover:bg-red-900 hover:cursor-pointer"
+              >
+                {t!(PostShortcut::Delete.label_key())}
+              </button>
+            </div>
+            {warn_view}
+            <div class="flex flex-wrap gap-2 items-center">
+              <input
+                type="number"
+                min="1"
+                placeholder=t!("thread.target_thread_id")
+                on:input:target=move |ev| set_target_thread_id(ev.target().value().parse().ok())
+                class="p-1 w-24 bg-purple-50 rounded-lg border border-purple-400"
+              />
+              <button
+                type="button"
+                on:click=move |_| run(PostShortcut::Move)
+                class="py-1 px-2 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+              >
+                {t!(PostShortcut::Move.label_key())}
+              </button>
+            </div>
+          </div>
+        </Show>
+      </details>
+    }
+}
+
+/// Renders a button for every enabled [`ReactionOption`] with how often the post got it,
+/// highlighting the ones the logged-in user picked
+#[component]
+fn ReactionBar(post_id: u32, actions: PostActions) -> impl IntoView {
+    let i18n = use_i18n();
+    // the action is shared by all posts, only show its errors at the clicked one
+    let clicked = RwSignal::new(false);
+    let error = move || match actions.react.value().get() {
+        Some(Err(e)) if clicked.get() => Some(i18n.error(&e)),
+        _ => None,
+    };
+
+    let buttons_view = move || {
+        Suspend::new(async move {
+            let options = actions.reaction_options.await.unwrap_or_default();
+            let counts = actions
+                .reactions
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .find(|reactions| reactions.post_id == post_id)
+                .map(|reactions| reactions.counts)
+                .unwrap_or_default();
+            options
+                .into_iter()
+                .map(|option| {
+                    let option_id = option.id;
+                    let (count, reacted) = counts
+                        .iter()
+                        .find(|count| count.option_id == option_id)
+                        .map_or((0, false), |count| (count.count, count.reacted));
+                    let label = match option.image {
+                        Some(image) => Either::Left(view! {
+                          <img
+                            src=image.url()
+                            alt=option.emoji.clone()
+                            title=option.emoji
+                            class="inline w-4 h-4"
+                          />
+                        }),
+                        None => Either::Right(option.emoji),
+                    };
+                    view! {
+                      <button
+                        type="button"
+                        on:click=move |_| {
+                          clicked.set(true);
+                          actions.react.dispatch(ToggleReaction { post_id, option_id });
+                        }
+                        class="py-1 px-2 text-xs rounded-lg border hover:bg-purple-100 hover:cursor-pointer"
+                        class=(["bg-purple-200", "border-purple-500"], move || reacted)
+                        class=(["border-purple-300"], move || !reacted)
+                      >
+                        {label}
+                        {(count > 0).then(|| format!(" {count}"))}
+                      </button>
+                    }
+                })
+                .collect_view()
+        })
+    };
+
+    view! {
+      <div class="flex flex-wrap gap-1 items-center mb-2">
+        <Suspense>{buttons_view}</Suspense>
+        <span class="text-xs text-red-600">{error}</span>
+      </div>
+    }
+}
+
+/// Renders a button copying the permalink of the [`Post`][api::Post] with `post_id`
+#[component]
+fn PermalinkButton(post_id: u32) -> impl IntoView {
+    let i18n = use_i18n();
+    let (copied, set_copied) = signal(false);
+
+    let copy = move |_| {
+        let Ok(origin) = window().location().origin() else {
+            return;
+        };
+        let url = format!("{origin}/post/{post_id}");
+        let promise = window().navigator().clipboard().write_text(&url);
+        spawn_local(async move {
+            match JsFuture::from(promise).await {
+                Ok(_) => set_copied(true),
+                Err(err) => logging::log!("{err:?}"),
+            }
+        });
+    };
+
+    view! {
+      <button
+        type="button"
+        on:click=copy
+        class="py-1 px-2 text-xs font-medium text-purple-700 rounded-lg border border-purple-300 hover:bg-purple-100 hover:cursor-pointer"
+      >
+        {move || i18n.t(if copied() { "thread.link_copied" } else { "thread.copy_link" })}
+      </button>
+    }
+}
+
+/// Renders the index of a megathread with links to its highlighted and most thanked
+/// [`Posts`][api::Post], nothing for smaller threads
+#[component]
+fn ThreadIndex(
+    thread_id: u32,
+    highlight: ServerAction<api::megathread::SetPostHighlighted>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let index_res = Resource::new(
+        move || highlight.version().get(),
+        move |_| api::megathread::get_thread_index(thread_id),
+    );
+
+    let index_view = move || {
+        Suspend::new(async move {
+            let entries = match index_res.await {
+                Ok(entries) => entries,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("thread.index.load_failed")}</p> });
+                }
+            };
+            if entries.is_empty() {
+                return EitherOf3::B(());
+            }
+            let items = entries
+                .into_iter()
+                .map(|entry| {
+                    let thanks = (entry.thanks > 0).then(|| {
+                        format!(
+                            " {}",
+                            i18n.t_count("thread.index.thanks", entry.thanks.into())
+                        )
+                    });
+                    view! {
+                      <li>
+                        <a
+                          href=format!("#post-{}", entry.post_id)
+                          class="underline hover:no-underline"
+                          class=("font-bold", entry.highlighted)
+                        >
+                          {entry.excerpt}
+                        </a>
+                        {thanks}
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! {
+              <nav class="p-4 mb-2 max-w-md text-sm bg-purple-100 rounded-lg">
+                <h3 class="mb-1 font-bold">{t!("thread.index.title")}</h3>
+                <ol class="list-decimal list-inside">{items}</ol>
+              </nav>
+            })
+        })
+    };
+
+    view! { <Suspense>{index_view}</Suspense> }
+}
+
+/// Renders the previous versions of a [`Post`][api::Post] and who wrote them
+#[component]
+fn PostHistory(post_id: u32) -> impl IntoView {
+    let i18n = use_i18n();
+    let revisions_res = Resource::new(move || (), move |()| api::get_post_revisions(post_id));
+
+    let revisions_view = move || {
+        Suspend::new(async move {
+            let revisions = match revisions_res.await {
+                Ok(revisions) => revisions,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("thread.history.load_failed")}</p> });
+                }
+            };
+            let view = revisions
+                .into_iter()
+                .map(|revision| {
+                    let by = match revision.edited_by {
+                        Some(user_id) => Either::Left(view! { <MemberName user_id /> }),
+                        None => {
+                            Either::Right(view! { <span>{t!("thread.history.the_author")}</span> })
+                        }
+                    };
+                    let staff_edit =
+                        revision
+                            .edit_reason
+                            .filter(|_| revision.staff_edit)
+                            .map(|reason| {
+                                i18n.t_with("thread.history.staff_edit", &[("reason", reason)])
+                            });
+                    view! {
+                      <li class="pt-1 border-t border-gray-200">
+                        <p class="font-bold">
+                          {t!("thread.history.revision", n = revision.revision)}
+                          " "
+                          {t!("thread.by")}
+                          " "
+                          {by}
+                          {staff_edit.map(|staff_edit| format!(" {staff_edit}"))}
+                          ", "
+                          {t!("thread.history.replaced")}
+                          " "
+                          <LocalTime timestamp=revision.replaced_at />
+                        </p>
+                        <p class="whitespace-pre-wrap break-words">{revision.content}</p>
+                      </li>
+                    }
+                })
+                .collect_view();
+            Either::Right(view! { <ol class="flex flex-col gap-1">{view}</ol> })
+        })
+    };
+
+    view! {
+      <details class="mt-2 w-full text-xs">
+        <summary class="font-medium text-purple-700 hover:cursor-pointer">
+          {t!("thread.history.title")}
+        </summary>
+        <Suspense fallback=move || {
+          view! { <p>{t!("common.loading")}</p> }
+        }>{revisions_view}</Suspense>
+      </details>
+    }
+}
+
+/// Renders the quoted part of a [`Post`][api::Post] as it was when it got quoted
+#[component]
+fn QuoteBlock(quote: Quote) -> impl IntoView {
+    view! {
+      <blockquote class="pl-3 mb-3 text-sm text-gray-600 border-l-4 border-purple-300">
+        <p class="font-bold">
+          {t!("thread.quote_wrote", id = quote.post_id)}
+          <Show when=move || quote.source_edited>
+            <span class="font-normal italic text-purple-700">
+              " "
+              {t!("thread.quote_source_edited", revision = quote.revision)}
+            </span>
+          </Show>
+        </p>
+        <p class="whitespace-pre-wrap break-words">{quote.content}</p>
+      </blockquote>
+    }
+}
+
+/// Renders the [`LinkPreviews`][LinkPreview] of a [`Post`][api::Post] as small cards
+#[component]
+fn LinkPreviewCards(previews: Vec<LinkPreview>) -> impl IntoView {
+    if previews.is_empty() {
+        return None;
+    }
+    let cards = previews
+        .into_iter()
+        .map(|preview| {
+            view! {
+              <li>
+                <a
+                  href=preview.url
+                  target="_blank"
+                  rel="noopener nofollow ugc"
+                  class="flex overflow-hidden max-w-lg bg-white rounded-lg border border-gray-200 hover:bg-purple-50"
+                >
+                  {preview
+                    .image_url
+                    .map(|src| {
+                      view! {
+                        <img
+                          src=src
+                          alt=""
+                          loading="lazy"
+                          class="object-cover w-24 h-24 shrink-0"
+                        />
+                      }
+                    })}
+                  <div class="p-2 min-w-0">
+                    <p class="text-xs text-gray-500 truncate">{preview.site_name}</p>
+                    <p class="text-sm font-bold text-gray-900 truncate">{preview.title}</p>
+                    <p class="text-xs text-gray-700 line-clamp-2">{preview.description}</p>
+                  </div>
+                </a>
+              </li>
+            }
+        })
+        .collect_view();
+
+    Some(view! { <ul class="flex flex-col gap-2 mb-3">{cards}</ul> })
+}
+
+/// Lets users thank the author of a [`Post`][api::Post] and shows how often it was thanked
+#[component]
+fn ThankButton(post_id: u32, thanks: u32) -> impl IntoView {
+    let i18n = use_i18n();
+    let thank = ServerAction::<api::ThankPost>::new();
+    let count = move || match thank.value().get() {
+        Some(Ok(count)) => count,
+        _ => thanks,
+    };
+    let error = move || match thank.value().get() {
+        Some(Err(e)) => Some(i18n.error(&e)),
+        _ => None,
+    };
+
+    view! {
+      <ActionForm action=thank attr:class="flex gap-2 items-center">
+        <input type="hidden" name="post_id" value=post_id />
+        <button
+          type="submit"
+          class="py-1 px-2 text-xs font-medium text-purple-700 rounded-lg border border-purple-300 hover:bg-purple-100"
+        >
+          {move || i18n.t_with("thread.thanks", &[("n", count().to_string())])}
+        </button>
+        <span class="text-xs text-red-600">{error}</span>
+      </ActionForm>
+    }
+}
+
+/// Lets users flag a [`Post`][api::Post] for moderators, with an optional reason
+#[component]
+fn FlagButton(post_id: u32) -> impl IntoView {
+    let i18n = use_i18n();
+    let flag = ServerAction::<api::flags::FlagPost>::new();
+    let status = move || match flag.value().get() {
+        Some(Ok(())) => Some(Either::Left(
+            view! { <span class="text-xs text-green-700">{t!("thread.flag.done")}</span> },
+        )),
+        Some(Err(e)) => Some(Either::Right(
+            view! { <span class="text-xs text-red-600">{i18n.error(&e)}</span> },
+        )),
+        None => None,
+    };
+
+    view! {
+      <details class="mt-2 w-full">
+        <summary class="text-xs font-medium text-gray-500 hover:cursor-pointer">
+          {t!("thread.flag.title")}
+        </summary>
+        <ActionForm action=flag attr:class="flex gap-2 items-center">
+          <input type="hidden" name="post_id" value=post_id />
+          <input
+            type="text"
+            name="reason"
+            placeholder=t!("thread.flag.reason")
+            maxlength=api::flags::MAX_FLAG_REASON_LEN
+            class="py-1 px-2 text-xs text-gray-900 bg-gray-50 rounded-lg border border-gray-200"
+          />
+          <input
+            type="submit"
+            value=t!("thread.flag.submit")
+            class="py-1 px-2 text-xs font-medium text-red-700 rounded-lg border border-red-300 hover:bg-red-100 hover:cursor-pointer"
+          />
+          {status}
+        </ActionForm>
+      </details>
+    }
+}
@@ -0,0 +1,92 @@
+//! Syntax highlighting of fenced code blocks in rendered Markdown, done with [`syntect`]
+//!
+//! The highlighted code only has CSS classes, the colors come from [`stylesheet`],
+//! with a light and a dark theme following `prefers-color-scheme`
+
+use pulldown_cmark::{Event, html};
+use std::sync::LazyLock;
+use syntect::highlighting::ThemeSet;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator, css_for_theme_with_class_style};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// Classes of highlighted code are prefixed so they don't clash with Tailwind's
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+const LIGHT_THEME: &str = "InspiredGitHub";
+const DARK_THEME: &str = "base16-ocean.dark";
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+
+/// The syntax of `code`, looked up by `lang` (e.g. `rust` or `rs`, the info string of the fence)
+/// or guessed from its first line (e.g. a shebang), plain text if neither works
+fn detect_syntax(code: &str, lang: &str) -> &'static SyntaxReference {
+    let lang = lang.split_whitespace().next().unwrap_or_default();
+    SYNTAXES
+        .find_syntax_by_token(lang)
+        .or_else(|| SYNTAXES.find_syntax_by_first_line(code))
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text())
+}
+
+/// Renders `code` as a highlighted code block with a copy button,
+/// `lang` is the info string of its fence, empty for indented code blocks
+///
+/// # Example
+///
+/// ```
+/// use app::highlight::code_block;
+///
+/// let html = code_block("fn main() {}\n", "rust");
+/// assert!(html.contains(r#"<span class="hl-source hl-rust">"#));
+/// assert!(code_block("<b>\n", "").contains("&lt;b&gt;"));
+/// ```
+#[must_use]
+pub fn code_block(code: &str, lang: &str) -> String {
+    let syntax = detect_syntax(code, lang);
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAXES, CLASS_STYLE);
+    let highlighted = LinesWithEndings::from(code)
+        .try_for_each(|line| generator.parse_html_for_line_which_includes_newline(line));
+    let code = match highlighted {
+        Ok(()) => generator.finalize(),
+        // only happens with broken syntax definitions, the code is still worth showing
+        Err(err) => {
+            tracing::warn!("highlighting {} failed: {err}", syntax.name);
+            let mut escaped = String::new();
+            html::push_html(&mut escaped, std::iter::once(Event::Text(code.into())));
+            escaped
+        }
+    };
+    // the copy button works wherever the HTML is put with `on:click=forum::copy_code`
+    format!(
+        r#"<div class="code-block"><button type="button" data-copy-code>Copy</button><pre class="hl-code"><code>{code}</code></pre></div>"#
+    )
+}
+
+/// The CSS of both themes, generated once
+static STYLESHEET: LazyLock<String> = LazyLock::new(|| {
+    let themes = ThemeSet::load_defaults();
+    let css = |name: &str| {
+        css_for_theme_with_class_style(&themes.themes[name], CLASS_STYLE).unwrap_or_else(|err| {
+            tracing::warn!("generating the CSS of {name} failed: {err}");
+            String::new()
+        })
+    };
+    format!(
+        "{}\n@media (prefers-color-scheme: dark) {{\n{}}}\n",
+        css(LIGHT_THEME),
+        css(DARK_THEME)
+    )
+});
+
+/// Axum handler for `GET /highlight.css`, the colors of highlighted code
+pub async fn stylesheet() -> axum::response::Response {
+    use axum::response::IntoResponse;
+
+    (
+        [
+            (http::header::CONTENT_TYPE, "text/css; charset=utf-8"),
+            (http::header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        STYLESHEET.as_str(),
+    )
+        .into_response()
+}
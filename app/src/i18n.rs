@@ -0,0 +1,369 @@
+//! Translations of the UI
+//!
+//! Texts are looked up by key, e.g. `nav.forums`, in a JSON bundle per [`Locale`] in `app/locales`.
+//! Keys missing from a bundle fall back to English, keys missing there too are shown as they are.
+//! Templates can contain `{name}` placeholders, and counted texts have a `.one` and an `.other`
+//! variant, see [`translate_count`]. Errors are translated by their
+//! [`Message`][crate::api::messages::Message] key, see [`I18n::error`].
+//!
+//! Views use the [`t!`][crate::t] macro, which reads the [`I18n`] context and updates
+//! when the language is switched. The chosen [`Locale`] is kept in the [`LOCALE_COOKIE`],
+//! without one the browser's preferred language is used
+
+use crate::api::ApiError;
+
+use leptos::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// Name of the cookie holding the [`Locale::code`] chosen in the language switcher
+pub const LOCALE_COOKIE: &str = "dafoerum_locale";
+
+/// A language the UI is translated to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+}
+impl Locale {
+    pub const ALL: [Self; 2] = [Self::En, Self::De];
+
+    /// ISO 639-1 code, e.g. `de`, also used as the `lang` of the page
+    #[must_use]
+    pub const fn code(self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::De => "de",
+        }
+    }
+
+    /// Name of the language in itself, for the language switcher
+    #[must_use]
+    pub const fn native_name(self) -> &'static str {
+        match self {
+            Self::En => "English",
+            Self::De => "Deutsch",
+        }
+    }
+
+    /// [`strftime`][jiff::fmt::strftime] format of a date with time
+    #[must_use]
+    pub const fn datetime_format(self) -> &'static str {
+        match self {
+            Self::En => "%F %R",
+            Self::De => "%d.%m.%Y %H:%M",
+        }
+    }
+
+    /// Finds the locale of a language tag like `de-AT` by its language,
+    /// [`None`] if it isn't supported
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::i18n::Locale;
+    ///
+    /// assert_eq!(Locale::from_tag("de-AT"), Some(Locale::De));
+    /// assert_eq!(Locale::from_tag("EN"), Some(Locale::En));
+    /// assert_eq!(Locale::from_tag("fr"), None);
+    /// ```
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_']).next()?;
+        Self::ALL
+            .into_iter()
+            .find(|locale| locale.code().eq_ignore_ascii_case(language))
+    }
+
+    /// Finds the first supported locale in an `Accept-Language` header,
+    /// which lists languages from most to least preferred
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use app::i18n::Locale;
+    ///
+    /// assert_eq!(Locale::from_accept_language("fr-CH, de;q=0.8, en;q=0.5"), Some(Locale::De));
+    /// assert_eq!(Locale::from_accept_language("fr"), None);
+    /// ```
+    #[must_use]
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        header
+            .split(',')
+            .filter_map(|language| language.split(';').next())
+            .find_map(Self::from_tag)
+    }
+
+    /// Reads the locale from a `Cookie` header or `document.cookie`
+    fn from_cookies(cookies: &str) -> Option<Self> {
+        cookies
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(name, _)| *name == LOCALE_COOKIE)
+            .and_then(|(_, code)| Self::from_tag(code))
+    }
+
+    fn bundle(self) -> &'static Bundle {
+        match self {
+            Self::En => &EN,
+            Self::De => &DE,
+        }
+    }
+}
+
+/// Templates by key
+type Bundle = BTreeMap<String, String>;
+
+fn parse_bundle(json: &str) -> Bundle {
+    let Ok(bundle) = serde_json::from_str(json) else {
+        unreachable!("the bundles are valid JSON, see the tests")
+    };
+    bundle
+}
+
+static EN: LazyLock<Bundle> = LazyLock::new(|| parse_bundle(include_str!("../locales/en.json")));
+static DE: LazyLock<Bundle> = LazyLock::new(|| parse_bundle(include_str!("../locales/de.json")));
+
+/// The template of `key` in `locale`, falling back to English, [`None`] if neither has it
+fn template(locale: Locale, key: &str) -> Option<&'static str> {
+    locale
+        .bundle()
+        .get(key)
+        .or_else(|| EN.get(key))
+        .map(String::as_str)
+}
+
+/// Replaces every `{name}` in `template` with its value in `params`
+fn fill(template: &str, params: &[(&str, String)]) -> String {
+    params
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+/// Translates `key` to `locale`, filling in the `params`
+///
+/// # Example
+///
+/// ```
+/// use app::i18n::{Locale, translate};
+///
+/// assert_eq!(translate(Locale::De, "nav.forums", &[]), "Foren");
+/// assert_eq!(translate(Locale::De, "preview.reply_in", &[("name", "FAQ".into())]), "Antwort in FAQ");
+/// assert_eq!(translate(Locale::De, "no.such.key", &[]), "no.such.key");
+/// ```
+#[must_use]
+pub fn translate(locale: Locale, key: &str, params: &[(&str, String)]) -> String {
+    template(locale, key).map_or_else(|| key.to_string(), |template| fill(template, params))
+}
+
+/// Translates the `.one` or `.other` variant of `key` depending on `count`,
+/// filling it in as `{n}`
+///
+/// # Example
+///
+/// ```
+/// use app::i18n::{Locale, translate_count};
+///
+/// assert_eq!(translate_count(Locale::En, "time.ago.day", 1), "1 day ago");
+/// assert_eq!(translate_count(Locale::De, "time.ago.day", 3), "vor 3 Tagen");
+/// ```
+#[must_use]
+pub fn translate_count(locale: Locale, key: &str, count: i64) -> String {
+    let variant = if count == 1 { "one" } else { "other" };
+    translate(
+        locale,
+        &format!("{key}.{variant}"),
+        &[("n", count.to_string())],
+    )
+}
+
+/// The [`Locale`] the UI is shown in, provided as context by [`provide_i18n`]
+#[derive(Clone, Copy, Debug)]
+pub struct I18n {
+    locale: RwSignal<Locale>,
+}
+impl I18n {
+    #[must_use]
+    pub fn locale(self) -> Locale {
+        self.locale.get()
+    }
+
+    /// Switches the UI to `locale` and remembers it in the [`LOCALE_COOKIE`] for a year
+    pub fn set_locale(self, locale: Locale) {
+        self.locale.set(locale);
+
+        #[cfg(not(feature = "ssr"))]
+        {
+            use wasm_bindgen::JsCast;
+
+            if let Ok(document) = document().dyn_into::<web_sys::HtmlDocument>() {
+                let cookie = format!(
+                    "{LOCALE_COOKIE}={}; Path=/; SameSite=Lax; Max-Age={}",
+                    locale.code(),
+                    365 * 24 * 60 * 60
+                );
+                // cookies disabled, it's only switched until the next reload then
+                let _ = document.set_cookie(&cookie);
+            }
+        }
+    }
+
+    /// Translates `key`, see [`translate`]
+    #[must_use]
+    pub fn t(self, key: &str) -> String {
+        translate(self.locale(), key, &[])
+    }
+
+    /// Translates `key` with `params`, see [`translate`]
+    #[must_use]
+    pub fn t_with(self, key: &str, params: &[(&str, String)]) -> String {
+        translate(self.locale(), key, params)
+    }
+
+    /// Translates `key` for `count`, see [`translate_count`]
+    #[must_use]
+    pub fn t_count(self, key: &str, count: i64) -> String {
+        translate_count(self.locale(), key, count)
+    }
+
+    /// Translates `err` by its [`Message`][crate::api::messages::Message],
+    /// its English text if the key isn't translated
+    #[must_use]
+    pub fn error(self, err: &ApiError) -> String {
+        let message = err.message();
+        match template(self.locale(), &message.key) {
+            Some(template) => message.fill(template),
+            None => message.fallback,
+        }
+    }
+}
+
+/// The [`I18n`] context, English if there is none
+#[must_use]
+pub fn use_i18n() -> I18n {
+    use_context::<I18n>().unwrap_or_else(|| I18n {
+        locale: RwSignal::new(Locale::default()),
+    })
+}
+
+/// Provides the [`I18n`] as context, in the [`Locale`] from the [`LOCALE_COOKIE`]
+/// or else the browser's preferred language
+pub fn provide_i18n() -> I18n {
+    let i18n = I18n {
+        locale: RwSignal::new(initial_locale().unwrap_or_default()),
+    };
+    provide_context(i18n);
+    i18n
+}
+
+#[cfg(feature = "ssr")]
+fn initial_locale() -> Option<Locale> {
+    let parts = use_context::<http::request::Parts>()?;
+    let header = |name| {
+        parts
+            .headers
+            .get_all(name)
+            .iter()
+            .filter_map(|header| header.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(";")
+    };
+    Locale::from_cookies(&header(http::header::COOKIE))
+        .or_else(|| Locale::from_accept_language(&header(http::header::ACCEPT_LANGUAGE)))
+}
+
+#[cfg(not(feature = "ssr"))]
+fn initial_locale() -> Option<Locale> {
+    use wasm_bindgen::JsCast;
+
+    document()
+        .dyn_into::<web_sys::HtmlDocument>()
+        .ok()
+        .and_then(|document| document.cookie().ok())
+        .and_then(|cookies| Locale::from_cookies(&cookies))
+        .or_else(|| {
+            window()
+                .navigator()
+                .language()
+                .and_then(|language| Locale::from_tag(&language))
+        })
+}
+
+/// Translates a key of the [`i18n`][crate::i18n] bundles, updating when the language is switched,
+/// e.g. `{t!("nav.forums")}` in a view
+///
+/// Parameters are given as `name = value`, e.g. `t!("preview.reply_in", name = thread.subject)`
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {{
+        let i18n = $crate::i18n::use_i18n();
+        move || i18n.t($key)
+    }};
+    ($key:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let i18n = $crate::i18n::use_i18n();
+        let params = [$((stringify!($name), ::std::string::ToString::to_string(&$value))),+];
+        move || i18n.t_with($key, &params)
+    }};
+}
+
+/// Renders a select switching the [`Locale`] of the UI
+#[component]
+pub fn LanguageSwitcher() -> impl IntoView {
+    let i18n = use_i18n();
+
+    view! {
+      <label class="flex gap-1 items-center">
+        {t!("footer.language")}
+        <select
+          on:change:target=move |ev| {
+            if let Some(locale) = Locale::from_tag(&ev.target().value()) {
+              i18n.set_locale(locale);
+            }
+          }
+          class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+        >
+          {Locale::ALL
+            .into_iter()
+            .map(|locale| {
+              view! {
+                <option value=locale.code() selected=move || i18n.locale() == locale>
+                  {locale.native_name()}
+                </option>
+              }
+            })
+            .collect_view()}
+        </select>
+      </label>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundles_parse() {
+        for locale in Locale::ALL {
+            assert!(!locale.bundle().is_empty(), "{locale:?} bundle is empty");
+        }
+    }
+
+    #[test]
+    fn bundles_have_the_same_keys() {
+        // errors fall back to their English text, so only translations need them
+        let keys = |locale: Locale| -> Vec<&String> {
+            locale
+                .bundle()
+                .keys()
+                .filter(|key| !key.starts_with("error."))
+                .collect()
+        };
+        for locale in Locale::ALL {
+            assert_eq!(keys(locale), keys(Locale::En), "keys of {locale:?} differ");
+        }
+    }
+}
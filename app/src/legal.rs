@@ -0,0 +1,119 @@
+use crate::api;
+use crate::i18n::use_i18n;
+use crate::t;
+use crate::time::LocalTime;
+use api::legal::{LegalPage, LegalPageKind};
+
+use leptos::either::Either;
+use leptos::{logging, prelude::*};
+use leptos_meta::Title;
+
+/// Renders the latest version of the given legal page
+#[component]
+pub fn LegalPageView(kind: LegalPageKind) -> impl IntoView {
+    let i18n = use_i18n();
+    let page_res = Resource::new(move || (), move |()| api::legal::get_legal_page(kind, None));
+
+    let page_view = move || {
+        Suspend::new(async move {
+            let rendered = match page_res.await {
+                Ok(rendered) => rendered,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    let key = match err {
+                        api::ApiError::NotFound(..) => "legal.not_written",
+                        _ => "legal.load_failed",
+                    };
+                    return Either::Left(view! { <p>{t!(key)}</p> });
+                }
+            };
+            let view = view! {
+              <p class="text-sm text-purple-900">
+                {t!("legal.version", version = rendered.page.version)}
+                " "
+                <LocalTime timestamp=rendered.page.created_at format="%F" />
+              </p>
+              <article class="markdown" inner_html=rendered.html></article>
+            };
+            Either::Right(view)
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t(kind.title_key())) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!(kind.title_key())}</h1>
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{page_view}</Suspense>
+      </section>
+    }
+}
+
+/// Renders a blocking overlay if the logged-in user has to accept updated legal pages
+#[component]
+pub fn LegalInterstitial() -> impl IntoView {
+    let accept = ServerAction::<api::legal::AcceptLegalPage>::new();
+    let pending_res = Resource::new(
+        move || accept.version().get(),
+        |_| api::legal::get_pending_legal_pages(),
+    );
+
+    let interstitial_view = move || {
+        Suspend::new(async move {
+            let pending = match pending_res.await {
+                Ok(pending) => pending,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(().into_view());
+                }
+            };
+            if pending.is_empty() {
+                return Either::Left(().into_view());
+            }
+
+            let items = pending
+                .into_iter()
+                .map(|page| PendingLegalPage(PendingLegalPageProps { page, accept }))
+                .collect_view();
+            let view = view! {
+              <div class="flex fixed inset-0 z-50 justify-center items-center backdrop-blur-[2px]">
+                <section
+                  role="dialog"
+                  class="p-6 text-purple-900 bg-purple-50 rounded-xl border-2 border-purple-200 w-sm md:w-md"
+                >
+                  <h2 class="mb-2 text-2xl font-bold">{t!("legal.updated.title")}</h2>
+                  <p class="mb-4">{t!("legal.updated.text")}</p>
+                  <ul class="flex flex-col gap-2">{items}</ul>
+                </section>
+              </div>
+            };
+            Either::Right(view)
+        })
+    };
+
+    view! { <Suspense>{interstitial_view}</Suspense> }
+}
+
+/// A list item with a link to a [`LegalPage`] that has to be accepted and its accept button
+#[component]
+fn PendingLegalPage(
+    page: LegalPage,
+    accept: ServerAction<api::legal::AcceptLegalPage>,
+) -> impl IntoView {
+    let kind = page.kind;
+    let version = page.version;
+    view! {
+      <li class="flex justify-between items-center">
+        <a href=kind.path() target="_blank" class="font-medium underline hover:no-underline">
+          {t!(kind.title_key())}
+        </a>
+        <button
+          on:click=move |_| {
+            accept.dispatch(api::legal::AcceptLegalPage { kind, version });
+          }
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        >
+          {t!("legal.accept")}
+        </button>
+      </li>
+    }
+}
@@ -9,44 +9,69 @@
     reason = "works badly with rust-analyzer and #[component]"
 )]
 
+mod admin;
+mod announcement;
 pub mod api;
+mod challenge;
+mod dashboard;
+pub mod diff;
+mod draft;
+pub mod emoji;
+mod error_page;
 mod forum;
+#[cfg(feature = "ssr")]
+pub mod highlight;
+pub mod i18n;
+mod legal;
+#[cfg(feature = "ssr")]
+pub mod markdown;
+pub mod markdown_editor;
+mod preview;
+pub mod time;
+mod user;
+mod wiki;
 
+use api::ActivitySummary;
+use api::legal::LegalPageKind;
+use api::site_settings::HomeRoute;
 use leptos::either::Either;
 use leptos::html::ol;
 use leptos::logging;
 use leptos::prelude::*;
-use leptos_meta::{MetaTags, Stylesheet, Title, provide_meta_context};
+use leptos_meta::{Html, MetaTags, Stylesheet, Title, provide_meta_context};
 use leptos_router::{
-    StaticSegment,
-    components::{A, Outlet, ParentRoute, Route, Router, Routes},
+    SsrMode, StaticSegment,
+    components::{A, Outlet, ParentRoute, Redirect, Route, Router, Routes},
     hooks::use_location,
     path,
 };
 
-pub trait TimeUtils {
-    /// Pretty prints how many minutes ago given date was from now
-    fn ago(self) -> String;
-}
-impl TimeUtils for jiff::Timestamp {
-    fn ago(self) -> String {
-        let now = jiff::Timestamp::now();
-        let diff = now - self;
-        // oh damn i get it. different api from chrono but i like it
-        // also it's natural i dont need to read the docs just follow RA/LSP
-        // and read first sentence of method (e.g. tried get_minutes() but docs said i wanted total)
-        let minutes = diff.total(jiff::Unit::Minute).expect("i dan faqd up");
-        format!("{minutes:.1}")
-    }
-}
-
 pub fn shell(options: LeptosOptions) -> impl IntoView {
+    // read by the client, see `CsrfField`
+    let csrf_token = api::csrf::current_token();
+    // sends it along with every same-origin request, see `api::csrf::check`
+    let csrf_script = format!(
+        "(() => {{\
+           const fetch = window.fetch;\
+           window.fetch = (input, init) => {{\
+             const request = new Request(input, init);\
+             if (new URL(request.url).origin === location.origin) {{\
+               request.headers.set('{}', '{csrf_token}');\
+             }}\
+             return fetch.call(window, request);\
+           }};\
+         }})();",
+        api::csrf::CSRF_HEADER,
+    );
+
     view! {
       <!DOCTYPE html>
-      <html lang="en">
+      <html>
         <head>
           <meta charset="utf-8" />
           <meta name="viewport" content="width=device-width, initial-scale=1" />
+          <meta name=api::csrf::CSRF_META_NAME content=csrf_token />
+          <script inner_html=csrf_script></script>
           <AutoReload options=options.clone() />
           <HydrationScripts options />
           <MetaTags />
@@ -58,52 +83,205 @@ pub fn shell(options: LeptosOptions) -> impl IntoView {
     }
 }
 
-/// View for when you do trailing slash mofo
-#[component]
-pub fn Faq() -> impl IntoView {
-    // because for some reason /latest/ etc otherwise cause db not found in context error
-    // i could redirect to og page but difficutl for e.g. /forum/:id :))))
-    view! { <p>"don't do this i hate you"</p> }
-}
-
 #[component]
 pub fn App() -> impl IntoView {
     // Provides context that manages stylesheets, titles, meta tags, etc.
     provide_meta_context();
+    time::provide_user_time_zone();
+    let i18n = i18n::provide_i18n();
 
     view! {
+      <Html attr:lang=move || i18n.locale().code() />
       <Stylesheet id="leptos" href="/pkg/start-axum-workspace.css" />
+      // colors of highlighted code blocks, see `highlight::stylesheet`
+      <Stylesheet id="highlight" href="/highlight.css" />
       <Title text="Dafoerum" />
 
       <Router>
-        <header>
-          <NavBar />
-        </header>
-        <main class="flex flex-col items-center py-8">
-          <div class="flex flex-col gap-4 items-center max-w-4xl sm:items-center md:w-3/4 2xl:w-2/3 w-9/11 sm:w-8/10 lg:w-8/11 xl:w-7/10">
-            <Routes fallback=|| "Page not found.".into_view()>
-              <Route path=StaticSegment("") view=HomePage />
-
-              <Route path=StaticSegment("/latest/") view=Faq />
-              <Route path=StaticSegment("/latest") view=Latest />
-
-              <ParentRoute path=StaticSegment("/forum") view=move || view! { <Outlet /> }>
-                <Route path=StaticSegment("/") view=Faq />
-                <Route path=path!(":id/") view=Faq />
-
-                <Route path=StaticSegment("") view=forum::Forums />
-                <Route path=path!(":id") view=forum::ForumOverview />
-              </ParentRoute>
-
-              <Route path=path!("/thread/:id/") view=Faq />
-              <Route path=path!("/thread/:id") view=forum::thread::ThreadOverview />
-            </Routes>
-          </div>
-        </main>
+        <ToastProvider>
+          <HiddenWhenEmbedded>
+            <header>
+              <NavBar />
+            </header>
+          </HiddenWhenEmbedded>
+          <main class="flex flex-col items-center py-8">
+            <div class="flex flex-col gap-4 items-center max-w-4xl sm:items-center md:w-3/4 2xl:w-2/3 w-9/11 sm:w-8/10 lg:w-8/11 xl:w-7/10">
+              <error_page::PageErrorBoundary>
+                <Routes fallback=|| view! { <error_page::NotFound /> }>
+                  <Route path=StaticSegment("") view=Home />
+                  <Route path=StaticSegment("/latest") view=Latest />
+                  <Route path=path!("/latest/staff") view=|| view! { <Latest staff=true /> } />
+                  <Route path=StaticSegment("/unanswered") view=forum::UnansweredThreads />
+                  <ParentRoute path=StaticSegment("/forum") view=move || view! { <Outlet /> }>
+                    <Route path=StaticSegment("") view=forum::Forums />
+                    <Route path=path!(":id") view=forum::ForumOverview ssr=SsrMode::Async />
+                  </ParentRoute>
+                  <Route
+                    path=path!("/thread/:id")
+                    view=forum::thread::ThreadOverview
+                    ssr=SsrMode::Async
+                  />
+                  <Route path=path!("/post/:id") view=forum::thread::PostPermalink />
+                  <Route
+                    path=path!("/embed/thread/:id")
+                    view=forum::thread::EmbeddedThread
+                    ssr=SsrMode::Async
+                  />
+                  <ParentRoute path=StaticSegment("/wiki") view=move || view! { <Outlet /> }>
+                    <Route path=StaticSegment("") view=wiki::WikiIndex />
+                    <Route path=path!(":slug") view=wiki::WikiPageView />
+                  </ParentRoute>
+                  <Route path=path!("/preview/:token") view=preview::PreviewPage />
+                  <Route path=StaticSegment("/profile") view=user::Profile />
+                  <Route path=path!("/account/revoke/:token") view=user::RevokeLoginPage />
+                  <Route path=path!("/account/invitation/:token") view=user::InvitationPage />
+                  <Route
+                    path=path!("/digest/unsubscribe/:token")
+                    view=user::UnsubscribeDigestPage
+                  />
+                  <Route path=StaticSegment("/members") view=user::MemberList />
+                  <Route path=path!("/members/:id") view=user::MemberProfile />
+                  <Route path=StaticSegment("/my-content") view=user::MyContent />
+                  <Route path=StaticSegment("/subscriptions") view=user::Subscriptions />
+                  <Route path=StaticSegment("/admin") view=admin::Admin />
+                  <Route
+                    path=StaticSegment("/terms")
+                    view=|| view! { <legal::LegalPageView kind=LegalPageKind::Terms /> }
+                  />
+                  <Route
+                    path=StaticSegment("/privacy")
+                    view=|| view! { <legal::LegalPageView kind=LegalPageKind::Privacy /> }
+                  />
+                  <Route
+                    path=StaticSegment("/imprint")
+                    view=|| view! { <legal::LegalPageView kind=LegalPageKind::Imprint /> }
+                  />
+                </Routes>
+              </error_page::PageErrorBoundary>
+            </div>
+          </main>
+          <HiddenWhenEmbedded>
+            <Footer />
+          </HiddenWhenEmbedded>
+          <legal::LegalInterstitial />
+        </ToastProvider>
       </Router>
     }
 }
 
+/// How long a success [`Toast`] is shown, errors stay until they're dismissed
+const SUCCESS_TOAST_SECS: u64 = 5;
+
+/// Whether a [`Toast`] tells about a failure or a success
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToastKind {
+    Error,
+    Success,
+}
+
+/// A short message in the corner of the page, see [`Toasts`]
+#[derive(Debug, Clone)]
+struct Toast {
+    id: u32,
+    kind: ToastKind,
+    message: String,
+}
+
+/// The [`Toasts`][Toast] currently shown, provided by [`ToastProvider`] and gotten with
+/// [`use_toasts`]
+///
+/// Meant for the outcome of actions, e.g. a failed post. Errors that belong to a single form
+/// field are better shown next to it
+#[derive(Clone, Copy)]
+pub struct Toasts {
+    toasts: RwSignal<Vec<Toast>>,
+    next_id: StoredValue<u32>,
+}
+impl Toasts {
+    /// Shows `message` as an error until it's dismissed
+    pub fn push_error(&self, message: impl Into<String>) {
+        self.push(ToastKind::Error, message.into());
+    }
+
+    /// Shows `message` as a success for [`SUCCESS_TOAST_SECS`]
+    pub fn push_success(&self, message: impl Into<String>) {
+        let id = self.push(ToastKind::Success, message.into());
+        let toasts = *self;
+        set_timeout(
+            move || toasts.dismiss(id),
+            std::time::Duration::from_secs(SUCCESS_TOAST_SECS),
+        );
+    }
+
+    fn push(&self, kind: ToastKind, message: String) -> u32 {
+        let id = self.next_id.get_value();
+        self.next_id.set_value(id.wrapping_add(1));
+        self.toasts
+            .update(|toasts| toasts.push(Toast { id, kind, message }));
+        id
+    }
+
+    fn dismiss(&self, id: u32) {
+        self.toasts
+            .update(|toasts| toasts.retain(|toast| toast.id != id));
+    }
+}
+
+/// Returns the [`Toasts`] of the [`ToastProvider`] around the calling component
+///
+/// # Panics
+///
+/// If there is no [`ToastProvider`] around it
+pub fn use_toasts() -> Toasts {
+    expect_context()
+}
+
+/// Provides [`Toasts`] to its `children` and shows them in the bottom right corner
+///
+/// Toasts should only be pushed on the client, e.g. in an [`Effect`] or event handler,
+/// since the ones pushed while rendering on the server aren't sent along
+#[component]
+fn ToastProvider(children: Children) -> impl IntoView {
+    let toasts = Toasts {
+        toasts: RwSignal::new(vec![]),
+        next_id: StoredValue::new(0),
+    };
+    provide_context(toasts);
+
+    view! {
+      {children()}
+      <div
+        role="status"
+        aria-live="polite"
+        class="flex fixed right-4 bottom-4 z-50 flex-col gap-2 max-w-sm"
+      >
+        <For each=move || toasts.toasts.get() key=|toast| toast.id let:toast>
+          <div
+            class="flex gap-4 justify-between items-start py-2 px-4 text-sm font-medium rounded-lg border-2 shadow-lg"
+            class=(
+              ["text-red-700", "bg-red-50", "border-red-400"],
+              toast.kind == ToastKind::Error,
+            )
+            class=(
+              ["text-green-800", "bg-green-50", "border-green-400"],
+              toast.kind == ToastKind::Success,
+            )
+          >
+            <p>{toast.message}</p>
+            <button
+              type="button"
+              aria-label=t!("toast.dismiss")
+              on:click=move |_| toasts.dismiss(toast.id)
+              class="font-bold hover:cursor-pointer"
+            >
+              "\u{d7}"
+            </button>
+          </div>
+        </For>
+      </div>
+    }
+}
+
 /// Used for e.g. highlighting a link if you're on a specific page
 pub enum MatchPath {
     /// `/{path}` and `{path}`
@@ -134,11 +312,58 @@ impl MatchPath {
     }
 }
 
+/// Renders `children` everywhere but on pages embedded on other sites, see [`api::embed`]
+#[component]
+fn HiddenWhenEmbedded(children: ChildrenFn) -> impl IntoView {
+    let path = use_location().pathname;
+    let embedded = move || path.with(|path| api::embed::is_embed_path(path));
+    view! { <Show when=move || !embedded()>{children()}</Show> }
+}
+
+/// Renders a hidden field submitting the [`csrf`][api::csrf] token of the browser,
+/// for `ActionForms` that logged-in members may submit before the page is hydrated
+#[component]
+fn CsrfField() -> impl IntoView {
+    view! { <input type="hidden" name=api::csrf::CSRF_FIELD value=api::csrf::current_token() /> }
+}
+
 /// Renders the top navigation bar
 #[component]
 fn NavBar() -> impl IntoView {
     let path = use_location().pathname;
 
+    // only fetched on the client since the badges depend on localStorage anyway
+    let summary_res = LocalResource::new(move || {
+        path.track();
+        api::get_activity_summary()
+    });
+    let (seen, set_seen) = signal::<Option<ActivitySummary>>(None);
+    Effect::new(move || {
+        let Some(Ok(summary)) = summary_res.get() else {
+            return;
+        };
+        let path = path.get();
+        // the first visit counts as having seen everything
+        let mut last_seen = load_seen_activity().unwrap_or(summary);
+        if MatchPath::Start("forum").matches(&path) || MatchPath::Start("thread").matches(&path) {
+            last_seen.thread_count = summary.thread_count;
+        }
+        if MatchPath::Full("latest").matches(&path) {
+            last_seen.post_count = summary.post_count;
+        }
+        store_seen_activity(last_seen);
+        set_seen(Some(last_seen));
+    });
+    let new_since_seen = move |count: fn(ActivitySummary) -> u64| {
+        Signal::derive(move || {
+            let summary = summary_res.get()?.ok()?;
+            let seen = seen.get()?;
+            Some(count(summary).saturating_sub(count(seen))).filter(|&new| new > 0)
+        })
+    };
+    let new_threads = new_since_seen(|summary| summary.thread_count);
+    let new_posts = new_since_seen(|summary| summary.post_count);
+
     view! {
       // hide on mobile - TBD: mobile navbar hamburger
       <nav class="hidden justify-around w-full h-20 bg-purple-700 sm:flex shadow-[0_3px_0_theme(colors.purple.300)]">
@@ -148,29 +373,37 @@ fn NavBar() -> impl IntoView {
         </div>
         <div class="flex flex-wrap justify-between items-center p-4 max-w-screen-xl">
           <menu class="flex flex-row gap-5 font-medium rounded-lg border-0">
-            <NavLink href="/" matching=&[MatchPath::Full("")] content="Home" pathname=path />
+            <NavLink href="/" matching=&[MatchPath::Full("")] content="nav.home" pathname=path />
             <NavLink
               href="/forum"
               matching=&[MatchPath::Start("forum"), MatchPath::Start("thread")]
-              content="Forums"
+              content="nav.forums"
               pathname=path
+              badge=new_threads
             />
             <NavLink
               href="/latest"
               matching=&[MatchPath::Full("latest")]
-              content="Latest Posts"
+              content="nav.latest"
               pathname=path
+              badge=new_posts
             />
             <NavLink
               href="/wiki"
               matching=&[MatchPath::Start("wiki")]
-              content="Wiki"
+              content="nav.wiki"
+              pathname=path
+            />
+            <NavLink
+              href="/members"
+              matching=&[MatchPath::Start("members")]
+              content="nav.members"
               pathname=path
             />
             <NavLink
               href="/profile"
               matching=&[MatchPath::Start("profile")]
-              content="Profile"
+              content="nav.profile"
               pathname=path
             />
           </menu>
@@ -180,6 +413,75 @@ fn NavBar() -> impl IntoView {
     }
 }
 
+/// Key of the [`ActivitySummary`] at the last visit of the forums and latest posts in localStorage
+const SEEN_ACTIVITY_KEY: &str = "dafoerum_seen_activity";
+
+/// Reads the [`ActivitySummary`] saved by [`store_seen_activity`], if there is one
+fn load_seen_activity() -> Option<ActivitySummary> {
+    let storage = window().local_storage().ok().flatten()?;
+    let json = storage.get_item(SEEN_ACTIVITY_KEY).ok().flatten()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Saves the [`ActivitySummary`] at the last visit in localStorage
+fn store_seen_activity(seen: ActivitySummary) {
+    let Some(storage) = window().local_storage().ok().flatten() else {
+        return;
+    };
+    if let Ok(json) = serde_json::to_string(&seen) {
+        // storage full or disabled, the badges just won't reset then
+        let _ = storage.set_item(SEEN_ACTIVITY_KEY, &json);
+    }
+}
+
+/// Renders the page footer with links to the legal pages and the language switcher
+#[component]
+fn Footer() -> impl IntoView {
+    let settings_res = Resource::new(|| (), |()| api::site_settings::get_site_settings());
+
+    let license_view = move || {
+        Suspend::new(async move {
+            let license = match settings_res.await {
+                Ok(settings) => settings.content_license?,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return None;
+                }
+            };
+            let name = match license.url {
+                Some(url) => Either::Left(view! {
+                  <a href=url rel="license" class="underline hover:no-underline">
+                    {license.name}
+                  </a>
+                }),
+                None => Either::Right(license.name),
+            };
+            let attribution =
+                (!license.attribution.is_empty()).then(|| format!(", {}", license.attribution));
+            Some(view! {
+              <span>{t!("footer.license")} " " {name} {attribution}</span>
+            })
+        })
+    };
+
+    view! {
+      <footer class="flex flex-wrap gap-4 justify-center items-center py-4 text-sm text-purple-900">
+        {LegalPageKind::ALL
+          .into_iter()
+          .map(|kind| {
+            view! {
+              <a href=kind.path() class="underline hover:no-underline">
+                {t!(kind.title_key())}
+              </a>
+            }
+          })
+          .collect_view()}
+        <Suspense>{license_view}</Suspense>
+        <i18n::LanguageSwitcher />
+      </footer>
+    }
+}
+
 /// Renders a list element with link for the navigation bar that changes colors when you're on its page
 #[component]
 fn NavLink(
@@ -187,10 +489,13 @@ fn NavLink(
     href: &'static str,
     /// Routes on which this link should be highlighted
     matching: &'static [MatchPath],
-    /// The displayed link text
+    /// Key of the displayed link text, see [`i18n`]
     content: &'static str,
     /// Gotten by [`use_location`]
     pathname: Memo<String>,
+    /// Number shown in a small badge next to the link text, e.g. new threads
+    #[prop(optional)]
+    badge: Option<Signal<Option<u64>>>,
 ) -> impl IntoView {
     let is_current = move || matching.iter().any(|p| p.matches(&pathname()));
 
@@ -205,18 +510,56 @@ fn NavLink(
           class=(["text-purple-100"], move || !is_current())
           class=(["text-purple-50", "bg-purple-500"], move || is_current())
         >
-          {content}
+          {t!(content)}
+          {move || {
+            badge
+              .and_then(|badge| badge.get())
+              .map(|count| {
+                view! {
+                  <span class="py-0.5 px-1.5 ml-1 text-xs font-bold text-purple-900 bg-purple-100 rounded-full">
+                    {count}
+                  </span>
+                }
+              })
+          }}
         </a>
       </li>
     }
 }
 
-/// Renders the home page of your application.
+/// Renders what's configured to be shown at `/`,
+/// see [`HomeRoute`][api::site_settings::HomeRoute]
+#[component]
+fn Home() -> impl IntoView {
+    let settings_res = Resource::new(|| (), |()| api::site_settings::get_site_settings());
+
+    let home_view = move || {
+        Suspend::new(async move {
+            // the welcome page still works if the settings can't be loaded
+            let home_route = match settings_res.await {
+                Ok(settings) => settings.home_route,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    HomeRoute::default()
+                }
+            };
+            match home_route.redirect_path() {
+                Some(path) => Either::Left(view! { <Redirect path /> }),
+                None => Either::Right(view! { <HomePage /> }),
+            }
+        })
+    };
+
+    view! { <Suspense>{home_view}</Suspense> }
+}
+
+/// Renders the home page of your application with the [`Dashboard`][dashboard::Dashboard]
 #[component]
 fn HomePage() -> impl IntoView {
     view! {
+      <announcement::AnnouncementBanners forum_id=None />
       <h1 class="mb-4 text-4xl font-extrabold tracking-tight leading-none text-gray-900 md:text-5xl lg:text-6xl">
-        "Welcome to Dafoerum!"
+        {t!("home.welcome")}
       </h1>
       // https://book.leptos.dev/view/03_components.html#spreading-attributes-onto-components
       <A
@@ -224,19 +567,41 @@ fn HomePage() -> impl IntoView {
         {..}
         class="flex justify-center items-center p-5 h-20 text-2xl font-bold text-purple-100 uppercase bg-purple-800 rounded-2xl hover:bg-purple-900 hover:cursor-pointer w-md"
       >
-        "Go to the forum"
+        {t!("home.go_to_forum")}
       </A>
+      <dashboard::Dashboard />
     }
 }
 
 /// Renders a list of the most recently posted posts
+///
+/// With `staff`, only the ones in staff-only forums, which are left out otherwise.
+/// Staff can switch between both lists
 #[component]
-fn Latest() -> impl IntoView {
+fn Latest(
+    /// Whether to list the posts of staff-only forums
+    #[prop(optional)]
+    staff: bool,
+) -> impl IntoView {
     const NUM_OF_POSTS_TO_FETCH: i64 = 10;
     let posts_res = Resource::new(
         move || (),
-        |()| api::get_latest_posts(NUM_OF_POSTS_TO_FETCH),
+        move |()| async move {
+            if staff {
+                api::get_latest_staff_posts(NUM_OF_POSTS_TO_FETCH).await
+            } else {
+                api::get_latest_posts(NUM_OF_POSTS_TO_FETCH).await
+            }
+        },
     );
+    let user_res = Resource::new(move || (), |()| api::user::get_current_user());
+    let is_staff = move || {
+        user_res
+            .get()
+            .and_then(Result::ok)
+            .flatten()
+            .is_some_and(|user| user.role >= api::user::Role::Moderator)
+    };
 
     let (is_loading, set_is_loading) = signal(true);
 
@@ -246,13 +611,21 @@ fn Latest() -> impl IntoView {
                 Ok(posts) => posts,
                 Err(err) => {
                     logging::log!("{err:?} - {err}");
-                    return Either::Left(view! { <p>"Posts couldn't be loaded!"</p> });
+                    return Either::Left(view! { <p>{t!("latest.load_failed")}</p> });
                 }
             };
             set_is_loading(false);
             let view = posts
                 .into_iter()
-                .map(|post| forum::thread::PostItem(forum::thread::PostItemProps { post }))
+                .map(|post| {
+                    forum::thread::PostItem(forum::thread::PostItemProps {
+                        post,
+                        editable: false,
+                        wiki: false,
+                        highlightable: false,
+                        moderating: false,
+                    })
+                })
                 .collect_view();
             Either::Right(ol().class("flex flex-col gap-2").child(view))
         })
@@ -282,7 +655,22 @@ fn Latest() -> impl IntoView {
     };
 
     view! {
-      <h1 class="text-4xl font-extrabold md:text-5xl">"Latest Posts"</h1>
+      <h1 class="text-4xl font-extrabold md:text-5xl">
+        {t!(if staff { "latest.staff_title" } else { "latest.title" })}
+      </h1>
+      <Transition>
+        <Show when=is_staff>
+          <A
+            href=if staff { "/latest" } else { "/latest/staff" }
+            attr:class="underline hover:no-underline"
+          >
+            {t!(if staff { "latest.to_public" } else { "latest.to_staff" })}
+          </A>
+        </Show>
+      </Transition>
+      <A href="/unanswered" attr:class="underline hover:no-underline">
+        {t!("latest.to_unanswered")}
+      </A>
       <button
         type="button"
         on:click=move |_| {
@@ -297,9 +685,9 @@ fn Latest() -> impl IntoView {
         )
         class=(["bg-purple-500", "cursor-not-allowed"], move || is_loading())
       >
-        <Show when=move || !is_loading() fallback=move || "Loading...">
+        <Show when=move || !is_loading() fallback=t!("common.loading")>
           {refresh_icon_view}
-          "Check for new posts"
+          {t!("latest.check_new")}
         </Show>
       </button>
       <ol class="flex flex-col gap-2">{post_list_view}</ol>
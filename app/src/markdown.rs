@@ -0,0 +1,109 @@
+//! Rendering of user-written Markdown to HTML
+//!
+//! Raw HTML in the input is escaped and links/images with unsafe schemes (e.g. `javascript:`)
+//! are neutered, so the output can be put into the page with `inner_html`
+//!
+//! Emoji shortcodes like `:smile:` outside of code are replaced, see [`replace_shortcodes`],
+//! and code blocks are highlighted, see [`highlight::code_block`]
+
+use crate::emoji::replace_shortcodes;
+use crate::highlight;
+
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd, html};
+
+/// Renders the given Markdown to sanitized HTML
+///
+/// # Example
+///
+/// ```
+/// use app::markdown::render;
+///
+/// assert_eq!(render("**hi**"), "<p><strong>hi</strong></p>\n");
+/// assert_eq!(render("<b>hi</b>"), "<p>&lt;b&gt;hi&lt;/b&gt;</p>\n");
+/// assert_eq!(render("[x](javascript:alert(1))"), "<p><a href=\"#\">x</a></p>\n");
+/// assert_eq!(render(":tada: `:tada:`"), "<p>🎉 <code>:tada:</code></p>\n");
+/// assert!(render("```rust\n:tada:\n```").contains("hl-rust"));
+/// assert!(!render("```rust\n:tada:\n```").contains('🎉'));
+/// ```
+pub fn render(markdown: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    // language and text of the code block being parsed
+    let mut code_block: Option<(CowStr<'_>, String)> = None;
+    let parser = Parser::new_ext(markdown, options).filter_map(|event| {
+        Some(match event {
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => lang,
+                    CodeBlockKind::Indented => CowStr::Borrowed(""),
+                };
+                code_block = Some((lang, String::new()));
+                return None;
+            }
+            Event::Text(text) if code_block.is_some() => {
+                if let Some((_, code)) = &mut code_block {
+                    code.push_str(&text);
+                }
+                return None;
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                let (lang, code) = code_block.take()?;
+                Event::Html(highlight::code_block(&code, &lang).into())
+            }
+            Event::Text(text) if text.contains(':') => {
+                Event::Text(replace_shortcodes(&text).into_owned().into())
+            }
+            // show raw HTML as text instead of interpreting it
+            Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Link {
+                link_type,
+                dest_url: sanitize_url(dest_url),
+                title,
+                id,
+            }),
+            Event::Start(Tag::Image {
+                link_type,
+                dest_url,
+                title,
+                id,
+            }) => Event::Start(Tag::Image {
+                link_type,
+                dest_url: sanitize_url(dest_url),
+                title,
+                id,
+            }),
+            event => event,
+        })
+    });
+
+    let mut output = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut output, parser);
+    output
+}
+
+/// Replaces URLs with schemes other than `http`, `https` and `mailto` with `#`
+///
+/// Relative URLs (without a scheme) are kept
+fn sanitize_url(url: CowStr<'_>) -> CowStr<'_> {
+    let scheme = url
+        .split_once(':')
+        .map(|(scheme, _)| scheme)
+        // a colon after a slash, ? or # isn't part of a scheme, e.g. "/thread/1?a=b:c"
+        .filter(|scheme| !scheme.contains(['/', '?', '#']));
+
+    match scheme {
+        None => url,
+        Some(scheme)
+            if ["http", "https", "mailto"]
+                .iter()
+                .any(|allowed| scheme.eq_ignore_ascii_case(allowed)) =>
+        {
+            url
+        }
+        Some(_) => CowStr::Borrowed("#"),
+    }
+}
@@ -0,0 +1,270 @@
+//! The textarea posts are written in, with a toolbar inserting Markdown and a preview
+//!
+//! The formatting itself is [`apply_format`], which works on byte indices. The DOM counts
+//! selections in UTF-16 code units, so [`MarkdownEditor`] converts between the two
+
+use crate::emoji::EmojiPicker;
+use crate::forum::{MarkdownPreview, PreviewTabs};
+use crate::i18n::use_i18n;
+use crate::t;
+
+use leptos::html::Textarea;
+use leptos::prelude::*;
+use std::ops::Range;
+
+/// What a toolbar button of the [`MarkdownEditor`] does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarkdownFormat {
+    Bold,
+    Italic,
+    /// Inline code, a code block if the selection spans multiple lines
+    Code,
+    Quote,
+    Link,
+    List,
+}
+impl MarkdownFormat {
+    pub const ALL: [Self; 6] = [
+        Self::Bold,
+        Self::Italic,
+        Self::Code,
+        Self::Quote,
+        Self::Link,
+        Self::List,
+    ];
+
+    /// Short text of the toolbar button, if it doesn't show the label
+    #[must_use]
+    pub fn symbol(self) -> Option<&'static str> {
+        match self {
+            Self::Bold => Some("B"),
+            Self::Italic => Some("I"),
+            Self::Code => Some("</>"),
+            Self::Quote => Some("\u{201c}"),
+            Self::Link | Self::List => None,
+        }
+    }
+
+    /// Translation key of the human-readable name, e.g. for screen readers
+    #[must_use]
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Bold => "markdown_editor.format.bold",
+            Self::Italic => "markdown_editor.format.italic",
+            Self::Code => "markdown_editor.format.code",
+            Self::Quote => "markdown_editor.format.quote",
+            Self::Link => "markdown_editor.format.link",
+            Self::List => "markdown_editor.format.list",
+        }
+    }
+}
+
+/// Formats the `selection` (byte indices) of `text` with `format`
+///
+/// Returns the new text and what to select afterwards: the formatted text,
+/// a placeholder if nothing was selected or the url of a link
+///
+/// # Example
+///
+/// ```
+/// use app::markdown_editor::{MarkdownFormat, apply_format};
+///
+/// assert_eq!(apply_format("a b", 2..3, MarkdownFormat::Bold), ("a **b**".into(), 4..5));
+/// assert_eq!(apply_format("", 0..0, MarkdownFormat::Italic), ("_italic text_".into(), 1..12));
+/// assert_eq!(apply_format("see", 0..3, MarkdownFormat::Link), ("[see](url)".into(), 6..9));
+/// assert_eq!(apply_format("x\ny", 1..2, MarkdownFormat::List), ("- x\n- y".into(), 0..7));
+/// ```
+#[must_use]
+pub fn apply_format(
+    text: &str,
+    selection: Range<usize>,
+    format: MarkdownFormat,
+) -> (String, Range<usize>) {
+    let selected = &text[selection.clone()];
+    match format {
+        MarkdownFormat::Bold => wrap(text, selection, "**", "**", "bold text"),
+        MarkdownFormat::Italic => wrap(text, selection, "_", "_", "italic text"),
+        MarkdownFormat::Code if selected.contains('\n') => {
+            wrap(text, selection, "```\n", "\n```", "")
+        }
+        MarkdownFormat::Code => wrap(text, selection, "`", "`", "code"),
+        MarkdownFormat::Link => {
+            let label = if selected.is_empty() {
+                "link text"
+            } else {
+                selected
+            };
+            let formatted = format!(
+                "{}[{label}](url){}",
+                &text[..selection.start],
+                &text[selection.end..]
+            );
+            let url_start = selection.start + label.len() + "[](".len();
+            (formatted, url_start..url_start + "url".len())
+        }
+        MarkdownFormat::Quote => prefix_lines(text, selection, "> "),
+        MarkdownFormat::List => prefix_lines(text, selection, "- "),
+    }
+}
+
+/// Puts `open` and `close` around the selection, or around `placeholder` if it's empty
+fn wrap(
+    text: &str,
+    selection: Range<usize>,
+    open: &str,
+    close: &str,
+    placeholder: &str,
+) -> (String, Range<usize>) {
+    let inner = if selection.is_empty() {
+        placeholder
+    } else {
+        &text[selection.clone()]
+    };
+    let formatted = format!(
+        "{}{open}{inner}{close}{}",
+        &text[..selection.start],
+        &text[selection.end..]
+    );
+    let start = selection.start + open.len();
+    (formatted, start..start + inner.len())
+}
+
+/// Puts `prefix` in front of every line the selection touches
+fn prefix_lines(text: &str, selection: Range<usize>, prefix: &str) -> (String, Range<usize>) {
+    let line_start = text[..selection.start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[selection.end..]
+        .find('\n')
+        .map_or(text.len(), |i| selection.end + i);
+    let lines = text[line_start..line_end]
+        .split('\n')
+        .map(|line| format!("{prefix}{line}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let formatted = format!("{}{lines}{}", &text[..line_start], &text[line_end..]);
+    let end = line_start + lines.len();
+    // nothing selected means writing goes on after the prefix
+    let start = if selection.is_empty() {
+        end
+    } else {
+        line_start
+    };
+    (formatted, start..end)
+}
+
+/// Turns an offset in UTF-16 code units, as used by the DOM, into a byte index of `text`
+fn byte_index(text: &str, utf16_offset: u32) -> usize {
+    let mut units = 0;
+    for (i, char) in text.char_indices() {
+        if units >= utf16_offset as usize {
+            return i;
+        }
+        units += char.len_utf16();
+    }
+    text.len()
+}
+
+/// Length of `text` in UTF-16 code units, as used by the DOM
+fn utf16_len(text: &str) -> u32 {
+    u32::try_from(text.encode_utf16().count()).unwrap_or(u32::MAX)
+}
+
+/// Renders a textarea for Markdown with a formatting toolbar and [`PreviewTabs`]
+///
+/// The toolbar formats the selection with [`apply_format`] and selects the result,
+/// so e.g. a link's url can be typed over right away. Its [`EmojiPicker`] replaces the selection
+#[component]
+pub fn MarkdownEditor(
+    /// Name of the textarea in its form
+    name: &'static str,
+    #[prop(into)] value: Signal<String>,
+    /// Called with the whole content after typing or using the toolbar
+    on_input: Callback<String>,
+    previewing: RwSignal<bool>,
+    /// Translation key of the placeholder of the textarea
+    placeholder: &'static str,
+    /// Classes of the textarea, so it fits the surrounding form
+    textarea_class: &'static str,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let textarea_ref = NodeRef::<Textarea>::new();
+
+    // replaces the text of the textarea with what `change` makes of it and its selection
+    let edit = move |change: &dyn Fn(&str, Range<usize>) -> (String, Range<usize>)| {
+        let Some(textarea) = textarea_ref.get() else {
+            return;
+        };
+        let text = textarea.value();
+        let start = textarea.selection_start().ok().flatten().unwrap_or(0);
+        let end = textarea.selection_end().ok().flatten().unwrap_or(start);
+        let selection = byte_index(&text, start)..byte_index(&text, end.max(start));
+
+        let (formatted, selected) = change(&text, selection);
+        textarea.set_value(&formatted);
+        let _ = textarea.focus();
+        let _ = textarea.set_selection_range(
+            utf16_len(&formatted[..selected.start]),
+            utf16_len(&formatted[..selected.end]),
+        );
+        on_input.run(formatted);
+    };
+    let apply = move |format: MarkdownFormat| {
+        edit(&|text, selection| apply_format(text, selection, format));
+    };
+    let insert_emoji = Callback::new(move |emoji: &'static str| {
+        edit(&|text, selection| {
+            let formatted = format!(
+                "{}{emoji}{}",
+                &text[..selection.start],
+                &text[selection.end..]
+            );
+            let end = selection.start + emoji.len();
+            (formatted, end..end)
+        });
+    });
+    let toolbar = MarkdownFormat::ALL
+        .into_iter()
+        .map(|format| {
+            view! {
+              <button
+                type="button"
+                title=t!(format.label_key())
+                aria-label=t!(format.label_key())
+                on:click=move |_| apply(format)
+                class="py-0.5 px-2 text-sm font-bold rounded-sm hover:bg-purple-200 hover:cursor-pointer"
+                class=("italic", format == MarkdownFormat::Italic)
+              >
+                {move || {
+                  format.symbol().map_or_else(|| i18n.t(format.label_key()), String::from)
+                }}
+              </button>
+            }
+        })
+        .collect_view();
+
+    view! {
+      <div class="flex flex-wrap gap-2 justify-between items-end">
+        <PreviewTabs previewing />
+        <div role="toolbar" class="flex gap-1" class:hidden=move || previewing.get()>
+          {toolbar}
+          <EmojiPicker on_pick=insert_emoji />
+        </div>
+      </div>
+      <textarea
+        node_ref=textarea_ref
+        name=name
+        rows="5"
+        placeholder=t!(placeholder)
+        required
+        wrap="soft"
+        on:input:target=move |ev| on_input.run(ev.target().value())
+        prop:value=value
+        class=textarea_class
+        class:hidden=move || previewing.get()
+      ></textarea>
+      <Show when=move || previewing.get()>
+        <div class="mb-2 rounded-lg border border-purple-400">
+          <MarkdownPreview content=value />
+        </div>
+      </Show>
+    }
+}
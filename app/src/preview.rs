@@ -0,0 +1,77 @@
+use crate::api;
+use crate::i18n::use_i18n;
+use crate::t;
+use crate::time::LocalTime;
+use api::previews::Preview;
+use api::scheduling::ScheduledTarget;
+
+use leptos::either::Either;
+use leptos::{logging, prelude::*};
+use leptos_meta::Title;
+use leptos_router::{hooks::use_params, params::Params};
+
+/// Parameters for /preview/:token
+#[derive(Params, PartialEq, Clone)]
+struct PreviewParams {
+    token: String,
+}
+
+/// Renders the scheduled thread or reply a [`PreviewLink`][api::previews::PreviewLink] leads to
+#[component]
+pub fn PreviewPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params::<PreviewParams>();
+    let token = move || {
+        params
+            .read()
+            .as_ref()
+            .map(|params| params.token.clone())
+            .unwrap_or_default()
+    };
+    let preview_res = Resource::new(token, api::previews::get_preview);
+
+    let preview_view = move || {
+        Suspend::new(async move {
+            match preview_res.await {
+                Ok(preview) => Either::Right(view! { <PreviewCard preview /> }),
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    Either::Left(view! { <p>{move || i18n.error(&err)}</p> })
+                }
+            }
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("preview.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("preview.title")}</h1>
+      <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{preview_view}</Suspense>
+    }
+}
+
+/// Renders a [`Preview`] like the post it'll become, with when it's published
+#[component]
+fn PreviewCard(preview: Preview) -> impl IntoView {
+    let (heading, location_key) = match preview.target {
+        ScheduledTarget::Thread { subject, .. } => (Some(subject), "preview.new_thread_in"),
+        ScheduledTarget::Reply { .. } => (None, "preview.reply_in"),
+    };
+
+    view! {
+      <p class="p-2 text-sm bg-yellow-100 rounded-lg border border-yellow-400">
+        {t!("preview.goes_live")} " "
+        <LocalTime timestamp=preview.publish_at />
+        ", " {t!("preview.works_until")} " "
+        <LocalTime timestamp=preview.expires_at />
+        "."
+      </p>
+      <article class="p-6 w-full max-w-md bg-white rounded-lg border border-gray-200 shadow-sm0">
+        <h6 class="mb-2 text-xs font-bold tracking-tight text-gray-900">
+          {t!(location_key, name = preview.target_name)} " " {t!("preview.by")} " "
+          {preview.author_name}
+        </h6>
+        {heading.map(|heading| view! { <h2 class="mb-2 text-2xl font-bold">{heading}</h2> })}
+        <p class="font-normal text-gray-700 whitespace-pre-wrap break-words">{preview.content}</p>
+      </article>
+    }
+}
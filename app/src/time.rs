@@ -0,0 +1,282 @@
+//! Human-readable relative times like "5 minutes ago"
+//! and timestamps in the time zone of the [`User`][crate::api::user::User]
+
+use crate::api;
+use crate::i18n::{self, Locale};
+
+use jiff::{Span, SpanRelativeTo, Timestamp, Unit};
+use leptos::prelude::*;
+
+/// Used when neither the user nor the browser provides a time zone
+pub const DEFAULT_TIME_ZONE: &str = "Europe/Berlin";
+
+pub trait TimeUtils {
+    /// Pretty prints how long ago given date was from now, see [`humanize_ago`]
+    fn ago(self) -> String;
+}
+impl TimeUtils for Timestamp {
+    fn ago(self) -> String {
+        humanize_ago(Timestamp::now() - self)
+    }
+}
+
+/// [`i18n`] keys of the units from largest to smallest with their length in seconds
+const UNITS: [(&str, i64); 5] = [
+    ("time.ago.week", 7 * 24 * 60 * 60),
+    ("time.ago.day", 24 * 60 * 60),
+    ("time.ago.hour", 60 * 60),
+    ("time.ago.minute", 60),
+    ("time.ago.second", 1),
+];
+
+/// Formats the `span` since something happened in English, see [`humanize_ago_in`]
+#[must_use]
+pub fn humanize_ago(span: Span) -> String {
+    humanize_ago_in(span, Locale::En)
+}
+
+/// Formats the `span` since something happened in the largest unit it has at least one of,
+/// from seconds up to weeks, e.g. "3 hours ago"
+///
+/// Days are treated as 24 hours long. Spans under a second and negative ones
+/// (e.g. due to clock skew) are "just now"
+#[must_use]
+pub fn humanize_ago_in(span: Span, locale: Locale) -> String {
+    let Ok(seconds) = span.total((Unit::Second, SpanRelativeTo::days_are_24_hours())) else {
+        // only spans with months or years, which timestamp differences never have
+        return i18n::translate(locale, "time.long_ago", &[]);
+    };
+    #[allow(
+        clippy::cast_possible_truncation,
+        reason = "any realistic span fits, and it's rounded down anyway"
+    )]
+    let seconds = seconds as i64;
+
+    for (unit, length) in UNITS {
+        let amount = seconds / length;
+        if amount >= 1 {
+            return i18n::translate_count(locale, unit, amount);
+        }
+    }
+    i18n::translate(locale, "time.just_now", &[])
+}
+
+/// Formats `timestamp` in the time zone with the IANA name `time_zone`,
+/// falling back to UTC for unknown names
+///
+/// `format` is a [`strftime`][jiff::fmt::strftime] format string
+#[must_use]
+pub fn format_in_user_tz(timestamp: Timestamp, time_zone: &str, format: &str) -> String {
+    let time_zone = jiff::tz::TimeZone::get(time_zone).unwrap_or(jiff::tz::TimeZone::UTC);
+    timestamp.to_zoned(time_zone).strftime(format).to_string()
+}
+
+/// Parses `local` as written by an `<input type="datetime-local">` (e.g. `2025-03-07T02:12`)
+/// in the time zone with the IANA name `time_zone`, falling back to UTC for unknown names
+///
+/// [`None`] if `local` isn't a valid date and time
+#[must_use]
+pub fn parse_in_user_tz(local: &str, time_zone: &str) -> Option<Timestamp> {
+    let time_zone = jiff::tz::TimeZone::get(time_zone).unwrap_or(jiff::tz::TimeZone::UTC);
+    let datetime: jiff::civil::DateTime = local.parse().ok()?;
+    datetime
+        .to_zoned(time_zone)
+        .ok()
+        .map(|zoned| zoned.timestamp())
+}
+
+/// Time zone timestamps are rendered in, provided as context by [`provide_user_time_zone`]
+#[derive(Clone, Copy, Debug)]
+pub struct UserTimeZone {
+    /// [`Preferences::time_zone`][api::user::Preferences::time_zone] of the logged-in user
+    stored: RwSignal<Option<String>>,
+    /// Detected by the browser, [`None`] during SSR
+    detected: RwSignal<Option<String>>,
+}
+impl UserTimeZone {
+    /// IANA name of the time zone, preferring the stored one over the detected one
+    #[must_use]
+    pub fn name(self) -> String {
+        self.stored
+            .get()
+            .or_else(|| self.detected.get())
+            .unwrap_or_else(|| DEFAULT_TIME_ZONE.to_string())
+    }
+
+    /// IANA name of the time zone detected by the browser, if any
+    #[must_use]
+    pub fn detected(self) -> Option<String> {
+        self.detected.get()
+    }
+
+    /// Applies a changed [`Preferences::time_zone`][api::user::Preferences::time_zone]
+    pub fn set_stored(self, time_zone: Option<String>) {
+        self.stored.set(time_zone);
+    }
+}
+
+/// Provides the [`UserTimeZone`] as context, filled in once hydrated
+pub fn provide_user_time_zone() {
+    let time_zone = UserTimeZone {
+        stored: RwSignal::new(None),
+        detected: RwSignal::new(None),
+    };
+    provide_context(time_zone);
+
+    // only fetched on the client since the detected time zone is only known there anyway
+    let user_res = LocalResource::new(api::user::get_current_user);
+    Effect::new(move || {
+        time_zone.detected.set(
+            jiff::tz::TimeZone::try_system()
+                .ok()
+                .and_then(|tz| tz.iana_name().map(str::to_string)),
+        );
+    });
+    Effect::new(move || {
+        if let Some(Ok(Some(user))) = user_res.get() {
+            time_zone.set_stored(user.preferences.time_zone);
+        }
+    });
+}
+
+/// Renders a `<time>` element showing `timestamp` in the [`UserTimeZone`]
+///
+/// If `relative`, it shows how long ago it was instead, with the local time on hover.
+/// Both are in the language of the [`I18n`][i18n::I18n] context
+#[component]
+pub fn LocalTime(
+    timestamp: Timestamp,
+    /// [`strftime`][jiff::fmt::strftime] format of the local time,
+    /// [`Locale::datetime_format`] if not given
+    #[prop(optional)]
+    format: Option<&'static str>,
+    #[prop(optional)] relative: bool,
+) -> impl IntoView {
+    let time_zone = use_context::<UserTimeZone>();
+    let i18n = i18n::use_i18n();
+    let local = move || {
+        let name = time_zone.map_or_else(|| DEFAULT_TIME_ZONE.to_string(), UserTimeZone::name);
+        let format = format.unwrap_or_else(|| i18n.locale().datetime_format());
+        format_in_user_tz(timestamp, &name, format)
+    };
+    let text = move || {
+        if relative {
+            humanize_ago_in(Timestamp::now() - timestamp, i18n.locale())
+        } else {
+            local()
+        }
+    };
+
+    view! {
+      <time datetime=timestamp.to_string() title=move || relative.then(local)>
+        {text}
+      </time>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_largest_fitting_unit() {
+        assert_eq!(humanize_ago(Span::new().seconds(42)), "42 seconds ago");
+        assert_eq!(
+            humanize_ago(Span::new().seconds(384 * 60 + 18)),
+            "6 hours ago"
+        );
+        assert_eq!(
+            humanize_ago(Span::new().minutes(59).seconds(59)),
+            "59 minutes ago"
+        );
+        assert_eq!(humanize_ago(Span::new().hours(50)), "2 days ago");
+        assert_eq!(humanize_ago(Span::new().days(20)), "2 weeks ago");
+    }
+
+    #[test]
+    fn switches_units_at_boundaries() {
+        assert_eq!(humanize_ago(Span::new().seconds(59)), "59 seconds ago");
+        assert_eq!(humanize_ago(Span::new().seconds(60)), "1 minute ago");
+        assert_eq!(
+            humanize_ago(Span::new().hours(23).minutes(59)),
+            "23 hours ago"
+        );
+        assert_eq!(humanize_ago(Span::new().hours(24)), "1 day ago");
+        assert_eq!(humanize_ago(Span::new().days(6).hours(23)), "6 days ago");
+        assert_eq!(humanize_ago(Span::new().days(7)), "1 week ago");
+    }
+
+    #[test]
+    fn uses_singular_for_one() {
+        assert_eq!(humanize_ago(Span::new().seconds(1)), "1 second ago");
+        assert_eq!(humanize_ago(Span::new().hours(1)), "1 hour ago");
+        assert_eq!(humanize_ago(Span::new().weeks(1)), "1 week ago");
+    }
+
+    #[test]
+    fn recent_and_future_are_just_now() {
+        assert_eq!(humanize_ago(Span::new()), "just now");
+        assert_eq!(humanize_ago(Span::new().milliseconds(999)), "just now");
+        assert_eq!(humanize_ago(Span::new().minutes(-3)), "just now");
+    }
+
+    #[test]
+    fn months_are_a_long_time_ago() {
+        assert_eq!(humanize_ago(Span::new().months(2)), "a long time ago");
+    }
+
+    #[test]
+    fn formats_in_given_time_zone() {
+        let timestamp: Timestamp = "2025-03-07T01:12:38Z".parse().unwrap();
+        assert_eq!(
+            format_in_user_tz(timestamp, "Europe/Berlin", "%F %R"),
+            "2025-03-07 02:12"
+        );
+        assert_eq!(
+            format_in_user_tz(timestamp, "America/New_York", "%F %R %Z"),
+            "2025-03-06 20:12 EST"
+        );
+    }
+
+    #[test]
+    fn unknown_time_zones_fall_back_to_utc() {
+        let timestamp: Timestamp = "2025-03-07T01:12:38Z".parse().unwrap();
+        assert_eq!(
+            format_in_user_tz(timestamp, "Mars/Olympus_Mons", "%F %R"),
+            "2025-03-07 01:12"
+        );
+    }
+
+    #[test]
+    fn parses_in_given_time_zone() {
+        let expected: Timestamp = "2025-03-07T01:12:00Z".parse().unwrap();
+        assert_eq!(
+            parse_in_user_tz("2025-03-07T02:12", "Europe/Berlin"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_in_user_tz("2025-03-07T01:12", "Mars/Olympus_Mons"),
+            Some(expected)
+        );
+        assert_eq!(parse_in_user_tz("next tuesday", "Europe/Berlin"), None);
+    }
+
+    #[test]
+    fn humanizes_in_locale() {
+        assert_eq!(
+            humanize_ago_in(Span::new().hours(50), Locale::De),
+            "vor 2 Tagen"
+        );
+        assert_eq!(
+            humanize_ago_in(Span::new().minutes(1), Locale::De),
+            "vor 1 Minute"
+        );
+        assert_eq!(humanize_ago_in(Span::new(), Locale::De), "gerade eben");
+    }
+
+    #[test]
+    fn timestamps_are_humanized() {
+        let created_at = Timestamp::now() - Span::new().minutes(90);
+        assert_eq!(created_at.ago(), "1 hour ago");
+    }
+}
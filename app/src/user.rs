@@ -0,0 +1,1524 @@
+use crate::api;
+use crate::i18n::{I18n, use_i18n};
+use crate::t;
+use crate::time::{LocalTime, UserTimeZone};
+use api::ApiError;
+use api::avatars::AvatarSize;
+use api::groups::Member;
+use api::my_content::{MyThreadsKind, MyThreadsOrder};
+use api::profiles::PostInThread;
+use api::subscriptions::WatchLevel;
+use api::user::{Role, User};
+use api::watch_digests::DigestFrequency;
+
+use leptos::either::{Either, EitherOf3};
+use leptos::{logging, prelude::*, task::spawn_local};
+use leptos_meta::Title;
+use leptos_router::{hooks::use_params, params::Params};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+/// Renders the profile of the logged-in [`User`] or login/register forms if not logged in
+#[component]
+pub fn Profile() -> impl IntoView {
+    let i18n = use_i18n();
+    let login = ServerAction::<api::user::Login>::new();
+    let register = ServerAction::<api::user::Register>::new();
+    let logout = ServerAction::<api::user::Logout>::new();
+
+    let user_res = Resource::new(
+        move || {
+            (
+                login.version().get(),
+                register.version().get(),
+                logout.version().get(),
+            )
+        },
+        |_| api::user::get_current_user(),
+    );
+
+    let profile_view = move || {
+        Suspend::new(async move {
+            let user = match user_res.await {
+                Ok(user) => user,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    let view = view! { <p>{t!("profile.load_failed")}</p> };
+                    return Either::Left(Either::Left(view));
+                }
+            };
+            let Some(user) = user else {
+                let login_error = Signal::derive(move || login.value().get().and_then(Result::err));
+                let register_error =
+                    Signal::derive(move || register.value().get().and_then(Result::err));
+                let view = view! {
+                  <div class="flex flex-col gap-4 justify-center w-full sm:flex-row">
+                    <AuthSection title="auth.login" error=login_error>
+                      <ActionForm action=login attr:class="flex flex-col gap-2">
+                        <AuthFields submit="auth.login" />
+                      </ActionForm>
+                    </AuthSection>
+                    <AuthSection title="auth.register" error=register_error>
+                      <ActionForm action=register attr:class="flex flex-col gap-2">
+                        <label class="font-medium">
+                          {t!("auth.email")}
+                          <input
+                            type="email"
+                            name="email"
+                            class="p-2.5 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400"
+                          />
+                        </label>
+                        <AuthFields submit="auth.register" />
+                      </ActionForm>
+                    </AuthSection>
+                  </div>
+                };
+                return Either::Left(Either::Right(view));
+            };
+            Either::Right(view! { <UserCard user logout /> })
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("nav.profile")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("nav.profile")}</h1>
+      <Suspense fallback=move || {
+        view! { <p>{t!("profile.loading")}</p> }
+      }>{profile_view}</Suspense>
+    }
+}
+
+/// Renders a section for logging in or registering, wrapping the [`ActionForm`] in `children`
+///
+/// Both take the same arguments, see [`AuthFields`]
+#[component]
+fn AuthSection(
+    /// Translation key of the heading
+    title: &'static str,
+    /// Error of the last dispatch of the action
+    error: Signal<Option<ApiError>>,
+    children: Children,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let error_view = move || {
+        let Some(e) = error() else {
+            return Either::Left(().into_view());
+        };
+        logging::log!("{e:?} - {e}");
+        Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&e)}</p> })
+    };
+
+    view! {
+      <section class="p-4 w-full bg-purple-200 sm:w-1/2 rounded-xs">
+        <h2 class="mb-2 text-2xl font-bold text-purple-950">{t!(title)}</h2>
+        {error_view}
+        {children()}
+      </section>
+    }
+}
+
+/// Renders the inputs of the login and register forms
+#[component]
+fn AuthFields(
+    /// Translation key of the submit button
+    submit: &'static str,
+) -> impl IntoView {
+    view! {
+      <label class="font-medium">
+        {t!("auth.username")}
+        <input
+          name="name"
+          required
+          class="p-2.5 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400"
+        />
+      </label>
+      <label class="font-medium">
+        {t!("auth.password")}
+        <input
+          type="password"
+          name="password"
+          required
+          class="p-2.5 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400"
+        />
+      </label>
+      <input
+        type="submit"
+        value=t!(submit)
+        class="py-2 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+      />
+    }
+}
+
+/// Renders info about the given [`User`] and a logout button
+#[component]
+fn UserCard(user: User, logout: ServerAction<api::user::Logout>) -> impl IntoView {
+    let role = match user.role {
+        Role::Member => "role.member",
+        Role::Moderator => "role.moderator",
+        Role::Admin => "role.admin",
+    };
+    let now = jiff::Timestamp::now();
+    let banned = user.ban.as_ref().is_some_and(|ban| ban.is_active(now));
+    let muted = user.mute.as_ref().is_some_and(|mute| mute.is_active(now));
+
+    view! {
+      <section class="p-4 w-full bg-purple-200 rounded-xs">
+        <AvatarEditor user=user.clone() />
+        <h2 class="text-2xl font-bold text-purple-950">
+          <MemberName user_id=user.id />
+        </h2>
+        <p>{t!("profile.user_id", id = user.id)}</p>
+        <p>{t!("profile.role")} " " {t!(role)}</p>
+        <p>{t!("profile.thanks_received", count = user.thanks_received)}</p>
+        <p>
+          {t!("members.member_since")} " "
+          <LocalTime timestamp=user.created_at format="%F" />
+        </p>
+        <a href="/my-content" class="block my-2 underline hover:no-underline">
+          {t!("my_content.title")}
+        </a>
+        <TimeZoneSettings time_zone=user.preferences.time_zone.clone() />
+        <WatchDigestSettings />
+        <PushSettings />
+        <ApiKeySettings />
+        <Show when=move || banned>
+          <p class="font-bold text-red-700">{t!("profile.banned")}</p>
+        </Show>
+        <Show when=move || muted>
+          <p class="font-bold text-red-700">{t!("profile.muted")}</p>
+        </Show>
+        <ActionForm action=logout attr:class="mt-4">
+          <input
+            type="submit"
+            value=t!("profile.logout")
+            class="py-1 px-4 font-bold text-red-50 bg-red-800 rounded-lg hover:bg-red-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+      </section>
+    }
+}
+
+/// Renders a form for changing the time zone timestamps are shown in,
+/// see [`Preferences::time_zone`][api::user::Preferences::time_zone]
+#[component]
+fn TimeZoneSettings(
+    /// The currently stored time zone
+    time_zone: Option<String>,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let set_time_zone = ServerAction::<api::user::SetTimeZone>::new();
+    let user_time_zone = expect_context::<UserTimeZone>();
+
+    Effect::new(move || {
+        if let Some(Ok(time_zone)) = set_time_zone.value().get() {
+            user_time_zone.set_stored(time_zone);
+        }
+    });
+    let error = move || {
+        set_time_zone
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| i18n.error(&err))
+    };
+    let placeholder = move || {
+        user_time_zone
+            .detected()
+            .map_or_else(String::new, |detected| {
+                i18n.t_with("time_zone.detected", &[("time_zone", detected)])
+            })
+    };
+
+    view! {
+      <ActionForm action=set_time_zone attr:class="flex flex-wrap gap-2 items-center mt-2">
+        <label class="font-medium">
+          {t!("time_zone.label")} " "
+          <input
+            name="time_zone"
+            value=time_zone
+            placeholder=placeholder
+            class="p-1 text-sm font-normal bg-purple-100 rounded-lg border border-purple-400"
+          />
+        </label>
+        <input
+          type="submit"
+          value=t!("common.save")
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+        <p class="text-sm">{t!("time_zone.hint")}</p>
+        <p class="font-bold text-red-700">{error}</p>
+      </ActionForm>
+    }
+}
+
+/// Renders a form for choosing how often the logged-in [`User`] gets an email
+/// of the new posts in their watched threads, see [`api::watch_digests`]
+#[component]
+fn WatchDigestSettings() -> impl IntoView {
+    let i18n = use_i18n();
+    let set_frequency = ServerAction::<api::watch_digests::SetDigestFrequency>::new();
+    let frequency_res = Resource::new(
+        move || set_frequency.version().get(),
+        |_| api::watch_digests::get_digest_frequency(),
+    );
+    let error = move || {
+        set_frequency
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| i18n.error(&err))
+    };
+
+    let form_view = move || {
+        Suspend::new(async move {
+            // None if not logged in
+            let current = frequency_res.await.ok().flatten()?;
+            let options = DigestFrequency::ALL
+                .into_iter()
+                .map(|frequency| {
+                    view! {
+                      <option value=format!("{frequency:?}") selected=frequency == current>
+                        {t!(frequency.label_key())}
+                      </option>
+                    }
+                })
+                .collect_view();
+            Some(view! {
+              <ActionForm action=set_frequency attr:class="flex flex-wrap gap-2 items-center mt-2">
+                <label class="font-medium">
+                  {t!("watch_digest.label")} " "
+                  <select
+                    name="frequency"
+                    class="p-1 text-sm font-normal bg-purple-100 rounded-lg border border-purple-400"
+                  >
+                    {options}
+                  </select>
+                </label>
+                <input
+                  type="submit"
+                  value=t!("common.save")
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                />
+                <p class="text-sm">{t!("watch_digest.hint")}</p>
+                <p class="font-bold text-red-700">{error}</p>
+              </ActionForm>
+            })
+        })
+    };
+
+    view! { <Suspense>{form_view}</Suspense> }
+}
+
+/// The push manager of the [service worker][api::web_push::SERVICE_WORKER_PATH],
+/// registering it if needed
+async fn push_manager(i18n: I18n) -> Result<web_sys::PushManager, String> {
+    let js_err = |err: wasm_bindgen::JsValue| format!("{err:?}");
+
+    let container = window().navigator().service_worker();
+    if container.is_undefined() {
+        return Err(i18n.t("push.unsupported"));
+    }
+    JsFuture::from(container.register(api::web_push::SERVICE_WORKER_PATH))
+        .await
+        .map_err(js_err)?;
+    // subscribing needs an active service worker
+    let registration = JsFuture::from(container.ready().map_err(js_err)?)
+        .await
+        .map_err(js_err)?;
+    let registration: web_sys::ServiceWorkerRegistration =
+        registration.dyn_into().map_err(js_err)?;
+    registration.push_manager().map_err(js_err)
+}
+
+/// The push subscription of this browser, [`None`] if it isn't subscribed
+async fn current_push_subscription(
+    i18n: I18n,
+) -> Result<Option<web_sys::PushSubscription>, String> {
+    let js_err = |err: wasm_bindgen::JsValue| format!("{err:?}");
+
+    let subscription = JsFuture::from(
+        push_manager(i18n)
+            .await?
+            .get_subscription()
+            .map_err(js_err)?,
+    )
+    .await
+    .map_err(js_err)?;
+    Ok(subscription.dyn_into().ok())
+}
+
+/// Subscribes this browser to push notifications signed with `public_key`
+/// and stores its subscription on the server
+async fn subscribe_push(public_key: &str, i18n: I18n) -> Result<(), String> {
+    let js_err = |err: wasm_bindgen::JsValue| format!("{err:?}");
+
+    let options = web_sys::PushSubscriptionOptionsInit::new();
+    options.set_user_visible_only(true);
+    options.set_application_server_key(Some(&wasm_bindgen::JsValue::from_str(public_key)));
+    // asks for permission to show notifications first
+    let subscription = push_manager(i18n)
+        .await?
+        .subscribe_with_options(&options)
+        .map_err(js_err)?;
+    let subscription: web_sys::PushSubscription = JsFuture::from(subscription)
+        .await
+        .map_err(js_err)?
+        .dyn_into()
+        .map_err(js_err)?;
+    api::web_push::subscribe_push(subscription.endpoint())
+        .await
+        .map_err(|err| i18n.error(&err))
+}
+
+/// Unsubscribes this browser from push notifications, also on the server
+async fn unsubscribe_push(i18n: I18n) -> Result<(), String> {
+    let js_err = |err: wasm_bindgen::JsValue| format!("{err:?}");
+
+    let Some(subscription) = current_push_subscription(i18n).await? else {
+        return Ok(());
+    };
+    api::web_push::unsubscribe_push(subscription.endpoint())
+        .await
+        .map_err(|err| i18n.error(&err))?;
+    JsFuture::from(subscription.unsubscribe().map_err(js_err)?)
+        .await
+        .map_err(js_err)?;
+    Ok(())
+}
+
+/// Renders a button subscribing this browser to push notifications about replies
+/// in the logged-in [`User`]'s threads and watched threads, see [`api::web_push`].
+/// Hidden if the forum has push notifications disabled
+#[component]
+fn PushSettings() -> impl IntoView {
+    let i18n = use_i18n();
+    let key_res = Resource::new(|| (), |()| api::web_push::get_push_public_key());
+    // None until checked in the browser
+    let (subscribed, set_subscribed) = signal::<Option<bool>>(None);
+    let (pending, set_pending) = signal(false);
+    let (error, set_error) = signal::<Option<String>>(None);
+
+    Effect::new(move || {
+        spawn_local(async move {
+            match current_push_subscription(i18n).await {
+                Ok(subscription) => set_subscribed(Some(subscription.is_some())),
+                Err(err) => {
+                    logging::log!("{err}");
+                    set_subscribed(Some(false));
+                }
+            }
+        });
+    });
+
+    let settings_view = move || {
+        Suspend::new(async move {
+            // None if push notifications are disabled
+            let public_key = key_res.await.ok().flatten()?;
+            let on_click = move |_| {
+                let Some(was_subscribed) = subscribed.get_untracked() else {
+                    return;
+                };
+                let public_key = public_key.clone();
+                set_error(None);
+                set_pending(true);
+                spawn_local(async move {
+                    let result = if was_subscribed {
+                        unsubscribe_push(i18n).await
+                    } else {
+                        subscribe_push(&public_key, i18n).await
+                    };
+                    match result {
+                        Ok(()) => set_subscribed(Some(!was_subscribed)),
+                        Err(err) => {
+                            logging::log!("{err}");
+                            set_error(Some(err));
+                        }
+                    }
+                    set_pending(false);
+                });
+            };
+            let label = move || match subscribed.get() {
+                Some(true) => i18n.t("push.turn_off"),
+                _ => i18n.t("push.turn_on"),
+            };
+            Some(view! {
+              <div class="flex flex-wrap gap-2 items-center mt-2">
+                <button
+                  class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer disabled:opacity-50"
+                  disabled=move || pending.get() || subscribed.get().is_none()
+                  on:click=on_click
+                >
+                  {label}
+                </button>
+                <p class="text-sm">{t!("push.hint")}</p>
+                <p class="font-bold text-red-700">{error}</p>
+              </div>
+            })
+        })
+    };
+
+    view! { <Suspense>{settings_view}</Suspense> }
+}
+
+/// Renders the [`ApiKeys`][api::api_keys::ApiKey] of the logged-in [`User`]
+/// with revoke buttons and a form for creating new ones
+#[component]
+fn ApiKeySettings() -> impl IntoView {
+    let i18n = use_i18n();
+    let create = ServerAction::<api::api_keys::CreateApiKey>::new();
+    let revoke = ServerAction::<api::api_keys::RevokeApiKey>::new();
+    let keys_res = Resource::new(
+        move || (create.version().get(), revoke.version().get()),
+        |_| api::api_keys::get_api_keys(),
+    );
+
+    let created_view = move || {
+        let view = match create.value().get()? {
+            Ok(key) => Either::Left(view! {
+              <p class="text-sm">
+                {t!("api_keys.created_key")} " "
+                <code class="font-mono break-all select-all">{key}</code>
+              </p>
+            }),
+            Err(err) => {
+                Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+            }
+        };
+        Some(view)
+    };
+
+    let keys_view = move || {
+        Suspend::new(async move {
+            let keys = match keys_res.await {
+                Ok(keys) => keys,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("api_keys.load_failed")}</p> });
+                }
+            };
+            let view = keys
+                .into_iter()
+                .map(|key| {
+                    let key_id = key.id;
+                    let last_used = key.last_used_at.map_or_else(
+                        || Either::Left(t!("api_keys.never_used")),
+                        |timestamp| {
+                            Either::Right(view! {
+                              {t!("api_keys.last_used")} " " <LocalTime timestamp />
+                            })
+                        },
+                    );
+                    view! {
+                      <li>
+                        <span class="font-bold">{key.name}</span>
+                        " - "
+                        {t!("api_keys.created")}
+                        " "
+                        <LocalTime timestamp=key.created_at format="%F" />
+                        ", "
+                        {last_used}
+                        " "
+                        <button
+                          on:click=move |_| {
+                            revoke.dispatch(api::api_keys::RevokeApiKey { key_id });
+                          }
+                          class="text-red-700 underline hover:no-underline hover:cursor-pointer"
+                        >
+                          {t!("api_keys.revoke")}
+                        </button>
+                      </li>
+                    }
+                })
+                .collect_view();
+            Either::Right(view! { <ul class="text-sm">{view}</ul> })
+        })
+    };
+
+    view! {
+      <details class="p-2 mt-2 bg-purple-100 rounded-lg">
+        <summary class="font-bold hover:cursor-pointer">{t!("api_keys.title")}</summary>
+        <p class="text-sm">
+          {t!("api_keys.help.before")} " " <code>"/api/v1"</code> " "
+          {t!("api_keys.help.middle")} " " <code>"Authorization: Bearer <key>"</code>
+          {t!("api_keys.help.after")}
+        </p>
+        <Suspense>{keys_view}</Suspense>
+        {created_view}
+        <ActionForm action=create attr:class="flex flex-wrap gap-2 items-center mt-2">
+          <input
+            name="name"
+            required
+            maxlength=api::api_keys::MAX_API_KEY_NAME_LEN
+            placeholder=t!("api_keys.name_placeholder")
+            class="p-1 text-sm bg-purple-50 rounded-lg border border-purple-400"
+          />
+          <input
+            type="submit"
+            value=t!("api_keys.create")
+            class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+          />
+        </ActionForm>
+      </details>
+    }
+}
+
+/// Parameters for /account/revoke/:token
+#[derive(Params, PartialEq, Clone)]
+struct RevokeLoginParams {
+    token: String,
+}
+
+/// Renders the login a [`LoginAlert`][api::login_alerts::LoginAlert] email links to,
+/// with a form undoing it by logging out everywhere and setting a new password
+#[component]
+pub fn RevokeLoginPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params::<RevokeLoginParams>();
+    let token = move || {
+        params
+            .read()
+            .as_ref()
+            .map(|params| params.token.clone())
+            .unwrap_or_default()
+    };
+    let alert_res = Resource::new(token, api::login_alerts::get_login_alert);
+    let revoke = ServerAction::<api::login_alerts::RevokeLogin>::new();
+    let revoked = move || matches!(revoke.value().get(), Some(Ok(())));
+    let error = move || {
+        revoke
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+    };
+
+    let alert_view = move || {
+        Suspend::new(async move {
+            let alert = match alert_res.await {
+                Ok(alert) => alert,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{i18n.error(&err)}</p> });
+                }
+            };
+            let browser = Some(alert.user_agent).filter(|user_agent| !user_agent.is_empty());
+            let token = alert.token;
+            Either::Right(view! {
+              <section class="p-4 w-full bg-purple-200 rounded-xs">
+                <h2 class="mb-2 text-2xl font-bold text-purple-950">{t!("revoke_login.new_login")}</h2>
+                <dl class="grid grid-cols-[auto_1fr] gap-x-4">
+                  <dt class="font-medium">{t!("revoke_login.time")}</dt>
+                  <dd>
+                    <LocalTime timestamp=alert.logged_in_at />
+                  </dd>
+                  <dt class="font-medium">{t!("revoke_login.ip")}</dt>
+                  <dd>{alert.ip.unwrap_or_else(|| i18n.t("revoke_login.unknown"))}</dd>
+                  <dt class="font-medium">{t!("revoke_login.browser")}</dt>
+                  <dd class="break-all">
+                    {browser.unwrap_or_else(|| i18n.t("revoke_login.unknown"))}
+                  </dd>
+                </dl>
+              </section>
+              <Show
+                when=revoked
+                fallback=move || {
+                  view! {
+                    <ActionForm action=revoke attr:class="flex flex-col gap-2 w-full">
+                      <input type="hidden" name="token" value=token.clone() />
+                      <label class="font-medium">
+                        {t!("revoke_login.new_password")}
+                        <input
+                          type="password"
+                          name="password"
+                          required
+                          minlength=api::user::MIN_PASSWORD_LEN
+                          class="p-2.5 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400"
+                        />
+                      </label>
+                      {error}
+                      <input
+                        type="submit"
+                        value=t!("revoke_login.submit")
+                        class="py-2 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                      />
+                    </ActionForm>
+                  }
+                }
+              >
+                <p>
+                  {t!("revoke_login.done.before")} " "
+                  <a href="/profile" class="underline hover:no-underline">
+                    {t!("revoke_login.done.link")}
+                  </a> " " {t!("revoke_login.done.after")}
+                </p>
+              </Show>
+            })
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("revoke_login.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("revoke_login.title")}</h1>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{alert_view}</Suspense>
+    }
+}
+
+/// Parameters for /account/invitation/:token
+#[derive(Params, PartialEq, Clone)]
+struct InvitationParams {
+    token: String,
+}
+
+/// Renders the page an [`Invitation`][api::user_import::Invitation] email links to,
+/// where an imported user chooses their first password
+#[component]
+pub fn InvitationPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params::<InvitationParams>();
+    let token = move || {
+        params
+            .read()
+            .as_ref()
+            .map(|params| params.token.clone())
+            .unwrap_or_default()
+    };
+    let user_res = Resource::new(token, api::user_import::get_invitation);
+    let accept = ServerAction::<api::user_import::AcceptInvitation>::new();
+    let accepted = move || matches!(accept.value().get(), Some(Ok(())));
+    let error = move || {
+        accept
+            .value()
+            .get()
+            .and_then(Result::err)
+            .map(|err| view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+    };
+
+    let invitation_view = move || {
+        Suspend::new(async move {
+            let user = match user_res.await {
+                Ok(user) => user,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{i18n.error(&err)}</p> });
+                }
+            };
+            let token = token();
+            Either::Right(view! {
+              <Show
+                when=accepted
+                fallback=move || {
+                  view! {
+                    <ActionForm action=accept attr:class="flex flex-col gap-2 w-full">
+                      <input type="hidden" name="token" value=token.clone() />
+                      <label class="font-medium">
+                        {t!("auth.password")}
+                        <input
+                          type="password"
+                          name="password"
+                          required
+                          minlength=api::user::MIN_PASSWORD_LEN
+                          class="p-2.5 w-full text-sm font-normal bg-purple-100 rounded-lg border border-purple-400"
+                        />
+                      </label>
+                      {error}
+                      <input
+                        type="submit"
+                        value=t!("invitation.submit")
+                        class="py-2 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+                      />
+                    </ActionForm>
+                  }
+                }
+              >
+                <p>
+                  {t!("invitation.welcome", name = user.name)} " "
+                  <a href="/" class="underline hover:no-underline">
+                    {t!("invitation.to_forums")}
+                  </a>
+                </p>
+              </Show>
+            })
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("invitation.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("invitation.heading")}</h1>
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{invitation_view}</Suspense>
+    }
+}
+
+/// Parameters for /digest/unsubscribe/:token
+#[derive(Params, PartialEq, Clone)]
+struct UnsubscribeDigestParams {
+    token: String,
+}
+
+/// Renders the page linked in every watched threads digest email, turning them off
+/// without logging in, see [`api::watch_digests::unsubscribe_digest`]
+#[component]
+pub fn UnsubscribeDigestPage() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params::<UnsubscribeDigestParams>();
+    let token = move || {
+        params
+            .read()
+            .as_ref()
+            .map(|params| params.token.clone())
+            .unwrap_or_default()
+    };
+    let unsubscribe = ServerAction::<api::watch_digests::UnsubscribeDigest>::new();
+
+    // a button instead of unsubscribing right away, so link checkers of email providers
+    // opening the link don't unsubscribe anyone
+    let unsubscribe_view = move || match unsubscribe.value().get() {
+        Some(Ok(())) => EitherOf3::A(view! {
+          <p>
+            {t!("unsubscribe_digest.done.before")} " "
+            <a href="/profile" class="underline hover:no-underline">
+              {t!("unsubscribe_digest.done.link")}
+            </a> {t!("unsubscribe_digest.done.after")}
+          </p>
+        }),
+        Some(Err(err)) => EitherOf3::B(view! {
+          <p class="font-bold text-red-700">{i18n.error(&err)}</p>
+        }),
+        None => EitherOf3::C(view! {
+          <ActionForm action=unsubscribe>
+            <input type="hidden" name="token" value=token />
+            <input
+              type="submit"
+              value=t!("unsubscribe_digest.submit")
+              class="py-2 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+            />
+          </ActionForm>
+        }),
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("unsubscribe_digest.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("unsubscribe_digest.title")}</h1>
+      {unsubscribe_view}
+    }
+}
+
+/// Renders the list of all [`Users`][User] with their group styling
+#[component]
+pub fn MemberList() -> impl IntoView {
+    let i18n = use_i18n();
+    let members_res = Resource::new(move || (), |()| api::groups::get_members());
+
+    let members_view = move || {
+        Suspend::new(async move {
+            let members = match members_res.await {
+                Ok(members) => members,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("members.load_failed")}</p> });
+                }
+            };
+            let view = members
+                .into_iter()
+                .map(|member| {
+                    let joined = member.user.created_at.strftime("%F").to_string();
+                    let group = member
+                        .primary_group
+                        .as_ref()
+                        .map(|group| group.name.clone());
+                    view! {
+                      <tr>
+                        <td class="pr-4">
+                          <a href=format!("/members/{}", member.user.id) class="hover:underline">
+                            <StyledName member />
+                          </a>
+                        </td>
+                        <td class="pr-4">{group}</td>
+                        <td>{joined}</td>
+                      </tr>
+                    }
+                })
+                .collect_view();
+            Either::Right(view! {
+              <table class="text-left">
+                <thead>
+                  <tr>
+                    <th class="pr-4">{t!("members.name")}</th>
+                    <th class="pr-4">{t!("members.group")}</th>
+                    <th>{t!("members.member_since")}</th>
+                  </tr>
+                </thead>
+                <tbody>{view}</tbody>
+              </table>
+            })
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("nav.members")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("nav.members")}</h1>
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <Suspense fallback=move || {
+          view! { <p>{t!("common.loading")}</p> }
+        }>{members_view}</Suspense>
+      </section>
+    }
+}
+
+/// Renders the [`Threads`][api::Thread] the logged-in [`User`] created, posted in or watches,
+/// a page at a time, see [`api::my_content`]
+#[component]
+pub fn MyContent() -> impl IntoView {
+    let i18n = use_i18n();
+    let (kind, set_kind) = signal(MyThreadsKind::default());
+    let (order, set_order) = signal(MyThreadsOrder::default());
+    let (page, set_page) = signal(1_u64);
+    let threads_res = Resource::new(
+        move || (kind.get(), order.get(), page.get()),
+        |(kind, order, page)| api::my_content::get_my_threads(kind, order, page),
+    );
+
+    let threads_view = move || {
+        Suspend::new(async move {
+            let threads_page = match threads_res.await {
+                Ok(threads_page) => threads_page,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! {
+                      <p class="font-bold text-red-700">{i18n.error(&err)}</p>
+                    });
+                }
+            };
+            if threads_page.threads.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("my_content.no_threads")}</p> });
+            }
+
+            let (current, page_count) = (threads_page.page, threads_page.page_count());
+            let list = threads_page
+                .threads
+                .into_iter()
+                .map(|thread| {
+                    view! {
+                      <li>
+                        <a
+                          href=format!("/thread/{}", thread.id)
+                          class="underline hover:no-underline"
+                        >
+                          {thread.display_subject()}
+                        </a>
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! {
+              <ul class="list-disc list-inside">{list}</ul>
+              <Pager current page_count set_page />
+            })
+        })
+    };
+
+    let tabs = MyThreadsKind::ALL
+        .into_iter()
+        .map(|tab| {
+            view! {
+              <button
+                type="button"
+                on:click=move |_| {
+                  set_kind(tab);
+                  set_page(1);
+                }
+                class="py-1 px-4 font-bold rounded-lg hover:cursor-pointer"
+                class=(["text-purple-100", "bg-purple-800"], move || kind.get() == tab)
+                class=(["text-purple-900", "bg-purple-100"], move || kind.get() != tab)
+              >
+                {t!(tab.label_key())}
+              </button>
+            }
+        })
+        .collect_view();
+    let order_options = MyThreadsOrder::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, option)| {
+            view! {
+              <option value=i selected=move || order.get() == option>
+                {t!(option.label_key())}
+              </option>
+            }
+        })
+        .collect_view();
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("my_content.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("my_content.title")}</h1>
+      <a href="/subscriptions" class="text-purple-900 underline hover:no-underline">
+        {t!("my_content.manage_subscriptions")}
+      </a>
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <div class="flex flex-wrap gap-2 justify-between items-center mb-4">
+          <div class="flex gap-2">{tabs}</div>
+          <select
+            aria-label=t!("my_content.order")
+            on:change:target=move |ev| {
+              let chosen = ev.target().value().parse::<usize>().ok();
+              if let Some(&chosen) = chosen.and_then(|i| MyThreadsOrder::ALL.get(i)) {
+                set_order(chosen);
+                set_page(1);
+              }
+            }
+            class="p-1 bg-purple-50 rounded-lg border border-purple-400"
+          >
+            {order_options}
+          </select>
+        </div>
+        <Suspense fallback=move || {
+          view! { <p>{t!("common.loading")}</p> }
+        }>{threads_view}</Suspense>
+      </section>
+    }
+}
+
+/// Renders everything the logged-in [`User`] watches with its last activity and notification
+/// level, with checkboxes to stop watching several at once, see [`api::subscriptions`]
+#[component]
+pub fn Subscriptions() -> impl IntoView {
+    let i18n = use_i18n();
+    let unwatch = ServerAction::<api::subscriptions::Unwatch>::new();
+    let set_thread_level = ServerAction::<api::subscriptions::SetThreadWatchLevel>::new();
+    let set_forum_level = ServerAction::<api::subscriptions::SetForumWatchLevel>::new();
+    let subscriptions_res = Resource::new(
+        move || {
+            (
+                unwatch.version().get(),
+                set_thread_level.version().get(),
+                set_forum_level.version().get(),
+            )
+        },
+        |_| api::subscriptions::get_subscriptions(),
+    );
+
+    let error_view = move || {
+        let err = match (
+            set_thread_level.value().get(),
+            set_forum_level.value().get(),
+        ) {
+            (Some(Err(err)), _) | (_, Some(Err(err))) => err,
+            _ => return None,
+        };
+        Some(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+    };
+
+    let list_view = move || {
+        Suspend::new(async move {
+            let subscriptions = match subscriptions_res.await {
+                Ok(subscriptions) => subscriptions,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! {
+                      <p class="font-bold text-red-700">{i18n.error(&err)}</p>
+                    });
+                }
+            };
+            if subscriptions.threads.is_empty() && subscriptions.forums.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("subscriptions.empty")}</p> });
+            }
+
+            let forums = subscriptions
+                .forums
+                .into_iter()
+                .enumerate()
+                .map(|(i, forum)| {
+                    let forum_id = forum.forum_id;
+                    view! {
+                      <SubscriptionRow
+                        checkbox_name=format!("forum_ids[{i}]")
+                        id=forum_id
+                        href=format!("/forum/{forum_id}")
+                        title=forum.name
+                        level=forum.level
+                        last_activity=forum.last_activity
+                        on_level=Callback::new(move |level| {
+                          set_forum_level
+                            .dispatch(api::subscriptions::SetForumWatchLevel {
+                              forum_id,
+                              level,
+                            });
+                        })
+                      />
+                    }
+                })
+                .collect_view();
+            let threads = subscriptions
+                .threads
+                .into_iter()
+                .enumerate()
+                .map(|(i, thread)| {
+                    let thread_id = thread.thread_id;
+                    view! {
+                      <SubscriptionRow
+                        checkbox_name=format!("thread_ids[{i}]")
+                        id=thread_id
+                        href=format!("/thread/{thread_id}")
+                        title=thread.subject
+                        level=thread.level
+                        last_activity=thread.last_activity
+                        on_level=Callback::new(move |level| {
+                          set_thread_level
+                            .dispatch(api::subscriptions::SetThreadWatchLevel {
+                              thread_id,
+                              level,
+                            });
+                        })
+                      />
+                    }
+                })
+                .collect_view();
+
+            EitherOf3::C(view! {
+              <ActionForm action=unwatch attr:class="flex flex-col gap-2">
+                <h2 class="text-2xl font-bold">{t!("nav.forums")}</h2>
+                <ul class="flex flex-col gap-1">{forums}</ul>
+                <h2 class="text-2xl font-bold">{t!("subscriptions.threads")}</h2>
+                <ul class="flex flex-col gap-1">{threads}</ul>
+                <input
+                  type="submit"
+                  value=t!("subscriptions.unwatch_checked")
+                  class="py-1 px-4 max-w-xs font-bold text-red-50 bg-red-800 rounded-lg hover:bg-red-900 hover:cursor-pointer"
+                />
+              </ActionForm>
+            })
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("subscriptions.title")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("subscriptions.title")}</h1>
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <p class="mb-2 text-sm">{t!("subscriptions.help")}</p>
+        {error_view}
+        <Suspense fallback=move || {
+          view! { <p>{t!("common.loading")}</p> }
+        }>{list_view}</Suspense>
+      </section>
+    }
+}
+
+/// A list item of the [`Subscriptions`] page with a checkbox for unwatching it in bulk,
+/// its last activity and a select calling `on_level` with a new [`WatchLevel`]
+#[component]
+fn SubscriptionRow(
+    /// Of the checkbox, e.g. `thread_ids[0]`
+    checkbox_name: String,
+    id: u32,
+    href: String,
+    title: String,
+    level: WatchLevel,
+    last_activity: Option<jiff::Timestamp>,
+    on_level: Callback<WatchLevel>,
+) -> impl IntoView {
+    let options = WatchLevel::ALL
+        .into_iter()
+        .enumerate()
+        .map(|(i, option)| {
+            view! {
+              <option value=i selected=option == level>
+                {t!(option.label_key())}
+              </option>
+            }
+        })
+        .collect_view();
+
+    view! {
+      <li class="flex flex-wrap gap-2 items-center">
+        <input type="checkbox" name=checkbox_name value=id aria-label=t!("subscriptions.select") />
+        <a href=href class="font-medium underline hover:no-underline">
+          {title}
+        </a>
+        <span class="text-sm text-purple-900">
+          {match last_activity {
+            Some(timestamp) => {
+              Either::Left(view! {
+                {t!("subscriptions.last_post")}
+                " "
+                <LocalTime timestamp relative=true />
+              })
+            }
+            None => Either::Right(t!("subscriptions.no_posts")),
+          }}
+        </span>
+        <select
+          aria-label=t!("subscriptions.notifications")
+          on:change:target=move |ev| {
+            let chosen = ev.target().value().parse::<usize>().ok();
+            if let Some(&chosen) = chosen.and_then(|i| WatchLevel::ALL.get(i)) {
+              on_level.run(chosen);
+            }
+          }
+          class="p-1 text-sm bg-purple-50 rounded-lg border border-purple-400"
+        >
+          {options}
+        </select>
+      </li>
+    }
+}
+
+/// Renders buttons for going to the previous and next page of a list,
+/// `current` starts at 1
+#[component]
+fn Pager(current: u64, page_count: u64, set_page: WriteSignal<u64>) -> impl IntoView {
+    view! {
+      <nav class="flex gap-4 items-center mt-2">
+        <button
+          type="button"
+          disabled=current <= 1
+          on:click=move |_| set_page(current - 1)
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer disabled:opacity-50"
+        >
+          {t!("pager.previous")}
+        </button>
+        <span>{t!("pager.page", current = current, count = page_count)}</span>
+        <button
+          type="button"
+          disabled=current >= page_count
+          on:click=move |_| set_page(current + 1)
+          class="py-1 px-4 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer disabled:opacity-50"
+        >
+          {t!("pager.next")}
+        </button>
+      </nav>
+    }
+}
+
+/// Parameters for /members/:id
+#[derive(Params, PartialEq, Clone, Copy)]
+struct MemberParams {
+    id: u32,
+}
+
+/// Renders the public profile of a member with the posts they wrote and the threads
+/// they started, see [`api::profiles`]
+#[component]
+pub fn MemberProfile() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params::<MemberParams>();
+    let Ok(MemberParams { id }) = params.get_untracked() else {
+        return Either::Left(
+            view! { <h2 class="text-4xl font-bold">{t!("common.invalid_id")}</h2> },
+        );
+    };
+    let member_res = Resource::new(move || (), move |()| api::groups::get_member(id));
+
+    let member_view = move || {
+        Suspend::new(async move {
+            match member_res.await {
+                Ok(member) => {
+                    let name = member.user.name.clone();
+                    let joined = member.user.created_at;
+                    Either::Left(view! {
+                      <Title text=name formatter=|text| format!("{text} | Dafoerum") />
+                      <h1 class="text-4xl font-extrabold md:text-5xl">
+                        <StyledName member />
+                      </h1>
+                      <p>
+                        {t!("members.member_since")} " " <LocalTime timestamp=joined format="%F" />
+                      </p>
+                    })
+                }
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    Either::Right(view! {
+                      <p class="font-bold text-red-700">{i18n.error(&err)}</p>
+                    })
+                }
+            }
+        })
+    };
+
+    Either::Right(view! {
+      <Suspense fallback=move || {
+        view! { <p>{t!("common.loading")}</p> }
+      }>{member_view}</Suspense>
+      <MemberPosts user_id=id />
+      <MemberThreads user_id=id />
+    })
+}
+
+/// Renders the posts of the member with `user_id`, a page at a time
+#[component]
+fn MemberPosts(user_id: u32) -> impl IntoView {
+    let i18n = use_i18n();
+    let (page, set_page) = signal(1_u64);
+    let posts_res = Resource::new(
+        move || page.get(),
+        move |page| api::profiles::get_posts_by_user(user_id, page),
+    );
+
+    let posts_view = move || {
+        Suspend::new(async move {
+            let posts_page = match posts_res.await {
+                Ok(posts_page) => posts_page,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! {
+                      <p class="font-bold text-red-700">{i18n.error(&err)}</p>
+                    });
+                }
+            };
+            if posts_page.items.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("member.no_posts")}</p> });
+            }
+
+            let (current, page_count) = (posts_page.page, posts_page.page_count());
+            let list = posts_page
+                .items
+                .into_iter()
+                .map(|PostInThread { post, thread_subject }| {
+                    view! {
+                      <li class="py-2">
+                        <p class="text-sm">
+                          <a href=format!("/post/{}", post.id) class="underline hover:no-underline">
+                            {thread_subject}
+                          </a>
+                          " "
+                          <LocalTime timestamp=post.created_at relative=true />
+                        </p>
+                        <p class="text-sm text-gray-700 whitespace-pre-wrap break-words line-clamp-3">
+                          {post.content}
+                        </p>
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! {
+              <ul class="divide-y divide-purple-300">{list}</ul>
+              <Pager current page_count set_page />
+            })
+        })
+    };
+
+    view! {
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <h2 class="mb-2 text-2xl font-bold">{t!("member.posts")}</h2>
+        <Suspense fallback=move || {
+          view! { <p>{t!("common.loading")}</p> }
+        }>{posts_view}</Suspense>
+      </section>
+    }
+}
+
+/// Renders the threads started by the member with `user_id`, a page at a time
+#[component]
+fn MemberThreads(user_id: u32) -> impl IntoView {
+    let i18n = use_i18n();
+    let (page, set_page) = signal(1_u64);
+    let threads_res = Resource::new(
+        move || page.get(),
+        move |page| api::profiles::get_threads_by_user(user_id, page),
+    );
+
+    let threads_view = move || {
+        Suspend::new(async move {
+            let threads_page = match threads_res.await {
+                Ok(threads_page) => threads_page,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! {
+                      <p class="font-bold text-red-700">{i18n.error(&err)}</p>
+                    });
+                }
+            };
+            if threads_page.items.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("member.no_threads")}</p> });
+            }
+
+            let (current, page_count) = (threads_page.page, threads_page.page_count());
+            let list = threads_page
+                .items
+                .into_iter()
+                .map(|thread| {
+                    view! {
+                      <li>
+                        <a
+                          href=format!("/thread/{}", thread.id)
+                          class="underline hover:no-underline"
+                        >
+                          {thread.display_subject()}
+                        </a>
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! {
+              <ul class="list-disc list-inside">{list}</ul>
+              <Pager current page_count set_page />
+            })
+        })
+    };
+
+    view! {
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <h2 class="mb-2 text-2xl font-bold">{t!("member.threads")}</h2>
+        <Suspense fallback=move || {
+          view! { <p>{t!("common.loading")}</p> }
+        }>{threads_view}</Suspense>
+      </section>
+    }
+}
+
+/// Renders the name of the [`User`] with `user_id` styled by their primary group,
+/// optionally with a small avatar in front of it
+#[component]
+pub fn MemberName(user_id: u32, #[prop(optional)] avatar: bool) -> impl IntoView {
+    let member_res = Resource::new(move || (), move |()| api::groups::get_member(user_id));
+
+    let name_view = move || {
+        Suspend::new(async move {
+            match member_res.await {
+                Ok(member) => EitherOf3::A(view! { <MemberLabel member avatar /> }),
+                // deleted accounts
+                Err(ApiError::NotFound(..)) => {
+                    EitherOf3::B(view! { <span>{t!("common.deleted_user")}</span> })
+                }
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    EitherOf3::C(view! { <span>{t!("member.user_number", id = user_id)}</span> })
+                }
+            }
+        })
+    };
+
+    view! { <Suspense fallback=move || view! { <span>"..."</span> }>{name_view}</Suspense> }
+}
+
+/// Renders the name of an already fetched [`Member`] like [`MemberName`] does
+#[component]
+pub fn MemberLabel(member: Member, #[prop(optional)] avatar: bool) -> impl IntoView {
+    let avatar = avatar.then(|| {
+        view! {
+          <Avatar
+            url=member.user.avatar_url(AvatarSize::Small)
+            name=member.user.name.clone()
+            size=AvatarSize::Small
+          />
+        }
+    });
+    view! { <span class="inline-flex gap-1 items-center">{avatar}<StyledName member /></span> }
+}
+
+/// Renders the name of a [`Member`] in the color of their primary group with its badge
+#[component]
+pub fn StyledName(member: Member) -> impl IntoView {
+    let (color, badge) = member
+        .primary_group
+        .map(|group| (Some(format!("color: {}", group.color)), group.badge))
+        .unwrap_or_default();
+    let badge = badge.map(|badge| {
+        view! {
+          <span class="py-0.5 px-1.5 ml-1 text-xs font-bold text-white bg-purple-700 rounded-sm">
+            {badge}
+          </span>
+        }
+    });
+
+    view! {
+      <span class="font-bold" style=color>
+        {member.user.name}
+      </span>
+      {badge}
+    }
+}
+
+/// Renders an avatar image, or the first letter of `name` if there's no `url`
+#[component]
+pub fn Avatar(url: Option<String>, name: String, size: AvatarSize) -> impl IntoView {
+    let px = size.px();
+    let style = format!("width: {px}px; height: {px}px");
+    let Some(url) = url else {
+        let initial = name
+            .chars()
+            .next()
+            .unwrap_or('?')
+            .to_uppercase()
+            .to_string();
+        return Either::Right(view! {
+          <span
+            style=style
+            class="inline-flex justify-center items-center font-bold text-purple-50 bg-purple-700 rounded-full"
+          >
+            {initial}
+          </span>
+        });
+    };
+    Either::Left(view! { <img src=url alt=name style=style class="rounded-full" /> })
+}
+
+/// Renders the avatar of the logged-in [`User`] with controls for changing and removing it
+#[component]
+fn AvatarEditor(user: User) -> impl IntoView {
+    let remove = ServerAction::<api::avatars::RemoveAvatar>::new();
+    let version = RwSignal::new(user.avatar_version);
+    let (error, set_error) = signal::<Option<String>>(None);
+
+    Effect::new(move || {
+        if let Some(Ok(())) = remove.value().get() {
+            version.set(None);
+        }
+    });
+
+    let on_change = move |ev: web_sys::Event| {
+        let Some(file) = ev
+            .target()
+            .and_then(|target| target.dyn_into::<web_sys::HtmlInputElement>().ok())
+            .and_then(|input| input.files())
+            .and_then(|files| files.get(0))
+        else {
+            return;
+        };
+        set_error(None);
+        spawn_local(async move {
+            match crate::forum::attachment::upload_file::<u32>("/avatars", file).await {
+                Ok(new_version) => version.set(Some(new_version)),
+                Err(err) => {
+                    logging::log!("{err}");
+                    set_error(Some(err));
+                }
+            }
+        });
+    };
+
+    let name = user.name.clone();
+    let avatar_view = move || {
+        let user = User {
+            avatar_version: version.get(),
+            ..user.clone()
+        };
+        view! {
+          <Avatar url=user.avatar_url(AvatarSize::Large) name=name.clone() size=AvatarSize::Large />
+        }
+    };
+
+    view! {
+      <div class="flex gap-4 items-center mb-2">
+        {avatar_view}
+        <div class="flex flex-col gap-1 text-sm">
+          <label class="font-medium">
+            {t!("avatar.change")} " "
+            <input type="file" accept="image/png,image/jpeg,image/gif,image/webp" on:change=on_change />
+          </label>
+          <Show when=move || version.get().is_some()>
+            <ActionForm action=remove>
+              <input
+                type="submit"
+                value=t!("avatar.remove")
+                class="text-red-700 underline hover:no-underline hover:cursor-pointer"
+              />
+            </ActionForm>
+          </Show>
+          <p class="text-red-700">{error}</p>
+        </div>
+      </div>
+    }
+}
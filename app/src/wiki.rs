@@ -0,0 +1,305 @@
+use crate::api;
+use crate::diff::Diff;
+use crate::i18n::use_i18n;
+use crate::t;
+use crate::time::LocalTime;
+use api::wiki::{SaveWikiPage, WikiRevision};
+
+use leptos::either::{Either, EitherOf3};
+use leptos::{logging, prelude::*};
+use leptos_meta::Title;
+use leptos_router::{
+    NavigateOptions,
+    hooks::{use_navigate, use_params},
+    params::Params,
+};
+
+/// Parameters for /wiki/:slug
+#[derive(Params, PartialEq, Clone)]
+struct WikiParams {
+    slug: String,
+}
+
+/// Renders a list of all wiki pages and a form to create a new one
+#[component]
+pub fn WikiIndex() -> impl IntoView {
+    let i18n = use_i18n();
+    let save = ServerAction::<SaveWikiPage>::new();
+    let pages_res = Resource::new(move || (), |()| api::wiki::get_wiki_pages());
+
+    // go to the newly created page
+    let navigate = use_navigate();
+    Effect::new(move || {
+        if let Some(Ok(slug)) = save.value().get() {
+            navigate(&format!("/wiki/{slug}"), NavigateOptions::default());
+        }
+    });
+
+    let pages_view = move || {
+        Suspend::new(async move {
+            let pages = match pages_res.await {
+                Ok(pages) => pages,
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return EitherOf3::A(view! { <p>{t!("wiki.pages_load_failed")}</p> });
+                }
+            };
+            if pages.is_empty() {
+                return EitherOf3::B(view! { <p>{t!("wiki.no_pages")}</p> });
+            }
+
+            let view = pages
+                .into_iter()
+                .map(|page| {
+                    view! {
+                      <li>
+                        <a
+                          href=format!("/wiki/{}", page.slug)
+                          class="font-medium text-blue-600 underline hover:no-underline"
+                        >
+                          {page.title}
+                        </a>
+                        <span class="text-sm text-gray-600">
+                          " " {t!("wiki.page_revision", n = page.revision)}
+                        </span>
+                      </li>
+                    }
+                })
+                .collect_view();
+            EitherOf3::C(view! { <ul class="list-disc list-inside">{view}</ul> })
+        })
+    };
+
+    view! {
+      <Title text=move || format!("{} | Dafoerum", i18n.t("nav.wiki")) />
+      <h1 class="text-4xl font-extrabold md:text-5xl">{t!("nav.wiki")}</h1>
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{pages_view}</Suspense>
+      </section>
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <h2 class="mb-2 text-2xl font-bold text-purple-950">{t!("wiki.new_page")}</h2>
+        <WikiEditor save slug=None title=String::new() content=String::new() />
+      </section>
+    }
+}
+
+/// Renders a single wiki page with its editor and revision history
+#[component]
+pub fn WikiPageView() -> impl IntoView {
+    let i18n = use_i18n();
+    let params = use_params::<WikiParams>();
+    let slug = move || {
+        params
+            .read()
+            .as_ref()
+            .map(|params| params.slug.clone())
+            .unwrap_or_default()
+    };
+
+    let save = ServerAction::<SaveWikiPage>::new();
+    let page_res = Resource::new(
+        move || (slug(), save.version().get()),
+        |(slug, _)| api::wiki::get_wiki_page(slug),
+    );
+    let revisions_res = Resource::new(
+        move || (slug(), save.version().get()),
+        |(slug, _)| api::wiki::get_wiki_revisions(slug),
+    );
+
+    let page_view = move || {
+        Suspend::new(async move {
+            let (title, html, content) = match page_res.await {
+                Ok(rendered) => (
+                    rendered.page.title,
+                    Some(rendered.html),
+                    rendered.page.content,
+                ),
+                // not written yet, offer to create it
+                Err(api::ApiError::WikiPageNotFound(slug)) => (slug, None, String::new()),
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    return Either::Left(view! { <p>{t!("wiki.page_load_failed")}</p> });
+                }
+            };
+
+            let body = match html {
+                Some(html) => Either::Left(view! {
+                  <article
+                    class="markdown"
+                    on:click=crate::forum::copy_code
+                    inner_html=html
+                  ></article>
+                }),
+                None => Either::Right(view! { <p>{t!("wiki.page_missing")}</p> }),
+            };
+            let view = view! {
+              <Title text=format!("{title} - {} | Dafoerum", i18n.t("nav.wiki")) />
+              <nav class="mb-2 w-full text-purple-900">
+                <a href="/wiki" class="font-medium underline hover:no-underline">
+                  {t!("nav.wiki")}
+                </a>
+                " -> "
+                {title.clone()}
+              </nav>
+              <h1 class="mb-4 text-3xl font-extrabold md:text-4xl text-purple-950 font-display">
+                {title.clone()}
+              </h1>
+              {body}
+              <details class="p-2 mt-4 bg-purple-100 rounded-lg">
+                <summary class="font-bold hover:cursor-pointer">{t!("wiki.edit")}</summary>
+                <WikiEditor save slug=Some(slug()) title content />
+              </details>
+            };
+            Either::Right(view)
+        })
+    };
+
+    let history_view = move || {
+        Suspend::new(async move {
+            match revisions_res.await {
+                Ok(revisions) => Either::Left(view! { <WikiHistory slug=slug() revisions /> }),
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    Either::Right(view! { <p>{t!("wiki.history_load_failed")}</p> })
+                }
+            }
+        })
+    };
+
+    view! {
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{page_view}</Suspense>
+      </section>
+      <section class="p-4 bg-purple-200 w-19/20 rounded-xs sm:8/10">
+        <h2 class="mb-2 text-2xl font-bold text-purple-950">{t!("wiki.history")}</h2>
+        <Suspense fallback=move || view! { <p>{t!("common.loading")}</p> }>{history_view}</Suspense>
+      </section>
+    }
+}
+
+/// Renders a form for saving a wiki page
+///
+/// If `slug` is [`None`], it can be chosen freely (i.e. for new pages)
+#[component]
+fn WikiEditor(
+    save: ServerAction<SaveWikiPage>,
+    slug: Option<String>,
+    title: String,
+    content: String,
+) -> impl IntoView {
+    let i18n = use_i18n();
+    let status_view = move || {
+        let result = save.value().get()?;
+        let view = match result {
+            Ok(_) => Either::Left(view! { <p class="font-bold">{t!("wiki.saved")}</p> }),
+            Err(err) => {
+                Either::Right(view! { <p class="font-bold text-red-700">{i18n.error(&err)}</p> })
+            }
+        };
+        Some(view)
+    };
+
+    let slug_view = match slug {
+        Some(slug) => Either::Left(view! { <input type="hidden" name="slug" value=slug /> }),
+        None => Either::Right(view! {
+          <input
+            name="slug"
+            placeholder=t!("wiki.slug_placeholder")
+            pattern="[a-z0-9\\-]+"
+            required
+            class="py-2 px-4 w-full text-sm bg-purple-100 rounded-lg border border-purple-400"
+          />
+        }),
+    };
+
+    view! {
+      {status_view}
+      <ActionForm action=save attr:class="flex flex-col gap-2">
+        {slug_view}
+        <input
+          name="title"
+          placeholder=t!("wiki.title_placeholder")
+          value=title
+          required
+          class="py-2 px-4 w-full text-sm bg-purple-100 rounded-lg border border-purple-400"
+        />
+        <textarea
+          name="content"
+          rows="12"
+          placeholder=t!("wiki.content_placeholder")
+          required
+          class="py-2 px-4 w-full text-sm bg-purple-100 rounded-lg border border-purple-400"
+        >
+          {content}
+        </textarea>
+        <input
+          type="submit"
+          value=t!("common.save")
+          class="py-2 font-bold text-purple-100 bg-purple-800 rounded-lg hover:bg-purple-900 hover:cursor-pointer"
+        />
+      </ActionForm>
+    }
+}
+
+/// Renders the revisions of a wiki page with a button to show what changed in each one
+#[component]
+fn WikiHistory(slug: String, revisions: Vec<WikiRevision>) -> impl IntoView {
+    if revisions.is_empty() {
+        return Either::Left(view! { <p>{t!("wiki.no_revisions")}</p> });
+    }
+
+    // the revision whose changes are shown
+    let (selected, set_selected) = signal::<Option<u32>>(None);
+    let diff_res = Resource::new(
+        move || selected.get(),
+        move |selected| {
+            let slug = slug.clone();
+            async move {
+                match selected {
+                    Some(revision) => api::wiki::get_wiki_diff(slug, revision - 1, revision).await,
+                    None => Ok(vec![]),
+                }
+            }
+        },
+    );
+
+    let items = revisions
+        .into_iter()
+        .map(|revision| {
+            let number = revision.revision;
+            view! {
+              <li class="flex gap-2 items-center">
+                <span>
+                  {t!("wiki.revision", n = number, author = revision.author_id)}
+                  " "
+                  <LocalTime timestamp=revision.created_at />
+                </span>
+                <button
+                  type="button"
+                  on:click=move |_| set_selected(Some(number))
+                  class="py-1 px-2 text-xs font-medium text-purple-700 rounded-lg border border-purple-300 hover:bg-purple-100 hover:cursor-pointer"
+                >
+                  {t!("wiki.changes")}
+                </button>
+              </li>
+            }
+        })
+        .collect_view();
+
+    let diff_view = move || {
+        Suspend::new(async move {
+            match diff_res.await {
+                Ok(lines) => Either::Left(view! { <Diff lines /> }),
+                Err(err) => {
+                    logging::log!("{err:?} - {err}");
+                    Either::Right(view! { <p>{t!("wiki.changes_load_failed")}</p> })
+                }
+            }
+        })
+    };
+
+    Either::Right(view! {
+      <ol class="flex flex-col gap-1">{items}</ol>
+      <Suspense>{diff_view}</Suspense>
+    })
+}
@@ -0,0 +1,38 @@
+// Shows the push notifications of dafoerum, see app/src/api/web_push.rs
+//
+// Pushes carry no payload, what to show is fetched with the session cookie instead
+
+self.addEventListener("push", (event) => {
+  event.waitUntil(showPending());
+});
+
+async function showPending() {
+  const subscription = await self.registration.pushManager.getSubscription();
+  let notification = null;
+  if (subscription) {
+    try {
+      const res = await fetch("/push/pending", {
+        method: "POST",
+        credentials: "same-origin",
+        headers: { "Content-Type": "application/json" },
+        body: JSON.stringify({ endpoint: subscription.endpoint }),
+      });
+      if (res.ok) {
+        notification = await res.json();
+      }
+    } catch (err) {
+      console.warn("fetching the pending push failed", err);
+    }
+  }
+  // browsers require every push to show a notification
+  notification ??= { title: "New replies", body: "", url: "/my-content" };
+  await self.registration.showNotification(notification.title, {
+    body: notification.body,
+    data: { url: notification.url },
+  });
+}
+
+self.addEventListener("notificationclick", (event) => {
+  event.notification.close();
+  event.waitUntil(self.clients.openWindow(event.notification.data.url));
+});
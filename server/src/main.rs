@@ -1,7 +1,12 @@
-use axum::Router;
-use axum::extract::FromRef;
+use axum::extract::{DefaultBodyLimit, FromRef, Request};
+use axum::http::{HeaderValue, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Router};
 use leptos::prelude::*;
 use leptos_axum::{LeptosRoutes, generate_route_list};
+use tower_http::compression::CompressionLayer;
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 
 use mongodb::Client;
@@ -30,22 +35,165 @@ async fn main() -> anyhow::Result<()> {
     let mongo_uri = std::env::var("MONGO_DB_URI")?;
     let mongo_client = Client::with_uri_str(mongo_uri).await?;
     let db = mongo_client.database("forum");
+    app::api::challenge::create_indexes(&db).await?;
+    app::api::spam::create_indexes(&db).await?;
+    app::api::user::create_indexes(&db).await?;
 
     let state = AppState { leptos_options };
 
+    let caches = app::api::cache::Caches::default();
+    {
+        let (caches, db) = (caches.clone(), db.clone());
+        tokio::spawn(async move {
+            if let Err(err) = caches.warm_up(&db).await {
+                tracing::warn!("warming up caches failed: {err}");
+            }
+        });
+    }
+
+    tokio::spawn(app::api::scheduling::run_publisher(db.clone()));
+    tokio::spawn(app::api::unanswered::run_digest_sender(db.clone()));
+    tokio::spawn(app::api::watch_digests::run_digest_sender(db.clone()));
+
+    let presence = app::api::presence::Presence::default();
+    let typing = app::api::typing::Typing::default();
+    let analytics = app::api::analytics::Analytics::load(db.clone()).await;
+
+    // multipart overhead on top of the file itself
+    let upload_limit = app::api::attachments::MAX_ATTACHMENT_SIZE + 64 * 1024;
+
     let app = Router::new()
-        .leptos_routes_with_context(&state, routes, move || provide_context(db.clone()), {
-            let opts = state.clone().leptos_options;
-            move || app::shell(opts.clone())
-        })
+        .route(
+            "/attachments",
+            post(app::api::attachments::upload).layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route("/attachments/{id}", get(app::api::attachments::download))
+        .route(
+            "/avatars",
+            post(app::api::avatars::upload).layer(DefaultBodyLimit::max(upload_limit)),
+        )
+        .route("/avatars/{user_id}/{px}", get(app::api::avatars::download))
+        .route(
+            "/archives/{id}/{format}",
+            get(app::api::forum_archive::download),
+        )
+        .route("/exports/mod-log/{id}", get(app::api::mod_log::download))
+        .route("/inbound-email", post(app::api::inbound_email::receive))
+        .route(
+            "/.well-known/webfinger",
+            get(app::api::activitypub::webfinger),
+        )
+        .route("/ap/forum/{id}", get(app::api::activitypub::actor))
+        .route("/ap/forum/{id}/outbox", get(app::api::activitypub::outbox))
+        .route("/ap/thread/{id}", get(app::api::activitypub::thread))
+        .route("/ap/post/{id}", get(app::api::activitypub::post))
+        .route("/feed/forum/{id}", get(app::api::feeds::forum_feed))
+        .route("/feed/thread/{id}", get(app::api::feeds::thread_feed))
+        .route("/sitemap.xml", get(app::api::seo::sitemap))
+        .route("/highlight.css", get(app::highlight::stylesheet))
+        .route("/og/thread/{file}", get(app::api::og_image::thread_image))
+        .route("/push/pending", post(app::api::web_push::take_pending_push))
+        .nest("/api/v1", app::api::rest::router())
+        .layer(Extension(db.clone()))
+        .layer(Extension(caches.clone()))
+        .leptos_routes_with_context(
+            &state,
+            routes,
+            {
+                let (db, presence, analytics) = (db.clone(), presence.clone(), analytics.clone());
+                move || {
+                    provide_context(db.clone());
+                    provide_context(caches.clone());
+                    provide_context(presence.clone());
+                    provide_context(typing.clone());
+                    provide_context(analytics.clone());
+                }
+            },
+            {
+                let opts = state.clone().leptos_options;
+                move || app::shell(opts.clone())
+            },
+        )
         .fallback(leptos_axum::file_and_error_handler::<AppState, _>(
             app::shell,
         ))
+        .layer(middleware::from_fn(set_cache_control))
+        // rejects forged requests of logged-in browsers, see `app::api::csrf`
+        .layer(middleware::from_fn(app::api::csrf::check))
+        // only lets allowed sites embed threads, see `app::api::embed`
+        .layer(middleware::from_fn(app::api::embed::set_frame_ancestors))
+        .layer(Extension(db))
+        // records who's online, see `app::api::presence`
+        .layer(middleware::from_fn(app::api::presence::track))
+        .layer(Extension(presence))
+        // counts page views if enabled, see `app::api::analytics`
+        .layer(middleware::from_fn(app::api::analytics::count))
+        .layer(Extension(analytics))
+        // before anything else sees the request, so e.g. `/forum/3/` isn't counted twice
+        .layer(middleware::from_fn(redirect_trailing_slash))
+        // brotli or gzip, whatever the browser supports, skipping already compressed images
+        .layer(CompressionLayer::new())
         .with_state(state);
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("listening on http://localhost:{}", addr.port());
-    axum::serve(listener, app.into_make_service()).await?;
+    // the peer address is needed for `app::api::helper::client_ip_of`
+    let app = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+    axum::serve(listener, app).await?;
 
     Ok(())
 }
+
+/// Picks the `Cache-Control` header for the given request path
+///
+/// Pages and server functions depend on who's logged in, so they're never
+/// shared or reused without asking. Static files can be cached for a day,
+/// their names don't change between builds so they can't be immutable
+fn cache_control_for(path: &str) -> &'static str {
+    let is_static_file = path.starts_with("/pkg/")
+        || path
+            .rsplit('/')
+            .next()
+            .is_some_and(|segment| segment.contains('.'));
+    if path.starts_with("/api/") {
+        "no-store"
+    } else if is_static_file {
+        "public, max-age=86400"
+    } else {
+        "private, no-cache"
+    }
+}
+
+/// Middleware setting `Cache-Control` on responses that don't have one yet,
+/// see [`cache_control_for`]
+async fn set_cache_control(req: Request, next: Next) -> Response {
+    let cache_control = cache_control_for(req.uri().path());
+    let mut res = next.run(req).await;
+    res.headers_mut()
+        .entry(header::CACHE_CONTROL)
+        .or_insert(HeaderValue::from_static(cache_control));
+    res
+}
+
+/// The canonical form of `path` without trailing slashes, [`None`] if it already is
+///
+/// Leading slashes are collapsed too, since `//host` would be a protocol-relative URL
+fn canonical_path(path: &str) -> Option<String> {
+    if path == "/" || !path.ends_with('/') {
+        return None;
+    }
+    Some(format!("/{}", path.trim_matches('/')))
+}
+
+/// Middleware permanently redirecting paths with a trailing slash like `/forum/3/`
+/// to their canonical form, keeping the method and query, see [`canonical_path`]
+async fn redirect_trailing_slash(req: Request, next: Next) -> Response {
+    let Some(path) = canonical_path(req.uri().path()) else {
+        return next.run(req).await;
+    };
+    let location = match req.uri().query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path,
+    };
+    Redirect::permanent(&location).into_response()
+}